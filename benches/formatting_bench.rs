@@ -32,6 +32,7 @@ fn create_sample_blocks(depth: usize, breadth: usize) -> Vec<Box<Block>> {
                     children,
                     has_children: !children.is_empty(),
                     archived: false,
+                    last_edited_time: None,
                 },
                 content: notion2prompt::model::HeadingContent {
                     rich_text: vec![RichTextItem {
@@ -49,6 +50,7 @@ fn create_sample_blocks(depth: usize, breadth: usize) -> Vec<Box<Block>> {
                     children,
                     has_children: !children.is_empty(),
                     archived: false,
+                    last_edited_time: None,
                 },
                 content: notion2prompt::model::ParagraphContent {
                     rich_text: vec![RichTextItem {
@@ -65,6 +67,7 @@ fn create_sample_blocks(depth: usize, breadth: usize) -> Vec<Box<Block>> {
                     children,
                     has_children: !children.is_empty(),
                     archived: false,
+                    last_edited_time: None,
                 },
             }))
         }
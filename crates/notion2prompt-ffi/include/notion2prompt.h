@@ -0,0 +1,73 @@
+/*
+ * notion2prompt C API
+ *
+ * Fetches Notion pages and databases and renders them into prompts.
+ * Link against libnotion2prompt_ffi (built by `cargo build --release
+ * -p notion2prompt-ffi`, as a shared or a static library).
+ *
+ * Configuration is passed in and results are returned as UTF-8 JSON
+ * strings. Every string the library returns must be released with
+ * n2p_string_free().
+ */
+
+#ifndef NOTION2PROMPT_H
+#define NOTION2PROMPT_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Status codes, the same as the CLI's exit codes.
+ */
+#define N2P_SUCCESS 0
+#define N2P_FAILURE 1                /* an error that fits no other code */
+#define N2P_PARTIAL_SUCCESS 2        /* rendered, but the fetch reported warnings */
+#define N2P_AUTHENTICATION_FAILED 3  /* API key invalid, expired, or lacking access */
+#define N2P_NOT_FOUND 4              /* page, database, or block not found */
+#define N2P_RATE_LIMITED 5           /* rate limited by the Notion API */
+#define N2P_INVALID_INPUT 6          /* invalid arguments or configuration */
+#define N2P_SERVICE_UNAVAILABLE 7    /* network failure or Notion unavailable */
+#define N2P_OUTPUT_FAILED 8          /* writing the output failed */
+#define N2P_TEMPLATE_FAILED 9        /* template missing or failed to render */
+#define N2P_SCHEMA_DRIFT 10          /* database schema differs from --expect-schema */
+
+/*
+ * Fetches a Notion page or database and renders it to a prompt. Blocks
+ * until the fetch completes.
+ *
+ * config_json: a JSON object with these fields; only notion_id is required.
+ *   notion_id               page/database URL or 32-char hex ID
+ *   api_key                 defaults to the NOTION_API_KEY environment variable
+ *   depth                   maximum recursion depth (default 5)
+ *   limit                   maximum items to fetch (default 1000)
+ *   template                template name (default "claude-xml")
+ *   always_fetch_databases  fetch child databases regardless of depth
+ *   include_properties      include the properties section
+ *   instruction             custom instruction text for the prompt
+ *   no_cache                disable response caching
+ *   cache_ttl               cache TTL in seconds (default 300)
+ *   concurrency             number of concurrent API workers
+ *
+ * out_json: receives a JSON object, which the caller must free with
+ * n2p_string_free():
+ *   on success  {"prompt": "...", "title": "...", "items_fetched": 12, "warnings": []}
+ *   on failure  {"error": "...", "code": 4}
+ *
+ * Returns one of the N2P_* status codes.
+ */
+int32_t n2p_fetch_and_render(const char *config_json, char **out_json);
+
+/* Releases a string returned by this library. NULL is ignored. */
+void n2p_string_free(char *s);
+
+/* The library version. Static; do not free. */
+const char *n2p_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* NOTION2PROMPT_H */
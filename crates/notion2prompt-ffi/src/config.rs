@@ -0,0 +1,99 @@
+//! The JSON configuration `n2p_fetch_and_render` accepts.
+//!
+//! Field names and defaults match the keyword arguments of the Python
+//! `fetch_and_render`; only `notion_id` is required. Unknown fields are
+//! rejected so that a misspelled option fails loudly instead of being
+//! ignored.
+
+use notion2prompt::{ApiKey, AppError, NotionId, PipelineConfig, TemplateName};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct FfiConfig {
+    /// Notion page/database URL or 32-char hex ID
+    pub notion_id: Option<String>,
+    /// Read from NOTION_API_KEY when not given
+    pub api_key: Option<String>,
+    pub depth: u8,
+    pub limit: u32,
+    pub template: String,
+    pub always_fetch_databases: bool,
+    pub include_properties: bool,
+    pub instruction: Option<String>,
+    pub no_cache: bool,
+    pub cache_ttl: u64,
+    pub concurrency: Option<usize>,
+}
+
+impl Default for FfiConfig {
+    fn default() -> Self {
+        Self {
+            notion_id: None,
+            api_key: None,
+            depth: 5,
+            limit: 1000,
+            template: "claude-xml".to_string(),
+            always_fetch_databases: false,
+            include_properties: false,
+            instruction: None,
+            no_cache: false,
+            cache_ttl: 300,
+            concurrency: None,
+        }
+    }
+}
+
+impl FfiConfig {
+    /// Parses the configuration from its JSON form.
+    pub(crate) fn from_json(json: &str) -> Result<Self, AppError> {
+        serde_json::from_str(json)
+            .map_err(|e| AppError::Validation(format!("Invalid configuration JSON: {}", e)))
+    }
+
+    /// Resolves the pipeline configuration, reading the API key from the
+    /// environment if the JSON did not carry one.
+    pub(crate) fn resolve(self) -> Result<PipelineConfig, AppError> {
+        let notion_id = self
+            .notion_id
+            .ok_or_else(|| AppError::MissingConfiguration("notion_id is required".to_string()))?;
+        let notion_id = NotionId::parse(&notion_id)?;
+
+        let api_key = match self.api_key {
+            Some(key) => key,
+            None => std::env::var("NOTION_API_KEY").map_err(|_| {
+                AppError::MissingConfiguration(
+                    "NOTION_API_KEY not set. Pass api_key or set the environment variable."
+                        .to_string(),
+                )
+            })?,
+        };
+        let api_key = ApiKey::new(&api_key)?;
+        let template = TemplateName::new(&self.template)?;
+
+        let content_dir = std::env::temp_dir().join(format!(
+            "notion2prompt_ffi_{}",
+            notion_id.value_hyphenated()
+        ));
+
+        let mut config = PipelineConfig::default();
+        config.notion_id = notion_id;
+        config.api_key = api_key;
+        config.depth = self.depth;
+        config.limit = self.limit;
+        config.template = template;
+        config.content_dir = content_dir;
+        config.output_file = None;
+        config.clipboard = false;
+        config.pipe = false;
+        config.verbose = false;
+        config.always_fetch_databases = self.always_fetch_databases;
+        config.include_properties = self.include_properties;
+        config.instruction = self.instruction;
+        config.no_cache = self.no_cache;
+        config.cache_ttl = self.cache_ttl;
+        config.concurrency = self.concurrency;
+        config.raw_input = String::new();
+        Ok(config)
+    }
+}
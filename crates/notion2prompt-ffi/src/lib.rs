@@ -0,0 +1,243 @@
+//! C bindings for notion2prompt.
+//!
+//! Exposes the one-shot fetch-and-render pipeline behind a small C ABI
+//! (declared in `include/notion2prompt.h`) so that Node.js, Go, and other
+//! languages can embed it without going through Python. Configuration goes
+//! in and results come out as JSON strings; the return value is a status
+//! code from the same table as the CLI's exit codes.
+
+use notion2prompt::{
+    AppError, CachedNotionClient, DeliveryFailure, ExitCode, NotionFetcher, NotionHttpClient,
+    NotionRepository, PipelineConfig,
+};
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::{Arc, LazyLock};
+use tokio::runtime::Runtime;
+
+mod config;
+
+use config::FfiConfig;
+
+/// Shared by all calls, so repeated calls don't pay for a new thread pool.
+static RUNTIME: LazyLock<std::io::Result<Runtime>> = LazyLock::new(Runtime::new);
+
+/// Fetches a Notion page or database and renders it to a prompt.
+///
+/// `config_json` is a JSON object with the same fields as the Python
+/// `fetch_and_render` keyword arguments; only `notion_id` is required.
+/// On return `*out_json` points to a JSON object: `{"prompt", "title",
+/// "items_fetched", "warnings"}` on success, `{"error", "code"}` otherwise.
+/// A delivery error adds `"failures"`, one `{"kind", "path", "io_kind",
+/// "retryable", "message"}` object per target that failed.
+/// It must be released with [`n2p_string_free`].
+///
+/// Returns 0 on success, 2 when the prompt was rendered but the fetch
+/// reported warnings, and one of the other CLI exit codes on failure.
+///
+/// # Safety
+///
+/// `config_json` must be null or a valid NUL-terminated string, and
+/// `out_json` must be null or valid for writing a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn n2p_fetch_and_render(
+    config_json: *const c_char,
+    out_json: *mut *mut c_char,
+) -> i32 {
+    if out_json.is_null() {
+        return ExitCode::InvalidInput.as_u8().into();
+    }
+    *out_json = ptr::null_mut();
+
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+        let config_json = read_str(config_json)?;
+        let config = FfiConfig::from_json(config_json)?.resolve()?;
+        fetch_and_render(&config)
+    }));
+
+    let (code, json) = match outcome {
+        Ok(Ok(rendered)) => rendered,
+        Ok(Err(e)) => app_error_json(&e),
+        Err(_) => error_json(ExitCode::Failure, "notion2prompt panicked"),
+    };
+    *out_json = into_c_string(json);
+    code.as_u8().into()
+}
+
+/// Releases a string returned by this library. Null is ignored.
+///
+/// # Safety
+///
+/// `s` must be null or a pointer returned by this library that has not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn n2p_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// The library version, as a static NUL-terminated string.
+#[no_mangle]
+pub extern "C" fn n2p_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+// --- Internal helpers ---
+
+fn read_str<'a>(s: *const c_char) -> Result<&'a str, AppError> {
+    if s.is_null() {
+        return Err(AppError::MissingConfiguration(
+            "config_json is null".to_string(),
+        ));
+    }
+    // SAFETY: the caller guarantees a valid NUL-terminated string
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|e| AppError::Validation(format!("config_json is not UTF-8: {}", e)))
+}
+
+fn fetch_and_render(config: &PipelineConfig) -> Result<(ExitCode, String), AppError> {
+    let runtime = RUNTIME.as_ref().map_err(|e| AppError::InternalError {
+        message: format!("Failed to start the async runtime: {}", e),
+        source: None,
+    })?;
+
+    let fetched = runtime.block_on(async {
+        let http_client =
+            NotionHttpClient::with_pool_config(&config.api_key, &config.connection_pool)?;
+        let client: Arc<dyn NotionRepository> = if config.no_cache {
+            Arc::new(http_client)
+        } else {
+            Arc::new(
+                CachedNotionClient::new(http_client, config.cache_ttl, config.cache_scope).await?,
+            )
+        };
+        NotionFetcher::new(client, config)
+            .fetch_recursive(&config.notion_id)
+            .await
+    })?;
+
+    let prompt = notion2prompt::render_prompt(&fetched.data, config)?;
+    let warnings: Vec<String> = fetched
+        .metadata
+        .warnings
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    let code = if warnings.is_empty() {
+        ExitCode::Success
+    } else {
+        ExitCode::PartialSuccess
+    };
+
+    let json = serde_json::json!({
+        "prompt": prompt.into_string(),
+        "title": fetched.data.display_title().to_string(),
+        "items_fetched": fetched.metadata.items_fetched,
+        "warnings": warnings,
+    });
+    Ok((code, json.to_string()))
+}
+
+fn error_json(code: ExitCode, message: &str) -> (ExitCode, String) {
+    (code, error_value(code, message).to_string())
+}
+
+/// `{"error", "code"}` for `e`, with the failed targets of a delivery error.
+fn app_error_json(e: &AppError) -> (ExitCode, String) {
+    let code = e.exit_code();
+    let mut json = error_value(code, &e.to_string());
+    if let AppError::DeliveryFailed { failures } = e {
+        json["failures"] = failures.iter().map(failure_value).collect();
+    }
+    (code, json.to_string())
+}
+
+fn error_value(code: ExitCode, message: &str) -> serde_json::Value {
+    serde_json::json!({
+        "error": message,
+        "code": code.as_u8(),
+    })
+}
+
+fn failure_value(failure: &DeliveryFailure) -> serde_json::Value {
+    serde_json::json!({
+        "kind": failure.kind.to_string(),
+        "path": failure.path.as_ref().map(|path| path.display().to_string()),
+        "io_kind": failure.io_kind.map(|kind| format!("{:?}", kind)),
+        "retryable": failure.is_retryable(),
+        "message": failure.message,
+    })
+}
+
+/// Hands `s` to C. JSON escapes control characters, so it has no NUL.
+fn into_c_string(s: String) -> *mut c_char {
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(config_json: Option<&str>) -> (i32, serde_json::Value) {
+        let config = config_json.map(|s| CString::new(s).unwrap());
+        let config_ptr = config.as_ref().map_or(ptr::null(), |c| c.as_ptr());
+        let mut out = ptr::null_mut();
+        let code = unsafe { n2p_fetch_and_render(config_ptr, &mut out) };
+        assert!(!out.is_null());
+        let json = unsafe { CStr::from_ptr(out) }.to_str().unwrap().to_string();
+        unsafe { n2p_string_free(out) };
+        (code, serde_json::from_str(&json).unwrap())
+    }
+
+    #[test]
+    fn test_invalid_configuration_is_reported_as_invalid_input() {
+        let invalid_input = i32::from(ExitCode::InvalidInput.as_u8());
+        for config in [
+            None,
+            Some("not json"),
+            Some("{}"),
+            Some(r#"{"notion_id": "abc", "api_key": "secret_x", "depht": 3}"#),
+            Some(r#"{"notion_id": "not an id", "api_key": "secret_x"}"#),
+        ] {
+            let (code, json) = call(config);
+            assert_eq!(code, invalid_input, "config {:?}", config);
+            assert_eq!(json["code"], invalid_input);
+            assert!(json["error"].is_string());
+        }
+    }
+
+    #[test]
+    fn test_delivery_errors_list_each_failed_target() {
+        let error = AppError::DeliveryFailed {
+            failures: vec![DeliveryFailure::new(
+                notion2prompt::DeliveryKind::Clipboard,
+                None,
+                &AppError::Validation("no display".to_string()),
+            )],
+        };
+
+        let (code, json) = app_error_json(&error);
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(code, ExitCode::OutputFailed);
+        assert_eq!(json["failures"][0]["kind"], "clipboard");
+        assert_eq!(json["failures"][0]["retryable"], true);
+        assert!(json["failures"][0]["path"].is_null());
+    }
+
+    #[test]
+    fn test_null_out_pointer_is_rejected() {
+        let config = CString::new("{}").unwrap();
+        let code = unsafe { n2p_fetch_and_render(config.as_ptr(), ptr::null_mut()) };
+        assert_eq!(code, i32::from(ExitCode::InvalidInput.as_u8()));
+    }
+
+    #[test]
+    fn test_version_matches_crate() {
+        let version = unsafe { CStr::from_ptr(n2p_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    }
+}
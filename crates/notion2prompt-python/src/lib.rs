@@ -5,7 +5,9 @@
 
 use pyo3::prelude::*;
 
+mod metrics;
 mod pipeline;
+mod stream;
 mod types;
 
 /// The main Python module: `notion2prompt._notion2prompt`
@@ -15,10 +17,23 @@ fn _notion2prompt(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(pipeline::fetch_and_render, m)?)?;
     m.add_function(wrap_pyfunction!(pipeline::fetch_content, m)?)?;
     m.add_function(wrap_pyfunction!(pipeline::render_content, m)?)?;
+    m.add_function(wrap_pyfunction!(pipeline::render_blocks, m)?)?;
+
+    // Sections streamed as they are composed
+    m.add_function(wrap_pyfunction!(stream::fetch_and_render_stream, m)?)?;
+
+    // Metrics of recent runs
+    m.add_function(wrap_pyfunction!(metrics::get_last_run_metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::get_run_metrics, m)?)?;
 
     // Types
     m.add_class::<types::PyPipelineConfig>()?;
     m.add_class::<types::PyNotionContent>()?;
+    m.add_class::<types::PyBlock>()?;
+    m.add_class::<metrics::PyRunMetrics>()?;
+    m.add_class::<types::PyDeliveryFailure>()?;
+    m.add_class::<stream::PyPromptChunk>()?;
+    m.add_class::<stream::PyRenderStream>()?;
 
     Ok(())
 }
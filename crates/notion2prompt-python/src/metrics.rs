@@ -0,0 +1,207 @@
+//! Metrics of recent fetches, read from Python with `get_last_run_metrics()`.
+//!
+//! Every `fetch_and_render`, `fetch_and_render_stream` or `fetch_content`
+//! call is a run with its own id. When a run ends, successfully or not, its metrics are stored in a
+//! process-wide registry keyed by run id, which any Python thread can read.
+//! Only the most recent runs are kept. The id comes back with the result:
+//! `NotionContent.run_id`, `RenderStream.run_id`, or the metrics themselves
+//! from `fetch_and_render(..., with_metrics=True)`.
+
+use notion2prompt::{AppError, NotionId, NotionObject};
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::Instant;
+
+use crate::pipeline::BindingPipeline;
+
+/// How many finished runs the registry keeps.
+const RETAINED_RUNS: usize = 64;
+
+static NEXT_RUN_ID: AtomicU64 = AtomicU64::new(1);
+static REGISTRY: LazyLock<Mutex<Registry>> = LazyLock::new(Mutex::default);
+
+#[derive(Default)]
+struct Registry {
+    runs: BTreeMap<u64, PyRunMetrics>,
+    /// The run that finished most recently
+    last: Option<u64>,
+}
+
+/// Fetch metrics of one run.
+#[pyclass(name = "RunMetrics", frozen, get_all)]
+#[derive(Clone, Debug)]
+pub struct PyRunMetrics {
+    /// Identifies the run; later calls get higher ids
+    pub run_id: u64,
+    pub notion_id: String,
+    pub items_fetched: u32,
+    pub warnings: Vec<String>,
+    pub fetch_ms: u64,
+    /// None when the run did not render, or failed before it
+    pub render_ms: Option<u64>,
+    /// Requests answered by the Notion API
+    pub api_requests: u64,
+    /// Responses answered from the disk cache
+    pub cache_hits: u64,
+    pub bytes_downloaded: u64,
+    /// Why the run failed, if it did
+    pub error: Option<String>,
+}
+
+#[pymethods]
+impl PyRunMetrics {
+    /// Serialize the metrics to a JSON string.
+    fn to_json(&self) -> PyResult<String> {
+        let val = serde_json::json!({
+            "run_id": self.run_id,
+            "notion_id": self.notion_id,
+            "items_fetched": self.items_fetched,
+            "warnings": self.warnings,
+            "fetch_ms": self.fetch_ms,
+            "render_ms": self.render_ms,
+            "api_requests": self.api_requests,
+            "cache_hits": self.cache_hits,
+            "bytes_downloaded": self.bytes_downloaded,
+            "error": self.error,
+        });
+        serde_json::to_string_pretty(&val)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RunMetrics(run_id={}, items_fetched={}, warnings={}, fetch_ms={}, cache_hits={})",
+            self.run_id,
+            self.items_fetched,
+            self.warnings.len(),
+            self.fetch_ms,
+            self.cache_hits,
+        )
+    }
+}
+
+/// Metrics of the most recently finished fetch, or None before the first.
+#[pyfunction]
+pub fn get_last_run_metrics() -> Option<PyRunMetrics> {
+    let registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+    registry.last.and_then(|id| registry.runs.get(&id).cloned())
+}
+
+/// Metrics of the run with `run_id`, or None if it is unknown or too old.
+#[pyfunction]
+pub fn get_run_metrics(run_id: u64) -> Option<PyRunMetrics> {
+    let registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+    registry.runs.get(&run_id).cloned()
+}
+
+/// A run in progress; its metrics are registered when it is dropped.
+pub(crate) struct Run {
+    metrics: PyRunMetrics,
+}
+
+impl Run {
+    pub(crate) fn start(id: &NotionId) -> Self {
+        Self {
+            metrics: PyRunMetrics {
+                run_id: NEXT_RUN_ID.fetch_add(1, Ordering::Relaxed),
+                notion_id: id.as_str().to_string(),
+                items_fetched: 0,
+                warnings: Vec::new(),
+                fetch_ms: 0,
+                render_ms: None,
+                api_requests: 0,
+                cache_hits: 0,
+                bytes_downloaded: 0,
+                error: None,
+            },
+        }
+    }
+
+    /// The id `get_run_metrics()` finds this run under once it ends.
+    pub(crate) fn id(&self) -> u64 {
+        self.metrics.run_id
+    }
+
+    /// Ends the run, returning the metrics it registered.
+    pub(crate) fn finish(self) -> PyRunMetrics {
+        self.metrics.clone()
+    }
+
+    /// Fetches `id` through `pipeline`, recording what the fetch did.
+    pub(crate) async fn fetch(
+        &mut self,
+        pipeline: &BindingPipeline<'_>,
+        id: &NotionId,
+    ) -> Result<NotionObject, AppError> {
+        let started = Instant::now();
+        let result = pipeline.fetch_with_metadata(id).await;
+        self.metrics.fetch_ms = started.elapsed().as_millis() as u64;
+
+        let result = self.failed_with(result)?;
+        let metadata = result.metadata;
+        self.metrics.items_fetched = metadata.items_fetched;
+        self.metrics.warnings = metadata.warnings.iter().map(ToString::to_string).collect();
+        if let Some(transfer) = metadata.transfer {
+            self.metrics.api_requests = transfer.requests;
+            self.metrics.cache_hits = transfer.cache_hits;
+            self.metrics.bytes_downloaded = transfer.bytes_downloaded;
+        }
+        Ok(result.data)
+    }
+
+    /// Awaits `render`, recording how long it took.
+    pub(crate) async fn render<T>(
+        &mut self,
+        render: impl Future<Output = Result<T, AppError>>,
+    ) -> Result<T, AppError> {
+        let started = Instant::now();
+        let result = render.await;
+        self.metrics.render_ms = Some(started.elapsed().as_millis() as u64);
+        self.failed_with(result)
+    }
+
+    fn failed_with<T>(&mut self, result: Result<T, AppError>) -> Result<T, AppError> {
+        if let Err(e) = &result {
+            self.metrics.error = Some(e.to_string());
+        }
+        result
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let metrics = self.metrics.clone();
+        let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+        registry.last = Some(metrics.run_id);
+        registry.runs.insert(metrics.run_id, metrics);
+        while registry.runs.len() > RETAINED_RUNS {
+            registry.runs.pop_first();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finished_runs_are_found_by_their_id() {
+        let id = NotionId::parse("550e8400e29b41d4a716446655440000").unwrap();
+        let first = Run::start(&id);
+        let second = Run::start(&id);
+        let (first_id, second_id) = (first.id(), second.id());
+        assert!(second_id > first_id);
+
+        let metrics = second.finish();
+        assert_eq!(metrics.run_id, second_id);
+        assert!(get_run_metrics(first_id).is_none());
+        drop(first);
+
+        assert_eq!(get_run_metrics(second_id).unwrap().run_id, second_id);
+        assert_eq!(get_run_metrics(first_id).unwrap().notion_id, id.as_str());
+        assert_eq!(get_last_run_metrics().unwrap().run_id, first_id);
+    }
+}
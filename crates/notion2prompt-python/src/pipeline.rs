@@ -1,9 +1,11 @@
 //! Python-exposed pipeline functions: fetch, compose, and the combined fetch_and_render.
 
-use crate::types::{resolve_config, PyNotionContent};
+use crate::metrics::Run;
+use crate::types::{resolve_config, PyBlock, PyDeliveryFailure, PyNotionContent, PyPipelineConfig};
 use notion2prompt::{
-    CachedNotionClient, NotionFetcher, NotionHttpClient, NotionObject, NotionRepository,
-    PipelineConfig,
+    AppError, AsyncPromptComposer, Block, CachedNotionClient, ContentSource, FetchResult,
+    NotionFetcher, NotionHttpClient, NotionId, NotionObject, NotionRepository, PipelineConfig,
+    PromptComposer, RenderContext, RenderedPrompt,
 };
 use pyo3::prelude::*;
 use std::sync::Arc;
@@ -24,9 +26,11 @@ use std::sync::Arc;
 ///     no_cache: Disable response caching
 ///     cache_ttl: Cache TTL in seconds (default 300)
 ///     concurrency: Number of concurrent API workers
+///     with_metrics: Also return the RunMetrics of this call
 ///
 /// Returns:
-///     The rendered prompt string.
+///     The rendered prompt string, or a (prompt, RunMetrics) tuple with
+///     with_metrics.
 #[pyfunction]
 #[pyo3(signature = (
     notion_id,
@@ -40,6 +44,7 @@ use std::sync::Arc;
     no_cache = false,
     cache_ttl = 300,
     concurrency = None,
+    with_metrics = false,
 ))]
 #[allow(clippy::too_many_arguments)]
 pub fn fetch_and_render<'py>(
@@ -55,6 +60,7 @@ pub fn fetch_and_render<'py>(
     no_cache: bool,
     cache_ttl: u64,
     concurrency: Option<usize>,
+    with_metrics: bool,
 ) -> PyResult<Bound<'py, PyAny>> {
     let config = resolve_config(
         notion_id,
@@ -71,9 +77,27 @@ pub fn fetch_and_render<'py>(
     )?;
 
     pyo3_async_runtimes::tokio::future_into_py(py, async move {
-        let content = fetch_notion_content(&config).await?;
-        let prompt = compose_prompt(&content, &config)?;
-        Ok(prompt)
+        let pipeline = BindingPipeline { config: &config };
+        let mut run = Run::start(&config.notion_id);
+        let content = run
+            .fetch(&pipeline, &config.notion_id)
+            .await
+            .map_err(fetch_error)?;
+        let prompt = run
+            .render(pipeline.compose_async(&content))
+            .await
+            .map_err(render_error)?;
+        let prompt = prompt.into_string();
+        Python::with_gil(|py| -> PyResult<PyObject> {
+            Ok(if with_metrics {
+                (prompt, run.finish())
+                    .into_pyobject(py)?
+                    .into_any()
+                    .unbind()
+            } else {
+                prompt.into_pyobject(py)?.into_any().unbind()
+            })
+        })
     })
 }
 
@@ -128,8 +152,16 @@ pub fn fetch_content<'py>(
     )?;
 
     pyo3_async_runtimes::tokio::future_into_py(py, async move {
-        let content = fetch_notion_content(&config).await?;
-        Ok(PyNotionContent { inner: content })
+        let pipeline = BindingPipeline { config: &config };
+        let mut run = Run::start(&config.notion_id);
+        let content = run
+            .fetch(&pipeline, &config.notion_id)
+            .await
+            .map_err(fetch_error)?;
+        Ok(PyNotionContent {
+            inner: content,
+            run_id: run.id(),
+        })
     })
 }
 
@@ -150,49 +182,112 @@ pub fn render_content(
 ) -> PyResult<String> {
     let template = notion2prompt::TemplateName::new(template)
         .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid template: {}", e)))?;
-    let config = PipelineConfig {
-        template,
-        include_properties,
-        instruction,
-        ..PipelineConfig::default()
-    };
+    let mut config = PipelineConfig::default();
+    config.template = template;
+    config.include_properties = include_properties;
+    config.instruction = instruction;
+
+    BindingPipeline { config: &config }
+        .compose(&content.inner)
+        .map(RenderedPrompt::into_string)
+        .map_err(render_error)
+}
 
-    compose_prompt(&content.inner, &config)
+/// Render blocks to markdown on their own, without a page or template
+/// around them: the same markdown the prompt holds for them.
+///
+/// Args:
+///     blocks: Blocks from NotionContent.blocks or Block.children, e.g.
+///         after filtering them, in the order to render them
+///     config: PipelineConfig whose rendering options apply (toggle_style,
+///         math_style, lang, ...); the defaults if None
+#[pyfunction]
+#[pyo3(signature = (blocks, config = None))]
+pub fn render_blocks(
+    blocks: Vec<PyRef<'_, PyBlock>>,
+    config: Option<&PyPipelineConfig>,
+) -> PyResult<String> {
+    let blocks: Vec<Block> = blocks.iter().map(|block| block.inner.clone()).collect();
+    let defaults;
+    let config = match config {
+        Some(config) => &config.inner,
+        None => {
+            defaults = PipelineConfig::default();
+            &defaults
+        }
+    };
+    let context = RenderContext {
+        app_config: Some(config),
+        databases: None,
+    };
+    notion2prompt::render_blocks(&blocks, &context).map_err(render_error)
 }
 
 // --- Internal helpers ---
 
-async fn fetch_notion_content(config: &PipelineConfig) -> PyResult<NotionObject> {
-    let http_client = NotionHttpClient::new(&config.api_key).map_err(|e| {
-        pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to create HTTP client: {}", e))
-    })?;
-
-    let client: Arc<dyn NotionRepository> = if config.no_cache {
-        Arc::new(http_client)
-    } else {
-        Arc::new(
-            CachedNotionClient::new(http_client, config.cache_ttl)
-                .await
-                .map_err(|e| {
-                    pyo3::exceptions::PyRuntimeError::new_err(format!(
-                        "Failed to create cache: {}",
-                        e
-                    ))
-                })?,
-        )
-    };
+/// The fetch and compose stages as the Python bindings run them: no
+/// console output and no delivery, since the prompt is returned to Python.
+pub(crate) struct BindingPipeline<'a> {
+    pub(crate) config: &'a PipelineConfig,
+}
+
+impl BindingPipeline<'_> {
+    /// Fetches `id` along with what the fetch did, for the run metrics.
+    pub(crate) async fn fetch_with_metadata(
+        &self,
+        id: &NotionId,
+    ) -> Result<FetchResult<NotionObject>, AppError> {
+        let http_client =
+            NotionHttpClient::with_pool_config(&self.config.api_key, &self.config.connection_pool)?;
+        let client: Arc<dyn NotionRepository> = if self.config.no_cache {
+            Arc::new(http_client)
+        } else {
+            Arc::new(
+                CachedNotionClient::new(
+                    http_client,
+                    self.config.cache_ttl,
+                    self.config.cache_scope,
+                )
+                .await?,
+            )
+        };
+
+        let fetcher = NotionFetcher::new(client, self.config);
+        fetcher.fetch_recursive(id).await
+    }
+}
 
-    let fetcher = NotionFetcher::new(client, config);
-    let result = fetcher
-        .fetch_recursive(&config.notion_id)
-        .await
-        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("Fetch failed: {}", e)))?;
+#[async_trait::async_trait]
+impl ContentSource for BindingPipeline<'_> {
+    async fn fetch(&self, id: &NotionId) -> Result<NotionObject, AppError> {
+        Ok(self.fetch_with_metadata(id).await?.data)
+    }
+}
+
+impl PromptComposer for BindingPipeline<'_> {
+    fn compose(&self, content: &NotionObject) -> Result<RenderedPrompt, AppError> {
+        notion2prompt::render_prompt(content, self.config)
+    }
+}
+
+pub(crate) fn fetch_error(e: AppError) -> PyErr {
+    runtime_error("Fetch failed", e)
+}
 
-    Ok(result.data)
+pub(crate) fn render_error(e: AppError) -> PyErr {
+    runtime_error("Render failed", e)
 }
 
-fn compose_prompt(content: &NotionObject, config: &PipelineConfig) -> PyResult<String> {
-    let rendered = notion2prompt::render_prompt(content, config)
-        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("Render failed: {}", e)))?;
-    Ok(rendered.into_string())
+/// A RuntimeError for `e`; a delivery error also carries its failed
+/// targets as the second argument.
+fn runtime_error(stage: &str, e: AppError) -> PyErr {
+    let message = format!("{}: {}", stage, e);
+    match e {
+        AppError::DeliveryFailed { failures } => {
+            let failures: Vec<PyDeliveryFailure> =
+                failures.iter().map(PyDeliveryFailure::from).collect();
+            pyo3::exceptions::PyRuntimeError::new_err((message, failures))
+        }
+        _ => pyo3::exceptions::PyRuntimeError::new_err(message),
+    }
 }
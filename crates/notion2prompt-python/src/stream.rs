@@ -0,0 +1,165 @@
+//! `fetch_and_render_stream`: rendered sections handed to Python as they
+//! are composed, for servers that stream a prompt to their clients.
+//!
+//! The fetch and the composition run on the shared tokio runtime. Sections
+//! reach Python through a bounded channel, so composition waits for a slow
+//! reader. Dropping the iterator aborts the fetch, and composition stops
+//! at the next section.
+
+use crate::metrics::Run;
+use crate::pipeline::{fetch_error, render_error, BindingPipeline};
+use crate::types::PyPipelineConfig;
+use notion2prompt::{compose_sections, AppError, PromptSection};
+use pyo3::exceptions::PyStopAsyncIteration;
+use pyo3::prelude::*;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+/// How many composed sections may wait for Python to read them.
+const BUFFERED_CHUNKS: usize = 8;
+
+/// One rendered section of the prompt.
+#[pyclass(name = "PromptChunk", frozen, get_all)]
+#[derive(Clone, Debug)]
+pub struct PyPromptChunk {
+    /// "header", "section", "database" or "block"
+    pub kind: String,
+    /// The file the section belongs to, as named in the prompt
+    pub document: String,
+    /// The heading text, or the document title for a header or database
+    pub heading: String,
+    /// Heading level 1–3, or 0 for anything that is not a heading's section
+    pub level: u8,
+    /// The rendered markdown
+    pub text: String,
+}
+
+impl From<PromptSection> for PyPromptChunk {
+    fn from(section: PromptSection) -> Self {
+        Self {
+            kind: section.kind.as_str().to_string(),
+            document: section.document,
+            heading: section.heading,
+            level: section.level,
+            text: section.markdown,
+        }
+    }
+}
+
+#[pymethods]
+impl PyPromptChunk {
+    fn __str__(&self) -> &str {
+        &self.text
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PromptChunk(kind={:?}, document={:?}, heading={:?}, chars={})",
+            self.kind,
+            self.document,
+            self.heading,
+            self.text.chars().count(),
+        )
+    }
+}
+
+/// An async iterator over the chunks of one `fetch_and_render_stream` call.
+#[pyclass(name = "RenderStream")]
+pub struct PyRenderStream {
+    chunks: Arc<Mutex<mpsc::Receiver<PyResult<PyPromptChunk>>>>,
+    /// The run streaming the chunks, for `get_run_metrics()`
+    #[pyo3(get)]
+    run_id: u64,
+    /// The fetch and composition, aborted when the stream is dropped
+    task: JoinHandle<()>,
+}
+
+impl Drop for PyRenderStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[pymethods]
+impl PyRenderStream {
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let chunks = Arc::clone(&self.chunks);
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            match chunks.lock().await.recv().await {
+                Some(chunk) => chunk,
+                None => Err(PyStopAsyncIteration::new_err(())),
+            }
+        })
+    }
+}
+
+/// Fetch a Notion page/database and yield its rendered sections as they are
+/// composed: each page's header, each top-level section, each database.
+///
+/// Usage:
+///     async for chunk in fetch_and_render_stream(config):
+///         await response.write(chunk.text)
+///
+/// Sections are the page content as markdown; the template's wrapping is
+/// not applied. A failed fetch or render raises RuntimeError from the
+/// iteration.
+///
+/// Args:
+///     config: PipelineConfig naming the page and how to fetch it
+///
+/// Returns:
+///     A RenderStream of PromptChunk.
+#[pyfunction]
+pub fn fetch_and_render_stream(config: &PyPipelineConfig) -> PyRenderStream {
+    let config = Arc::new(config.inner.clone());
+    let (sender, receiver) = mpsc::channel(BUFFERED_CHUNKS);
+    let mut run = Run::start(&config.notion_id);
+    let run_id = run.id();
+
+    let task = pyo3_async_runtimes::tokio::get_runtime().spawn(async move {
+        let pipeline = BindingPipeline { config: &config };
+        let content = match run.fetch(&pipeline, &config.notion_id).await {
+            Ok(content) => content,
+            Err(e) => {
+                let _ = sender.send(Err(fetch_error(e))).await;
+                return;
+            }
+        };
+
+        let composing = {
+            let config = Arc::clone(&config);
+            let sender = sender.clone();
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    compose_sections(&content, &config, &mut |section| {
+                        // A closed channel means the stream was dropped
+                        match sender.blocking_send(Ok(section.into())) {
+                            Ok(()) => ControlFlow::Continue(()),
+                            Err(_) => ControlFlow::Break(()),
+                        }
+                    })
+                })
+                .await
+                .map_err(|e| AppError::InternalError {
+                    message: "Section composer failed".to_string(),
+                    source: Some(Box::new(e)),
+                })?
+            }
+        };
+        if let Err(e) = run.render(composing).await {
+            let _ = sender.send(Err(render_error(e))).await;
+        }
+    });
+
+    PyRenderStream {
+        chunks: Arc::new(Mutex::new(receiver)),
+        run_id,
+        task,
+    }
+}
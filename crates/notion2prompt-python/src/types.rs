@@ -1,7 +1,8 @@
 //! Python wrapper types for notion2prompt domain objects.
 
 use notion2prompt::{
-    ApiKey, Block, Database, NotionId, NotionObject, Page, PipelineConfig, TemplateName,
+    ApiKey, Block, Database, DeliveryFailure, NotionId, NotionObject, Page, PipelineConfig,
+    TemplateName, ValidationError,
 };
 use pyo3::prelude::*;
 
@@ -30,6 +31,13 @@ impl PyPipelineConfig {
     ///     no_cache: Disable response caching
     ///     cache_ttl: Cache TTL in seconds (default 300)
     ///     concurrency: Number of concurrent API workers
+    ///     toggle_style: "indent", "details" or "summary", as with --toggle-style
+    ///     toggle_headings: "expand" or "collapse", as with --toggle-headings
+    ///     math_style: "dollar", "latex" or "code"
+    ///     callout_style: "quote", "gfm" or "obsidian"
+    ///     lang: Language of the added labels: "en", "de" or "fi"
+    ///     property_placement: "hide", "top" or "bottom"
+    ///     auto_summary: "off", "outline" or "lead"
     #[new]
     #[pyo3(signature = (
         notion_id,
@@ -43,6 +51,13 @@ impl PyPipelineConfig {
         no_cache = false,
         cache_ttl = 300,
         concurrency = None,
+        toggle_style = None,
+        toggle_headings = None,
+        math_style = None,
+        callout_style = None,
+        lang = None,
+        property_placement = None,
+        auto_summary = None,
     ))]
     #[allow(clippy::too_many_arguments)]
     fn new(
@@ -57,7 +72,23 @@ impl PyPipelineConfig {
         no_cache: bool,
         cache_ttl: u64,
         concurrency: Option<usize>,
+        toggle_style: Option<&str>,
+        toggle_headings: Option<&str>,
+        math_style: Option<&str>,
+        callout_style: Option<&str>,
+        lang: Option<&str>,
+        property_placement: Option<&str>,
+        auto_summary: Option<&str>,
     ) -> PyResult<Self> {
+        let defaults = PipelineConfig::default();
+        let toggle_style = choice(toggle_style)?.unwrap_or(defaults.toggle_style);
+        let toggle_headings = choice(toggle_headings)?.unwrap_or(defaults.toggle_headings);
+        let math_style = choice(math_style)?.unwrap_or(defaults.math_style);
+        let callout_style = choice(callout_style)?.unwrap_or(defaults.callout_style);
+        let lang = choice(lang)?.unwrap_or(defaults.lang);
+        let property_placement = choice(property_placement)?.unwrap_or(defaults.property_placement);
+        let auto_summary = choice(auto_summary)?.unwrap_or(defaults.auto_summary);
+
         let notion_id = NotionId::parse(notion_id).map_err(|e| {
             pyo3::exceptions::PyValueError::new_err(format!("Invalid Notion ID: {}", e))
         })?;
@@ -73,27 +104,33 @@ impl PyPipelineConfig {
         let content_dir =
             std::env::temp_dir().join(format!("notion2prompt_py_{}", notion_id.value_hyphenated()));
 
-        Ok(Self {
-            inner: PipelineConfig {
-                notion_id,
-                api_key,
-                depth,
-                limit,
-                template,
-                content_dir,
-                output_file: None,
-                clipboard: false,
-                pipe: false,
-                verbose: false,
-                always_fetch_databases,
-                include_properties,
-                instruction,
-                no_cache,
-                cache_ttl,
-                concurrency,
-                raw_input: String::new(),
-            },
-        })
+        let mut config = defaults;
+        config.notion_id = notion_id;
+        config.api_key = api_key;
+        config.depth = depth;
+        config.limit = limit;
+        config.template = template;
+        config.content_dir = content_dir;
+        config.output_file = None;
+        config.clipboard = false;
+        config.pipe = false;
+        config.verbose = false;
+        config.always_fetch_databases = always_fetch_databases;
+        config.include_properties = include_properties;
+        config.instruction = instruction;
+        config.no_cache = no_cache;
+        config.cache_ttl = cache_ttl;
+        config.concurrency = concurrency;
+        config.raw_input = String::new();
+        config.toggle_style = toggle_style;
+        config.toggle_headings = toggle_headings;
+        config.math_style = math_style;
+        config.callout_style = callout_style;
+        config.lang = lang;
+        config.property_placement = property_placement;
+        config.auto_summary = auto_summary;
+
+        Ok(Self { inner: config })
     }
 
     #[getter]
@@ -121,6 +158,41 @@ impl PyPipelineConfig {
         self.inner.include_properties
     }
 
+    #[getter]
+    fn toggle_style(&self) -> String {
+        self.inner.toggle_style.to_string()
+    }
+
+    #[getter]
+    fn toggle_headings(&self) -> String {
+        self.inner.toggle_headings.to_string()
+    }
+
+    #[getter]
+    fn math_style(&self) -> String {
+        self.inner.math_style.to_string()
+    }
+
+    #[getter]
+    fn callout_style(&self) -> String {
+        self.inner.callout_style.to_string()
+    }
+
+    #[getter]
+    fn lang(&self) -> String {
+        self.inner.lang.to_string()
+    }
+
+    #[getter]
+    fn property_placement(&self) -> String {
+        self.inner.property_placement.to_string()
+    }
+
+    #[getter]
+    fn auto_summary(&self) -> String {
+        self.inner.auto_summary.to_string()
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "PipelineConfig(notion_id='{}', depth={}, limit={}, template='{}')",
@@ -140,10 +212,17 @@ impl PyPipelineConfig {
 #[derive(Clone)]
 pub struct PyNotionContent {
     pub(crate) inner: NotionObject,
+    pub(crate) run_id: u64,
 }
 
 #[pymethods]
 impl PyNotionContent {
+    /// The fetch that produced the content, for get_run_metrics().
+    #[getter]
+    fn run_id(&self) -> u64 {
+        self.run_id
+    }
+
     /// The type of the content: "page", "database", or "block".
     #[getter]
     fn content_type(&self) -> &str {
@@ -160,6 +239,18 @@ impl PyNotionContent {
         self.inner.display_title().to_string()
     }
 
+    /// The top-level blocks: a page's content, or the block itself. A
+    /// database has none.
+    #[getter]
+    fn blocks(&self) -> Vec<PyBlock> {
+        let blocks = match &self.inner {
+            NotionObject::Page(page) => page.blocks.as_slice(),
+            NotionObject::Database(_) => &[],
+            NotionObject::Block(block) => std::slice::from_ref(block),
+        };
+        blocks.iter().cloned().map(PyBlock::from).collect()
+    }
+
     /// Serialize the content to a JSON string.
     fn to_json(&self) -> PyResult<String> {
         match &self.inner {
@@ -178,6 +269,62 @@ impl PyNotionContent {
     }
 }
 
+/// A block of fetched content, from `NotionContent.blocks` or
+/// `Block.children`.
+///
+/// Filter blocks in Python and pass the ones to keep to `render_blocks()`.
+#[pyclass(name = "Block")]
+#[derive(Clone)]
+pub struct PyBlock {
+    pub(crate) inner: Block,
+}
+
+impl From<Block> for PyBlock {
+    fn from(inner: Block) -> Self {
+        Self { inner }
+    }
+}
+
+#[pymethods]
+impl PyBlock {
+    /// The block ID.
+    #[getter]
+    fn id(&self) -> String {
+        self.inner.id().as_str().to_string()
+    }
+
+    /// The Notion block type, e.g. "paragraph" or "heading_2".
+    #[getter]
+    fn block_type(&self) -> &'static str {
+        self.inner.block_type()
+    }
+
+    /// The fetched child blocks, rendered below the block.
+    #[getter]
+    fn children(&self) -> Vec<PyBlock> {
+        self.inner
+            .children()
+            .iter()
+            .cloned()
+            .map(Self::from)
+            .collect()
+    }
+
+    /// Serialize the whole block, text and children included, to a JSON string.
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string_pretty(&self.inner)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Block(type='{}', id='{}')",
+            self.block_type(),
+            self.inner.id().as_str(),
+        )
+    }
+}
+
 fn page_to_json(page: &Page) -> PyResult<String> {
     let val = serde_json::json!({
         "type": "page",
@@ -214,6 +361,17 @@ fn block_to_json(block: &Block) -> PyResult<String> {
         .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
 }
 
+/// Parses an option named as on the command line; the error lists the
+/// valid names.
+fn choice<'a, T>(name: Option<&'a str>) -> PyResult<Option<T>>
+where
+    T: TryFrom<&'a str, Error = ValidationError>,
+{
+    name.map(T::try_from)
+        .transpose()
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+}
+
 /// Resolve a PipelineConfig from environment and arguments.
 ///
 /// Reads NOTION_API_KEY from environment if api_key is None.
@@ -253,23 +411,61 @@ pub(crate) fn resolve_config(
     let content_dir =
         std::env::temp_dir().join(format!("notion2prompt_py_{}", notion_id.value_hyphenated()));
 
-    Ok(PipelineConfig {
-        notion_id,
-        api_key,
-        depth,
-        limit,
-        template,
-        content_dir,
-        output_file: None,
-        clipboard: false,
-        pipe: false,
-        verbose: false,
-        always_fetch_databases,
-        include_properties,
-        instruction,
-        no_cache,
-        cache_ttl,
-        concurrency,
-        raw_input: String::new(),
-    })
+    let mut config = PipelineConfig::default();
+    config.notion_id = notion_id;
+    config.api_key = api_key;
+    config.depth = depth;
+    config.limit = limit;
+    config.template = template;
+    config.content_dir = content_dir;
+    config.output_file = None;
+    config.clipboard = false;
+    config.pipe = false;
+    config.verbose = false;
+    config.always_fetch_databases = always_fetch_databases;
+    config.include_properties = include_properties;
+    config.instruction = instruction;
+    config.no_cache = no_cache;
+    config.cache_ttl = cache_ttl;
+    config.concurrency = concurrency;
+    config.raw_input = String::new();
+    Ok(config)
+}
+
+/// One delivery target that failed, carried by the RuntimeError of a
+/// failed delivery as its second argument.
+#[pyclass(name = "DeliveryFailure", frozen, get_all)]
+#[derive(Clone, Debug)]
+pub struct PyDeliveryFailure {
+    /// "file", "directory", "clipboard" or "stdout"
+    pub kind: String,
+    /// The file or directory, for filesystem targets
+    pub path: Option<String>,
+    /// The I/O error kind, e.g. "PermissionDenied", when the target failed with one
+    pub io_kind: Option<String>,
+    /// Whether delivering to the same target again may succeed
+    pub retryable: bool,
+    pub message: String,
+}
+
+#[pymethods]
+impl PyDeliveryFailure {
+    fn __repr__(&self) -> String {
+        format!(
+            "DeliveryFailure(kind={:?}, path={:?}, retryable={})",
+            self.kind, self.path, self.retryable
+        )
+    }
+}
+
+impl From<&DeliveryFailure> for PyDeliveryFailure {
+    fn from(failure: &DeliveryFailure) -> Self {
+        Self {
+            kind: failure.kind.to_string(),
+            path: failure.path.as_ref().map(|path| path.display().to_string()),
+            io_kind: failure.io_kind.map(|kind| format!("{:?}", kind)),
+            retryable: failure.is_retryable(),
+            message: failure.message.clone(),
+        }
+    }
 }
@@ -0,0 +1,79 @@
+//! WebAssembly bindings for the notion2prompt compose stage.
+//!
+//! Renders content fetched elsewhere, e.g. by `notion2prompt --format json`,
+//! to Markdown or HTML without any network access, so web apps can do it
+//! client-side. The library is built without its `fetch` feature, which
+//! leaves out tokio, reqwest and the Notion API client.
+
+use notion2prompt::{compose_notion_markdown, Document, NotionObject, RenderContext};
+use wasm_bindgen::prelude::*;
+
+/// Renders serialized `NotionObject` JSON to Markdown, as the prompt
+/// renders it.
+#[wasm_bindgen(js_name = renderMarkdown)]
+pub fn render_markdown(content_json: &str) -> Result<String, JsError> {
+    compose_notion_markdown(&content(content_json)?, &RenderContext::default())
+        .map_err(|e| JsError::new(&format!("Render failed: {}", e)))
+}
+
+/// Renders serialized `NotionObject` JSON to a standalone HTML `<article>`.
+#[wasm_bindgen(js_name = renderHtml)]
+pub fn render_html(content_json: &str) -> Result<String, JsError> {
+    Document::from_object(&content(content_json)?)
+        .map(|document| document.to_html())
+        .map_err(|e| JsError::new(&format!("Render failed: {}", e)))
+}
+
+/// The version of the bindings.
+#[wasm_bindgen]
+pub fn version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+fn content(content_json: &str) -> Result<NotionObject, JsError> {
+    serde_json::from_str(content_json)
+        .map_err(|e| JsError::new(&format!("Invalid content JSON: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notion2prompt::{
+        Block, BlockCommon, BlockId, Color, Heading1Block, Page, PageId, ParagraphBlock,
+        RichTextItem, TextBlockContent,
+    };
+
+    fn content(text: &str) -> TextBlockContent {
+        TextBlockContent {
+            rich_text: vec![RichTextItem::plain_text(text)],
+            color: Color::Default,
+        }
+    }
+
+    fn page_json() -> String {
+        let page = NotionObject::Page(Page::new(PageId::new_v4(), "Plan").with_blocks(vec![
+            Block::Heading1(Heading1Block {
+                common: BlockCommon::new(BlockId::new_v4()),
+                content: content("Goals"),
+            }),
+            Block::Paragraph(ParagraphBlock {
+                common: BlockCommon::new(BlockId::new_v4()),
+                content: content("Ship it"),
+            }),
+        ]));
+        serde_json::to_string(&page).unwrap()
+    }
+
+    #[test]
+    fn test_serialized_content_renders_to_markdown_and_html() {
+        let json = page_json();
+
+        let markdown = render_markdown(&json).unwrap();
+        assert!(markdown.contains("# Goals"), "{}", markdown);
+        assert!(markdown.contains("Ship it"), "{}", markdown);
+
+        let html = render_html(&json).unwrap();
+        assert!(html.contains("<h2>Goals</h2>"), "{}", html);
+        assert!(html.contains("<p>Ship it</p>"), "{}", html);
+    }
+}
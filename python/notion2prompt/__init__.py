@@ -15,20 +15,50 @@ Usage:
     # Two-stage: fetch first, render later (possibly with different templates)
     content = asyncio.run(notion2prompt.fetch_content("your-page-id"))
     prompt = notion2prompt.render_content(content, template="claude-xml")
+
+    # Partial: render only the blocks picked in Python
+    headings = [b for b in content.blocks if b.block_type.startswith("heading")]
+    markdown = notion2prompt.render_blocks(headings)
+
+    # Streamed: rendered sections as they are composed
+    async def stream(config):
+        async for chunk in notion2prompt.fetch_and_render_stream(config):
+            yield chunk.text
+
+    # What the most recent fetch did: items, warnings, timings, cache hits
+    metrics = notion2prompt.get_last_run_metrics()
 """
 
 from notion2prompt._notion2prompt import (
+    Block,
+    DeliveryFailure,
     PipelineConfig,
     NotionContent,
+    PromptChunk,
+    RenderStream,
+    RunMetrics,
     fetch_and_render,
+    fetch_and_render_stream,
     fetch_content,
+    get_last_run_metrics,
+    get_run_metrics,
+    render_blocks,
     render_content,
 )
 
 __all__ = [
+    "Block",
+    "DeliveryFailure",
     "PipelineConfig",
     "NotionContent",
+    "PromptChunk",
+    "RenderStream",
+    "RunMetrics",
     "fetch_and_render",
+    "fetch_and_render_stream",
     "fetch_content",
+    "get_last_run_metrics",
+    "get_run_metrics",
+    "render_blocks",
     "render_content",
 ]
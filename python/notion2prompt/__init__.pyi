@@ -1,6 +1,6 @@
 """Type stubs for notion2prompt."""
 
-from typing import Optional
+from typing import AsyncIterator, List, Optional, Tuple, Union
 
 class PipelineConfig:
     """Configuration for the notion2prompt pipeline."""
@@ -18,7 +18,18 @@ class PipelineConfig:
         no_cache: bool = False,
         cache_ttl: int = 300,
         concurrency: Optional[int] = None,
-    ) -> None: ...
+        toggle_style: Optional[str] = None,
+        toggle_headings: Optional[str] = None,
+        math_style: Optional[str] = None,
+        callout_style: Optional[str] = None,
+        lang: Optional[str] = None,
+        property_placement: Optional[str] = None,
+        auto_summary: Optional[str] = None,
+    ) -> None:
+        """Options that pick one of several behaviors take the names the
+        command line uses (math_style="latex" as with --math-style latex);
+        an unknown name raises ValueError listing the valid ones."""
+        ...
 
     @property
     def depth(self) -> int: ...
@@ -30,6 +41,20 @@ class PipelineConfig:
     def always_fetch_databases(self) -> bool: ...
     @property
     def include_properties(self) -> bool: ...
+    @property
+    def toggle_style(self) -> str: ...
+    @property
+    def toggle_headings(self) -> str: ...
+    @property
+    def math_style(self) -> str: ...
+    @property
+    def callout_style(self) -> str: ...
+    @property
+    def lang(self) -> str: ...
+    @property
+    def property_placement(self) -> str: ...
+    @property
+    def auto_summary(self) -> str: ...
 
 class NotionContent:
     """Fetched Notion content — a page, database, or block."""
@@ -42,10 +67,133 @@ class NotionContent:
     def title(self) -> str:
         """The display title of the content."""
         ...
+    @property
+    def run_id(self) -> int:
+        """The fetch that produced the content, for get_run_metrics()."""
+        ...
+    @property
+    def blocks(self) -> List["Block"]:
+        """The top-level blocks: a page's content, or the block itself.
+        A database has none."""
+        ...
     def to_json(self) -> str:
         """Serialize the content to a JSON string."""
         ...
 
+class Block:
+    """A block of fetched content, from NotionContent.blocks or
+    Block.children. Filter blocks in Python and pass the ones to keep to
+    render_blocks()."""
+
+    @property
+    def id(self) -> str: ...
+    @property
+    def block_type(self) -> str:
+        """The Notion block type, e.g. "paragraph" or "heading_2"."""
+        ...
+    @property
+    def children(self) -> List["Block"]:
+        """The fetched child blocks, rendered below the block."""
+        ...
+    def to_json(self) -> str:
+        """Serialize the whole block, text and children included, to a JSON string."""
+        ...
+
+class PromptChunk:
+    """One rendered section of the prompt."""
+
+    @property
+    def kind(self) -> str:
+        """One of "header", "section", "database" or "block"."""
+        ...
+    @property
+    def document(self) -> str:
+        """The file the section belongs to, as named in the prompt."""
+        ...
+    @property
+    def heading(self) -> str:
+        """The heading text, or the document title for a header or database."""
+        ...
+    @property
+    def level(self) -> int:
+        """Heading level 1-3, or 0 for anything that is not a heading's section."""
+        ...
+    @property
+    def text(self) -> str:
+        """The rendered markdown."""
+        ...
+
+class RenderStream(AsyncIterator[PromptChunk]):
+    """An async iterator over the chunks of one fetch_and_render_stream() call."""
+
+    @property
+    def run_id(self) -> int:
+        """The run streaming the chunks, for get_run_metrics()."""
+        ...
+    def __aiter__(self) -> "RenderStream": ...
+    async def __anext__(self) -> PromptChunk: ...
+
+class RunMetrics:
+    """Fetch metrics of one fetch_and_render(), fetch_and_render_stream() or
+    fetch_content() call."""
+
+    @property
+    def run_id(self) -> int:
+        """Identifies the run; later calls get higher ids."""
+        ...
+    @property
+    def notion_id(self) -> str: ...
+    @property
+    def items_fetched(self) -> int: ...
+    @property
+    def warnings(self) -> List[str]: ...
+    @property
+    def fetch_ms(self) -> int: ...
+    @property
+    def render_ms(self) -> Optional[int]:
+        """None when the run did not render, or failed before it."""
+        ...
+    @property
+    def api_requests(self) -> int:
+        """Requests answered by the Notion API."""
+        ...
+    @property
+    def cache_hits(self) -> int:
+        """Responses answered from the disk cache."""
+        ...
+    @property
+    def bytes_downloaded(self) -> int: ...
+    @property
+    def error(self) -> Optional[str]:
+        """Why the run failed, if it did."""
+        ...
+    def to_json(self) -> str:
+        """Serialize the metrics to a JSON string."""
+        ...
+
+class DeliveryFailure:
+    """One delivery target that failed. A RuntimeError from a failed
+    delivery carries a list of them as its second argument."""
+
+    @property
+    def kind(self) -> str:
+        """"file", "directory", "clipboard" or "stdout"."""
+        ...
+    @property
+    def path(self) -> Optional[str]:
+        """The file or directory, for filesystem targets."""
+        ...
+    @property
+    def io_kind(self) -> Optional[str]:
+        """The I/O error kind, e.g. "PermissionDenied", when there was one."""
+        ...
+    @property
+    def retryable(self) -> bool:
+        """Whether delivering to the same target again may succeed."""
+        ...
+    @property
+    def message(self) -> str: ...
+
 async def fetch_and_render(
     notion_id: str,
     api_key: Optional[str] = None,
@@ -58,7 +206,8 @@ async def fetch_and_render(
     no_cache: bool = False,
     cache_ttl: int = 300,
     concurrency: Optional[int] = None,
-) -> str:
+    with_metrics: bool = False,
+) -> Union[str, Tuple[str, RunMetrics]]:
     """Fetch a Notion page/database and render it to a prompt string.
 
     Args:
@@ -73,9 +222,21 @@ async def fetch_and_render(
         no_cache: Disable response caching
         cache_ttl: Cache TTL in seconds (default 300)
         concurrency: Number of concurrent API workers
+        with_metrics: Also return the RunMetrics of this call
 
     Returns:
-        The rendered prompt string.
+        The rendered prompt string, or a (prompt, RunMetrics) tuple with
+        with_metrics.
+    """
+    ...
+
+def fetch_and_render_stream(config: PipelineConfig) -> RenderStream:
+    """Fetch a Notion page/database and yield its rendered sections as they
+    are composed: each page's header, each top-level section, each database.
+
+    Sections are the page content as markdown; the template's wrapping is
+    not applied. A failed fetch or render raises RuntimeError from the
+    iteration.
     """
     ...
 
@@ -103,3 +264,26 @@ def render_content(
 ) -> str:
     """Render previously fetched content to a prompt string."""
     ...
+
+def render_blocks(blocks: List[Block], config: Optional[PipelineConfig] = None) -> str:
+    """Render blocks to markdown on their own, without a page or template
+    around them: the same markdown the prompt holds for them.
+
+    config's rendering options apply (toggle_style, math_style, lang, ...);
+    the defaults if None.
+    """
+    ...
+
+def get_last_run_metrics() -> Optional[RunMetrics]:
+    """Metrics of the most recently finished fetch, from any thread.
+
+    Returns None before the first fetch.
+    """
+    ...
+
+def get_run_metrics(run_id: int) -> Optional[RunMetrics]:
+    """Metrics of the run with run_id, or None if it is unknown.
+
+    Only the 64 most recent runs are kept.
+    """
+    ...
@@ -87,6 +87,22 @@ pub trait NotionContent: Send + Sync {
     /// Returns all pages (rows) in the database, respecting pagination.
     /// Each row includes its properties and content.
     async fn query_rows(&self, database: &NotionId) -> Result<Vec<Page>, FetchError>;
+
+    /// Query the first `limit` rows of a database, in query order.
+    ///
+    /// By default this queries every row and keeps the first `limit`, so
+    /// the whole database is still fetched. Implementations backed by the
+    /// API should stop paginating once `limit` rows are in, for example by
+    /// giving the paginated walk `limit` as its `PageLimits::max_items`.
+    async fn query_rows_limited(
+        &self,
+        database: &NotionId,
+        limit: usize,
+    ) -> Result<Vec<Page>, FetchError> {
+        let mut rows = self.query_rows(database).await?;
+        rows.truncate(limit);
+        Ok(rows)
+    }
 }
 
 // ==============================================================================
@@ -124,6 +140,35 @@ pub trait NotionContentExt: NotionContent {
         Ok(database)
     }
 
+    /// Retrieve a page together with its blocks, `depth` levels deep.
+    ///
+    /// Unlike [`retrieve_page_recursive`](Self::retrieve_page_recursive),
+    /// which only expands blocks already on the page, this lists the page's
+    /// own blocks first. A depth of 1 fetches the top-level blocks only; 0
+    /// returns the page without content.
+    async fn fetch_page_with_children(&self, id: &NotionId, depth: u8) -> Result<Page, FetchError> {
+        let mut page = self.retrieve_page(id).await?;
+        if depth > 0 {
+            page.blocks = self.retrieve_children(id).await?;
+            fetch_children_recursive(self, &mut page.blocks, depth - 1).await?;
+        }
+        Ok(page)
+    }
+
+    /// Retrieve a database with at most `row_limit` of its rows, in query order.
+    ///
+    /// The rows come from [`query_rows_limited`](NotionContent::query_rows_limited),
+    /// so how many are fetched beyond `row_limit` is up to the implementation.
+    async fn fetch_database_with_rows(
+        &self,
+        id: &NotionId,
+        row_limit: usize,
+    ) -> Result<Database, FetchError> {
+        let mut database = self.retrieve_database(id).await?;
+        database.pages = self.query_rows_limited(id, row_limit).await?;
+        Ok(database)
+    }
+
     /// Check if an ID refers to a valid, accessible object.
     ///
     /// Attempts to fetch the object and returns true if successful.
@@ -187,6 +232,8 @@ mod tests {
         blocks: Arc<RwLock<HashMap<String, Block>>>,
         children: Arc<RwLock<HashMap<String, Vec<Block>>>>,
         rows: Arc<RwLock<HashMap<String, Vec<Page>>>>,
+        /// The limit of each row query, `None` for an unlimited one
+        row_queries: Arc<RwLock<Vec<Option<usize>>>>,
     }
 
     impl MockNotionContent {
@@ -198,6 +245,7 @@ mod tests {
                 blocks: Arc::new(RwLock::new(HashMap::new())),
                 children: Arc::new(RwLock::new(HashMap::new())),
                 rows: Arc::new(RwLock::new(HashMap::new())),
+                row_queries: Arc::new(RwLock::new(Vec::new())),
             }
         }
 
@@ -208,7 +256,6 @@ mod tests {
         }
 
         /// Add a database to the mock store.
-        #[allow(dead_code)]
         pub async fn add_database(&self, database: Database) {
             let mut databases = self.databases.write().await;
             databases.insert(database.id.as_str().to_string(), database);
@@ -277,9 +324,24 @@ mod tests {
         }
 
         async fn query_rows(&self, database: &NotionId) -> Result<Vec<Page>, FetchError> {
+            self.row_queries.write().await.push(None);
             let rows = self.rows.read().await;
             Ok(rows.get(database.as_str()).cloned().unwrap_or_default())
         }
+
+        async fn query_rows_limited(
+            &self,
+            database: &NotionId,
+            limit: usize,
+        ) -> Result<Vec<Page>, FetchError> {
+            self.row_queries.write().await.push(Some(limit));
+            let rows = self.rows.read().await;
+            let rows = rows
+                .get(database.as_str())
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            Ok(rows.iter().take(limit).cloned().collect())
+        }
     }
 
     // ========================================================================
@@ -293,15 +355,11 @@ mod tests {
         let id = NotionId::parse("550e8400e29b41d4a716446655440000").unwrap();
 
         // Create and add a test page
-        let page = Page {
-            id: PageId::parse("550e8400e29b41d4a716446655440000").unwrap(),
-            title: crate::model::PageTitle::new("Test"),
-            url: "https://notion.so/test".to_string(),
-            blocks: vec![],
-            properties: HashMap::new(),
-            parent: None,
-            archived: false,
-        };
+        let page = Page::new(
+            PageId::parse("550e8400e29b41d4a716446655440000").unwrap(),
+            "Test",
+        )
+        .with_url("https://notion.so/test");
         content.add_page(page).await;
 
         // Fetch twice — both return cloned data from the store
@@ -324,6 +382,8 @@ mod tests {
                 children: vec![],
                 has_children: false,
                 archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
             },
             content: crate::model::TextBlockContent::default(),
         });
@@ -344,17 +404,14 @@ mod tests {
         let db_id = NotionId::parse("550e8400e29b41d4a716446655440000").unwrap();
 
         // Create a row page with this database as parent
-        let row = Page {
-            id: PageId::parse("550e8400e29b41d4a716446655440001").unwrap(),
-            title: crate::model::PageTitle::new("Row"),
-            url: "https://notion.so/row".to_string(),
-            blocks: vec![],
-            properties: HashMap::new(),
-            parent: Some(crate::model::Parent::Database {
-                database_id: DatabaseId::parse("550e8400e29b41d4a716446655440000").unwrap(),
-            }),
-            archived: false,
-        };
+        let row = Page::new(
+            PageId::parse("550e8400e29b41d4a716446655440001").unwrap(),
+            "Row",
+        )
+        .with_url("https://notion.so/row")
+        .with_parent(crate::model::Parent::Database {
+            database_id: DatabaseId::parse("550e8400e29b41d4a716446655440000").unwrap(),
+        });
 
         content.add_rows(&db_id, vec![row.clone()]).await;
 
@@ -381,6 +438,8 @@ mod tests {
                 children: vec![],
                 has_children: false,
                 archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
             },
             content: crate::model::TextBlockContent::default(),
         });
@@ -394,6 +453,39 @@ mod tests {
         assert_eq!(retrieved.id().as_str(), id.as_str());
     }
 
+    /// All four laws, on generated retrieval orders over a small workspace
+    #[test]
+    fn mock_content_satisfies_laws() {
+        let content = MockNotionContent::new();
+        let page_id = "550e8400e29b41d4a716446655440000";
+        let db_id = "550e8400e29b41d4a716446655440001";
+        let block_id = "550e8400e29b41d4a716446655440002";
+        let paragraph = Block::Paragraph(crate::model::ParagraphBlock {
+            common: crate::model::BlockCommon::new(BlockId::parse(block_id).unwrap()),
+            content: crate::model::TextBlockContent::default(),
+        });
+        let row = Page::new(
+            PageId::parse("550e8400e29b41d4a716446655440003").unwrap(),
+            "Row",
+        )
+        .with_parent(crate::model::Parent::Database {
+            database_id: DatabaseId::parse(db_id).unwrap(),
+        });
+        let ids: Vec<NotionId> = [page_id, db_id, block_id, "550e8400e29b41d4a7164466554400ff"]
+            .iter()
+            .map(|id| NotionId::parse(id).unwrap())
+            .collect();
+        futures::executor::block_on(async {
+            content.add_block(paragraph.clone()).await;
+            content.add_children(&ids[0], vec![paragraph]).await;
+            content.add_rows(&ids[1], vec![row]).await;
+        });
+
+        futures::executor::block_on(super::super::testkit::check_notion_content_laws(
+            &content, &ids,
+        ));
+    }
+
     // ========================================================================
     // Extension Trait Tests
     // ========================================================================
@@ -403,15 +495,11 @@ mod tests {
         let content = MockNotionContent::new();
         let id = NotionId::parse("550e8400e29b41d4a716446655440000").unwrap();
 
-        let page = Page {
-            id: PageId::parse("550e8400e29b41d4a716446655440000").unwrap(),
-            title: crate::model::PageTitle::new("Test"),
-            url: "https://notion.so/test".to_string(),
-            blocks: vec![],
-            properties: HashMap::new(),
-            parent: None,
-            archived: false,
-        };
+        let page = Page::new(
+            PageId::parse("550e8400e29b41d4a716446655440000").unwrap(),
+            "Test",
+        )
+        .with_url("https://notion.so/test");
         content.add_page(page).await;
 
         assert!(content.exists(&id).await);
@@ -424,4 +512,88 @@ mod tests {
 
         assert!(!content.exists(&id).await);
     }
+
+    fn paragraph(id: &str, has_children: bool) -> Block {
+        Block::Paragraph(crate::model::ParagraphBlock {
+            common: crate::model::BlockCommon {
+                id: BlockId::parse(id).unwrap(),
+                children: vec![],
+                has_children,
+                archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
+            },
+            content: crate::model::TextBlockContent::default(),
+        })
+    }
+
+    fn page(id: &str, title: &str) -> Page {
+        Page::new(PageId::parse(id).unwrap(), title)
+    }
+
+    #[tokio::test]
+    async fn fetch_page_with_children_stops_at_depth() {
+        let content = MockNotionContent::new();
+        let page_id = NotionId::parse("550e8400e29b41d4a716446655440000").unwrap();
+        let top = NotionId::parse("550e8400e29b41d4a716446655440001").unwrap();
+        let nested = NotionId::parse("550e8400e29b41d4a716446655440002").unwrap();
+        content
+            .add_page(page("550e8400e29b41d4a716446655440000", "Test"))
+            .await;
+        content
+            .add_children(
+                &page_id,
+                vec![paragraph("550e8400e29b41d4a716446655440001", true)],
+            )
+            .await;
+        content
+            .add_children(
+                &top,
+                vec![paragraph("550e8400e29b41d4a716446655440002", true)],
+            )
+            .await;
+        content
+            .add_children(
+                &nested,
+                vec![paragraph("550e8400e29b41d4a716446655440003", false)],
+            )
+            .await;
+
+        let shallow = content.fetch_page_with_children(&page_id, 0).await.unwrap();
+        assert!(shallow.blocks.is_empty());
+
+        let page = content.fetch_page_with_children(&page_id, 2).await.unwrap();
+        assert_eq!(page.blocks.len(), 1);
+        let second = page.blocks[0].children();
+        assert_eq!(second.len(), 1);
+        assert!(second[0].children().is_empty(), "fetched past depth 2");
+    }
+
+    #[tokio::test]
+    async fn fetch_database_with_rows_truncates_to_limit() {
+        let content = MockNotionContent::new();
+        let db_id = NotionId::parse("550e8400e29b41d4a716446655440000").unwrap();
+        content
+            .add_database(Database::new(
+                DatabaseId::parse("550e8400e29b41d4a716446655440000").unwrap(),
+                "",
+            ))
+            .await;
+        content
+            .add_rows(
+                &db_id,
+                vec![
+                    page("550e8400e29b41d4a716446655440001", "a"),
+                    page("550e8400e29b41d4a716446655440002", "b"),
+                    page("550e8400e29b41d4a716446655440003", "c"),
+                ],
+            )
+            .await;
+
+        let database = content.fetch_database_with_rows(&db_id, 2).await.unwrap();
+
+        let titles: Vec<_> = database.pages.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["a", "b"]);
+        assert_eq!(*content.row_queries.read().await, [Some(2)]);
+    }
 }
@@ -129,11 +129,14 @@ impl std::error::Error for TrackError {}
 
 impl From<crate::error::AppError> for FetchError {
     fn from(err: crate::error::AppError) -> Self {
-        use crate::error::{AppError, NotionClientError, NotionErrorCode};
+        #[cfg(feature = "fetch")]
+        use crate::error::NotionErrorCode;
+        use crate::error::{AppError, NotionClientError};
 
         match err {
             // Note: `ref` bindings are required here so `err` remains available
             // for `err.to_string()` in the wildcard catch-all sub-arm.
+            #[cfg(feature = "fetch")]
             AppError::NotionService {
                 ref code,
                 ref message,
@@ -188,6 +191,7 @@ impl From<crate::error::AppError> for FetchError {
                     status: status as u16,
                 },
             },
+            #[cfg(feature = "fetch")]
             AppError::NetworkFailure(_) => Self::Transport {
                 message: err.to_string(),
             },
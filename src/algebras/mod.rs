@@ -34,11 +34,15 @@
 //! # Laws
 //!
 //! Each trait documents algebraic laws that all implementations must satisfy.
-//! These are verified via law tests in each module's test suite.
+//! These are verified via law tests in each module's test suite, and
+//! [`testkit`] (with the `testkit` feature) checks any implementation
+//! against them with property-based tests.
 
 pub mod content;
 pub mod error;
 pub mod state;
+#[cfg(any(test, feature = "testkit"))]
+pub mod testkit;
 
 // Re-exports for convenience
 pub use content::{NotionContent, NotionContentExt};
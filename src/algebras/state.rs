@@ -311,6 +311,24 @@ mod tests {
         assert_eq!(limiter.enter().await, None);
     }
 
+    // ========================================================================
+    // Property-Based Law Checks
+    // ========================================================================
+
+    #[test]
+    fn in_memory_visit_tracker_satisfies_laws() {
+        futures::executor::block_on(super::super::testkit::check_visit_tracker_laws(
+            InMemoryVisitTracker::new,
+        ));
+    }
+
+    #[test]
+    fn in_memory_depth_limiter_satisfies_laws() {
+        futures::executor::block_on(super::super::testkit::check_depth_limiter_laws(
+            InMemoryDepthLimiter::new,
+        ));
+    }
+
     // ========================================================================
     // Extension Trait Tests
     // ========================================================================
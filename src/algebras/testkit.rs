@@ -0,0 +1,500 @@
+//! Law checks for implementations of the capability traits.
+//!
+//! Each trait in this module's parent documents laws that every
+//! implementation must satisfy. The functions here check an implementation
+//! against them with property-based testing: [`proptest`](mod@proptest) generates
+//! sequences of operations, runs them, and checks every result against the
+//! laws and against a model of what the results must be. A violation panics
+//! with the shrunk sequence that caused it.
+//!
+//! The checks are `async` and await the implementation on the caller's
+//! executor, so they run from whatever test runtime the implementation
+//! needs:
+//!
+//! ```ignore
+//! #[tokio::test]
+//! async fn my_content_is_lawful() {
+//!     notion2prompt::testkit::check_notion_content_laws(&MyContent::new(), &ids).await;
+//! }
+//! ```
+//!
+//! An implementation that needs no runtime can be checked from a plain
+//! `#[test]` with `futures::executor::block_on`.
+//!
+//! Available with the `testkit` feature. The number of cases can be set with
+//! the `PROPTEST_CASES` environment variable.
+
+use super::{DepthLimiter, FetchError, NotionContent, VisitTracker};
+use crate::model::Parent;
+use crate::types::NotionId;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+
+/// How many distinct IDs the generated operations draw from. Few enough
+/// that the same ID comes up again and again.
+const ID_POOL: usize = 8;
+
+/// Longest generated operation sequence.
+const MAX_OPS: usize = 40;
+
+/// Runs `test` on the cases `strategy` generates, panicking with the
+/// shrunk counterexample when one fails.
+///
+/// `TestRunner::run` only takes a synchronous test, so the cases are drawn
+/// and shrunk here, awaiting each run of `test`.
+async fn run<S, F, Fut>(law: &str, strategy: S, test: F)
+where
+    S: Strategy,
+    F: Fn(S::Value) -> Fut,
+    Fut: Future<Output = Result<(), TestCaseError>>,
+{
+    let config = Config {
+        // Counterexamples are reported, not saved next to the caller's sources
+        failure_persistence: None,
+        ..Config::default()
+    };
+    let (cases, max_shrink_iters) = (config.cases, config.max_shrink_iters);
+    let mut runner = TestRunner::new(config);
+    for _ in 0..cases {
+        let mut tree = strategy
+            .new_tree(&mut runner)
+            .unwrap_or_else(|e| panic!("{} laws: no case generated: {}", law, e));
+        let Err(TestCaseError::Fail(reason)) = test(tree.current()).await else {
+            continue;
+        };
+
+        // Simplify while the case still fails, back off when it passes
+        let mut minimal = (tree.current(), reason);
+        let mut shrinking = tree.simplify();
+        let mut iterations = 0;
+        while shrinking && iterations < max_shrink_iters {
+            iterations += 1;
+            shrinking = match test(tree.current()).await {
+                Err(TestCaseError::Fail(reason)) => {
+                    minimal = (tree.current(), reason);
+                    tree.simplify()
+                }
+                _ => tree.complicate(),
+            };
+        }
+        panic!(
+            "{} laws violated: {}; minimal failing input: {:?}",
+            law, minimal.1, minimal.0
+        );
+    }
+}
+
+/// `index`'s ID in the pool.
+fn pool_id(index: usize) -> NotionId {
+    NotionId::parse(&format!("{:032x}", 0x5ee0_0000_u128 + index as u128))
+        .expect("valid generated ID")
+}
+
+// ==============================================================================
+// VisitTracker
+// ==============================================================================
+
+/// An operation on a [`VisitTracker`], on one of the pooled IDs.
+#[derive(Debug, Clone, Copy)]
+enum VisitOp {
+    Visit(usize),
+    IsVisited(usize),
+}
+
+fn visit_ops() -> impl Strategy<Value = Vec<VisitOp>> {
+    let op = prop_oneof![
+        (0..ID_POOL).prop_map(VisitOp::Visit),
+        (0..ID_POOL).prop_map(VisitOp::IsVisited),
+    ];
+    vec(op, 0..MAX_OPS)
+}
+
+/// Checks the [`VisitTracker`] laws on trackers made by `new`, one fresh
+/// tracker per generated case:
+///
+/// - **L1 (Idempotency)**: only the first visit of an ID returns `true`
+/// - **L2 (Is-Visited Consistency)**: `is_visited` is `true` exactly for
+///   visited IDs
+/// - **L3 (Persistence)**: a visited ID stays visited, whatever follows
+///
+/// # Panics
+///
+/// When a law is violated, or a visit fails.
+pub async fn check_visit_tracker_laws<T, F>(new: F)
+where
+    T: VisitTracker,
+    F: Fn() -> T,
+{
+    let new = &new;
+    run("VisitTracker", visit_ops(), |ops| async move {
+        let tracker = new();
+        let mut visited = HashSet::new();
+        for op in ops {
+            match op {
+                VisitOp::Visit(index) => {
+                    let id = pool_id(index);
+                    let first = tracker
+                        .visit(&id)
+                        .await
+                        .map_err(|e| TestCaseError::fail(format!("visit failed: {}", e)))?;
+                    prop_assert_eq!(
+                        first,
+                        visited.insert(index),
+                        "L1: visit({}) must be true on the first visit only",
+                        id
+                    );
+                }
+                VisitOp::IsVisited(index) => {
+                    let id = pool_id(index);
+                    prop_assert_eq!(
+                        tracker.is_visited(&id).await,
+                        visited.contains(&index),
+                        "L2/L3: is_visited({}) must be true once visited",
+                        id
+                    );
+                }
+            }
+        }
+        Ok(())
+    })
+    .await;
+}
+
+// ==============================================================================
+// DepthLimiter
+// ==============================================================================
+
+/// An operation on a [`DepthLimiter`]. `EnterExit` leaves a level it
+/// entered again, so exits never outnumber entries.
+#[derive(Debug, Clone, Copy)]
+enum DepthOp {
+    Enter,
+    EnterExit,
+    CanEnter,
+}
+
+fn depth_cases() -> impl Strategy<Value = (u8, Vec<DepthOp>)> {
+    let op = prop_oneof![
+        Just(DepthOp::Enter),
+        Just(DepthOp::EnterExit),
+        Just(DepthOp::CanEnter),
+    ];
+    (0..=20u8, vec(op, 0..MAX_OPS))
+}
+
+/// Checks the [`DepthLimiter`] laws on limiters made by `new` with a
+/// generated initial depth, one fresh limiter per case:
+///
+/// - **L1 (Monotonic Decrease)**: `enter` never raises the depth, and
+///   `depth()` reports what it returned; `exit` restores the depth
+///   before the matching `enter`
+/// - **L2 (Can-Enter Consistency)**: `can_enter() == (depth() > 0)`
+/// - **L3 (Zero Termination)**: entering until the depth is 0 stops there,
+///   with `can_enter` false and `enter` returning `None`
+///
+/// # Panics
+///
+/// When a law is violated.
+pub async fn check_depth_limiter_laws<T, F>(new: F)
+where
+    T: DepthLimiter,
+    F: Fn(u8) -> T,
+{
+    let new = &new;
+    run("DepthLimiter", depth_cases(), |(initial, ops)| async move {
+        let limiter = new(initial);
+        for op in ops {
+            let before = limiter.depth().await;
+            prop_assert_eq!(
+                limiter.can_enter().await,
+                before > 0,
+                "L2: can_enter() must be depth() > 0 at depth {}",
+                before
+            );
+            if let DepthOp::CanEnter = op {
+                continue;
+            }
+            let entered = limiter.enter().await;
+            if let Some(after) = entered {
+                prop_assert!(
+                    after <= before,
+                    "L1: enter() raised {} to {}",
+                    before,
+                    after
+                );
+                prop_assert_eq!(limiter.depth().await, after, "L1: depth() after enter()");
+            } else {
+                prop_assert_eq!(before, 0, "L3: enter() refused at depth {}", before);
+                prop_assert_eq!(limiter.depth().await, 0, "L3: refused enter() moved depth");
+            }
+            if let (DepthOp::EnterExit, Some(_)) = (op, entered) {
+                limiter.exit().await;
+                prop_assert_eq!(
+                    limiter.depth().await,
+                    before,
+                    "L1: exit() must restore the depth before enter()"
+                );
+            }
+        }
+
+        // L3: entering runs out, and stays out
+        let mut steps = 0usize;
+        while limiter.enter().await.is_some() {
+            steps += 1;
+            prop_assert!(steps <= usize::from(u8::MAX), "L3: enter() never reached 0");
+        }
+        prop_assert_eq!(limiter.depth().await, 0, "L3: depth() after running out");
+        prop_assert!(!limiter.can_enter().await, "L3: can_enter() at 0");
+        prop_assert_eq!(limiter.enter().await, None, "L3: enter() at 0");
+        Ok(())
+    })
+    .await;
+}
+
+// ==============================================================================
+// NotionContent
+// ==============================================================================
+
+/// A retrieval from a [`NotionContent`], by index into the caller's IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ContentOp {
+    Page(usize),
+    Database(usize),
+    Block(usize),
+    Children(usize),
+    Rows(usize),
+}
+
+impl ContentOp {
+    fn id_index(self) -> usize {
+        match self {
+            Self::Page(i)
+            | Self::Database(i)
+            | Self::Block(i)
+            | Self::Children(i)
+            | Self::Rows(i) => i,
+        }
+    }
+}
+
+/// What a retrieval returned, comparable across calls.
+#[derive(Debug, PartialEq)]
+enum Retrieved {
+    Page(crate::model::Page),
+    Database(crate::model::Database),
+    Block(crate::model::Block),
+    Blocks(Vec<crate::model::Block>),
+    Rows(Vec<crate::model::Page>),
+}
+
+fn content_ops(ids: usize) -> impl Strategy<Value = Vec<ContentOp>> {
+    let op = prop_oneof![
+        (0..ids).prop_map(ContentOp::Page),
+        (0..ids).prop_map(ContentOp::Database),
+        (0..ids).prop_map(ContentOp::Block),
+        (0..ids).prop_map(ContentOp::Children),
+        (0..ids).prop_map(ContentOp::Rows),
+    ];
+    vec(op, 0..MAX_OPS)
+}
+
+/// Checks the [`NotionContent`] laws on `content`, retrieving the objects
+/// in `ids` in generated orders. `ids` should name the pages, databases and
+/// blocks the implementation holds, and may name some it does not:
+///
+/// - **L1 (Idempotency)**: every retrieval returns what the first one of the
+///   same kind for the same ID did, whatever came between. Retryable errors
+///   ([`FetchError::is_retryable`]) are exempt
+/// - **L2 (Children-Consistency)**: `retrieve_children` lists each child
+///   once, never the parent itself
+/// - **L3 (Database-Rows)**: rows `query_rows` returns name the database as
+///   their parent, when they name one
+/// - **L4 (Block-Identity)**: `retrieve_block(id)` returns the block `id`
+///
+/// Unlike the other checks this one reuses `content` for every case, since
+/// its data comes from the caller.
+///
+/// # Panics
+///
+/// When a law is violated, or `ids` is empty.
+pub async fn check_notion_content_laws<C>(content: &C, ids: &[NotionId])
+where
+    C: NotionContent + ?Sized,
+{
+    assert!(
+        !ids.is_empty(),
+        "check_notion_content_laws needs IDs to retrieve"
+    );
+    // The first result of each retrieval, across all cases
+    let first: RefCell<HashMap<ContentOp, Result<Retrieved, FetchError>>> = RefCell::default();
+    let first = &first;
+    run("NotionContent", content_ops(ids.len()), |ops| async move {
+        for op in ops {
+            let id = &ids[op.id_index()];
+            let result = retrieve(content, op, id).await;
+            if let Ok(retrieved) = &result {
+                check_retrieved(id, retrieved)?;
+            }
+            let mut first = first.borrow_mut();
+            match first.get(&op) {
+                None => {
+                    first.insert(op, result);
+                }
+                Some(Err(e)) if e.is_retryable() => {
+                    first.insert(op, result);
+                }
+                Some(_) if matches!(&result, Err(e) if e.is_retryable()) => {}
+                Some(earlier) => {
+                    prop_assert_eq!(earlier, &result, "L1: {:?} of {} changed", op, id)
+                }
+            }
+        }
+        Ok(())
+    })
+    .await;
+}
+
+async fn retrieve<C>(content: &C, op: ContentOp, id: &NotionId) -> Result<Retrieved, FetchError>
+where
+    C: NotionContent + ?Sized,
+{
+    Ok(match op {
+        ContentOp::Page(_) => Retrieved::Page(content.retrieve_page(id).await?),
+        ContentOp::Database(_) => Retrieved::Database(content.retrieve_database(id).await?),
+        ContentOp::Block(_) => Retrieved::Block(content.retrieve_block(id).await?),
+        ContentOp::Children(_) => Retrieved::Blocks(content.retrieve_children(id).await?),
+        ContentOp::Rows(_) => Retrieved::Rows(content.query_rows(id).await?),
+    })
+}
+
+/// Checks the laws L2–L4 on what retrieving `id` returned.
+fn check_retrieved(id: &NotionId, retrieved: &Retrieved) -> Result<(), TestCaseError> {
+    match retrieved {
+        Retrieved::Block(block) => {
+            prop_assert_eq!(
+                &NotionId::from(block.id()),
+                id,
+                "L4: retrieve_block returned another block"
+            );
+        }
+        Retrieved::Blocks(children) => {
+            let mut seen = HashSet::new();
+            for child in children {
+                let child_id = NotionId::from(child.id());
+                prop_assert!(&child_id != id, "L2: {} is its own child", id);
+                prop_assert!(
+                    seen.insert(child_id.clone()),
+                    "L2: child {} of {} listed twice",
+                    child_id,
+                    id
+                );
+            }
+        }
+        Retrieved::Rows(rows) => {
+            for row in rows {
+                if let Some(parent) = &row.parent {
+                    let Parent::Database { database_id } = parent else {
+                        return Err(TestCaseError::fail(format!(
+                            "L3: row {} of {} has a {:?} parent",
+                            row.id.as_str(),
+                            id,
+                            parent
+                        )));
+                    };
+                    prop_assert_eq!(
+                        &NotionId::from(database_id),
+                        id,
+                        "L3: row {} belongs to another database",
+                        row.id.as_str()
+                    );
+                }
+            }
+        }
+        Retrieved::Page(_) | Retrieved::Database(_) => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use parking_lot::Mutex;
+
+    /// Forgets every visit: each one is a first.
+    struct Forgetful;
+
+    #[async_trait]
+    impl VisitTracker for Forgetful {
+        async fn visit(&self, _id: &NotionId) -> Result<bool, super::super::TrackError> {
+            Ok(true)
+        }
+        async fn is_visited(&self, _id: &NotionId) -> bool {
+            false
+        }
+    }
+
+    /// Never reaches zero: `enter` stops at one.
+    struct Bottomless(Mutex<u8>);
+
+    #[async_trait]
+    impl DepthLimiter for Bottomless {
+        async fn depth(&self) -> u8 {
+            *self.0.lock()
+        }
+        async fn can_enter(&self) -> bool {
+            *self.0.lock() > 0
+        }
+        async fn enter(&self) -> Option<u8> {
+            let mut depth = self.0.lock();
+            *depth = depth.saturating_sub(1).max(1);
+            Some(*depth)
+        }
+        async fn exit(&self) {
+            *self.0.lock() += 1;
+        }
+    }
+
+    /// Remembers visits in a spawned task, as implementations that need a
+    /// runtime do.
+    #[derive(Default)]
+    struct Spawning(std::sync::Arc<Mutex<HashSet<NotionId>>>);
+
+    #[async_trait]
+    impl VisitTracker for Spawning {
+        async fn visit(&self, id: &NotionId) -> Result<bool, super::super::TrackError> {
+            let (visited, id) = (self.0.clone(), id.clone());
+            Ok(tokio::spawn(async move { visited.lock().insert(id) })
+                .await
+                .expect("visit task"))
+        }
+        async fn is_visited(&self, id: &NotionId) -> bool {
+            let (visited, id) = (self.0.clone(), id.clone());
+            tokio::spawn(async move { visited.lock().contains(&id) })
+                .await
+                .expect("is_visited task")
+        }
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "VisitTracker laws violated")]
+    async fn test_forgetful_tracker_breaks_idempotency() {
+        check_visit_tracker_laws(|| Forgetful).await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "DepthLimiter laws violated")]
+    async fn test_bottomless_limiter_breaks_zero_termination() {
+        check_depth_limiter_laws(|depth| Bottomless(Mutex::new(depth))).await;
+    }
+
+    #[tokio::test]
+    async fn test_spawning_tracker_is_checked_on_a_current_thread_runtime() {
+        check_visit_tracker_laws(Spawning::default).await;
+    }
+}
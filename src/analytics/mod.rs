@@ -3,8 +3,17 @@
 
 #![allow(dead_code)]
 
-use crate::model::{Block, BlockVisitor, Database, NotionObject, Page};
-use crate::types::BlockId;
+mod schema;
+
+#[allow(unused_imports)] // Library API
+pub use self::schema::{ExpectedSchema, SchemaDrift};
+
+use crate::constants::CHARS_PER_TOKEN_ESTIMATE;
+use crate::model::ChildDatabaseContent;
+use crate::model::{Block, BlockVisitor, Database, NotionObject, Page, UnsupportedBlock};
+use crate::types::{BlockId, NotionId};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
 
 /// Quick statistics for user-facing progress messages.
 ///
@@ -28,6 +37,94 @@ pub struct ContentMeasurement {
     pub embedded_databases: usize,
     pub pages_with_content: usize,
     pub total_pages: usize,
+    /// Block count per Notion block type (`paragraph`, `to_do`, ...)
+    pub blocks_by_type: BTreeMap<&'static str, usize>,
+    /// Block count per nesting depth; the object itself is depth 0
+    pub depth_histogram: BTreeMap<usize, usize>,
+    /// Every fetched database, root and embedded, in document order
+    pub database_sizes: Vec<DatabaseSize>,
+}
+
+impl ContentMeasurement {
+    /// The `n` databases with the most rows, largest first.
+    pub fn largest_databases(&self, n: usize) -> Vec<&DatabaseSize> {
+        let mut sizes: Vec<_> = self.database_sizes.iter().collect();
+        sizes.sort_by_key(|db| std::cmp::Reverse(db.rows));
+        sizes.truncate(n);
+        sizes
+    }
+}
+
+/// The row count of one fetched database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseSize {
+    pub title: String,
+    pub rows: usize,
+}
+
+/// The structural report printed by `notion2prompt stats`.
+#[derive(Debug, Clone)]
+pub struct StatsReport {
+    pub title: String,
+    pub measurement: ContentMeasurement,
+    /// Estimated prompt size, see [`estimate_tokens`]
+    pub estimated_tokens: usize,
+}
+
+/// How many databases `StatsReport` lists.
+const REPORTED_DATABASES: usize = 5;
+
+impl fmt::Display for StatsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let m = &self.measurement;
+        writeln!(f, "# {}", self.title)?;
+        writeln!(f)?;
+        writeln!(f, "Objects:           {}", m.total_objects)?;
+        writeln!(f, "Pages:             {}", m.pages)?;
+        writeln!(f, "Databases:         {}", m.databases)?;
+        writeln!(f, "Blocks:            {}", m.blocks)?;
+        writeln!(f, "Deepest nesting:   {}", m.deepest_nesting)?;
+        writeln!(f, "Estimated tokens:  ~{}", self.estimated_tokens)?;
+
+        if !m.blocks_by_type.is_empty() {
+            let mut by_count: Vec<_> = m.blocks_by_type.iter().collect();
+            by_count.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            writeln!(f)?;
+            writeln!(f, "## Blocks by type")?;
+            writeln!(f)?;
+            for (block_type, count) in by_count {
+                writeln!(f, "{:<20} {}", block_type, count)?;
+            }
+        }
+
+        if !m.depth_histogram.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "## Blocks by depth")?;
+            writeln!(f)?;
+            for (depth, count) in &m.depth_histogram {
+                writeln!(f, "{:<20} {}", depth, count)?;
+            }
+        }
+
+        let largest = m.largest_databases(REPORTED_DATABASES);
+        if !largest.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "## Largest databases")?;
+            writeln!(f)?;
+            for db in largest {
+                writeln!(f, "{} rows  {}", db.rows, db.title)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Estimates how many model tokens `text` takes up.
+///
+/// A character-count heuristic, not a tokenizer: good enough to tell a
+/// 2k-token page from a 200k-token one before running the full pipeline.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN_ESTIMATE)
 }
 
 /// Counts databases embedded within block trees (child_database blocks).
@@ -82,7 +179,7 @@ fn count_page_items(page: &Page) -> usize {
 }
 
 fn count_database_items(db: &Database) -> usize {
-    1 + db.pages.iter().map(count_page_items).sum::<usize>()
+    1 + db.rows().map(count_page_items).sum::<usize>()
 }
 
 /// Counts blocks recursively.
@@ -99,8 +196,7 @@ pub fn deepest_nesting_level(object: &NotionObject) -> usize {
     match object {
         NotionObject::Page(page) => blocks_max_depth(&page.blocks, 1),
         NotionObject::Database(db) => db
-            .pages
-            .iter()
+            .rows()
             .map(|page| blocks_max_depth(&page.blocks, 1))
             .max()
             .unwrap_or(0),
@@ -135,6 +231,86 @@ pub fn embedded_database_count(blocks: &[Block]) -> usize {
     counter.count
 }
 
+/// Collects the IDs of every page and database contained in a Notion object tree.
+///
+/// Child page and child database blocks share their ID with the object they
+/// represent, so they count as contained even when their content was not fetched.
+pub fn contained_object_ids(object: &NotionObject) -> HashSet<NotionId> {
+    let mut ids = HashSet::new();
+    collect_object_ids(object, &mut ids);
+    ids
+}
+
+fn collect_object_ids(object: &NotionObject, ids: &mut HashSet<NotionId>) {
+    ids.insert(object.id());
+    match object {
+        NotionObject::Page(page) => collect_block_object_ids(&page.blocks, ids),
+        NotionObject::Database(db) => collect_database_ids(db, ids),
+        NotionObject::Block(block) => collect_block_object_ids(std::slice::from_ref(block), ids),
+    }
+}
+
+fn collect_database_ids(db: &Database, ids: &mut HashSet<NotionId>) {
+    ids.insert(NotionId::from(&db.id));
+    for page in db.rows() {
+        ids.insert(NotionId::from(&page.id));
+        collect_block_object_ids(&page.blocks, ids);
+    }
+}
+
+fn collect_block_object_ids(blocks: &[Block], ids: &mut HashSet<NotionId>) {
+    for block in blocks {
+        match block {
+            Block::ChildPage(_) => {
+                ids.insert(NotionId::from(block.id()));
+            }
+            Block::ChildDatabase(child) => {
+                ids.insert(NotionId::from(block.id()));
+                if let ChildDatabaseContent::Fetched(db) = &child.content {
+                    collect_database_ids(db, ids);
+                }
+            }
+            _ => {}
+        }
+        collect_block_object_ids(block.children(), ids);
+    }
+}
+
+/// Collects every unsupported block in a Notion object tree, in document
+/// order. Blocks left out for exceeding `--max-block-bytes` are not included.
+pub fn unsupported_blocks(object: &NotionObject) -> Vec<&UnsupportedBlock> {
+    let mut found = Vec::new();
+    match object {
+        NotionObject::Page(page) => collect_unsupported(&page.blocks, &mut found),
+        NotionObject::Database(db) => collect_database_unsupported(db, &mut found),
+        NotionObject::Block(block) => collect_unsupported(std::slice::from_ref(block), &mut found),
+    }
+    found
+}
+
+fn collect_database_unsupported<'a>(db: &'a Database, found: &mut Vec<&'a UnsupportedBlock>) {
+    for page in db.rows() {
+        collect_unsupported(&page.blocks, found);
+    }
+}
+
+fn collect_unsupported<'a>(blocks: &'a [Block], found: &mut Vec<&'a UnsupportedBlock>) {
+    for block in blocks {
+        match block {
+            Block::Unsupported(unsupported) if unsupported.skipped_bytes.is_none() => {
+                found.push(unsupported)
+            }
+            Block::ChildDatabase(child) => {
+                if let ChildDatabaseContent::Fetched(db) = &child.content {
+                    collect_database_unsupported(db, found);
+                }
+            }
+            _ => {}
+        }
+        collect_unsupported(block.children(), found);
+    }
+}
+
 fn walk_object(measurement: &mut ContentMeasurement, obj: &NotionObject, depth: usize) {
     measurement.total_objects += 1;
     measurement.deepest_nesting = measurement.deepest_nesting.max(depth);
@@ -144,39 +320,58 @@ fn walk_object(measurement: &mut ContentMeasurement, obj: &NotionObject, depth:
             measurement.pages += 1;
             walk_blocks(measurement, &page.blocks, depth + 1);
         }
-        NotionObject::Database(db) => {
-            measurement.databases += 1;
-            measurement.total_pages += db.pages.len();
+        NotionObject::Database(db) => walk_database(measurement, db, depth),
+        NotionObject::Block(block) => {
+            walk_blocks(measurement, std::slice::from_ref(block), depth + 1);
+        }
+    }
+}
 
-            for page in &db.pages {
-                measurement.pages += 1;
-                measurement.total_objects += 1;
+fn walk_database(measurement: &mut ContentMeasurement, db: &Database, depth: usize) {
+    measurement.databases += 1;
+    measurement.total_pages += db.row_count();
+    measurement.database_sizes.push(DatabaseSize {
+        title: db.title().as_plain_text(),
+        rows: db.row_count(),
+    });
 
-                if !page.blocks.is_empty() {
-                    measurement.pages_with_content += 1;
-                }
+    for page in db.rows() {
+        measurement.pages += 1;
+        measurement.total_objects += 1;
 
-                walk_blocks(measurement, &page.blocks, depth + 2);
-            }
-        }
-        NotionObject::Block(block) => {
-            measurement.blocks += 1;
-            walk_blocks(measurement, std::slice::from_ref(block), depth + 1);
+        if !page.blocks.is_empty() {
+            measurement.pages_with_content += 1;
         }
+
+        walk_blocks(measurement, &page.blocks, depth + 2);
     }
 }
 
 fn walk_blocks(measurement: &mut ContentMeasurement, blocks: &[Block], depth: usize) {
+    if blocks.is_empty() {
+        return;
+    }
     measurement.deepest_nesting = measurement.deepest_nesting.max(depth);
 
     for block in blocks {
         measurement.total_objects += 1;
         measurement.blocks += 1;
+        *measurement
+            .blocks_by_type
+            .entry(block.block_type())
+            .or_default() += 1;
+        *measurement.depth_histogram.entry(depth).or_default() += 1;
 
         let mut counter = EmbeddedDatabaseCounter::new();
         block.accept(&mut counter);
         measurement.embedded_databases += counter.count;
 
+        if let Block::ChildDatabase(child) = block {
+            if let ChildDatabaseContent::Fetched(db) = &child.content {
+                walk_database(measurement, db, depth);
+            }
+        }
+
         walk_blocks(measurement, block.children(), depth + 1);
     }
 }
@@ -184,20 +379,17 @@ fn walk_blocks(measurement: &mut ContentMeasurement, blocks: &[Block], depth: us
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::{Page, PageTitle};
+    use crate::model::testing::test_page;
+    use crate::model::Page;
     use crate::types::PageId;
 
     #[test]
     fn test_empty_page_stats() {
-        let page = Page {
-            id: PageId::parse("12345678123456781234567812345678").unwrap(),
-            title: PageTitle::new("Test Page"),
-            url: "https://notion.so/test".to_string(),
-            blocks: vec![],
-            properties: Default::default(),
-            parent: None,
-            archived: false,
-        };
+        let page = Page::new(
+            PageId::parse("12345678123456781234567812345678").unwrap(),
+            "Test Page",
+        )
+        .with_url("https://notion.so/test");
 
         let obj = NotionObject::Page(page);
         let stats = measure_content(&obj);
@@ -205,4 +397,77 @@ mod tests {
         assert_eq!(stats.total_objects, 1);
         assert_eq!(stats.deepest_nesting, 1);
     }
+
+    #[test]
+    fn test_contained_object_ids_include_rows_and_child_pages() {
+        use crate::model::{ChildPageBlock, Database};
+        use crate::types::{BlockId, DatabaseId};
+
+        let row_id = PageId::parse("22222222222222222222222222222222").unwrap();
+        let child_id = BlockId::parse("33333333333333333333333333333333").unwrap();
+        let row =
+            Page::new(row_id.clone(), "Row").with_blocks(vec![Block::ChildPage(ChildPageBlock {
+                common: crate::model::BlockCommon {
+                    id: child_id.clone(),
+                    children: vec![],
+                    has_children: false,
+                    archived: false,
+                    last_edited_time: None,
+                    unfetched_children: None,
+                },
+                title: "Child".to_string(),
+                included_elsewhere: None,
+            })]);
+        let db = Database::new(
+            DatabaseId::parse("11111111111111111111111111111111").unwrap(),
+            "",
+        )
+        .with_pages(vec![row]);
+
+        let ids = contained_object_ids(&NotionObject::Database(db));
+        assert_eq!(ids.len(), 3);
+        assert!(ids.contains(&NotionId::from(&row_id)));
+        assert!(ids.contains(&NotionId::from(&child_id)));
+    }
+
+    #[test]
+    fn test_detailed_measurement_counts_blocks_by_type_and_depth() {
+        use crate::model::{BlockCommon, ParagraphBlock, TextBlockContent};
+        use crate::types::Color;
+
+        let paragraph = |children: Vec<Block>| {
+            Block::Paragraph(ParagraphBlock {
+                common: BlockCommon {
+                    id: BlockId::new_v4(),
+                    has_children: !children.is_empty(),
+                    children,
+                    archived: false,
+                    last_edited_time: None,
+                    unfetched_children: None,
+                },
+                content: TextBlockContent {
+                    rich_text: vec![],
+                    color: Color::Default,
+                },
+            })
+        };
+        let page = test_page("Nested")
+            .with_blocks(vec![paragraph(vec![paragraph(vec![])]), paragraph(vec![])]);
+
+        let measurement = measure_content_detailed(&NotionObject::Page(page));
+        assert_eq!(measurement.blocks, 3);
+        assert_eq!(measurement.blocks_by_type.get("paragraph"), Some(&3));
+        assert_eq!(
+            measurement.depth_histogram.into_iter().collect::<Vec<_>>(),
+            vec![(1, 2), (2, 1)]
+        );
+        assert_eq!(measurement.deepest_nesting, 2);
+    }
+
+    #[test]
+    fn test_token_estimate_rounds_up() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abc"), 1);
+        assert_eq!(estimate_tokens("abcdefghi"), 3);
+    }
 }
@@ -0,0 +1,265 @@
+// src/analytics/schema.rs
+//! `--expect-schema`: checks a fetched database against the properties it
+//! is expected to have.
+//!
+//! Teams that read configuration from a Notion database want to hear about
+//! a renamed column before their prompt quietly loses it. The schema file
+//! names each expected property with its Notion type:
+//!
+//! ```json
+//! {
+//!   "properties": {
+//!     "Name": "title",
+//!     "Status": "status",
+//!     "Due": "date"
+//!   },
+//!   "allow_extra": false
+//! }
+//! ```
+//!
+//! A property that is missing or has another type is drift. Properties the
+//! file does not name are drift only with `"allow_extra": false`; by
+//! default new columns are fine.
+
+use crate::config::Language;
+use crate::error::AppError;
+use crate::formatting::Messages;
+use crate::model::{Database, DatabaseProperty};
+use crate::types::PropertyName;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+/// Property types as the Notion API names them.
+const PROPERTY_TYPES: &[&str] = &[
+    "title",
+    "rich_text",
+    "number",
+    "select",
+    "multi_select",
+    "date",
+    "formula",
+    "relation",
+    "rollup",
+    "people",
+    "files",
+    "checkbox",
+    "url",
+    "email",
+    "phone_number",
+    "created_time",
+    "created_by",
+    "last_edited_time",
+    "last_edited_by",
+    "status",
+];
+
+/// The properties a database is expected to have, from a schema file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedSchema {
+    /// Property name → Notion type
+    properties: BTreeMap<String, String>,
+    /// Whether properties the file does not name are fine
+    #[serde(default = "allow_extra_default")]
+    allow_extra: bool,
+}
+
+fn allow_extra_default() -> bool {
+    true
+}
+
+/// One way a database differs from its expected schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaDrift {
+    /// An expected property is not there, e.g. after a rename
+    Missing { property: String, expected: String },
+    /// A property has another type than expected
+    WrongType {
+        property: String,
+        expected: String,
+        found: String,
+    },
+    /// A property the schema does not name, with `"allow_extra": false`
+    Unexpected { property: String, found: String },
+}
+
+impl SchemaDrift {
+    /// The difference, worded in `language`.
+    pub fn describe(&self, language: Language) -> String {
+        let messages = Messages::for_language(language);
+        match self {
+            Self::Missing { property, expected } => {
+                format!(
+                    "{} '{}' ({})",
+                    messages.missing_property, property, expected
+                )
+            }
+            Self::WrongType {
+                property,
+                expected,
+                found,
+            } => format!(
+                "{} '{}' {} {}, {} {}",
+                messages.property, property, messages.is, found, messages.expected, expected
+            ),
+            Self::Unexpected { property, found } => {
+                format!(
+                    "{} '{}' ({})",
+                    messages.unexpected_property, property, found
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for SchemaDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.describe(Language::En))
+    }
+}
+
+impl ExpectedSchema {
+    /// Reads and checks a schema file.
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|message| {
+            AppError::Validation(format!("schema file {}: {}", path.display(), message))
+        })
+    }
+
+    /// Parses a schema written as JSON, rejecting unknown property types.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let schema: Self = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        for (property, property_type) in &schema.properties {
+            if !PROPERTY_TYPES.contains(&property_type.as_str()) {
+                return Err(format!(
+                    "property '{}' has unknown type '{}', expected one of: {}",
+                    property,
+                    property_type,
+                    PROPERTY_TYPES.join(", ")
+                ));
+            }
+        }
+        Ok(schema)
+    }
+
+    /// How `db` differs from the schema, missing properties first. Each
+    /// data source of a multi-source database is checked on its own.
+    pub fn check(&self, db: &Database) -> Vec<SchemaDrift> {
+        if db.data_sources.is_empty() {
+            return self.check_properties(&db.properties);
+        }
+        db.data_sources
+            .iter()
+            .flat_map(|source| self.check_properties(&source.properties))
+            .collect()
+    }
+
+    fn check_properties(
+        &self,
+        properties: &HashMap<PropertyName, DatabaseProperty>,
+    ) -> Vec<SchemaDrift> {
+        let found: BTreeMap<&str, String> = properties
+            .iter()
+            .map(|(name, property)| (name.as_str(), property.property_type.to_string()))
+            .collect();
+
+        let mut drift = Vec::new();
+        for (property, expected) in &self.properties {
+            match found.get(property.as_str()) {
+                None => drift.push(SchemaDrift::Missing {
+                    property: property.clone(),
+                    expected: expected.clone(),
+                }),
+                Some(found) if found != expected => drift.push(SchemaDrift::WrongType {
+                    property: property.clone(),
+                    expected: expected.clone(),
+                    found: found.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        if !self.allow_extra {
+            for (property, found) in &found {
+                if !self.properties.contains_key(*property) {
+                    drift.push(SchemaDrift::Unexpected {
+                        property: property.to_string(),
+                        found: found.clone(),
+                    });
+                }
+            }
+        }
+        drift
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::testing::test_database;
+    use crate::model::DatabasePropertyType;
+
+    fn database(properties: &[(&str, DatabasePropertyType)]) -> Database {
+        test_database("").with_properties(
+            properties
+                .iter()
+                .map(|(name, property_type)| {
+                    (
+                        PropertyName::new(*name),
+                        DatabaseProperty {
+                            id: PropertyName::new(*name),
+                            name: PropertyName::new(*name),
+                            property_type: property_type.clone(),
+                        },
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_renamed_and_retyped_columns_are_drift() {
+        let schema = ExpectedSchema::parse(
+            r#"{"properties": {"Name": "title", "Owner": "people", "Due": "date"}}"#,
+        )
+        .unwrap();
+        let db = database(&[
+            ("Name", DatabasePropertyType::Title),
+            ("Assignee", DatabasePropertyType::People),
+            ("Due", DatabasePropertyType::RichText),
+        ]);
+
+        let drift: Vec<String> = schema.check(&db).iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            drift,
+            [
+                "property 'Due' is rich_text, expected date",
+                "missing property 'Owner' (people)",
+            ]
+        );
+
+        let strict =
+            ExpectedSchema::parse(r#"{"properties": {"Name": "title"}, "allow_extra": false}"#)
+                .unwrap();
+        let drift: Vec<String> = strict.check(&db).iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            drift,
+            [
+                "unexpected property 'Assignee' (people)",
+                "unexpected property 'Due' (rich_text)",
+            ]
+        );
+        assert_eq!(
+            strict.check(&db)[0].describe(Language::De),
+            "unerwartete Eigenschaft 'Assignee' (people)"
+        );
+    }
+
+    #[test]
+    fn test_unknown_types_are_rejected() {
+        let error = ExpectedSchema::parse(r#"{"properties": {"Due": "datetime"}}"#).unwrap_err();
+        assert!(error.contains("unknown type 'datetime'"), "{}", error);
+    }
+}
@@ -4,17 +4,38 @@
 //! Caches raw JSON responses keyed by endpoint, with TTL-based expiry.
 //! On cache hit, the cached JSON is re-parsed through the same parsers
 //! used for live API responses — the domain model is never serialized.
-
-use super::client::{extract_response_text, ApiResponse, NotionHttpClient};
+//!
+//! With `--cache-scope run` a client gets its own key namespace in a
+//! temporary directory that is deleted when the client is dropped, so
+//! concurrent CI jobs and tests never see each other's responses. Runs
+//! sharing the cache never see a half-written entry: each entry is written
+//! to a temporary file and renamed into place, so the last writer wins.
+//!
+//! Within a run, parallel workers often need the same response at once.
+//! Only one of them fetches a missing response; the others wait and read
+//! what it stored. A response that has just expired is refreshed by one
+//! worker while the others keep serving the stale copy for up to
+//! [`CACHE_STALE_WHILE_REVALIDATE_SECS`], so an expiry never turns into a
+//! burst of identical API calls.
+
+use super::client::{ApiResponse, NotionHttpClient, DATA_SOURCES_VERSION, NOTION_VERSION};
 use super::parser;
-use crate::constants::NOTION_API_PAGE_SIZE;
+use super::simple_pagination::{fetch_all_pages_resumable, PageLimits, Pages};
+use super::types::{
+    DataSourceRef, DataSourceRows, DatabaseRows, QueriedRow, ResumablePagination, TransferUsage,
+};
+use crate::clock::{Clock, SystemClock};
+use crate::config::CacheScope;
+use crate::constants::CACHE_STALE_WHILE_REVALIDATE_SECS;
 use crate::error::AppError;
-use crate::model::{Block, Database, Page};
+use crate::model::{Block, Database, NotionObject, Page};
 use crate::types::NotionId;
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedMutexGuard};
 
 // ---------------------------------------------------------------------------
 // Disk cache
@@ -27,6 +48,9 @@ use std::time::{SystemTime, UNIX_EPOCH};
 pub struct DiskCache {
     cache_dir: PathBuf,
     ttl_secs: u64,
+    clock: Arc<dyn Clock>,
+    /// Whether `cache_dir` belongs to this cache alone and is deleted on drop
+    remove_on_drop: bool,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -40,17 +64,36 @@ impl DiskCache {
     ///
     /// On creation, expired entries are purged to prevent unbounded disk growth.
     pub async fn new(ttl_secs: u64) -> Result<Self, std::io::Error> {
-        let cache_dir = Self::default_cache_dir();
+        Self::with_clock(Self::default_cache_dir(), ttl_secs, Arc::new(SystemClock)).await
+    }
+
+    /// Creates an empty cache in a fresh temporary directory that is deleted on drop.
+    pub async fn ephemeral(run_id: &str, ttl_secs: u64) -> Result<Self, std::io::Error> {
+        let dir = std::env::temp_dir().join(format!("notion2prompt-run-{}", run_id));
+        let mut cache = Self::with_clock(dir, ttl_secs, Arc::new(SystemClock)).await?;
+        cache.remove_on_drop = true;
+        Ok(cache)
+    }
+
+    /// Creates a cache in `cache_dir` that reads the time from `clock`.
+    pub async fn with_clock(
+        cache_dir: PathBuf,
+        ttl_secs: u64,
+        clock: Arc<dyn Clock>,
+    ) -> Result<Self, std::io::Error> {
         tokio::fs::create_dir_all(&cache_dir).await?;
         let cache = Self {
             cache_dir,
             ttl_secs,
+            clock,
+            remove_on_drop: false,
         };
         cache.purge_expired().await;
         Ok(cache)
     }
 
-    fn default_cache_dir() -> PathBuf {
+    /// `$XDG_CACHE_HOME/notion2prompt`, or `~/.cache/notion2prompt`.
+    pub(crate) fn default_cache_dir() -> PathBuf {
         std::env::var("XDG_CACHE_HOME")
             .map(PathBuf::from)
             .unwrap_or_else(|_| {
@@ -62,41 +105,73 @@ impl DiskCache {
 
     /// Returns cached data if the entry exists and has not expired.
     pub async fn get(&self, key: &str) -> Option<String> {
+        match self.lookup(key).await? {
+            Cached::Fresh(data) => Some(data),
+            Cached::Stale(_) => None,
+        }
+    }
+
+    /// Whether an entry is stored under `key`, however old.
+    async fn contains(&self, key: &str) -> bool {
+        tokio::fs::try_exists(self.key_to_path(key))
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Returns cached data that is fresh, or expired for no longer than the
+    /// stale window. Older entries are removed.
+    async fn lookup(&self, key: &str) -> Option<Cached> {
         let path = self.key_to_path(key);
         let content = tokio::fs::read_to_string(&path).await.ok()?;
         let entry: CacheEntry = serde_json::from_str(&content).ok()?;
-        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
-        if now.saturating_sub(entry.cached_at) > self.ttl_secs {
+        let age = self.clock.unix_secs().saturating_sub(entry.cached_at);
+        if age <= self.ttl_secs {
+            Some(Cached::Fresh(entry.data))
+        } else if age <= self.stale_ttl_secs() {
+            Some(Cached::Stale(entry.data))
+        } else {
             let _ = tokio::fs::remove_file(&path).await;
-            return None;
+            None
         }
-        Some(entry.data)
+    }
+
+    /// How old an entry may be before it is no longer served even stale.
+    fn stale_ttl_secs(&self) -> u64 {
+        self.ttl_secs
+            .saturating_add(CACHE_STALE_WHILE_REVALIDATE_SECS)
     }
 
     /// Stores data in the cache. Errors are silently ignored (cache is best-effort).
+    ///
+    /// The entry is written to a temporary file and renamed over the old
+    /// one, so a run reading the shared cache never sees a partial entry.
     pub async fn set(&self, key: &str, data: &str) {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        let now = self.clock.unix_secs();
         let entry = CacheEntry {
             data: data.to_string(),
             cached_at: now,
         };
-        if let Ok(json) = serde_json::to_string(&entry) {
-            let _ = tokio::fs::write(self.key_to_path(key), json).await;
+        let Ok(json) = serde_json::to_string(&entry) else {
+            return;
+        };
+        let path = self.key_to_path(key);
+        let temp = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4().simple()));
+        let written = match tokio::fs::write(&temp, json).await {
+            Ok(()) => tokio::fs::rename(&temp, &path).await,
+            Err(e) => Err(e),
+        };
+        if written.is_err() {
+            let _ = tokio::fs::remove_file(&temp).await;
         }
     }
 
     /// Removes all expired cache entries from disk.
     ///
     /// Called automatically on construction to prevent unbounded disk growth.
+    /// Entries still inside the stale window are kept.
     /// Errors are silently ignored — a broken purge never blocks operation.
     async fn purge_expired(&self) {
-        let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
-            Ok(d) => d.as_secs(),
-            Err(_) => return,
-        };
+        let now = self.clock.unix_secs();
 
         let mut dir = match tokio::fs::read_dir(&self.cache_dir).await {
             Ok(d) => d,
@@ -110,7 +185,7 @@ impl DiskCache {
             }
             if let Ok(content) = tokio::fs::read_to_string(&path).await {
                 if let Ok(cached) = serde_json::from_str::<CacheEntry>(&content) {
-                    if now.saturating_sub(cached.cached_at) > self.ttl_secs {
+                    if now.saturating_sub(cached.cached_at) > self.stale_ttl_secs() {
                         let _ = tokio::fs::remove_file(&path).await;
                     }
                 }
@@ -126,31 +201,203 @@ impl DiskCache {
     }
 }
 
+/// A cached response and whether it has expired.
+#[derive(Debug, PartialEq, Eq)]
+enum Cached {
+    Fresh(String),
+    /// Past its TTL, but inside the stale window
+    Stale(String),
+}
+
+impl Drop for DiskCache {
+    fn drop(&mut self) {
+        if self.remove_on_drop {
+            let _ = std::fs::remove_dir_all(&self.cache_dir);
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Cached Notion client
 // ---------------------------------------------------------------------------
 
+/// Which side of the cache a [`CachedNotionClient`] uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    /// Serve cached responses, fetching and storing the rest
+    #[default]
+    ReadWrite,
+    /// Fetch everything, storing the fresh responses over the cached ones
+    Refresh,
+    /// Serve only cached responses; a response that is not cached is an error
+    Stored,
+}
+
+/// What the cache gives a worker asking for a response.
+enum Lookup {
+    /// A response to serve, possibly stale while another worker refreshes it
+    Hit(String),
+    /// Nothing to serve: the worker fetches the response, holding the key's
+    /// lock, when it has one, until the response is stored
+    Miss(Option<Fetching>),
+}
+
+/// One lock per cache key that missed or went stale.
+type InFlight = parking_lot::Mutex<HashMap<String, Arc<Mutex<()>>>>;
+
+/// The lock on a cache key, held by the worker fetching its response.
+/// Dropping it forgets the key once no other worker is waiting on it.
+struct Fetching {
+    key: String,
+    guard: Option<OwnedMutexGuard<()>>,
+    in_flight: Arc<InFlight>,
+}
+
+impl Drop for Fetching {
+    fn drop(&mut self) {
+        // The guard holds a reference to the lock; release it first
+        drop(self.guard.take());
+        let mut in_flight = self.in_flight.lock();
+        if in_flight
+            .get(&self.key)
+            .is_some_and(|lock| Arc::strong_count(lock) == 1)
+        {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
 /// A [`NotionRepository`](super::NotionRepository) implementation that caches
 /// raw API JSON responses to disk. Cache hits re-parse through the standard
 /// parsers so the domain model is never serialized/deserialized directly.
 pub struct CachedNotionClient {
     inner: NotionHttpClient,
     cache: DiskCache,
+    /// Prefix of every cache key; empty for the shared scope
+    namespace: String,
+    mode: CacheMode,
+    /// Locks of the keys being fetched, held by the worker fetching each
+    in_flight: Arc<InFlight>,
 }
 
 impl CachedNotionClient {
     /// Wraps an existing HTTP client with a disk cache.
     ///
     /// `ttl_secs` controls how long cached responses are considered valid.
-    /// Expired entries are purged on construction.
-    pub async fn new(inner: NotionHttpClient, ttl_secs: u64) -> Result<Self, AppError> {
-        let cache = DiskCache::new(ttl_secs)
-            .await
-            .map_err(|e| AppError::InternalError {
-                message: format!("Failed to initialize disk cache: {}", e),
+    /// Expired entries are purged on construction. `scope` decides whether
+    /// responses are shared with other runs or private to this client.
+    pub async fn new(
+        inner: NotionHttpClient,
+        ttl_secs: u64,
+        scope: CacheScope,
+    ) -> Result<Self, AppError> {
+        let (cache, namespace) = match scope {
+            CacheScope::Shared => (DiskCache::new(ttl_secs).await, String::new()),
+            CacheScope::Run => {
+                let run_id = uuid::Uuid::new_v4().simple().to_string();
+                let cache = DiskCache::ephemeral(&run_id, ttl_secs).await;
+                (cache, format!("run_{}/", run_id))
+            }
+        };
+        let cache = cache.map_err(|e| AppError::InternalError {
+            message: format!("Failed to initialize disk cache: {}", e),
+            source: None,
+        })?;
+        Ok(Self {
+            inner,
+            cache,
+            namespace,
+            mode: CacheMode::default(),
+            in_flight: Arc::default(),
+        })
+    }
+
+    /// Opens the shared cache to read the responses an earlier run stored,
+    /// however old they are. Nothing is fetched or purged.
+    pub async fn stored(inner: NotionHttpClient) -> Result<Self, AppError> {
+        let client = Self::new(inner, u64::MAX, CacheScope::Shared).await?;
+        Ok(client.with_mode(CacheMode::Stored))
+    }
+
+    /// Sets which side of the cache the client uses.
+    pub fn with_mode(mut self, mode: CacheMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// The cached response under `cache_key`, unless the mode skips cached
+    /// responses.
+    ///
+    /// Only one worker at a time is sent to fetch a response. On a miss,
+    /// the others wait for its lock and then read what it stored; on a
+    /// stale hit, they serve the stale response without waiting.
+    async fn cached(&self, cache_key: &str) -> Lookup {
+        match self.mode {
+            CacheMode::Refresh => return Lookup::Miss(None),
+            CacheMode::Stored => {
+                return match self.cache.get(cache_key).await {
+                    Some(data) => Lookup::Hit(data),
+                    None => Lookup::Miss(None),
+                }
+            }
+            CacheMode::ReadWrite => {}
+        }
+
+        let stale = match self.cache.lookup(cache_key).await {
+            Some(Cached::Fresh(data)) => return Lookup::Hit(data),
+            Some(Cached::Stale(data)) => Some(data),
+            None => None,
+        };
+        let lock = Arc::clone(
+            self.in_flight
+                .lock()
+                .entry(cache_key.to_string())
+                .or_default(),
+        );
+        let guard = match stale {
+            Some(data) => match lock.try_lock_owned() {
+                Ok(guard) => guard,
+                Err(_) => {
+                    log::debug!("Serving stale while refreshing: {}", cache_key);
+                    return Lookup::Hit(data);
+                }
+            },
+            None => lock.lock_owned().await,
+        };
+        let fetching = Fetching {
+            key: cache_key.to_string(),
+            guard: Some(guard),
+            in_flight: Arc::clone(&self.in_flight),
+        };
+        // Another worker may have stored the response since the lookup
+        match self.cache.lookup(cache_key).await {
+            Some(Cached::Fresh(data)) => Lookup::Hit(data),
+            _ => Lookup::Miss(Some(fetching)),
+        }
+    }
+
+    /// Fails a cache miss when only stored responses may be served.
+    fn check_miss(&self, cache_key: &str) -> Result<(), AppError> {
+        log::debug!("Cache miss: {}", cache_key);
+        if self.mode == CacheMode::Stored {
+            return Err(AppError::InternalError {
+                message: format!("No cached response for {}", cache_key),
                 source: None,
-            })?;
-        Ok(Self { inner, cache })
+            });
+        }
+        Ok(())
+    }
+
+    /// Stores a fetched response, counting whether it replaced a cached one.
+    async fn store(&self, cache_key: &str, data: &str) {
+        let replaced = self.cache.contains(cache_key).await;
+        self.cache.set(cache_key, data).await;
+        self.inner.transfer().record_cache_store(replaced);
+    }
+
+    /// Builds the cache key for one kind of response about `id`.
+    fn cache_key(&self, kind: &str, id: &NotionId) -> String {
+        format!("{}{}_{}", self.namespace, kind, id.as_str())
     }
 
     /// Performs a cached GET returning the raw response text.
@@ -158,22 +405,26 @@ impl CachedNotionClient {
         &self,
         cache_key: &str,
         endpoint: &str,
+        version: &'static str,
     ) -> Result<ApiResponse<String>, AppError> {
-        if let Some(cached) = self.cache.get(cache_key).await {
-            log::debug!("Cache hit: {}", cache_key);
-            return Ok(ApiResponse {
-                data: cached,
-                status: reqwest::StatusCode::OK,
-                url: format!("cache://{}", cache_key),
-            });
-        }
+        let _fetching = match self.cached(cache_key).await {
+            Lookup::Hit(cached) => {
+                log::debug!("Cache hit: {}", cache_key);
+                self.inner.transfer().record_cache_hit(cached.len());
+                return Ok(ApiResponse {
+                    data: cached,
+                    status: reqwest::StatusCode::OK,
+                    url: format!("cache://{}", cache_key),
+                });
+            }
+            Lookup::Miss(fetching) => fetching,
+        };
 
-        log::debug!("Cache miss: {}", cache_key);
-        let response = self.inner.get(endpoint).await?;
-        let api_response = extract_response_text(response).await?;
+        self.check_miss(cache_key)?;
+        let api_response = self.inner.get_text_with_version(endpoint, version).await?;
 
         if api_response.status.is_success() {
-            self.cache.set(cache_key, &api_response.data).await;
+            self.store(cache_key, &api_response.data).await;
         }
 
         Ok(api_response)
@@ -187,58 +438,55 @@ impl CachedNotionClient {
         base_endpoint: &str,
     ) -> Result<Vec<Block>, AppError> {
         // Check for cached full result
-        if let Some(cached) = self.cache.get(cache_key).await {
-            log::debug!("Cache hit: {}", cache_key);
-            let raw_pages: Vec<String> =
-                serde_json::from_str(&cached).map_err(|e| AppError::InternalError {
-                    message: format!("Cache deserialization failed for {}: {}", cache_key, e),
-                    source: None,
-                })?;
-            let mut all_blocks = Vec::new();
-            for raw in raw_pages {
-                let api_resp = ApiResponse {
-                    data: raw,
-                    status: reqwest::StatusCode::OK,
-                    url: String::new(),
-                };
-                let parsed = parser::parse_blocks_pagination(api_resp)?;
-                all_blocks.extend(parsed.results);
+        let _fetching = match self.cached(cache_key).await {
+            Lookup::Hit(cached) => {
+                log::debug!("Cache hit: {}", cache_key);
+                let raw_pages: Vec<String> =
+                    serde_json::from_str(&cached).map_err(|e| AppError::InternalError {
+                        message: format!("Cache deserialization failed for {}: {}", cache_key, e),
+                        source: None,
+                    })?;
+                self.inner
+                    .transfer()
+                    .record_cache_hit(raw_pages.iter().map(String::len).sum());
+                let mut all_blocks = Vec::new();
+                for raw in raw_pages {
+                    let api_resp = ApiResponse {
+                        data: raw,
+                        status: reqwest::StatusCode::OK,
+                        url: String::new(),
+                    };
+                    let parsed = parser::parse_blocks_pagination_within(
+                        api_resp,
+                        self.inner.max_block_bytes(),
+                    )?;
+                    all_blocks.extend(parsed.results);
+                }
+                return Ok(all_blocks);
             }
-            return Ok(all_blocks);
-        }
+            Lookup::Miss(fetching) => fetching,
+        };
 
         // Fetch all pages, collecting raw JSON for caching
-        log::debug!("Cache miss: {}", cache_key);
-        let mut all_blocks = Vec::new();
+        self.check_miss(cache_key)?;
+        let mut pages = Pages::new(PageLimits::default());
         let mut raw_responses = Vec::new();
-        let mut cursor: Option<String> = None;
-
-        loop {
-            let endpoint = match &cursor {
-                Some(c) => format!(
-                    "{}?page_size={}&start_cursor={}",
-                    base_endpoint, NOTION_API_PAGE_SIZE, c
-                ),
-                None => format!("{}?page_size={}", base_endpoint, NOTION_API_PAGE_SIZE),
-            };
-
-            let response = self.inner.get(&endpoint).await?;
-            let api_response = extract_response_text(response).await?;
+        while let Some(request) = pages.next_request() {
+            let api_response = self
+                .inner
+                .get_text(&request.get_endpoint(base_endpoint))
+                .await?;
             raw_responses.push(api_response.data.clone());
-
-            let parsed = parser::parse_blocks_pagination(api_response)?;
-            let has_more = parsed.has_more;
-            cursor = parsed.next_cursor;
-            all_blocks.extend(parsed.results);
-
-            if !has_more || cursor.is_none() {
-                break;
-            }
+            pages.add(parser::parse_blocks_pagination_within(
+                api_response,
+                self.inner.max_block_bytes(),
+            )?);
         }
+        let all_blocks = pages.into_items();
 
         // Cache the raw page responses
         if let Ok(cache_data) = serde_json::to_string(&raw_responses) {
-            self.cache.set(cache_key, &cache_data).await;
+            self.store(cache_key, &cache_data).await;
         }
 
         Ok(all_blocks)
@@ -246,107 +494,344 @@ impl CachedNotionClient {
 
     /// Fetches all pages for a POST-paginated endpoint (database queries),
     /// caching the raw response text for each page as a JSON array.
+    ///
+    /// Pagination resumes from a failing cursor; a run that still stops
+    /// early returns its partial rows and is not cached.
     async fn cached_post_paginated_pages(
         &self,
         cache_key: &str,
         endpoint: &str,
-    ) -> Result<Vec<Page>, AppError> {
+        version: &'static str,
+    ) -> Result<ResumablePagination<QueriedRow>, AppError> {
         // Check for cached full result
-        if let Some(cached) = self.cache.get(cache_key).await {
-            log::debug!("Cache hit: {}", cache_key);
-            let raw_pages: Vec<String> =
-                serde_json::from_str(&cached).map_err(|e| AppError::InternalError {
-                    message: format!("Cache deserialization failed for {}: {}", cache_key, e),
-                    source: None,
-                })?;
-            let mut all_pages = Vec::new();
-            for raw in raw_pages {
-                let api_resp = ApiResponse {
-                    data: raw,
-                    status: reqwest::StatusCode::OK,
-                    url: String::new(),
-                };
-                let parsed = parser::parse_pages_pagination(api_resp)?;
-                all_pages.extend(parsed.results);
+        let _fetching = match self.cached(cache_key).await {
+            Lookup::Hit(cached) => {
+                log::debug!("Cache hit: {}", cache_key);
+                let raw_pages: Vec<String> =
+                    serde_json::from_str(&cached).map_err(|e| AppError::InternalError {
+                        message: format!("Cache deserialization failed for {}: {}", cache_key, e),
+                        source: None,
+                    })?;
+                self.inner
+                    .transfer()
+                    .record_cache_hit(raw_pages.iter().map(String::len).sum());
+                let mut all_rows = Vec::new();
+                for raw in raw_pages {
+                    let api_resp = ApiResponse {
+                        data: raw,
+                        status: reqwest::StatusCode::OK,
+                        url: String::new(),
+                    };
+                    let parsed = parser::parse_rows_pagination(api_resp)?;
+                    all_rows.extend(parsed.results);
+                }
+                return Ok(ResumablePagination {
+                    items: all_rows,
+                    interrupted: None,
+                });
             }
-            super::client::sort_pages_by_date_desc(&mut all_pages);
-            return Ok(all_pages);
-        }
+            Lookup::Miss(fetching) => fetching,
+        };
 
         // Fetch all pages with raw response collection
-        log::debug!("Cache miss: {}", cache_key);
-        let mut all_pages = Vec::new();
-        let mut raw_responses = Vec::new();
-        let mut cursor: Option<String> = None;
-        let page_size = u32::try_from(NOTION_API_PAGE_SIZE).unwrap_or(100);
-
-        loop {
-            let mut query = serde_json::json!({
-                "page_size": page_size,
-            });
-            if let Some(ref c) = cursor {
-                query["start_cursor"] = serde_json::json!(c);
-            }
-
-            let response = self.inner.post(endpoint, &query).await?;
-            let api_response = extract_response_text(response).await?;
-            raw_responses.push(api_response.data.clone());
-
-            let parsed = parser::parse_pages_pagination(api_response)?;
-            let has_more = parsed.has_more;
-            cursor = parsed.next_cursor;
-            all_pages.extend(parsed.results);
-
-            if !has_more || cursor.is_none() {
-                break;
+        self.check_miss(cache_key)?;
+        let raw_responses = parking_lot::Mutex::new(Vec::new());
+
+        let result = fetch_all_pages_resumable(
+            |request| {
+                let raw_responses = &raw_responses;
+                async move {
+                    let api_response = self
+                        .inner
+                        .post_text_with_version(endpoint, &request.post_body(), version)
+                        .await?;
+                    let raw = api_response.data.clone();
+                    let parsed = parser::parse_rows_pagination(api_response)?;
+                    raw_responses.lock().push(raw);
+                    Ok(parsed)
+                }
+            },
+            PageLimits::default(),
+            &|error, wait| self.inner.transfer().record_retry(error, wait),
+        )
+        .await?;
+
+        // Cache the raw page responses, but only for a complete result
+        if result.interrupted.is_none() {
+            let raw_responses = raw_responses.into_inner();
+            if let Ok(cache_data) = serde_json::to_string(&raw_responses) {
+                self.store(cache_key, &cache_data).await;
             }
         }
 
-        super::client::sort_pages_by_date_desc(&mut all_pages);
-
-        // Cache the raw page responses
-        if let Ok(cache_data) = serde_json::to_string(&raw_responses) {
-            self.cache.set(cache_key, &cache_data).await;
-        }
-
-        Ok(all_pages)
+        Ok(result)
     }
 }
 
 #[async_trait::async_trait]
 impl super::NotionRepository for CachedNotionClient {
+    fn transfer_usage(&self) -> TransferUsage {
+        self.inner.transfer().usage()
+    }
+
+    fn record_retry(&self, error: &AppError, wait: std::time::Duration) {
+        self.inner.transfer().record_retry(error, wait);
+    }
+
     async fn retrieve_page(&self, id: &NotionId) -> Result<Page, AppError> {
-        let cache_key = format!("page_{}", id.as_str());
+        let cache_key = self.cache_key("page", id);
         let endpoint = format!("pages/{}", id.to_hyphenated());
-        let result = self.cached_get(&cache_key, &endpoint).await?;
+        let result = self
+            .cached_get(&cache_key, &endpoint, NOTION_VERSION)
+            .await?;
         parser::parse_page_response(result)
     }
 
     async fn retrieve_database(&self, id: &NotionId) -> Result<Database, AppError> {
-        let cache_key = format!("db_{}", id.as_str());
+        let cache_key = self.cache_key("db", id);
         let endpoint = format!("databases/{}", id.to_hyphenated());
-        let result = self.cached_get(&cache_key, &endpoint).await?;
+        let result = self
+            .cached_get(&cache_key, &endpoint, NOTION_VERSION)
+            .await?;
         parser::parse_database_response(result)
     }
 
     async fn retrieve_block(&self, id: &NotionId) -> Result<Block, AppError> {
-        let cache_key = format!("block_{}", id.as_str());
+        let cache_key = self.cache_key("block", id);
         let endpoint = format!("blocks/{}", id.to_hyphenated());
-        let result = self.cached_get(&cache_key, &endpoint).await?;
+        let result = self
+            .cached_get(&cache_key, &endpoint, NOTION_VERSION)
+            .await?;
         parser::parse_block_response(result)
     }
 
     async fn retrieve_children(&self, parent: &NotionId) -> Result<Vec<Block>, AppError> {
-        let cache_key = format!("children_{}", parent.as_str());
+        let cache_key = self.cache_key("children", parent);
         let base_endpoint = format!("blocks/{}/children", parent.to_hyphenated());
         self.cached_get_paginated_blocks(&cache_key, &base_endpoint)
             .await
     }
 
-    async fn query_rows(&self, database: &NotionId) -> Result<Vec<Page>, AppError> {
-        let cache_key = format!("rows_{}", database.as_str());
+    async fn query_rows(&self, database: &NotionId) -> Result<DatabaseRows, AppError> {
+        let cache_key = self.cache_key("rows", database);
         let endpoint = format!("databases/{}/query", database.to_hyphenated());
-        self.cached_post_paginated_pages(&cache_key, &endpoint)
+        let result = self
+            .cached_post_paginated_pages(&cache_key, &endpoint, NOTION_VERSION)
+            .await?;
+        Ok(DatabaseRows::from_pagination(
+            database,
+            result.items,
+            result.interrupted,
+        ))
+    }
+
+    async fn list_data_sources(&self, database: &NotionId) -> Result<Vec<DataSourceRef>, AppError> {
+        let cache_key = self.cache_key("sources", database);
+        let endpoint = format!("databases/{}", database.to_hyphenated());
+        let result = self
+            .cached_get(&cache_key, &endpoint, DATA_SOURCES_VERSION)
+            .await?;
+        parser::parse_data_source_list(result)
+    }
+
+    async fn query_data_source(&self, source: &DataSourceRef) -> Result<DataSourceRows, AppError> {
+        let endpoint = format!("data_sources/{}", source.id.to_hyphenated());
+        let schema = self
+            .cached_get(
+                &self.cache_key("source", &source.id),
+                &endpoint,
+                DATA_SOURCES_VERSION,
+            )
+            .await?;
+        let properties = parser::parse_data_source_schema(schema)?;
+
+        let result = self
+            .cached_post_paginated_pages(
+                &self.cache_key("source_rows", &source.id),
+                &format!("{}/query", endpoint),
+                DATA_SOURCES_VERSION,
+            )
+            .await?;
+        Ok(DataSourceRows {
+            properties,
+            rows: DatabaseRows::from_pagination(&source.id, result.items, result.interrupted),
+        })
+    }
+
+    async fn search_shared(&self) -> Result<Vec<NotionObject>, AppError> {
+        // Search results decide what gets exported, so they always come fresh.
+        super::NotionRepository::search_shared(&self.inner).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::time::Duration;
+
+    async fn cache_with(clock: Arc<ManualClock>, ttl_secs: u64) -> (DiskCache, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("n2p_cache_{}", uuid::Uuid::new_v4()));
+        let cache = DiskCache::with_clock(dir.clone(), ttl_secs, clock)
+            .await
+            .unwrap();
+        (cache, dir)
+    }
+
+    #[tokio::test]
+    async fn test_entry_expires_after_ttl() {
+        let clock = Arc::new(ManualClock::at_unix_secs(10_000));
+        let (cache, _dir) = cache_with(clock.clone(), 60).await;
+        cache.set("page_a", "{}").await;
+
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(cache.get("page_a").await.as_deref(), Some("{}"));
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(cache.get("page_a").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_set_replaces_the_entry_without_leaving_temporary_files() {
+        let clock = Arc::new(ManualClock::at_unix_secs(10_000));
+        let (cache, dir) = cache_with(clock, 60).await;
+        cache.set("page_a", "{\"v\":1}").await;
+        cache.set("page_a", "{\"v\":2}").await;
+
+        assert_eq!(cache.get("page_a").await.as_deref(), Some("{\"v\":2}"));
+        let files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files, [cache.key_to_path("page_a")]);
+    }
+
+    #[tokio::test]
+    async fn test_construction_purges_expired_entries() {
+        let clock = Arc::new(ManualClock::at_unix_secs(10_000));
+        let (cache, dir) = cache_with(clock.clone(), 60).await;
+        cache.set("page_a", "{}").await;
+        let path = cache.key_to_path("page_a");
+        assert!(path.exists());
+
+        clock.advance(Duration::from_secs(120));
+        let _reopened = DiskCache::with_clock(dir, 60, clock).await.unwrap();
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_run_scoped_clients_are_isolated_and_cleaned_up() {
+        let http = || NotionHttpClient::new(&crate::types::ApiKey::new_unchecked("secret_test"));
+        let first = CachedNotionClient::new(http().unwrap(), 300, CacheScope::Run)
+            .await
+            .unwrap();
+        let second = CachedNotionClient::new(http().unwrap(), 300, CacheScope::Run)
+            .await
+            .unwrap();
+        let id = NotionId::parse("12345678123412341234123456789abc").unwrap();
+
+        first.cache.set(&first.cache_key("page", &id), "{}").await;
+        assert_eq!(
+            first
+                .cache
+                .get(&first.cache_key("page", &id))
+                .await
+                .as_deref(),
+            Some("{}")
+        );
+        assert_eq!(second.cache.get(&second.cache_key("page", &id)).await, None);
+        assert_ne!(first.cache.cache_dir, second.cache.cache_dir);
+
+        let dir = first.cache.cache_dir.clone();
+        drop(first);
+        assert!(!dir.exists());
+    }
+
+    async fn client_with(clock: Arc<ManualClock>, ttl_secs: u64) -> CachedNotionClient {
+        let http = NotionHttpClient::new(&crate::types::ApiKey::new_unchecked("secret_test"));
+        let mut client = CachedNotionClient::new(http.unwrap(), ttl_secs, CacheScope::Run)
+            .await
+            .unwrap();
+        client.cache = cache_with(clock, ttl_secs).await.0;
+        client
+    }
+
+    #[tokio::test]
+    async fn test_one_worker_refreshes_a_stale_entry_while_others_serve_it() {
+        let clock = Arc::new(ManualClock::at_unix_secs(10_000));
+        let client = client_with(clock.clone(), 60).await;
+        client.cache.set("page_a", "{}").await;
+        clock.advance(Duration::from_secs(61));
+
+        let refreshing = client.cached("page_a").await;
+        assert!(matches!(refreshing, Lookup::Miss(Some(_))));
+        assert!(matches!(client.cached("page_a").await, Lookup::Hit(data) if data == "{}"));
+        drop(refreshing);
+
+        clock.advance(Duration::from_secs(CACHE_STALE_WHILE_REVALIDATE_SECS));
+        assert!(matches!(
+            client.cached("page_a").await,
+            Lookup::Miss(Some(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_workers_wait_for_the_one_fetching_a_missing_entry() {
+        let clock = Arc::new(ManualClock::at_unix_secs(10_000));
+        let client = client_with(clock, 60).await;
+        let Lookup::Miss(Some(fetching)) = client.cached("page_a").await else {
+            panic!("the first worker should fetch");
+        };
+
+        let waiting = client.cached("page_a");
+        let fetch = async {
+            tokio::task::yield_now().await;
+            client.cache.set("page_a", "{}").await;
+            drop(fetching);
+        };
+        let (lookup, ()) = tokio::join!(waiting, fetch);
+        assert!(matches!(lookup, Lookup::Hit(data) if data == "{}"));
+        assert!(client.in_flight.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_a_key_is_forgotten_once_its_response_is_stored() {
+        let clock = Arc::new(ManualClock::at_unix_secs(10_000));
+        let client = client_with(clock, 60).await;
+        let Lookup::Miss(Some(fetching)) = client.cached("page_a").await else {
+            panic!("the first worker should fetch");
+        };
+        assert_eq!(client.in_flight.lock().len(), 1);
+
+        client.store("page_a", "{}").await;
+        drop(fetching);
+        assert!(client.in_flight.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_store_counts_new_and_refreshed_entries() {
+        let clock = Arc::new(ManualClock::at_unix_secs(10_000));
+        let client = client_with(clock, 60).await;
+        client.store("page_a", "{}").await;
+        client.store("page_a", "{\"v\":2}").await;
+        client.store("page_b", "{}").await;
+
+        let usage = client.inner.transfer().usage();
+        assert_eq!((usage.cache_added, usage.cache_refreshed), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn test_stored_mode_serves_only_cached_responses() {
+        let http = NotionHttpClient::new(&crate::types::ApiKey::new_unchecked("secret_test"));
+        let client = CachedNotionClient::new(http.unwrap(), 300, CacheScope::Run)
             .await
+            .unwrap()
+            .with_mode(CacheMode::Stored);
+        client.cache.set("page_a", "{}").await;
+
+        let hit = client.cached_get("page_a", "pages/a", NOTION_VERSION).await;
+        assert_eq!(hit.unwrap().data, "{}");
+        let miss = client.cached_get("page_b", "pages/b", NOTION_VERSION).await;
+        assert!(miss.is_err());
     }
 }
@@ -0,0 +1,431 @@
+// src/api/checkpoint.rs
+//! Checkpoints that let an interrupted fetch resume where it stopped.
+//!
+//! [`CheckpointedRepository`] wraps another repository and records every
+//! successful response, writing them to the `--checkpoint` file every
+//! [`CHECKPOINT_INTERVAL_SECS`]. A run started with `--resume` replays the
+//! same exploration from the root: steps whose responses are in the file
+//! complete from it without touching the network, so only the work that
+//! was still outstanding when the first run died is fetched.
+
+use super::types::{DataSourceRef, DataSourceRows, DatabaseRows, TransferUsage};
+use super::NotionRepository;
+use crate::clock::{Clock, SystemClock};
+use crate::constants::CHECKPOINT_INTERVAL_SECS;
+use crate::error::AppError;
+use crate::model::{Block, Database, NotionObject, Page};
+use crate::types::NotionId;
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Bumped when the file layout changes; older checkpoints are rejected.
+const CHECKPOINT_FORMAT_VERSION: u32 = 1;
+
+/// The on-disk checkpoint: the root it belongs to and every response so far.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CheckpointFile {
+    version: u32,
+    root: NotionId,
+    responses: BTreeMap<String, serde_json::Value>,
+}
+
+struct CheckpointState {
+    file: CheckpointFile,
+    last_saved: u64,
+    /// Bumped with every recorded response
+    generation: u64,
+}
+
+impl CheckpointState {
+    /// A copy of the file to write outside the lock, with its generation.
+    fn snapshot(&self) -> (u64, CheckpointFile) {
+        (self.generation, self.file.clone())
+    }
+}
+
+/// A repository that records responses to a checkpoint file and answers
+/// from it when resuming.
+pub struct CheckpointedRepository {
+    inner: Arc<dyn NotionRepository>,
+    path: PathBuf,
+    clock: Arc<dyn Clock>,
+    restored: usize,
+    state: Mutex<CheckpointState>,
+    /// Held while writing; the generation last written, so a slow write
+    /// never replaces a newer checkpoint
+    written: Arc<Mutex<u64>>,
+}
+
+impl CheckpointedRepository {
+    /// Starts an empty checkpoint for a fetch of `root`, saved to `path`.
+    pub fn start(inner: Arc<dyn NotionRepository>, root: &NotionId, path: PathBuf) -> Self {
+        let file = CheckpointFile {
+            version: CHECKPOINT_FORMAT_VERSION,
+            root: root.clone(),
+            responses: BTreeMap::new(),
+        };
+        Self::with_file(inner, file, path, Arc::new(SystemClock))
+    }
+
+    /// Loads the checkpoint at `from` to continue a fetch of `root`, saving
+    /// further progress to `path`.
+    pub fn resume(
+        inner: Arc<dyn NotionRepository>,
+        root: &NotionId,
+        from: &Path,
+        path: PathBuf,
+    ) -> Result<Self, AppError> {
+        let text = std::fs::read_to_string(from)?;
+        let file: CheckpointFile =
+            serde_json::from_str(&text).map_err(|source| AppError::JsonParseError {
+                path: from.to_path_buf(),
+                source,
+            })?;
+        if file.version != CHECKPOINT_FORMAT_VERSION {
+            return Err(AppError::Validation(format!(
+                "checkpoint {} has format version {}, expected {}",
+                from.display(),
+                file.version,
+                CHECKPOINT_FORMAT_VERSION
+            )));
+        }
+        if &file.root != root {
+            return Err(AppError::Validation(format!(
+                "checkpoint {} belongs to {}, not {}",
+                from.display(),
+                file.root,
+                root
+            )));
+        }
+        Ok(Self::with_file(inner, file, path, Arc::new(SystemClock)))
+    }
+
+    fn with_file(
+        inner: Arc<dyn NotionRepository>,
+        file: CheckpointFile,
+        path: PathBuf,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        let last_saved = clock.unix_secs();
+        Self {
+            inner,
+            path,
+            restored: file.responses.len(),
+            clock,
+            state: Mutex::new(CheckpointState {
+                file,
+                last_saved,
+                generation: 0,
+            }),
+            written: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Reads the time from `clock` instead of the system clock.
+    #[allow(dead_code)]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.state.get_mut().last_saved = clock.unix_secs();
+        self.clock = clock;
+        self
+    }
+
+    /// Where the checkpoint is written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// How many responses were loaded from the checkpoint being resumed.
+    pub fn restored(&self) -> usize {
+        self.restored
+    }
+
+    /// Writes every response recorded so far.
+    ///
+    /// The file is replaced atomically, so a run killed mid-write leaves
+    /// the previous checkpoint intact.
+    pub fn save(&self) -> Result<(), AppError> {
+        let (generation, file) = {
+            let mut state = self.state.lock();
+            state.last_saved = self.clock.unix_secs();
+            state.snapshot()
+        };
+        write_snapshot(&self.path, &self.written, generation, &file)
+    }
+
+    /// Deletes the checkpoint once the fetch it protected has finished.
+    pub fn remove(&self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("Could not remove checkpoint {}: {}", self.path.display(), e);
+            }
+        }
+    }
+
+    /// Answers `key` from the checkpoint, or runs `fetch` and records it.
+    async fn recorded<T, F>(&self, key: String, fetch: F) -> Result<T, AppError>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = Result<T, AppError>>,
+    {
+        let saved = self.state.lock().file.responses.get(&key).cloned();
+        if let Some(value) = saved {
+            match serde_json::from_value(value) {
+                Ok(response) => {
+                    log::debug!("Checkpoint hit: {}", key);
+                    return Ok(response);
+                }
+                Err(e) => log::debug!("Ignoring unreadable checkpoint entry {}: {}", key, e),
+            }
+        }
+
+        let response = fetch.await?;
+        let value = serde_json::to_value(&response)?;
+
+        let due = {
+            let mut state = self.state.lock();
+            state.file.responses.insert(key, value);
+            state.generation += 1;
+            let now = self.clock.unix_secs();
+            let due = now.saturating_sub(state.last_saved) >= CHECKPOINT_INTERVAL_SECS;
+            if due {
+                state.last_saved = now;
+            }
+            due.then(|| state.snapshot())
+        };
+        // Serializing and writing a large checkpoint must not stall this
+        // worker's thread or hold up the others recording responses
+        if let Some((generation, file)) = due {
+            let path = self.path.clone();
+            let written = Arc::clone(&self.written);
+            let saved = tokio::task::spawn_blocking(move || {
+                write_snapshot(&path, &written, generation, &file)
+            })
+            .await
+            .unwrap_or_else(|e| {
+                Err(AppError::InternalError {
+                    message: "Checkpoint writer failed".to_string(),
+                    source: Some(Box::new(e)),
+                })
+            });
+            if let Err(e) = saved {
+                log::warn!("Could not write checkpoint {}: {}", self.path.display(), e);
+            }
+        }
+        Ok(response)
+    }
+}
+
+/// Writes `file` to `path` unless a newer generation was written already.
+///
+/// The file is written to a uniquely named temporary file beside `path`
+/// and renamed over it, so a run killed mid-write leaves the previous
+/// checkpoint intact.
+fn write_snapshot(
+    path: &Path,
+    written: &Mutex<u64>,
+    generation: u64,
+    file: &CheckpointFile,
+) -> Result<(), AppError> {
+    let mut last_written = written.lock();
+    if *last_written > generation {
+        return Ok(());
+    }
+    let json = serde_json::to_vec(file)?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4().simple()));
+    let result = std::fs::write(&temp, json).and_then(|()| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result?;
+    *last_written = generation;
+    log::debug!(
+        "Checkpoint saved: {} responses to {}",
+        file.responses.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl NotionRepository for CheckpointedRepository {
+    async fn retrieve_page(&self, id: &NotionId) -> Result<Page, AppError> {
+        self.recorded(format!("page/{}", id), self.inner.retrieve_page(id))
+            .await
+    }
+
+    async fn retrieve_database(&self, id: &NotionId) -> Result<Database, AppError> {
+        self.recorded(format!("database/{}", id), self.inner.retrieve_database(id))
+            .await
+    }
+
+    async fn retrieve_block(&self, id: &NotionId) -> Result<Block, AppError> {
+        self.recorded(format!("block/{}", id), self.inner.retrieve_block(id))
+            .await
+    }
+
+    async fn retrieve_children(&self, parent: &NotionId) -> Result<Vec<Block>, AppError> {
+        self.recorded(
+            format!("children/{}", parent),
+            self.inner.retrieve_children(parent),
+        )
+        .await
+    }
+
+    async fn query_rows(&self, database: &NotionId) -> Result<DatabaseRows, AppError> {
+        self.recorded(
+            format!("rows/{}", database),
+            self.inner.query_rows(database),
+        )
+        .await
+    }
+
+    async fn list_data_sources(&self, database: &NotionId) -> Result<Vec<DataSourceRef>, AppError> {
+        self.recorded(
+            format!("data_sources/{}", database),
+            self.inner.list_data_sources(database),
+        )
+        .await
+    }
+
+    async fn query_data_source(&self, source: &DataSourceRef) -> Result<DataSourceRows, AppError> {
+        self.recorded(
+            format!("data_source/{}", source.id),
+            self.inner.query_data_source(source),
+        )
+        .await
+    }
+
+    async fn search_shared(&self) -> Result<Vec<NotionObject>, AppError> {
+        self.inner.search_shared().await
+    }
+
+    fn transfer_usage(&self) -> TransferUsage {
+        self.inner.transfer_usage()
+    }
+
+    fn record_retry(&self, error: &AppError, wait: std::time::Duration) {
+        self.inner.record_retry(error, wait);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Answers child listings with no blocks and counts the calls.
+    #[derive(Default)]
+    struct CountingRepository {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl NotionRepository for CountingRepository {
+        async fn retrieve_page(&self, id: &NotionId) -> Result<Page, AppError> {
+            Err(AppError::InvalidId(id.to_string()))
+        }
+        async fn retrieve_database(&self, id: &NotionId) -> Result<Database, AppError> {
+            Err(AppError::InvalidId(id.to_string()))
+        }
+        async fn retrieve_block(&self, id: &NotionId) -> Result<Block, AppError> {
+            Err(AppError::InvalidId(id.to_string()))
+        }
+        async fn retrieve_children(&self, _parent: &NotionId) -> Result<Vec<Block>, AppError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Vec::new())
+        }
+        async fn query_rows(&self, id: &NotionId) -> Result<DatabaseRows, AppError> {
+            Err(AppError::InvalidId(id.to_string()))
+        }
+        async fn search_shared(&self) -> Result<Vec<NotionObject>, AppError> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn id(hex: char) -> NotionId {
+        NotionId::parse(&hex.to_string().repeat(32)).unwrap()
+    }
+
+    fn temp_checkpoint() -> PathBuf {
+        std::env::temp_dir().join(format!("n2p_checkpoint_{}.json", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_resume_answers_recorded_requests_without_fetching() {
+        let path = temp_checkpoint();
+        let clock = Arc::new(ManualClock::at_unix_secs(10_000));
+        let first = Arc::new(CountingRepository::default());
+        let checkpoint = CheckpointedRepository::start(first.clone(), &id('a'), path.clone())
+            .with_clock(clock.clone());
+
+        checkpoint.retrieve_children(&id('b')).await.unwrap();
+        assert!(!path.exists(), "saved before the interval elapsed");
+        clock.advance(Duration::from_secs(CHECKPOINT_INTERVAL_SECS));
+        checkpoint.retrieve_children(&id('c')).await.unwrap();
+        assert!(path.exists());
+
+        let second = Arc::new(CountingRepository::default());
+        let resumed =
+            CheckpointedRepository::resume(second.clone(), &id('a'), &path, path.clone()).unwrap();
+        assert_eq!(resumed.restored(), 2);
+        resumed.retrieve_children(&id('b')).await.unwrap();
+        resumed.retrieve_children(&id('c')).await.unwrap();
+        resumed.retrieve_children(&id('d')).await.unwrap();
+
+        assert_eq!(first.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(second.calls.load(Ordering::SeqCst), 1);
+        resumed.remove();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_resume_rejects_checkpoint_of_another_root() {
+        let path = temp_checkpoint();
+        let inner: Arc<dyn NotionRepository> = Arc::new(CountingRepository::default());
+        CheckpointedRepository::start(inner.clone(), &id('a'), path.clone())
+            .save()
+            .unwrap();
+
+        let result = CheckpointedRepository::resume(inner, &id('b'), &path, path.clone());
+
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_save_leaves_neighbouring_files_and_newer_checkpoints_alone() {
+        let dir = std::env::temp_dir().join(format!("n2p_checkpoint_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        std::fs::write(dir.join("state.tmp"), "mine").unwrap();
+        let inner: Arc<dyn NotionRepository> = Arc::new(CountingRepository::default());
+        let checkpoint = CheckpointedRepository::start(inner, &id('a'), path.clone());
+        checkpoint.save().unwrap();
+
+        let older = CheckpointFile {
+            version: CHECKPOINT_FORMAT_VERSION,
+            root: id('b'),
+            responses: BTreeMap::new(),
+        };
+        *checkpoint.written.lock() = 2;
+        write_snapshot(&path, &checkpoint.written, 1, &older).unwrap();
+
+        let saved: CheckpointFile =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.root, id('a'));
+        assert_eq!(
+            std::fs::read_to_string(dir.join("state.tmp")).unwrap(),
+            "mine"
+        );
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
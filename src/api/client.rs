@@ -5,40 +5,87 @@
 //! HTTP requests to the Notion API. It handles authentication and
 //! basic request/response operations without parsing or business logic.
 
+use super::har::{HarRecorder, RecordedRequest, RecordedResponse};
+use super::simple_pagination::PageLimits;
+use super::types::{EndpointCategory, TransferCounter, TransferUsage};
+use crate::config::PoolConfig;
+use crate::constants::NOTION_MAX_BLOCK_PAYLOAD_BYTES;
 use crate::error::AppError;
 use crate::types::ApiKey;
-use reqwest::{header, Client, Response};
+use reqwest::{header, Client, Request, Response};
 use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-const NOTION_VERSION: &str = "2022-06-28";
-const API_BASE_URL: &str = "https://api.notion.com/v1";
+pub(super) const NOTION_VERSION: &str = "2022-06-28";
+
+/// First API version that exposes a database's data sources.
+///
+/// Multi-source databases cannot be queried through `NOTION_VERSION`, so
+/// only the data source requests opt into it.
+pub(super) const DATA_SOURCES_VERSION: &str = "2025-09-03";
 
 /// A thin wrapper around reqwest Client for Notion API requests.
 #[derive(Clone)]
 pub struct NotionHttpClient {
     client: Client,
+    /// `https://api.notion.com/v1` or a gateway's, without a trailing slash
+    base_url: Arc<str>,
+    /// The header the API key goes in, redacted in HAR captures
+    auth_header: header::HeaderName,
+    har: Option<Arc<HarRecorder>>,
+    transfer: Arc<TransferCounter>,
+    max_block_bytes: usize,
 }
 
 impl NotionHttpClient {
     /// Creates a new HTTP client with Notion API authentication.
     pub fn new(api_key: &ApiKey) -> Result<Self, AppError> {
-        let client = Client::builder()
-            .default_headers(Self::create_headers(api_key)?)
+        Self::with_pool_config(api_key, &PoolConfig::default())
+    }
+
+    /// Creates an authenticated client with custom pooling, protocol and
+    /// proxy settings, talking to `pool.api_base_url`.
+    pub fn with_pool_config(api_key: &ApiKey, pool: &PoolConfig) -> Result<Self, AppError> {
+        let base_url = parse_base_url(&pool.api_base_url)?;
+        let auth_header = match &pool.auth_header {
+            Some(name) => header::HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+                AppError::MissingConfiguration(format!("Invalid auth header '{}': {}", name, e))
+            })?,
+            None => header::AUTHORIZATION,
+        };
+        let client = pool
+            .client_builder()?
+            .default_headers(Self::create_headers(api_key, &auth_header)?)
             .build()?;
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            base_url: base_url.into(),
+            auth_header,
+            har: None,
+            transfer: Arc::default(),
+            max_block_bytes: NOTION_MAX_BLOCK_PAYLOAD_BYTES,
+        })
     }
 
-    /// Creates the default headers for Notion API requests.
-    fn create_headers(api_key: &ApiKey) -> Result<header::HeaderMap, AppError> {
+    /// Creates the default headers for Notion API requests: the API key as
+    /// a bearer token, or as is in a gateway's own `auth_header`.
+    fn create_headers(
+        api_key: &ApiKey,
+        auth_header: &header::HeaderName,
+    ) -> Result<header::HeaderMap, AppError> {
         let mut headers = header::HeaderMap::new();
 
-        let auth_header = format!("Bearer {}", api_key.as_str());
-        headers.insert(
-            header::AUTHORIZATION,
-            header::HeaderValue::from_str(&auth_header).map_err(|e| {
-                AppError::MissingConfiguration(format!("Invalid API token format: {}", e))
-            })?,
-        );
+        let credentials = if auth_header == header::AUTHORIZATION {
+            format!("Bearer {}", api_key.as_str())
+        } else {
+            api_key.as_str().to_string()
+        };
+        let mut credentials = header::HeaderValue::from_str(&credentials).map_err(|e| {
+            AppError::MissingConfiguration(format!("Invalid API token format: {}", e))
+        })?;
+        credentials.set_sensitive(true);
+        headers.insert(auth_header.clone(), credentials);
 
         headers.insert(
             "Notion-Version",
@@ -53,6 +100,28 @@ impl NotionHttpClient {
         Ok(headers)
     }
 
+    /// Records every request and response to `recorder` from now on.
+    pub fn with_har(mut self, recorder: Arc<HarRecorder>) -> Self {
+        self.har = Some(recorder);
+        self
+    }
+
+    /// Leaves out blocks whose JSON exceeds `bytes` when parsing block lists.
+    pub fn with_max_block_bytes(mut self, bytes: usize) -> Self {
+        self.max_block_bytes = bytes;
+        self
+    }
+
+    /// The largest block payload parsed from a block list.
+    pub(super) fn max_block_bytes(&self) -> usize {
+        self.max_block_bytes
+    }
+
+    /// Counts the payloads of text requests made through this client and its clones.
+    pub(super) fn transfer(&self) -> &TransferCounter {
+        &self.transfer
+    }
+
     /// Makes a GET request to the specified endpoint.
     ///
     /// # Arguments
@@ -62,8 +131,46 @@ impl NotionHttpClient {
     /// # Returns
     ///
     /// A `Response` from the Notion API, or an `AppError` if the request fails.
+    #[allow(dead_code)]
     pub async fn get(&self, endpoint: &str) -> Result<Response, AppError> {
-        let url = format!("{}/{}", API_BASE_URL, endpoint);
+        self.get_with_version(endpoint, NOTION_VERSION).await
+    }
+
+    /// Makes a GET request against a specific Notion API version.
+    #[allow(dead_code)]
+    pub async fn get_with_version(
+        &self,
+        endpoint: &str,
+        version: &'static str,
+    ) -> Result<Response, AppError> {
+        let request = self.get_request(endpoint, version)?;
+        let response = self.client.execute(request).await?;
+        log_response("GET", endpoint, response.status());
+        Ok(response)
+    }
+
+    /// Makes a GET request and reads the response body.
+    ///
+    /// Unlike [`get`](Self::get), the payload is counted in
+    /// [`transfer_usage`](super::NotionRepository::transfer_usage) and the
+    /// exchange is recorded when a HAR recorder is attached.
+    pub async fn get_text(&self, endpoint: &str) -> Result<ApiResponse<String>, AppError> {
+        self.get_text_with_version(endpoint, NOTION_VERSION).await
+    }
+
+    /// Makes a GET request against a specific Notion API version and reads
+    /// the response body.
+    pub async fn get_text_with_version(
+        &self,
+        endpoint: &str,
+        version: &'static str,
+    ) -> Result<ApiResponse<String>, AppError> {
+        let request = self.get_request(endpoint, version)?;
+        self.execute_text("GET", endpoint, request).await
+    }
+
+    fn get_request(&self, endpoint: &str, version: &'static str) -> Result<Request, AppError> {
+        let url = format!("{}/{}", self.base_url, endpoint);
 
         // Enhanced logging for database-related requests
         if endpoint.contains("databases") {
@@ -72,18 +179,11 @@ impl NotionHttpClient {
             log::debug!("GET {}", url);
         }
 
-        let response = self.client.get(url).send().await?;
-
-        // Log response status for database requests
-        if endpoint.contains("databases") {
-            log::info!(
-                "📡 DATABASE GET RESPONSE: {} (status: {})",
-                endpoint,
-                response.status()
-            );
-        }
-
-        Ok(response)
+        Ok(self
+            .client
+            .get(url)
+            .header("Notion-Version", version)
+            .build()?)
     }
 
     /// Makes a POST request with JSON body to the specified endpoint.
@@ -96,8 +196,57 @@ impl NotionHttpClient {
     /// # Returns
     ///
     /// A `Response` from the Notion API, or an `AppError` if the request fails.
+    #[allow(dead_code)]
     pub async fn post<T: Serialize>(&self, endpoint: &str, body: &T) -> Result<Response, AppError> {
-        let url = format!("{}/{}", API_BASE_URL, endpoint);
+        self.post_with_version(endpoint, body, NOTION_VERSION).await
+    }
+
+    /// Makes a POST request against a specific Notion API version.
+    #[allow(dead_code)]
+    pub async fn post_with_version<T: Serialize>(
+        &self,
+        endpoint: &str,
+        body: &T,
+        version: &'static str,
+    ) -> Result<Response, AppError> {
+        let request = self.post_request(endpoint, body, version)?;
+        let response = self.client.execute(request).await?;
+        log_response("POST", endpoint, response.status());
+        Ok(response)
+    }
+
+    /// Makes a POST request and reads the response body.
+    ///
+    /// Unlike [`post`](Self::post), the payload is counted and the exchange
+    /// is recorded when a HAR recorder is attached.
+    pub async fn post_text<T: Serialize>(
+        &self,
+        endpoint: &str,
+        body: &T,
+    ) -> Result<ApiResponse<String>, AppError> {
+        self.post_text_with_version(endpoint, body, NOTION_VERSION)
+            .await
+    }
+
+    /// Makes a POST request against a specific Notion API version and reads
+    /// the response body.
+    pub async fn post_text_with_version<T: Serialize>(
+        &self,
+        endpoint: &str,
+        body: &T,
+        version: &'static str,
+    ) -> Result<ApiResponse<String>, AppError> {
+        let request = self.post_request(endpoint, body, version)?;
+        self.execute_text("POST", endpoint, request).await
+    }
+
+    fn post_request<T: Serialize>(
+        &self,
+        endpoint: &str,
+        body: &T,
+        version: &'static str,
+    ) -> Result<Request, AppError> {
+        let url = format!("{}/{}", self.base_url, endpoint);
 
         // Enhanced logging for database queries
         if endpoint.contains("databases") && endpoint.contains("query") {
@@ -111,18 +260,63 @@ impl NotionHttpClient {
             log::debug!("POST {}", url);
         }
 
-        let response = self.client.post(url).json(body).send().await?;
+        Ok(self
+            .client
+            .post(url)
+            .header("Notion-Version", version)
+            .json(body)
+            .build()?)
+    }
 
-        // Log response status for database queries
-        if endpoint.contains("databases") && endpoint.contains("query") {
-            log::info!(
-                "📊 DATABASE QUERY RESPONSE: {} (status: {})",
-                endpoint,
-                response.status()
-            );
+    /// Sends `request`, reads the body and records the exchange if a HAR
+    /// recorder is attached.
+    async fn execute_text(
+        &self,
+        method: &str,
+        endpoint: &str,
+        request: Request,
+    ) -> Result<ApiResponse<String>, AppError> {
+        let Some(har) = &self.har else {
+            let response = self.client.execute(request).await?;
+            log_response(method, endpoint, response.status());
+            let result = extract_response_text(response).await?;
+            self.count(endpoint, &result);
+            return Ok(result);
+        };
+
+        let recorded = RecordedRequest::capture(&request, &self.auth_header);
+        let started = chrono::Utc::now();
+        let timer = Instant::now();
+        let response = self.client.execute(request).await?;
+        log_response(method, endpoint, response.status());
+        let headers = response.headers().clone();
+        let result = extract_response_text(response).await?;
+        self.count(endpoint, &result);
+        har.record(
+            started,
+            timer.elapsed(),
+            recorded,
+            RecordedResponse {
+                status: result.status,
+                headers,
+                body: result.data.clone(),
+            },
+        );
+        Ok(result)
+    }
+
+    fn count(&self, endpoint: &str, result: &ApiResponse<String>) {
+        self.transfer
+            .record_download(EndpointCategory::of(endpoint), result.data.len());
+        if result.status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            self.transfer.record_rate_limited();
         }
+    }
 
-        Ok(response)
+    /// Checks the API key by asking Notion which integration it belongs to.
+    pub async fn retrieve_bot_user(&self) -> Result<super::types::IntegrationIdentity, AppError> {
+        let result = self.get_text("users/me").await?;
+        super::parser::parse_bot_user_response(result)
     }
 
     /// Makes a PATCH request with JSON body to the specified endpoint.
@@ -132,7 +326,7 @@ impl NotionHttpClient {
         endpoint: &str,
         body: &T,
     ) -> Result<Response, AppError> {
-        let url = format!("{}/{}", API_BASE_URL, endpoint);
+        let url = format!("{}/{}", self.base_url, endpoint);
         log::debug!("PATCH {}", url);
         Ok(self.client.patch(url).json(body).send().await?)
     }
@@ -140,13 +334,20 @@ impl NotionHttpClient {
 
 #[async_trait::async_trait]
 impl super::NotionRepository for NotionHttpClient {
+    fn transfer_usage(&self) -> TransferUsage {
+        self.transfer.usage()
+    }
+
+    fn record_retry(&self, error: &AppError, wait: Duration) {
+        self.transfer.record_retry(error, wait);
+    }
+
     async fn retrieve_page(
         &self,
         id: &crate::types::NotionId,
     ) -> Result<crate::model::Page, AppError> {
         let endpoint = format!("pages/{}", id.to_hyphenated());
-        let response = self.get(&endpoint).await?;
-        let result = extract_response_text(response).await?;
+        let result = self.get_text(&endpoint).await?;
         super::parser::parse_page_response(result)
     }
 
@@ -155,8 +356,7 @@ impl super::NotionRepository for NotionHttpClient {
         id: &crate::types::NotionId,
     ) -> Result<crate::model::Database, AppError> {
         let endpoint = format!("databases/{}", id.to_hyphenated());
-        let response = self.get(&endpoint).await?;
-        let result = extract_response_text(response).await?;
+        let result = self.get_text(&endpoint).await?;
         super::parser::parse_database_response(result)
     }
 
@@ -165,8 +365,7 @@ impl super::NotionRepository for NotionHttpClient {
         id: &crate::types::NotionId,
     ) -> Result<crate::model::Block, AppError> {
         let endpoint = format!("blocks/{}", id.to_hyphenated());
-        let response = self.get(&endpoint).await?;
-        let result = extract_response_text(response).await?;
+        let result = self.get_text(&endpoint).await?;
         super::parser::parse_block_response(result)
     }
 
@@ -177,20 +376,15 @@ impl super::NotionRepository for NotionHttpClient {
         let endpoint = format!("blocks/{}/children", parent.to_hyphenated());
         let client = self.clone();
         let pagination_result = super::simple_pagination::fetch_all_pages_simple(
-            |page_size, cursor| {
+            |request| {
                 let client = client.clone();
-                let endpoint = endpoint.clone();
+                let endpoint = request.get_endpoint(&endpoint);
                 async move {
-                    let mut query = serde_json::json!({ "page_size": page_size });
-                    if let Some(cursor) = cursor {
-                        query["start_cursor"] = serde_json::json!(cursor);
-                    }
-                    let response = client.get(&endpoint).await?;
-                    let result = extract_response_text(response).await?;
-                    super::parser::parse_blocks_pagination(result)
+                    let result = client.get_text(&endpoint).await?;
+                    super::parser::parse_blocks_pagination_within(result, client.max_block_bytes)
                 }
             },
-            None,
+            PageLimits::default(),
         )
         .await?;
         Ok(pagination_result.items)
@@ -199,31 +393,95 @@ impl super::NotionRepository for NotionHttpClient {
     async fn query_rows(
         &self,
         database: &crate::types::NotionId,
-    ) -> Result<Vec<crate::model::Page>, AppError> {
+    ) -> Result<super::types::DatabaseRows, AppError> {
         let endpoint = format!("databases/{}/query", database.to_hyphenated());
         let client = self.clone();
-        let pagination_result = super::simple_pagination::fetch_all_pages_simple(
-            |page_size, cursor| {
+        let pagination_result = super::simple_pagination::fetch_all_pages_resumable(
+            |request| {
                 let client = client.clone();
                 let endpoint = endpoint.clone();
                 async move {
-                    let mut query = serde_json::json!({
-                        "page_size": page_size
-                    });
-                    if let Some(cursor) = cursor {
-                        query["start_cursor"] = serde_json::json!(cursor);
-                    }
-                    let response = client.post(&endpoint, &query).await?;
-                    let result = extract_response_text(response).await?;
-                    super::parser::parse_pages_pagination(result)
+                    let result = client.post_text(&endpoint, &request.post_body()).await?;
+                    super::parser::parse_rows_pagination(result)
+                }
+            },
+            PageLimits::default(),
+            &|error, wait| self.transfer.record_retry(error, wait),
+        )
+        .await?;
+        Ok(super::types::DatabaseRows::from_pagination(
+            database,
+            pagination_result.items,
+            pagination_result.interrupted,
+        ))
+    }
+
+    async fn list_data_sources(
+        &self,
+        database: &crate::types::NotionId,
+    ) -> Result<Vec<super::types::DataSourceRef>, AppError> {
+        let endpoint = format!("databases/{}", database.to_hyphenated());
+        let result = self
+            .get_text_with_version(&endpoint, DATA_SOURCES_VERSION)
+            .await?;
+        super::parser::parse_data_source_list(result)
+    }
+
+    async fn query_data_source(
+        &self,
+        source: &super::types::DataSourceRef,
+    ) -> Result<super::types::DataSourceRows, AppError> {
+        let endpoint = format!("data_sources/{}", source.id.to_hyphenated());
+        let result = self
+            .get_text_with_version(&endpoint, DATA_SOURCES_VERSION)
+            .await?;
+        let properties = super::parser::parse_data_source_schema(result)?;
+
+        let query_endpoint = format!("{}/query", endpoint);
+        let client = self.clone();
+        let pagination_result = super::simple_pagination::fetch_all_pages_resumable(
+            |request| {
+                let client = client.clone();
+                let endpoint = query_endpoint.clone();
+                async move {
+                    let result = client
+                        .post_text_with_version(
+                            &endpoint,
+                            &request.post_body(),
+                            DATA_SOURCES_VERSION,
+                        )
+                        .await?;
+                    super::parser::parse_rows_pagination(result)
                 }
             },
-            None,
+            PageLimits::default(),
+            &|error, wait| self.transfer.record_retry(error, wait),
         )
         .await?;
-        let mut pages = pagination_result.items;
-        sort_pages_by_date_desc(&mut pages);
-        Ok(pages)
+        Ok(super::types::DataSourceRows {
+            properties,
+            rows: super::types::DatabaseRows::from_pagination(
+                &source.id,
+                pagination_result.items,
+                pagination_result.interrupted,
+            ),
+        })
+    }
+
+    async fn search_shared(&self) -> Result<Vec<crate::model::NotionObject>, AppError> {
+        let client = self.clone();
+        let pagination_result = super::simple_pagination::fetch_all_pages_simple(
+            |request| {
+                let client = client.clone();
+                async move {
+                    let result = client.post_text("search", &request.post_body()).await?;
+                    super::parser::parse_search_pagination(result)
+                }
+            },
+            PageLimits::default(),
+        )
+        .await?;
+        Ok(pagination_result.items)
     }
 }
 
@@ -307,6 +565,42 @@ pub(super) fn sort_pages_by_date_desc(pages: &mut [crate::model::Page]) {
     });
 }
 
+/// Logs the status of database requests, which are the usual suspects when
+/// an export comes out incomplete.
+fn log_response(method: &str, endpoint: &str, status: reqwest::StatusCode) {
+    if endpoint.contains("databases") {
+        log::info!(
+            "📡 DATABASE {} RESPONSE: {} (status: {})",
+            method,
+            endpoint,
+            status
+        );
+    }
+}
+
+/// Checks a `--api-base-url`, returning it without a trailing slash.
+fn parse_base_url(raw: &str) -> Result<&str, AppError> {
+    let invalid = |reason: &dyn std::fmt::Display| {
+        AppError::MissingConfiguration(format!("Invalid API base URL '{}': {}", raw, reason))
+    };
+    let url = reqwest::Url::parse(raw).map_err(|e| invalid(&e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid(&"expected an http or https URL"));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(invalid(&"a base URL has no query or fragment"));
+    }
+    Ok(raw.trim_end_matches('/'))
+}
+
+/// Turns a path as written in Notion's API reference (`/v1/blocks/<id>/children`,
+/// optionally with the host) into an endpoint relative to the API base URL.
+pub fn relative_endpoint(path: &str) -> &str {
+    let path = path.strip_prefix("https://api.notion.com").unwrap_or(path);
+    let path = path.trim_start_matches('/');
+    path.strip_prefix("v1/").unwrap_or(path)
+}
+
 /// Result of an HTTP operation with response metadata.
 #[derive(Debug)]
 pub struct ApiResponse<T> {
@@ -335,3 +629,65 @@ pub async fn extract_response_text(response: Response) -> Result<ApiResponse<Str
         url,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_endpoint_accepts_reference_paths_and_urls() {
+        let expected = "blocks/abc/children?page_size=10";
+        assert_eq!(
+            relative_endpoint("/v1/blocks/abc/children?page_size=10"),
+            expected
+        );
+        assert_eq!(
+            relative_endpoint("v1/blocks/abc/children?page_size=10"),
+            expected
+        );
+        assert_eq!(
+            relative_endpoint("blocks/abc/children?page_size=10"),
+            expected
+        );
+        assert_eq!(
+            relative_endpoint("https://api.notion.com/v1/blocks/abc/children?page_size=10"),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_gateway_base_url_and_auth_header() {
+        let pool = PoolConfig {
+            api_base_url: "https://gateway.internal/notion/v1/".to_string(),
+            auth_header: Some("X-Api-Key".to_string()),
+            ..Default::default()
+        };
+        let client =
+            NotionHttpClient::with_pool_config(&ApiKey::new_unchecked("secret_x"), &pool).unwrap();
+        let request = client.get_request("users/me", NOTION_VERSION).unwrap();
+        assert_eq!(
+            request.url().as_str(),
+            "https://gateway.internal/notion/v1/users/me"
+        );
+        assert_eq!(client.auth_header.as_str(), "x-api-key");
+
+        let headers = NotionHttpClient::create_headers(
+            &ApiKey::new_unchecked("secret_x"),
+            &client.auth_header,
+        )
+        .unwrap();
+        assert_eq!(headers["x-api-key"], "secret_x");
+        assert!(!headers.contains_key(header::AUTHORIZATION));
+
+        for base_url in ["gateway.internal/v1", "ftp://gateway.internal/v1"] {
+            let pool = PoolConfig {
+                api_base_url: base_url.to_string(),
+                ..Default::default()
+            };
+            assert!(matches!(
+                NotionHttpClient::with_pool_config(&ApiKey::new_unchecked("secret_x"), &pool),
+                Err(AppError::MissingConfiguration(_))
+            ));
+        }
+    }
+}
@@ -1,7 +1,12 @@
 // src/api/concurrent_queue.rs
 //! Concurrent work-stealing queue implementation for parallel fetching.
+//!
+//! Child database and row steps (`High` and `Critical`) skip the FIFO
+//! injector: they wait in a shared priority lane that every worker checks
+//! before its own work, so they are fetched before queued block recursion
+//! uses up the item limit.
 
-use super::fetch_queue::{ExplorationStep, PrioritizedWorkItem, StepOutcome};
+use super::fetch_queue::{ExplorationStep, PrioritizedWorkItem, StepOutcome, WorkPriority};
 use crossbeam::deque::{Injector, Stealer, Worker};
 use parking_lot::Mutex;
 use std::collections::BinaryHeap;
@@ -44,6 +49,8 @@ impl WorkTracker {
 pub struct ConcurrentWorkQueue {
     /// Global injector for new work items
     injector: Arc<Injector<PrioritizedWorkItem>>,
+    /// High-priority items, taken before anything in the injector
+    priority_lane: Arc<Mutex<BinaryHeap<PrioritizedWorkItem>>>,
     /// Stealers for work-stealing between workers
     stealers: Vec<Stealer<PrioritizedWorkItem>>,
     /// Results collector
@@ -58,6 +65,7 @@ impl ConcurrentWorkQueue {
     /// Creates a new concurrent work queue with the specified number of workers.
     pub fn new(num_workers: usize) -> (Self, Vec<WorkerQueue>) {
         let injector = Arc::new(Injector::new());
+        let priority_lane = Arc::new(Mutex::new(BinaryHeap::new()));
         let mut workers = Vec::with_capacity(num_workers);
         let mut stealers = Vec::with_capacity(num_workers);
 
@@ -67,6 +75,7 @@ impl ConcurrentWorkQueue {
             workers.push(WorkerQueue {
                 worker,
                 injector: Arc::clone(&injector),
+                priority_lane: Arc::clone(&priority_lane),
                 work_tracker: None,     // Will be set later
                 sequence_counter: None, // Will be set later
                 priority_buffer: Arc::new(Mutex::new(BinaryHeap::new())),
@@ -78,6 +87,7 @@ impl ConcurrentWorkQueue {
 
         let queue = Self {
             injector,
+            priority_lane,
             stealers,
             results: Arc::new(Mutex::new(Vec::new())),
             work_tracker: work_tracker.clone(),
@@ -101,12 +111,11 @@ impl ConcurrentWorkQueue {
     pub fn enqueue(&self, item: ExplorationStep) {
         self.work_tracker.add_pending(1);
         let sequence = self.sequence_counter.fetch_add(1, Ordering::SeqCst);
-        let prioritized = PrioritizedWorkItem {
+        self.push(PrioritizedWorkItem {
             priority: item.priority(),
             sequence,
             item,
-        };
-        self.injector.push(prioritized);
+        });
     }
 
     /// Enqueues multiple work items.
@@ -131,6 +140,14 @@ impl ConcurrentWorkQueue {
         prioritized_items.sort_by(|a, b| b.cmp(a));
 
         for item in prioritized_items {
+            self.push(item);
+        }
+    }
+
+    fn push(&self, item: PrioritizedWorkItem) {
+        if item.priority >= WorkPriority::High {
+            self.priority_lane.lock().push(item);
+        } else {
             self.injector.push(item);
         }
     }
@@ -169,6 +186,8 @@ pub struct WorkerQueue {
     worker: Worker<PrioritizedWorkItem>,
     /// Reference to global injector
     injector: Arc<Injector<PrioritizedWorkItem>>,
+    /// Reference to the shared high-priority lane
+    priority_lane: Arc<Mutex<BinaryHeap<PrioritizedWorkItem>>>,
     /// Work tracker for completion detection
     work_tracker: Option<Arc<WorkTracker>>,
     /// Sequence counter for priority ordering
@@ -180,7 +199,12 @@ pub struct WorkerQueue {
 impl WorkerQueue {
     /// Dequeues a work item, trying local queue first, then stealing.
     pub fn dequeue(&self, stealers: &[Stealer<PrioritizedWorkItem>]) -> Option<ExplorationStep> {
-        // First, check if we have items in the priority buffer
+        // Child databases and rows come before anything else
+        if let Some(prioritized) = self.priority_lane.lock().pop() {
+            return Some(prioritized.item);
+        }
+
+        // Then check if we have items in the priority buffer
         if let Some(mut buffer) = self.priority_buffer.try_lock() {
             if let Some(prioritized) = buffer.pop() {
                 return Some(prioritized.item);
@@ -219,9 +243,9 @@ impl WorkerQueue {
         }
 
         // Sort stolen items by priority and put all but the highest priority in the buffer
-        stolen_items.sort_by(|a, b| b.cmp(a)); // Highest priority first
+        stolen_items.sort(); // Highest priority last
 
-        // Split off the first item
+        // Split off the highest priority item
         if let Some(first) = stolen_items.pop() {
             // Put the rest in the buffer
             if !stolen_items.is_empty() {
@@ -236,3 +260,45 @@ impl WorkerQueue {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::types::FetchContext;
+    use crate::types::PageId;
+
+    fn retrieve_children(depth_remaining: u8) -> ExplorationStep {
+        ExplorationStep::RetrieveChildren {
+            parent_id: PageId::new_v4().into(),
+            context: FetchContext::new(depth_remaining, 100),
+        }
+    }
+
+    #[test]
+    fn test_child_database_steps_outrank_earlier_block_recursion() {
+        let (queue, workers) = ConcurrentWorkQueue::new(1);
+        queue.enqueue_multiple(vec![retrieve_children(1), retrieve_children(1)]);
+        queue.enqueue(ExplorationStep::CollectRows {
+            database_id: PageId::new_v4().into(),
+            context: FetchContext::new(1, 100),
+        });
+
+        let first = workers[0].dequeue(queue.stealers()).unwrap();
+        assert_eq!(first.kind(), "collect_rows");
+    }
+
+    #[test]
+    fn test_shallow_steps_run_before_deep_ones_of_the_same_kind() {
+        let (queue, workers) = ConcurrentWorkQueue::new(1);
+        queue.enqueue_multiple(vec![
+            retrieve_children(1),
+            retrieve_children(3),
+            retrieve_children(2),
+        ]);
+
+        let depths: Vec<u8> = std::iter::from_fn(|| workers[0].dequeue(queue.stealers()))
+            .map(|step| step.context().depth_remaining)
+            .collect();
+        assert_eq!(depths, vec![3, 2, 1]);
+    }
+}
@@ -4,49 +4,98 @@
 //! This module provides connection pooling and client management
 //! for optimized API performance.
 
+use crate::config::{HttpVersion, PoolConfig};
 use crate::error::AppError;
 use crate::types::ApiKey;
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
-use reqwest::{Client, ClientBuilder};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, ClientBuilder, Identity, Proxy};
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
 /// Global connection pool for HTTP clients
 static CONNECTION_POOL: Lazy<ConnectionPool> = Lazy::new(ConnectionPool::new);
 
-/// Connection pool configuration
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
-pub struct PoolConfig {
-    /// Maximum idle connections per host
-    pub max_idle_per_host: usize,
-    /// Connection timeout
-    pub connect_timeout: Duration,
-    /// Request timeout
-    pub request_timeout: Duration,
-    /// Pool timeout
-    pub pool_timeout: Duration,
-    /// Maximum connections
-    pub max_connections: usize,
-    /// Enable HTTP/2
-    pub http2_prior_knowledge: bool,
-    /// Enable connection keep-alive
-    pub tcp_keepalive: Option<Duration>,
-}
+impl PoolConfig {
+    /// A client builder with these pool, timeout, protocol, proxy, header and
+    /// client certificate settings.
+    ///
+    /// Responses are requested gzip- or deflate-compressed and decompressed
+    /// transparently.
+    pub fn client_builder(&self) -> Result<ClientBuilder, AppError> {
+        let mut builder = ClientBuilder::new()
+            .pool_max_idle_per_host(self.max_idle_per_host)
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.request_timeout)
+            .pool_idle_timeout(self.pool_timeout)
+            .tcp_keepalive(self.tcp_keepalive)
+            // Ask for compressed responses; large block listings shrink several-fold
+            .gzip(true)
+            .deflate(true);
+
+        builder = match self.http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1 => builder.http1_only(),
+            HttpVersion::Http2 => builder.http2_prior_knowledge(),
+        };
 
-impl Default for PoolConfig {
-    fn default() -> Self {
-        Self {
-            max_idle_per_host: 10,
-            connect_timeout: Duration::from_secs(10),
-            request_timeout: Duration::from_secs(30),
-            pool_timeout: Duration::from_secs(90),
-            max_connections: 100,
-            http2_prior_knowledge: true,
-            tcp_keepalive: Some(Duration::from_secs(60)),
+        // An explicit proxy replaces the one reqwest reads from the environment
+        if let Some(url) = &self.proxy {
+            let proxy = Proxy::all(url)
+                .map_err(|e| AppError::Validation(format!("Invalid proxy URL '{}': {}", url, e)))?;
+            builder = builder.proxy(proxy);
         }
+
+        if !self.headers.is_empty() {
+            builder = builder.default_headers(self.header_map()?);
+        }
+
+        if let Some(cert) = &self.client_cert {
+            builder = builder.identity(load_identity(cert, self.client_key.as_deref())?);
+        }
+
+        Ok(builder)
+    }
+
+    fn header_map(&self) -> Result<HeaderMap, AppError> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let invalid = |e: &dyn std::fmt::Display| {
+                AppError::MissingConfiguration(format!("Invalid header '{}': {}", name, e))
+            };
+            let header = HeaderName::from_bytes(name.as_bytes()).map_err(|e| invalid(&e))?;
+            let value = HeaderValue::from_str(value).map_err(|e| invalid(&e))?;
+            headers.append(header, value);
+        }
+        Ok(headers)
+    }
+}
+
+/// Reads a PEM client identity from `cert`, appending `key` when given.
+fn load_identity(cert: &Path, key: Option<&Path>) -> Result<Identity, AppError> {
+    let read = |path: &Path| {
+        std::fs::read(path).map_err(|e| {
+            AppError::MissingConfiguration(format!(
+                "Cannot read client certificate file {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    };
+    let mut pem = read(cert)?;
+    if let Some(key) = key {
+        pem.push(b'\n');
+        pem.extend(read(key)?);
     }
+    Identity::from_pem(&pem).map_err(|e| {
+        AppError::MissingConfiguration(format!(
+            "Invalid client certificate {}: {}",
+            cert.display(),
+            e
+        ))
+    })
 }
 
 /// Connection pool for managing HTTP clients
@@ -74,22 +123,8 @@ impl ConnectionPool {
     }
 
     /// Build a client with the given configuration
-    fn build_client(config: &PoolConfig) -> Result<Client, reqwest::Error> {
-        let mut builder = ClientBuilder::new()
-            .pool_max_idle_per_host(config.max_idle_per_host)
-            .connect_timeout(config.connect_timeout)
-            .timeout(config.request_timeout)
-            .pool_idle_timeout(config.pool_timeout);
-
-        if config.http2_prior_knowledge {
-            builder = builder.http2_prior_knowledge();
-        }
-
-        if let Some(keepalive) = config.tcp_keepalive {
-            builder = builder.tcp_keepalive(keepalive);
-        }
-
-        builder.build()
+    fn build_client(config: &PoolConfig) -> Result<Client, AppError> {
+        Ok(config.client_builder()?.build()?)
     }
 
     /// Get the default client
@@ -372,7 +407,62 @@ mod tests {
         let config = PoolConfig::default();
         assert_eq!(config.max_idle_per_host, 10);
         assert_eq!(config.max_connections, 100);
-        assert!(config.http2_prior_knowledge);
+        assert_eq!(config.http_version, HttpVersion::Auto);
+        assert!(config.proxy.is_none());
+    }
+
+    #[test]
+    fn test_pool_config_builds_clients_for_each_http_version_and_proxy() {
+        for http_version in [HttpVersion::Auto, HttpVersion::Http1, HttpVersion::Http2] {
+            let config = PoolConfig {
+                http_version,
+                tcp_keepalive: None,
+                proxy: Some("http://proxy.internal:3128".to_string()),
+                ..Default::default()
+            };
+            assert!(ConnectionPool::build_client(&config).is_ok());
+        }
+
+        let invalid = PoolConfig {
+            proxy: Some("not a proxy url".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            ConnectionPool::build_client(&invalid),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_pool_config_validates_headers_and_client_certificate() {
+        let config = PoolConfig {
+            headers: vec![("X-Corp-Trace".to_string(), "abc".to_string())],
+            ..Default::default()
+        };
+        assert!(ConnectionPool::build_client(&config).is_ok());
+
+        let bad_header = PoolConfig {
+            headers: vec![("X Corp Trace".to_string(), "abc".to_string())],
+            ..Default::default()
+        };
+        assert!(matches!(
+            ConnectionPool::build_client(&bad_header),
+            Err(AppError::MissingConfiguration(_))
+        ));
+
+        let cert = std::env::temp_dir().join(format!("n2p_cert_{}.pem", uuid::Uuid::new_v4()));
+        std::fs::write(&cert, "not a certificate").unwrap();
+        for path in [cert.clone(), cert.with_extension("missing")] {
+            let config = PoolConfig {
+                client_cert: Some(path),
+                ..Default::default()
+            };
+            assert!(matches!(
+                ConnectionPool::build_client(&config),
+                Err(AppError::MissingConfiguration(_))
+            ));
+        }
+        let _ = std::fs::remove_file(cert);
     }
 
     #[test]
@@ -1,10 +1,10 @@
 // src/api/fetch_queue.rs
 //! Work items for exploring a Notion content tree iteratively.
 
-use super::types::{FetchContext, FetchMetadata, FetchObjective, FetchRequest};
+use super::types::{FetchCategory, FetchContext, FetchMetadata, FetchObjective, FetchRequest};
 use crate::error::AppError;
-use crate::model::{Block, NotionObject, Page};
-use crate::types::{NotionId, Warning};
+use crate::model::{Block, DataSource, NotionObject, Page, UnfetchedBlocks};
+use crate::types::{NotionId, PageId, Warning, WarningLevel};
 use std::cmp::Ordering;
 use std::sync::Arc;
 
@@ -18,6 +18,10 @@ pub enum WorkPriority {
 }
 
 /// Work item with priority for the exploration queue.
+///
+/// Items order by priority, then by remaining depth, so shallow steps run
+/// before deep ones of the same kind, then first come first served. The
+/// greatest item runs first, as in a `BinaryHeap`.
 #[derive(Debug, Clone)]
 pub struct PrioritizedWorkItem {
     pub priority: WorkPriority,
@@ -25,9 +29,15 @@ pub struct PrioritizedWorkItem {
     pub item: ExplorationStep,
 }
 
+impl PrioritizedWorkItem {
+    fn depth_remaining(&self) -> u8 {
+        self.item.context().depth_remaining
+    }
+}
+
 impl PartialEq for PrioritizedWorkItem {
     fn eq(&self, other: &Self) -> bool {
-        self.priority == other.priority && self.sequence == other.sequence
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -41,11 +51,11 @@ impl PartialOrd for PrioritizedWorkItem {
 
 impl Ord for PrioritizedWorkItem {
     fn cmp(&self, other: &Self) -> Ordering {
-        // Higher priority first, then earlier sequence
-        match self.priority.cmp(&other.priority).reverse() {
-            Ordering::Equal => self.sequence.cmp(&other.sequence),
-            other => other,
-        }
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| self.depth_remaining().cmp(&other.depth_remaining()))
+            // Earlier sequence first
+            .then_with(|| other.sequence.cmp(&self.sequence))
     }
 }
 
@@ -76,6 +86,9 @@ pub enum ExplorationStep {
 
 impl ExplorationStep {
     /// Determines the priority of this exploration step.
+    ///
+    /// Child databases and their rows outrank block recursion, so a tight
+    /// `--limit` is spent on tables before deep paragraph trees.
     pub fn priority(&self) -> WorkPriority {
         match self {
             ExplorationStep::IdentifyAndExplore { request, .. } => match &request.objective {
@@ -90,6 +103,36 @@ impl ExplorationStep {
             ExplorationStep::FollowReferences { .. } => WorkPriority::Low,
         }
     }
+
+    /// The step's type, as recorded on its tracing span.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ExplorationStep::IdentifyAndExplore { .. } => "identify_and_explore",
+            ExplorationStep::RetrieveChildren { .. } => "retrieve_children",
+            ExplorationStep::FollowReferences { .. } => "follow_references",
+            ExplorationStep::CollectRows { .. } => "collect_rows",
+        }
+    }
+
+    /// The ID of the object this step fetches or explores.
+    pub fn target_id(&self) -> &str {
+        match self {
+            ExplorationStep::IdentifyAndExplore { request, .. } => request.id.as_str(),
+            ExplorationStep::RetrieveChildren { parent_id, .. } => parent_id.as_str(),
+            ExplorationStep::FollowReferences { block, .. } => block.id().as_str(),
+            ExplorationStep::CollectRows { database_id, .. } => database_id.as_str(),
+        }
+    }
+
+    /// The step's fetch context.
+    pub fn context(&self) -> &FetchContext {
+        match self {
+            ExplorationStep::IdentifyAndExplore { context, .. }
+            | ExplorationStep::RetrieveChildren { context, .. }
+            | ExplorationStep::FollowReferences { context, .. }
+            | ExplorationStep::CollectRows { context, .. } => context,
+        }
+    }
 }
 
 /// Result of processing an exploration step.
@@ -114,16 +157,29 @@ pub enum StepOutcome {
 #[allow(dead_code)]
 pub enum SkipReason {
     AlreadyVisited(NotionId),
+    /// An earlier root of the run already includes the object
+    CoveredByEarlierRoot(NotionId),
+    /// Another child database block of the fetch already resolves the database
+    DatabaseAlreadyResolved(NotionId),
     DepthExhausted,
-    ItemLimitReached,
+    BudgetExhausted(FetchCategory),
+    /// The estimated prompt already fills `--token-budget`
+    TokenBudgetReached,
 }
 
 impl std::fmt::Display for SkipReason {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             SkipReason::AlreadyVisited(id) => write!(f, "ID {} already visited", id),
+            SkipReason::CoveredByEarlierRoot(id) => {
+                write!(f, "ID {} already included by an earlier root", id)
+            }
+            SkipReason::DatabaseAlreadyResolved(id) => {
+                write!(f, "database {} already resolved by another block", id)
+            }
             SkipReason::DepthExhausted => write!(f, "maximum recursion depth reached"),
-            SkipReason::ItemLimitReached => write!(f, "item limit reached"),
+            SkipReason::BudgetExhausted(category) => write!(f, "{} budget exhausted", category),
+            SkipReason::TokenBudgetReached => write!(f, "token budget reached"),
         }
     }
 }
@@ -149,6 +205,13 @@ impl std::fmt::Display for FailureReason {
     }
 }
 
+impl FailureReason {
+    /// The warning a failed step leaves in the fetch result.
+    pub fn to_warning(&self) -> Warning {
+        Warning::new(WarningLevel::Warning, self.to_string())
+    }
+}
+
 /// A completed exploration step — the discovered content plus metadata.
 #[derive(Debug, Clone)]
 pub struct CompletedStep {
@@ -163,7 +226,7 @@ pub struct CompletedStep {
 pub enum DiscoveredContent {
     /// A single object was discovered
     Object {
-        object: NotionObject,
+        object: Box<NotionObject>,
         #[allow(dead_code)]
         children_to_fetch: Vec<NotionId>,
         /// For databases fetched via child_database blocks, this is the block ID
@@ -173,10 +236,19 @@ pub enum DiscoveredContent {
     Blocks {
         parent_id: NotionId,
         blocks: Vec<Block>,
+        /// Children `--limit` cut off after `blocks`
+        unfetched: Option<UnfetchedBlocks>,
     },
     /// Database rows were collected
     Rows {
         database_id: NotionId,
         pages: Vec<Page>,
+        /// Rows listed without their content, for lack of access
+        inaccessible: Vec<PageId>,
+    },
+    /// The data sources of a multi-source database were collected, with their rows
+    DataSources {
+        database_id: NotionId,
+        sources: Vec<DataSource>,
     },
 }
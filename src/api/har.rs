@@ -0,0 +1,335 @@
+// src/api/har.rs
+//! HTTP Archive (HAR 1.2) capture of Notion API traffic for `--har`.
+//!
+//! [`HarRecorder`] collects every request the HTTP client sends together
+//! with the response it got back, so odd API behavior can be inspected in
+//! a browser's network panel or attached to an issue. The API key never
+//! reaches the archive: authorization headers, and a gateway's
+//! `--auth-header`, are replaced by a marker.
+
+use crate::error::AppError;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use reqwest::header::{HeaderMap, HeaderName, AUTHORIZATION};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+/// Stands in for the value of every authorization header.
+const REDACTED: &str = "[REDACTED]";
+
+/// Collects request/response pairs and writes them as a HAR file.
+#[derive(Debug, Default)]
+pub struct HarRecorder {
+    entries: Mutex<Vec<HarEntry>>,
+}
+
+/// What was sent, captured before the request leaves the client.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    method: String,
+    url: String,
+    headers: Vec<HarHeader>,
+    body: Option<String>,
+}
+
+impl RecordedRequest {
+    /// Captures `request`, adding the redacted `auth_header` the client's
+    /// default headers will attach.
+    pub fn capture(request: &reqwest::Request, auth_header: &HeaderName) -> Self {
+        let mut headers = vec![HarHeader {
+            name: auth_header.as_str().to_string(),
+            value: REDACTED.to_string(),
+        }];
+        headers.extend(har_headers(request.headers()));
+        Self {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            headers,
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|bytes| String::from_utf8_lossy(bytes).into_owned()),
+        }
+    }
+}
+
+/// A successful exchange reduced to what a test fixture keeps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedExchange {
+    pub method: String,
+    pub url: String,
+    pub body: String,
+}
+
+/// What came back, with the body already read.
+#[derive(Debug, Clone)]
+pub struct RecordedResponse {
+    pub status: reqwest::StatusCode,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl HarRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records one exchange that started at `started` and took `elapsed`.
+    pub fn record(
+        &self,
+        started: DateTime<Utc>,
+        elapsed: Duration,
+        request: RecordedRequest,
+        response: RecordedResponse,
+    ) {
+        let time = elapsed.as_secs_f64() * 1000.0;
+        let query_string = url::Url::parse(&request.url)
+            .map(|url| {
+                url.query_pairs()
+                    .map(|(name, value)| HarHeader {
+                        name: name.into_owned(),
+                        value: value.into_owned(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let response_headers = har_headers(&response.headers);
+        let mime_type = response
+            .headers
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("application/json")
+            .to_string();
+
+        let entry = HarEntry {
+            started_date_time: started.to_rfc3339(),
+            time,
+            request: HarRequest {
+                body_size: request.body.as_ref().map_or(0, |body| body.len() as i64),
+                post_data: request.body.map(|text| HarPostData {
+                    mime_type: "application/json".to_string(),
+                    text,
+                }),
+                method: request.method,
+                url: request.url,
+                http_version: "HTTP/1.1".to_string(),
+                cookies: Vec::new(),
+                headers: request.headers,
+                query_string,
+                headers_size: -1,
+            },
+            response: HarResponse {
+                status: response.status.as_u16(),
+                status_text: response
+                    .status
+                    .canonical_reason()
+                    .unwrap_or_default()
+                    .to_string(),
+                http_version: "HTTP/1.1".to_string(),
+                cookies: Vec::new(),
+                headers: response_headers,
+                content: HarContent {
+                    size: response.body.len() as i64,
+                    mime_type,
+                    text: response.body.clone(),
+                },
+                redirect_url: String::new(),
+                headers_size: -1,
+                body_size: response.body.len() as i64,
+            },
+            cache: serde_json::Value::Object(Default::default()),
+            timings: HarTimings {
+                send: 0.0,
+                wait: time,
+                receive: 0.0,
+            },
+        };
+        self.entries.lock().push(entry);
+    }
+
+    /// How many exchanges were recorded.
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Whether nothing was recorded.
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The exchanges Notion answered with a 2xx status, in the order recorded.
+    pub fn successful_exchanges(&self) -> Vec<RecordedExchange> {
+        self.entries
+            .lock()
+            .iter()
+            .filter(|entry| (200..300).contains(&entry.response.status))
+            .map(|entry| RecordedExchange {
+                method: entry.request.method.clone(),
+                url: entry.request.url.clone(),
+                body: entry.response.content.text.clone(),
+            })
+            .collect()
+    }
+
+    /// Writes every recorded exchange to `path` as a HAR 1.2 document.
+    pub fn write(&self, path: &Path) -> Result<(), AppError> {
+        let entries = self.entries.lock();
+        let archive = serde_json::json!({
+            "log": {
+                "version": "1.2",
+                "creator": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "entries": &*entries,
+            }
+        });
+        std::fs::write(path, serde_json::to_string_pretty(&archive)? + "\n")?;
+        Ok(())
+    }
+}
+
+/// Headers as HAR name/value pairs, with authorization redacted.
+fn har_headers(headers: &HeaderMap) -> Vec<HarHeader> {
+    headers
+        .iter()
+        .map(|(name, value)| HarHeader {
+            name: name.as_str().to_string(),
+            value: if name == AUTHORIZATION {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            },
+        })
+        .collect()
+}
+
+// --- HAR 1.2 document types ---
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarEntry {
+    started_date_time: String,
+    time: f64,
+    request: HarRequest,
+    response: HarResponse,
+    cache: serde_json::Value,
+    timings: HarTimings,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarRequest {
+    method: String,
+    url: String,
+    http_version: String,
+    cookies: Vec<HarHeader>,
+    headers: Vec<HarHeader>,
+    query_string: Vec<HarHeader>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_data: Option<HarPostData>,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarResponse {
+    status: u16,
+    status_text: String,
+    http_version: String,
+    cookies: Vec<HarHeader>,
+    headers: Vec<HarHeader>,
+    content: HarContent,
+    #[serde(rename = "redirectURL")]
+    redirect_url: String,
+    headers_size: i64,
+    body_size: i64,
+}
+
+/// A name/value pair; HAR uses the same shape for headers, cookies and
+/// query parameters.
+#[derive(Debug, Clone, Serialize)]
+struct HarHeader {
+    name: String,
+    value: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarPostData {
+    mime_type: String,
+    text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarContent {
+    size: i64,
+    mime_type: String,
+    text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct HarTimings {
+    send: f64,
+    wait: f64,
+    receive: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_redacts_authorization_and_keeps_bodies() {
+        let client = reqwest::Client::new();
+        let request = client
+            .post("https://api.notion.com/v1/search?page_size=100")
+            .header(AUTHORIZATION, "Bearer secret_abc")
+            .header("Notion-Version", "2022-06-28")
+            .json(&serde_json::json!({ "page_size": 100 }))
+            .build()
+            .unwrap();
+        let recorder = HarRecorder::new();
+        recorder.record(
+            Utc::now(),
+            Duration::from_millis(120),
+            RecordedRequest::capture(&request, &AUTHORIZATION),
+            RecordedResponse {
+                status: reqwest::StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: r#"{"results":[]}"#.to_string(),
+            },
+        );
+        assert_eq!(
+            recorder.successful_exchanges(),
+            vec![RecordedExchange {
+                method: "POST".to_string(),
+                url: "https://api.notion.com/v1/search?page_size=100".to_string(),
+                body: r#"{"results":[]}"#.to_string(),
+            }]
+        );
+        let path = std::env::temp_dir().join(format!("n2p_{}.har", uuid::Uuid::new_v4()));
+
+        recorder.write(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(!text.contains("secret_abc"));
+        let har: serde_json::Value = serde_json::from_str(&text).unwrap();
+        let entry = &har["log"]["entries"][0];
+        assert_eq!(entry["request"]["method"], "POST");
+        assert_eq!(entry["request"]["queryString"][0]["name"], "page_size");
+        assert_eq!(entry["request"]["postData"]["text"], r#"{"page_size":100}"#);
+        assert_eq!(entry["response"]["content"]["text"], r#"{"results":[]}"#);
+        assert!(entry["request"]["headers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|h| h["name"] == "authorization")
+            .all(|h| h["value"] == REDACTED));
+    }
+}
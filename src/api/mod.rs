@@ -5,21 +5,28 @@
 //! with clear separation between I/O operations, parsing, and business logic.
 
 pub mod cache;
+mod checkpoint;
 pub mod client;
 mod concurrent_queue;
 mod connection_pool;
 mod fetch_queue;
+mod har;
 pub mod notion_client_adapter;
 pub mod object_graph;
+mod offline;
 mod parallel_fetcher;
 pub mod parser;
+mod resolve;
 mod responses;
+mod root_visits;
 mod simple_pagination;
+mod size_estimate;
 mod types;
 
 use crate::error::AppError;
 use crate::model::{Block, Database, Page};
 use crate::types::NotionId;
+use types::DatabaseRows;
 
 /// The ability to retrieve content from a Notion workspace.
 ///
@@ -31,35 +38,74 @@ pub trait NotionRepository: Send + Sync {
     async fn retrieve_database(&self, id: &NotionId) -> Result<Database, AppError>;
     async fn retrieve_block(&self, id: &NotionId) -> Result<Block, AppError>;
     async fn retrieve_children(&self, parent: &NotionId) -> Result<Vec<Block>, AppError>;
-    async fn query_rows(&self, database: &NotionId) -> Result<Vec<Page>, AppError>;
 
-    /// Resolves an object by trying page, then database, then block.
-    async fn resolve_object(&self, id: &NotionId) -> Result<crate::model::NotionObject, AppError> {
-        use crate::model::NotionObject;
+    /// Queries a database's rows. Pagination that fails after the first page
+    /// yields the rows fetched so far plus a warning rather than an error.
+    async fn query_rows(&self, database: &NotionId) -> Result<DatabaseRows, AppError>;
+
+    /// Lists a database's data sources (Notion API 2025-09).
+    ///
+    /// Repositories without data source support report none, and callers
+    /// fall back to `query_rows`.
+    async fn list_data_sources(
+        &self,
+        _database: &NotionId,
+    ) -> Result<Vec<DataSourceRef>, AppError> {
+        Ok(Vec::new())
+    }
+
+    /// Retrieves one data source's schema and queries its rows.
+    async fn query_data_source(&self, source: &DataSourceRef) -> Result<DataSourceRows, AppError> {
+        Err(AppError::InternalError {
+            message: format!(
+                "data source {} cannot be queried by this repository",
+                source.id.as_str()
+            ),
+            source: None,
+        })
+    }
 
-        // Try page first (most common)
-        if let Ok(page) = self.retrieve_page(id).await {
-            return Ok(NotionObject::Page(page));
-        }
+    /// Response payloads counted so far. Repositories that never touch the
+    /// network report nothing.
+    fn transfer_usage(&self) -> TransferUsage {
+        TransferUsage::default()
+    }
+
+    /// Counts a retry made `wait` after `error` in the transfer usage.
+    fn record_retry(&self, _error: &AppError, _wait: std::time::Duration) {}
+
+    /// Enumerates every page and database shared with the integration.
+    async fn search_shared(&self) -> Result<Vec<crate::model::NotionObject>, AppError>;
 
-        // Try database
-        if let Ok(db) = self.retrieve_database(id).await {
-            return Ok(NotionObject::Database(db));
-        }
+    /// Resolves an object by trying page, then database, then block.
+    ///
+    /// When every lookup fails, the error lists each attempt and whether it
+    /// was refused (403) or missed (404).
+    async fn resolve_object(&self, id: &NotionId) -> Result<crate::model::NotionObject, AppError> {
+        resolve::resolve_object(self, id).await
+    }
 
-        // Try block (last resort)
-        match self.retrieve_block(id).await {
-            Ok(block) => Ok(NotionObject::Block(block)),
-            Err(_) => Err(AppError::InvalidId(format!(
-                "Could not determine type for ID: {} (object not found or access denied)",
-                id.as_str()
-            ))),
-        }
+    /// Resolves the object a run starts from, like `resolve_object`; a
+    /// refused root is also looked for through its children and the search
+    /// index before giving up.
+    async fn resolve_root(&self, id: &NotionId) -> Result<crate::model::NotionObject, AppError> {
+        resolve::resolve_root(self, id).await
     }
 }
 
 // Re-export the public interface
 #[allow(unused_imports)]
-pub use cache::CachedNotionClient;
+pub use cache::{CacheMode, CachedNotionClient};
+pub use checkpoint::CheckpointedRepository;
 pub use client::NotionHttpClient;
+pub use har::HarRecorder;
+#[allow(unused_imports)] // Used by the library API
+pub use har::RecordedExchange;
+pub use offline::assemble_from_json;
 pub use parallel_fetcher::NotionFetcher;
+pub use root_visits::RootVisits;
+#[allow(unused_imports)] // Used by the library API
+pub use types::{
+    BudgetUsage, CategoryUsage, DataSourceRef, DataSourceRows, FetchCategory, FetchMetadata,
+    FetchResult, IntegrationIdentity, SizeUsage, TransferUsage,
+};
@@ -29,6 +29,7 @@ pub fn convert_page(notion_page: notion_client::objects::page::Page) -> Result<P
         properties: convert_page_properties(notion_page.properties)?,
         parent,
         archived: notion_page.archived,
+        unfetched_blocks: None,
     })
 }
 
@@ -57,9 +58,92 @@ pub fn convert_database(
         properties: convert_database_properties(notion_db.properties)?,
         parent,
         archived: notion_db.archived,
+        data_sources: Vec::new(), // Filled in during fetch for multi-source databases
+        inaccessible_rows: Vec::new(),
     })
 }
 
+/// Media payload shared by audio-style blocks: a file plus an optional caption.
+#[derive(serde::Deserialize)]
+struct MediaValue {
+    #[serde(flatten)]
+    file_type: notion_client::objects::file::File,
+    #[serde(default)]
+    caption: Vec<notion_client::objects::rich_text::RichText>,
+}
+
+/// Convert a raw block JSON object to our domain Block.
+///
+/// notion-client has no `audio` block type and its embed value drops the
+/// caption, so those two are handled here before delegating to
+/// [`convert_block`]. Audio blocks borrow notion-client's `unsupported`
+/// shape to parse the common fields, as do block types notion-client does
+/// not know. Blocks that end up unsupported carry the raw JSON and their API
+/// type name.
+pub fn convert_block_value(mut value: serde_json::Value) -> Result<Block, AppError> {
+    let block_type = value
+        .get("type")
+        .and_then(|t| t.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    match block_type.as_str() {
+        "audio" => {
+            let payload = value
+                .as_object_mut()
+                .and_then(|o| o.remove("audio"))
+                .unwrap_or_default();
+            value["type"] = serde_json::Value::from("unsupported");
+            let notion_block: notion_client::objects::block::Block = serde_json::from_value(value)?;
+            let audio: MediaValue = serde_json::from_value(payload)?;
+
+            Ok(Block::Audio(AudioBlock {
+                common: convert_block_common(&notion_block)?,
+                audio: convert_file_object(audio.file_type)?,
+                caption: convert_rich_text_array(audio.caption)?,
+            }))
+        }
+        "embed" => {
+            let caption = match value.pointer("/embed/caption") {
+                Some(raw) => serde_json::from_value(raw.clone())?,
+                None => Vec::new(),
+            };
+            let mut block = convert_block(serde_json::from_value(value)?)?;
+            if let Block::Embed(embed) = &mut block {
+                embed.caption = convert_rich_text_array(caption)?;
+            }
+            Ok(block)
+        }
+        _ => {
+            let notion_block = match serde::Deserialize::deserialize(&value) {
+                Ok(notion_block) => notion_block,
+                // notion-client rejects block types newer than it knows;
+                // parse the common fields through its `unsupported` shape.
+                Err(err) => {
+                    log::debug!(
+                        "Block type '{}' not parsed ({}), keeping it as unsupported",
+                        block_type,
+                        err
+                    );
+                    let mut common_only = value.clone();
+                    common_only["type"] = serde_json::Value::from("unsupported");
+                    serde_json::from_value(common_only).map_err(|_| err)?
+                }
+            };
+            let mut block = convert_block(notion_block)?;
+            // Keep what Notion actually sent, so the block can be reported
+            // and supported later. The fetch stage drops it unless asked.
+            if let Block::Unsupported(unsupported) = &mut block {
+                if !block_type.is_empty() {
+                    unsupported.block_type = block_type;
+                }
+                unsupported.raw = Some(value);
+            }
+            Ok(block)
+        }
+    }
+}
+
 /// Convert notion-client Block to our domain Block
 pub fn convert_block(
     notion_block: notion_client::objects::block::Block,
@@ -136,13 +220,21 @@ pub fn convert_block(
         BlockType::Code { code } => Ok(Block::Code(CodeBlock {
             common,
             content: convert_text_block_content(code.rich_text, Default::default())?,
-            language: format!("{:?}", code.language),
+            // Notion's own name (`plain text`, `c++`); formatting maps it to a fence identifier
+            language: serde_json::to_value(&code.language)?
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
             caption: convert_rich_text_array(code.caption)?,
         })),
 
         BlockType::Divider { .. } => Ok(Block::Divider(DividerBlock { common })),
 
-        BlockType::Breadcrumb { .. } => Ok(Block::Breadcrumb(BreadcrumbBlock { common })),
+        BlockType::Breadcrumb { .. } => Ok(Block::Breadcrumb(BreadcrumbBlock {
+            common,
+            ancestors: Vec::new(),
+            from_workspace: false,
+        })),
 
         BlockType::TableOfContents { .. } => {
             Ok(Block::TableOfContents(TableOfContentsBlock { common }))
@@ -157,6 +249,7 @@ pub fn convert_block(
         BlockType::Embed { embed } => Ok(Block::Embed(EmbedBlock {
             common,
             url: embed.url,
+            caption: Vec::new(), // EmbedValue drops the caption; see convert_block_value
         })),
 
         BlockType::Equation { equation } => Ok(Block::Equation(EquationBlock {
@@ -167,6 +260,7 @@ pub fn convert_block(
         BlockType::ChildPage { child_page } => Ok(Block::ChildPage(ChildPageBlock {
             common,
             title: child_page.title,
+            included_elsewhere: None,
         })),
 
         BlockType::Image { image } => Ok(Block::Image(ImageBlock {
@@ -266,6 +360,8 @@ pub fn convert_block(
         _ => Ok(Block::Unsupported(UnsupportedBlock {
             common,
             block_type: format!("{:?}", notion_block.block_type),
+            raw: None,
+            skipped_bytes: None,
         })),
     }
 }
@@ -309,6 +405,8 @@ fn convert_block_common(
         children: Vec::new(), // Will be populated during fetch stage
         has_children: notion_block.has_children.unwrap_or(false),
         archived: notion_block.archived.unwrap_or(false),
+        last_edited_time: notion_block.last_edited_time,
+        unfetched_children: None,
     })
 }
 
@@ -360,8 +458,7 @@ fn convert_rich_text(
             plain_text,
             href,
         } => {
-            // For link mentions that fallback, use the href if available
-            let mention_data = match convert_mention(mention) {
+            let mention_data = match convert_mention(mention, href.as_deref()) {
                 Ok(mention_data) => mention_data,
                 Err(_) => {
                     // If mention conversion fails, use href as fallback if available
@@ -369,7 +466,13 @@ fn convert_rich_text(
                         .clone()
                         .unwrap_or_else(|| "https://notion.so".to_string());
                     crate::types::MentionData {
-                        mention_type: crate::types::MentionType::LinkMention { url },
+                        mention_type: crate::types::MentionType::LinkMention {
+                            link_mention: crate::types::LinkMentionReference {
+                                href: url,
+                                title: None,
+                                description: None,
+                            },
+                        },
                     }
                 }
             };
@@ -425,11 +528,18 @@ fn convert_annotations(
     }
 }
 
-/// Convert mention with graceful fallback for unsupported types
+/// Convert mention
+///
+/// `href` is the link Notion attaches to the rich text item; for database
+/// mentions it carries the view being referenced, if any.
 fn convert_mention(
     mention: notion_client::objects::rich_text::Mention,
+    href: Option<&str>,
 ) -> Result<crate::types::MentionData, AppError> {
-    use notion_client::objects::rich_text::Mention as NcMention;
+    use crate::types::TemplateMentionValue;
+    use notion_client::objects::rich_text::{
+        Mention as NcMention, TemplateMention, TemplateMentionDate,
+    };
 
     match mention {
         NcMention::User { user } => Ok(crate::types::MentionData {
@@ -448,6 +558,7 @@ fn convert_mention(
             mention_type: crate::types::MentionType::Database {
                 database: crate::types::DatabaseReference {
                     id: crate::types::NotionId::parse(&database.id)?,
+                    view_id: href.and_then(view_id_from_href),
                 },
             },
         }),
@@ -473,18 +584,44 @@ fn convert_mention(
                 },
             },
         }),
-        // Graceful fallback for any other mention types that might exist
-        _ => {
-            log::debug!("Unsupported mention type encountered, will use href from parent context");
-            // Return an error so the parent context can use href
-            Err(NotionClientError::ConversionError {
-                message: "Unsupported mention type".to_string(),
-            }
-            .into())
-        }
+        NcMention::LinkMention { link_mention } => Ok(crate::types::MentionData {
+            mention_type: crate::types::MentionType::LinkMention {
+                link_mention: crate::types::LinkMentionReference {
+                    href: link_mention
+                        .href
+                        .or_else(|| href.map(str::to_string))
+                        .ok_or_else(|| NotionClientError::ConversionError {
+                            message: "Link mention has no URL".to_string(),
+                        })?,
+                    title: Some(link_mention.title).filter(|t| !t.is_empty()),
+                    description: link_mention.description,
+                },
+            },
+        }),
+        NcMention::TemplateMention { template_mention } => Ok(crate::types::MentionData {
+            mention_type: crate::types::MentionType::TemplateMention {
+                template_mention: match template_mention {
+                    TemplateMention::TemplateMentionDate {
+                        template_mention_date: TemplateMentionDate::Today,
+                    } => TemplateMentionValue::Today,
+                    TemplateMention::TemplateMentionDate {
+                        template_mention_date: TemplateMentionDate::Now,
+                    } => TemplateMentionValue::Now,
+                    TemplateMention::TemplateMentionUser { .. } => TemplateMentionValue::Me,
+                },
+            },
+        }),
     }
 }
 
+/// Extracts the view ID from a database link such as
+/// `https://www.notion.so/<database>?v=<view>`.
+fn view_id_from_href(href: &str) -> Option<crate::types::NotionId> {
+    let url = url::Url::parse(href).ok()?;
+    let (_, view) = url.query_pairs().find(|(key, _)| key == "v")?;
+    crate::types::NotionId::parse(&view).ok()
+}
+
 /// Convert user
 fn convert_user(
     user: notion_client::objects::user::User,
@@ -653,7 +790,7 @@ fn convert_page_properties(
 }
 
 /// Convert database properties with graceful fallbacks
-fn convert_database_properties(
+pub fn convert_database_properties(
     properties: HashMap<String, notion_client::objects::database::DatabaseProperty>,
 ) -> Result<HashMap<PropertyName, crate::model::DatabaseProperty>, AppError> {
     let mut converted = HashMap::new();
@@ -6,9 +6,9 @@
 //!   2. Assemble the tree by walking parent→child edges
 //!   3. Embed databases into their ChildDatabaseBlock hosts
 
-use crate::model::{Block, Database, NotionObject, Page};
-use crate::types::NotionId;
-use std::collections::HashMap;
+use crate::model::{Block, DataSource, Database, NotionObject, Page, Parent, UnfetchedBlocks};
+use crate::types::{NotionId, PageId};
+use std::collections::{HashMap, HashSet};
 
 /// Immutable graph representing parent-child relationships between Notion objects.
 #[derive(Debug, Clone)]
@@ -23,6 +23,12 @@ pub struct ObjectGraph {
     database_locations: HashMap<NotionId, DatabaseLocation>,
     /// Maps child database block IDs to actual database IDs
     child_db_block_to_database: HashMap<NotionId, NotionId>,
+    /// Data sources of multi-source databases, keyed by database ID
+    data_sources: HashMap<NotionId, Vec<DataSource>>,
+    /// Rows of each database the integration may not read
+    inaccessible_rows: HashMap<NotionId, Vec<PageId>>,
+    /// Children `--limit` cut off, keyed by parent ID
+    unfetched: HashMap<NotionId, UnfetchedBlocks>,
 }
 
 /// Tracks where a database was found in the object tree
@@ -68,6 +74,9 @@ impl ObjectGraph {
             parents: HashMap::with_capacity(expected_objects),
             database_locations: HashMap::with_capacity(expected_objects / 10),
             child_db_block_to_database: HashMap::with_capacity(expected_objects / 20),
+            data_sources: HashMap::new(),
+            inaccessible_rows: HashMap::new(),
+            unfetched: HashMap::new(),
         }
     }
 
@@ -120,14 +129,16 @@ impl ObjectGraph {
                 .insert(block_id.clone(), id.clone());
         }
 
-        // Wire up parent-child relationship if already tracked from a ChildDatabaseBlock
+        // Wire up parent-child relationship if already tracked from a ChildDatabaseBlock,
+        // which is tracked under the block's ID when it differs from the database's
+        let block_id = source_id.as_ref().unwrap_or(id);
         let needs_relationship = graph
             .database_locations
-            .get(id)
+            .get(block_id)
             .is_some_and(|loc| loc.parent_type == DatabaseParentType::ChildDatabaseBlock);
 
         if needs_relationship {
-            if let Some(location) = graph.database_locations.get(id) {
+            if let Some(location) = graph.database_locations.get(block_id) {
                 let parent_id = location.parent_id.clone();
                 graph = graph.with_relationship(parent_id, id.clone());
             }
@@ -168,6 +179,9 @@ impl ObjectGraph {
             parents,
             database_locations: self.database_locations,
             child_db_block_to_database: self.child_db_block_to_database,
+            data_sources: self.data_sources,
+            inaccessible_rows: self.inaccessible_rows,
+            unfetched: self.unfetched,
         }
     }
 
@@ -213,11 +227,45 @@ impl ObjectGraph {
         })
     }
 
+    /// Records the rows of a database that were listed without their content.
+    pub fn with_inaccessible_rows(mut self, database_id: NotionId, rows: Vec<PageId>) -> Self {
+        if !rows.is_empty() {
+            self.inaccessible_rows.insert(database_id, rows);
+        }
+        self
+    }
+
+    /// Records that `--limit` cut off children of `parent_id`.
+    pub fn with_unfetched(
+        mut self,
+        parent_id: NotionId,
+        unfetched: Option<UnfetchedBlocks>,
+    ) -> Self {
+        if let Some(unfetched) = unfetched {
+            self.unfetched.insert(parent_id, unfetched);
+        }
+        self
+    }
+
+    /// Adds the data sources of a multi-source database, rows included.
+    pub fn with_data_sources(mut self, database_id: NotionId, sources: Vec<DataSource>) -> Self {
+        self.data_sources.insert(database_id, sources);
+        self
+    }
+
     // --- Assembly: walk edges to build the tree ---
 
     /// Assembles the complete object tree starting from a root ID.
+    ///
+    /// A database shown by several child database blocks is embedded in the
+    /// first; the others refer to it (see [`reference_repeated_views`]).
     pub fn assemble(&self, root_id: &NotionId) -> Result<NotionObject, String> {
-        self.assemble_recursive(root_id, &mut Vec::new())
+        let mut root = self.assemble_recursive(root_id, &mut Vec::new())?;
+        let repeated = reference_repeated_views(&mut root, &self.child_db_block_to_database);
+        if repeated > 0 {
+            log::debug!("{} repeated database views refer to their first", repeated);
+        }
+        Ok(root)
     }
 
     /// Gets database location information.
@@ -246,10 +294,37 @@ impl ObjectGraph {
             return Err(format!("Cycle detected at ID: {}", id.as_str()));
         }
 
-        let object = self
+        let mut object = self
             .objects
             .get(id)
-            .ok_or_else(|| format!("Object not found: {}", id.as_str()))?;
+            .ok_or_else(|| format!("Object not found: {}", id.as_str()))?
+            .clone();
+        match &mut object {
+            NotionObject::Database(db) => {
+                if let Some(rows) = self.inaccessible_rows.get(id) {
+                    db.inaccessible_rows = rows.clone();
+                }
+                if let Some(sources) = self.data_sources.get(id) {
+                    db.data_sources = sources.clone();
+                    // Data source rows live outside the edges; attach any blocks fetched for them
+                    for row in db.data_sources.iter_mut().flat_map(|s| &mut s.pages) {
+                        if let Some(child_ids) = self.children.get(&row.id.clone().into()) {
+                            let children = child_ids
+                                .iter()
+                                .map(|child_id| self.assemble_recursive(child_id, stack))
+                                .collect::<Result<Vec<_>, _>>()?;
+                            self.attach_children_to_page(row, children);
+                        }
+                    }
+                }
+            }
+            NotionObject::Block(Block::Breadcrumb(breadcrumb)) => {
+                let (ancestors, from_workspace) = self.ancestor_titles(id);
+                breadcrumb.ancestors = ancestors;
+                breadcrumb.from_workspace = from_workspace;
+            }
+            _ => {}
+        }
 
         stack.push(id.clone());
 
@@ -259,14 +334,57 @@ impl ObjectGraph {
                 .map(|child_id| self.assemble_recursive(child_id, stack))
                 .collect::<Result<Vec<_>, _>>()?;
 
-            self.assemble_with_children(object.clone(), children)
+            self.assemble_with_children(object, children)
         } else {
-            Ok(object.clone())
+            Ok(object)
         };
 
         stack.pop();
 
-        result
+        let mut result = result?;
+        if let Some(unfetched) = self.unfetched.get(id) {
+            match &mut result {
+                NotionObject::Page(page) => page.unfetched_blocks = Some(*unfetched),
+                NotionObject::Block(block) => {
+                    block.common_mut().unfetched_children = Some(*unfetched)
+                }
+                NotionObject::Database(_) => {}
+            }
+        }
+        Ok(result)
+    }
+
+    /// Titles of the pages and databases above `id`, outermost first, and
+    /// whether the outermost one sits at the top level of the workspace.
+    ///
+    /// Only fetched objects are known, so the trail stops at the fetch root.
+    fn ancestor_titles(&self, id: &NotionId) -> (Vec<String>, bool) {
+        let mut titles = Vec::new();
+        let mut from_workspace = false;
+        let mut seen = HashSet::new();
+        let mut current = self.parents.get(id);
+
+        while let Some(parent_id) = current {
+            if !seen.insert(parent_id) {
+                break;
+            }
+            if let Some(object) = self.objects.get(parent_id) {
+                // Blocks between pages (toggles, columns) are not part of the trail
+                let upward = match object {
+                    NotionObject::Page(page) => Some(page.parent.as_ref()),
+                    NotionObject::Database(db) => Some(db.parent.as_ref()),
+                    NotionObject::Block(_) => None,
+                };
+                if let Some(upward) = upward {
+                    titles.push(object.display_title());
+                    from_workspace = matches!(upward, Some(Parent::Workspace));
+                }
+            }
+            current = self.parents.get(parent_id);
+        }
+
+        titles.reverse();
+        (titles, from_workspace)
     }
 
     // --- Nesting: embed databases into their ChildDatabaseBlock hosts ---
@@ -353,7 +471,11 @@ impl ObjectGraph {
                         // Ensure a ChildDatabaseBlock exists in the blocks list
                         let block_exists = blocks.iter().any(|b| {
                             if let Block::ChildDatabase(cdb) = b {
-                                cdb.common.id.as_str() == db_id.as_str()
+                                let block_id: NotionId = cdb.common.id.clone().into();
+                                self.child_db_block_to_database
+                                    .get(&block_id)
+                                    .unwrap_or(&block_id)
+                                    == &db_id
                             } else {
                                 false
                             }
@@ -419,6 +541,8 @@ fn recreate_child_database_block(
             children: Vec::new(),
             has_children: false,
             archived: false,
+            last_edited_time: None,
+            unfetched_children: None,
         },
         title: database.title().as_plain_text(),
         content: crate::model::blocks::ChildDatabaseContent::NotFetched,
@@ -503,3 +627,202 @@ fn embed_database_if_present(
 
     block
 }
+
+/// Keeps the first child database block of each database, in document
+/// order, and turns later blocks showing the same database into
+/// references to it. Returns how many blocks became references.
+///
+/// Blocks are matched to databases through `block_to_db`, falling back to
+/// the block's own ID, which is the database's for inline databases.
+pub(crate) fn reference_repeated_views(
+    object: &mut NotionObject,
+    block_to_db: &HashMap<NotionId, NotionId>,
+) -> usize {
+    let mut shown = HashMap::new();
+    match object {
+        NotionObject::Page(page) => reference_in_blocks(&mut page.blocks, block_to_db, &mut shown),
+        NotionObject::Database(db) => {
+            shown.insert(NotionId::from(&db.id), db.title().as_plain_text());
+            reference_in_database(db, block_to_db, &mut shown)
+        }
+        NotionObject::Block(block) => {
+            reference_in_blocks(std::slice::from_mut(block), block_to_db, &mut shown)
+        }
+    }
+}
+
+fn reference_in_database(
+    db: &mut Database,
+    block_to_db: &HashMap<NotionId, NotionId>,
+    shown: &mut HashMap<NotionId, String>,
+) -> usize {
+    let rows = db
+        .pages
+        .iter_mut()
+        .chain(db.data_sources.iter_mut().flat_map(|s| &mut s.pages));
+    rows.map(|row| reference_in_blocks(&mut row.blocks, block_to_db, shown))
+        .sum()
+}
+
+fn reference_in_blocks(
+    blocks: &mut [Block],
+    block_to_db: &HashMap<NotionId, NotionId>,
+    shown: &mut HashMap<NotionId, String>,
+) -> usize {
+    use crate::model::blocks::ChildDatabaseContent;
+
+    let mut referenced = 0;
+    for block in blocks {
+        if let Block::ChildDatabase(child) = block {
+            let block_id = NotionId::from(&child.common.id);
+            let db_id = match &child.content {
+                ChildDatabaseContent::Fetched(db) => Some(NotionId::from(&db.id)),
+                ChildDatabaseContent::NotFetched => {
+                    Some(block_to_db.get(&block_id).unwrap_or(&block_id).clone())
+                }
+                _ => None,
+            };
+            if let Some(db_id) = db_id {
+                if let Some(title) = shown.get(&db_id) {
+                    child.content = ChildDatabaseContent::ShownAbove {
+                        title: title.clone(),
+                    };
+                    referenced += 1;
+                } else if let ChildDatabaseContent::Fetched(db) = &mut child.content {
+                    shown.insert(db_id, child.title.clone());
+                    referenced += reference_in_database(db, block_to_db, shown);
+                }
+            }
+        }
+        referenced += reference_in_blocks(block.children_mut(), block_to_db, shown);
+    }
+    referenced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::blocks::{ChildDatabaseBlock, ChildDatabaseContent};
+    use crate::model::{BlockCommon, BreadcrumbBlock};
+    use crate::types::{BlockId, DatabaseId, PageId};
+
+    fn page(id: &PageId, title: &str, parent: Parent) -> NotionObject {
+        NotionObject::Page(Page::new(id.clone(), title).with_parent(parent))
+    }
+
+    fn breadcrumb(id: &BlockId) -> Block {
+        Block::Breadcrumb(BreadcrumbBlock {
+            common: BlockCommon {
+                id: id.clone(),
+                children: vec![],
+                has_children: false,
+                archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
+            },
+            ancestors: vec![],
+            from_workspace: false,
+        })
+    }
+
+    fn assembled_breadcrumb(graph: &ObjectGraph, page_id: &PageId) -> BreadcrumbBlock {
+        match graph.assemble(&NotionId::from(page_id)).unwrap() {
+            NotionObject::Page(page) => match page.blocks.into_iter().next() {
+                Some(Block::Breadcrumb(breadcrumb)) => breadcrumb,
+                other => panic!("expected a breadcrumb, got {:?}", other),
+            },
+            other => panic!("expected a page, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_breadcrumbs_follow_fetched_ancestors() {
+        let root_id = PageId::new_v4();
+        let child_id = PageId::new_v4();
+        let crumb_id = BlockId::new_v4();
+
+        let graph = ObjectGraph::new()
+            .with_object(page(&root_id, "Projects", Parent::Workspace))
+            .with_object(page(
+                &child_id,
+                "Roadmap",
+                Parent::Page {
+                    page_id: root_id.clone(),
+                },
+            ))
+            .with_relationship(NotionId::from(&root_id), NotionId::from(&child_id))
+            .with_blocks(NotionId::from(&child_id), vec![breadcrumb(&crumb_id)]);
+
+        let crumb = assembled_breadcrumb(&graph, &child_id);
+        assert_eq!(crumb.ancestors, vec!["Projects", "Roadmap"]);
+        assert!(crumb.from_workspace);
+    }
+
+    #[test]
+    fn test_breadcrumbs_stop_at_the_fetch_root() {
+        let page_id = PageId::new_v4();
+        let crumb_id = BlockId::new_v4();
+
+        let graph = ObjectGraph::new()
+            .with_object(page(
+                &page_id,
+                "Roadmap",
+                Parent::Page {
+                    page_id: PageId::new_v4(),
+                },
+            ))
+            .with_blocks(NotionId::from(&page_id), vec![breadcrumb(&crumb_id)]);
+
+        let crumb = assembled_breadcrumb(&graph, &page_id);
+        assert_eq!(crumb.ancestors, vec!["Roadmap"]);
+        assert!(!crumb.from_workspace);
+    }
+
+    fn child_database(id: &BlockId, title: &str) -> Block {
+        Block::ChildDatabase(ChildDatabaseBlock {
+            common: BlockCommon::new(id.clone()),
+            title: title.to_string(),
+            content: ChildDatabaseContent::NotFetched,
+        })
+    }
+
+    #[test]
+    fn test_repeated_database_views_refer_to_the_first() {
+        let page_id = PageId::new_v4();
+        let (table, board) = (BlockId::new_v4(), BlockId::new_v4());
+        let db_id = DatabaseId::new_v4();
+        let database = NotionObject::Database(Database::new(db_id.clone(), "Tasks"));
+
+        let graph = ObjectGraph::new()
+            .with_object(page(&page_id, "Roadmap", Parent::Workspace))
+            .with_blocks(
+                NotionId::from(&page_id),
+                vec![
+                    child_database(&table, "Tasks"),
+                    child_database(&board, "Tasks board"),
+                ],
+            )
+            .with_object_from_source(database.clone(), Some(NotionId::from(&table)))
+            .with_object_from_source(database, Some(NotionId::from(&board)));
+
+        let NotionObject::Page(page) = graph.assemble(&NotionId::from(&page_id)).unwrap() else {
+            panic!("expected a page");
+        };
+        let contents: Vec<_> = page
+            .blocks
+            .iter()
+            .map(|block| match block {
+                Block::ChildDatabase(child) => &child.content,
+                other => panic!("unexpected block: {:?}", other),
+            })
+            .collect();
+        assert_eq!(contents.len(), 2);
+        assert!(matches!(contents[0], ChildDatabaseContent::Fetched(db) if db.id == db_id));
+        assert_eq!(
+            contents[1],
+            &ChildDatabaseContent::ShownAbove {
+                title: "Tasks".to_string()
+            }
+        );
+    }
+}
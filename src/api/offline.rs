@@ -0,0 +1,370 @@
+// src/api/offline.rs
+//! Content assembled from API responses fetched elsewhere, for
+//! `notion2prompt render`.
+//!
+//! The input is one or more JSON values as the Notion API returns them: a
+//! page, database or block, or a list of them (`{"object": "list",
+//! "results": [...]}` or a bare array). They are parsed exactly like fetched
+//! responses and put back together by their `parent` fields:
+//!
+//! - the first page or database is the root;
+//! - blocks go to the page or block their parent names, or to the root page
+//!   when that is not part of the input; a block may also carry its
+//!   children inline in a `children` array;
+//! - pages whose parent is a database in the input become its rows;
+//! - a database whose ID matches a `child_database` block is embedded there,
+//!   in the first such block; later ones refer to it.
+//!
+//! So `cat page.json blocks.json | notion2prompt render --stdin-content`
+//! renders a page fetched with two requests. With no page or database, the
+//! blocks render as an untitled page (or as the one block given).
+
+use super::notion_client_adapter::convert_block_value;
+use super::object_graph::reference_repeated_views;
+use super::parser::attribute_data_source_row_to_database;
+use super::responses::{NotionDatabase, NotionPage, ToDomain};
+use crate::error::AppError;
+use crate::model::blocks::ChildDatabaseContent;
+use crate::model::{Block, Database, NotionObject, Page, PageTitle, Parent};
+use crate::types::PageId;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Assembles the content described by the JSON values in `text`.
+pub fn assemble_from_json(text: &str) -> Result<NotionObject, AppError> {
+    let mut input = Input::default();
+    for value in serde_json::Deserializer::from_str(text).into_iter::<Value>() {
+        let value =
+            value.map_err(|e| AppError::Validation(format!("input is not valid JSON: {}", e)))?;
+        input.add(value)?;
+    }
+    input.assemble()
+}
+
+/// A page or database from the input.
+enum Container {
+    Page(Page),
+    Database(Database),
+}
+
+#[derive(Default)]
+struct Input {
+    /// Pages and databases in input order
+    containers: Vec<Container>,
+    /// Blocks in input order, with the ID of the page or block they belong to
+    blocks: Vec<(Option<String>, Block)>,
+    /// The first page a block's `parent` names, to stand in for a missing root
+    first_parent_page: Option<PageId>,
+}
+
+impl Input {
+    fn add(&mut self, mut value: Value) -> Result<(), AppError> {
+        if let Value::Array(items) = value {
+            return items.into_iter().try_for_each(|item| self.add(item));
+        }
+        match value.get("object").and_then(Value::as_str) {
+            Some("list") => match value.get_mut("results").map(Value::take) {
+                Some(results) => self.add(results),
+                None => Err(AppError::Validation(
+                    "input list has no 'results' array".to_string(),
+                )),
+            },
+            Some("page") => {
+                attribute_data_source_row_to_database(&mut value);
+                let page: NotionPage = serde_json::from_value(value)?;
+                self.containers
+                    .push(Container::Page(ToDomain::to_domain(page)?));
+                Ok(())
+            }
+            Some("database") => {
+                let database: NotionDatabase = serde_json::from_value(value)?;
+                self.containers
+                    .push(Container::Database(ToDomain::to_domain(database)?));
+                Ok(())
+            }
+            Some("block") => self.add_block(value, None),
+            other => Err(AppError::Validation(format!(
+                "input holds {} where a Notion page, database, block or list was expected",
+                other.map_or("a value without an 'object' field".to_string(), |o| {
+                    format!("an object of type '{}'", o)
+                })
+            ))),
+        }
+    }
+
+    /// Adds a block and its inline children, under `parent` when given and
+    /// under the block's own `parent` field otherwise.
+    fn add_block(&mut self, mut value: Value, parent: Option<String>) -> Result<(), AppError> {
+        if self.first_parent_page.is_none() {
+            self.first_parent_page = value
+                .pointer("/parent/page_id")
+                .and_then(Value::as_str)
+                .and_then(|id| PageId::parse(id).ok());
+        }
+        let parent = parent.or_else(|| parent_key(&value));
+        let children = value
+            .as_object_mut()
+            .and_then(|block| block.remove("children"));
+        let block = convert_block_value(value)?;
+        let id = key(block.id().as_str());
+        self.blocks.push((parent, block));
+
+        if let Some(Value::Array(children)) = children {
+            for child in children {
+                self.add_block(child, Some(id.clone()))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn assemble(self) -> Result<NotionObject, AppError> {
+        let mut known: HashSet<String> = self
+            .blocks
+            .iter()
+            .map(|(_, block)| key(block.id().as_str()))
+            .collect();
+        let mut containers = self.containers.into_iter();
+        let root = containers.next();
+
+        let mut tree = Tree::default();
+        for container in containers {
+            match container {
+                Container::Page(page) => {
+                    known.insert(key(page.id.as_str()));
+                    match &page.parent {
+                        Some(Parent::Database { database_id }) => tree
+                            .rows
+                            .entry(key(database_id.as_str()))
+                            .or_default()
+                            .push(page),
+                        _ => log::warn!(
+                            "Ignoring page '{}': it is not a row of a database in the input",
+                            page.title.as_str()
+                        ),
+                    }
+                }
+                Container::Database(database) => {
+                    tree.databases.insert(key(database.id.as_str()), database);
+                }
+            }
+        }
+        if let Some(Container::Page(page)) = &root {
+            known.insert(key(page.id.as_str()));
+        }
+
+        let mut orphans = Vec::new();
+        for (parent, block) in self.blocks {
+            match parent.filter(|parent| known.contains(parent)) {
+                Some(parent) => tree.children.entry(parent).or_default().push(block),
+                None => orphans.push(block),
+            }
+        }
+
+        let mut object = match root {
+            Some(Container::Page(mut page)) => {
+                tree.fill_page(&mut page);
+                page.blocks.extend(tree.attach_all(orphans));
+                NotionObject::Page(page)
+            }
+            Some(Container::Database(mut database)) => {
+                if !orphans.is_empty() {
+                    log::warn!(
+                        "Ignoring {} block(s) whose parent is not in the input",
+                        orphans.len()
+                    );
+                }
+                tree.fill_database(&mut database);
+                NotionObject::Database(database)
+            }
+            None if orphans.len() == 1 => {
+                let mut blocks = tree.attach_all(orphans);
+                NotionObject::Block(blocks.remove(0))
+            }
+            None if orphans.is_empty() => {
+                return Err(AppError::Validation(
+                    "input holds no page, database or block".to_string(),
+                ))
+            }
+            None => NotionObject::Page(Page {
+                id: self.first_parent_page.unwrap_or_else(PageId::new_v4),
+                title: PageTitle::new("Untitled"),
+                url: String::new(),
+                blocks: tree.attach_all(orphans),
+                properties: Default::default(),
+                parent: None,
+                archived: false,
+                unfetched_blocks: None,
+            }),
+        };
+
+        let unplaced = tree.children.values().map(Vec::len).sum::<usize>()
+            + tree.rows.values().map(Vec::len).sum::<usize>();
+        if unplaced > 0 {
+            log::warn!(
+                "Ignoring {} object(s) that are not part of the root's content",
+                unplaced
+            );
+        }
+        reference_repeated_views(&mut object, &HashMap::new());
+        Ok(object)
+    }
+}
+
+/// Input objects waiting for their place in the tree, keyed by parent.
+#[derive(Default)]
+struct Tree {
+    children: HashMap<String, Vec<Block>>,
+    rows: HashMap<String, Vec<Page>>,
+    databases: HashMap<String, Database>,
+}
+
+impl Tree {
+    fn fill_page(&mut self, page: &mut Page) {
+        let blocks = self
+            .children
+            .remove(&key(page.id.as_str()))
+            .unwrap_or_default();
+        page.blocks.extend(self.attach_all(blocks));
+    }
+
+    fn fill_database(&mut self, database: &mut Database) {
+        for mut row in self
+            .rows
+            .remove(&key(database.id.as_str()))
+            .unwrap_or_default()
+        {
+            self.fill_page(&mut row);
+            database.pages.push(row);
+        }
+    }
+
+    fn attach_all(&mut self, mut blocks: Vec<Block>) -> Vec<Block> {
+        for block in &mut blocks {
+            self.attach(block);
+        }
+        blocks
+    }
+
+    /// Gives `block` its children from the input, and a `child_database`
+    /// block its database.
+    fn attach(&mut self, block: &mut Block) {
+        let id = key(block.id().as_str());
+        if let Block::ChildDatabase(child) = block {
+            if let Some(mut database) = self.databases.remove(&id) {
+                self.fill_database(&mut database);
+                child.content = ChildDatabaseContent::Fetched(Box::new(database));
+            }
+        }
+        if let Some(children) = self.children.remove(&id) {
+            let children = self.attach_all(children);
+            block.common_mut().has_children = true;
+            block.children_mut().extend(children);
+        }
+    }
+}
+
+/// The ID a block's `parent` field names, if it is a page or block.
+fn parent_key(block: &Value) -> Option<String> {
+    let parent = block.get("parent")?;
+    ["block_id", "page_id"]
+        .iter()
+        .find_map(|field| parent.get(*field).and_then(Value::as_str))
+        .map(key)
+}
+
+/// A Notion ID in one form, however it was written.
+fn key(id: &str) -> String {
+    id.replace('-', "").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        let path = format!(
+            "{}/tests/fixtures/api_responses/{}.json",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_page_blocks_database_and_rows_assemble_into_one_tree() {
+        let text = [
+            "page_aie_agents_nyc",
+            "blocks_aie_agents_nyc",
+            "database_key_highlights",
+            "pages_key_highlights",
+        ]
+        .map(fixture)
+        .join("\n");
+
+        let NotionObject::Page(page) = assemble_from_json(&text).unwrap() else {
+            panic!("expected a page");
+        };
+        assert_eq!(page.blocks.len(), 3);
+        let Block::ChildDatabase(child) = &page.blocks[0] else {
+            panic!("expected the child database first");
+        };
+        let ChildDatabaseContent::Fetched(database) = &child.content else {
+            panic!("expected the database to be embedded");
+        };
+        assert_eq!(database.pages.len(), 3);
+    }
+
+    #[test]
+    fn test_blocks_alone_render_as_an_untitled_page() {
+        let NotionObject::Page(page) =
+            assemble_from_json(&fixture("blocks_aie_agents_nyc")).unwrap()
+        else {
+            panic!("expected a page");
+        };
+        assert_eq!(page.title.as_str(), "Untitled");
+        assert_eq!(
+            key(page.id.as_str()),
+            key("1abcd412-8533-8084-9d72-c1cd98f9e8ef")
+        );
+        assert_eq!(page.blocks.len(), 3);
+    }
+
+    #[test]
+    fn test_inline_children_nest_under_their_block() {
+        let text = r#"{
+            "object": "block",
+            "id": "11111111-1111-1111-1111-111111111111",
+            "type": "toggle",
+            "has_children": true,
+            "toggle": { "rich_text": [], "color": "default" },
+            "children": [{
+                "object": "block",
+                "id": "22222222-2222-2222-2222-222222222222",
+                "type": "paragraph",
+                "has_children": false,
+                "paragraph": { "rich_text": [], "color": "default" }
+            }]
+        }"#;
+        let NotionObject::Block(block) = assemble_from_json(text).unwrap() else {
+            panic!("expected a block");
+        };
+        assert_eq!(block.children().len(), 1);
+        assert_eq!(block.children()[0].block_type(), "paragraph");
+    }
+
+    #[test]
+    fn test_non_notion_input_is_rejected() {
+        assert!(matches!(
+            assemble_from_json(r#"{"name": "x"}"#),
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            assemble_from_json("{"),
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            assemble_from_json(""),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
@@ -3,31 +3,43 @@
 //!
 //! Workers explore the content tree by executing exploration steps, each of which
 //! may discover new content and produce further steps to follow.
+//!
+//! A fetch runs in a `fetch` span, each worker in a `worker` span and each
+//! step in a `step` span carrying the step type, target ID and remaining
+//! depth, so a tracing subscriber (tokio-console, tracing-flame, ...) shows
+//! where a large fetch spends its time.
 
 use super::concurrent_queue::{ConcurrentWorkQueue, WorkerQueue};
 use super::fetch_queue::{
     CompletedStep, DiscoveredContent, ExplorationStep, FailureReason, SkipReason, StepOutcome,
+    WorkPriority,
 };
 use super::object_graph::ObjectGraph;
+use super::size_estimate;
 use super::types::*;
 use crate::config::PipelineConfig;
 use crate::error::{classify_database_fetch_failure, AppError, DatabaseFetchFailure};
-use crate::error_recovery::retry_with_backoff;
+use crate::error_recovery::retry_with_backoff_observed;
+use crate::formatting::row_selected;
 use crate::model::{
-    Block, Database, DatabaseProperty, DatabasePropertyType, DatabaseTitle, NotionObject,
-    NumberFormat, Page,
+    Block, DataSource, Database, DatabaseProperty, DatabasePropertyType, DatabaseTitle,
+    NotionObject, NumberFormat, Page, UnfetchedBlocks,
 };
+use crate::pipeline::WarningSink;
 use crate::types::{DatabaseId, NotionId, PropertyName, Warning, WarningLevel};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::task::JoinSet;
+use tracing::Instrument;
 
 /// Enhanced queue-based fetcher with parallel work-stealing and error recovery.
 pub struct NotionFetcher {
     client: Arc<dyn super::NotionRepository>,
     config: PipelineConfig,
     num_workers: usize,
+    earlier_roots: Option<Arc<super::RootVisits>>,
+    warning_sink: Option<Arc<dyn WarningSink>>,
 }
 
 impl NotionFetcher {
@@ -44,6 +56,8 @@ impl NotionFetcher {
             client,
             config: config.clone(),
             num_workers: num_workers.clamp(1, 32),
+            earlier_roots: None,
+            warning_sink: None,
         }
     }
 
@@ -58,6 +72,8 @@ impl NotionFetcher {
             client,
             config: config.clone(),
             num_workers: num_workers.clamp(1, 32),
+            earlier_roots: None,
+            warning_sink: None,
         }
     }
 
@@ -67,29 +83,51 @@ impl NotionFetcher {
         Self::with_workers(client, config, 1)
     }
 
+    /// Stops at objects that earlier roots of a multi-root run already
+    /// include, instead of fetching them again.
+    pub fn with_earlier_roots(mut self, earlier_roots: Arc<super::RootVisits>) -> Self {
+        self.earlier_roots = Some(earlier_roots);
+        self
+    }
+
+    /// Reports each warning to `sink` as soon as a worker produces it. The
+    /// warnings are still collected into the result's metadata.
+    pub fn with_warning_sink(mut self, sink: Arc<dyn WarningSink>) -> Self {
+        self.warning_sink = Some(sink);
+        self
+    }
+
     /// Fetches a Notion object recursively using parallel work-stealing.
+    #[tracing::instrument(
+        name = "fetch",
+        skip_all,
+        fields(root = %id.as_str(), depth = self.config.depth, limit = self.config.limit)
+    )]
     pub async fn fetch_recursive(
         &self,
         id: &NotionId,
     ) -> Result<FetchResult<NotionObject>, AppError> {
         let (queue, workers) = ConcurrentWorkQueue::new(self.num_workers);
-        let initial_context = FetchContext::with_options(
+        let transfer_before = self.client.transfer_usage();
+        let mut initial_context = FetchContext::with_options(
             self.config.depth,
             self.config.limit,
             self.config.always_fetch_databases,
         );
+        if let Some(earlier_roots) = &self.earlier_roots {
+            initial_context = initial_context.with_earlier_roots(Arc::clone(earlier_roots));
+        }
+        initial_context = initial_context.with_token_budget(self.config.token_budget);
 
-        log::info!(
-            "Starting recursive fetch for {} (depth: {}, limit: {}, always_fetch_databases: {})",
-            id.as_str(),
-            self.config.depth,
-            self.config.limit,
-            self.config.always_fetch_databases
+        tracing::info!(
+            always_fetch_databases = self.config.always_fetch_databases,
+            "Starting recursive fetch for {}",
+            id.as_str()
         );
 
         // Detect object type from the original URL to skip unnecessary API calls
         let type_hint = super::types::ObjectTypeHint::from_input(&self.config.raw_input);
-        log::info!("Object type hint: {:?}", type_hint);
+        tracing::info!(?type_hint, "Detected object type hint");
 
         // Enqueue initial work
         queue.enqueue(ExplorationStep::IdentifyAndExplore {
@@ -105,16 +143,27 @@ impl NotionFetcher {
         let queue_arc = Arc::new(queue);
         let stealers = queue_arc.stealers().to_vec();
 
-        for worker in workers {
+        for (index, worker) in workers.into_iter().enumerate() {
             let queue = Arc::clone(&queue_arc);
             let client = Arc::clone(&self.client);
             let config = self.config.clone();
             let stealers = stealers.clone();
-
-            join_set.spawn(async move {
-                let worker_fetcher = ExplorationWorker::new(&*client, &config);
-                run_exploration_loop(worker, &worker_fetcher, &queue, &stealers).await
-            });
+            let warning_sink = self.warning_sink.clone();
+
+            join_set.spawn(
+                async move {
+                    let worker_fetcher = ExplorationWorker::new(&*client, &config);
+                    run_exploration_loop(
+                        worker,
+                        &worker_fetcher,
+                        &queue,
+                        &stealers,
+                        warning_sink.as_deref(),
+                    )
+                    .await
+                }
+                .instrument(tracing::debug_span!("worker", worker = index)),
+            );
         }
 
         // Wait for all workers to complete
@@ -130,7 +179,11 @@ impl NotionFetcher {
             .unwrap_or_else(|_| unreachable!("All workers should be done"))
             .collect_results();
 
-        self.assemble_results(results, id, initial_context)
+        let mut result = self.assemble_results(results, id, initial_context)?;
+        let transfer = self.client.transfer_usage().since(transfer_before);
+        tracing::info!("Transferred: {}", transfer);
+        result.metadata.transfer = Some(transfer);
+        Ok(result)
     }
 
     /// Assembles work results into the final object tree.
@@ -141,7 +194,7 @@ impl NotionFetcher {
         initial_context: FetchContext,
     ) -> Result<FetchResult<NotionObject>, AppError> {
         let mut graph = ObjectGraph::with_capacity(results.len());
-        let mut final_context = initial_context;
+        let mut final_context = initial_context.clone();
         let mut total_metadata = FetchMetadata::default();
 
         // Process all results
@@ -152,7 +205,15 @@ impl NotionFetcher {
             total_metadata = total_metadata.merge(new_metadata);
         }
 
-        log::debug!(
+        let budget = initial_context.budget().usage();
+        tracing::info!("Fetch budget spent: {}", budget);
+        total_metadata.budget = Some(budget);
+        total_metadata.size = initial_context.size().usage();
+        if let Some(size) = total_metadata.size {
+            tracing::info!("Estimated prompt size: {}", size);
+        }
+
+        tracing::debug!(
             "{} databases tracked, {} block-to-database mappings",
             graph.database_locations().len(),
             graph.child_db_block_to_database().len()
@@ -167,7 +228,7 @@ impl NotionFetcher {
                 ),
             })?;
 
-        log::info!(
+        tracing::info!(
             "Fetch complete for {}: object tree assembled",
             root_id.as_str()
         );
@@ -183,7 +244,6 @@ impl NotionFetcher {
 /// Worker-specific fetcher that handles individual work items.
 struct ExplorationWorker<'a> {
     client: &'a dyn super::NotionRepository,
-    #[allow(dead_code)]
     config: &'a PipelineConfig,
 }
 
@@ -197,6 +257,18 @@ impl<'a> ExplorationWorker<'a> {
         &self,
         item: ExplorationStep,
     ) -> Result<(StepOutcome, Vec<ExplorationStep>), AppError> {
+        // Databases and their rows still complete once the estimated prompt
+        // fills the token budget; blocks and linked pages are left out.
+        if item.priority() < WorkPriority::High && !item.context().size().has_room() {
+            let context = item.context().clone();
+            return Ok((
+                StepOutcome::Skipped {
+                    reason: SkipReason::TokenBudgetReached,
+                    context,
+                },
+                vec![],
+            ));
+        }
         match item {
             ExplorationStep::IdentifyAndExplore { request, context } => {
                 self.identify_and_explore(request, context).await
@@ -231,17 +303,63 @@ impl<'a> ExplorationWorker<'a> {
             ));
         }
 
+        if !context.is_root() && context.covered_by_earlier_root(&request.id) {
+            return Ok((
+                StepOutcome::Skipped {
+                    reason: SkipReason::CoveredByEarlierRoot(request.id.clone()),
+                    context,
+                },
+                vec![],
+            ));
+        }
+
+        // Pages may hold several views of one database; the first block resolves it
+        if matches!(
+            request.objective,
+            FetchObjective::ResolveChildDatabase { .. }
+        ) && !context.claim_database(&request.id)
+        {
+            return Ok((
+                StepOutcome::Skipped {
+                    reason: SkipReason::DatabaseAlreadyResolved(request.id.clone()),
+                    context,
+                },
+                vec![],
+            ));
+        }
+
+        // Objects reached through links spend the child page budget; the root
+        // and child databases (whose rows are budgeted on their own) do not.
+        let is_linked = !context.is_root()
+            && matches!(request.objective, FetchObjective::ExploreRecursively { .. });
+        if is_linked && context.budget().claim(FetchCategory::ChildPages, 1) == 0 {
+            return Ok((
+                StepOutcome::Skipped {
+                    reason: SkipReason::BudgetExhausted(FetchCategory::ChildPages),
+                    context,
+                },
+                vec![],
+            ));
+        }
+
         // Mark as visited
         let context = context.with_visited(request.id.clone());
 
         // Fetch the object with retry — use targeted resolution for child databases
-        let obj = retry_with_backoff(
-            || self.resolve_by_objective(&request.id, &request.objective),
+        let mut obj = retry_with_backoff_observed(
+            || self.resolve_by_objective(&request.id, &request.objective, context.is_root()),
             3,
             Duration::from_millis(100),
             Duration::from_secs(5),
+            &|error, wait| self.client.record_retry(error, wait),
         )
         .await?;
+        if !self.config.keep_raw_unsupported {
+            if let NotionObject::Block(block) = &mut obj {
+                discard_raw_payloads(std::slice::from_mut(block));
+            }
+        }
+        context.size().record(size_estimate::object_chars(&obj));
 
         let metadata = FetchMetadata {
             items_fetched: 1,
@@ -261,12 +379,16 @@ impl<'a> ExplorationWorker<'a> {
 
         match (&request.objective, &obj) {
             (FetchObjective::ExploreRecursively { .. }, NotionObject::Page(page)) => {
-                log::debug!("Fetched page '{}' ({})", page.title(), page.id.as_str());
+                tracing::debug!(
+                    page_id = page.id.as_str(),
+                    "Fetched page '{}'",
+                    page.title()
+                );
                 if context.depth_remaining > 0 {
-                    log::debug!(
-                        "Queueing RetrieveChildren for page {} (depth_remaining: {})",
-                        page.id.as_str(),
-                        context.depth_remaining
+                    tracing::debug!(
+                        page_id = page.id.as_str(),
+                        depth_remaining = context.depth_remaining,
+                        "Queueing RetrieveChildren"
                     );
                     more_work.push(ExplorationStep::RetrieveChildren {
                         parent_id: page.id.clone().into(),
@@ -275,14 +397,14 @@ impl<'a> ExplorationWorker<'a> {
                 }
             }
             (FetchObjective::ExploreRecursively { .. }, NotionObject::Database(db)) => {
-                log::debug!(
-                    "Fetched database '{}' ({}) - {} properties (exploring recursively)",
+                tracing::debug!(
+                    database_id = db.id.as_str(),
+                    properties = db.properties.len(),
+                    "Fetched database '{}' (exploring recursively)",
                     db.title(),
-                    db.id.as_str(),
-                    db.properties.len(),
                 );
                 if context.depth_remaining > 0 {
-                    log::debug!(
+                    tracing::debug!(
                         "Queueing CollectRows for '{}' ({})",
                         db.title(),
                         db.id.as_str()
@@ -294,23 +416,28 @@ impl<'a> ExplorationWorker<'a> {
                 }
             }
             (FetchObjective::ResolveChildDatabase { .. }, NotionObject::Database(db)) => {
-                log::debug!(
-                    "Fetched child database '{}' ({}) - {} properties, {} pages",
+                tracing::debug!(
+                    database_id = db.id.as_str(),
+                    properties = db.properties.len(),
+                    pages = db.pages.len(),
+                    "Fetched child database '{}'",
                     db.title(),
-                    db.id.as_str(),
-                    db.properties.len(),
-                    db.pages.len(),
                 );
                 // Skip CollectRows if the database already has pages
                 // (linked database fallback pre-populates pages via query_rows)
                 if !db.pages.is_empty() {
-                    log::debug!(
+                    tracing::debug!(
                         "Child database '{}' already has {} pages (from linked db query fallback)",
                         db.title(),
                         db.pages.len(),
                     );
+                } else if db.id != request.id && !context.claim_database(&db.id.clone().into()) {
+                    tracing::debug!(
+                        "Child database '{}' is another view of a database already being fetched",
+                        db.title(),
+                    );
                 } else if context.depth_remaining > 0 {
-                    log::debug!(
+                    tracing::debug!(
                         "Queueing CollectRows for child database '{}' ({})",
                         db.title(),
                         db.id.as_str()
@@ -320,7 +447,7 @@ impl<'a> ExplorationWorker<'a> {
                         context: context.clone(),
                     });
                 } else {
-                    log::debug!(
+                    tracing::debug!(
                         "Skipping database rows for '{}' (depth_remaining: {})",
                         db.title(),
                         context.depth_remaining,
@@ -328,10 +455,10 @@ impl<'a> ExplorationWorker<'a> {
                 }
             }
             (_, NotionObject::Block(block)) => {
-                log::debug!(
-                    "Fetched block {} ({})",
-                    block.block_type(),
-                    block.id().as_str()
+                tracing::debug!(
+                    block_id = block.id().as_str(),
+                    block_type = %block.block_type(),
+                    "Fetched block"
                 );
                 if context.depth_remaining > 0 && block.has_children() {
                     more_work.push(ExplorationStep::FollowReferences {
@@ -350,7 +477,7 @@ impl<'a> ExplorationWorker<'a> {
         Ok((
             StepOutcome::Success(Box::new(CompletedStep {
                 content: DiscoveredContent::Object {
-                    object: obj,
+                    object: Box::new(obj),
                     children_to_fetch,
                     source_id,
                 },
@@ -368,24 +495,45 @@ impl<'a> ExplorationWorker<'a> {
         parent_id: NotionId,
         context: FetchContext,
     ) -> Result<(StepOutcome, Vec<ExplorationStep>), AppError> {
-        log::debug!(
-            "Processing blocks for parent {} (depth_remaining: {})",
-            parent_id.as_str(),
-            context.depth_remaining
+        tracing::debug!(
+            parent_id = parent_id.as_str(),
+            depth_remaining = context.depth_remaining,
+            "Processing blocks"
         );
 
+        if !context.budget().has_room(FetchCategory::Blocks) {
+            // Recorded rather than skipped, so the cut is marked in place
+            return Ok((
+                StepOutcome::Success(Box::new(CompletedStep {
+                    content: DiscoveredContent::Blocks {
+                        parent_id,
+                        blocks: vec![],
+                        unfetched: Some(UnfetchedBlocks::default()),
+                    },
+                    context,
+                    metadata: FetchMetadata::default(),
+                    warnings: vec![],
+                })),
+                vec![],
+            ));
+        }
+
         // Step 1: Retrieve raw blocks from the API
-        let blocks = match self.client.retrieve_children(&parent_id).await {
+        let mut blocks = match self.client.retrieve_children(&parent_id).await {
             Ok(blocks) => {
-                log::debug!(
-                    "Fetched {} blocks for parent {}",
-                    blocks.len(),
-                    parent_id.as_str()
+                tracing::debug!(
+                    parent_id = parent_id.as_str(),
+                    blocks = blocks.len(),
+                    "Fetched blocks"
                 );
                 blocks
             }
             Err(e) => {
-                log::warn!("Failed to fetch blocks for {}: {}", parent_id.as_str(), e);
+                tracing::warn!(
+                    parent_id = parent_id.as_str(),
+                    error = %e,
+                    "Failed to fetch blocks"
+                );
                 return Ok((
                     StepOutcome::Failed {
                         reason: FailureReason::Unreachable { cause: Arc::new(e) },
@@ -396,20 +544,33 @@ impl<'a> ExplorationWorker<'a> {
             }
         };
 
+        if !self.config.keep_raw_unsupported {
+            discard_raw_payloads(&mut blocks);
+        }
+
+        let unfetched = fit_blocks_to_budget(&context, &mut blocks);
+        mark_children_beyond_depth(&mut blocks, &context);
+        context.size().record(size_estimate::blocks_chars(&blocks));
         let metadata = FetchMetadata {
             items_fetched: blocks.len() as u32,
             ..Default::default()
         };
 
+        let warnings = oversized_block_warnings(&blocks, self.config.max_block_bytes);
+
         // Step 2: Plan follow-up work for child databases and enrichable blocks
         let more_work = plan_deeper_exploration(&blocks, &parent_id, &context);
 
         Ok((
             StepOutcome::Success(Box::new(CompletedStep {
-                content: DiscoveredContent::Blocks { parent_id, blocks },
+                content: DiscoveredContent::Blocks {
+                    parent_id,
+                    blocks,
+                    unfetched,
+                },
                 context,
                 metadata,
-                warnings: vec![],
+                warnings,
             })),
             more_work,
         ))
@@ -444,7 +605,7 @@ impl<'a> ExplorationWorker<'a> {
         Ok((
             StepOutcome::Success(Box::new(CompletedStep {
                 content: DiscoveredContent::Object {
-                    object: NotionObject::Block(*block),
+                    object: Box::new(NotionObject::Block(*block)),
                     children_to_fetch: vec![],
                     source_id: None,
                 },
@@ -462,24 +623,57 @@ impl<'a> ExplorationWorker<'a> {
         database_id: NotionId,
         context: FetchContext,
     ) -> Result<(StepOutcome, Vec<ExplorationStep>), AppError> {
-        log::debug!(
-            "Querying database rows for {} (depth_remaining: {}, items_remaining: {})",
-            database_id.as_str(),
-            context.depth_remaining,
-            context.items_remaining
+        tracing::debug!(
+            database_id = database_id.as_str(),
+            depth_remaining = context.depth_remaining,
+            rows_remaining = context.budget().remaining(FetchCategory::DatabaseRows),
+            "Querying database rows"
         );
 
-        let rows = match self.client.query_rows(&database_id).await {
-            Ok(rows) => {
-                log::debug!(
-                    "Queried database {} - {} rows",
-                    database_id.as_str(),
-                    rows.len()
+        if !context.budget().has_room(FetchCategory::DatabaseRows) {
+            return Ok((
+                StepOutcome::Skipped {
+                    reason: SkipReason::BudgetExhausted(FetchCategory::DatabaseRows),
+                    context,
+                },
+                vec![],
+            ));
+        }
+
+        match self.client.list_data_sources(&database_id).await {
+            Ok(sources) if sources.len() > 1 => {
+                return Ok(self
+                    .collect_data_sources(database_id, sources, context)
+                    .await);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::debug!(
+                "Could not list data sources of {} ({}); querying it as a single-source database",
+                database_id.as_str(),
+                e
+            ),
+        }
+
+        let (mut rows, inaccessible, warning) = match self.client.query_rows(&database_id).await {
+            Ok(DatabaseRows {
+                pages: rows,
+                inaccessible,
+                warning,
+            }) => {
+                tracing::debug!(
+                    database_id = database_id.as_str(),
+                    rows = rows.len(),
+                    inaccessible = inaccessible.len(),
+                    "Queried database"
                 );
-                rows
+                (rows, inaccessible, warning)
             }
             Err(e) => {
-                log::warn!("Failed to query database {}: {}", database_id.as_str(), e);
+                tracing::warn!(
+                    database_id = database_id.as_str(),
+                    error = %e,
+                    "Failed to query database"
+                );
                 return Ok((
                     StepOutcome::Failed {
                         reason: FailureReason::Unreachable { cause: Arc::new(e) },
@@ -490,7 +684,15 @@ impl<'a> ExplorationWorker<'a> {
             }
         };
 
-        let updated_context = context.with_items_used(rows.len() as u32);
+        self.select_rows(&mut rows);
+        let mut warnings: Vec<Warning> = warning.into_iter().collect();
+        warnings.extend(fit_rows_to_budget(
+            &context,
+            &format!("Database {}", database_id.as_str()),
+            &mut rows,
+        ));
+        let more_work = self.row_block_fetches(&rows, &context);
+        context.size().record(size_estimate::rows_chars(&rows));
         let metadata = FetchMetadata {
             items_fetched: rows.len() as u32,
             ..Default::default()
@@ -501,15 +703,130 @@ impl<'a> ExplorationWorker<'a> {
                 content: DiscoveredContent::Rows {
                     database_id,
                     pages: rows,
+                    inaccessible,
                 },
-                context: updated_context,
+                context,
                 metadata,
-                warnings: vec![],
+                warnings,
             })),
-            vec![],
+            more_work,
         ))
     }
 
+    /// Keeps only the rows `--row` selects; without `--row`, every row.
+    fn select_rows(&self, rows: &mut Vec<Page>) {
+        rows.retain(|row| row_selected(&self.config.rows, row));
+    }
+
+    /// A block fetch for each of `rows` when `--row` selected them, once the
+    /// row budget has cut them down. Without `--row`, row blocks are not fetched.
+    fn row_block_fetches(&self, rows: &[Page], context: &FetchContext) -> Vec<ExplorationStep> {
+        if self.config.rows.is_empty() || context.depth_remaining == 0 {
+            return vec![];
+        }
+        rows.iter()
+            .map(|row| ExplorationStep::RetrieveChildren {
+                parent_id: row.id.clone().into(),
+                context: context.clone().with_decremented_depth(),
+            })
+            .collect()
+    }
+
+    /// Collects the schema and rows of each data source of a multi-source database.
+    ///
+    /// A source that cannot be queried is left out with a warning; the others
+    /// still render.
+    async fn collect_data_sources(
+        &self,
+        database_id: NotionId,
+        sources: Vec<DataSourceRef>,
+        context: FetchContext,
+    ) -> (StepOutcome, Vec<ExplorationStep>) {
+        tracing::debug!(
+            database_id = database_id.as_str(),
+            data_sources = sources.len(),
+            "Listed data sources"
+        );
+
+        let mut collected = Vec::with_capacity(sources.len());
+        let mut warnings = Vec::new();
+        let mut more_work = Vec::new();
+        let mut sources = sources.into_iter();
+        while let Some(source) = sources.next() {
+            if !context.budget().has_room(FetchCategory::DatabaseRows) {
+                let skipped: Vec<String> = std::iter::once(source)
+                    .chain(sources.by_ref())
+                    .map(|source| format!("'{}'", source.name))
+                    .collect();
+                warnings.push(Warning::new(
+                    WarningLevel::Warning,
+                    format!(
+                        "The database row budget ran out before data sources {} of database {} were queried",
+                        skipped.join(", "),
+                        database_id.as_str()
+                    ),
+                ));
+                break;
+            }
+            let DataSourceRows { properties, rows } =
+                match self.client.query_data_source(&source).await {
+                    Ok(result) => result,
+                    Err(e) => {
+                        tracing::warn!(
+                            data_source_id = source.id.as_str(),
+                            error = %e,
+                            "Failed to query data source"
+                        );
+                        warnings.push(Warning::new(
+                            WarningLevel::Warning,
+                            format!(
+                                "Data source '{}' of database {} could not be queried: {}",
+                                source.name,
+                                database_id.as_str(),
+                                e
+                            ),
+                        ));
+                        continue;
+                    }
+                };
+
+            let mut pages = rows.pages;
+            self.select_rows(&mut pages);
+            warnings.extend(rows.warning);
+            warnings.extend(fit_rows_to_budget(
+                &context,
+                &format!("Data source '{}'", source.name),
+                &mut pages,
+            ));
+            more_work.extend(self.row_block_fetches(&pages, &context));
+            context.size().record(size_estimate::rows_chars(&pages));
+            collected.push(DataSource {
+                id: source.id,
+                name: source.name,
+                properties,
+                pages,
+                inaccessible_rows: rows.inaccessible,
+            });
+        }
+
+        let metadata = FetchMetadata {
+            items_fetched: collected.iter().map(|s| s.pages.len() as u32).sum(),
+            ..Default::default()
+        };
+        (
+            StepOutcome::Success(Box::new(CompletedStep {
+                content: DiscoveredContent::DataSources {
+                    database_id,
+                    sources: collected,
+                },
+                context,
+                metadata,
+                warnings,
+            })),
+            more_work,
+        )
+    }
+
     /// Resolves an object using the appropriate strategy for the fetch objective.
     ///
     /// For child databases, tries database first (skip the wasted page attempt).
@@ -520,6 +837,7 @@ impl<'a> ExplorationWorker<'a> {
         &self,
         id: &NotionId,
         objective: &FetchObjective,
+        is_root: bool,
     ) -> Result<NotionObject, AppError> {
         match objective {
             FetchObjective::ResolveChildDatabase { .. } => {
@@ -530,7 +848,7 @@ impl<'a> ExplorationWorker<'a> {
                         let failure = classify_database_fetch_failure(&e);
                         match &failure {
                             DatabaseFetchFailure::LinkedDatabase => {
-                                log::info!(
+                                tracing::info!(
                                     "Child database {} is a linked database — \
                                      attempting query_rows() fallback.",
                                     id.as_str()
@@ -538,22 +856,27 @@ impl<'a> ExplorationWorker<'a> {
 
                                 // Try querying rows — the query endpoint works for linked databases
                                 match self.client.query_rows(id).await {
-                                    Ok(rows) => {
-                                        log::info!(
+                                    Ok(DatabaseRows {
+                                        pages: rows,
+                                        inaccessible,
+                                        ..
+                                    }) => {
+                                        tracing::info!(
                                             "Successfully queried {} rows from linked database {}",
                                             rows.len(),
                                             id.as_str()
                                         );
                                         let schema = infer_schema_from_pages(&rows);
                                         let db = Database {
-                                            id: DatabaseId::parse(id.as_str())
-                                                .unwrap_or_else(|_| DatabaseId::new_v4()),
+                                            id: DatabaseId::from(id),
                                             title: DatabaseTitle::new(vec![]),
                                             url: String::new(),
                                             pages: rows,
                                             properties: schema,
                                             parent: None,
                                             archived: false,
+                                            data_sources: Vec::new(),
+                                            inaccessible_rows: inaccessible,
                                         };
                                         return Ok(NotionObject::Database(db));
                                     }
@@ -567,7 +890,7 @@ impl<'a> ExplorationWorker<'a> {
                                             // the linked DB and tried to access the source — retrying
                                             // with that same ID won't help.
                                             if is_not_found_error(&query_err) {
-                                                log::info!(
+                                                tracing::info!(
                                                     "Linked database {} references source database {} \
                                                      which is not shared with the integration",
                                                     id.as_str(),
@@ -576,34 +899,37 @@ impl<'a> ExplorationWorker<'a> {
                                             } else {
                                                 // Transient or other error — worth retrying with
                                                 // the source ID directly.
-                                                log::info!(
+                                                tracing::info!(
                                                     "Extracted source database ID {} from error, retrying query_rows()",
                                                     source_id.as_str()
                                                 );
                                                 match self.client.query_rows(&source_id).await {
-                                                    Ok(rows) => {
-                                                        log::info!(
+                                                    Ok(DatabaseRows {
+                                                        pages: rows,
+                                                        inaccessible,
+                                                        ..
+                                                    }) => {
+                                                        tracing::info!(
                                                             "Successfully queried {} rows from source database {}",
                                                             rows.len(),
                                                             source_id.as_str()
                                                         );
                                                         let schema = infer_schema_from_pages(&rows);
                                                         let db = Database {
-                                                            id: DatabaseId::parse(id.as_str())
-                                                                .unwrap_or_else(|_| {
-                                                                    DatabaseId::new_v4()
-                                                                }),
+                                                            id: DatabaseId::from(id),
                                                             title: DatabaseTitle::new(vec![]),
                                                             url: String::new(),
                                                             pages: rows,
                                                             properties: schema,
                                                             parent: None,
                                                             archived: false,
+                                                            data_sources: Vec::new(),
+                                                            inaccessible_rows: inaccessible,
                                                         };
                                                         return Ok(NotionObject::Database(db));
                                                     }
                                                     Err(retry_err) => {
-                                                        log::warn!(
+                                                        tracing::warn!(
                                                             "Retry with source database {} also failed: {}",
                                                             source_id.as_str(),
                                                             retry_err
@@ -612,7 +938,7 @@ impl<'a> ExplorationWorker<'a> {
                                                 }
                                             }
                                         } else {
-                                            log::warn!(
+                                            tracing::warn!(
                                                 "query_rows() failed for linked database {}: {}",
                                                 id.as_str(),
                                                 query_err
@@ -623,7 +949,7 @@ impl<'a> ExplorationWorker<'a> {
                                 }
                             }
                             other => {
-                                log::warn!(
+                                tracing::warn!(
                                     "Child database fetch failed for {}: {}",
                                     id.as_str(),
                                     other
@@ -648,7 +974,7 @@ impl<'a> ExplorationWorker<'a> {
                 match type_hint {
                     ObjectTypeHint::Database => {
                         // URL hints this is a database — try database first
-                        log::debug!(
+                        tracing::debug!(
                             "Speculative typing: trying database first for {}",
                             id.as_str()
                         );
@@ -656,13 +982,22 @@ impl<'a> ExplorationWorker<'a> {
                             return Ok(NotionObject::Database(db));
                         }
                         // Fall back to default resolution order
-                        self.client.resolve_object(id).await
+                        self.resolve(id, is_root).await
                     }
-                    ObjectTypeHint::Unknown => self.client.resolve_object(id).await,
+                    ObjectTypeHint::Unknown => self.resolve(id, is_root).await,
                 }
             }
         }
     }
+
+    /// Resolves `id`, salvaging refused content only for the root.
+    async fn resolve(&self, id: &NotionId, is_root: bool) -> Result<NotionObject, AppError> {
+        if is_root {
+            self.client.resolve_root(id).await
+        } else {
+            self.client.resolve_object(id).await
+        }
+    }
 }
 
 /// Enriches a child database block with the classified reason its database couldn't be fetched.
@@ -695,6 +1030,7 @@ async fn run_exploration_loop(
     fetcher: &ExplorationWorker<'_>,
     global_queue: &ConcurrentWorkQueue,
     stealers: &[crossbeam::deque::Stealer<super::fetch_queue::PrioritizedWorkItem>],
+    warning_sink: Option<&dyn WarningSink>,
 ) -> Result<(), AppError> {
     let mut consecutive_empty_attempts = 0;
     const MAX_EMPTY_ATTEMPTS: u32 = 10;
@@ -709,7 +1045,7 @@ async fn run_exploration_loop(
             None => {
                 // Check if there's still pending work globally
                 if !global_queue.has_pending_work() {
-                    log::debug!("No pending work, worker exiting");
+                    tracing::debug!("No pending work, worker exiting");
                     break;
                 }
 
@@ -721,7 +1057,7 @@ async fn run_exploration_loop(
                 // After several attempts, check one more time
                 if consecutive_empty_attempts >= MAX_EMPTY_ATTEMPTS {
                     if !global_queue.has_pending_work() {
-                        log::debug!(
+                        tracing::debug!(
                             "No work after {} attempts, worker exiting",
                             MAX_EMPTY_ATTEMPTS
                         );
@@ -734,24 +1070,42 @@ async fn run_exploration_loop(
             }
         };
 
-        log::debug!("Processing work item: {:?}", work_item.priority());
+        let span = tracing::debug_span!(
+            "step",
+            step = work_item.kind(),
+            id = work_item.target_id(),
+            depth_remaining = work_item.context().depth_remaining,
+            priority = ?work_item.priority(),
+        );
 
         // Process the work item
-        match fetcher.execute_step(work_item).await {
+        let outcome = fetcher
+            .execute_step(work_item)
+            .instrument(span.clone())
+            .await;
+        let _step = span.enter();
+        match outcome {
             Ok((result, more_work)) => {
                 // Queue additional work BEFORE marking this item complete
                 if !more_work.is_empty() {
-                    log::debug!("Queueing {} additional work items", more_work.len());
+                    tracing::debug!(steps = more_work.len(), "Queueing follow-up steps");
                     global_queue.enqueue_multiple(more_work);
                 }
 
                 match &result {
-                    StepOutcome::Success(_) => {}
+                    StepOutcome::Success(success) => {
+                        if let Some(sink) = warning_sink {
+                            success.warnings.iter().for_each(|w| sink.warn(w));
+                        }
+                    }
                     StepOutcome::Skipped { reason, .. } => {
-                        log::debug!("Work item skipped: {}", reason);
+                        tracing::debug!(%reason, "Step skipped");
                     }
                     StepOutcome::Failed { reason, .. } => {
-                        log::warn!("Work item failed: {}", reason);
+                        tracing::warn!(%reason, "Step failed");
+                        if let Some(sink) = warning_sink {
+                            sink.warn(&reason.to_warning());
+                        }
                     }
                 }
 
@@ -762,9 +1116,13 @@ async fn run_exploration_loop(
                 global_queue.mark_completed();
             }
             Err(e) => {
-                log::warn!("Error processing work item: {}", e);
+                tracing::warn!(error = %e, "Error processing step");
+                let reason = FailureReason::Unprocessable { cause: Arc::new(e) };
+                if let Some(sink) = warning_sink {
+                    sink.warn(&reason.to_warning());
+                }
                 global_queue.store_result(StepOutcome::Failed {
-                    reason: FailureReason::Unprocessable { cause: Arc::new(e) },
+                    reason,
                     context: FetchContext::new(0, 0),
                 });
 
@@ -777,6 +1135,72 @@ async fn run_exploration_loop(
     Ok(())
 }
 
+/// Claims database row budget for `rows`, truncating them to what was granted.
+fn fit_rows_to_budget(
+    context: &FetchContext,
+    label: &str,
+    rows: &mut Vec<Page>,
+) -> Option<Warning> {
+    let granted = context
+        .budget()
+        .claim(FetchCategory::DatabaseRows, rows.len() as u32);
+    if granted as usize >= rows.len() {
+        return None;
+    }
+    let warning = Warning {
+        level: WarningLevel::Warning,
+        message: format!(
+            "{} has {} rows; kept {} within the database row budget",
+            label,
+            rows.len(),
+            granted
+        ),
+        context: None,
+    };
+    rows.truncate(granted as usize);
+    Some(warning)
+}
+
+/// Claims block budget for `blocks`, cutting them off at what was granted.
+/// A table's rows are kept whole, even if they overrun the budget slightly.
+fn fit_blocks_to_budget(
+    context: &FetchContext,
+    blocks: &mut Vec<Block>,
+) -> Option<UnfetchedBlocks> {
+    let budget = context.budget();
+    if blocks
+        .iter()
+        .all(|block| matches!(block, Block::TableRow(_)))
+    {
+        budget.record(FetchCategory::Blocks, blocks.len() as u32);
+        return None;
+    }
+    let granted = budget.claim(FetchCategory::Blocks, blocks.len() as u32) as usize;
+    if granted >= blocks.len() {
+        return None;
+    }
+    let cut = UnfetchedBlocks {
+        count: Some(blocks.len() - granted),
+    };
+    blocks.truncate(granted);
+    Some(cut)
+}
+
+/// Marks the blocks whose children `--depth` leaves unfetched. Children are
+/// fetched two steps on (following the block, then retrieving its
+/// children), each a level deeper. Child pages and databases are not
+/// marked; they stand for content of their own.
+fn mark_children_beyond_depth(blocks: &mut [Block], context: &FetchContext) {
+    if context.depth_remaining >= 2 {
+        return;
+    }
+    for block in blocks {
+        if block.has_children() && !matches!(block, Block::ChildPage(_) | Block::ChildDatabase(_)) {
+            block.common_mut().unfetched_children = Some(UnfetchedBlocks::default());
+        }
+    }
+}
+
 /// Folds a step outcome into the growing object graph.
 fn fold_into_graph(
     graph: ObjectGraph,
@@ -793,17 +1217,13 @@ fn fold_into_graph(
             )
         }
         StepOutcome::Skipped { reason, context } => {
-            log::debug!("Work item skipped: {}", reason);
+            tracing::debug!("Work item skipped: {}", reason);
             (graph, context, FetchMetadata::default())
         }
         StepOutcome::Failed { reason, context } => {
-            log::warn!("Work item failed: {}", reason);
+            tracing::warn!("Work item failed: {}", reason);
             let mut metadata = FetchMetadata::default();
-            metadata.warnings.push(Warning {
-                level: WarningLevel::Warning,
-                message: reason.to_string(),
-                context: None,
-            });
+            metadata.warnings.push(reason.to_warning());
             (graph, context, metadata)
         }
     }
@@ -815,24 +1235,47 @@ fn register_content(graph: ObjectGraph, content: DiscoveredContent) -> ObjectGra
         DiscoveredContent::Object {
             object, source_id, ..
         } => {
-            log::debug!("Adding object to graph: {:?}", object.id());
-            graph.with_object_from_source(object, source_id)
+            tracing::debug!("Adding object to graph: {:?}", object.id());
+            graph.with_object_from_source(*object, source_id)
         }
-        DiscoveredContent::Blocks { parent_id, blocks } => {
-            log::debug!(
+        DiscoveredContent::Blocks {
+            parent_id,
+            blocks,
+            unfetched,
+        } => {
+            tracing::debug!(
                 "Adding {} blocks to parent {}",
                 blocks.len(),
                 parent_id.as_str()
             );
-            graph.with_blocks(parent_id, blocks)
+            graph
+                .with_unfetched(parent_id.clone(), unfetched)
+                .with_blocks(parent_id, blocks)
         }
-        DiscoveredContent::Rows { database_id, pages } => {
-            log::debug!(
+        DiscoveredContent::Rows {
+            database_id,
+            pages,
+            inaccessible,
+        } => {
+            tracing::debug!(
                 "Adding {} rows to database {}",
                 pages.len(),
                 database_id.as_str()
             );
-            graph.with_rows(database_id, pages)
+            graph
+                .with_inaccessible_rows(database_id.clone(), inaccessible)
+                .with_rows(database_id, pages)
+        }
+        DiscoveredContent::DataSources {
+            database_id,
+            sources,
+        } => {
+            tracing::debug!(
+                "Adding {} data sources to database {}",
+                sources.len(),
+                database_id.as_str()
+            );
+            graph.with_data_sources(database_id, sources)
         }
     }
 }
@@ -965,18 +1408,23 @@ fn plan_deeper_exploration(
     for block in blocks {
         match block {
             Block::ChildDatabase(child_db) => {
-                log::debug!(
+                tracing::debug!(
                     "Child database detected: '{}' ({}, parent: {})",
                     child_db.title,
                     child_db.common.id.as_str(),
                     parent_id.as_str()
                 );
 
-                if context.always_fetch_databases || context.depth_remaining > 0 {
-                    let db_id: NotionId = child_db.common.id.clone().into();
+                let db_id: NotionId = child_db.common.id.clone().into();
+                if context.covered_by_earlier_root(&db_id) {
+                    tracing::debug!(
+                        "Child database '{}' is already included by an earlier root",
+                        child_db.title
+                    );
+                } else if context.always_fetch_databases || context.depth_remaining > 0 {
                     let block_id: NotionId = child_db.common.id.clone().into();
 
-                    log::debug!(
+                    tracing::debug!(
                         "Queueing database fetch for '{}' ({})",
                         child_db.title,
                         db_id.as_str()
@@ -992,16 +1440,24 @@ fn plan_deeper_exploration(
                         context: context.clone().with_decremented_depth(),
                     });
                 } else {
-                    log::warn!(
+                    tracing::warn!(
                         "Skipping database fetch for '{}' ({}) - depth exhausted",
                         child_db.title,
                         child_db.common.id.as_str()
                     );
                 }
             }
+            Block::ChildPage(child_page)
+                if context.covered_by_earlier_root(&NotionId::from(&child_page.common.id)) =>
+            {
+                tracing::debug!(
+                    "Child page '{}' is already included by an earlier root",
+                    child_page.title
+                );
+            }
             _ => {
                 if context.depth_remaining > 0 && (block.has_children() || has_links(block)) {
-                    log::debug!(
+                    tracing::debug!(
                         "Queueing FollowReferences for {} ({})",
                         block.block_type(),
                         block.id().as_str()
@@ -1029,12 +1485,12 @@ fn exploration_steps_for_references(
 
     links
         .iter()
-        .filter(|link| context.should_fetch(&link.id))
+        .filter(|link| context.should_fetch(&link.id) && !context.covered_by_earlier_root(&link.id))
         .map(|link| ExplorationStep::IdentifyAndExplore {
             request: FetchRequest {
                 id: link.id.clone(),
                 objective: FetchObjective::ExploreRecursively {
-                    type_hint: super::types::ObjectTypeHint::Unknown,
+                    type_hint: super::types::ObjectTypeHint::for_link(&link.link_type),
                 },
             },
             context: context.clone().with_decremented_depth(),
@@ -1044,6 +1500,38 @@ fn exploration_steps_for_references(
 
 // Helper functions
 
+/// Drops the raw JSON the parser keeps on unsupported blocks.
+fn discard_raw_payloads(blocks: &mut [Block]) {
+    for block in blocks {
+        if let Block::Unsupported(unsupported) = block {
+            unsupported.raw = None;
+        }
+        discard_raw_payloads(block.children_mut());
+    }
+}
+
+/// One warning per block left out for exceeding `max_block_bytes`.
+fn oversized_block_warnings(blocks: &[Block], max_block_bytes: usize) -> Vec<Warning> {
+    blocks
+        .iter()
+        .filter_map(|block| match block {
+            Block::Unsupported(b) => b.skipped_bytes.map(|bytes| {
+                Warning::new(
+                    WarningLevel::Warning,
+                    format!(
+                        "Left out {} block {}: its {} payload exceeds --max-block-bytes ({})",
+                        b.block_type,
+                        b.common.id.as_str(),
+                        format_bytes(bytes as u64),
+                        format_bytes(max_block_bytes as u64)
+                    ),
+                )
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Checks if a block has links.
 fn has_links(block: &Block) -> bool {
     // TODO: Check rich text for links in each block variant
@@ -1079,3 +1567,283 @@ fn extract_links_from_block(block: &Block) -> Vec<DiscoveredLink> {
 
     links
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::NotionErrorCode;
+    use crate::model::{BlockCommon, ParagraphBlock, TextBlockContent, ToggleBlock};
+    use crate::types::{BlockId, Color, PageId, RichTextItem};
+    use parking_lot::Mutex;
+
+    /// Misses every lookup, so each step fails with a warning.
+    struct Missing;
+
+    #[async_trait::async_trait]
+    impl super::super::NotionRepository for Missing {
+        async fn retrieve_page(&self, id: &NotionId) -> Result<Page, AppError> {
+            Err(not_found(id))
+        }
+        async fn retrieve_database(&self, id: &NotionId) -> Result<Database, AppError> {
+            Err(not_found(id))
+        }
+        async fn retrieve_block(&self, id: &NotionId) -> Result<Block, AppError> {
+            Err(not_found(id))
+        }
+        async fn retrieve_children(&self, id: &NotionId) -> Result<Vec<Block>, AppError> {
+            Err(not_found(id))
+        }
+        async fn query_rows(&self, id: &NotionId) -> Result<DatabaseRows, AppError> {
+            Err(not_found(id))
+        }
+        async fn search_shared(&self) -> Result<Vec<NotionObject>, AppError> {
+            Ok(Vec::new())
+        }
+    }
+
+    const ROOT: &str = "550e8400e29b41d4a716446655440000";
+    const TOGGLE: &str = "660e8400e29b41d4a716446655440000";
+
+    /// A page holding a toggle, which holds a paragraph.
+    struct NestedPage;
+
+    #[async_trait::async_trait]
+    impl super::super::NotionRepository for NestedPage {
+        async fn retrieve_page(&self, id: &NotionId) -> Result<Page, AppError> {
+            Ok(Page::new(PageId::parse(id.as_str())?, "Plan"))
+        }
+        async fn retrieve_database(&self, id: &NotionId) -> Result<Database, AppError> {
+            Err(not_found(id))
+        }
+        async fn retrieve_block(&self, id: &NotionId) -> Result<Block, AppError> {
+            Err(not_found(id))
+        }
+        async fn retrieve_children(&self, id: &NotionId) -> Result<Vec<Block>, AppError> {
+            let text = |text: &str| TextBlockContent {
+                rich_text: vec![RichTextItem::plain_text(text)],
+                color: Color::Default,
+            };
+            if id.as_str().replace('-', "") == ROOT {
+                let mut common = BlockCommon::new(BlockId::parse(TOGGLE)?);
+                common.has_children = true;
+                return Ok(vec![Block::Toggle(ToggleBlock {
+                    common,
+                    content: text("Details"),
+                })]);
+            }
+            Ok(vec![Block::Paragraph(ParagraphBlock {
+                common: BlockCommon::new(BlockId::new_v4()),
+                content: text("Fine print"),
+            })])
+        }
+        async fn query_rows(&self, id: &NotionId) -> Result<DatabaseRows, AppError> {
+            Err(not_found(id))
+        }
+        async fn search_shared(&self) -> Result<Vec<NotionObject>, AppError> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn not_found(id: &NotionId) -> AppError {
+        AppError::NotionService {
+            code: NotionErrorCode::ObjectNotFound,
+            message: format!("Could not find {}", id.as_str()),
+            status: reqwest::StatusCode::NOT_FOUND,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_warning_sink_hears_failures_before_the_fetch_returns() {
+        let heard = Arc::new(Mutex::new(Vec::<Warning>::new()));
+        let sink = {
+            let heard = Arc::clone(&heard);
+            move |warning: &Warning| heard.lock().push(warning.clone())
+        };
+        let fetcher = NotionFetcher::sequential(Arc::new(Missing), &PipelineConfig::default())
+            .with_warning_sink(Arc::new(sink));
+
+        let id = NotionId::parse("550e8400e29b41d4a716446655440000").unwrap();
+        let result = fetcher.fetch_recursive(&id).await;
+
+        assert!(result.is_err());
+        let heard = heard.lock();
+        assert_eq!(heard.len(), 1);
+        assert_eq!(heard[0].level, WarningLevel::Warning);
+        assert!(heard[0].message.contains("550e8400"));
+    }
+
+    #[tokio::test]
+    async fn test_token_budget_skips_low_priority_work_once_reached() {
+        let id = NotionId::parse(ROOT).unwrap();
+        let nested_blocks = |object: &NotionObject| {
+            let NotionObject::Page(page) = object else {
+                panic!("expected a page");
+            };
+            page.blocks[0].children().len()
+        };
+
+        let unlimited = NotionFetcher::sequential(Arc::new(NestedPage), &PipelineConfig::default())
+            .fetch_recursive(&id)
+            .await
+            .unwrap();
+        assert_eq!(nested_blocks(&unlimited.data), 1);
+        assert_eq!(unlimited.metadata.size, None);
+
+        // The page and its toggle fill four tokens, so the toggle's
+        // children are not fetched
+        let config = PipelineConfig {
+            token_budget: 4,
+            ..PipelineConfig::default()
+        };
+        let budgeted = NotionFetcher::sequential(Arc::new(NestedPage), &config)
+            .fetch_recursive(&id)
+            .await
+            .unwrap();
+        assert_eq!(nested_blocks(&budgeted.data), 0);
+        let size = budgeted.metadata.size.unwrap();
+        assert!(size.reached);
+        assert_eq!(size.budget_tokens, 4);
+    }
+
+    #[tokio::test]
+    async fn test_depth_marks_blocks_whose_children_are_left_unfetched() {
+        let config = PipelineConfig {
+            depth: 1,
+            ..PipelineConfig::default()
+        };
+        let result = NotionFetcher::sequential(Arc::new(NestedPage), &config)
+            .fetch_recursive(&NotionId::parse(ROOT).unwrap())
+            .await
+            .unwrap();
+        let NotionObject::Page(page) = &result.data else {
+            panic!("expected a page");
+        };
+        let toggle = &page.blocks[0];
+        assert!(toggle.children().is_empty());
+        assert_eq!(
+            toggle.common().unfetched_children,
+            Some(UnfetchedBlocks { count: None })
+        );
+        assert_eq!(page.unfetched_blocks, None);
+    }
+
+    /// A database of five rows per data source, recording whose blocks
+    /// are listed. Without sources it is a single-source database.
+    #[derive(Default)]
+    struct Tasks {
+        sources: Vec<&'static str>,
+        listed: Mutex<Vec<String>>,
+    }
+
+    fn five_rows() -> DatabaseRows {
+        let pages = (1..=5)
+            .map(|n| Page::new(NotionId::from(PageId::new_v4()), format!("Row {}", n)))
+            .collect();
+        DatabaseRows {
+            pages,
+            inaccessible: Vec::new(),
+            warning: None,
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl super::super::NotionRepository for Tasks {
+        async fn retrieve_page(&self, id: &NotionId) -> Result<Page, AppError> {
+            Err(not_found(id))
+        }
+        async fn retrieve_database(&self, id: &NotionId) -> Result<Database, AppError> {
+            Ok(Database::new(id.clone(), "Tasks"))
+        }
+        async fn retrieve_block(&self, id: &NotionId) -> Result<Block, AppError> {
+            Err(not_found(id))
+        }
+        async fn retrieve_children(&self, id: &NotionId) -> Result<Vec<Block>, AppError> {
+            self.listed.lock().push(id.as_str().replace('-', ""));
+            Ok(Vec::new())
+        }
+        async fn query_rows(&self, _id: &NotionId) -> Result<DatabaseRows, AppError> {
+            Ok(five_rows())
+        }
+        async fn list_data_sources(
+            &self,
+            _database: &NotionId,
+        ) -> Result<Vec<DataSourceRef>, AppError> {
+            Ok(self
+                .sources
+                .iter()
+                .map(|name| DataSourceRef {
+                    id: NotionId::from(PageId::new_v4()),
+                    name: name.to_string(),
+                })
+                .collect())
+        }
+        async fn query_data_source(
+            &self,
+            _source: &DataSourceRef,
+        ) -> Result<DataSourceRows, AppError> {
+            Ok(DataSourceRows {
+                properties: HashMap::new(),
+                rows: five_rows(),
+            })
+        }
+        async fn search_shared(&self) -> Result<Vec<NotionObject>, AppError> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_selected_rows_beyond_the_row_budget_are_not_fetched() {
+        // A limit of 10 leaves 3 rows to the database
+        let config = PipelineConfig {
+            limit: 10,
+            rows: vec!["Row*".parse().unwrap()],
+            ..PipelineConfig::default()
+        };
+        let tasks = Arc::new(Tasks::default());
+        let result = NotionFetcher::sequential(tasks.clone(), &config)
+            .fetch_recursive(&NotionId::parse(ROOT).unwrap())
+            .await
+            .unwrap();
+        let NotionObject::Database(database) = &result.data else {
+            panic!("expected a database");
+        };
+
+        let kept: Vec<_> = database
+            .pages
+            .iter()
+            .map(|row| row.id.as_str().replace('-', ""))
+            .collect();
+        assert_eq!(kept.len(), 3);
+        assert_eq!(*tasks.listed.lock(), kept);
+    }
+
+    #[tokio::test]
+    async fn test_data_sources_past_the_row_budget_are_named_in_a_warning() {
+        // A limit of 10 leaves 3 rows, which the first source uses up
+        let config = PipelineConfig {
+            limit: 10,
+            ..PipelineConfig::default()
+        };
+        let tasks = Tasks {
+            sources: vec!["Open", "Done", "Archive"],
+            ..Tasks::default()
+        };
+        let result = NotionFetcher::sequential(Arc::new(tasks), &config)
+            .fetch_recursive(&NotionId::parse(ROOT).unwrap())
+            .await
+            .unwrap();
+
+        let NotionObject::Database(database) = &result.data else {
+            panic!("expected a database");
+        };
+        assert_eq!(database.data_sources.len(), 1);
+        let skipped: Vec<_> = result
+            .metadata
+            .warnings
+            .iter()
+            .filter(|w| w.message.contains("before data sources"))
+            .collect();
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].message.contains("'Done', 'Archive'"));
+    }
+}
@@ -5,14 +5,19 @@
 //! serde implementations, ensuring robust handling of all Notion API responses.
 
 use super::client::ApiResponse;
-use super::responses::{
-    NotionBlock, NotionDatabase, NotionError, NotionPage, QueryDatabaseResponse,
-    RetrieveBlockChildrenResponse, ToDomain,
-};
+use super::notion_client_adapter::convert_block_value;
+use super::responses::{NotionDatabase, NotionError, NotionPage, QueryDatabaseResponse, ToDomain};
+use crate::constants::NOTION_MAX_BLOCK_PAYLOAD_BYTES;
 use crate::error::{AppError, NotionClientError};
-use crate::model::{Block, Database, NotionObject, Page};
+use crate::model::{
+    Block, BlockCommon, Database, DatabaseProperty, NotionObject, Page, UnsupportedBlock,
+};
+use crate::types::{BlockId, PageId, PropertyName};
 use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::value::RawValue;
 use serde_json::Value;
+use std::collections::HashMap;
 
 /// Parse any Notion API response using notion-client types
 pub fn parse_api_response<T>(result: ApiResponse<String>) -> Result<T, AppError>
@@ -26,6 +31,11 @@ where
     }
 }
 
+/// The error an unsuccessful response stands for.
+pub fn parse_error_response(result: &ApiResponse<String>) -> AppError {
+    parse_error_with_notion_client::<()>(&result.data, result.status, &result.url).unwrap_err()
+}
+
 /// Parse successful response using notion-client's robust parsing
 fn parse_with_notion_client<T>(body: &str, url: &str) -> Result<T, AppError>
 where
@@ -90,8 +100,8 @@ pub fn parse_database_response(result: ApiResponse<String>) -> Result<Database,
 
 /// Parse block response using notion-client
 pub fn parse_block_response(result: ApiResponse<String>) -> Result<Block, AppError> {
-    let notion_block: NotionBlock = parse_api_response(result)?;
-    ToDomain::to_domain(notion_block)
+    let raw_block: Value = parse_api_response(result)?;
+    super::notion_client_adapter::convert_block_value(raw_block)
 }
 
 /// Parse any object type (page/database/block) dynamically
@@ -157,10 +167,27 @@ where
 }
 
 /// Pagination function for pages (using notion-client) - returns PaginatedResponse
+///
+/// Rows of a data source query name the data source as their parent, which
+/// notion-client does not model; they are attributed to the database instead.
+#[allow(dead_code)] // Library API
 pub fn parse_pages_pagination(
     result: ApiResponse<String>,
 ) -> Result<super::types::PaginatedResponse<Page>, AppError> {
-    let response: QueryDatabaseResponse = parse_api_response(result)?;
+    let mut raw: super::types::PaginatedResponse<Value> = parse_api_response(result)?;
+    for page in &mut raw.results {
+        attribute_data_source_row_to_database(page);
+    }
+    let response = QueryDatabaseResponse {
+        object: raw.object,
+        results: raw
+            .results
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<_, _>>()?,
+        next_cursor: raw.next_cursor,
+        has_more: raw.has_more,
+    };
     let pages = response.clone().into_domain_pages()?;
 
     Ok(super::types::PaginatedResponse {
@@ -171,12 +198,74 @@ pub fn parse_pages_pagination(
     })
 }
 
+/// Parses a page of database query results, keeping the rows the
+/// integration may not read.
+///
+/// Notion lists such a row as a partial page object, an ID without
+/// properties. It becomes [`QueriedRow::Inaccessible`](super::types::QueriedRow)
+/// instead of failing the whole page of results.
+pub fn parse_rows_pagination(
+    result: ApiResponse<String>,
+) -> Result<super::types::PaginatedResponse<super::types::QueriedRow>, AppError> {
+    let raw: super::types::PaginatedResponse<Value> = parse_api_response(result)?;
+    Ok(super::types::PaginatedResponse {
+        object: raw.object,
+        results: raw
+            .results
+            .into_iter()
+            .map(parse_row)
+            .collect::<Result<_, _>>()?,
+        next_cursor: raw.next_cursor,
+        has_more: raw.has_more,
+    })
+}
+
+fn parse_row(mut row: Value) -> Result<super::types::QueriedRow, AppError> {
+    if row.get("properties").is_none() {
+        let id = row.get("id").and_then(Value::as_str);
+        if let Some(id) = id.and_then(|id| PageId::parse(id).ok()) {
+            return Ok(super::types::QueriedRow::Inaccessible(id));
+        }
+    }
+    attribute_data_source_row_to_database(&mut row);
+    let page: NotionPage = serde_json::from_value(row)?;
+    Ok(super::types::QueriedRow::Page(page.to_domain()?))
+}
+
 /// Pagination function for blocks (using notion-client) - returns PaginatedResponse
+///
+/// Blocks over [`NOTION_MAX_BLOCK_PAYLOAD_BYTES`] are left out; see
+/// [`parse_blocks_pagination_within`].
+#[allow(dead_code)] // Library API
 pub fn parse_blocks_pagination(
     result: ApiResponse<String>,
 ) -> Result<super::types::PaginatedResponse<Block>, AppError> {
-    let response: RetrieveBlockChildrenResponse = parse_api_response(result)?;
-    let blocks = response.clone().into_domain_blocks()?;
+    parse_blocks_pagination_within(result, NOTION_MAX_BLOCK_PAYLOAD_BYTES)
+}
+
+/// Parses a page of blocks, leaving out any block whose JSON exceeds
+/// `max_block_bytes`.
+///
+/// Each block is kept as raw JSON until its size is known, so an oversized
+/// one is never built into a value tree. It becomes an unsupported block
+/// carrying its type and `skipped_bytes`, and keeps `has_children`, so
+/// its children are still fetched.
+pub fn parse_blocks_pagination_within(
+    result: ApiResponse<String>,
+    max_block_bytes: usize,
+) -> Result<super::types::PaginatedResponse<Block>, AppError> {
+    let response: super::types::PaginatedResponse<Box<RawValue>> = parse_api_response(result)?;
+
+    let mut blocks = Vec::with_capacity(response.results.len());
+    for raw in response.results {
+        let size = raw.get().len();
+        let block = if size > max_block_bytes {
+            oversized_block(&raw, size)?
+        } else {
+            convert_block_value(serde_json::from_str(raw.get())?)?
+        };
+        blocks.push(block);
+    }
 
     Ok(super::types::PaginatedResponse {
         object: response.object,
@@ -186,6 +275,140 @@ pub fn parse_blocks_pagination(
     })
 }
 
+/// The fields of a block needed to stand in for it; everything else is
+/// skipped over without being parsed.
+#[derive(Deserialize)]
+struct BlockHeader {
+    id: String,
+    #[serde(rename = "type", default)]
+    block_type: String,
+    #[serde(default)]
+    has_children: bool,
+    #[serde(default)]
+    archived: bool,
+}
+
+/// A placeholder for a block left out for its `size`.
+fn oversized_block(raw: &RawValue, size: usize) -> Result<Block, AppError> {
+    let header: BlockHeader = serde_json::from_str(raw.get())?;
+    log::debug!(
+        "Skipping {} block {}: {} bytes",
+        header.block_type,
+        header.id,
+        size
+    );
+    Ok(Block::Unsupported(UnsupportedBlock {
+        common: BlockCommon {
+            has_children: header.has_children,
+            archived: header.archived,
+            ..BlockCommon::new(BlockId::parse(&header.id)?)
+        },
+        block_type: header.block_type,
+        raw: None,
+        skipped_bytes: Some(size),
+    }))
+}
+
+/// Pagination function for search results — pages and databases interleaved.
+///
+/// Objects of any other type are skipped, since the search endpoint only
+/// promises pages and databases.
+pub fn parse_search_pagination(
+    result: ApiResponse<String>,
+) -> Result<super::types::PaginatedResponse<NotionObject>, AppError> {
+    let response: super::types::PaginatedResponse<Value> = parse_api_response(result)?;
+
+    let mut objects = Vec::with_capacity(response.results.len());
+    for item in response.results {
+        match item.get("object").and_then(|v| v.as_str()) {
+            Some("page") => {
+                let page: NotionPage = serde_json::from_value(item)?;
+                objects.push(NotionObject::Page(ToDomain::to_domain(page)?));
+            }
+            Some("database") => {
+                let database: NotionDatabase = serde_json::from_value(item)?;
+                objects.push(NotionObject::Database(ToDomain::to_domain(database)?));
+            }
+            other => log::debug!("Skipping search result of type {:?}", other),
+        }
+    }
+
+    Ok(super::types::PaginatedResponse {
+        object: response.object,
+        results: objects,
+        next_cursor: response.next_cursor,
+        has_more: response.has_more,
+    })
+}
+
+/// Rewrites a `data_source_id` parent to the `database_id` parent it sits in.
+pub(super) fn attribute_data_source_row_to_database(page: &mut Value) {
+    let Some(parent) = page.get_mut("parent") else {
+        return;
+    };
+    if parent.get("type").and_then(Value::as_str) != Some("data_source_id") {
+        return;
+    }
+    if let Some(database_id) = parent.get("database_id").cloned() {
+        *parent = serde_json::json!({ "type": "database_id", "database_id": database_id });
+    }
+}
+
+/// Parse the data sources a database lists (API 2025-09 `GET /databases/{id}`).
+///
+/// Databases retrieved through an older API version have no `data_sources`
+/// field and yield an empty list.
+pub fn parse_data_source_list(
+    result: ApiResponse<String>,
+) -> Result<Vec<super::types::DataSourceRef>, AppError> {
+    let database: Value = parse_api_response(result)?;
+    let Some(sources) = database.get("data_sources").and_then(Value::as_array) else {
+        return Ok(Vec::new());
+    };
+    sources
+        .iter()
+        .map(|source| {
+            let id = source.get("id").and_then(Value::as_str).ok_or_else(|| {
+                AppError::MalformedResponse("Data source without an id".to_string())
+            })?;
+            Ok(super::types::DataSourceRef {
+                id: crate::types::NotionId::parse(id)?,
+                name: source
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parse a data source's schema (`GET /data_sources/{id}`).
+pub fn parse_data_source_schema(
+    result: ApiResponse<String>,
+) -> Result<HashMap<PropertyName, DatabaseProperty>, AppError> {
+    #[derive(serde::Deserialize)]
+    struct DataSourceObject {
+        #[serde(default)]
+        properties: HashMap<String, notion_client::objects::database::DatabaseProperty>,
+    }
+
+    let source: DataSourceObject = parse_api_response(result)?;
+    super::notion_client_adapter::convert_database_properties(source.properties)
+}
+
+/// Parse the bot user an API key authenticates as (`GET /users/me`).
+pub fn parse_bot_user_response(
+    result: ApiResponse<String>,
+) -> Result<super::types::IntegrationIdentity, AppError> {
+    let user: Value = parse_api_response(result)?;
+    let text = |value: Option<&Value>| value.and_then(Value::as_str).map(str::to_string);
+    Ok(super::types::IntegrationIdentity {
+        name: text(user.get("name")),
+        workspace_name: text(user.pointer("/bot/workspace_name")),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -209,4 +432,284 @@ mod tests {
             panic!("Expected NotionClientError::NotionApi");
         }
     }
+
+    #[test]
+    fn test_bot_user_identity() {
+        let body = r#"{
+            "object": "user",
+            "id": "9a3b5ae0-c6e6-482d-b0e1-ed315ee6dc57",
+            "name": "Prompt Export",
+            "type": "bot",
+            "bot": { "owner": { "type": "workspace", "workspace": true }, "workspace_name": "Acme" }
+        }"#;
+        let identity = parse_bot_user_response(ApiResponse {
+            data: body.to_string(),
+            status: StatusCode::OK,
+            url: "https://api.notion.com/v1/users/me".to_string(),
+        })
+        .unwrap();
+        assert_eq!(identity.name.as_deref(), Some("Prompt Export"));
+        assert_eq!(identity.workspace_name.as_deref(), Some("Acme"));
+    }
+
+    #[test]
+    fn test_unsupported_block_keeps_raw_json() {
+        let body = serde_json::json!({
+            "object": "block",
+            "id": "59833787-2cf9-4fdf-8782-e53db20768a5",
+            "created_time": "2024-01-01T00:00:00.000Z",
+            "last_edited_time": "2024-01-01T00:00:00.000Z",
+            "created_by": { "object": "user", "id": "u1" },
+            "last_edited_by": { "object": "user", "id": "u1" },
+            "has_children": false,
+            "archived": false,
+            "type": "meeting_notes",
+            "meeting_notes": { "title": [], "status": "notes_ready" }
+        });
+        let block = parse_block_response(ApiResponse {
+            data: body.to_string(),
+            status: StatusCode::OK,
+            url: "https://api.notion.com/v1/blocks/59833787".to_string(),
+        })
+        .unwrap();
+
+        match block {
+            Block::Unsupported(unsupported) => {
+                assert_eq!(unsupported.block_type, "meeting_notes");
+                assert_eq!(unsupported.raw, Some(body));
+            }
+            other => panic!("Expected an unsupported block, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_search_pagination_keeps_pages_and_databases() {
+        let page: Value = serde_json::from_str(include_str!(
+            "../../tests/fixtures/api_responses/page_flow_ai_amundi.json"
+        ))
+        .unwrap();
+        let database: Value = serde_json::from_str(include_str!(
+            "../../tests/fixtures/api_responses/database_key_highlights.json"
+        ))
+        .unwrap();
+        let body = serde_json::json!({
+            "object": "list",
+            "results": [page, database, { "object": "user", "id": "u1" }],
+            "next_cursor": "cursor-2",
+            "has_more": true
+        });
+
+        let response = parse_search_pagination(ApiResponse {
+            data: body.to_string(),
+            status: StatusCode::OK,
+            url: "https://api.notion.com/v1/search".to_string(),
+        })
+        .unwrap();
+
+        assert_eq!(response.results.len(), 2);
+        assert!(matches!(response.results[0], NotionObject::Page(_)));
+        assert!(matches!(response.results[1], NotionObject::Database(_)));
+        assert_eq!(response.next_cursor.as_deref(), Some("cursor-2"));
+        assert!(response.has_more);
+    }
+
+    #[test]
+    fn test_blocks_pagination_converts_audio_and_embed_captions() {
+        let common = |id: &str, kind: &str| {
+            serde_json::json!({
+                "object": "block",
+                "id": id,
+                "type": kind,
+                "has_children": false,
+                "archived": false
+            })
+        };
+        let caption = serde_json::json!([{
+            "type": "text",
+            "text": { "content": "Standup", "link": null },
+            "annotations": {
+                "bold": false, "italic": false, "strikethrough": false,
+                "underline": false, "code": false, "color": "default"
+            },
+            "plain_text": "Standup",
+            "href": null
+        }]);
+
+        let mut audio = common("44444444-4444-4444-4444-444444444444", "audio");
+        audio["audio"] = serde_json::json!({
+            "type": "external",
+            "external": { "url": "https://example.com/standup.mp3" },
+            "caption": caption
+        });
+        let mut embed = common("55555555-5555-5555-5555-555555555555", "embed");
+        embed["embed"] = serde_json::json!({
+            "url": "https://www.loom.com/share/abc",
+            "caption": caption
+        });
+        let body = serde_json::json!({
+            "object": "list",
+            "results": [audio, embed],
+            "next_cursor": null,
+            "has_more": false
+        });
+
+        let response = parse_blocks_pagination(ApiResponse {
+            data: body.to_string(),
+            status: StatusCode::OK,
+            url: "https://api.notion.com/v1/blocks/x/children".to_string(),
+        })
+        .unwrap();
+
+        match &response.results[0] {
+            Block::Audio(audio) => {
+                assert_eq!(audio.caption.len(), 1);
+                assert!(matches!(
+                    &audio.audio,
+                    crate::model::FileObject::External { external }
+                        if external.url == "https://example.com/standup.mp3"
+                ));
+            }
+            other => panic!("Expected audio block, got {}", other.block_type()),
+        }
+        match &response.results[1] {
+            Block::Embed(embed) => assert_eq!(embed.caption.len(), 1),
+            other => panic!("Expected embed block, got {}", other.block_type()),
+        }
+    }
+
+    #[test]
+    fn test_oversized_blocks_are_left_out_of_a_block_list() {
+        let divider = serde_json::json!({
+            "object": "block",
+            "id": "66666666-6666-6666-6666-666666666666",
+            "type": "divider",
+            "divider": {},
+            "has_children": false,
+            "archived": false
+        });
+        let code = serde_json::json!({
+            "object": "block",
+            "id": "77777777-7777-7777-7777-777777777777",
+            "type": "code",
+            "code": {
+                "rich_text": [{
+                    "type": "text",
+                    "text": { "content": "x".repeat(4096), "link": null },
+                    "plain_text": "x".repeat(4096),
+                    "href": null
+                }],
+                "language": "plain text"
+            },
+            "has_children": true,
+            "archived": false
+        });
+        let body = serde_json::json!({
+            "object": "list",
+            "results": [divider, code],
+            "next_cursor": null,
+            "has_more": false
+        });
+
+        let response = parse_blocks_pagination_within(
+            ApiResponse {
+                data: body.to_string(),
+                status: StatusCode::OK,
+                url: "https://api.notion.com/v1/blocks/x/children".to_string(),
+            },
+            1024,
+        )
+        .unwrap();
+
+        assert!(matches!(response.results[0], Block::Divider(_)));
+        match &response.results[1] {
+            Block::Unsupported(skipped) => {
+                assert_eq!(skipped.block_type, "code");
+                assert!(skipped.skipped_bytes.unwrap() > 4096);
+                assert!(skipped.common.has_children);
+                assert_eq!(skipped.raw, None);
+            }
+            other => panic!("Expected a skipped block, got {}", other.block_type()),
+        }
+    }
+
+    #[test]
+    fn test_data_source_list_and_rows() {
+        let database = serde_json::json!({
+            "object": "database",
+            "id": "248104cd-477e-80fd-b757-e945d38000bd",
+            "data_sources": [
+                { "id": "248104cd-477e-80af-bc30-000bd28de8f9", "name": "Tasks" },
+                { "id": "248104cd-477e-80af-bc30-000bd28de8fa", "name": "Bugs" }
+            ]
+        });
+        let response = |body: &Value| ApiResponse {
+            data: body.to_string(),
+            status: StatusCode::OK,
+            url: String::new(),
+        };
+
+        let sources = parse_data_source_list(response(&database)).unwrap();
+        let names: Vec<_> = sources.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Tasks", "Bugs"]);
+        assert!(
+            parse_data_source_list(response(&serde_json::json!({ "object": "database" })))
+                .unwrap()
+                .is_empty()
+        );
+
+        let mut row: Value = serde_json::from_str(include_str!(
+            "../../tests/fixtures/api_responses/page_flow_ai_amundi.json"
+        ))
+        .unwrap();
+        row["parent"] = serde_json::json!({
+            "type": "data_source_id",
+            "data_source_id": "248104cd-477e-80af-bc30-000bd28de8f9",
+            "database_id": "248104cd-477e-80fd-b757-e945d38000bd"
+        });
+        let rows = parse_pages_pagination(response(&serde_json::json!({
+            "object": "list",
+            "results": [row],
+            "next_cursor": null,
+            "has_more": false
+        })))
+        .unwrap();
+        assert!(matches!(
+            rows.results[0].parent,
+            Some(crate::model::Parent::Database { .. })
+        ));
+    }
+
+    #[test]
+    fn test_partial_rows_are_inaccessible() {
+        let row: Value = serde_json::from_str(include_str!(
+            "../../tests/fixtures/api_responses/page_flow_ai_amundi.json"
+        ))
+        .unwrap();
+        let rows = parse_rows_pagination(ApiResponse {
+            data: serde_json::json!({
+                "object": "list",
+                "results": [
+                    row,
+                    { "object": "page", "id": "248104cd-477e-80af-bc30-000bd28de8f9" }
+                ],
+                "next_cursor": null,
+                "has_more": false
+            })
+            .to_string(),
+            status: StatusCode::OK,
+            url: String::new(),
+        })
+        .unwrap();
+
+        assert!(matches!(
+            rows.results[0],
+            super::super::types::QueriedRow::Page(_)
+        ));
+        match &rows.results[1] {
+            super::super::types::QueriedRow::Inaccessible(id) => {
+                assert_eq!(id.as_str(), "248104cd477e80afbc30000bd28de8f9")
+            }
+            other => panic!("expected an inaccessible row, got {:?}", other),
+        }
+    }
 }
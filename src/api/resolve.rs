@@ -0,0 +1,222 @@
+// src/api/resolve.rs
+//! Working out what kind of object an ID names.
+//!
+//! The API has no "what is this?" endpoint, so an ID is tried as a page,
+//! then a database, then a block. Every failed attempt is kept, so the
+//! final error tells a refused lookup (403: the object exists) apart from
+//! a missing one (404). When the lookups of the root were refused, its
+//! content may still be reachable another way: the children endpoint and
+//! the search index are tried before giving up. Objects reached from the
+//! root are not salvaged, since every refused link would list the whole
+//! search index again.
+
+use super::NotionRepository;
+use crate::error::{AccessFailure, AppError, ResolveAttempt};
+use crate::model::{NotionObject, Page, PageTitle};
+use crate::types::NotionId;
+
+/// Resolves `id` by trying page, then database, then block.
+pub(super) async fn resolve_object<R>(
+    repository: &R,
+    id: &NotionId,
+) -> Result<NotionObject, AppError>
+where
+    R: NotionRepository + ?Sized,
+{
+    let mut attempts = Vec::with_capacity(3);
+
+    // Try page first (most common)
+    match repository.retrieve_page(id).await {
+        Ok(page) => return Ok(NotionObject::Page(page)),
+        Err(e) => attempts.push(attempt("page", &e)),
+    }
+    match repository.retrieve_database(id).await {
+        Ok(db) => return Ok(NotionObject::Database(db)),
+        Err(e) => attempts.push(attempt("database", &e)),
+    }
+    match repository.retrieve_block(id).await {
+        Ok(block) => return Ok(NotionObject::Block(block)),
+        Err(e) => attempts.push(attempt("block", &e)),
+    }
+
+    Err(AppError::Unresolved {
+        id: id.to_string(),
+        attempts,
+    })
+}
+
+/// Resolves the root `id` like [`resolve_object`], salvaging its content
+/// when the lookups were refused.
+pub(super) async fn resolve_root<R>(repository: &R, id: &NotionId) -> Result<NotionObject, AppError>
+where
+    R: NotionRepository + ?Sized,
+{
+    let err = match resolve_object(repository, id).await {
+        Err(err @ AppError::Unresolved { .. }) => err,
+        resolved => return resolved,
+    };
+    let refused = matches!(&err, AppError::Unresolved { attempts, .. }
+        if attempts.iter().any(|a| matches!(a.failure, AccessFailure::Restricted { .. })));
+    if refused {
+        if let Some(object) = salvage(repository, id).await {
+            return Ok(object);
+        }
+    }
+    Err(err)
+}
+
+fn attempt(kind: &'static str, error: &AppError) -> ResolveAttempt {
+    let failure = AccessFailure::classify(error);
+    log::debug!("{} lookup failed: {}", kind, failure);
+    ResolveAttempt { kind, failure }
+}
+
+/// Looks for content of an object whose direct lookups were refused.
+async fn salvage<R>(repository: &R, id: &NotionId) -> Option<NotionObject>
+where
+    R: NotionRepository + ?Sized,
+{
+    // Children are sometimes listable when the parent itself is not. The
+    // fetcher lists them again under a stand-in page.
+    match repository.retrieve_children(id).await {
+        Ok(children) if !children.is_empty() => {
+            log::warn!(
+                "{} could not be retrieved, but its {} blocks could; using a stand-in page",
+                id,
+                children.len()
+            );
+            return Some(NotionObject::Page(stand_in_page(id)));
+        }
+        Ok(_) => log::debug!("{} has no listable children", id),
+        Err(e) => log::debug!("Listing children of {} failed: {}", id, e),
+    }
+
+    match repository.search_shared().await {
+        Ok(objects) => {
+            let found = objects.into_iter().find(|object| &object.id() == id);
+            if found.is_some() {
+                log::warn!(
+                    "{} could not be retrieved directly; using its search result",
+                    id
+                );
+            }
+            found
+        }
+        Err(e) => {
+            log::debug!("Searching for {} failed: {}", id, e);
+            None
+        }
+    }
+}
+
+/// A page carrying only an ID, for content whose page could not be read.
+fn stand_in_page(id: &NotionId) -> Page {
+    Page {
+        id: id.into(),
+        title: PageTitle::new(format!("Restricted page {}", id)),
+        url: format!("https://www.notion.so/{}", id.as_str()),
+        blocks: Vec::new(),
+        properties: Default::default(),
+        parent: None,
+        archived: false,
+        unfetched_blocks: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::types::DatabaseRows;
+    use crate::error::NotionErrorCode;
+    use crate::model::{Block, BlockCommon, Database, ParagraphBlock, TextBlockContent};
+    use crate::types::BlockId;
+
+    /// Refuses or misses every lookup; optionally lists children.
+    struct Refusing {
+        code: NotionErrorCode,
+        children: Vec<Block>,
+    }
+
+    impl Refusing {
+        fn error(&self) -> AppError {
+            AppError::NotionService {
+                code: self.code.clone(),
+                message: "nope".to_string(),
+                status: reqwest::StatusCode::FORBIDDEN,
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl NotionRepository for Refusing {
+        async fn retrieve_page(&self, _id: &NotionId) -> Result<Page, AppError> {
+            Err(self.error())
+        }
+        async fn retrieve_database(&self, _id: &NotionId) -> Result<Database, AppError> {
+            Err(self.error())
+        }
+        async fn retrieve_block(&self, _id: &NotionId) -> Result<Block, AppError> {
+            Err(self.error())
+        }
+        async fn retrieve_children(&self, _parent: &NotionId) -> Result<Vec<Block>, AppError> {
+            Ok(self.children.clone())
+        }
+        async fn query_rows(&self, _database: &NotionId) -> Result<DatabaseRows, AppError> {
+            Err(self.error())
+        }
+        async fn search_shared(&self) -> Result<Vec<NotionObject>, AppError> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn id() -> NotionId {
+        NotionId::parse("550e8400e29b41d4a716446655440000").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_missing_and_refused_objects_are_told_apart() {
+        let missing = Refusing {
+            code: NotionErrorCode::ObjectNotFound,
+            children: vec![],
+        };
+        let err = resolve_object(&missing, &id()).await.unwrap_err();
+        assert_eq!(err.exit_code(), crate::error::ExitCode::NotFound);
+        assert!(err.to_string().contains("page not found (404)"));
+
+        let refused = Refusing {
+            code: NotionErrorCode::RestrictedResource,
+            children: vec![],
+        };
+        let err = resolve_object(&refused, &id()).await.unwrap_err();
+        assert_eq!(
+            err.exit_code(),
+            crate::error::ExitCode::AuthenticationFailed
+        );
+        assert!(err.to_string().contains("access denied (403)"));
+        assert!(err.to_string().contains("the object exists"));
+    }
+
+    #[tokio::test]
+    async fn test_refused_object_with_listable_children_becomes_stand_in_page() {
+        let refused = Refusing {
+            code: NotionErrorCode::RestrictedResource,
+            children: vec![Block::Paragraph(ParagraphBlock {
+                common: BlockCommon {
+                    id: BlockId::new_v4(),
+                    children: vec![],
+                    has_children: false,
+                    archived: false,
+                    last_edited_time: None,
+                    unfetched_children: None,
+                },
+                content: TextBlockContent::default(),
+            })],
+        };
+
+        assert!(resolve_object(&refused, &id()).await.is_err());
+        let object = resolve_root(&refused, &id()).await.unwrap();
+
+        assert_eq!(object.id(), id());
+        assert!(object.display_title().starts_with("Restricted page"));
+    }
+}
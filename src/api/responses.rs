@@ -24,8 +24,9 @@ pub struct PaginatedResponse<T> {
 /// Database query response using notion-client types
 pub type QueryDatabaseResponse = PaginatedResponse<NotionPage>;
 
-/// Block children response using notion-client types
-pub type RetrieveBlockChildrenResponse = PaginatedResponse<NotionBlock>;
+/// Block children response, kept as raw JSON so block types notion-client
+/// does not model can still be converted.
+pub type RetrieveBlockChildrenResponse = PaginatedResponse<serde_json::Value>;
 
 /// Trait for converting notion-client types to domain types
 pub trait ToDomain<T> {
@@ -59,14 +60,6 @@ impl QueryDatabaseResponse {
     }
 }
 
-/// Batch conversion for blocks
-impl RetrieveBlockChildrenResponse {
-    /// Convert all blocks to domain model
-    pub fn into_domain_blocks(self) -> Result<Vec<crate::model::Block>, crate::error::AppError> {
-        self.results.into_iter().map(ToDomain::to_domain).collect()
-    }
-}
-
 /// Response envelope for error handling
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -0,0 +1,174 @@
+// src/api/root_visits.rs
+//! Objects already fetched by earlier roots of a multi-root run.
+//!
+//! A workspace export fetches one root after another, and their subtrees
+//! overlap: a database embedded in two pages, a page linked from several.
+//! `RootVisits` records which output first included each object. Roots
+//! that are themselves already covered are skipped; with
+//! `--dedupe-across-roots` the fetcher also stops at covered child
+//! databases and child pages, and [`RootVisits::link_duplicates`] turns
+//! them into references to the first occurrence.
+
+use crate::model::{Block, ChildDatabaseContent, Database, NotionObject};
+use crate::types::NotionId;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+
+/// Which output of the run first included each object.
+#[derive(Debug, Default)]
+pub struct RootVisits {
+    first_seen: RwLock<HashMap<NotionId, String>>,
+}
+
+impl RootVisits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The output that first included `id`, if any root did.
+    pub fn first_occurrence(&self, id: &NotionId) -> Option<String> {
+        self.first_seen.read().get(id).cloned()
+    }
+
+    /// Whether an earlier root already included `id`.
+    pub fn contains(&self, id: &NotionId) -> bool {
+        self.first_seen.read().contains_key(id)
+    }
+
+    /// Records `ids` as included in `location`, keeping earlier records.
+    pub fn record(&self, ids: impl IntoIterator<Item = NotionId>, location: &str) {
+        let mut first_seen = self.first_seen.write();
+        for id in ids {
+            first_seen.entry(id).or_insert_with(|| location.to_string());
+        }
+    }
+
+    /// Points unfetched child databases and child pages that an earlier
+    /// root included at that root's output. Returns how many were linked.
+    pub fn link_duplicates(&self, object: &mut NotionObject) -> usize {
+        match object {
+            NotionObject::Page(page) => self.link_in_blocks(&mut page.blocks),
+            NotionObject::Database(db) => self.link_in_database(db),
+            NotionObject::Block(block) => self.link_in_blocks(std::slice::from_mut(block)),
+        }
+    }
+
+    fn link_in_database(&self, db: &mut Database) -> usize {
+        let mut linked = 0;
+        for row in db.pages.iter_mut() {
+            linked += self.link_in_blocks(&mut row.blocks);
+        }
+        for source in &mut db.data_sources {
+            for row in source.pages.iter_mut() {
+                linked += self.link_in_blocks(&mut row.blocks);
+            }
+        }
+        linked
+    }
+
+    fn link_in_blocks(&self, blocks: &mut [Block]) -> usize {
+        let mut linked = 0;
+        for block in blocks {
+            match block {
+                Block::ChildDatabase(child) => match &mut child.content {
+                    ChildDatabaseContent::NotFetched => {
+                        let id = NotionId::from(&child.common.id);
+                        if let Some(location) = self.first_occurrence(&id) {
+                            child.content = ChildDatabaseContent::IncludedElsewhere { location };
+                            linked += 1;
+                        }
+                    }
+                    ChildDatabaseContent::Fetched(db) => linked += self.link_in_database(db),
+                    _ => {}
+                },
+                Block::ChildPage(child) if child.common.children.is_empty() => {
+                    let id = NotionId::from(&child.common.id);
+                    if let Some(location) = self.first_occurrence(&id) {
+                        child.included_elsewhere = Some(location);
+                        linked += 1;
+                    }
+                }
+                _ => {}
+            }
+            linked += self.link_in_blocks(block.children_mut());
+        }
+        linked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BlockCommon, ChildDatabaseBlock, ChildPageBlock};
+    use crate::types::BlockId;
+
+    fn child_database(id: &BlockId) -> Block {
+        Block::ChildDatabase(ChildDatabaseBlock {
+            common: BlockCommon {
+                id: id.clone(),
+                children: vec![],
+                has_children: false,
+                archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
+            },
+            title: "Tasks".to_string(),
+            content: ChildDatabaseContent::NotFetched,
+        })
+    }
+
+    #[test]
+    fn test_unfetched_child_pages_link_to_first_occurrence() {
+        let visits = RootVisits::new();
+        let seen = BlockId::new_v4();
+        visits.record([NotionId::from(&seen)], "handbook.md");
+
+        let mut object = NotionObject::Block(Block::ChildPage(ChildPageBlock {
+            common: BlockCommon::new(seen),
+            title: "Onboarding".to_string(),
+            included_elsewhere: None,
+        }));
+        assert_eq!(visits.link_duplicates(&mut object), 1);
+
+        match object {
+            NotionObject::Block(Block::ChildPage(child)) => {
+                assert_eq!(child.included_elsewhere.as_deref(), Some("handbook.md"))
+            }
+            other => panic!("unexpected object: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_first_location_wins() {
+        let visits = RootVisits::new();
+        let id = NotionId::from(&BlockId::new_v4());
+
+        visits.record([id.clone()], "a.md");
+        visits.record([id.clone()], "b.md");
+
+        assert_eq!(visits.first_occurrence(&id).as_deref(), Some("a.md"));
+    }
+
+    #[test]
+    fn test_unfetched_duplicates_link_to_first_occurrence() {
+        let visits = RootVisits::new();
+        let seen = BlockId::new_v4();
+        let unseen = BlockId::new_v4();
+        visits.record([NotionId::from(&seen)], "projects.md");
+
+        let mut object = NotionObject::Block(child_database(&seen));
+        assert_eq!(visits.link_duplicates(&mut object), 1);
+        let mut other = NotionObject::Block(child_database(&unseen));
+        assert_eq!(visits.link_duplicates(&mut other), 0);
+
+        match object {
+            NotionObject::Block(Block::ChildDatabase(child)) => assert_eq!(
+                child.content,
+                ChildDatabaseContent::IncludedElsewhere {
+                    location: "projects.md".to_string()
+                }
+            ),
+            other => panic!("unexpected object: {:?}", other),
+        }
+    }
+}
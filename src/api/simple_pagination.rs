@@ -1,48 +1,520 @@
 // src/api/simple_pagination.rs
-//! Simplified pagination without BoxFuture.
+//! Cursor pagination shared by every paginated Notion endpoint.
+//!
+//! [`Pages`] tracks one walk through an endpoint: the cursor, the page size
+//! and an optional item limit. [`PageRequest`] is the page it asks for next,
+//! as query parameters for GET endpoints or a JSON body for POST ones. The
+//! `fetch_all_pages_*` loops drive it for the HTTP client; the cache drives
+//! it directly, since it keeps the raw responses as well.
 
-use super::types::{PaginatedResponse, PaginationResult};
-use crate::constants::NOTION_API_PAGE_SIZE;
+use super::types::{PaginatedResponse, PaginationResult, ResumablePagination};
+use crate::clock::{Clock, Jitter, RandomJitter, SystemClock};
+use crate::constants::{
+    NOTION_API_PAGE_SIZE, PAGINATION_CURSOR_ATTEMPTS, PAGINATION_RETRY_DELAY_MS,
+};
 use crate::error::AppError;
+use crate::error_recovery::{retry_with_backoff_using, RetryObserver};
+use std::time::Duration;
 
-/// Fetches all pages using async closures directly.
+/// Page size and item limit of a paginated walk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageLimits {
+    /// Items asked for per request; the API allows at most 100
+    pub page_size: u32,
+    /// Stop once this many items are gathered, asking only for what is
+    /// still missing in the last request
+    pub max_items: Option<usize>,
+}
+
+impl Default for PageLimits {
+    fn default() -> Self {
+        Self {
+            page_size: NOTION_API_PAGE_SIZE as u32,
+            max_items: None,
+        }
+    }
+}
+
+/// The next page of a paginated endpoint to request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageRequest {
+    pub page_size: u32,
+    /// None for the first page
+    pub start_cursor: Option<String>,
+}
+
+impl PageRequest {
+    /// `endpoint` with the page as query parameters, for GET endpoints.
+    pub fn get_endpoint(&self, endpoint: &str) -> String {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        query.append_pair("page_size", &self.page_size.to_string());
+        if let Some(cursor) = &self.start_cursor {
+            query.append_pair("start_cursor", cursor);
+        }
+        format!("{}?{}", endpoint, query.finish())
+    }
+
+    /// The page as a JSON request body, for POST endpoints.
+    pub fn post_body(&self) -> serde_json::Value {
+        let mut body = serde_json::json!({ "page_size": self.page_size });
+        if let Some(cursor) = &self.start_cursor {
+            body["start_cursor"] = serde_json::json!(cursor);
+        }
+        body
+    }
+}
+
+/// The state of one walk through a paginated endpoint.
+#[derive(Debug)]
+pub struct Pages<T> {
+    limits: PageLimits,
+    cursor: Option<String>,
+    pages_fetched: u32,
+    finished: bool,
+    items: Vec<T>,
+}
+
+impl<T> Pages<T> {
+    pub fn new(limits: PageLimits) -> Self {
+        Self {
+            limits,
+            cursor: None,
+            pages_fetched: 0,
+            finished: false,
+            items: Vec::new(),
+        }
+    }
+
+    /// The page to request next, or None once the walk is over.
+    pub fn next_request(&self) -> Option<PageRequest> {
+        if self.finished {
+            return None;
+        }
+        let page_size = match self.limits.max_items {
+            Some(max) => {
+                let missing = max.saturating_sub(self.items.len());
+                if missing == 0 {
+                    return None;
+                }
+                self.limits
+                    .page_size
+                    .min(u32::try_from(missing).unwrap_or(u32::MAX))
+            }
+            None => self.limits.page_size,
+        };
+        Some(PageRequest {
+            page_size,
+            start_cursor: self.cursor.clone(),
+        })
+    }
+
+    /// Adds a fetched page and moves the cursor past it.
+    pub fn add(&mut self, page: PaginatedResponse<T>) {
+        self.pages_fetched += 1;
+        self.items.extend(page.results);
+        self.cursor = page.next_cursor;
+        if !page.has_more || self.cursor.is_none() {
+            self.finished = true;
+        }
+        if let Some(max) = self.limits.max_items {
+            if self.items.len() >= max {
+                if !self.finished {
+                    log::debug!("Reached the limit of {} items; not fetching further", max);
+                }
+                self.items.truncate(max);
+                self.finished = true;
+            }
+        }
+    }
+
+    /// Whether no page has been added yet.
+    pub fn is_first(&self) -> bool {
+        self.pages_fetched == 0
+    }
+
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+}
+
+/// Fetches every page `fetch_fn` returns, up to the item limit.
 pub async fn fetch_all_pages_simple<T, F, Fut>(
     mut fetch_fn: F,
-    max_pages: Option<u32>,
+    limits: PageLimits,
 ) -> Result<PaginationResult<T>, AppError>
 where
     T: Send + 'static,
-    F: FnMut(u32, Option<String>) -> Fut,
+    F: FnMut(PageRequest) -> Fut,
+    Fut: std::future::Future<Output = Result<PaginatedResponse<T>, AppError>>,
+{
+    let mut pages = Pages::new(limits);
+    while let Some(request) = pages.next_request() {
+        pages.add(fetch_fn(request).await?);
+    }
+
+    let items = pages.into_items();
+    Ok(PaginationResult {
+        total_fetched: items.len(),
+        items,
+    })
+}
+
+/// Fetches all pages, retrying a failing cursor instead of giving up.
+///
+/// A failure on the first page is returned as an error, since nothing has
+/// been gathered yet and callers may fall back to another strategy. After
+/// that, a cursor failing with a retryable error is retried with backoff;
+/// if it keeps failing, or fails with an error retrying cannot fix, the
+/// items fetched so far are returned with the error in `interrupted`.
+pub async fn fetch_all_pages_resumable<T, F, Fut>(
+    fetch_fn: F,
+    limits: PageLimits,
+    on_retry: RetryObserver<'_>,
+) -> Result<ResumablePagination<T>, AppError>
+where
+    T: Send + 'static,
+    F: FnMut(PageRequest) -> Fut,
+    Fut: std::future::Future<Output = Result<PaginatedResponse<T>, AppError>>,
+{
+    fetch_all_pages_resumable_using(fetch_fn, limits, &SystemClock, &RandomJitter, on_retry).await
+}
+
+/// [`fetch_all_pages_resumable`], waiting between retries on `clock` with
+/// `jitter`.
+async fn fetch_all_pages_resumable_using<T, F, Fut>(
+    mut fetch_fn: F,
+    limits: PageLimits,
+    clock: &dyn Clock,
+    jitter: &dyn Jitter,
+    on_retry: RetryObserver<'_>,
+) -> Result<ResumablePagination<T>, AppError>
+where
+    T: Send + 'static,
+    F: FnMut(PageRequest) -> Fut,
     Fut: std::future::Future<Output = Result<PaginatedResponse<T>, AppError>>,
 {
-    let mut all_items = Vec::new();
-    let mut cursor = None;
-    let mut pages_fetched = 0u32;
-
-    loop {
-        // Check if we've reached the page limit
-        if let Some(max) = max_pages {
-            if pages_fetched >= max {
-                log::debug!("Reached maximum page limit: {}", max);
-                break;
+    let mut pages = Pages::new(limits);
+    while let Some(request) = pages.next_request() {
+        let response = if pages.is_first() {
+            fetch_fn(request).await?
+        } else {
+            let attempt = retry_with_backoff_using(
+                || fetch_fn(request.clone()),
+                PAGINATION_CURSOR_ATTEMPTS,
+                Duration::from_millis(PAGINATION_RETRY_DELAY_MS),
+                Duration::from_secs(5),
+                clock,
+                jitter,
+                &AppError::is_retryable,
+                on_retry,
+            )
+            .await;
+            match attempt {
+                Ok(response) => response,
+                Err(e) => {
+                    return Ok(ResumablePagination {
+                        items: pages.into_items(),
+                        interrupted: Some(e),
+                    })
+                }
+            }
+        };
+        pages.add(response);
+    }
+
+    Ok(ResumablePagination {
+        items: pages.into_items(),
+        interrupted: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::client::ApiResponse;
+    use crate::api::parser::parse_blocks_pagination;
+    use crate::clock::{ManualClock, NoJitter};
+    use crate::error::NotionErrorCode;
+    use crate::model::Block;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    /// The blocks of a recorded children response, served `per_page` at a
+    /// time with cursors `page-1`, `page-2`, ... as the API would.
+    struct FixturePages {
+        raw_pages: Vec<String>,
+        requests: Mutex<Vec<PageRequest>>,
+    }
+
+    impl FixturePages {
+        fn new(per_page: usize) -> Self {
+            let body: serde_json::Value = serde_json::from_str(include_str!(
+                "../../tests/fixtures/api_responses/blocks_flow_ai_amundi.json"
+            ))
+            .unwrap();
+            let results = body["results"].as_array().unwrap();
+            let chunks: Vec<_> = results.chunks(per_page).collect();
+            let raw_pages = chunks
+                .iter()
+                .enumerate()
+                .map(|(index, chunk)| {
+                    let has_more = index + 1 < chunks.len();
+                    serde_json::json!({
+                        "object": "list",
+                        "results": chunk,
+                        "next_cursor": has_more.then(|| format!("page-{}", index + 1)),
+                        "has_more": has_more,
+                    })
+                    .to_string()
+                })
+                .collect();
+            Self {
+                raw_pages,
+                requests: Mutex::new(Vec::new()),
             }
         }
 
-        // Fetch the next page
-        let response = fetch_fn(NOTION_API_PAGE_SIZE as u32, cursor).await?;
+        /// Every block of the fixture, in order.
+        fn all_blocks(&self) -> Vec<Block> {
+            let mut pages = Pages::new(PageLimits::default());
+            for raw in &self.raw_pages {
+                pages.add(Self::parse(raw));
+            }
+            pages.into_items()
+        }
 
-        let has_more = response.has_more;
-        cursor = response.next_cursor.clone();
-        all_items.extend(response.results);
-        pages_fetched += 1;
+        async fn fetch(&self, request: PageRequest) -> Result<PaginatedResponse<Block>, AppError> {
+            let index = match request.start_cursor.as_deref() {
+                None => 0,
+                Some(cursor) => cursor.trim_start_matches("page-").parse().unwrap(),
+            };
+            self.requests.lock().unwrap().push(request);
+            Ok(Self::parse(&self.raw_pages[index]))
+        }
 
-        if !has_more || cursor.is_none() {
-            break;
+        fn parse(raw: &str) -> PaginatedResponse<Block> {
+            parse_blocks_pagination(ApiResponse {
+                data: raw.to_string(),
+                status: reqwest::StatusCode::OK,
+                url: "https://api.notion.com/v1/blocks/x/children".to_string(),
+            })
+            .unwrap()
         }
     }
 
-    Ok(PaginationResult {
-        total_fetched: all_items.len(),
-        items: all_items,
-    })
+    #[tokio::test]
+    async fn test_walks_every_fixture_page_in_order() {
+        let fixture = FixturePages::new(2);
+        let limits = PageLimits {
+            page_size: 2,
+            ..PageLimits::default()
+        };
+
+        let result = fetch_all_pages_simple(|request| fixture.fetch(request), limits)
+            .await
+            .unwrap();
+
+        assert_eq!(result.items, fixture.all_blocks());
+        assert_eq!(result.total_fetched, 6);
+        let cursors: Vec<Option<String>> = fixture
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| r.start_cursor.clone())
+            .collect();
+        assert_eq!(
+            cursors,
+            [None, Some("page-1".to_string()), Some("page-2".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_item_limit_stops_early_and_shrinks_last_page() {
+        let fixture = FixturePages::new(2);
+        let limits = PageLimits {
+            page_size: 2,
+            max_items: Some(3),
+        };
+
+        let result =
+            fetch_all_pages_resumable(|request| fixture.fetch(request), limits, &|_, _| {})
+                .await
+                .unwrap();
+
+        assert_eq!(result.items, fixture.all_blocks()[..3]);
+        let sizes: Vec<u32> = fixture
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| r.page_size)
+            .collect();
+        // The third page is never requested
+        assert_eq!(sizes, [2, 1]);
+    }
+
+    #[test]
+    fn test_page_request_forms() {
+        let first = PageRequest {
+            page_size: 100,
+            start_cursor: None,
+        };
+        assert_eq!(
+            first.get_endpoint("blocks/abc/children"),
+            "blocks/abc/children?page_size=100"
+        );
+        assert_eq!(first.post_body(), serde_json::json!({ "page_size": 100 }));
+
+        let next = PageRequest {
+            page_size: 50,
+            start_cursor: Some("a1b2+c3/d4".to_string()),
+        };
+        assert_eq!(
+            next.get_endpoint("blocks/abc/children"),
+            "blocks/abc/children?page_size=50&start_cursor=a1b2%2Bc3%2Fd4"
+        );
+        assert_eq!(
+            next.post_body(),
+            serde_json::json!({ "page_size": 50, "start_cursor": "a1b2+c3/d4" })
+        );
+    }
+
+    fn page(items: Vec<u32>, next: Option<&str>) -> PaginatedResponse<u32> {
+        PaginatedResponse {
+            object: "list".to_string(),
+            results: items,
+            next_cursor: next.map(str::to_string),
+            has_more: next.is_some(),
+        }
+    }
+
+    fn transient() -> AppError {
+        AppError::NotionService {
+            code: NotionErrorCode::ServiceUnavailable,
+            message: "try again".to_string(),
+            status: reqwest::StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// Runs the resumable walk with a clock that never really waits.
+    async fn resumable<T, F, Fut>(
+        fetch_fn: F,
+        clock: &ManualClock,
+        on_retry: RetryObserver<'_>,
+    ) -> Result<ResumablePagination<T>, AppError>
+    where
+        T: Send + 'static,
+        F: FnMut(PageRequest) -> Fut,
+        Fut: std::future::Future<Output = Result<PaginatedResponse<T>, AppError>>,
+    {
+        fetch_all_pages_resumable_using(fetch_fn, PageLimits::default(), clock, &NoJitter, on_retry)
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_resumable_retries_only_failing_cursor() {
+        let second_page_calls = AtomicU32::new(0);
+        let first_page_calls = AtomicU32::new(0);
+        let retries = AtomicU32::new(0);
+        let clock = ManualClock::at_unix_secs(0);
+
+        let result = resumable(
+            |request| {
+                let response = match request.start_cursor.as_deref() {
+                    None => {
+                        first_page_calls.fetch_add(1, Ordering::SeqCst);
+                        Ok(page(vec![1, 2], Some("c2")))
+                    }
+                    Some("c2") if second_page_calls.fetch_add(1, Ordering::SeqCst) == 0 => {
+                        Err(transient())
+                    }
+                    Some("c2") => Ok(page(vec![3], None)),
+                    Some(other) => panic!("unexpected cursor {}", other),
+                };
+                async move { response }
+            },
+            &clock,
+            &|_, _| {
+                retries.fetch_add(1, Ordering::SeqCst);
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.items, vec![1, 2, 3]);
+        assert!(result.interrupted.is_none());
+        assert_eq!(first_page_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(second_page_calls.load(Ordering::SeqCst), 2);
+        assert_eq!(retries.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            clock.sleeps(),
+            [Duration::from_millis(PAGINATION_RETRY_DELAY_MS)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resumable_keeps_rows_when_cursor_keeps_failing() {
+        let clock = ManualClock::at_unix_secs(0);
+        let result = resumable(
+            |request| {
+                let response = match request.start_cursor {
+                    None => Ok(page(vec![1, 2], Some("c2"))),
+                    Some(_) => Err(transient()),
+                };
+                async move { response }
+            },
+            &clock,
+            &|_, _| {},
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.items, vec![1, 2]);
+        assert!(result.interrupted.is_some());
+        assert_eq!(
+            clock.sleeps().len(),
+            PAGINATION_CURSOR_ATTEMPTS as usize - 1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resumable_does_not_retry_a_permanent_failure() {
+        let clock = ManualClock::at_unix_secs(0);
+        let cursor_calls = AtomicU32::new(0);
+        let result = resumable(
+            |request| {
+                let response = match request.start_cursor {
+                    None => Ok(page(vec![1, 2], Some("c2"))),
+                    Some(_) => {
+                        cursor_calls.fetch_add(1, Ordering::SeqCst);
+                        Err(AppError::MalformedResponse("not a list".to_string()))
+                    }
+                };
+                async move { response }
+            },
+            &clock,
+            &|_, _| {},
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.items, vec![1, 2]);
+        assert!(matches!(
+            result.interrupted,
+            Some(AppError::MalformedResponse(_))
+        ));
+        assert_eq!(cursor_calls.load(Ordering::SeqCst), 1);
+        assert!(clock.sleeps().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resumable_first_page_failure_is_an_error() {
+        let clock = ManualClock::at_unix_secs(0);
+        let result: Result<ResumablePagination<u32>, AppError> =
+            resumable(|_| async { Err(transient()) }, &clock, &|_, _| {}).await;
+
+        assert!(result.is_err());
+        assert!(clock.sleeps().is_empty());
+    }
 }
@@ -0,0 +1,166 @@
+// src/api/size_estimate.rs
+//! Rendered-size estimates for `--token-budget`, made as content arrives.
+//!
+//! The fetcher cannot render a tree it has not assembled yet, so every
+//! object is priced on its own when it is fetched: the text it carries plus
+//! an allowance for the markdown around it. Blocks without text (images,
+//! files, dividers) get a fixed size for the link or placeholder that
+//! stands in for them. The figures only need to be close enough to stop
+//! fetching before a prompt grows far past its budget.
+
+use crate::model::{
+    Block, BulletedListItemBlock, CalloutBlock, Database, Heading1Block, Heading2Block,
+    Heading3Block, NotionObject, NumberedListItemBlock, Page, ParagraphBlock, PropertyTypeValue,
+    QuoteBlock, TemplateBlock, ToDoBlock, ToggleBlock,
+};
+use crate::types::RichTextItem;
+
+/// Markup around a block's text: list markers, heading hashes, blank lines.
+const BLOCK_MARKUP: usize = 4;
+/// The link or placeholder rendered for an image, file or other media block.
+const MEDIA_CHARS: usize = 80;
+/// Pipes and padding around one table cell.
+const CELL_MARKUP: usize = 3;
+/// A property value that is not free text: a date, a select option, a number.
+const SHORT_VALUE_CHARS: usize = 12;
+/// The `- **Name**: ` around a property shown under a page title.
+const PROPERTY_LINE_MARKUP: usize = 10;
+
+/// Estimated characters `object` adds to the prompt, without its children.
+pub(super) fn object_chars(object: &NotionObject) -> usize {
+    match object {
+        NotionObject::Page(page) => page_chars(page),
+        NotionObject::Database(db) => database_chars(db),
+        NotionObject::Block(block) => block_chars(block),
+    }
+}
+
+/// A page's heading and property list.
+fn page_chars(page: &Page) -> usize {
+    let properties: usize = page
+        .properties
+        .iter()
+        .map(|(name, value)| {
+            name.as_str().len()
+                + property_value_chars(&value.type_specific_value)
+                + PROPERTY_LINE_MARKUP
+        })
+        .sum();
+    page.title.as_str().len() + BLOCK_MARKUP + properties
+}
+
+/// A database's heading, table header and any rows it came with.
+fn database_chars(db: &Database) -> usize {
+    let header: usize = db
+        .properties
+        .keys()
+        .map(|name| name.as_str().len() + CELL_MARKUP)
+        .sum();
+    db.title().as_plain_text().len() + BLOCK_MARKUP + header * 2 + rows_chars(&db.pages)
+}
+
+/// One table row per database row.
+pub(super) fn rows_chars(rows: &[Page]) -> usize {
+    rows.iter()
+        .map(|row| {
+            row.properties
+                .values()
+                .map(|value| property_value_chars(&value.type_specific_value) + CELL_MARKUP)
+                .sum::<usize>()
+                + 1
+        })
+        .sum()
+}
+
+/// Blocks as they arrive from one children request, which carries no
+/// grandchildren.
+pub(super) fn blocks_chars(blocks: &[Block]) -> usize {
+    blocks.iter().map(block_chars).sum()
+}
+
+fn block_chars(block: &Block) -> usize {
+    let content = match block {
+        Block::Paragraph(ParagraphBlock { content, .. })
+        | Block::Heading1(Heading1Block { content, .. })
+        | Block::Heading2(Heading2Block { content, .. })
+        | Block::Heading3(Heading3Block { content, .. })
+        | Block::BulletedListItem(BulletedListItemBlock { content, .. })
+        | Block::NumberedListItem(NumberedListItemBlock { content, .. })
+        | Block::Toggle(ToggleBlock { content, .. })
+        | Block::ToDo(ToDoBlock { content, .. })
+        | Block::Quote(QuoteBlock { content, .. })
+        | Block::Callout(CalloutBlock { content, .. })
+        | Block::Template(TemplateBlock { content, .. }) => rich_text_chars(&content.rich_text),
+        Block::Code(b) => {
+            rich_text_chars(&b.content.rich_text) + b.language.len() + rich_text_chars(&b.caption)
+        }
+        Block::Equation(b) => b.expression.len(),
+        Block::Image(b) => MEDIA_CHARS + rich_text_chars(&b.caption),
+        Block::Video(b) => MEDIA_CHARS + rich_text_chars(&b.caption),
+        Block::Audio(b) => MEDIA_CHARS + rich_text_chars(&b.caption),
+        Block::File(b) => MEDIA_CHARS + rich_text_chars(&b.caption),
+        Block::Pdf(b) => MEDIA_CHARS + rich_text_chars(&b.caption),
+        Block::Bookmark(b) => b.url.len() + rich_text_chars(&b.caption),
+        Block::Embed(b) => b.url.len() + rich_text_chars(&b.caption),
+        Block::LinkPreview(b) => b.url.len(),
+        Block::ChildPage(b) => b.title.len(),
+        Block::ChildDatabase(b) => b.title.len(),
+        Block::TableRow(b) => b
+            .cells
+            .iter()
+            .map(|cell| rich_text_chars(cell) + CELL_MARKUP)
+            .sum(),
+        _ => 0,
+    };
+    content + BLOCK_MARKUP
+}
+
+fn property_value_chars(value: &PropertyTypeValue) -> usize {
+    match value {
+        PropertyTypeValue::Title { title } => rich_text_chars(title),
+        PropertyTypeValue::RichText { rich_text } => rich_text_chars(rich_text),
+        _ => SHORT_VALUE_CHARS,
+    }
+}
+
+/// Text plus the link syntax around linked spans.
+fn rich_text_chars(items: &[RichTextItem]) -> usize {
+    items
+        .iter()
+        .map(|item| item.plain_text.len() + item.href.as_ref().map_or(0, |href| href.len() + 4))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BlockCommon, DividerBlock, TextBlockContent};
+    use crate::types::{BlockId, Color};
+
+    fn paragraph(text: &str) -> Block {
+        Block::Paragraph(ParagraphBlock {
+            common: BlockCommon::new(BlockId::new_v4()),
+            content: TextBlockContent {
+                rich_text: vec![RichTextItem::plain_text(text)],
+                color: Color::Default,
+            },
+        })
+    }
+
+    #[test]
+    fn test_blocks_are_priced_by_their_text_and_markup() {
+        let divider = Block::Divider(DividerBlock {
+            common: BlockCommon::new(BlockId::new_v4()),
+        });
+        assert_eq!(block_chars(&paragraph("Ship it")), 7 + BLOCK_MARKUP);
+        assert_eq!(block_chars(&divider), BLOCK_MARKUP);
+
+        let mut linked = RichTextItem::plain_text("docs");
+        linked.href = Some("https://example.com".to_string());
+        assert_eq!(rich_text_chars(&[linked]), 4 + 19 + 4);
+        assert_eq!(
+            blocks_chars(&[paragraph("a"), paragraph("bc")]),
+            3 + 2 * BLOCK_MARKUP
+        );
+    }
+}
@@ -4,8 +4,13 @@
 //! This module defines immutable types for API operations,
 //! following data-oriented design principles.
 
-use crate::types::{NotionId, Warning};
-use serde::Deserialize;
+use crate::error::AppError;
+use crate::model::{DatabaseProperty, Page};
+use crate::types::{NotionId, PageId, PropertyName, Warning, WarningLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
 
 // --- Fetch Context Types ---
 
@@ -16,10 +21,16 @@ pub struct FetchContext {
     visited: im::HashSet<NotionId>,
     /// Remaining recursion depth
     pub depth_remaining: u8,
-    /// Remaining item limit
-    pub items_remaining: u32,
+    /// Item limit split by category, shared by every context of one fetch
+    budget: Arc<FetchBudget>,
+    /// Estimated prompt size against `--token-budget`, shared likewise
+    size: Arc<SizeBudget>,
     /// Always fetch child databases regardless of depth
     pub always_fetch_databases: bool,
+    /// Objects earlier roots of the run already include; not fetched again
+    earlier_roots: Option<Arc<super::RootVisits>>,
+    /// Databases a child database block of this fetch already resolves
+    resolved_databases: Arc<parking_lot::Mutex<HashSet<NotionId>>>,
 }
 
 impl FetchContext {
@@ -42,8 +53,11 @@ impl FetchContext {
         Self {
             visited: im::HashSet::new(),
             depth_remaining: safe_depth,
-            items_remaining: max_items,
+            budget: Arc::new(FetchBudget::split(max_items)),
+            size: Arc::new(SizeBudget::new(0)),
             always_fetch_databases,
+            earlier_roots: None,
+            resolved_databases: Arc::default(),
         }
     }
 
@@ -62,17 +76,561 @@ impl FetchContext {
         }
     }
 
-    /// Returns a new context with decremented item count.
-    pub fn with_items_used(self, count: u32) -> Self {
+    /// Checks if we should continue fetching.
+    pub fn should_fetch(&self, id: &NotionId) -> bool {
+        !self.visited.contains(id) && self.depth_remaining > 0
+    }
+
+    /// Returns a new context that leaves out objects earlier roots included.
+    pub fn with_earlier_roots(self, earlier_roots: Arc<super::RootVisits>) -> Self {
         Self {
-            items_remaining: self.items_remaining.saturating_sub(count),
+            earlier_roots: Some(earlier_roots),
             ..self
         }
     }
 
-    /// Checks if we should continue fetching.
-    pub fn should_fetch(&self, id: &NotionId) -> bool {
-        !self.visited.contains(id) && self.depth_remaining > 0 && self.items_remaining > 0
+    /// Whether an earlier root of the run already includes `id`.
+    pub fn covered_by_earlier_root(&self, id: &NotionId) -> bool {
+        self.earlier_roots
+            .as_ref()
+            .is_some_and(|visits| visits.contains(id))
+    }
+
+    /// Claims `id` for the first child database block that resolves it.
+    /// False when another block of this fetch already did, so the database
+    /// is fetched once however many views of it the pages hold.
+    pub fn claim_database(&self, id: &NotionId) -> bool {
+        self.resolved_databases.lock().insert(id.clone())
+    }
+
+    /// True before the root object has been visited.
+    pub fn is_root(&self) -> bool {
+        self.visited.is_empty()
+    }
+
+    /// The item budget shared by all workers of this fetch.
+    pub fn budget(&self) -> &FetchBudget {
+        &self.budget
+    }
+
+    /// Returns a new context whose fetch stops scheduling low-priority work
+    /// once the estimated prompt reaches `tokens` (0 for no budget).
+    pub fn with_token_budget(self, tokens: u32) -> Self {
+        Self {
+            size: Arc::new(SizeBudget::new(tokens)),
+            ..self
+        }
+    }
+
+    /// The size estimate shared by all workers of this fetch.
+    pub fn size(&self) -> &SizeBudget {
+        &self.size
+    }
+}
+
+// --- Fetch Budget Types ---
+
+/// What a unit of the item limit is spent on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchCategory {
+    /// Blocks retrieved as the children of pages and blocks
+    Blocks,
+    /// Pages returned by database queries
+    DatabaseRows,
+    /// Pages and databases reached through links, beyond the root object
+    ChildPages,
+}
+
+impl FetchCategory {
+    /// Every category, in reporting order.
+    pub const ALL: [FetchCategory; 3] = [
+        FetchCategory::Blocks,
+        FetchCategory::DatabaseRows,
+        FetchCategory::ChildPages,
+    ];
+
+    fn index(self) -> usize {
+        match self {
+            FetchCategory::Blocks => 0,
+            FetchCategory::DatabaseRows => 1,
+            FetchCategory::ChildPages => 2,
+        }
+    }
+}
+
+impl std::fmt::Display for FetchCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchCategory::Blocks => write!(f, "blocks"),
+            FetchCategory::DatabaseRows => write!(f, "database rows"),
+            FetchCategory::ChildPages => write!(f, "child pages"),
+        }
+    }
+}
+
+/// The `--limit` item budget split across fetch categories.
+///
+/// Workers explore branches in parallel, each with its own [`FetchContext`],
+/// so the counters are atomic and shared behind an `Arc`.
+#[derive(Debug)]
+pub struct FetchBudget {
+    limits: [u32; 3],
+    used: [AtomicU32; 3],
+    /// Set once a category has turned away something that was asked for
+    refused: [AtomicBool; 3],
+}
+
+impl FetchBudget {
+    /// Splits `limit` by the proportions in [`crate::constants`]; blocks get
+    /// whatever rows and child pages leave, including rounding.
+    pub fn split(limit: u32) -> Self {
+        let share = |percent: u32| (u64::from(limit) * u64::from(percent) / 100) as u32;
+        let rows = share(crate::constants::FETCH_BUDGET_ROWS_PERCENT);
+        let child_pages = share(crate::constants::FETCH_BUDGET_CHILD_PAGES_PERCENT);
+        Self {
+            limits: [limit - rows - child_pages, rows, child_pages],
+            used: Default::default(),
+            refused: Default::default(),
+        }
+    }
+
+    /// Items still available in `category`.
+    pub fn remaining(&self, category: FetchCategory) -> u32 {
+        let i = category.index();
+        self.limits[i].saturating_sub(self.used[i].load(Ordering::Relaxed))
+    }
+
+    /// Checks whether `category` has room left, noting the refusal if not.
+    pub fn has_room(&self, category: FetchCategory) -> bool {
+        let room = self.remaining(category) > 0;
+        if !room {
+            self.refused[category.index()].store(true, Ordering::Relaxed);
+        }
+        room
+    }
+
+    /// Takes up to `wanted` items from `category` and returns how many were granted.
+    pub fn claim(&self, category: FetchCategory, wanted: u32) -> u32 {
+        let i = category.index();
+        let limit = self.limits[i];
+        let previous = self.used[i]
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                Some(used.saturating_add(wanted).min(limit.max(used)))
+            })
+            .unwrap_or_else(|used| used);
+        let granted = limit.saturating_sub(previous).min(wanted);
+        if granted < wanted {
+            self.refused[i].store(true, Ordering::Relaxed);
+        }
+        granted
+    }
+
+    /// Counts items that were fetched whole, even if they overrun the limit.
+    pub fn record(&self, category: FetchCategory, count: u32) {
+        self.used[category.index()].fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// A snapshot of how much of each category has been spent.
+    pub fn usage(&self) -> BudgetUsage {
+        let usage = |category: FetchCategory| CategoryUsage {
+            used: self.used[category.index()].load(Ordering::Relaxed),
+            limit: self.limits[category.index()],
+            exhausted: self.refused[category.index()].load(Ordering::Relaxed),
+        };
+        BudgetUsage {
+            blocks: usage(FetchCategory::Blocks),
+            database_rows: usage(FetchCategory::DatabaseRows),
+            child_pages: usage(FetchCategory::ChildPages),
+        }
+    }
+}
+
+/// Items spent against one category's share of the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CategoryUsage {
+    pub used: u32,
+    pub limit: u32,
+    /// Whether content was skipped or cut short because the share ran out
+    pub exhausted: bool,
+}
+
+/// Per-category spending at the end of a fetch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BudgetUsage {
+    pub blocks: CategoryUsage,
+    pub database_rows: CategoryUsage,
+    pub child_pages: CategoryUsage,
+}
+
+impl BudgetUsage {
+    /// The usage recorded for `category`.
+    pub fn get(&self, category: FetchCategory) -> CategoryUsage {
+        match category {
+            FetchCategory::Blocks => self.blocks,
+            FetchCategory::DatabaseRows => self.database_rows,
+            FetchCategory::ChildPages => self.child_pages,
+        }
+    }
+
+    /// Categories whose share ran out, in reporting order.
+    pub fn exhausted(&self) -> Vec<FetchCategory> {
+        FetchCategory::ALL
+            .into_iter()
+            .filter(|category| self.get(*category).exhausted)
+            .collect()
+    }
+}
+
+impl std::fmt::Display for BudgetUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, category) in FetchCategory::ALL.into_iter().enumerate() {
+            let usage = self.get(category);
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}/{} {}", usage.used, usage.limit, category)?;
+        }
+        Ok(())
+    }
+}
+
+// --- Size Budget Types ---
+
+/// The estimated rendered size of a fetch, checked against `--token-budget`.
+///
+/// Objects are priced as they arrive (see `size_estimate`), so the estimate
+/// only grows; once it reaches the budget, work that can be left out is no
+/// longer scheduled.
+#[derive(Debug, Default)]
+pub struct SizeBudget {
+    /// The budget in characters, if any
+    limit: Option<u64>,
+    estimated: AtomicU64,
+    /// Set once work was turned away because the budget was reached
+    refused: AtomicBool,
+}
+
+impl SizeBudget {
+    /// A budget of `tokens`, or none for 0.
+    pub fn new(tokens: u32) -> Self {
+        Self {
+            limit: (tokens > 0)
+                .then(|| u64::from(tokens) * crate::constants::CHARS_PER_TOKEN_ESTIMATE as u64),
+            ..Self::default()
+        }
+    }
+
+    /// Adds `chars` to the estimate.
+    pub fn record(&self, chars: usize) {
+        self.estimated.fetch_add(chars as u64, Ordering::Relaxed);
+    }
+
+    /// Checks whether the estimate is still under budget, noting the
+    /// refusal if not.
+    pub fn has_room(&self) -> bool {
+        let Some(limit) = self.limit else {
+            return true;
+        };
+        let room = self.estimated.load(Ordering::Relaxed) < limit;
+        if !room {
+            self.refused.store(true, Ordering::Relaxed);
+        }
+        room
+    }
+
+    /// The estimate against the budget, when there is one.
+    pub fn usage(&self) -> Option<SizeUsage> {
+        let chars_per_token = crate::constants::CHARS_PER_TOKEN_ESTIMATE as u64;
+        self.limit.map(|limit| SizeUsage {
+            estimated_tokens: self.estimated.load(Ordering::Relaxed) / chars_per_token,
+            budget_tokens: limit / chars_per_token,
+            reached: self.refused.load(Ordering::Relaxed),
+        })
+    }
+}
+
+/// The estimated prompt size at the end of a fetch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SizeUsage {
+    pub estimated_tokens: u64,
+    pub budget_tokens: u64,
+    /// Whether lower-priority content was skipped because the budget was reached
+    pub reached: bool,
+}
+
+impl std::fmt::Display for SizeUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "~{} of {} tokens",
+            self.estimated_tokens, self.budget_tokens
+        )
+    }
+}
+
+/// The API endpoint families that calls are counted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointCategory {
+    Pages,
+    Blocks,
+    Databases,
+    DataSources,
+    Search,
+    Users,
+    Other,
+}
+
+impl EndpointCategory {
+    const COUNT: usize = 7;
+
+    /// The category of a relative endpoint such as `blocks/{id}/children`.
+    pub fn of(endpoint: &str) -> Self {
+        match endpoint.split(['/', '?']).next().unwrap_or_default() {
+            "pages" => Self::Pages,
+            "blocks" => Self::Blocks,
+            "databases" => Self::Databases,
+            "data_sources" => Self::DataSources,
+            "search" => Self::Search,
+            "users" => Self::Users,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Counts response payloads as they arrive, shared by every clone of a client.
+///
+/// Payload sizes are measured after decompression; with gzip on the wire,
+/// the bytes actually transferred are usually several times smaller.
+/// Alongside the payloads it counts calls per endpoint category, rate
+/// limited responses, and the retries made and time waited because of them.
+#[derive(Debug, Default)]
+pub struct TransferCounter {
+    requests: AtomicU64,
+    bytes_downloaded: AtomicU64,
+    cache_hits: AtomicU64,
+    bytes_from_cache: AtomicU64,
+    cache_added: AtomicU64,
+    cache_refreshed: AtomicU64,
+    calls: [AtomicU64; EndpointCategory::COUNT],
+    rate_limited: AtomicU64,
+    retries: AtomicU64,
+    throttled_ms: AtomicU64,
+}
+
+impl TransferCounter {
+    /// Counts a response that came from the Notion API.
+    pub fn record_download(&self, category: EndpointCategory, bytes: usize) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.bytes_downloaded
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        self.calls[category as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a response answered from the disk cache instead.
+    pub fn record_cache_hit(&self, bytes: usize) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+        self.bytes_from_cache
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Counts a fetched response stored in the disk cache, as a refresh when
+    /// it `replaced` an entry already there.
+    pub fn record_cache_store(&self, replaced: bool) {
+        let counter = if replaced {
+            &self.cache_refreshed
+        } else {
+            &self.cache_added
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a 429 response.
+    pub fn record_rate_limited(&self) {
+        self.rate_limited.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a retry made `wait` after `error`. The wait counts as
+    /// throttled time when the error was a rate limit.
+    pub fn record_retry(&self, error: &AppError, wait: std::time::Duration) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+        if error.exit_code() == crate::error::ExitCode::RateLimited {
+            self.throttled_ms
+                .fetch_add(wait.as_millis() as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// A snapshot of everything counted so far.
+    pub fn usage(&self) -> TransferUsage {
+        let calls =
+            |category: EndpointCategory| self.calls[category as usize].load(Ordering::Relaxed);
+        TransferUsage {
+            requests: self.requests.load(Ordering::Relaxed),
+            bytes_downloaded: self.bytes_downloaded.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            bytes_from_cache: self.bytes_from_cache.load(Ordering::Relaxed),
+            cache_added: self.cache_added.load(Ordering::Relaxed),
+            cache_refreshed: self.cache_refreshed.load(Ordering::Relaxed),
+            calls: EndpointCalls {
+                pages: calls(EndpointCategory::Pages),
+                blocks: calls(EndpointCategory::Blocks),
+                databases: calls(EndpointCategory::Databases),
+                data_sources: calls(EndpointCategory::DataSources),
+                search: calls(EndpointCategory::Search),
+                users: calls(EndpointCategory::Users),
+                other: calls(EndpointCategory::Other),
+            },
+            rate_limited: self.rate_limited.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            throttled_ms: self.throttled_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Requests answered by the Notion API, per endpoint category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct EndpointCalls {
+    pub pages: u64,
+    pub blocks: u64,
+    pub databases: u64,
+    pub data_sources: u64,
+    pub search: u64,
+    pub users: u64,
+    pub other: u64,
+}
+
+impl EndpointCalls {
+    fn since(self, earlier: Self) -> Self {
+        Self {
+            pages: self.pages.saturating_sub(earlier.pages),
+            blocks: self.blocks.saturating_sub(earlier.blocks),
+            databases: self.databases.saturating_sub(earlier.databases),
+            data_sources: self.data_sources.saturating_sub(earlier.data_sources),
+            search: self.search.saturating_sub(earlier.search),
+            users: self.users.saturating_sub(earlier.users),
+            other: self.other.saturating_sub(earlier.other),
+        }
+    }
+
+    /// The categories that were called, busiest first.
+    fn by_count(&self) -> Vec<(&'static str, u64)> {
+        let mut calls: Vec<_> = [
+            ("pages", self.pages),
+            ("blocks", self.blocks),
+            ("databases", self.databases),
+            ("data sources", self.data_sources),
+            ("search", self.search),
+            ("users", self.users),
+            ("other", self.other),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .collect();
+        calls.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        calls
+    }
+}
+
+/// Response payloads of a fetch, split by where they came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct TransferUsage {
+    /// Requests answered by the Notion API
+    pub requests: u64,
+    /// Decompressed size of those responses
+    pub bytes_downloaded: u64,
+    /// Responses answered from the disk cache
+    pub cache_hits: u64,
+    pub bytes_from_cache: u64,
+    /// Fetched responses stored in the disk cache under a new key
+    pub cache_added: u64,
+    /// Fetched responses stored over an entry already in the disk cache
+    pub cache_refreshed: u64,
+    /// `requests` split by endpoint category
+    pub calls: EndpointCalls,
+    /// Responses that were 429 Too Many Requests
+    pub rate_limited: u64,
+    /// Requests repeated after a failure, rate limits included
+    pub retries: u64,
+    /// Time spent waiting to retry rate limited requests
+    pub throttled_ms: u64,
+}
+
+impl TransferUsage {
+    /// What was transferred after `earlier` was taken from the same counter.
+    pub fn since(self, earlier: Self) -> Self {
+        Self {
+            requests: self.requests.saturating_sub(earlier.requests),
+            bytes_downloaded: self
+                .bytes_downloaded
+                .saturating_sub(earlier.bytes_downloaded),
+            cache_hits: self.cache_hits.saturating_sub(earlier.cache_hits),
+            bytes_from_cache: self
+                .bytes_from_cache
+                .saturating_sub(earlier.bytes_from_cache),
+            cache_added: self.cache_added.saturating_sub(earlier.cache_added),
+            cache_refreshed: self.cache_refreshed.saturating_sub(earlier.cache_refreshed),
+            calls: self.calls.since(earlier.calls),
+            rate_limited: self.rate_limited.saturating_sub(earlier.rate_limited),
+            retries: self.retries.saturating_sub(earlier.retries),
+            throttled_ms: self.throttled_ms.saturating_sub(earlier.throttled_ms),
+        }
+    }
+
+    /// API calls by endpoint category and how much rate limiting slowed
+    /// them down, e.g. `42 API calls (blocks 30, pages 12); 3 rate limited,
+    /// 4 retries, 6.5s throttled`.
+    pub fn rate_limit_summary(&self) -> String {
+        let mut summary = format!("{} API calls", self.requests);
+        let calls = self.calls.by_count();
+        if !calls.is_empty() {
+            let calls: Vec<String> = calls
+                .iter()
+                .map(|(category, count)| format!("{} {}", category, count))
+                .collect();
+            summary.push_str(&format!(" ({})", calls.join(", ")));
+        }
+        summary.push_str(&format!(
+            "; {} rate limited, {} retries, {:.1}s throttled",
+            self.rate_limited,
+            self.retries,
+            self.throttled_ms as f64 / 1000.0
+        ));
+        summary
+    }
+}
+
+impl std::fmt::Display for TransferUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} requests, {} downloaded",
+            self.requests,
+            format_bytes(self.bytes_downloaded)
+        )?;
+        if self.cache_hits > 0 {
+            write!(
+                f,
+                "; {} cache hits, {} from cache",
+                self.cache_hits,
+                format_bytes(self.bytes_from_cache)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats a byte count with a binary unit, e.g. `1.5 MiB`.
+pub(super) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
     }
 }
 
@@ -114,6 +672,12 @@ pub struct FetchMetadata {
     pub links_found: Vec<DiscoveredLink>,
     /// Warnings generated during fetch
     pub warnings: Vec<Warning>,
+    /// Per-category budget spending, filled in once the fetch completes
+    pub budget: Option<BudgetUsage>,
+    /// Estimated prompt size against `--token-budget`, filled in once the fetch completes
+    pub size: Option<SizeUsage>,
+    /// Response payloads of the fetch, filled in once the fetch completes
+    pub transfer: Option<TransferUsage>,
 }
 
 impl FetchMetadata {
@@ -124,6 +688,9 @@ impl FetchMetadata {
             max_depth_reached: self.max_depth_reached.max(other.max_depth_reached),
             links_found: [self.links_found, other.links_found].concat(),
             warnings: [self.warnings, other.warnings].concat(),
+            budget: other.budget.or(self.budget),
+            size: other.size.or(self.size),
+            transfer: other.transfer.or(self.transfer),
         }
     }
 }
@@ -160,24 +727,47 @@ pub enum LinkOrigin {
 
 /// Hint about what type of Notion object an ID refers to.
 ///
-/// Detected from URL structure to avoid wasting API calls trying
-/// the wrong endpoint first.
+/// Detected from URL structure, or from how a link was found, to avoid
+/// wasting API calls trying the wrong endpoint first.
 #[derive(Debug, Clone, PartialEq)]
 pub enum ObjectTypeHint {
-    /// URL contains `?v=` — likely a database view
+    /// The URL names a database view (`v=` query parameter), or the link
+    /// points at a database
     Database,
-    /// No URL clues available
+    /// No clues available
     Unknown,
 }
 
 impl ObjectTypeHint {
     /// Detects a type hint from a raw Notion URL or ID string.
+    ///
+    /// Only the query string says anything about the type: a non-empty
+    /// `v` parameter names a database view, wherever it sits among other
+    /// parameters such as `pvs`, `p` and `pm`. The path (titles, hyphenated
+    /// or bare IDs) and the `#` fragment look the same for pages and
+    /// databases, so they are not consulted.
     pub fn from_input(input: &str) -> Self {
-        // Database views have a `?v=` query parameter
-        if input.contains("?v=") || input.contains("&v=") {
-            return ObjectTypeHint::Database;
+        let without_fragment = input.trim().split('#').next().unwrap_or_default();
+        let Some((_, query)) = without_fragment.split_once('?') else {
+            return ObjectTypeHint::Unknown;
+        };
+        let names_view = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .any(|(key, value)| key == "v" && !value.is_empty());
+        if names_view {
+            ObjectTypeHint::Database
+        } else {
+            ObjectTypeHint::Unknown
+        }
+    }
+
+    /// The hint for a link discovered in fetched content.
+    pub fn for_link(link_type: &LinkType) -> Self {
+        match link_type {
+            LinkType::Database => ObjectTypeHint::Database,
+            LinkType::Page | LinkType::Block | LinkType::Unknown => ObjectTypeHint::Unknown,
         }
-        ObjectTypeHint::Unknown
     }
 }
 
@@ -237,6 +827,108 @@ pub struct PaginationResult<T> {
     pub total_fetched: usize,
 }
 
+/// Result of a resumable pagination run.
+///
+/// `interrupted` holds the error for a cursor that kept failing after at
+/// least one page had been fetched; `items` are the pages gathered before it.
+#[derive(Debug)]
+pub struct ResumablePagination<T> {
+    pub items: Vec<T>,
+    pub interrupted: Option<AppError>,
+}
+
+/// A data source as listed by its database: enough to query it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataSourceRef {
+    pub id: NotionId,
+    pub name: String,
+}
+
+/// One data source's schema and rows.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DataSourceRows {
+    pub properties: HashMap<PropertyName, DatabaseProperty>,
+    pub rows: DatabaseRows,
+}
+
+/// A row as a database query lists it.
+#[derive(Debug, Clone)]
+pub enum QueriedRow {
+    /// A row the integration may read
+    Page(Page),
+    /// A row listed as a bare ID: its page is not shared with the integration
+    Inaccessible(PageId),
+}
+
+/// The rows of a database query, possibly cut short by a pagination failure.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatabaseRows {
+    pub pages: Vec<Page>,
+    /// Rows the query listed without their content, for lack of access
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inaccessible: Vec<PageId>,
+    /// Set when pagination stopped early and `pages` is incomplete.
+    pub warning: Option<Warning>,
+}
+
+impl DatabaseRows {
+    /// Builds the rows for `database`, newest first, turning an
+    /// interruption into a warning.
+    pub fn from_pagination(
+        database: &NotionId,
+        rows: Vec<QueriedRow>,
+        interrupted: Option<AppError>,
+    ) -> Self {
+        let mut pages = Vec::with_capacity(rows.len());
+        let mut inaccessible = Vec::new();
+        for row in rows {
+            match row {
+                QueriedRow::Page(page) => pages.push(page),
+                QueriedRow::Inaccessible(id) => inaccessible.push(id),
+            }
+        }
+        super::client::sort_pages_by_date_desc(&mut pages);
+        if !inaccessible.is_empty() {
+            log::warn!(
+                "{} rows of database {} are not shared with the integration",
+                inaccessible.len(),
+                database.as_str()
+            );
+        }
+        let warning = interrupted.map(|error| {
+            log::warn!(
+                "Row pagination for database {} stopped after {} rows: {}",
+                database.as_str(),
+                pages.len(),
+                error
+            );
+            Warning::new(
+                WarningLevel::Warning,
+                format!(
+                    "Database {} is incomplete: only the first {} rows could be fetched",
+                    database.as_str(),
+                    pages.len()
+                ),
+            )
+            .with_context(error.to_string())
+        });
+        Self {
+            pages,
+            inaccessible,
+            warning,
+        }
+    }
+}
+
+/// The integration an API key belongs to, as reported by `GET /users/me`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrationIdentity {
+    /// The integration's display name
+    pub name: Option<String>,
+    /// The workspace the integration was installed in
+    pub workspace_name: Option<String>,
+}
+
 /// Request for paginated data.
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -253,3 +945,214 @@ impl Default for PaginationRequest {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_splits_limit_by_category() {
+        let usage = FetchBudget::split(1000).usage();
+        assert_eq!(usage.blocks.limit, 600);
+        assert_eq!(usage.database_rows.limit, 300);
+        assert_eq!(usage.child_pages.limit, 100);
+
+        // Rounding leftovers go to blocks, so the shares always add up.
+        let usage = FetchBudget::split(7).usage();
+        assert_eq!(
+            (
+                usage.blocks.limit,
+                usage.database_rows.limit,
+                usage.child_pages.limit
+            ),
+            (5, 2, 0)
+        );
+    }
+
+    #[test]
+    fn test_budget_claims_are_capped_and_reported() {
+        let budget = FetchBudget::split(100);
+        assert_eq!(budget.claim(FetchCategory::DatabaseRows, 25), 25);
+        assert_eq!(budget.claim(FetchCategory::DatabaseRows, 25), 5);
+        assert_eq!(budget.claim(FetchCategory::DatabaseRows, 1), 0);
+        assert!(budget.has_room(FetchCategory::Blocks));
+
+        // Blocks are recorded whole and may overrun their share.
+        budget.record(FetchCategory::Blocks, 70);
+        assert!(!budget.has_room(FetchCategory::Blocks));
+
+        let usage = budget.usage();
+        assert_eq!(
+            usage.exhausted(),
+            vec![FetchCategory::Blocks, FetchCategory::DatabaseRows]
+        );
+        assert_eq!(
+            usage.to_string(),
+            "70/60 blocks, 30/30 database rows, 0/10 child pages"
+        );
+    }
+
+    #[test]
+    fn test_budget_is_shared_across_context_clones() {
+        let root = FetchContext::new(5, 10);
+        let branch = root.clone().with_decremented_depth();
+        branch.budget().claim(FetchCategory::ChildPages, 1);
+        assert_eq!(root.budget().remaining(FetchCategory::ChildPages), 0);
+        assert!(root.is_root());
+        assert!(!branch
+            .with_visited(NotionId::parse("12345678123456781234567812345678").unwrap())
+            .is_root());
+    }
+
+    #[test]
+    fn test_size_budget_refuses_once_reached() {
+        let root = FetchContext::new(5, 10).with_token_budget(10);
+        let branch = root.clone().with_decremented_depth();
+        branch.size().record(39);
+        assert!(root.size().has_room());
+        assert!(!root.size().usage().unwrap().reached);
+
+        branch.size().record(1);
+        assert!(!root.size().has_room());
+        let usage = root.size().usage().unwrap();
+        assert_eq!((usage.estimated_tokens, usage.budget_tokens), (10, 10));
+        assert!(usage.reached);
+
+        let unlimited = SizeBudget::new(0);
+        unlimited.record(1_000_000);
+        assert!(unlimited.has_room());
+        assert_eq!(unlimited.usage(), None);
+    }
+
+    #[test]
+    fn test_transfer_usage_since_snapshot_and_display() {
+        let counter = TransferCounter::default();
+        counter.record_download(EndpointCategory::Pages, 100);
+        let before = counter.usage();
+        counter.record_download(EndpointCategory::Blocks, 3 * 1024 * 1024 / 2);
+        counter.record_download(EndpointCategory::Blocks, 512);
+        counter.record_cache_hit(2048);
+
+        let usage = counter.usage().since(before);
+
+        assert_eq!(usage.requests, 2);
+        assert_eq!(usage.bytes_downloaded, 3 * 1024 * 1024 / 2 + 512);
+        assert_eq!(
+            usage.to_string(),
+            "2 requests, 1.5 MiB downloaded; 1 cache hits, 2.0 KiB from cache"
+        );
+        assert_eq!(
+            TransferUsage::default().to_string(),
+            "0 requests, 0 B downloaded"
+        );
+    }
+
+    #[test]
+    fn test_endpoint_categories() {
+        assert_eq!(EndpointCategory::of("pages/abc"), EndpointCategory::Pages);
+        assert_eq!(
+            EndpointCategory::of("blocks/abc/children?page_size=100"),
+            EndpointCategory::Blocks
+        );
+        assert_eq!(
+            EndpointCategory::of("databases/abc/query"),
+            EndpointCategory::Databases
+        );
+        assert_eq!(
+            EndpointCategory::of("data_sources/abc"),
+            EndpointCategory::DataSources
+        );
+        assert_eq!(EndpointCategory::of("search"), EndpointCategory::Search);
+        assert_eq!(EndpointCategory::of("users/me"), EndpointCategory::Users);
+        assert_eq!(EndpointCategory::of("comments"), EndpointCategory::Other);
+    }
+
+    #[test]
+    fn test_rate_limit_summary_counts_throttled_retries_only() {
+        let counter = TransferCounter::default();
+        counter.record_download(EndpointCategory::Pages, 10);
+        for _ in 0..3 {
+            counter.record_download(EndpointCategory::Blocks, 10);
+        }
+        counter.record_rate_limited();
+        counter.record_retry(
+            &AppError::NotionService {
+                code: crate::error::NotionErrorCode::RateLimited,
+                message: "slow down".to_string(),
+                status: reqwest::StatusCode::TOO_MANY_REQUESTS,
+            },
+            std::time::Duration::from_millis(1500),
+        );
+        counter.record_retry(
+            &AppError::MissingConfiguration("transient".to_string()),
+            std::time::Duration::from_millis(200),
+        );
+
+        let usage = counter.usage();
+        assert_eq!(usage.calls.blocks, 3);
+        assert_eq!(
+            usage.rate_limit_summary(),
+            "4 API calls (blocks 3, pages 1); 1 rate limited, 2 retries, 1.5s throttled"
+        );
+        assert_eq!(
+            TransferUsage::default().rate_limit_summary(),
+            "0 API calls; 0 rate limited, 0 retries, 0.0s throttled"
+        );
+    }
+
+    #[test]
+    fn test_type_hint_from_url_shapes() {
+        let db = "0123456789abcdef0123456789abcdef";
+        let view = "fedcba9876543210fedcba9876543210";
+        let databases = [
+            format!("https://www.notion.so/acme/{db}?v={view}"),
+            format!("https://www.notion.so/acme/Tasks-{db}?v={view}"),
+            format!("https://www.notion.so/acme/Tasks-Tasks-{db}?v={view}&pvs=4"),
+            format!("https://www.notion.so/{db}?pvs=4&v={view}"),
+            format!("https://www.notion.so/acme/{db}?v={view}&p=abcd&pm=s"),
+            format!("https://acme.notion.site/{db}?v={view}#heading"),
+            format!("notion.so/acme/{db}?v={view}"),
+            format!("https://www.notion.so/acme/{db}/?v={view}"),
+        ];
+        for url in &databases {
+            assert_eq!(
+                ObjectTypeHint::from_input(url),
+                ObjectTypeHint::Database,
+                "{}",
+                url
+            );
+        }
+
+        let unknown = [
+            db.to_string(),
+            "01234567-89ab-cdef-0123-456789abcdef".to_string(),
+            format!("https://www.notion.so/acme/Roadmap-{db}"),
+            format!("https://www.notion.so/acme/Roadmap-{db}?pvs=4"),
+            format!("https://www.notion.so/Roadmap-2024-{db}?pvs=25"),
+            format!("https://www.notion.so/acme/Roadmap-{db}#{view}"),
+            format!("https://www.notion.so/acme/Roadmap-{db}#section?v={view}"),
+            format!("https://www.notion.so/acme/{db}?v="),
+            format!("https://www.notion.so/acme/{db}?pv={view}"),
+        ];
+        for input in &unknown {
+            assert_eq!(
+                ObjectTypeHint::from_input(input),
+                ObjectTypeHint::Unknown,
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_type_hint_for_links() {
+        assert_eq!(
+            ObjectTypeHint::for_link(&LinkType::Database),
+            ObjectTypeHint::Database
+        );
+        assert_eq!(
+            ObjectTypeHint::for_link(&LinkType::Page),
+            ObjectTypeHint::Unknown
+        );
+    }
+}
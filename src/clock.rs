@@ -0,0 +1,141 @@
+// src/clock.rs
+//! Time and randomness as injected capabilities.
+//!
+//! Retry backoff and cache expiry depend on the wall clock and on random
+//! jitter, which makes them impossible to test deterministically when they
+//! call `SystemTime::now()` and `tokio::time::sleep` directly. Production
+//! code uses [`SystemClock`] and [`RandomJitter`]; tests substitute a
+//! manually advanced clock and a jitter that leaves delays untouched.
+
+use crate::constants::RETRY_JITTER_RATIO;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A source of the current time that can also wait.
+#[async_trait::async_trait]
+pub trait Clock: Send + Sync {
+    /// The current wall-clock time.
+    fn now(&self) -> SystemTime;
+
+    /// Waits for `duration` to pass on this clock.
+    async fn sleep(&self, duration: Duration);
+
+    /// Whole seconds since the Unix epoch, saturating to zero before it.
+    fn unix_secs(&self) -> u64 {
+        self.now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+/// The real clock, backed by the system time and the tokio timer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[async_trait::async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// Perturbs a backoff delay so concurrent retries do not line up.
+pub trait Jitter: Send + Sync {
+    /// Returns the delay to actually wait, given the nominal backoff delay.
+    fn apply(&self, delay: Duration) -> Duration;
+}
+
+/// Adds a uniformly random extra delay of up to [`RETRY_JITTER_RATIO`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomJitter;
+
+impl Jitter for RandomJitter {
+    fn apply(&self, delay: Duration) -> Duration {
+        let factor: f64 = rand::random_range(0.0..=RETRY_JITTER_RATIO);
+        delay + delay.mul_f64(factor)
+    }
+}
+
+/// Leaves delays unchanged, so backoff sequences are exact.
+#[cfg(test)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoJitter;
+
+#[cfg(test)]
+impl Jitter for NoJitter {
+    fn apply(&self, delay: Duration) -> Duration {
+        delay
+    }
+}
+
+/// A clock that only moves when told to. Sleeping advances it instantly
+/// and records the requested duration.
+#[cfg(test)]
+#[derive(Debug)]
+pub struct ManualClock {
+    now: parking_lot::Mutex<SystemTime>,
+    sleeps: parking_lot::Mutex<Vec<Duration>>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    /// Starts the clock at `secs` seconds after the Unix epoch.
+    pub fn at_unix_secs(secs: u64) -> Self {
+        Self {
+            now: parking_lot::Mutex::new(UNIX_EPOCH + Duration::from_secs(secs)),
+            sleeps: parking_lot::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Moves the clock forward without recording a sleep.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock() += duration;
+    }
+
+    /// Every duration passed to [`Clock::sleep`], in order.
+    pub fn sleeps(&self) -> Vec<Duration> {
+        self.sleeps.lock().clone()
+    }
+}
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        self.sleeps.lock().push(duration);
+        self.advance(duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_jitter_stays_within_ratio() {
+        let delay = Duration::from_millis(400);
+        for _ in 0..100 {
+            let jittered = RandomJitter.apply(delay);
+            assert!(jittered >= delay);
+            assert!(jittered <= delay.mul_f64(1.0 + RETRY_JITTER_RATIO));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_manual_clock_advances_on_sleep() {
+        let clock = ManualClock::at_unix_secs(1_000);
+        clock.sleep(Duration::from_secs(5)).await;
+        clock.advance(Duration::from_secs(10));
+
+        assert_eq!(clock.unix_secs(), 1_015);
+        assert_eq!(clock.sleeps(), vec![Duration::from_secs(5)]);
+    }
+}
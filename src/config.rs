@@ -1,9 +1,23 @@
 // src/config.rs
-use crate::error::AppError;
-use crate::types::{ApiKey, NotionId, TemplateName};
-use clap::Parser;
+use crate::analytics::ExpectedSchema;
+use crate::console::{ColorChoice, Verbosity};
+use crate::constants::{
+    HTTP_POOL_MAX_IDLE_PER_HOST, HTTP_TCP_KEEPALIVE_SECS, INLINE_DATABASE_MAX_DEPTH,
+    NOTION_API_BASE_URL, NOTION_MAX_BLOCK_PAYLOAD_BYTES, PAGE_MAX_PROPERTIES,
+    PROPERTY_MAX_VALUE_CHARS, TABLE_MAX_CELL_CHARS, TOGGLE_COLLAPSE_THRESHOLD_CHARS,
+};
+use crate::error::{exit_code_help, AppError};
+use crate::formatting::plugins::{ComposePlugin, ComposePlugins};
+use crate::formatting::{
+    BlockRules, CalloutRule, ColumnAlign, DatabaseTitleOverride, RowPredicate, RowSort,
+    RowTitleGlob,
+};
+use crate::types::{ApiKey, NotionId, PropertyName, TemplateName, ValidationError};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
 use std::default::Default;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Creates a unique temporary content directory.
 fn create_temp_content_dir() -> PathBuf {
@@ -15,104 +29,1037 @@ fn create_temp_content_dir() -> PathBuf {
     std::env::temp_dir().join(format!("notion_fetcher_{}", timestamp))
 }
 
+/// How toggle blocks render once their body exceeds the collapse threshold.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToggleStyle {
+    /// Always indent toggle content under a `▸` marker
+    #[default]
+    Indent,
+    /// Wrap large toggles in `<details>`/`<summary>` (HTML and GFM renderers)
+    Details,
+    /// Replace large toggle bodies with a one-line summary marker
+    Summary,
+}
+
+/// How heading blocks with children (Notion's toggleable headings) render.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeadingToggles {
+    /// Render the content under the heading, as if the toggle were open
+    #[default]
+    Expand,
+    /// Keep the heading and replace its content with a one-line summary marker
+    Collapse,
+}
+
+/// How equations are delimited in the rendered markdown.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MathStyle {
+    /// `$…$` inline and `$$…$$` blocks
+    #[default]
+    Dollar,
+    /// `\(…\)` inline and `\[…\]` blocks, for KaTeX/MathJax setups without dollar delimiters
+    Latex,
+    /// Inline code spans and ```` ```math ```` blocks, which markdown never reinterprets
+    Code,
+}
+
+/// How callouts render once their icon or color gives them a type.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CalloutStyle {
+    /// A blockquote opening with the callout's icon, whatever its type
+    #[default]
+    Quote,
+    /// GitHub alerts, e.g. `> [!WARNING]`
+    Gfm,
+    /// Obsidian callouts, e.g. `> [!warning]`
+    Obsidian,
+}
+
+/// How links to external URLs render.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LinkStyle {
+    /// `[text](https://…)` where the link stands
+    #[default]
+    Inline,
+    /// `[text][3]`, with the URLs numbered once under a closing `## Links`
+    Reference,
+    /// `text (https://…)`, without link syntax
+    Bare,
+    /// `text[^3]`, with the URLs as footnote definitions under a closing `## Links`
+    Footnote,
+}
+
+/// The language of the labels notion2prompt adds to prompts.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    /// English
+    #[default]
+    En,
+    /// German
+    De,
+    /// Finnish
+    Fi,
+}
+
+impl Language {
+    /// The BCP 47 tag, as used in `lang` attributes.
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::En => "en",
+            Language::De => "de",
+            Language::Fi => "fi",
+        }
+    }
+}
+
+/// Where a page's Properties section goes in the prompt.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PropertyPlacement {
+    /// Leave the section out
+    Hide,
+    /// Between the title and the content
+    #[default]
+    Top,
+    /// After the content, before the metadata
+    Bottom,
+}
+
+/// What the generated Summary section at the top of each page holds.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AutoSummary {
+    /// No Summary section
+    #[default]
+    Off,
+    /// The page's headings as a nested list
+    Outline,
+    /// The page's first paragraphs
+    Lead,
+}
+
+/// Which cached API responses a run may read and write.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheScope {
+    /// Share the user cache directory with every other run
+    #[default]
+    Shared,
+    /// Use a private cache that no other run sees and that is deleted on exit
+    Run,
+}
+
+/// Which HTTP version the Notion client speaks.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HttpVersion {
+    /// Negotiate HTTP/2 with the server, falling back to HTTP/1.1
+    #[default]
+    Auto,
+    /// Always use HTTP/1.1, e.g. behind proxies that mishandle HTTP/2
+    Http1,
+    /// Use HTTP/2 without negotiating it first
+    Http2,
+}
+
+/// Connection pool configuration of the Notion HTTP client.
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct PoolConfig {
+    /// Maximum idle connections per host
+    pub max_idle_per_host: usize,
+    /// Connection timeout
+    pub connect_timeout: Duration,
+    /// Longest a single request may take, from `--request-timeout`
+    pub request_timeout: Duration,
+    /// Pool timeout
+    pub pool_timeout: Duration,
+    /// Maximum connections
+    pub max_connections: usize,
+    /// HTTP version to speak
+    pub http_version: HttpVersion,
+    /// Enable connection keep-alive
+    pub tcp_keepalive: Option<Duration>,
+    /// Proxy for all requests; `None` uses HTTPS_PROXY/HTTP_PROXY/ALL_PROXY from the environment
+    pub proxy: Option<String>,
+    /// Extra headers sent with every request, e.g. trace or audit IDs an
+    /// inspection proxy expects; Notion's own headers take precedence
+    pub headers: Vec<(String, String)>,
+    /// PEM client certificate presented for mutual TLS; may also hold the key
+    pub client_cert: Option<PathBuf>,
+    /// PEM private key for `client_cert`, when stored separately
+    pub client_key: Option<PathBuf>,
+    /// Base URL of the Notion API, or of a gateway in front of it
+    pub api_base_url: String,
+    /// Header carrying the bare API key for gateways that expect one;
+    /// `None` sends the usual `Authorization: Bearer <key>`
+    pub auth_header: Option<String>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: HTTP_POOL_MAX_IDLE_PER_HOST,
+            connect_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(30),
+            pool_timeout: Duration::from_secs(90),
+            max_connections: 100,
+            http_version: HttpVersion::Auto,
+            tcp_keepalive: Some(Duration::from_secs(HTTP_TCP_KEEPALIVE_SECS)),
+            proxy: None,
+            headers: Vec::new(),
+            client_cert: None,
+            client_key: None,
+            api_base_url: NOTION_API_BASE_URL.to_string(),
+            auth_header: None,
+        }
+    }
+}
+
+/// How the fetch provenance header is written at the top of the prompt.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProvenanceFormat {
+    /// An HTML comment, invisible when the Markdown is rendered
+    Comment,
+    /// A YAML front matter block
+    Yaml,
+}
+
+/// Line endings of the delivered prompt.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Newline {
+    /// `\n`, turning any `\r\n` in the content into `\n`
+    Lf,
+    /// `\r\n`, for Windows consumers
+    Crlf,
+}
+
+/// What happens when a database differs from `--expect-schema`.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaDriftAction {
+    /// Stop before rendering, with exit code 10
+    #[default]
+    Fail,
+    /// Print the differences and render anyway
+    Warn,
+}
+
+/// What the run produces.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// A prompt rendered through the template
+    #[default]
+    Prompt,
+    /// An EPUB book of the rendered pages, written to the output file
+    Epub,
+    /// The fetched content as JSON, for rendering elsewhere, e.g. in the
+    /// browser with the notion2prompt-wasm bindings
+    Json,
+}
+
+/// Parses the name of a variant, spelled as on the command line.
+fn parse_choice<T: ValueEnum>(value: &str, kind: &'static str) -> Result<T, ValidationError> {
+    T::from_str(value, false).map_err(|_| ValidationError::InvalidChoice {
+        kind,
+        value: value.to_string(),
+        expected: T::value_variants()
+            .iter()
+            .filter_map(ValueEnum::to_possible_value)
+            .filter(|choice| !choice.is_hide_set())
+            .map(|choice| choice.get_name().to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+    })
+}
+
+/// Gives config enums one set of names, the ones the CLI takes: they parse
+/// with `TryFrom<&str>` and `FromStr`, print with `Display` and round-trip
+/// through serde, so config files and the language bindings spell values
+/// the way `--flags` do.
+macro_rules! named_choices {
+    ($($ty:ty => $kind:literal),* $(,)?) => {$(
+        impl TryFrom<&str> for $ty {
+            type Error = ValidationError;
+
+            fn try_from(value: &str) -> Result<Self, Self::Error> {
+                parse_choice(value, $kind)
+            }
+        }
+
+        impl std::str::FromStr for $ty {
+            type Err = ValidationError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                Self::try_from(value)
+            }
+        }
+
+        impl std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self.to_possible_value() {
+                    Some(choice) => f.write_str(choice.get_name()),
+                    None => write!(f, "{:?}", self),
+                }
+            }
+        }
+
+        impl Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let name = String::deserialize(deserializer)?;
+                Self::try_from(name.as_str()).map_err(serde::de::Error::custom)
+            }
+        }
+    )*};
+}
+
+named_choices! {
+    ToggleStyle => "toggle style",
+    HeadingToggles => "heading toggle mode",
+    MathStyle => "math style",
+    LinkStyle => "link style",
+    CalloutStyle => "callout style",
+    Language => "language",
+    PropertyPlacement => "property placement",
+    AutoSummary => "auto summary",
+    CacheScope => "cache scope",
+    HttpVersion => "HTTP version",
+    ProvenanceFormat => "provenance format",
+    Newline => "newline",
+    SchemaDriftAction => "schema drift action",
+    OutputFormat => "output format",
+}
+
 /// Parsed and validated command-line input.
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    after_help = exit_code_help(),
+    subcommand_negates_reqs = true
+)]
 pub struct CommandLineInput {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Notion page/database URL or ID (e.g., "https://www.notion.so/...")
-    pub notion_input: String,
+    #[arg(required = true)]
+    pub notion_input: Option<String>,
 
     /// Directory to store intermediate content (defaults to temp dir)
-    #[arg(short = 'd', long)]
+    #[arg(short = 'd', long, global = true)]
     pub content_dir: Option<String>,
 
     /// Output file for the final prompt (optional)
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub output_file: Option<String>,
 
+    /// Keep the N newest earlier versions of the output file as timestamped .bak files beside it
+    #[arg(long = "backup", value_name = "N", default_value_t = 0, global = true)]
+    pub backups: usize,
+
     /// Copy the generated prompt to the clipboard
-    #[arg(short = 'b', long, default_value_t = true)]
+    #[arg(short = 'b', long, default_value_t = true, global = true)]
     // Changed default to true for convenience
     pub clipboard: bool,
 
+    /// Do not copy the generated prompt to the clipboard
+    #[arg(long = "no-clipboard", action = clap::ArgAction::SetTrue, global = true)]
+    pub no_clipboard: bool,
+
     /// Enable verbose logging (debug level)
-    #[arg(short, long, default_value_t = false)]
+    #[arg(short, long, default_value_t = false, global = true)]
     pub verbose: bool,
 
+    /// Print errors only; suppress status, warnings and progress output
+    #[arg(
+        short,
+        long,
+        default_value_t = false,
+        global = true,
+        conflicts_with = "verbose"
+    )]
+    pub quiet: bool,
+
+    /// When to use color and symbols in status output
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto, global = true)]
+    pub color: ColorChoice,
+
+    /// Disable color and symbols (same as --color never)
+    #[arg(long, default_value_t = false, global = true)]
+    pub no_color: bool,
+
     /// Use specific template name (e.g., 'claude-xml', 'default') excluding .hbs extension. Defaults to 'claude-xml'.
-    #[arg(long, default_value = "claude-xml")]
+    #[arg(long, default_value = "claude-xml", global = true)]
     pub template: String,
 
     /// Custom instruction text to include in the prompt
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub instruction: Option<String>,
 
+    /// What to produce; epub packages the rendered pages as a book and needs --output-file,
+    /// json writes the fetched content for rendering elsewhere
+    #[arg(long, value_enum, default_value_t = OutputFormat::Prompt, global = true)]
+    pub format: OutputFormat,
+
     /// Pipe mode - output prompt directly to stdout for piping
-    #[arg(short = 'p', long, default_value_t = false)]
+    #[arg(short = 'p', long, default_value_t = false, global = true)]
     pub pipe: bool,
 
+    /// Line endings of the delivered prompt; without it they are left as rendered
+    #[arg(long, value_enum, global = true)]
+    pub newline: Option<Newline>,
+
+    /// Deliver ASCII only: accented letters and typographic punctuation are transliterated, emoji and other symbols dropped
+    #[arg(long, global = true)]
+    pub ascii: bool,
+
     /// Maximum recursion depth when fetching Notion content (0 = no recursion)
-    #[arg(long, default_value_t = 5)]
+    #[arg(long, default_value_t = 5, global = true)]
     pub depth: u8,
 
-    /// Maximum number of items (pages, blocks) to fetch
-    #[arg(long, default_value_t = 1000)]
+    /// Maximum number of items to fetch, split 60/30/10 across blocks, database rows, and child pages
+    #[arg(long, default_value_t = 1000, global = true)]
     pub limit: u32,
 
+    /// Stop fetching blocks and linked pages once the estimated prompt reaches this many tokens (0 = no budget)
+    #[arg(long, value_name = "TOKENS", default_value_t = 0, global = true)]
+    pub token_budget: u32,
+
     /// Parse and include child pages when encountered (default true)
-    #[arg(long, default_value_t = true)]
+    #[arg(long, default_value_t = true, global = true)]
     pub parse_child_pages: bool,
 
     /// Create separate sections for child pages in the markdown output
-    #[arg(long, default_value_t = true)]
+    #[arg(long, default_value_t = true, global = true)]
     pub separate_child_page: bool,
 
     /// Always fetch child databases regardless of depth limit
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, global = true)]
     pub always_fetch_databases: bool,
 
     /// Include Properties section in the output
-    #[arg(long, action = clap::ArgAction::SetTrue)]
+    #[arg(long, action = clap::ArgAction::SetTrue, global = true)]
     pub include_properties: bool,
 
     /// Exclude Properties section from the output
-    #[arg(long = "no-properties", action = clap::ArgAction::SetTrue)]
+    #[arg(long = "no-properties", action = clap::ArgAction::SetTrue, global = true)]
     pub no_properties: bool,
 
+    /// Where to put the Properties section; overrides --include-properties and --no-properties
+    #[arg(
+        long = "properties",
+        value_enum,
+        value_name = "PLACEMENT",
+        global = true
+    )]
+    pub property_placement: Option<PropertyPlacement>,
+
+    /// Add a Summary section under each page title: its heading outline or first paragraphs, plus database stats
+    #[arg(long, value_enum, default_value_t = AutoSummary::Off, global = true)]
+    pub auto_summary: AutoSummary,
+
     /// Disable response caching (always fetch fresh data)
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, global = true)]
     pub no_cache: bool,
 
     /// Cache TTL in seconds (default: 300 = 5 minutes)
-    #[arg(long, default_value_t = 300)]
+    #[arg(long, default_value_t = 300, global = true)]
     pub cache_ttl: u64,
 
+    /// Cache isolation: `shared` reuses responses across runs, `run` keeps them to this run
+    #[arg(long, value_enum, default_value_t = CacheScope::Shared, global = true)]
+    pub cache_scope: CacheScope,
+
+    /// Fetch everything fresh and mark what changed since the cached version with (changed) or (new)
+    #[arg(long, conflicts_with = "no_cache", global = true)]
+    pub annotate_changes: bool,
+
+    /// How long to wait when another run holds the output or checkpoint lock (e.g. 30s, 2m)
+    #[arg(long, value_name = "DURATION", value_parser = parse_wait, default_value = "0s", global = true)]
+    pub wait_lock: std::time::Duration,
+
     /// Number of concurrent API workers (default: auto, max 32)
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub concurrency: Option<usize>,
+
+    /// Idle HTTP connections kept open for reuse
+    #[arg(long, value_name = "N", default_value_t = HTTP_POOL_MAX_IDLE_PER_HOST, global = true)]
+    pub pool_max_idle: usize,
+
+    /// Seconds between TCP keep-alive probes on open connections (0 disables)
+    #[arg(long, value_name = "SECS", default_value_t = HTTP_TCP_KEEPALIVE_SECS, global = true)]
+    pub keep_alive: u64,
+
+    /// HTTP version for Notion API requests
+    #[arg(long, value_enum, default_value_t = HttpVersion::Auto, global = true)]
+    pub http_version: HttpVersion,
+
+    /// Give up on a Notion API request that takes longer than this, retries aside (e.g. 30s, 2m)
+    #[arg(long, value_name = "DURATION", value_parser = parse_wait, default_value = "30s", global = true)]
+    pub request_timeout: std::time::Duration,
+
+    /// Send Notion API requests through this proxy (default: HTTPS_PROXY, HTTP_PROXY or ALL_PROXY)
+    #[arg(long, value_name = "URL", global = true)]
+    pub proxy: Option<String>,
+
+    /// Extra header sent with every Notion API request, as "Name: value" (repeatable)
+    #[arg(long = "header", value_name = "HEADER", value_parser = parse_header, global = true)]
+    pub headers: Vec<(String, String)>,
+
+    /// PEM client certificate for proxies that require mutual TLS; may also hold the key
+    #[arg(long, value_name = "PATH", global = true)]
+    pub client_cert: Option<PathBuf>,
+
+    /// PEM private key for --client-cert, when it is not in the certificate file
+    #[arg(long, value_name = "PATH", requires = "client_cert", global = true)]
+    pub client_key: Option<PathBuf>,
+
+    /// Send Notion API requests to this base URL instead, e.g. an API gateway in front of Notion
+    #[arg(long, value_name = "URL", default_value = NOTION_API_BASE_URL, global = true)]
+    pub api_base_url: String,
+
+    /// Send the API key as is in this header instead of "Authorization: Bearer <key>"
+    #[arg(long, value_name = "NAME", global = true)]
+    pub auth_header: Option<String>,
+
+    /// How to render toggles whose content exceeds --toggle-collapse-chars
+    #[arg(long, value_enum, default_value_t = ToggleStyle::Indent, global = true)]
+    pub toggle_style: ToggleStyle,
+
+    /// Whether toggleable headings show their content or collapse it to a summary marker
+    #[arg(long, value_enum, default_value_t = HeadingToggles::Expand, global = true)]
+    pub toggle_headings: HeadingToggles,
+
+    /// How to delimit equations; code keeps underscores and asterisks in them from turning into emphasis
+    #[arg(long, value_enum, default_value_t = MathStyle::Dollar, global = true)]
+    pub math_style: MathStyle,
+
+    /// How to render callouts whose icon or color marks them as a tip, warning, etc.
+    #[arg(long, value_enum, default_value_t = CalloutStyle::Quote, global = true)]
+    pub callout_style: CalloutStyle,
+
+    /// Give callouts with this icon or color a type, as ICON=TYPE or COLOR=TYPE, e.g. "🔥=danger" (repeatable)
+    #[arg(long, value_name = "RULE", value_delimiter = ',', global = true)]
+    pub callout_type: Vec<CalloutRule>,
+
+    /// Language of the labels added to the prompt, such as placeholders and table notes
+    #[arg(long, value_enum, default_value_t = Language::En, global = true)]
+    pub lang: Language,
+
+    /// Rendered size (characters) above which a toggle is collapsed
+    #[arg(long, default_value_t = TOGGLE_COLLAPSE_THRESHOLD_CHARS, global = true)]
+    pub toggle_collapse_chars: usize,
+
+    /// Prepend a header recording the source URL, fetch time, tool version, limits, and warnings
+    #[arg(long, value_enum, value_name = "FORMAT", global = true)]
+    pub provenance: Option<ProvenanceFormat>,
+
+    /// Truncate database table cells longer than this many characters (0 disables)
+    #[arg(long, default_value_t = TABLE_MAX_CELL_CHARS, global = true)]
+    pub max_cell_chars: usize,
+
+    /// List the full content of truncated table cells as footnotes below the table
+    #[arg(long, global = true)]
+    pub cell_footnotes: bool,
+
+    /// Leave out database table columns that are empty in every rendered row, noting their names under the table
+    #[arg(long, global = true)]
+    pub exclude_empty_columns: bool,
+
+    /// Show at most N properties per page or table; the rest are summarized as a count (0 disables)
+    #[arg(long, value_name = "N", default_value_t = PAGE_MAX_PROPERTIES, global = true)]
+    pub max_properties: usize,
+
+    /// Render child databases nested in more than N enclosing databases as a one-line summary instead of a table
+    #[arg(long, value_name = "N", default_value_t = INLINE_DATABASE_MAX_DEPTH, global = true)]
+    pub max_inline_db_depth: usize,
+
+    /// Render each row's page content under child database tables of at most MAX_ROWS rows (20 when given without a value)
+    #[arg(long, value_name = "MAX_ROWS", num_args = 0..=1, default_missing_value = "20", default_value_t = 0, global = true)]
+    pub expand_rows: usize,
+
+    /// Truncate page property values longer than this many characters (0 disables)
+    #[arg(long, default_value_t = PROPERTY_MAX_VALUE_CHARS, global = true)]
+    pub max_property_chars: usize,
+
+    /// Leave out database rows matching PROPERTY=VALUE, PROPERTY!=VALUE or PROPERTY~TEXT (repeatable)
+    #[arg(long, value_name = "PREDICATE", global = true)]
+    pub drop_rows: Vec<RowPredicate>,
+
+    /// Render and fetch blocks for only the database rows whose title matches GLOB, e.g. "Q3 Launch*" (repeatable)
+    #[arg(long = "row", value_name = "GLOB", global = true)]
+    pub rows: Vec<RowTitleGlob>,
+
+    /// Sort database tables by PROPERTY:asc or PROPERTY:desc keys, e.g. "Due Date:asc,Priority:desc"
+    #[arg(long, value_name = "KEYS", value_delimiter = ',', global = true)]
+    pub db_sort: Vec<RowSort>,
+
+    /// Align database table columns as PROPERTY=left|center|right instead of by property type, e.g. "Estimate=left"
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',', global = true)]
+    pub db_align: Vec<ColumnAlign>,
+
+    /// Render database tables as one section per option of this select or status property, in the schema's order, e.g. "Status"
+    #[arg(long, value_name = "PROPERTY", global = true)]
+    pub db_group_by: Option<String>,
+
+    /// Head the database with this ID (or URL) with NAME instead of its own title, e.g. "1a2b...=Roadmap" (repeatable)
+    #[arg(long = "db-title", value_name = "ID=NAME", global = true)]
+    pub db_titles: Vec<DatabaseTitleOverride>,
+
+    /// Apply the block rules in this YAML file (drop block types, replace callout prefixes, rewrite links) before rendering
+    #[arg(long, value_name = "PATH", global = true)]
+    pub rules: Option<PathBuf>,
+
+    /// Render Notion headings N levels deeper (an H1 becomes H(1+N), capped at H6)
+    #[arg(long, value_name = "N", default_value_t = 0, global = true)]
+    pub heading_offset: u8,
+
+    /// Keep the page title the only H1, shifting content headings down when needed
+    #[arg(long, global = true)]
+    pub normalize_headings: bool,
+
+    /// Number headings as sections (1., 1.1, 1.1.1), in the table of contents too
+    #[arg(long, global = true)]
+    pub numbered_headings: bool,
+
+    /// Collapse blank-line runs, padded table cells and Unicode spaces and hyphens outside code
+    #[arg(long, global = true)]
+    pub normalize_whitespace: bool,
+
+    /// How links to external URLs render, in rich text, mentions, bookmarks, embeds and property values alike
+    #[arg(long, value_enum, default_value_t = LinkStyle::Inline, global = true)]
+    pub link_style: LinkStyle,
+
+    /// Same as --link-style reference
+    #[arg(long, conflicts_with = "link_style", global = true)]
+    pub link_index: bool,
+
+    /// End the prompt's root document with a "## People" list of every user mentioned in it
+    #[arg(long, global = true)]
+    pub people_glossary: bool,
+
+    /// Merge runs of plain, unstyled paragraphs (Notion splits pasted text into one per line) into single paragraphs
+    #[arg(long, global = true)]
+    pub merge_paragraphs: bool,
+
+    /// Render only blocks edited on or after this date (YYYY-MM-DD), with the headings they sit under
+    #[arg(long, value_name = "DATE", global = true)]
+    pub since: Option<chrono::NaiveDate>,
+
+    /// Render only blocks whose text matches this regular expression, with the headings they sit under, and set the matches in bold
+    #[arg(long, value_name = "PATTERN", global = true)]
+    pub grep: Option<regex::Regex>,
+
+    /// Keep this many sibling blocks on either side of each --grep match
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 0,
+        requires = "grep",
+        global = true
+    )]
+    pub grep_context: usize,
+
+    /// Check the fetched database against the properties and types in this JSON file, e.g. {"properties": {"Name": "title", "Status": "status"}}
+    #[arg(long, value_name = "PATH", global = true)]
+    pub expect_schema: Option<PathBuf>,
+
+    /// What to do when the database differs from --expect-schema: fail (exit code 10) or warn and render anyway
+    #[arg(
+        long,
+        value_enum,
+        value_name = "MODE",
+        default_value_t = SchemaDriftAction::Fail,
+        requires = "expect_schema",
+        global = true
+    )]
+    pub schema_drift: SchemaDriftAction,
+
+    /// Check the rendered markdown for unclosed fences, broken table rows and malformed links, fixing what can be fixed
+    #[arg(long, global = true)]
+    pub check_markdown: bool,
+
+    /// Keep the raw JSON Notion returned for blocks notion2prompt cannot render
+    #[arg(long, global = true)]
+    pub keep_raw_unsupported: bool,
+
+    /// Leave out, with a warning, any block whose JSON exceeds this many bytes
+    #[arg(long, value_name = "BYTES", default_value_t = NOTION_MAX_BLOCK_PAYLOAD_BYTES, global = true)]
+    pub max_block_bytes: usize,
+
+    /// Write the raw JSON of unsupported blocks to this file (requires --keep-raw-unsupported)
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "keep_raw_unsupported",
+        global = true
+    )]
+    pub diagnostics_file: Option<PathBuf>,
+
+    /// Write the prompt's sections, ranked by estimated tokens, to this JSON file
+    #[arg(long, value_name = "PATH", global = true)]
+    pub outline_file: Option<PathBuf>,
+
+    /// Write every fetched database row to this JSON Lines file: title, properties and plain-text content
+    #[arg(long, value_name = "PATH", global = true)]
+    pub export_jsonl: Option<PathBuf>,
+
+    /// Replace Notion IDs in the prompt with stable pseudonyms such as page-1 and db-2
+    #[arg(long, global = true)]
+    pub redact_ids: bool,
+
+    /// Local JSON file mapping pseudonyms back to IDs; reused so pseudonyms stay stable across runs
+    #[arg(
+        long,
+        value_name = "PATH",
+        default_value = "notion2prompt-ids.json",
+        global = true
+    )]
+    pub redact_map: PathBuf,
+
+    /// Save fetch progress to this file every few seconds so an interrupted run can be resumed
+    #[arg(long, value_name = "PATH", global = true)]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Continue an interrupted fetch from this checkpoint, skipping the requests it already holds
+    #[arg(long, value_name = "PATH", global = true)]
+    pub resume: Option<PathBuf>,
+
+    /// Record every Notion API request and response to this HAR file, with the API key redacted
+    #[arg(long, value_name = "PATH", global = true)]
+    pub har: Option<PathBuf>,
+}
+
+/// Parses a `--wait-lock` value: seconds, optionally with an `ms`, `s` or `m` unit.
+fn parse_wait(raw: &str) -> Result<std::time::Duration, String> {
+    let raw = raw.trim();
+    let digits = raw.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let value: u64 = digits
+        .parse()
+        .map_err(|_| format!("expected a duration such as 30s or 2m, got '{}'", raw))?;
+    match &raw[digits.len()..] {
+        "ms" => Ok(std::time::Duration::from_millis(value)),
+        "" | "s" => Ok(std::time::Duration::from_secs(value)),
+        "m" => Ok(std::time::Duration::from_secs(value * 60)),
+        unit => Err(format!("unknown unit '{}' (use ms, s or m)", unit)),
+    }
+}
+
+/// Parses a `--header` value of the form `Name: value`.
+fn parse_header(raw: &str) -> Result<(String, String), String> {
+    let (name, value) = raw
+        .split_once(':')
+        .ok_or_else(|| format!("expected \"Name: value\", got '{}'", raw))?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(format!("missing header name in '{}'", raw));
+    }
+    Ok((name.to_string(), value.trim().to_string()))
+}
+
+impl CommandLineInput {
+    /// How much user-facing output to print.
+    pub fn verbosity(&self) -> Verbosity {
+        Verbosity::from_flags(self.quiet, self.verbose)
+    }
+
+    /// HTTP connection settings from `--pool-max-idle`, `--keep-alive`,
+    /// `--http-version`, `--request-timeout`, `--proxy`, `--header`,
+    /// `--client-cert`, `--api-base-url` and `--auth-header`.
+    pub fn pool_config(&self) -> PoolConfig {
+        PoolConfig {
+            max_idle_per_host: self.pool_max_idle,
+            tcp_keepalive: (self.keep_alive > 0)
+                .then(|| std::time::Duration::from_secs(self.keep_alive)),
+            http_version: self.http_version,
+            request_timeout: self.request_timeout,
+            proxy: self.proxy.clone(),
+            headers: self.headers.clone(),
+            client_cert: self.client_cert.clone(),
+            client_key: self.client_key.clone(),
+            api_base_url: self.api_base_url.clone(),
+            auth_header: self.auth_header.clone(),
+            ..PoolConfig::default()
+        }
+    }
+
+    /// The effective color policy, with `--no-color` taking precedence.
+    pub fn color_choice(&self) -> ColorChoice {
+        if self.no_color {
+            ColorChoice::Never
+        } else {
+            self.color
+        }
+    }
+}
+
+/// Subcommands that run something other than the single-object pipeline.
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Export every page and database shared with the integration
+    ExportWorkspace {
+        /// Directory for one prompt file per exported object plus index.md
+        #[arg(long, default_value = "./export")]
+        output_dir: PathBuf,
+        /// Don't refetch databases and pages an earlier file already includes; child databases and child pages reference that file instead
+        #[arg(long)]
+        dedupe_across_roots: bool,
+        /// Also write each page's typed property values to <file>.properties.json
+        #[arg(long)]
+        properties_json: bool,
+    },
+    /// Set up an API key, save it to the config file, and test it on a page
+    Init,
+    /// Fetch a page or database and print a structural report instead of a prompt
+    Stats {
+        /// Notion page/database URL or ID
+        notion_input: String,
+    },
+    /// Render Notion API JSON fetched elsewhere, without calling the API
+    Render {
+        /// File holding the page, database and block responses
+        #[arg(value_name = "FILE", required_unless_present = "stdin_content")]
+        input: Option<PathBuf>,
+        /// Read the responses from stdin instead of a file
+        #[arg(long, conflicts_with = "input")]
+        stdin_content: bool,
+    },
+    /// Fetch a page or database and save its API responses, with IDs randomized, as test fixtures
+    Fixture {
+        /// Notion page/database URL or ID
+        notion_input: String,
+        /// Directory for the fixture files and endpoints.json
+        #[arg(long, value_name = "DIR")]
+        out: PathBuf,
+        /// Also replace the letters and digits of all text with random ones
+        #[arg(long)]
+        scramble_text: bool,
+    },
+    /// Manage the response cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+    /// Send one authenticated request to the Notion API and print the response
+    Api {
+        /// HTTP method
+        #[arg(value_enum, ignore_case = true)]
+        method: ApiMethod,
+        /// API path as in Notion's reference, e.g. /v1/blocks/<id>/children
+        path: String,
+        /// JSON request body for POST [default: {}]
+        #[arg(long)]
+        data: Option<String>,
+    },
+}
+
+/// Methods `notion2prompt api` can send.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiMethod {
+    Get,
+    /// Search and database queries are POST requests
+    Post,
+}
+
+/// `notion2prompt cache` subcommands.
+#[derive(Subcommand, Debug, Clone)]
+pub enum CacheCommand {
+    /// Fetch a page or database into the cache without composing or delivering a prompt
+    Warm {
+        /// Notion page/database URL or ID
+        notion_input: String,
+    },
+}
+
+/// Settings saved by `notion2prompt init`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredConfig {
+    pub api_key: String,
+}
+
+impl StoredConfig {
+    /// `notion2prompt/config.json` under the platform's user config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join("notion2prompt").join("config.json"))
+    }
+
+    /// Reads the config file at `path`, or `None` if there is none.
+    pub fn load(path: &Path) -> Result<Option<Self>, AppError> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|source| AppError::JsonParseError {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+
+    /// Writes the config file, readable only by the current user on Unix.
+    ///
+    /// The key goes to a temporary file created with those permissions, which
+    /// is then renamed over `path`, so it is never readable by others, not
+    /// even while being written.
+    #[allow(dead_code)] // Used by bin crate
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        use std::io::Write;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)? + "\n";
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let temp = path.with_file_name(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4().simple()));
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let result = options
+            .open(&temp)
+            .and_then(|mut file| {
+                file.write_all(json.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|()| std::fs::rename(&temp, path));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp);
+        }
+        Ok(result?)
+    }
 }
 
 /// Resolved pipeline configuration — validated and ready to drive all three stages.
+///
+/// Options may be added in any release; start from
+/// `PipelineConfig::default()` and set the fields you need.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct PipelineConfig {
     pub notion_id: NotionId,
     pub api_key: ApiKey,
     pub depth: u8,
     pub limit: u32,
+    /// Estimated tokens after which low-priority fetch work is skipped (0 = none)
+    pub token_budget: u32,
     pub template: TemplateName,
     #[allow(dead_code)] // Used by bin crate
     pub content_dir: PathBuf,
     pub output_file: Option<PathBuf>,
+    /// Earlier versions of the output file kept as backups (`--backup`)
+    pub backups: usize,
     pub clipboard: bool,
     pub pipe: bool,
+    /// Line endings written on delivery, or None to leave them as rendered
+    pub newline: Option<Newline>,
+    /// Transliterate or drop non-ASCII text on delivery
+    pub ascii: bool,
     #[allow(dead_code)] // Used by bin crate
     pub verbose: bool,
     pub always_fetch_databases: bool,
     pub include_properties: bool,
+    /// Where the Properties section goes when `include_properties` is set
+    pub property_placement: PropertyPlacement,
+    /// What the Summary section under each page title holds
+    pub auto_summary: AutoSummary,
     pub instruction: Option<String>,
     pub no_cache: bool,
     pub cache_ttl: u64,
+    pub cache_scope: CacheScope,
+    /// Whether to mark what changed since the cached version.
+    #[allow(dead_code)] // Used by bin crate
+    pub annotate_changes: bool,
+    /// How long to wait for a lock another run holds
+    pub lock_wait: std::time::Duration,
     pub concurrency: Option<usize>,
+    pub connection_pool: PoolConfig,
+    pub toggle_style: ToggleStyle,
+    pub toggle_headings: HeadingToggles,
+    pub math_style: MathStyle,
+    pub callout_style: CalloutStyle,
+    /// `--callout-type` rules, checked before the built-in icons and colors
+    pub callout_types: Vec<CalloutRule>,
+    pub lang: Language,
+    pub toggle_collapse_chars: usize,
+    pub max_cell_chars: usize,
+    pub cell_footnotes: bool,
+    pub exclude_empty_columns: bool,
+    pub max_properties: usize,
+    pub max_property_chars: usize,
+    pub max_inline_db_depth: usize,
+    /// Child databases with at most this many rows show their rows' content
+    /// under the table (0 = never)
+    pub expand_rows: usize,
+    pub drop_rows: Vec<RowPredicate>,
+    pub rows: Vec<RowTitleGlob>,
+    pub db_sort: Vec<RowSort>,
+    pub db_align: Vec<ColumnAlign>,
+    /// Property whose options group database rows (`--db-group-by`)
+    pub db_group_by: Option<PropertyName>,
+    /// `--db-title` names, which win over the automatic disambiguation
+    pub db_titles: Vec<DatabaseTitleOverride>,
+    /// Block transforms from the `--rules` file, checked when it was loaded
+    pub rules: BlockRules,
+    pub heading_offset: u8,
+    pub normalize_headings: bool,
+    /// Section numbers before heading text, from `--numbered-headings`
+    pub numbered_headings: bool,
+    pub normalize_whitespace: bool,
+    /// How external links render (`--link-style`, or `--link-index`).
+    pub link_style: LinkStyle,
+    /// Append a who's-who of mentioned users (`--people-glossary`).
+    pub people_glossary: bool,
+    pub merge_paragraphs: bool,
+    /// Render only blocks edited since this date (`--since`).
+    pub since: Option<chrono::NaiveDate>,
+    /// Render only blocks matching this pattern (`--grep`), with
+    /// `grep_context` siblings on either side.
+    pub grep: Option<regex::Regex>,
+    pub grep_context: usize,
+    /// Properties the fetched database must have (`--expect-schema`)
+    pub expect_schema: Option<ExpectedSchema>,
+    pub schema_drift: SchemaDriftAction,
+    pub check_markdown: bool,
+    pub provenance: Option<ProvenanceFormat>,
+    #[allow(dead_code)] // Used by bin crate
+    pub format: OutputFormat,
+    pub keep_raw_unsupported: bool,
+    /// Blocks whose JSON exceeds this many bytes are left out
+    pub max_block_bytes: usize,
+    #[allow(dead_code)] // Used by bin crate
+    pub diagnostics_file: Option<PathBuf>,
+    #[allow(dead_code)] // Used by bin crate
+    pub outline_file: Option<PathBuf>,
+    /// Where to write database rows as JSON Lines, from `--export-jsonl`
+    #[allow(dead_code)] // Used by bin crate
+    pub export_jsonl: Option<PathBuf>,
+    /// The pseudonym mapping file, when IDs are redacted.
+    #[allow(dead_code)] // Used by bin crate
+    pub redact_ids: Option<PathBuf>,
+    /// Where to save fetch progress; a resumed run keeps saving to its checkpoint.
+    #[allow(dead_code)] // Used by bin crate
+    pub checkpoint: Option<PathBuf>,
+    #[allow(dead_code)] // Used by bin crate
+    pub resume: Option<PathBuf>,
+    #[allow(dead_code)] // Used by bin crate
+    pub har: Option<PathBuf>,
+    /// Compose-phase plugins; registered by embedders with [`Self::with_plugin`]
+    pub plugins: ComposePlugins,
     /// The raw URL/input string — preserved for type-hint detection.
     pub raw_input: String,
 }
@@ -120,44 +1067,158 @@ pub struct PipelineConfig {
 impl PipelineConfig {
     /// Resolves a complete pipeline configuration from CLI input and environment.
     pub fn resolve(cli: CommandLineInput) -> Result<Self, AppError> {
-        let api_key_str = std::env::var("NOTION_API_KEY").map_err(|_| {
-            AppError::MissingConfiguration(
-                "NOTION_API_KEY environment variable not set".to_string(),
-            )
+        let notion_input = cli.notion_input.clone().ok_or_else(|| {
+            AppError::MissingConfiguration("a Notion page/database URL or ID is required".into())
         })?;
+        Self::resolve_for(&cli, &notion_input)
+    }
 
-        let api_key = ApiKey::new(api_key_str)?;
-        let notion_id = NotionId::parse(&cli.notion_input)?;
+    /// Resolves a configuration for a specific object, sharing every other
+    /// setting with the CLI input. Used when one invocation fetches many roots.
+    pub fn resolve_for(cli: &CommandLineInput, notion_input: &str) -> Result<Self, AppError> {
+        Self::resolve_with_key(cli, notion_input, Self::resolve_api_key()?)
+    }
+
+    /// Resolves a configuration for rendering content that is already at
+    /// hand (`notion2prompt render`). No API key is needed; the placeholder
+    /// one is never sent.
+    pub fn resolve_offline(cli: &CommandLineInput, notion_input: &str) -> Result<Self, AppError> {
+        Self::resolve_with_key(
+            cli,
+            notion_input,
+            ApiKey::new("secret_default_key_for_testing_only")?,
+        )
+    }
+
+    fn resolve_with_key(
+        cli: &CommandLineInput,
+        notion_input: &str,
+        api_key: ApiKey,
+    ) -> Result<Self, AppError> {
+        let notion_id = NotionId::parse(notion_input)?;
 
         let content_dir_base = cli
             .content_dir
+            .as_ref()
             .map(PathBuf::from)
             .unwrap_or_else(create_temp_content_dir);
 
         let notion_specific_content_dir = content_dir_base.join(notion_id.value_hyphenated());
-        let template = TemplateName::new(cli.template)?;
+        let template = TemplateName::new(cli.template.clone())?;
 
         Ok(PipelineConfig {
             notion_id,
             api_key,
             content_dir: notion_specific_content_dir,
-            output_file: cli.output_file.map(PathBuf::from),
-            clipboard: cli.clipboard,
+            output_file: cli.output_file.as_ref().map(PathBuf::from),
+            backups: cli.backups,
+            clipboard: cli.clipboard && !cli.no_clipboard,
             template,
-            instruction: cli.instruction,
+            instruction: cli.instruction.clone(),
             pipe: cli.pipe,
+            newline: cli.newline,
+            ascii: cli.ascii,
             verbose: cli.verbose,
             depth: cli.depth,
             limit: cli.limit,
+            token_budget: cli.token_budget,
             always_fetch_databases: cli.always_fetch_databases,
-            include_properties: cli.include_properties && !cli.no_properties,
+            include_properties: match cli.property_placement {
+                Some(placement) => placement != PropertyPlacement::Hide,
+                None => cli.include_properties && !cli.no_properties,
+            },
+            property_placement: cli.property_placement.unwrap_or_default(),
+            auto_summary: cli.auto_summary,
             no_cache: cli.no_cache,
             cache_ttl: cli.cache_ttl,
+            cache_scope: cli.cache_scope,
+            annotate_changes: cli.annotate_changes,
+            lock_wait: cli.wait_lock,
             concurrency: cli.concurrency,
-            raw_input: cli.notion_input,
+            connection_pool: cli.pool_config(),
+            toggle_style: cli.toggle_style,
+            toggle_headings: cli.toggle_headings,
+            math_style: cli.math_style,
+            callout_style: cli.callout_style,
+            callout_types: cli.callout_type.clone(),
+            lang: cli.lang,
+            toggle_collapse_chars: cli.toggle_collapse_chars,
+            max_cell_chars: cli.max_cell_chars,
+            cell_footnotes: cli.cell_footnotes,
+            exclude_empty_columns: cli.exclude_empty_columns,
+            max_properties: cli.max_properties,
+            max_property_chars: cli.max_property_chars,
+            max_inline_db_depth: cli.max_inline_db_depth,
+            expand_rows: cli.expand_rows,
+            drop_rows: cli.drop_rows.clone(),
+            rows: cli.rows.clone(),
+            db_sort: cli.db_sort.clone(),
+            db_align: cli.db_align.clone(),
+            db_group_by: cli.db_group_by.as_deref().map(PropertyName::new),
+            db_titles: cli.db_titles.clone(),
+            rules: cli
+                .rules
+                .as_deref()
+                .map(BlockRules::load)
+                .transpose()?
+                .unwrap_or_default(),
+            heading_offset: cli.heading_offset,
+            normalize_headings: cli.normalize_headings,
+            numbered_headings: cli.numbered_headings,
+            normalize_whitespace: cli.normalize_whitespace,
+            link_style: if cli.link_index {
+                LinkStyle::Reference
+            } else {
+                cli.link_style
+            },
+            people_glossary: cli.people_glossary,
+            merge_paragraphs: cli.merge_paragraphs,
+            since: cli.since,
+            grep: cli.grep.clone(),
+            grep_context: cli.grep_context,
+            expect_schema: cli
+                .expect_schema
+                .as_deref()
+                .map(ExpectedSchema::load)
+                .transpose()?,
+            schema_drift: cli.schema_drift,
+            check_markdown: cli.check_markdown,
+            provenance: cli.provenance,
+            format: cli.format,
+            keep_raw_unsupported: cli.keep_raw_unsupported,
+            max_block_bytes: cli.max_block_bytes,
+            diagnostics_file: cli.diagnostics_file.clone(),
+            outline_file: cli.outline_file.clone(),
+            export_jsonl: cli.export_jsonl.clone(),
+            redact_ids: cli.redact_ids.then(|| cli.redact_map.clone()),
+            checkpoint: cli.checkpoint.clone().or_else(|| cli.resume.clone()),
+            resume: cli.resume.clone(),
+            har: cli.har.clone(),
+            plugins: ComposePlugins::default(),
+            raw_input: notion_input.to_string(),
         })
     }
 
+    /// Reads and validates the API key from `NOTION_API_KEY`, falling back to
+    /// the key saved by `notion2prompt init`.
+    pub fn resolve_api_key() -> Result<ApiKey, AppError> {
+        if let Ok(api_key_str) = std::env::var("NOTION_API_KEY") {
+            return Ok(ApiKey::new(api_key_str)?);
+        }
+        let stored = match StoredConfig::default_path() {
+            Some(path) => StoredConfig::load(&path)?,
+            None => None,
+        };
+        let stored = stored.ok_or_else(|| {
+            AppError::MissingConfiguration(
+                "NOTION_API_KEY environment variable not set and no saved API key found. \
+                 Run `notion2prompt init` to set one up."
+                    .to_string(),
+            )
+        })?;
+        Ok(ApiKey::new(stored.api_key)?)
+    }
+
     /// Returns the full template path.
     pub fn get_template_path(&self) -> PathBuf {
         PathBuf::from("./templates").join(format!("{}.hbs", self.template.as_str()))
@@ -172,27 +1233,205 @@ impl Default for PipelineConfig {
                 .expect("Default API key should be valid"),
             depth: 10,
             limit: 1000,
+            token_budget: 0,
             template: TemplateName::new("default").expect("Default template name should be valid"),
             content_dir: PathBuf::from(".content"),
             output_file: None,
+            backups: 0,
             clipboard: false,
             pipe: false,
+            newline: None,
+            ascii: false,
             verbose: false,
             always_fetch_databases: false,
             include_properties: true,
+            property_placement: PropertyPlacement::default(),
+            auto_summary: AutoSummary::default(),
             instruction: None,
             no_cache: false,
             cache_ttl: 300,
+            cache_scope: CacheScope::default(),
+            annotate_changes: false,
+            lock_wait: std::time::Duration::ZERO,
             concurrency: None,
+            connection_pool: PoolConfig::default(),
+            toggle_style: ToggleStyle::default(),
+            toggle_headings: HeadingToggles::default(),
+            math_style: MathStyle::default(),
+            callout_style: CalloutStyle::default(),
+            callout_types: Vec::new(),
+            lang: Language::default(),
+            toggle_collapse_chars: TOGGLE_COLLAPSE_THRESHOLD_CHARS,
+            max_cell_chars: TABLE_MAX_CELL_CHARS,
+            cell_footnotes: false,
+            exclude_empty_columns: false,
+            max_properties: PAGE_MAX_PROPERTIES,
+            max_property_chars: PROPERTY_MAX_VALUE_CHARS,
+            max_inline_db_depth: INLINE_DATABASE_MAX_DEPTH,
+            expand_rows: 0,
+            drop_rows: Vec::new(),
+            rows: Vec::new(),
+            db_sort: Vec::new(),
+            db_align: Vec::new(),
+            db_group_by: None,
+            db_titles: Vec::new(),
+            rules: BlockRules::default(),
+            heading_offset: 0,
+            normalize_headings: false,
+            numbered_headings: false,
+            normalize_whitespace: false,
+            link_style: LinkStyle::default(),
+            people_glossary: false,
+            merge_paragraphs: false,
+            since: None,
+            grep: None,
+            grep_context: 0,
+            expect_schema: None,
+            schema_drift: SchemaDriftAction::Fail,
+            check_markdown: false,
+            provenance: None,
+            format: OutputFormat::Prompt,
+            keep_raw_unsupported: false,
+            max_block_bytes: NOTION_MAX_BLOCK_PAYLOAD_BYTES,
+            diagnostics_file: None,
+            outline_file: None,
+            export_jsonl: None,
+            redact_ids: None,
+            checkpoint: None,
+            resume: None,
+            har: None,
+            plugins: ComposePlugins::default(),
             raw_input: String::new(),
         }
     }
 }
 
 impl PipelineConfig {
+    /// Registers a compose-phase plugin; plugins run in registration order.
+    #[allow(dead_code)] // Library API
+    pub fn with_plugin(mut self, plugin: impl ComposePlugin + 'static) -> Self {
+        self.plugins.push(std::sync::Arc::new(plugin));
+        self
+    }
+
     /// Returns a valid example NotionId for use in tests and defaults.
     fn example_notion_id() -> NotionId {
         NotionId::parse("12345678123456781234567812345678")
             .expect("Example NotionId should always be valid")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stored_config_round_trip() {
+        let path = std::env::temp_dir()
+            .join(format!("notion2prompt-config-{}", uuid::Uuid::new_v4()))
+            .join("config.json");
+        assert_eq!(StoredConfig::load(&path).unwrap(), None);
+
+        let stored = StoredConfig {
+            api_key: "secret_abc".to_string(),
+        };
+        stored.save(&path).unwrap();
+        assert_eq!(StoredConfig::load(&path).unwrap(), Some(stored));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let entries = std::fs::read_dir(path.parent().unwrap()).unwrap().count();
+        assert_eq!(entries, 1, "the temporary file is renamed away");
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(matches!(
+            StoredConfig::load(&path),
+            Err(AppError::JsonParseError { .. })
+        ));
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_parse_header() {
+        assert_eq!(
+            parse_header("X-Corp-Trace: abc:def "),
+            Ok(("X-Corp-Trace".to_string(), "abc:def".to_string()))
+        );
+        assert!(parse_header("X-Corp-Trace").is_err());
+        assert!(parse_header(": abc").is_err());
+    }
+
+    #[test]
+    fn test_config_enums_share_their_cli_names() {
+        assert_eq!(MathStyle::try_from("latex").unwrap(), MathStyle::Latex);
+        assert_eq!("http1".parse::<HttpVersion>().unwrap(), HttpVersion::Http1);
+        assert_eq!(CalloutStyle::Obsidian.to_string(), "obsidian");
+        assert_eq!(
+            MathStyle::try_from("tex").unwrap_err().to_string(),
+            "Invalid math style 'tex', expected one of: dollar, latex, code"
+        );
+
+        for style in ToggleStyle::value_variants() {
+            let json = serde_json::to_string(style).unwrap();
+            assert_eq!(json, format!("\"{}\"", style));
+            assert_eq!(serde_json::from_str::<ToggleStyle>(&json).unwrap(), *style);
+        }
+        let error = serde_json::from_str::<Newline>("\"cr\"").unwrap_err();
+        assert!(error.to_string().contains("expected one of: lf, crlf"));
+    }
+
+    #[test]
+    fn test_parse_wait() {
+        use std::time::Duration;
+        assert_eq!(parse_wait("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_wait("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_wait("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_wait("5"), Ok(Duration::from_secs(5)));
+        assert!(parse_wait("5h").is_err());
+        assert!(parse_wait("soon").is_err());
+    }
+
+    #[test]
+    fn test_cache_warm_parses_with_global_fetch_options() {
+        let cli = CommandLineInput::try_parse_from([
+            "notion2prompt",
+            "cache",
+            "warm",
+            "12345678123456781234567812345678",
+            "--depth",
+            "3",
+        ])
+        .unwrap();
+
+        assert!(matches!(
+            cli.command,
+            Some(Command::Cache {
+                action: CacheCommand::Warm { ref notion_input }
+            }) if notion_input == "12345678123456781234567812345678"
+        ));
+        assert_eq!(cli.depth, 3);
+    }
+
+    #[test]
+    fn test_api_command_accepts_uppercase_method() {
+        let cli = CommandLineInput::try_parse_from([
+            "notion2prompt",
+            "api",
+            "GET",
+            "/v1/blocks/12345678123456781234567812345678/children",
+        ])
+        .unwrap();
+
+        assert!(matches!(
+            cli.command,
+            Some(Command::Api {
+                method: ApiMethod::Get,
+                ref path,
+                data: None,
+            }) if path == "/v1/blocks/12345678123456781234567812345678/children"
+        ));
+    }
+}
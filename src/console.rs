@@ -0,0 +1,292 @@
+// src/console.rs
+//! User-facing terminal output: status lines, warnings, errors and progress.
+//!
+//! Every message meant for a person — as opposed to the prompt itself or the
+//! log file — goes through [`Console`], which applies a single output policy:
+//! how much to say ([`Verbosity`]) and whether to decorate messages with
+//! color and symbols ([`ColorChoice`]). Pipe mode is one more input to that
+//! policy rather than a special case: stdout belongs to the prompt, so status
+//! lines move to stderr.
+
+use std::io::{IsTerminal, Write};
+
+/// How much the CLI tells the user beyond errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    /// Errors only
+    Quiet,
+    /// Status, completion messages and warnings
+    #[default]
+    Normal,
+    /// Everything in `Normal`, plus per-item detail
+    Verbose,
+}
+
+impl Verbosity {
+    /// Resolves the `--quiet` / `--verbose` flags; quiet wins if both are set.
+    pub fn from_flags(quiet: bool, verbose: bool) -> Self {
+        if quiet {
+            Verbosity::Quiet
+        } else if verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+}
+
+/// When to decorate output with ANSI color and symbols.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Decorate when writing to a terminal and `NO_COLOR` is unset
+    #[default]
+    Auto,
+    /// Always decorate
+    Always,
+    /// Never decorate
+    Never,
+}
+
+impl ColorChoice {
+    fn enabled_for(self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => is_terminal && std::env::var_os("NO_COLOR").is_none(),
+        }
+    }
+}
+
+/// The kind of a console message, which decides its stream, marker and color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tone {
+    Success,
+    Info,
+    Detail,
+    Warning,
+    Error,
+}
+
+impl Tone {
+    /// The least verbose setting at which this tone is shown.
+    fn minimum_verbosity(self) -> Verbosity {
+        match self {
+            Tone::Error => Verbosity::Quiet,
+            Tone::Success | Tone::Info | Tone::Warning => Verbosity::Normal,
+            Tone::Detail => Verbosity::Verbose,
+        }
+    }
+
+    fn marker(self, decorated: bool) -> &'static str {
+        match (self, decorated) {
+            (Tone::Success, true) => "✓ ",
+            (Tone::Warning, true) => "⚠️  ",
+            (Tone::Warning, false) => "Warning: ",
+            (Tone::Error, _) => "Error: ",
+            _ => "",
+        }
+    }
+
+    fn ansi_color(self) -> Option<&'static str> {
+        match self {
+            Tone::Success => Some("32"),
+            Tone::Warning => Some("33"),
+            Tone::Error => Some("31"),
+            Tone::Detail => Some("2"),
+            Tone::Info => None,
+        }
+    }
+
+    /// Whether the message belongs on stderr regardless of pipe mode.
+    fn is_diagnostic(self) -> bool {
+        matches!(self, Tone::Warning | Tone::Error)
+    }
+}
+
+/// Width of the progress bar, in characters.
+const PROGRESS_BAR_WIDTH: usize = 24;
+
+/// Writes user-facing messages according to one output policy.
+#[derive(Debug, Clone)]
+pub struct Console {
+    verbosity: Verbosity,
+    /// Status lines go to stderr because stdout carries the prompt.
+    pipe: bool,
+    stdout_decorated: bool,
+    stderr_decorated: bool,
+    stderr_is_terminal: bool,
+}
+
+impl Console {
+    /// Creates a console for the current process's stdout and stderr.
+    pub fn new(verbosity: Verbosity, color: ColorChoice, pipe: bool) -> Self {
+        let stderr_is_terminal = std::io::stderr().is_terminal();
+        Self {
+            verbosity,
+            pipe,
+            stdout_decorated: color.enabled_for(std::io::stdout().is_terminal()),
+            stderr_decorated: color.enabled_for(stderr_is_terminal),
+            stderr_is_terminal,
+        }
+    }
+
+    /// A completed action, e.g. "Prompt saved to …".
+    pub fn success(&self, message: impl AsRef<str>) {
+        self.emit(Tone::Success, message.as_ref());
+    }
+
+    /// A neutral status line.
+    pub fn info(&self, message: impl AsRef<str>) {
+        self.emit(Tone::Info, message.as_ref());
+    }
+
+    /// Extra detail shown only with `--verbose`.
+    pub fn detail(&self, message: impl AsRef<str>) {
+        self.emit(Tone::Detail, message.as_ref());
+    }
+
+    /// Something the user should know about, such as missing content.
+    pub fn warn(&self, message: impl AsRef<str>) {
+        self.emit(Tone::Warning, message.as_ref());
+    }
+
+    /// A fatal error. Shown even with `--quiet`.
+    pub fn error(&self, message: impl AsRef<str>) {
+        self.emit(Tone::Error, message.as_ref());
+    }
+
+    /// Draws a progress bar on stderr, redrawn in place on a terminal and
+    /// printed one line per step otherwise.
+    pub fn progress(&self, done: usize, total: usize, label: &str) {
+        if self.verbosity < Verbosity::Normal {
+            return;
+        }
+        let bar = render_progress_bar(done, total, label);
+        let mut stderr = std::io::stderr();
+        if self.stderr_is_terminal {
+            let _ = write!(stderr, "\r\x1b[2K{}", bar);
+            let _ = stderr.flush();
+        } else {
+            let _ = writeln!(stderr, "{}", bar);
+        }
+    }
+
+    /// Ends an in-place progress bar line.
+    pub fn finish_progress(&self) {
+        if self.verbosity >= Verbosity::Normal && self.stderr_is_terminal {
+            eprintln!();
+        }
+    }
+
+    fn shows(&self, tone: Tone) -> bool {
+        self.verbosity >= tone.minimum_verbosity()
+    }
+
+    fn uses_stderr(&self, tone: Tone) -> bool {
+        tone.is_diagnostic() || self.pipe
+    }
+
+    fn emit(&self, tone: Tone, message: &str) {
+        if !self.shows(tone) {
+            return;
+        }
+        if self.uses_stderr(tone) {
+            eprintln!("{}", format_message(tone, message, self.stderr_decorated));
+        } else {
+            println!("{}", format_message(tone, message, self.stdout_decorated));
+        }
+    }
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new(Verbosity::Normal, ColorChoice::Auto, false)
+    }
+}
+
+/// Renders one message line with its marker and optional color.
+fn format_message(tone: Tone, message: &str, decorated: bool) -> String {
+    let line = format!("{}{}", tone.marker(decorated), message);
+    match tone.ansi_color() {
+        Some(code) if decorated => format!("\x1b[{}m{}\x1b[0m", code, line),
+        _ => line,
+    }
+}
+
+fn render_progress_bar(done: usize, total: usize, label: &str) -> String {
+    let filled = ((done * PROGRESS_BAR_WIDTH) / total.max(1)).min(PROGRESS_BAR_WIDTH);
+    format!(
+        "[{}{}] {}/{} {}",
+        "#".repeat(filled),
+        "-".repeat(PROGRESS_BAR_WIDTH - filled),
+        done,
+        total,
+        label
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn console(verbosity: Verbosity, pipe: bool) -> Console {
+        Console {
+            verbosity,
+            pipe,
+            stdout_decorated: false,
+            stderr_decorated: false,
+            stderr_is_terminal: false,
+        }
+    }
+
+    #[test]
+    fn test_quiet_shows_only_errors() {
+        let quiet = console(Verbosity::Quiet, false);
+        assert!(quiet.shows(Tone::Error));
+        assert!(!quiet.shows(Tone::Warning));
+        assert!(!quiet.shows(Tone::Success));
+
+        let normal = console(Verbosity::Normal, false);
+        assert!(normal.shows(Tone::Success));
+        assert!(!normal.shows(Tone::Detail));
+        assert!(console(Verbosity::Verbose, false).shows(Tone::Detail));
+    }
+
+    #[test]
+    fn test_pipe_moves_status_to_stderr() {
+        assert!(!console(Verbosity::Normal, false).uses_stderr(Tone::Success));
+        assert!(console(Verbosity::Normal, true).uses_stderr(Tone::Success));
+        assert!(console(Verbosity::Normal, false).uses_stderr(Tone::Warning));
+    }
+
+    #[test]
+    fn test_undecorated_messages_have_no_ansi_or_symbols() {
+        assert_eq!(
+            format_message(Tone::Success, "Prompt saved", false),
+            "Prompt saved"
+        );
+        assert_eq!(
+            format_message(Tone::Warning, "Item limit reached", false),
+            "Warning: Item limit reached"
+        );
+        assert_eq!(
+            format_message(Tone::Success, "Prompt saved", true),
+            "\x1b[32m✓ Prompt saved\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_flags_resolve_to_verbosity() {
+        assert_eq!(Verbosity::from_flags(true, true), Verbosity::Quiet);
+        assert_eq!(Verbosity::from_flags(false, true), Verbosity::Verbose);
+        assert_eq!(Verbosity::from_flags(false, false), Verbosity::Normal);
+    }
+
+    #[test]
+    fn test_progress_bar_fills_proportionally() {
+        assert_eq!(
+            render_progress_bar(12, 24, "Notes"),
+            format!("[{}{}] 12/24 Notes", "#".repeat(12), "-".repeat(12))
+        );
+    }
+}
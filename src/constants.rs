@@ -14,6 +14,7 @@
 ///
 /// The Notion API maximum is 100. We use the maximum to minimize
 /// round-trips during recursive fetching.
+#[cfg(feature = "fetch")]
 pub const NOTION_API_PAGE_SIZE: usize = 100;
 
 /// Maximum nesting depth when recursively fetching from the Notion API.
@@ -21,8 +22,82 @@ pub const NOTION_API_PAGE_SIZE: usize = 100;
 /// Notion pages can nest arbitrarily deep (pages within databases within
 /// pages). This limit prevents stack overflow and runaway fetches.
 /// 50 levels is far deeper than any real Notion workspace.
+#[cfg(feature = "fetch")]
 pub const NOTION_MAX_FETCH_DEPTH: u8 = 50;
 
+/// Largest JSON payload, in bytes, parsed for a single block.
+///
+/// A giant code block or a table row packed with rich text can make one
+/// response enormous and slow to parse. Blocks over this size are left
+/// out with a warning instead; `--max-block-bytes` raises the limit.
+pub const NOTION_MAX_BLOCK_PAYLOAD_BYTES: usize = 1024 * 1024;
+
+/// Share of `--limit`, in percent, spent on database rows.
+///
+/// The item limit is split by what it is spent on, so that one huge
+/// database or a page linking to hundreds of others cannot use it up
+/// before the root page's own content is in. Blocks inside fetched pages
+/// get whatever rows and child pages leave: 60% at these defaults.
+#[cfg(feature = "fetch")]
+pub const FETCH_BUDGET_ROWS_PERCENT: u32 = 30;
+
+/// Share of `--limit`, in percent, spent on linked child pages and databases.
+#[cfg(feature = "fetch")]
+pub const FETCH_BUDGET_CHILD_PAGES_PERCENT: u32 = 10;
+
+/// Fraction of each retry backoff delay added as random jitter.
+///
+/// Parallel workers that fail together would otherwise retry in lockstep
+/// and hit a rate-limited API at the same instant. Up to 25% extra delay
+/// is enough to spread them out without noticeably slowing recovery.
+#[cfg(feature = "fetch")]
+pub const RETRY_JITTER_RATIO: f64 = 0.25;
+
+/// Attempts made for a single pagination cursor after the first page.
+///
+/// Once some rows have arrived, a failing cursor is retried on its own
+/// rather than restarting the whole query. If it still fails, the rows
+/// fetched so far are kept and reported as incomplete.
+#[cfg(feature = "fetch")]
+pub const PAGINATION_CURSOR_ATTEMPTS: u32 = 3;
+
+/// Initial backoff before retrying a failed pagination cursor, in milliseconds.
+#[cfg(feature = "fetch")]
+pub const PAGINATION_RETRY_DELAY_MS: u64 = 200;
+
+/// Where Notion API requests go unless `--api-base-url` names a gateway.
+pub const NOTION_API_BASE_URL: &str = "https://api.notion.com/v1";
+
+/// Idle HTTP connections kept open per host between requests.
+///
+/// Parallel fetch workers all talk to api.notion.com, so reusing warm
+/// connections saves a TLS handshake per request. Large fetches with high
+/// `--concurrency` benefit from raising this with `--pool-max-idle`.
+pub const HTTP_POOL_MAX_IDLE_PER_HOST: usize = 10;
+
+/// Interval between TCP keep-alive probes on pooled connections, in seconds.
+///
+/// Keeps idle connections from being silently dropped by NATs and
+/// corporate proxies during long fetches.
+pub const HTTP_TCP_KEEPALIVE_SECS: u64 = 60;
+
+/// Seconds between writes of a `--checkpoint` file during a fetch.
+///
+/// Each write serializes every response fetched so far, so writing after
+/// every request would dominate long runs. A crash loses at most this
+/// much work.
+#[cfg(feature = "fetch")]
+pub const CHECKPOINT_INTERVAL_SECS: u64 = 15;
+
+/// Seconds past its TTL that a cached response may still be served while
+/// another worker refreshes it.
+///
+/// When an entry expires in the middle of a parallel fetch, every worker
+/// needing it would otherwise refetch at once. One of them refreshes it;
+/// the others serve the stale response for at most this long.
+#[cfg(feature = "fetch")]
+pub const CACHE_STALE_WHILE_REVALIDATE_SECS: u64 = 30;
+
 // ---------------------------------------------------------------------------
 // Formatting boundaries
 // ---------------------------------------------------------------------------
@@ -50,6 +125,63 @@ pub const INDENT_SPACES: usize = 2;
 #[allow(dead_code)]
 pub const TABLE_MAX_COLUMNS: u32 = 100;
 
+/// Characters a database table cell may hold before it is truncated.
+///
+/// Long rich-text properties stretch a Markdown table row until the other
+/// columns are unreadable. Truncated cells end in an ellipsis; with
+/// `--cell-footnotes` their full content is listed below the table.
+pub const TABLE_MAX_CELL_CHARS: usize = 120;
+
+/// Properties a page (or database table) shows before the rest are summarized.
+///
+/// CRM-style databases can carry hundreds of properties, and listing every
+/// one buries the page content. Past this count the page lists the first
+/// properties by name and a table keeps its most filled columns; the rest
+/// are reduced to a count.
+pub const PAGE_MAX_PROPERTIES: usize = 50;
+
+/// Database nesting depth up to which child databases render as full tables.
+///
+/// A database in a row page of another database is one level deep; tables
+/// nested further bury the document under rows of rows. Deeper databases
+/// render as a one-line summary with a link instead; 0 keeps tables out of
+/// database rows entirely.
+pub const INLINE_DATABASE_MAX_DEPTH: usize = 2;
+
+/// Characters a single page property value may hold before it is truncated.
+///
+/// A rich-text property can hold a whole document; one such value in the
+/// properties list can outweigh the page body. Truncated values end in a
+/// marker giving the full length.
+pub const PROPERTY_MAX_VALUE_CHARS: usize = 2_000;
+
+/// Rendered size (in characters) above which a toggle's body counts as large.
+///
+/// Small toggles always render their content inline. Large ones are folded
+/// according to `--toggle-style`, since a prompt reader cannot click to
+/// collapse them and long hidden sections tend to dominate the context.
+pub const TOGGLE_COLLAPSE_THRESHOLD_CHARS: usize = 600;
+
+/// Paragraphs quoted in the Summary section by `--auto-summary lead`.
+///
+/// Most pages state what they are about in their opening lines; three
+/// paragraphs give a reader the gist without repeating much of the body.
+pub const SUMMARY_LEAD_PARAGRAPHS: usize = 3;
+
+/// Deepest heading level Markdown has.
+///
+/// `--heading-offset` and `--normalize-headings` push Notion headings
+/// down; headings that would go past `######` stay there instead of
+/// turning into paragraphs that start with a run of `#`.
+pub const MARKDOWN_MAX_HEADING_LEVEL: u8 = 6;
+
+/// Characters per model token when estimating prompt size.
+///
+/// English prose averages about four characters per token across common
+/// tokenizers. `notion2prompt stats` uses this to size a page before a
+/// full run, and `--token-budget` to turn tokens into characters.
+pub const CHARS_PER_TOKEN_ESTIMATE: usize = 4;
+
 // ---------------------------------------------------------------------------
 // String capacity hints (performance, not correctness)
 // ---------------------------------------------------------------------------
@@ -6,6 +6,7 @@
 //! enabling composable recovery strategies.
 
 use std::fmt;
+use std::path::PathBuf;
 use thiserror::Error;
 
 /// Notion API error codes as a typed vocabulary.
@@ -60,6 +61,7 @@ impl NotionErrorCode {
     }
 
     /// Create from an HTTP status code when the error body is unparseable.
+    #[cfg(feature = "fetch")]
     pub fn from_http_status(status: u16) -> Self {
         Self::HttpStatus(status)
     }
@@ -74,6 +76,7 @@ impl NotionErrorCode {
     }
 
     /// Whether this error means the resource simply doesn't exist.
+    #[cfg(feature = "fetch")]
     pub fn is_not_found(&self) -> bool {
         matches!(self, Self::ObjectNotFound)
     }
@@ -98,7 +101,10 @@ impl fmt::Display for NotionErrorCode {
 }
 
 /// Main application error type.
+///
+/// Variants may be added in any release.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 #[allow(dead_code)] // Some variants only constructed in bin target
 pub enum AppError {
     #[error("Missing configuration: {0}")]
@@ -107,9 +113,11 @@ pub enum AppError {
     #[error("Invalid Notion ID format: {0}")]
     InvalidId(String),
 
+    #[cfg(feature = "fetch")]
     #[error("Network failure: {0}")]
     NetworkFailure(#[from] reqwest::Error),
 
+    #[cfg(feature = "fetch")]
     #[error("Notion API returned an error ({code}): {message}")]
     NotionService {
         code: NotionErrorCode,
@@ -141,8 +149,8 @@ pub enum AppError {
     #[error("Failed to assemble object tree for root '{root_id}': {cause}")]
     AssemblyFailed { root_id: String, cause: String },
 
-    #[error("Output delivery failed: {}", failures.join(", "))]
-    DeliveryFailed { failures: Vec<String> },
+    #[error("Output delivery failed: {}", describe_deliveries(failures))]
+    DeliveryFailed { failures: Vec<DeliveryFailure> },
 
     #[error("Internal error: {message}")]
     InternalError {
@@ -160,6 +168,30 @@ pub enum AppError {
         source: serde_json::Error,
     },
 
+    #[error("Could not resolve {id}: {}", describe_attempts(attempts))]
+    Unresolved {
+        id: String,
+        attempts: Vec<ResolveAttempt>,
+    },
+
+    #[error(
+        "Another run holds the lock on {resource}; wait for it to finish or pass --wait-lock (lock file: {})",
+        path.display()
+    )]
+    LockHeld {
+        resource: String,
+        path: std::path::PathBuf,
+    },
+
+    #[error(
+        "Database '{database}' differs from the expected schema: {}",
+        drift.join("; ")
+    )]
+    SchemaDrift {
+        database: String,
+        drift: Vec<String>,
+    },
+
     #[error("Maximum recursion depth ({0}) exceeded")]
     RecursionLimitExceeded(usize),
 
@@ -180,6 +212,7 @@ impl From<anyhow::Error> for AppError {
     }
 }
 
+#[cfg(feature = "clipboard")]
 impl From<arboard::Error> for AppError {
     fn from(err: arboard::Error) -> Self {
         AppError::Clipboard(format!("Clipboard error: {}", err))
@@ -237,6 +270,7 @@ pub enum NotionClientError {
 }
 
 // Convert notion_client errors to our error hierarchy
+#[cfg(feature = "fetch")]
 impl From<notion_client::NotionClientError> for NotionClientError {
     fn from(err: notion_client::NotionClientError) -> Self {
         use notion_client::NotionClientError as NcError;
@@ -298,6 +332,7 @@ impl std::fmt::Display for DatabaseFetchFailure {
 /// This is a pure function that examines the error structure to determine
 /// whether the failure is due to a linked database (Notion API limitation),
 /// a permission issue, or something else.
+#[cfg(feature = "fetch")]
 pub fn classify_database_fetch_failure(error: &AppError) -> DatabaseFetchFailure {
     match error {
         AppError::NotionClient(NotionClientError::NotionApi { message, code, .. }) => {
@@ -327,7 +362,163 @@ pub fn classify_database_fetch_failure(error: &AppError) -> DatabaseFetchFailure
     }
 }
 
+/// Why looking an object up as one kind failed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccessFailure {
+    /// 404: nothing with this ID is visible to the integration
+    NotFound,
+    /// 401/403: the object exists, but the integration may not read it
+    Restricted { reason: String },
+    /// Anything else, e.g. a network failure
+    Other { cause: String },
+}
+
+impl AccessFailure {
+    /// Classifies `error` through the exit code taxonomy, so 403 and 404
+    /// mean the same here as everywhere else.
+    pub fn classify(error: &AppError) -> Self {
+        match error.exit_code() {
+            ExitCode::NotFound => Self::NotFound,
+            ExitCode::AuthenticationFailed => Self::Restricted {
+                reason: error.to_string(),
+            },
+            _ => Self::Other {
+                cause: error.to_string(),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for AccessFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "not found (404)"),
+            Self::Restricted { reason } => write!(f, "access denied (403): {}", reason),
+            Self::Other { cause } => write!(f, "{}", cause),
+        }
+    }
+}
+
+/// One failed attempt to look an object up as a page, database or block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolveAttempt {
+    pub kind: &'static str,
+    pub failure: AccessFailure,
+}
+
+/// Lists every attempt and says what the pattern of failures means.
+fn describe_attempts(attempts: &[ResolveAttempt]) -> String {
+    let tried = attempts
+        .iter()
+        .map(|a| format!("{} {}", a.kind, a.failure))
+        .collect::<Vec<_>>()
+        .join("; ");
+    let hint = if attempts
+        .iter()
+        .any(|a| matches!(a.failure, AccessFailure::Restricted { .. }))
+    {
+        "the object exists, but is not shared with the integration or the integration lacks the read content capability"
+    } else if attempts
+        .iter()
+        .all(|a| a.failure == AccessFailure::NotFound)
+    {
+        "no object with this ID is visible to the integration; check the ID, and share the page with the integration if it exists"
+    } else {
+        "the lookups failed for reasons other than access"
+    };
+    format!("{} — {}", tried, hint)
+}
+
+/// The kind of destination a prompt was being delivered to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeliveryKind {
+    File,
+    Directory,
+    Clipboard,
+    Stdout,
+}
+
+impl std::fmt::Display for DeliveryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File => write!(f, "file"),
+            Self::Directory => write!(f, "directory"),
+            Self::Clipboard => write!(f, "clipboard"),
+            Self::Stdout => write!(f, "stdout"),
+        }
+    }
+}
+
+/// One delivery target that failed, with enough context to act on it,
+/// e.g. to retry the clipboard while keeping the file that was written.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct DeliveryFailure {
+    pub kind: DeliveryKind,
+    /// The file or directory, for filesystem targets
+    pub path: Option<PathBuf>,
+    /// The I/O error kind, when the target failed with an I/O error
+    pub io_kind: Option<std::io::ErrorKind>,
+    pub message: String,
+}
+
+impl DeliveryFailure {
+    /// Records `error` as the failure of a `kind` target at `path`.
+    pub fn new(kind: DeliveryKind, path: Option<PathBuf>, error: &AppError) -> Self {
+        let io_kind = match error {
+            AppError::Io(e) => Some(e.kind()),
+            _ => None,
+        };
+        Self {
+            kind,
+            path,
+            io_kind,
+            message: error.to_string(),
+        }
+    }
+
+    /// Whether delivering to the same target again may succeed.
+    ///
+    /// Interrupted, blocked, timed-out and busy I/O may clear up; missing
+    /// directories, permissions and full disks will not. Clipboard failures
+    /// count as retryable, since they usually mean the clipboard owner or
+    /// display server was briefly unavailable.
+    #[allow(dead_code)] // Library API
+    pub fn is_retryable(&self) -> bool {
+        use std::io::ErrorKind;
+        match self.io_kind {
+            Some(kind) => matches!(
+                kind,
+                ErrorKind::Interrupted
+                    | ErrorKind::WouldBlock
+                    | ErrorKind::TimedOut
+                    | ErrorKind::ResourceBusy
+            ),
+            None => self.kind == DeliveryKind::Clipboard,
+        }
+    }
+}
+
+impl std::fmt::Display for DeliveryFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{} {}: {}", self.kind, path.display(), self.message),
+            None => write!(f, "{}: {}", self.kind, self.message),
+        }
+    }
+}
+
+fn describe_deliveries(failures: &[DeliveryFailure]) -> String {
+    failures
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Classifies based on Notion API error code and message strings.
+#[cfg(feature = "fetch")]
 fn classify_from_code_and_message(code: &str, message: &str) -> DatabaseFetchFailure {
     if message.contains("linked database") {
         DatabaseFetchFailure::LinkedDatabase
@@ -344,6 +535,288 @@ fn classify_from_code_and_message(code: &str, message: &str) -> DatabaseFetchFai
     }
 }
 
+/// Process exit codes as a typed vocabulary for scripts.
+///
+/// Every failure mode the CLI can end in maps onto exactly one of these
+/// codes, so wrapper scripts can branch on *why* a run failed (retry on
+/// rate limits, re-authenticate on auth errors, give up on not-found)
+/// without scraping stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// Content was fetched, rendered, and delivered in full
+    Success,
+    /// An error that fits no more specific category
+    Failure,
+    /// The prompt was delivered, but fetch warnings or limits left content out
+    PartialSuccess,
+    /// The API key is missing permissions, invalid, or expired
+    AuthenticationFailed,
+    /// The requested page, database, or block does not exist or is not shared
+    NotFound,
+    /// Notion rejected requests due to rate limiting
+    RateLimited,
+    /// Command-line arguments or configuration are invalid
+    InvalidInput,
+    /// The network or the Notion service was unreachable
+    ServiceUnavailable,
+    /// Rendering succeeded but writing the output failed
+    OutputFailed,
+    /// The prompt template is missing or failed to render
+    TemplateFailed,
+    /// The fetched database differs from `--expect-schema`
+    SchemaDrift,
+}
+
+impl ExitCode {
+    /// All exit codes in numeric order.
+    pub const ALL: [ExitCode; 11] = [
+        Self::Success,
+        Self::Failure,
+        Self::PartialSuccess,
+        Self::AuthenticationFailed,
+        Self::NotFound,
+        Self::RateLimited,
+        Self::InvalidInput,
+        Self::ServiceUnavailable,
+        Self::OutputFailed,
+        Self::TemplateFailed,
+        Self::SchemaDrift,
+    ];
+
+    /// The numeric process exit status.
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Success => 0,
+            Self::Failure => 1,
+            Self::PartialSuccess => 2,
+            Self::AuthenticationFailed => 3,
+            Self::NotFound => 4,
+            Self::RateLimited => 5,
+            Self::InvalidInput => 6,
+            Self::ServiceUnavailable => 7,
+            Self::OutputFailed => 8,
+            Self::TemplateFailed => 9,
+            Self::SchemaDrift => 10,
+        }
+    }
+
+    /// A one-line description, as shown in `--help`.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failure => "unclassified error",
+            Self::PartialSuccess => "partial success: prompt delivered with fetch warnings",
+            Self::AuthenticationFailed => "authentication or permission error",
+            Self::NotFound => "page, database, or block not found",
+            Self::RateLimited => "rate limited by the Notion API",
+            Self::InvalidInput => "invalid arguments or configuration",
+            Self::ServiceUnavailable => "network failure or Notion unavailable",
+            Self::OutputFailed => "writing the output failed",
+            Self::TemplateFailed => "template missing or failed to render",
+            Self::SchemaDrift => "database schema differs from --expect-schema",
+        }
+    }
+
+    /// Classifies a Notion API error code.
+    fn from_notion_code(code: &NotionErrorCode) -> Self {
+        match code {
+            NotionErrorCode::Unauthorized | NotionErrorCode::RestrictedResource => {
+                Self::AuthenticationFailed
+            }
+            NotionErrorCode::ObjectNotFound => Self::NotFound,
+            NotionErrorCode::RateLimited => Self::RateLimited,
+            NotionErrorCode::InvalidJson | NotionErrorCode::ValidationFailed => Self::InvalidInput,
+            NotionErrorCode::InternalError | NotionErrorCode::ServiceUnavailable => {
+                Self::ServiceUnavailable
+            }
+            NotionErrorCode::HttpStatus(status) => Self::from_http_status(*status),
+            NotionErrorCode::Conflict | NotionErrorCode::Unknown(_) => Self::Failure,
+        }
+    }
+
+    /// Classifies a raw HTTP status when no error code is available.
+    fn from_http_status(status: u16) -> Self {
+        match status {
+            401 | 403 => Self::AuthenticationFailed,
+            404 => Self::NotFound,
+            429 => Self::RateLimited,
+            400 | 422 => Self::InvalidInput,
+            500..=599 => Self::ServiceUnavailable,
+            _ => Self::Failure,
+        }
+    }
+}
+
+impl From<ExitCode> for std::process::ExitCode {
+    fn from(code: ExitCode) -> Self {
+        std::process::ExitCode::from(code.as_u8())
+    }
+}
+
+/// Exit code table appended to `--help`, generated from [`ExitCode::ALL`].
+pub fn exit_code_help() -> String {
+    let mut help = String::from("Exit codes:\n");
+    for code in ExitCode::ALL {
+        help.push_str(&format!("  {}  {}\n", code.as_u8(), code.description()));
+    }
+    help
+}
+
+impl AppError {
+    /// Whether the request that failed with this error may succeed if sent
+    /// again: rate limits, server errors and transport failures.
+    #[cfg(feature = "fetch")]
+    pub fn is_retryable(&self) -> bool {
+        let retryable_status = |status: u16| status == 429 || (500..=599).contains(&status);
+        match self {
+            AppError::NotionService { code, status, .. } => {
+                code.is_retryable() || retryable_status(status.as_u16())
+            }
+            AppError::NotionClient(NotionClientError::NotionApi { code, status, .. }) => {
+                NotionErrorCode::from_api_response(code).is_retryable()
+                    || retryable_status(*status as u16)
+            }
+            AppError::NotionClient(NotionClientError::Transport { .. }) => true,
+            AppError::NetworkFailure(err) => err.status().map_or(!err.is_builder(), |status| {
+                retryable_status(status.as_u16())
+            }),
+            _ => false,
+        }
+    }
+
+    /// Maps this error onto the process exit code taxonomy.
+    ///
+    /// This is the single place where failure modes are classified for
+    /// scripts — new variants must be added here.
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            #[cfg(feature = "fetch")]
+            AppError::NotionService { code, .. } => ExitCode::from_notion_code(code),
+            AppError::NotionClient(NotionClientError::NotionApi { code, status, .. }) => {
+                match NotionErrorCode::from_api_response(code) {
+                    NotionErrorCode::Unknown(_) => ExitCode::from_http_status(*status as u16),
+                    known => ExitCode::from_notion_code(&known),
+                }
+            }
+            AppError::NotionClient(NotionClientError::InvalidHeader { .. }) => {
+                ExitCode::AuthenticationFailed
+            }
+            AppError::NotionClient(NotionClientError::Transport { .. }) => {
+                ExitCode::ServiceUnavailable
+            }
+            #[cfg(feature = "fetch")]
+            AppError::NetworkFailure(err) => match err.status() {
+                Some(status) => ExitCode::from_http_status(status.as_u16()),
+                None => ExitCode::ServiceUnavailable,
+            },
+            AppError::ValidationError(crate::types::ValidationError::InvalidApiKey { .. }) => {
+                ExitCode::AuthenticationFailed
+            }
+            AppError::Unresolved { attempts, .. } => {
+                if attempts
+                    .iter()
+                    .any(|a| matches!(a.failure, AccessFailure::Restricted { .. }))
+                {
+                    ExitCode::AuthenticationFailed
+                } else if attempts
+                    .iter()
+                    .all(|a| a.failure == AccessFailure::NotFound)
+                {
+                    ExitCode::NotFound
+                } else {
+                    ExitCode::Failure
+                }
+            }
+            AppError::MissingConfiguration(_)
+            | AppError::InvalidId(_)
+            | AppError::Validation(_)
+            | AppError::ValidationError(_) => ExitCode::InvalidInput,
+            AppError::TemplateNotFound { .. } | AppError::TemplateRenderError { .. } => {
+                ExitCode::TemplateFailed
+            }
+            AppError::SchemaDrift { .. } => ExitCode::SchemaDrift,
+            AppError::Io(_)
+            | AppError::Clipboard(_)
+            | AppError::PathError(_)
+            | AppError::DeliveryFailed { .. } => ExitCode::OutputFailed,
+            AppError::MalformedResponse(_)
+            | AppError::AssemblyFailed { .. }
+            | AppError::InternalError { .. }
+            | AppError::JsonParseError { .. }
+            | AppError::LockHeld { .. }
+            | AppError::RecursionLimitExceeded(_)
+            | AppError::NotionClient(_) => ExitCode::Failure,
+        }
+    }
+}
+
 /// Result type alias for convenience
 #[allow(dead_code)]
 pub type Result<T, E = AppError> = std::result::Result<T, E>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notion_service(code: NotionErrorCode, status: u16) -> AppError {
+        AppError::NotionService {
+            code,
+            message: "test".to_string(),
+            status: reqwest::StatusCode::from_u16(status).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_exit_codes_are_distinct_and_ordered() {
+        for (index, code) in ExitCode::ALL.iter().enumerate() {
+            assert_eq!(code.as_u8() as usize, index);
+        }
+    }
+
+    #[test]
+    fn test_notion_service_errors_map_to_exit_codes() {
+        assert_eq!(
+            notion_service(NotionErrorCode::Unauthorized, 401).exit_code(),
+            ExitCode::AuthenticationFailed
+        );
+        assert_eq!(
+            notion_service(NotionErrorCode::ObjectNotFound, 404).exit_code(),
+            ExitCode::NotFound
+        );
+        assert_eq!(
+            notion_service(NotionErrorCode::RateLimited, 429).exit_code(),
+            ExitCode::RateLimited
+        );
+        assert_eq!(
+            notion_service(NotionErrorCode::HttpStatus(503), 503).exit_code(),
+            ExitCode::ServiceUnavailable
+        );
+    }
+
+    #[test]
+    fn test_notion_client_errors_fall_back_to_status() {
+        let err = AppError::NotionClient(NotionClientError::NotionApi {
+            status: 403,
+            code: "something_new".to_string(),
+            message: "denied".to_string(),
+            request_id: None,
+        });
+        assert_eq!(err.exit_code(), ExitCode::AuthenticationFailed);
+    }
+
+    #[test]
+    fn test_local_errors_map_to_exit_codes() {
+        assert_eq!(
+            AppError::InvalidId("x".to_string()).exit_code(),
+            ExitCode::InvalidInput
+        );
+        assert_eq!(
+            AppError::DeliveryFailed { failures: vec![] }.exit_code(),
+            ExitCode::OutputFailed
+        );
+        assert_eq!(
+            AppError::RecursionLimitExceeded(3).exit_code(),
+            ExitCode::Failure
+        );
+    }
+}
@@ -1,15 +1,78 @@
 // src/error_recovery.rs
 //! Retry with exponential backoff for API operations.
 
+use crate::clock::{Clock, Jitter, RandomJitter, SystemClock};
 use crate::error::AppError;
 use std::time::Duration;
 
+/// Told about every retry: the error that caused it and how long the
+/// retry waits, for the usage summary.
+pub type RetryObserver<'a> = &'a (dyn Fn(&AppError, Duration) + Sync);
+
 /// Retries an async operation with exponential backoff.
+#[allow(dead_code)] // Used by bin crate
 pub async fn retry_with_backoff<F, T, Fut>(
+    operation: F,
+    max_attempts: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+) -> Result<T, AppError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, AppError>>,
+{
+    retry_with_backoff_observed(
+        operation,
+        max_attempts,
+        initial_delay,
+        max_delay,
+        &|_, _| {},
+    )
+    .await
+}
+
+/// Retries an async operation with exponential backoff, reporting each
+/// retry to `on_retry`.
+pub async fn retry_with_backoff_observed<F, T, Fut>(
+    operation: F,
+    max_attempts: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+    on_retry: RetryObserver<'_>,
+) -> Result<T, AppError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, AppError>>,
+{
+    retry_with_backoff_using(
+        operation,
+        max_attempts,
+        initial_delay,
+        max_delay,
+        &SystemClock,
+        &RandomJitter,
+        &|_| true,
+        on_retry,
+    )
+    .await
+}
+
+/// Retries an async operation with exponential backoff, waiting on `clock`
+/// and perturbing each delay with `jitter`. An error `should_retry` rejects
+/// is returned at once.
+///
+/// The nominal delay doubles after every failure up to `max_delay`; jitter
+/// only affects how long each individual wait lasts.
+#[allow(clippy::too_many_arguments)]
+pub async fn retry_with_backoff_using<F, T, Fut>(
     mut operation: F,
     max_attempts: u32,
     initial_delay: Duration,
     max_delay: Duration,
+    clock: &dyn Clock,
+    jitter: &dyn Jitter,
+    should_retry: &(dyn Fn(&AppError) -> bool + Sync),
+    on_retry: RetryObserver<'_>,
 ) -> Result<T, AppError>
 where
     F: FnMut() -> Fut,
@@ -21,12 +84,17 @@ where
     for attempt in 1..=max_attempts {
         match operation().await {
             Ok(result) => return Ok(result),
+            Err(e) if !should_retry(&e) => return Err(e),
             Err(e) => {
                 last_error = Some(e);
 
                 if attempt < max_attempts {
-                    log::warn!("Attempt {} failed, retrying after {:?}", attempt, delay);
-                    tokio::time::sleep(delay).await;
+                    let wait = jitter.apply(delay);
+                    log::warn!("Attempt {} failed, retrying after {:?}", attempt, wait);
+                    if let Some(e) = &last_error {
+                        on_retry(e, wait);
+                    }
+                    clock.sleep(wait).await;
 
                     // Exponential backoff with cap
                     delay = std::cmp::min(delay * 2, max_delay);
@@ -40,3 +108,96 @@ where
         source: None,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{ManualClock, NoJitter};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn failure() -> AppError {
+        AppError::MissingConfiguration("transient".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_backoff_doubles_until_capped() {
+        let clock = ManualClock::at_unix_secs(0);
+        let result: Result<(), AppError> = retry_with_backoff_using(
+            || async { Err(failure()) },
+            5,
+            Duration::from_millis(100),
+            Duration::from_millis(300),
+            &clock,
+            &NoJitter,
+            &|_| true,
+            &|_, _| {},
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            clock.sleeps(),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(300),
+                Duration::from_millis(300),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stops_sleeping_after_success() {
+        let clock = ManualClock::at_unix_secs(0);
+        let attempts = AtomicU32::new(0);
+        let observed = std::sync::Mutex::new(Vec::new());
+        let result = retry_with_backoff_using(
+            || {
+                let n = attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if n < 2 {
+                        Err(failure())
+                    } else {
+                        Ok(n)
+                    }
+                }
+            },
+            5,
+            Duration::from_millis(50),
+            Duration::from_secs(1),
+            &clock,
+            &NoJitter,
+            &|_| true,
+            &|_, wait| observed.lock().unwrap().push(wait),
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(clock.sleeps().len(), 2);
+        assert_eq!(*observed.lock().unwrap(), clock.sleeps());
+    }
+
+    #[tokio::test]
+    async fn test_an_error_not_worth_retrying_returns_at_once() {
+        let clock = ManualClock::at_unix_secs(0);
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), AppError> = retry_with_backoff_using(
+            || {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async { Err(failure()) }
+            },
+            5,
+            Duration::from_millis(50),
+            Duration::from_secs(1),
+            &clock,
+            &NoJitter,
+            &|_| false,
+            &|_, _| {},
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert!(clock.sleeps().is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+// src/file_lock.rs
+//! Advisory locks that keep concurrent runs from writing the same files.
+//!
+//! Two runs sharing an output file or a checkpoint would interleave their
+//! writes. Each run locks what it writes to; a second run either waits for
+//! the lock (`--wait-lock`) or stops with [`AppError::LockHeld`]. Locks
+//! live on separate lock files and are released by the operating system
+//! when the run exits, even if it crashes.
+
+use crate::api::cache::DiskCache;
+use crate::error::AppError;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How often a waiting run retries a held lock.
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// An exclusive advisory lock, released on drop.
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+}
+
+impl FileLock {
+    /// Locks `lock_path`, creating it if needed, and waits up to `wait` for
+    /// another run to release it. `resource` names what the lock guards in
+    /// the error.
+    pub async fn acquire(
+        lock_path: &Path,
+        resource: &str,
+        wait: Duration,
+    ) -> Result<Self, AppError> {
+        if let Some(parent) = lock_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(lock_path)?;
+
+        let deadline = Instant::now() + wait;
+        loop {
+            match file.try_lock() {
+                Ok(()) => {
+                    log::debug!("Locked {} ({})", resource, lock_path.display());
+                    return Ok(Self { file });
+                }
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                    tokio::time::sleep(RETRY_INTERVAL).await;
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(AppError::LockHeld {
+                        resource: resource.to_string(),
+                        path: lock_path.to_path_buf(),
+                    })
+                }
+                Err(TryLockError::Error(e)) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Locks the file at `path` through a lock file in the user's cache
+    /// directory, so output directories are not cluttered with lock files
+    /// and other users' locks never stand in the way.
+    pub async fn for_file(path: &Path, resource: &str, wait: Duration) -> Result<Self, AppError> {
+        let lock_path = lock_path_for(&DiskCache::default_cache_dir().join("locks"), path)?;
+        Self::acquire(
+            &lock_path,
+            &format!("{} {}", resource, path.display()),
+            wait,
+        )
+        .await
+    }
+}
+
+/// The lock file in `lock_dir` for `path`, named by a hash of the path
+/// that is the same across runs and builds. The path is canonicalized, so
+/// every spelling of it (relative, through a symlink, with `..`) shares one
+/// lock; `path` itself need not exist yet, only its directory.
+fn lock_path_for(lock_dir: &Path, path: &Path) -> Result<PathBuf, AppError> {
+    let absolute = std::path::absolute(path)?;
+    let resolved = match (absolute.parent(), absolute.file_name()) {
+        (Some(dir), Some(name)) => dir
+            .canonicalize()
+            .map(|dir| dir.join(name))
+            .unwrap_or(absolute),
+        _ => absolute,
+    };
+    Ok(lock_dir.join(format!(
+        "{:016x}.lock",
+        fnv1a(resolved.as_os_str().as_encoded_bytes())
+    )))
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is fixed across Rust
+/// releases, so runs built by different toolchains agree on lock names.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn lock_path() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("n2p_lock_{}", uuid::Uuid::new_v4()))
+            .join(".lock")
+    }
+
+    #[tokio::test]
+    async fn test_second_holder_is_refused_until_the_first_releases() {
+        let path = lock_path();
+        let first = FileLock::acquire(&path, "checkpoint", Duration::ZERO)
+            .await
+            .unwrap();
+
+        let refused = FileLock::acquire(&path, "checkpoint", Duration::ZERO).await;
+        match refused {
+            Err(AppError::LockHeld {
+                resource,
+                path: held,
+            }) => {
+                assert_eq!(resource, "checkpoint");
+                assert_eq!(held, path);
+            }
+            other => panic!("expected LockHeld, got {:?}", other),
+        }
+
+        drop(first);
+        assert!(FileLock::acquire(&path, "checkpoint", Duration::ZERO)
+            .await
+            .is_ok());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_waiting_holder_gets_the_lock_once_released() {
+        let path = lock_path();
+        let first = FileLock::acquire(&path, "output file", Duration::ZERO)
+            .await
+            .unwrap();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            drop(first);
+        });
+
+        let second = FileLock::acquire(&path, "output file", Duration::from_secs(5)).await;
+        assert!(second.is_ok());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_every_spelling_of_a_path_shares_one_lock() {
+        let dir = std::env::temp_dir().join(format!("n2p_lock_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        let locks = Path::new("/locks");
+
+        let direct = lock_path_for(locks, &dir.join("out.md")).unwrap();
+        let detour = lock_path_for(locks, &dir.join("sub").join("..").join("out.md")).unwrap();
+        let other = lock_path_for(locks, &dir.join("other.md")).unwrap();
+
+        assert_eq!(direct, detour);
+        assert_ne!(direct, other);
+        assert_eq!(direct.parent(), Some(locks));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_lock_names_are_stable() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
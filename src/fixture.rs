@@ -0,0 +1,332 @@
+// src/fixture.rs
+//! The `notion2prompt fixture` command: API responses saved as test fixtures.
+//!
+//! A failing case is easiest to fix with the responses that trigger it.
+//! The command fetches an object past the cache, records every response and
+//! writes them in the layout of `tests/fixtures/api_responses`: one JSON
+//! file per response, named by endpoint (`page_<id>.json`,
+//! `blocks_<id>.json`, `database_<id>.json`, `pages_<id>.json`, ...), plus
+//! `endpoints.json` listing which request each file answers, in order, for
+//! a mock client to serve.
+//!
+//! Nothing is written as fetched. Every Notion ID (and pagination cursor)
+//! is replaced by a random one, the same everywhere it appears; user names,
+//! emails and avatars are dropped; signed file URLs lose their signature.
+//! With `--scramble-text`, the letters and digits of all text content and
+//! URL slugs are replaced as well, keeping length, case and punctuation.
+
+use crate::api::client::relative_endpoint;
+use crate::api::{HarRecorder, NotionFetcher, NotionHttpClient};
+use crate::config::{CommandLineInput, PipelineConfig};
+use crate::console::Console;
+use crate::error::{AppError, ExitCode};
+use once_cell::sync::Lazy;
+use rand::Rng;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A Notion ID, hyphenated or not.
+static NOTION_ID: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b[0-9a-f]{8}-?[0-9a-f]{4}-?[0-9a-f]{4}-?[0-9a-f]{4}-?[0-9a-f]{12}\b")
+        .expect("valid regex")
+});
+
+/// One entry of `endpoints.json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct FixtureEntry {
+    method: String,
+    /// Relative to `https://api.notion.com/v1/`, with its query string
+    endpoint: String,
+    file: String,
+}
+
+/// Fetches `notion_input` and writes its sanitized responses to `out`.
+pub async fn write_fixtures(
+    cli: &CommandLineInput,
+    notion_input: &str,
+    out: &Path,
+    scramble_text: bool,
+    console: &Console,
+) -> Result<ExitCode, AppError> {
+    let config = PipelineConfig::resolve_for(cli, notion_input)?;
+    let recorder = Arc::new(HarRecorder::new());
+    let client = NotionHttpClient::with_pool_config(&config.api_key, &config.connection_pool)?
+        .with_max_block_bytes(config.max_block_bytes)
+        .with_har(Arc::clone(&recorder));
+    let result = NotionFetcher::new(Arc::new(client), &config)
+        .fetch_recursive(&config.notion_id)
+        .await?;
+
+    fs::create_dir_all(out)?;
+    let mut sanitizer = Sanitizer::new(scramble_text);
+    let mut entries: Vec<FixtureEntry> = Vec::new();
+    for exchange in recorder.successful_exchanges() {
+        let endpoint = sanitizer.ids(relative_endpoint(&exchange.url));
+        let mut body: Value = serde_json::from_str(&exchange.body)?;
+        sanitizer.sanitize(&mut body);
+
+        let file = unique_name(&fixture_name(&endpoint), &entries);
+        fs::write(out.join(&file), serde_json::to_string_pretty(&body)? + "\n")?;
+        entries.push(FixtureEntry {
+            method: exchange.method,
+            endpoint,
+            file,
+        });
+    }
+    fs::write(
+        out.join("endpoints.json"),
+        serde_json::to_string_pretty(&entries)? + "\n",
+    )?;
+
+    console.success(format!(
+        "Wrote {} fixture(s) for '{}' to {}",
+        entries.len(),
+        result.data.display_title(),
+        out.display()
+    ));
+    if !scramble_text {
+        console
+            .info("Text content is kept as is; pass --scramble-text before sharing private pages.");
+    }
+    Ok(if result.metadata.warnings.is_empty() {
+        ExitCode::Success
+    } else {
+        ExitCode::PartialSuccess
+    })
+}
+
+/// The fixture file name for a response to `endpoint`, following the
+/// naming convention of `tests/fixtures/api_responses`.
+fn fixture_name(endpoint: &str) -> String {
+    let path = endpoint.split('?').next().unwrap_or(endpoint);
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let stem = match segments.as_slice() {
+        ["pages", id] => format!("page_{}", id),
+        ["blocks", id] => format!("block_{}", id),
+        ["blocks", id, "children"] => format!("blocks_{}", id),
+        ["databases", id] => format!("database_{}", id),
+        ["databases", id, "query"] | ["data_sources", id, "query"] => format!("pages_{}", id),
+        ["data_sources", id] => format!("data_source_{}", id),
+        other => other.join("_"),
+    };
+    format!("{}.json", stem)
+}
+
+/// `name`, or `name` numbered from 2 when earlier pages of the same
+/// endpoint already took it.
+fn unique_name(name: &str, taken: &[FixtureEntry]) -> String {
+    let stem = name.trim_end_matches(".json");
+    let mut candidate = name.to_string();
+    let mut n = 1;
+    while taken.iter().any(|entry| entry.file == candidate) {
+        n += 1;
+        candidate = format!("{}_{}.json", stem, n);
+    }
+    candidate
+}
+
+/// Rewrites response bodies so they can be shared.
+struct Sanitizer {
+    /// Original ID, lowercase without hyphens, to its replacement
+    ids: HashMap<String, uuid::Uuid>,
+    scramble_text: bool,
+}
+
+impl Sanitizer {
+    fn new(scramble_text: bool) -> Self {
+        Self {
+            ids: HashMap::new(),
+            scramble_text,
+        }
+    }
+
+    /// `text` with every Notion ID replaced, keeping its hyphenation and case.
+    fn ids(&mut self, text: &str) -> String {
+        NOTION_ID
+            .replace_all(text, |captures: &regex::Captures| {
+                let original = &captures[0];
+                let key = original.replace('-', "").to_lowercase();
+                let replacement = *self.ids.entry(key).or_insert_with(uuid::Uuid::new_v4);
+                let mut id = if original.contains('-') {
+                    replacement.hyphenated().to_string()
+                } else {
+                    replacement.simple().to_string()
+                };
+                if original.chars().any(|c| c.is_ascii_uppercase()) {
+                    id = id.to_uppercase();
+                }
+                id
+            })
+            .into_owned()
+    }
+
+    fn sanitize(&mut self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                if map.get("object").and_then(Value::as_str) == Some("user") {
+                    map.remove("name");
+                    map.remove("avatar_url");
+                    if let Some(Value::Object(person)) = map.get_mut("person") {
+                        person.remove("email");
+                    }
+                }
+                // Notion-hosted files come with a signed, expiring URL
+                if map.contains_key("expiry_time") {
+                    if let Some(Value::String(url)) = map.get_mut("url") {
+                        url.truncate(url.find('?').unwrap_or(url.len()));
+                    }
+                }
+                for (key, field) in map.iter_mut() {
+                    match field {
+                        Value::String(text) if self.scramble_text => {
+                            if matches!(key.as_str(), "content" | "plain_text") {
+                                *text = scramble(text);
+                            } else if matches!(key.as_str(), "url" | "public_url") {
+                                *text = scramble_notion_slug(text);
+                            }
+                            *text = self.ids(text);
+                        }
+                        _ => self.sanitize(field),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.sanitize(item)),
+            Value::String(text) => *text = self.ids(text),
+            _ => {}
+        }
+    }
+}
+
+/// `text` with every letter and digit replaced by a random one of the same
+/// kind and case.
+fn scramble(text: &str) -> String {
+    let mut rng = rand::rng();
+    text.chars()
+        .map(|c| match c {
+            'a'..='z' => rng.random_range('a'..='z'),
+            'A'..='Z' => rng.random_range('A'..='Z'),
+            '0'..='9' => rng.random_range('0'..='9'),
+            c if c.is_alphanumeric() => rng.random_range('a'..='z'),
+            c => c,
+        })
+        .collect()
+}
+
+/// A notion.so page URL with the title slug before its ID scrambled.
+fn scramble_notion_slug(url: &str) -> String {
+    let Some(path_start) = url.find("notion.so/").map(|i| i + "notion.so/".len()) else {
+        return url.to_string();
+    };
+    let (base, path) = url.split_at(path_start);
+    let segment_start = path.rfind('/').map_or(0, |i| i + 1);
+    let (dirs, segment) = path.split_at(segment_start);
+    match segment.rsplit_once('-') {
+        Some((slug, id)) => format!("{}{}{}-{}", base, dirs, scramble(slug), id),
+        None => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PAGE_ID: &str = "216cd412-8533-8087-a989-cf37889137c3";
+
+    #[test]
+    fn test_ids_are_replaced_consistently_across_forms() {
+        let mut sanitizer = Sanitizer::new(false);
+        let hyphenated = sanitizer.ids(&format!("pages/{}", PAGE_ID));
+        let simple = sanitizer.ids(&format!(
+            "https://www.notion.so/Plan-{}",
+            PAGE_ID.replace('-', "")
+        ));
+
+        let new_id = hyphenated.strip_prefix("pages/").unwrap();
+        assert_ne!(new_id, PAGE_ID);
+        assert_eq!(new_id.len(), PAGE_ID.len());
+        assert_eq!(
+            simple,
+            format!("https://www.notion.so/Plan-{}", new_id.replace('-', ""))
+        );
+    }
+
+    #[test]
+    fn test_users_and_file_signatures_are_dropped() {
+        let mut body = json!({
+            "id": PAGE_ID,
+            "created_by": {
+                "object": "user",
+                "id": "11111111-1111-1111-1111-111111111111",
+                "name": "Ada",
+                "avatar_url": "https://example.com/ada.png",
+                "type": "person",
+                "person": { "email": "ada@example.com" }
+            },
+            "file": {
+                "url": "https://prod-files-secure.s3.amazonaws.com/a/b.png?X-Amz-Signature=abc",
+                "expiry_time": "2024-05-01T00:00:00.000Z"
+            },
+            "plain_text": "Keep me"
+        });
+        Sanitizer::new(false).sanitize(&mut body);
+
+        let user = &body["created_by"];
+        assert!(user.get("name").is_none() && user.get("avatar_url").is_none());
+        assert_eq!(user["person"], json!({}));
+        assert_eq!(user["type"], "person");
+        assert_eq!(
+            body["file"]["url"],
+            "https://prod-files-secure.s3.amazonaws.com/a/b.png"
+        );
+        assert_eq!(body["plain_text"], "Keep me");
+        assert_ne!(body["id"], PAGE_ID);
+    }
+
+    #[test]
+    fn test_scrambling_keeps_shape_and_structure() {
+        let mut body = json!({
+            "type": "text",
+            "text": { "content": "Ship v2, now!", "link": null },
+            "plain_text": "Ship v2, now!",
+            "url": format!("https://www.notion.so/Launch-Plan-{}", PAGE_ID.replace('-', ""))
+        });
+        Sanitizer::new(true).sanitize(&mut body);
+
+        let content = body["text"]["content"].as_str().unwrap();
+        assert_ne!(content, "Ship v2, now!");
+        assert_eq!(content.len(), "Ship v2, now!".len());
+        assert_eq!(&content[4..5], " ");
+        assert!(content[..1].chars().all(|c| c.is_ascii_uppercase()));
+        assert!(content.ends_with('!'));
+        assert_eq!(body["type"], "text");
+        let url = body["url"].as_str().unwrap();
+        assert!(!url.contains("Launch"), "{}", url);
+        assert!(!url.contains(&PAGE_ID.replace('-', "")), "{}", url);
+    }
+
+    #[test]
+    fn test_fixture_names_follow_the_convention() {
+        assert_eq!(fixture_name("pages/abc"), "page_abc.json");
+        assert_eq!(
+            fixture_name("blocks/abc/children?page_size=100"),
+            "blocks_abc.json"
+        );
+        assert_eq!(fixture_name("databases/abc/query"), "pages_abc.json");
+        assert_eq!(fixture_name("data_sources/abc"), "data_source_abc.json");
+        assert_eq!(fixture_name("users/me"), "users_me.json");
+
+        let taken = vec![FixtureEntry {
+            method: "POST".to_string(),
+            endpoint: "databases/abc/query".to_string(),
+            file: "pages_abc.json".to_string(),
+        }];
+        assert_eq!(unique_name("pages_abc.json", &taken), "pages_abc_2.json");
+        assert_eq!(unique_name("page_abc.json", &taken), "page_abc.json");
+    }
+}
@@ -4,12 +4,18 @@
 //! This module implements the recursive rendering logic for Notion blocks
 //! using a data-oriented approach with immutable state transitions.
 
+use super::messages::Messages;
+use super::plugins::{ComposePlugins, NO_PLUGINS};
+use super::property_limits::{rendered_properties, PropertyLimits};
 use super::pure_visitor::MarkdownBlockRenderer;
 use super::state::FormatContext;
-use crate::config::PipelineConfig;
+use super::summary::compose_summary_section;
+use crate::config::{AutoSummary, PipelineConfig, PropertyPlacement};
 use crate::constants::CHARS_PER_BLOCK_ESTIMATE;
 use crate::error::AppError;
-use crate::model::{Block, Database, NotionObject, Page};
+use crate::model::{Block, Database, DatabaseProperty, NotionObject, Page};
+use crate::types::PropertyName;
+use std::collections::HashMap;
 use std::fmt::Write;
 
 // --- Core Types ---
@@ -33,6 +39,18 @@ pub struct RenderContext<'a> {
     pub databases: Option<&'a dyn DatabaseResolver>,
 }
 
+impl RenderContext<'_> {
+    /// The labels for the configured `--lang`, English without a config.
+    pub fn messages(&self) -> &'static Messages {
+        Messages::for_language(self.app_config.map(|c| c.lang).unwrap_or_default())
+    }
+
+    /// The configured compose plugins, none without a config.
+    pub fn plugins(&self) -> &ComposePlugins {
+        self.app_config.map_or(&NO_PLUGINS, |c| &c.plugins)
+    }
+}
+
 impl std::fmt::Debug for RenderContext<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RenderContext")
@@ -42,18 +60,44 @@ impl std::fmt::Debug for RenderContext<'_> {
     }
 }
 
+/// Where a top-level heading starts in rendered markdown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionBoundary {
+    /// Byte offset of the heading in the rendered output
+    pub offset: usize,
+    pub heading: String,
+    pub level: u8,
+}
+
 // --- Public API ---
 
 /// Renders a slice of blocks into markdown.
 pub fn render_blocks(blocks: &[Block], config: &RenderContext) -> Result<String, AppError> {
+    render_block_sections(blocks, config).map(|(output, _)| output)
+}
+
+/// Renders a slice of blocks into markdown, noting where each top-level
+/// heading starts.
+pub fn render_block_sections(
+    blocks: &[Block],
+    config: &RenderContext,
+) -> Result<(String, Vec<SectionBoundary>), AppError> {
+    render_blocks_from(blocks, config, FormatContext::new())
+}
+
+/// Renders a slice of blocks starting from `initial_context`, such as the
+/// content of a database row.
+pub(super) fn render_blocks_from(
+    blocks: &[Block],
+    config: &RenderContext,
+    initial_context: FormatContext,
+) -> Result<(String, Vec<SectionBoundary>), AppError> {
     let formatter = MarkdownBlockRenderer::with_document_blocks(config, blocks);
-    let initial_context = FormatContext::new();
 
     let estimated_capacity = blocks.len() * CHARS_PER_BLOCK_ESTIMATE;
     let mut output = String::with_capacity(estimated_capacity);
 
     let mut context = initial_context;
-    let mut list_context_stack = Vec::new();
 
     log::debug!(
         "Rendering {} blocks (databases available: {})",
@@ -61,61 +105,88 @@ pub fn render_blocks(blocks: &[Block], config: &RenderContext) -> Result<String,
         config.databases.is_some()
     );
 
-    for (i, block) in blocks.iter().enumerate() {
-        let is_list_item = matches!(
-            block,
-            Block::NumberedListItem(_) | Block::BulletedListItem(_)
-        );
-        let next_is_list = i + 1 < blocks.len()
-            && matches!(
-                blocks[i + 1],
-                Block::NumberedListItem(_) | Block::BulletedListItem(_)
-            );
-
-        if is_list_item
-            && (i == 0
-                || !matches!(
-                    blocks[i - 1],
-                    Block::NumberedListItem(_) | Block::BulletedListItem(_)
-                ))
-        {
-            match block {
-                Block::NumberedListItem(_) => {
-                    list_context_stack.push(context.clone());
-                    context = context.enter_numbered_list();
-                }
-                Block::BulletedListItem(_) => {
-                    list_context_stack.push(context.clone());
-                    context = context.enter_bulleted_list();
-                }
-                _ => {}
-            }
+    let mut boundaries = Vec::new();
+    for block in blocks {
+        if let Some((level, heading)) = heading_text(block) {
+            boundaries.push(SectionBoundary {
+                offset: output.len(),
+                heading,
+                level,
+            });
         }
-
         let result = formatter.format_with_context(block, context)?;
-
-        output.push_str(&result.content);
-        context = result.context;
-
-        if is_list_item && !next_is_list {
-            if let Some(saved_context) = list_context_stack.pop() {
-                context = saved_context;
-            }
-        }
+        let mut content = result.content;
+        config.plugins().after_block(block, &mut content);
+        output.push_str(&content);
+        context = result.context.after_block(block);
     }
 
-    Ok(output)
+    Ok((output, boundaries))
+}
+
+/// The level and plain text of a heading block.
+pub(super) fn heading_text(block: &Block) -> Option<(u8, String)> {
+    let (level, content) = match block {
+        Block::Heading1(b) => (1, &b.content),
+        Block::Heading2(b) => (2, &b.content),
+        Block::Heading3(b) => (3, &b.content),
+        _ => return None,
+    };
+    let text = content
+        .rich_text
+        .iter()
+        .map(|item| item.plain_text.as_str())
+        .collect();
+    Some((level, text))
 }
 
 // --- Object-Level Rendering ---
 
 /// Composes a Notion page into markdown: title, properties, content, metadata.
+///
+/// The properties follow the content instead with `--properties bottom`, and
+/// are left out with `--properties hide` or `--no-properties`. With
+/// `--auto-summary` a generated Summary section follows the title.
 pub fn compose_page_markdown(page: &Page, config: &RenderContext) -> Result<String, AppError> {
-    let title = compose_title_section(page);
+    compose_page_sections(page, config).map(|(markdown, _)| markdown)
+}
+
+/// Composes a page like [`compose_page_markdown`], also returning where
+/// each top-level heading of its content starts.
+pub fn compose_page_sections(
+    page: &Page,
+    config: &RenderContext,
+) -> Result<(String, Vec<SectionBoundary>), AppError> {
+    let title = config.plugins().before_page(page)
+        + &compose_title_section(page)
+        + &compose_summary_section(page, auto_summary(config), config.messages())?;
     let properties = compose_properties_section(page, config)?;
-    let content = compose_content_section(page, config)?;
-    let metadata = compose_metadata_section(page);
-    Ok([title, properties, content, metadata].concat())
+    let (content, mut boundaries) = compose_content_section(page, config)?;
+    let metadata = compose_metadata_section(page, config.messages());
+
+    let placement = config
+        .app_config
+        .map(|cfg| cfg.property_placement)
+        .unwrap_or_default();
+    if placement == PropertyPlacement::Bottom {
+        for boundary in &mut boundaries {
+            boundary.offset += title.len();
+        }
+        return Ok(([title, content, properties, metadata].concat(), boundaries));
+    }
+
+    let content_offset = title.len() + properties.len();
+    for boundary in &mut boundaries {
+        boundary.offset += content_offset;
+    }
+    Ok(([title, properties, content, metadata].concat(), boundaries))
+}
+
+fn auto_summary(config: &RenderContext) -> AutoSummary {
+    config
+        .app_config
+        .map(|cfg| cfg.auto_summary)
+        .unwrap_or_default()
 }
 
 fn compose_title_section(page: &Page) -> String {
@@ -125,92 +196,155 @@ fn compose_title_section(page: &Page) -> String {
 fn compose_properties_section(page: &Page, config: &RenderContext) -> Result<String, AppError> {
     let include_properties = config
         .app_config
-        .map(|cfg| cfg.include_properties)
+        .map(|cfg| cfg.include_properties && cfg.property_placement != PropertyPlacement::Hide)
         .unwrap_or(true);
 
     if !include_properties {
         return Ok(String::new());
     }
 
-    let mut out = String::from("## Properties\n\n");
-    for (key, value) in &page.properties {
-        if matches!(
-            value.type_specific_value,
-            crate::model::PropertyTypeValue::Title { .. }
-        ) {
-            continue;
-        }
+    let limits = PropertyLimits::from_config(config.app_config);
+    let properties = rendered_properties(page)?;
+    let shown = limits.max_properties.unwrap_or(properties.len());
 
-        let formatted = super::properties::render_property_value(Some(value))?;
-        if !formatted.is_empty() {
-            writeln!(out, "- **{}**: {}", key, formatted)?;
-        }
+    let mut out = format!("## {}\n\n", config.messages().properties);
+    for (key, value) in properties.iter().take(shown) {
+        writeln!(out, "- **{}**: {}", key, limits.truncate(value))?;
+    }
+    if properties.len() > shown {
+        writeln!(
+            out,
+            "- *…{} {} {}*",
+            config.messages().and,
+            properties.len() - shown,
+            config.messages().more_properties_not_shown
+        )?;
     }
     out.push('\n');
     Ok(out)
 }
 
-fn compose_content_section(page: &Page, config: &RenderContext) -> Result<String, AppError> {
+fn compose_content_section(
+    page: &Page,
+    config: &RenderContext,
+) -> Result<(String, Vec<SectionBoundary>), AppError> {
     if page.blocks.is_empty() {
-        return Ok(String::new());
+        return Ok((String::new(), Vec::new()));
     }
-    let blocks_md = render_blocks(&page.blocks, config)?;
+    let (blocks_md, boundaries) = render_block_sections(&page.blocks, config)?;
     log::debug!(
         "Rendered page '{}': {} bytes",
         page.title().as_str(),
         blocks_md.len()
     );
-    Ok(format!("{}\n", blocks_md))
+    Ok((format!("{}\n", blocks_md), boundaries))
 }
 
-fn compose_metadata_section(page: &Page) -> String {
+fn compose_metadata_section(page: &Page, messages: &Messages) -> String {
     format!(
-        "## Metadata\n\n- **Page ID**: {}\n- **URL**: {}\n",
+        "## {}\n\n- **{}**: {}\n- **URL**: {}\n",
+        messages.metadata,
+        messages.page_id,
         page.id.as_str(),
         page.url
     )
 }
 
 /// Composes a database summary as markdown: title, schema, data count, metadata.
-pub fn compose_database_summary(db: &Database) -> Result<String, AppError> {
+pub fn compose_database_summary(db: &Database, config: &RenderContext) -> Result<String, AppError> {
+    let messages = config.messages();
     let mut content = String::new();
     let title = db.title().as_plain_text();
 
     writeln!(content, "# {}", title)?;
     writeln!(content)?;
 
-    writeln!(content, "## Schema")?;
+    writeln!(content, "## {}", messages.schema)?;
     writeln!(content)?;
 
-    if !db.properties.is_empty() {
-        writeln!(content, "| Property | Type |")?;
-        writeln!(content, "|----------|------|")?;
+    if db.data_sources.is_empty() {
+        write_schema_table(&mut content, &db.properties, messages)?;
+    } else {
+        for source in &db.data_sources {
+            writeln!(content, "### {}", source.name)?;
+            writeln!(content)?;
+            write_schema_table(&mut content, &source.properties, messages)?;
+        }
+    }
+
+    writeln!(content, "## {}", messages.data)?;
+    writeln!(content)?;
 
-        for (name, schema) in &db.properties {
+    if db.row_count() == 0 {
+        writeln!(content, "*{}*", messages.database_has_no_rows)?;
+    } else if db.data_sources.is_empty() {
+        writeln!(
+            content,
+            "{} {} {}.",
+            messages.database_contains,
+            db.pages.len(),
+            messages.pages
+        )?;
+    } else {
+        writeln!(
+            content,
+            "{} {} {} {} {}:",
+            messages.database_contains,
+            db.row_count(),
+            messages.pages_in,
+            db.data_sources.len(),
+            messages.data_sources
+        )?;
+        writeln!(content)?;
+        for source in &db.data_sources {
             writeln!(
                 content,
-                "| {} | {} |",
-                name.as_str().replace('|', "\\|"),
-                schema.property_type
+                "- {}: {} {}",
+                source.name,
+                source.pages.len(),
+                messages.pages
             )?;
         }
         writeln!(content)?;
     }
 
-    writeln!(content, "## Data")?;
+    writeln!(content, "## {}", messages.metadata)?;
     writeln!(content)?;
+    writeln!(
+        content,
+        "- **{}**: {}",
+        messages.database_id,
+        db.id.as_str()
+    )?;
 
-    if !db.pages.is_empty() {
-        writeln!(content, "Database contains {} pages.", db.pages.len())?;
-    } else {
-        writeln!(content, "*Database has no rows.*")?;
-    }
+    Ok(content)
+}
 
-    writeln!(content, "## Metadata")?;
+/// Writes a property/type table for one schema, if it has any properties.
+fn write_schema_table(
+    content: &mut String,
+    properties: &HashMap<PropertyName, DatabaseProperty>,
+    messages: &Messages,
+) -> Result<(), AppError> {
+    if properties.is_empty() {
+        return Ok(());
+    }
+    writeln!(
+        content,
+        "| {} | {} |",
+        messages.property_column, messages.type_column
+    )?;
+    writeln!(content, "|----------|------|")?;
+    for (name, schema) in properties {
+        writeln!(
+            content,
+            "| {} | {} |",
+            name.as_str().replace('|', "\\|"),
+            schema.property_type
+        )?;
+    }
     writeln!(content)?;
-    writeln!(content, "- **Database ID**: {}", db.id.as_str())?;
-
-    Ok(content)
+    Ok(())
 }
 
 /// Composes a single block into markdown with a heading.
@@ -231,7 +365,7 @@ pub fn compose_notion_markdown(
 ) -> Result<String, AppError> {
     match obj {
         NotionObject::Page(page) => compose_page_markdown(page, config),
-        NotionObject::Database(db) => compose_database_summary(db),
+        NotionObject::Database(db) => compose_database_summary(db, config),
         NotionObject::Block(block) => compose_block_markdown(block, config),
     }
 }
@@ -0,0 +1,249 @@
+// src/formatting/callouts.rs
+//! Semantic types for callout blocks, read from their icon and color.
+//!
+//! Notion callouts carry meaning only through their look: a 💡 on green is
+//! a tip, a ⚠️ on yellow a warning. Classifying them lets `--callout-style
+//! gfm` and `--callout-style obsidian` render admonitions a reader (human or
+//! model) recognizes, and gives the HTML output a class to style. Rules from
+//! `--callout-type` are checked before the built-in ones.
+
+use crate::model::{CalloutBlock, Icon};
+use crate::types::Color;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// What a callout is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CalloutKind {
+    Note,
+    Tip,
+    Info,
+    Important,
+    Warning,
+    Danger,
+}
+
+impl CalloutKind {
+    /// The lowercase name, as Obsidian and `--callout-type` spell it.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Note => "note",
+            Self::Tip => "tip",
+            Self::Info => "info",
+            Self::Important => "important",
+            Self::Warning => "warning",
+            Self::Danger => "danger",
+        }
+    }
+
+    /// The GitHub alert type. GitHub has no info or danger alerts, so those
+    /// become NOTE and CAUTION.
+    pub fn gfm_alert(self) -> &'static str {
+        match self {
+            Self::Note | Self::Info => "NOTE",
+            Self::Tip => "TIP",
+            Self::Important => "IMPORTANT",
+            Self::Warning => "WARNING",
+            Self::Danger => "CAUTION",
+        }
+    }
+}
+
+impl fmt::Display for CalloutKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CalloutKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "note" => Ok(Self::Note),
+            "tip" => Ok(Self::Tip),
+            "info" => Ok(Self::Info),
+            "important" => Ok(Self::Important),
+            "warning" => Ok(Self::Warning),
+            "danger" => Ok(Self::Danger),
+            other => Err(format!(
+                "'{}' is not a callout type; expected note, tip, info, important, warning or danger",
+                other
+            )),
+        }
+    }
+}
+
+/// What a `--callout-type` rule matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CalloutMatch {
+    Icon(String),
+    Color(Color),
+}
+
+/// A user rule mapping a callout icon or color to a type, parsed from
+/// `ICON=TYPE` or `COLOR=TYPE`, e.g. `🔥=danger` or `purple_background=note`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalloutRule {
+    matches: CalloutMatch,
+    kind: CalloutKind,
+}
+
+impl FromStr for CalloutRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, kind) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format!("expected ICON=TYPE or COLOR=TYPE, got '{}'", s))?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(format!("'{}' names no icon or color", s));
+        }
+        let matches = match key.parse::<Color>() {
+            Ok(color) => CalloutMatch::Color(color),
+            Err(_) => CalloutMatch::Icon(bare_emoji(key).to_string()),
+        };
+        Ok(Self {
+            matches,
+            kind: kind.parse()?,
+        })
+    }
+}
+
+impl fmt::Display for CalloutRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.matches {
+            CalloutMatch::Icon(icon) => write!(f, "{}={}", icon, self.kind),
+            CalloutMatch::Color(color) => write!(f, "{}={}", color.as_str(), self.kind),
+        }
+    }
+}
+
+/// The type of `callout`: by the first of `rules` that matches its icon or
+/// color, else by the built-in icons, else by the built-in colors.
+pub fn classify(callout: &CalloutBlock, rules: &[CalloutRule]) -> Option<CalloutKind> {
+    let icon = match &callout.icon {
+        Some(Icon::Emoji { emoji }) => Some(bare_emoji(emoji)),
+        _ => None,
+    };
+    let color = callout.content.color;
+
+    let by_rule = rules.iter().find(|rule| match &rule.matches {
+        CalloutMatch::Icon(rule_icon) => icon == Some(rule_icon.as_str()),
+        CalloutMatch::Color(rule_color) => color == *rule_color,
+    });
+    if let Some(rule) = by_rule {
+        return Some(rule.kind);
+    }
+    icon.and_then(kind_of_icon).or_else(|| kind_of_color(color))
+}
+
+fn kind_of_icon(icon: &str) -> Option<CalloutKind> {
+    match icon {
+        "📝" | "📌" | "🗒" => Some(CalloutKind::Note),
+        "💡" | "✨" => Some(CalloutKind::Tip),
+        "ℹ" => Some(CalloutKind::Info),
+        "❗" | "‼" | "📣" => Some(CalloutKind::Important),
+        "⚠" | "🚧" => Some(CalloutKind::Warning),
+        "🚫" | "⛔" | "🛑" | "❌" | "🔥" => Some(CalloutKind::Danger),
+        _ => None,
+    }
+}
+
+fn kind_of_color(color: Color) -> Option<CalloutKind> {
+    match color {
+        Color::Blue | Color::LightBlue => Some(CalloutKind::Info),
+        Color::Green | Color::LightGreen => Some(CalloutKind::Tip),
+        Color::Yellow | Color::LightYellow | Color::Orange | Color::LightOrange => {
+            Some(CalloutKind::Warning)
+        }
+        Color::Red | Color::LightRed => Some(CalloutKind::Danger),
+        _ => None,
+    }
+}
+
+/// `emoji` without the variation selector, so `⚠️` and `⚠` match alike.
+fn bare_emoji(emoji: &str) -> &str {
+    emoji.trim().trim_end_matches('\u{FE0F}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BlockCommon, TextBlockContent};
+    use crate::types::BlockId;
+
+    fn callout(emoji: Option<&str>, color: Color) -> CalloutBlock {
+        CalloutBlock {
+            common: BlockCommon::new(BlockId::new_v4()),
+            icon: emoji.map(|emoji| Icon::Emoji {
+                emoji: emoji.to_string(),
+            }),
+            content: TextBlockContent {
+                rich_text: vec![],
+                color,
+            },
+        }
+    }
+
+    #[test]
+    fn test_built_in_icons_win_over_colors() {
+        let cases = [
+            ("💡", CalloutKind::Tip),
+            ("⚠️", CalloutKind::Warning),
+            ("⚠", CalloutKind::Warning),
+            ("🚫", CalloutKind::Danger),
+            ("ℹ️", CalloutKind::Info),
+        ];
+        for (emoji, kind) in cases {
+            assert_eq!(
+                classify(&callout(Some(emoji), Color::LightRed), &[]),
+                Some(kind),
+                "{}",
+                emoji
+            );
+        }
+        assert_eq!(
+            classify(&callout(Some("🦀"), Color::LightRed), &[]),
+            Some(CalloutKind::Danger)
+        );
+        assert_eq!(classify(&callout(Some("🦀"), Color::LightGray), &[]), None);
+        assert_eq!(classify(&callout(None, Color::Default), &[]), None);
+    }
+
+    #[test]
+    fn test_rules_come_first() {
+        let rules: Vec<CalloutRule> = ["🦀=note", "💡=important", "gray_background=info"]
+            .iter()
+            .map(|rule| rule.parse().unwrap())
+            .collect();
+        assert_eq!(
+            classify(&callout(Some("🦀"), Color::LightRed), &rules),
+            Some(CalloutKind::Note)
+        );
+        assert_eq!(
+            classify(&callout(Some("💡"), Color::Default), &rules),
+            Some(CalloutKind::Important)
+        );
+        assert_eq!(
+            classify(&callout(None, Color::LightGray), &rules),
+            Some(CalloutKind::Info)
+        );
+    }
+
+    #[test]
+    fn test_rule_parsing() {
+        let rule: CalloutRule = "⚠️ = Danger".parse().unwrap();
+        assert_eq!(rule.to_string(), "⚠=danger");
+        assert_eq!(
+            "red=tip".parse::<CalloutRule>().unwrap().to_string(),
+            "red=tip"
+        );
+        assert!("💡".parse::<CalloutRule>().is_err());
+        assert!("=tip".parse::<CalloutRule>().is_err());
+        assert!("💡=hint".parse::<CalloutRule>().is_err());
+    }
+}
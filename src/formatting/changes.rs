@@ -0,0 +1,271 @@
+// src/formatting/changes.rs
+//! Change markers for `--annotate-changes`.
+//!
+//! Compares freshly fetched content with the version an earlier run left in
+//! the response cache and marks what differs inline. Text blocks and
+//! database rows that were not there before end in `(new)`; those whose
+//! text or properties differ end in `(changed)`. Changed properties of the
+//! root page are marked by name in its Properties section. Blocks without
+//! text, such as images and code, carry no marker, but their children do.
+
+use crate::model::blocks::ChildDatabaseContent;
+use crate::model::{Block, Database, NotionObject, Page, PageTitle, PropertyTypeValue};
+use crate::types::{PropertyName, RichTextItem};
+use std::collections::HashMap;
+
+const NEW: &str = " (new)";
+const CHANGED: &str = " (changed)";
+
+/// Marks what differs in `content` from `previous`; returns how many
+/// markers were added.
+pub fn annotate_changes(content: &mut NotionObject, previous: &NotionObject) -> usize {
+    let mut earlier = Earlier::default();
+    earlier.add_object(previous);
+    match content {
+        NotionObject::Page(page) => earlier.annotate_page(page, false),
+        NotionObject::Database(db) => earlier.annotate_database(db),
+        NotionObject::Block(block) => earlier.annotate_blocks(std::slice::from_mut(block)),
+    }
+}
+
+/// The pages and blocks of the earlier version, by ID.
+#[derive(Default)]
+struct Earlier<'a> {
+    pages: HashMap<&'a str, &'a Page>,
+    blocks: HashMap<&'a str, &'a Block>,
+}
+
+impl<'a> Earlier<'a> {
+    fn add_object(&mut self, object: &'a NotionObject) {
+        match object {
+            NotionObject::Page(page) => self.add_page(page),
+            NotionObject::Database(db) => self.add_database(db),
+            NotionObject::Block(block) => self.add_blocks(std::slice::from_ref(block)),
+        }
+    }
+
+    fn add_page(&mut self, page: &'a Page) {
+        self.pages.insert(page.id.as_str(), page);
+        self.add_blocks(&page.blocks);
+    }
+
+    fn add_database(&mut self, db: &'a Database) {
+        for row in db.rows() {
+            self.add_page(row);
+        }
+    }
+
+    fn add_blocks(&mut self, blocks: &'a [Block]) {
+        for block in blocks {
+            self.blocks.insert(block.id().as_str(), block);
+            if let Block::ChildDatabase(child) = block {
+                if let ChildDatabaseContent::Fetched(db) = &child.content {
+                    self.add_database(db);
+                }
+            }
+            self.add_blocks(block.children());
+        }
+    }
+
+    /// Marks a row by its title, or the root page's properties by name,
+    /// then the page's blocks.
+    fn annotate_page(&self, page: &mut Page, is_row: bool) -> usize {
+        let Some(before) = self.pages.get(page.id.as_str()) else {
+            // A new row is marked as a whole; its blocks are all new
+            if is_row {
+                mark_title(page, NEW);
+                return 1;
+            }
+            return 0;
+        };
+
+        let changed: Vec<PropertyName> = page
+            .properties
+            .iter()
+            .filter(|(name, value)| {
+                before.properties.get(*name).map(|v| &v.type_specific_value)
+                    != Some(&value.type_specific_value)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        let mut marked = 0;
+        if is_row {
+            if !changed.is_empty() {
+                mark_title(page, CHANGED);
+                marked += 1;
+            }
+        } else {
+            for name in changed {
+                if let Some(value) = page.properties.remove(&name) {
+                    let marked_name = PropertyName::new(format!("{}{}", name.as_str(), CHANGED));
+                    page.properties.insert(marked_name, value);
+                    marked += 1;
+                }
+            }
+        }
+        marked + self.annotate_blocks(&mut page.blocks)
+    }
+
+    fn annotate_database(&self, db: &mut Database) -> usize {
+        let mut marked = 0;
+        for row in &mut db.pages {
+            marked += self.annotate_page(row, true);
+        }
+        for source in &mut db.data_sources {
+            for row in &mut source.pages {
+                marked += self.annotate_page(row, true);
+            }
+        }
+        marked
+    }
+
+    fn annotate_blocks(&self, blocks: &mut [Block]) -> usize {
+        let mut marked = 0;
+        for block in blocks {
+            let marker = match self.blocks.get(block.id().as_str()) {
+                None => Some(NEW),
+                Some(before) if !same_content(block, before) => Some(CHANGED),
+                Some(_) => None,
+            };
+            if let (Some(marker), Some(text)) = (marker, text_of(block)) {
+                text.push(RichTextItem::plain_text(marker));
+                marked += 1;
+                // Everything under a new block is new as well
+                if marker == NEW {
+                    continue;
+                }
+            }
+            if let Block::ChildDatabase(child) = block {
+                if let ChildDatabaseContent::Fetched(db) = &mut child.content {
+                    marked += self.annotate_database(db);
+                }
+            }
+            marked += self.annotate_blocks(block.children_mut());
+        }
+        marked
+    }
+}
+
+/// Whether two versions of a block match apart from their children.
+/// A child database is compared by title; its rows are compared on their own.
+fn same_content(block: &Block, before: &Block) -> bool {
+    match (block, before) {
+        (Block::ChildDatabase(now), Block::ChildDatabase(then)) => now.title == then.title,
+        _ => {
+            let (mut now, mut then) = (block.clone(), before.clone());
+            now.set_children(Vec::new());
+            then.set_children(Vec::new());
+            now == then
+        }
+    }
+}
+
+/// The text a marker is appended to, for blocks that have text.
+fn text_of(block: &mut Block) -> Option<&mut Vec<RichTextItem>> {
+    let content = match block {
+        Block::Paragraph(b) => &mut b.content,
+        Block::Heading1(b) => &mut b.content,
+        Block::Heading2(b) => &mut b.content,
+        Block::Heading3(b) => &mut b.content,
+        Block::BulletedListItem(b) => &mut b.content,
+        Block::NumberedListItem(b) => &mut b.content,
+        Block::ToDo(b) => &mut b.content,
+        Block::Toggle(b) => &mut b.content,
+        Block::Quote(b) => &mut b.content,
+        Block::Callout(b) => &mut b.content,
+        Block::Template(b) => &mut b.content,
+        _ => return None,
+    };
+    Some(&mut content.rich_text)
+}
+
+/// Appends `marker` to a row's title, both the title property the table
+/// shows and the title its own section is headed with.
+fn mark_title(page: &mut Page, marker: &str) {
+    for value in page.properties.values_mut() {
+        if let PropertyTypeValue::Title { title } = &mut value.type_specific_value {
+            title.push(RichTextItem::plain_text(marker));
+        }
+    }
+    page.title = PageTitle::new(format!("{}{}", page.title.as_str(), marker));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BlockCommon, ParagraphBlock, TextBlockContent};
+    use crate::types::{BlockId, Color, PageId};
+
+    fn paragraph(id: &BlockId, text: &str) -> Block {
+        Block::Paragraph(ParagraphBlock {
+            common: BlockCommon::new(id.clone()),
+            content: TextBlockContent {
+                rich_text: vec![RichTextItem::plain_text(text)],
+                color: Color::Default,
+            },
+        })
+    }
+
+    fn text(block: &Block) -> String {
+        match block {
+            Block::Paragraph(p) => p
+                .content
+                .rich_text
+                .iter()
+                .map(|i| i.plain_text.as_str())
+                .collect(),
+            _ => panic!("expected a paragraph"),
+        }
+    }
+
+    fn page(id: &PageId, status: &str, blocks: Vec<Block>) -> Page {
+        Page::new(id.clone(), "Plan")
+            .with_blocks(blocks)
+            .with_property(
+                "Status",
+                PropertyTypeValue::RichText {
+                    rich_text: vec![RichTextItem::plain_text(status)],
+                },
+            )
+    }
+
+    #[test]
+    fn test_new_and_changed_blocks_and_properties_are_marked() {
+        let page_id = PageId::new_v4();
+        let (kept, edited, added) = (BlockId::new_v4(), BlockId::new_v4(), BlockId::new_v4());
+        let previous = NotionObject::Page(page(
+            &page_id,
+            "Draft",
+            vec![paragraph(&kept, "Same"), paragraph(&edited, "Before")],
+        ));
+        let mut content = NotionObject::Page(page(
+            &page_id,
+            "Final",
+            vec![
+                paragraph(&kept, "Same"),
+                paragraph(&edited, "After"),
+                paragraph(&added, "Added"),
+            ],
+        ));
+
+        assert_eq!(annotate_changes(&mut content, &previous), 3);
+        let NotionObject::Page(page) = content else {
+            unreachable!()
+        };
+        let texts: Vec<String> = page.blocks.iter().map(text).collect();
+        assert_eq!(texts, ["Same", "After (changed)", "Added (new)"]);
+        assert!(page
+            .properties
+            .contains_key(&PropertyName::new("Status (changed)")));
+    }
+
+    #[test]
+    fn test_unchanged_content_gets_no_markers() {
+        let content = NotionObject::Page(page(
+            &PageId::new_v4(),
+            "Draft",
+            vec![paragraph(&BlockId::new_v4(), "Same")],
+        ));
+        assert_eq!(annotate_changes(&mut content.clone(), &content), 0);
+    }
+}
@@ -0,0 +1,138 @@
+// src/formatting/code_language.rs
+//! Maps Notion code block languages to Markdown fence identifiers.
+//!
+//! Notion names languages for display (`plain text`, `c++`, `visual basic`),
+//! while Markdown renderers and syntax highlighters expect short identifiers
+//! (`plaintext`, `cpp`, `vb`). Blocks left as plain text are often real code
+//! pasted without picking a language, so their content is checked for a few
+//! unambiguous signatures before falling back to `plaintext`.
+
+/// Notion language names, lowercased with spaces removed, whose fence
+/// identifier differs from that key. The Rust-style variant names some
+/// older data carries (`CPlusPlus`, `ObjectiveC`) normalize to keys here too.
+static FENCE_ALIASES: &[(&str, &str)] = &[
+    ("c++", "cpp"),
+    ("cplusplus", "cpp"),
+    ("c#", "csharp"),
+    ("f#", "fsharp"),
+    ("objective-c", "objectivec"),
+    ("vb.net", "vbnet"),
+    ("visualbasic", "vb"),
+    ("docker", "dockerfile"),
+    ("markup", "html"),
+    ("webassembly", "wasm"),
+    ("flow", "javascript"),
+];
+
+/// Languages Notion offers that are too vague to name a highlighter.
+static UNSPECIFIED: &[&str] = &[
+    "plaintext",
+    "java/c/c++/c#",
+    "javaorcorcplusplusorcsharp",
+    "",
+];
+
+/// Returns the fence identifier for a code block.
+///
+/// Known Notion languages map through [`FENCE_ALIASES`] or are lowercased;
+/// unspecified ones are detected from `content` and default to `plaintext`.
+pub fn fence_language(notion_language: &str, content: &str) -> String {
+    let key: String = notion_language
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+
+    if UNSPECIFIED.contains(&key.as_str()) {
+        return detect_language(content).unwrap_or("plaintext").to_string();
+    }
+
+    FENCE_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == key)
+        .map(|(_, fence)| fence.to_string())
+        .unwrap_or(key)
+}
+
+/// Recognizes content whose language is unambiguous from its first line.
+fn detect_language(content: &str) -> Option<&'static str> {
+    let trimmed = content.trim_start();
+    let first_line = trimmed.lines().next()?.trim();
+
+    if let Some(interpreter) = first_line.strip_prefix("#!") {
+        return detect_interpreter(interpreter);
+    }
+
+    let lowercase = first_line.to_lowercase();
+    if lowercase.starts_with("<?php") {
+        Some("php")
+    } else if lowercase.starts_with("<?xml") {
+        Some("xml")
+    } else if lowercase.starts_with("<!doctype html") || lowercase.starts_with("<html") {
+        Some("html")
+    } else if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(trimmed).is_ok()
+    {
+        Some("json")
+    } else {
+        None
+    }
+}
+
+/// Maps a shebang line's interpreter (`/usr/bin/env python3`) to a language.
+fn detect_interpreter(interpreter: &str) -> Option<&'static str> {
+    let program = interpreter
+        .split_whitespace()
+        .rfind(|part| !part.starts_with('-'))?
+        .rsplit('/')
+        .next()?;
+
+    match program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.') {
+        "bash" => Some("bash"),
+        "sh" | "zsh" | "dash" | "ksh" => Some("shell"),
+        "python" => Some("python"),
+        "node" | "deno" => Some("javascript"),
+        "ruby" => Some("ruby"),
+        "perl" => Some("perl"),
+        "pwsh" => Some("powershell"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notion_names_map_to_fence_identifiers() {
+        assert_eq!(fence_language("TypeScript", ""), "typescript");
+        assert_eq!(fence_language("c++", ""), "cpp");
+        assert_eq!(fence_language("CPlusPlus", ""), "cpp");
+        assert_eq!(fence_language("visual basic", ""), "vb");
+        assert_eq!(fence_language("shell", ""), "shell");
+        assert_eq!(fence_language("rust", ""), "rust");
+    }
+
+    #[test]
+    fn test_plain_text_is_detected_from_content() {
+        assert_eq!(fence_language("plain text", "just words"), "plaintext");
+        assert_eq!(fence_language("PlainText", ""), "plaintext");
+        assert_eq!(
+            fence_language("plain text", "#!/usr/bin/env python3\nprint(1)"),
+            "python"
+        );
+        assert_eq!(fence_language("plain text", "#!/bin/sh\nls"), "shell");
+        assert_eq!(fence_language("plain text", "{\"a\": [1, 2]}"), "json");
+        assert_eq!(fence_language("plain text", "{ not json"), "plaintext");
+        assert_eq!(
+            fence_language("java/c/c++/c#", "<?xml version=\"1.0\"?>"),
+            "xml"
+        );
+    }
+
+    #[test]
+    fn test_explicit_language_is_never_overridden() {
+        assert_eq!(fence_language("yaml", "#!/bin/bash\n"), "yaml");
+    }
+}
@@ -0,0 +1,348 @@
+// src/formatting/database_titles.rs
+//! Compose-time database naming for `--db-title`, and disambiguation of
+//! databases that would otherwise read the same.
+//!
+//! A page with two untitled databases, or a "Tasks" database in each of
+//! several projects, leaves a reader of the prompt guessing which table is
+//! which. Databases named by `--db-title` take that name. Among the rest,
+//! every title that is empty or shared with another database gets the title
+//! of the page it sits in, as in `Tasks (Project Alpha)`; any that still
+//! match are numbered.
+
+use super::messages::Messages;
+use crate::config::PipelineConfig;
+use crate::model::blocks::ChildDatabaseContent;
+use crate::model::{Block, Database, DatabaseTitle, NotionObject, Page};
+use crate::types::{NotionId, RichTextItem};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A name for one database, parsed from `ID=NAME`, e.g. `1a2b...=Roadmap`.
+/// The ID may be given as a Notion URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseTitleOverride {
+    id: NotionId,
+    title: String,
+}
+
+impl FromStr for DatabaseTitleOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (id, title) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected ID=NAME, got '{}'", s))?;
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(format!("'{}' gives the database no name", s));
+        }
+        Ok(Self {
+            id: NotionId::parse(id.trim()).map_err(|e| e.to_string())?,
+            title: title.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for DatabaseTitleOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.id.as_str(), self.title)
+    }
+}
+
+/// `object` with its databases renamed by `--db-title` and made distinct,
+/// passed through unchanged when no title needs to change.
+pub fn titled_databases<'a>(
+    object: Cow<'a, NotionObject>,
+    config: &PipelineConfig,
+) -> Cow<'a, NotionObject> {
+    let mut found = Vec::new();
+    match object.as_ref() {
+        NotionObject::Page(page) => collect_in_page(page, &mut found),
+        NotionObject::Database(db) => collect_database(db.id.as_str(), db, None, &mut found),
+        NotionObject::Block(block) => {
+            collect_in_blocks(std::slice::from_ref(block), None, &mut found)
+        }
+    }
+    let titles = new_titles(
+        found,
+        &config.db_titles,
+        Messages::for_language(config.lang),
+    );
+    if titles.is_empty() {
+        return object;
+    }
+
+    let mut object = object.into_owned();
+    match &mut object {
+        NotionObject::Page(page) => retitle_in_blocks(&mut page.blocks, &titles),
+        NotionObject::Database(db) => {
+            if let Some(title) = titles.get(&key(db.id.as_str())) {
+                db.title = database_title(title);
+            }
+            retitle_in_database(db, &titles);
+        }
+        NotionObject::Block(block) => retitle_in_blocks(std::slice::from_mut(block), &titles),
+    }
+    Cow::Owned(object)
+}
+
+/// A database as it would be headed in the prompt.
+struct FoundDatabase {
+    id: String,
+    title: String,
+    /// Title of the page the database sits in
+    page: Option<String>,
+}
+
+fn collect_in_page(page: &Page, found: &mut Vec<FoundDatabase>) {
+    collect_in_blocks(&page.blocks, Some(page.title.as_str()), found);
+}
+
+fn collect_database(id: &str, db: &Database, page: Option<&str>, found: &mut Vec<FoundDatabase>) {
+    found.push(FoundDatabase {
+        id: key(id),
+        title: db.title().as_plain_text(),
+        page: page.map(str::to_string),
+    });
+    let sources = db.data_sources.iter().map(|source| &source.pages);
+    for row in std::iter::once(&db.pages).chain(sources).flatten() {
+        collect_in_page(row, found);
+    }
+}
+
+fn collect_in_blocks(blocks: &[Block], page: Option<&str>, found: &mut Vec<FoundDatabase>) {
+    for block in blocks {
+        let mut page = page;
+        match block {
+            Block::ChildDatabase(child) => match &child.content {
+                ChildDatabaseContent::Fetched(db) => {
+                    collect_database(child.common.id.as_str(), db, page, found)
+                }
+                _ => found.push(FoundDatabase {
+                    id: key(child.common.id.as_str()),
+                    title: child.title.clone(),
+                    page: page.map(str::to_string),
+                }),
+            },
+            Block::ChildPage(child) => page = Some(&child.title),
+            _ => {}
+        }
+        collect_in_blocks(block.children(), page, found);
+    }
+}
+
+/// The new title of every database whose title changes, by ID.
+fn new_titles(
+    found: Vec<FoundDatabase>,
+    overrides: &[DatabaseTitleOverride],
+    messages: &Messages,
+) -> HashMap<String, String> {
+    let overridden = |id: &str| {
+        overrides
+            .iter()
+            .rfind(|o| key(o.id.as_str()) == id)
+            .map(|o| o.title.clone())
+    };
+
+    let mut titles = HashMap::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for db in &found {
+        let title = overridden(&db.id).unwrap_or_else(|| db.title.clone());
+        *seen.entry(normalized(&title)).or_default() += 1;
+    }
+
+    let mut ambiguous = Vec::new();
+    for db in found {
+        if let Some(title) = overridden(&db.id) {
+            titles.insert(db.id, title);
+        } else if db.title.trim().is_empty() || seen[&normalized(&db.title)] > 1 {
+            ambiguous.push(db);
+        }
+    }
+    if ambiguous.len() < 2 && ambiguous.iter().all(|db| db.title.trim().is_empty()) {
+        // A lone untitled database is not mistaken for anything
+        return titles;
+    }
+
+    let mut taken: HashMap<String, usize> = HashMap::new();
+    for db in ambiguous {
+        let base = if db.title.trim().is_empty() {
+            messages.untitled_database.to_string()
+        } else {
+            db.title.trim().to_string()
+        };
+        let mut title = match &db.page {
+            Some(page) if !page.trim().is_empty() => format!("{} ({})", base, page.trim()),
+            _ => base,
+        };
+        let count = taken.entry(normalized(&title)).or_default();
+        *count += 1;
+        if *count > 1 {
+            title = format!("{} {}", title, count);
+        }
+        titles.insert(db.id, title);
+    }
+    titles
+}
+
+fn retitle_in_database(db: &mut Database, titles: &HashMap<String, String>) {
+    for row in db.pages.iter_mut() {
+        retitle_in_blocks(&mut row.blocks, titles);
+    }
+    for source in db.data_sources.iter_mut() {
+        for row in source.pages.iter_mut() {
+            retitle_in_blocks(&mut row.blocks, titles);
+        }
+    }
+}
+
+fn retitle_in_blocks(blocks: &mut [Block], titles: &HashMap<String, String>) {
+    for block in blocks {
+        if let Block::ChildDatabase(child) = block {
+            if let Some(title) = titles.get(&key(child.common.id.as_str())) {
+                child.title = title.clone();
+                if let ChildDatabaseContent::Fetched(db) = &mut child.content {
+                    db.title = database_title(title);
+                }
+            }
+            if let ChildDatabaseContent::Fetched(db) = &mut child.content {
+                retitle_in_database(db, titles);
+            }
+        }
+        retitle_in_blocks(block.children_mut(), titles);
+    }
+}
+
+fn database_title(title: &str) -> DatabaseTitle {
+    DatabaseTitle::new(vec![RichTextItem::plain_text(title)])
+}
+
+/// Titles that differ only in case or surrounding space read the same.
+fn normalized(title: &str) -> String {
+    title.trim().to_lowercase()
+}
+
+fn key(id: &str) -> String {
+    id.replace('-', "").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Language;
+    use crate::model::testing::test_page;
+    use crate::model::{BlockCommon, ChildDatabaseBlock, ChildPageBlock};
+    use crate::types::{BlockId, DatabaseId};
+
+    fn database_block(id: &BlockId, title: &str) -> Block {
+        let db = Database::new(DatabaseId::parse(id.as_str()).unwrap(), title);
+        Block::ChildDatabase(ChildDatabaseBlock {
+            common: BlockCommon::new(id.clone()),
+            title: title.to_string(),
+            content: ChildDatabaseContent::Fetched(Box::new(db)),
+        })
+    }
+
+    fn child_page(title: &str, blocks: Vec<Block>) -> Block {
+        let mut common = BlockCommon::new(BlockId::new_v4());
+        common.has_children = true;
+        common.children = blocks;
+        Block::ChildPage(ChildPageBlock {
+            common,
+            title: title.to_string(),
+            included_elsewhere: None,
+        })
+    }
+
+    fn page(blocks: Vec<Block>) -> NotionObject {
+        NotionObject::Page(test_page("Plan").with_blocks(blocks))
+    }
+
+    fn titles(object: &NotionObject) -> Vec<String> {
+        let NotionObject::Page(page) = object else {
+            panic!("expected a page");
+        };
+        let mut found = Vec::new();
+        collect_in_page(page, &mut found);
+        found.into_iter().map(|db| db.title).collect()
+    }
+
+    fn retitled(object: &NotionObject, overrides: &[&str]) -> Vec<String> {
+        let config = PipelineConfig {
+            db_titles: overrides.iter().map(|o| o.parse().unwrap()).collect(),
+            lang: Language::En,
+            ..PipelineConfig::default()
+        };
+        titles(&titled_databases(Cow::Borrowed(object), &config))
+    }
+
+    #[test]
+    fn test_distinct_titles_are_left_alone() {
+        let object = page(vec![
+            database_block(&BlockId::new_v4(), "Tasks"),
+            database_block(&BlockId::new_v4(), ""),
+        ]);
+        let config = PipelineConfig::default();
+        assert!(matches!(
+            titled_databases(Cow::Borrowed(&object), &config),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_shared_and_missing_titles_get_their_page() {
+        let object = page(vec![
+            child_page(
+                "Alpha",
+                vec![
+                    database_block(&BlockId::new_v4(), "Tasks"),
+                    database_block(&BlockId::new_v4(), ""),
+                ],
+            ),
+            child_page(
+                "Beta",
+                vec![
+                    database_block(&BlockId::new_v4(), "tasks"),
+                    database_block(&BlockId::new_v4(), ""),
+                ],
+            ),
+            database_block(&BlockId::new_v4(), "Notes"),
+            database_block(&BlockId::new_v4(), "Notes"),
+        ]);
+        assert_eq!(
+            retitled(&object, &[]),
+            [
+                "Tasks (Alpha)",
+                "Untitled database (Alpha)",
+                "tasks (Beta)",
+                "Untitled database (Beta)",
+                "Notes (Plan)",
+                "Notes (Plan) 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overrides_name_databases_by_id() {
+        let roadmap = BlockId::new_v4();
+        let object = page(vec![
+            database_block(&roadmap, "Tasks"),
+            database_block(&BlockId::new_v4(), "Tasks"),
+        ]);
+        let rule = format!("{}=Roadmap", roadmap.as_str());
+        assert_eq!(retitled(&object, &[&rule]), ["Roadmap", "Tasks"]);
+    }
+
+    #[test]
+    fn test_override_parsing() {
+        let id = "1abcd412-8533-800c-984c-f7a33514bc7d";
+        let rule: DatabaseTitleOverride = format!("{} = Road = map ", id).parse().unwrap();
+        assert_eq!(rule.title, "Road = map");
+        assert_eq!(key(rule.id.as_str()), key(id));
+        assert!("Roadmap".parse::<DatabaseTitleOverride>().is_err());
+        assert!(format!("{}=", id).parse::<DatabaseTitleOverride>().is_err());
+        assert!("nope=Roadmap".parse::<DatabaseTitleOverride>().is_err());
+    }
+}
@@ -4,11 +4,13 @@
 //! This module provides builders for constructing tables from database data,
 //! keeping construction logic separate from rendering.
 
+use super::sort::{sort_rows, RowSort};
 use super::types::*;
 use crate::error::AppError;
 use crate::formatting::properties::render_property_value;
 use crate::model::{Database, DatabasePropertyType, Page};
 use crate::output::create_clean_filename;
+use crate::types::PropertyName;
 use std::collections::HashSet;
 
 // --- Table Builder ---
@@ -51,11 +53,54 @@ impl<'a> TableBuilder<'a> {
         self
     }
 
+    /// Sets the maximum number of columns to include.
+    ///
+    /// The title column is always kept; the remaining slots go to the
+    /// columns with the most non-empty cells.
+    pub fn max_columns(mut self, max: Option<usize>) -> Self {
+        self.config.max_columns = max;
+        self
+    }
+
+    /// Sets whether to leave out columns without a value in any row.
+    ///
+    /// The title column is always kept.
+    pub fn exclude_empty_columns(mut self, exclude: bool) -> Self {
+        self.config.exclude_empty_columns = exclude;
+        self
+    }
+
+    /// Orders rows by the given keys, earlier keys first.
+    pub fn sort_rows(mut self, sorts: &'a [RowSort]) -> Self {
+        self.config.sorts = sorts;
+        self
+    }
+
+    /// Aligns the named columns as given instead of by property type.
+    pub fn align_columns(mut self, alignments: &'a [ColumnAlign]) -> Self {
+        self.config.alignments = alignments;
+        self
+    }
+
     /// Builds the table.
     pub fn build(self) -> Result<Table, AppError> {
-        let columns = self.build_columns()?;
-        let rows = self.build_rows(&columns)?;
-        let metadata = self.calculate_metadata(&columns, &rows);
+        let mut columns = self.build_columns()?;
+        let mut rows = self.build_rows(&columns)?;
+        let empty_columns = if self.config.exclude_empty_columns && !rows.is_empty() {
+            drop_empty_columns(&mut columns, &mut rows)
+        } else {
+            Vec::new()
+        };
+        let omitted_columns = match self.config.max_columns {
+            Some(max) if columns.len() > max => {
+                keep_most_filled_columns(&mut columns, &mut rows, max)
+            }
+            _ => 0,
+        };
+        let mut metadata = self.calculate_metadata(&columns, &rows);
+        metadata.omitted_columns = omitted_columns;
+        metadata.empty_columns = empty_columns;
+        metadata.inaccessible_rows = self.database.inaccessible_rows.len();
 
         Ok(Table {
             columns,
@@ -82,10 +127,17 @@ impl<'a> TableBuilder<'a> {
             .map(|(name, schema)| {
                 let property_type = property_type_from_schema(&schema.property_type);
                 log::debug!("  Creating column '{}' of type {:?}", name, property_type);
+                let alignment = self
+                    .config
+                    .alignments
+                    .iter()
+                    .rev()
+                    .find(|align| &align.property == name)
+                    .map_or_else(|| property_type.default_alignment(), |a| a.alignment);
                 Column {
                     name: name.clone(),
                     property_type,
-                    alignment: property_type.default_alignment(),
+                    alignment,
                     width_hint: None,
                 }
             })
@@ -104,14 +156,14 @@ impl<'a> TableBuilder<'a> {
 
     /// Builds rows from pages.
     fn build_rows(&self, columns: &[Column]) -> Result<Vec<TableRow>, AppError> {
-        let pages_to_process = if let Some(max) = self.config.max_pages {
-            &self.pages[..self.pages.len().min(max)]
-        } else {
-            self.pages
-        };
+        let mut pages_to_process: Vec<&Page> = self.pages.iter().collect();
+        sort_rows(&mut pages_to_process, self.database, self.config.sorts);
+        if let Some(max) = self.config.max_pages {
+            pages_to_process.truncate(max);
+        }
 
         pages_to_process
-            .iter()
+            .into_iter()
             .filter(|page| self.config.include_empty_pages || self.is_meaningful_row(page))
             .map(|page| self.build_row(page, columns))
             .collect()
@@ -212,10 +264,83 @@ impl<'a> TableBuilder<'a> {
                     .any(|cell| matches!(cell.value, CellValue::Link { .. }))
             }),
             total_cells: columns.len() * pages.len(),
+            omitted_columns: 0,
+            empty_columns: Vec::new(),
+            inaccessible_rows: 0,
         }
     }
 }
 
+/// Drops all but `max` columns, keeping the title column and then the
+/// columns with the most non-empty cells, in their original order.
+/// Returns the number of columns dropped.
+fn keep_most_filled_columns(columns: &mut Vec<Column>, rows: &mut [TableRow], max: usize) -> usize {
+    let filled = |index: usize| {
+        rows.iter()
+            .filter(|row| {
+                row.cells
+                    .get(index)
+                    .is_some_and(|cell| !cell.value.is_empty())
+            })
+            .count()
+    };
+    let mut ranked: Vec<usize> = (0..columns.len()).collect();
+    ranked.sort_by_key(|&index| {
+        (
+            columns[index].property_type != PropertyType::Title,
+            std::cmp::Reverse(filled(index)),
+            index,
+        )
+    });
+    let kept: HashSet<usize> = ranked.into_iter().take(max).collect();
+
+    let omitted = columns.len() - kept.len();
+    retain_columns(columns, rows, &kept);
+    omitted
+}
+
+/// Drops the columns that are empty in every row, except the title column.
+/// Returns the names of the columns dropped, in table order.
+fn drop_empty_columns(columns: &mut Vec<Column>, rows: &mut [TableRow]) -> Vec<PropertyName> {
+    let kept: HashSet<usize> = (0..columns.len())
+        .filter(|&index| {
+            columns[index].property_type == PropertyType::Title
+                || rows.iter().any(|row| {
+                    row.cells
+                        .get(index)
+                        .is_some_and(|cell| !cell.value.is_empty())
+                })
+        })
+        .collect();
+
+    let empty = columns
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !kept.contains(index))
+        .map(|(_, column)| column.name.clone())
+        .collect();
+    retain_columns(columns, rows, &kept);
+    empty
+}
+
+/// Keeps only the columns at the `kept` indices, and their cells.
+fn retain_columns(columns: &mut Vec<Column>, rows: &mut [TableRow], kept: &HashSet<usize>) {
+    *columns = std::mem::take(columns)
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| kept.contains(index))
+        .map(|(_, column)| column)
+        .collect();
+    for row in rows {
+        row.cells = std::mem::take(&mut row.cells)
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| kept.contains(index))
+            .map(|(_, cell)| cell)
+            .collect();
+    }
+}
+
 // --- Configuration Types ---
 
 /// Configuration for table building.
@@ -224,6 +349,10 @@ struct TableConfig<'a> {
     link_config: Option<LinkConfig<'a>>,
     include_empty_pages: bool,
     max_pages: Option<usize>,
+    max_columns: Option<usize>,
+    exclude_empty_columns: bool,
+    sorts: &'a [RowSort],
+    alignments: &'a [ColumnAlign],
 }
 
 /// Configuration for generating links in table cells.
@@ -298,3 +427,71 @@ impl From<&DatabasePropertyType> for PropertyType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, property_type: PropertyType) -> Column {
+        Column {
+            name: PropertyName::new(name),
+            property_type,
+            alignment: property_type.default_alignment(),
+            width_hint: None,
+        }
+    }
+
+    fn row(values: &[Option<&str>]) -> TableRow {
+        values
+            .iter()
+            .fold(TableRow::new(String::new()), |row, value| {
+                row.with_cell(TableCell::new(match value {
+                    Some(text) => CellValue::Text(text.to_string()),
+                    None => CellValue::Empty,
+                }))
+            })
+    }
+
+    #[test]
+    fn test_column_limit_keeps_title_and_most_filled_columns_in_order() {
+        let mut columns = vec![
+            column("Name", PropertyType::Title),
+            column("Sparse", PropertyType::Text),
+            column("Full", PropertyType::Text),
+            column("Half", PropertyType::Text),
+        ];
+        let mut rows = vec![
+            row(&[Some("a"), None, Some("x"), Some("y")]),
+            row(&[Some("b"), None, Some("x"), None]),
+        ];
+
+        let omitted = keep_most_filled_columns(&mut columns, &mut rows, 2);
+
+        assert_eq!(omitted, 2);
+        let names: Vec<_> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Name", "Full"]);
+        assert!(rows.iter().all(|row| row.cells.len() == 2));
+    }
+
+    #[test]
+    fn test_empty_columns_are_dropped_but_title_is_kept() {
+        let mut columns = vec![
+            column("Name", PropertyType::Title),
+            column("Blank", PropertyType::Text),
+            column("Status", PropertyType::Select),
+            column("Unused", PropertyType::Date),
+        ];
+        let mut rows = vec![
+            row(&[None, None, Some("Done"), None]),
+            row(&[None, None, None, None]),
+        ];
+
+        let empty = drop_empty_columns(&mut columns, &mut rows);
+
+        let empty: Vec<_> = empty.iter().map(PropertyName::as_str).collect();
+        assert_eq!(empty, ["Blank", "Unused"]);
+        let names: Vec<_> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Name", "Status"]);
+        assert!(rows.iter().all(|row| row.cells.len() == 2));
+    }
+}
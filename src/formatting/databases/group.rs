@@ -0,0 +1,151 @@
+// src/formatting/databases/group.rs
+//! Board-style row groups for `--db-group-by`.
+//!
+//! Like Notion's board and grouped views, rows are split by the value of a
+//! select or status property. Groups follow the option order of the schema,
+//! so they stay put between runs however the rows were fetched; options the
+//! schema no longer lists follow in order of first appearance. Rows without
+//! a value come first, as in Notion, and empty groups are left out.
+
+use crate::model::{Database, DatabasePropertyType, Page, PropertyTypeValue};
+use crate::types::{PropertyName, SelectOption};
+
+/// The rows of one group, in fetched order.
+#[derive(Debug)]
+pub struct RowGroup<'a> {
+    /// The option name, or `None` for rows without a value
+    pub option: Option<String>,
+    pub rows: Vec<&'a Page>,
+}
+
+/// `pages` grouped by `property`, or `None` when the database has no select
+/// or status property of that name.
+pub fn group_rows<'a>(
+    database: &Database,
+    pages: &'a [Page],
+    property: &PropertyName,
+) -> Option<Vec<RowGroup<'a>>> {
+    let options = match &database.properties.get(property)?.property_type {
+        DatabasePropertyType::Select { options } | DatabasePropertyType::Status { options } => {
+            options
+        }
+        _ => return None,
+    };
+
+    let mut groups: Vec<RowGroup> = std::iter::once(None)
+        .chain(options.iter().map(|option| Some(option.name.clone())))
+        .map(|option| RowGroup {
+            option,
+            rows: Vec::new(),
+        })
+        .collect();
+    for page in pages {
+        let selected = selected_option(page, property);
+        let position = match selected {
+            None => 0,
+            Some(selected) => match options.iter().position(|o| same_option(o, selected)) {
+                Some(position) => position + 1,
+                None => match groups[options.len() + 1..]
+                    .iter()
+                    .position(|group| group.option.as_deref() == Some(&selected.name))
+                {
+                    Some(position) => options.len() + 1 + position,
+                    None => {
+                        groups.push(RowGroup {
+                            option: Some(selected.name.clone()),
+                            rows: Vec::new(),
+                        });
+                        groups.len() - 1
+                    }
+                },
+            },
+        };
+        groups[position].rows.push(page);
+    }
+    groups.retain(|group| !group.rows.is_empty());
+    Some(groups)
+}
+
+fn same_option(option: &SelectOption, selected: &SelectOption) -> bool {
+    (!option.id.is_empty() && option.id == selected.id) || option.name == selected.name
+}
+
+fn selected_option<'a>(page: &'a Page, property: &PropertyName) -> Option<&'a SelectOption> {
+    match &page.properties.get(property)?.type_specific_value {
+        PropertyTypeValue::Select { select: selected }
+        | PropertyTypeValue::Status { status: selected } => selected.as_ref(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::testing::{test_database, test_page};
+    use crate::model::DatabaseProperty;
+    use crate::types::Color;
+
+    fn option(name: &str) -> SelectOption {
+        SelectOption {
+            id: String::new(),
+            name: name.to_string(),
+            color: Color::Default,
+        }
+    }
+
+    fn row(title: &str, status: Option<&str>) -> Page {
+        test_page(title).with_property(
+            "Status",
+            PropertyTypeValue::Status {
+                status: status.map(option),
+            },
+        )
+    }
+
+    #[test]
+    fn test_rows_group_in_schema_option_order() {
+        let database = test_database("").with_properties(
+            [(
+                PropertyName::new("Status"),
+                DatabaseProperty {
+                    id: PropertyName::new("Status"),
+                    name: PropertyName::new("Status"),
+                    property_type: DatabasePropertyType::Status {
+                        options: vec![option("Not started"), option("In progress"), option("Done")],
+                    },
+                },
+            )]
+            .into_iter()
+            .collect(),
+        );
+        let pages = [
+            row("a", Some("Done")),
+            row("b", Some("Archived")),
+            row("c", None),
+            row("d", Some("Not started")),
+            row("e", Some("Done")),
+        ];
+
+        let groups = group_rows(&database, &pages, &PropertyName::new("Status")).unwrap();
+
+        let summary: Vec<(Option<&str>, Vec<&str>)> = groups
+            .iter()
+            .map(|group| {
+                (
+                    group.option.as_deref(),
+                    group.rows.iter().map(|row| row.title().as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (None, vec!["c"]),
+                (Some("Not started"), vec!["d"]),
+                (Some("Done"), vec!["a", "e"]),
+                (Some("Archived"), vec!["b"]),
+            ]
+        );
+        assert!(group_rows(&database, &pages, &PropertyName::new("Owner")).is_none());
+    }
+}
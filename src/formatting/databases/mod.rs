@@ -5,14 +5,22 @@
 //! separating table structure from rendering concerns.
 
 pub mod builder;
+mod group;
 mod render;
+mod sort;
 mod types;
 
 // Re-export the public interface
 pub use builder::{LinkConfig, RelativeUrlResolver, TableBuilder};
+pub use render::RenderConfig;
+pub(crate) use sort::sort_rows;
+pub use sort::RowSort;
+#[allow(unused_imports)] // Used by the library API
+pub use types::{ColumnAlign, ColumnAlignment};
 
 use crate::error::AppError;
 use crate::model::{Database, Page};
+use crate::types::PropertyName;
 use std::collections::HashSet;
 use std::path::Path;
 
@@ -47,6 +55,7 @@ pub fn format_database_inline(
     database: &Database,
     pages: &[Page],
     parent_indent: &str,
+    render_config: RenderConfig,
 ) -> Result<String, AppError> {
     log::debug!(
         "format_database_inline: Formatting database '{}' with {} pages",
@@ -54,28 +63,42 @@ pub fn format_database_inline(
         pages.len()
     );
 
-    if pages.is_empty() {
+    if pages.is_empty() && database.inaccessible_rows.is_empty() {
         log::debug!("  Database has no pages to format");
         return Ok(format!(
-            "{}🗄️ **{}**\n{}\n*No data available.*\n\n",
+            "{0}🗄️ **{1}**\n\n{0}*{2}*\n\n",
             parent_indent,
             database.title().as_plain_text(),
-            parent_indent
+            render_config.messages.no_data,
         ));
     }
 
-    let table = TableBuilder::new(database, pages)
-        .include_empty_rows(true) // Include pages without blocks for child databases
-        .build()?;
-
-    log::debug!(
-        "  Built table with {} columns and {} rows",
-        table.columns.len(),
-        table.pages.len()
-    );
-
     let indent = format!("{}  ", parent_indent); // Add 2 spaces for nesting
-    let formatted = table.render_indented(&indent);
+    let groups = render_config.group_by.as_ref().and_then(|property| {
+        let groups = group::group_rows(database, pages, property);
+        if groups.is_none() {
+            log::warn!(
+                "Database '{}' has no select or status property '{}' to group by",
+                database.title().as_plain_text(),
+                property
+            );
+        }
+        groups.map(|groups| (property, groups))
+    });
+    let formatted = match groups {
+        Some((property, groups)) => {
+            format_groups(database, property, groups, &indent, &render_config)?
+        }
+        None => {
+            let table = build_table(database, pages, &render_config)?;
+            log::debug!(
+                "  Built table with {} columns and {} rows",
+                table.columns.len(),
+                table.pages.len()
+            );
+            table.render_indented_with(&indent, render_config)
+        }
+    };
 
     // Add database title as header
     let title = database.title().as_plain_text();
@@ -94,6 +117,84 @@ pub fn format_database_inline(
     Ok(final_output)
 }
 
+fn build_table(
+    database: &Database,
+    pages: &[Page],
+    render_config: &RenderConfig,
+) -> Result<types::Table, AppError> {
+    TableBuilder::new(database, pages)
+        .include_empty_rows(true) // Include pages without blocks for child databases
+        .max_columns(render_config.max_columns)
+        .exclude_empty_columns(render_config.exclude_empty_columns)
+        .sort_rows(&render_config.row_sort)
+        .align_columns(&render_config.column_align)
+        .build()
+}
+
+/// One table per group under a heading naming its option, for
+/// `--db-group-by`. Rows without access have no value to group by, so they
+/// are counted once, after the groups.
+fn format_groups(
+    database: &Database,
+    property: &PropertyName,
+    groups: Vec<group::RowGroup>,
+    indent: &str,
+    render_config: &RenderConfig,
+) -> Result<String, AppError> {
+    let mut output = String::new();
+    for group in groups {
+        let pages: Vec<Page> = group.rows.into_iter().cloned().collect();
+        let mut table = build_table(database, &pages, render_config)?;
+        table.metadata.inaccessible_rows = 0;
+        let heading = match group.option {
+            Some(option) => option,
+            None => format!("{} {}", render_config.messages.no_value, property),
+        };
+        output.push_str(&format!("{}#### {}\n\n", indent, heading));
+        output.push_str(
+            table
+                .render_indented_with(indent, render_config.clone())
+                .trim_end(),
+        );
+        output.push_str("\n\n");
+    }
+    if !database.inaccessible_rows.is_empty() {
+        output.push_str(&format!(
+            "{}*{} {}.*\n",
+            indent,
+            database.inaccessible_rows.len(),
+            render_config.messages.rows_without_access
+        ));
+    }
+    Ok(output)
+}
+
+/// Formats a multi-source database inline: one table per data source,
+/// each titled with the source name, under the database title.
+pub fn format_data_sources_inline(
+    database: &Database,
+    parent_indent: &str,
+    render_config: RenderConfig,
+) -> Result<String, AppError> {
+    let mut output = format!(
+        "{}🗄️ **{}**\n\n",
+        parent_indent,
+        database.title().as_plain_text()
+    );
+    let source_indent = format!("{}  ", parent_indent);
+    for source in &database.data_sources {
+        let section = format_database_inline(
+            &source.as_database(database),
+            &source.pages,
+            &source_indent,
+            render_config.clone(),
+        )?;
+        output.push_str(section.trim_end());
+        output.push_str("\n\n");
+    }
+    Ok(output)
+}
+
 // --- Helper Functions ---
 
 /// Identifies rows that should have their own files.
@@ -127,6 +228,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_column_alignment_overrides() {
+        let align: ColumnAlign = "Estimate = Left".parse().unwrap();
+        assert_eq!(align.property.as_str(), "Estimate");
+        assert_eq!(align.alignment, ColumnAlignment::Left);
+        assert_eq!(align.to_string(), "Estimate=left");
+        assert_eq!(
+            "Ratio=a=b=right"
+                .parse::<ColumnAlign>()
+                .unwrap()
+                .to_string(),
+            "Ratio=a=b=right"
+        );
+        assert!("Estimate".parse::<ColumnAlign>().is_err());
+        assert!("Estimate=justify".parse::<ColumnAlign>().is_err());
+        assert!("=right".parse::<ColumnAlign>().is_err());
+    }
+
     #[test]
     fn test_property_type_names() {
         assert_eq!(PropertyType::MultiSelect.display_name(), "Multi-select");
@@ -4,7 +4,11 @@
 //! This module handles the rendering of table structures to Markdown format,
 //! keeping rendering logic separate from data structures.
 
+use super::sort::RowSort;
 use super::types::*;
+use crate::constants::{PAGE_MAX_PROPERTIES, TABLE_MAX_CELL_CHARS};
+use crate::formatting::messages::{Messages, EN};
+use crate::types::PropertyName;
 use std::fmt::Write;
 
 /// Trait for rendering tables to different formats.
@@ -27,7 +31,6 @@ impl MarkdownRenderer {
     }
 
     /// Creates a new Markdown renderer with custom configuration.
-    #[allow(dead_code)]
     pub fn with_config(config: RenderConfig) -> Self {
         Self { config }
     }
@@ -48,12 +51,12 @@ impl TableRenderer for MarkdownRenderer {
         let mut output = String::new();
 
         if table.is_empty() {
-            output.push_str("*No data available.*\n");
+            let _ = writeln!(output, "*{}*", self.config.messages.no_data);
             return output;
         }
 
         if table.columns.is_empty() {
-            output.push_str("*Database has no properties defined.*\n");
+            let _ = writeln!(output, "*{}*", self.config.messages.no_properties);
             return output;
         }
 
@@ -64,11 +67,44 @@ impl TableRenderer for MarkdownRenderer {
         self.render_separator(&mut output, &table.columns);
 
         // Render rows
+        let mut footnotes = Vec::new();
         for row in &table.pages {
-            self.render_row(&mut output, row);
+            self.render_row(&mut output, row, &mut footnotes);
+        }
+        for _ in 0..table.metadata.inaccessible_rows {
+            self.render_inaccessible_row(&mut output, &table.columns);
         }
 
         let _ = writeln!(output);
+        if table.metadata.omitted_columns > 0 {
+            let _ = writeln!(
+                output,
+                "*{} more columns not shown.*\n",
+                table.metadata.omitted_columns
+            );
+        }
+        if table.metadata.inaccessible_rows > 0 {
+            let _ = writeln!(
+                output,
+                "*{} {}.*\n",
+                table.metadata.inaccessible_rows, self.config.messages.rows_without_access
+            );
+        }
+        if !table.metadata.empty_columns.is_empty() {
+            let names: Vec<&str> = table
+                .metadata
+                .empty_columns
+                .iter()
+                .map(|name| name.as_str())
+                .collect();
+            let _ = writeln!(
+                output,
+                "*{}: {}.*\n",
+                self.config.messages.empty_columns_not_shown,
+                names.join(", ")
+            );
+        }
+        self.render_footnotes(&mut output, &footnotes);
         output
     }
 }
@@ -95,39 +131,133 @@ impl MarkdownRenderer {
         let _ = writeln!(output);
     }
 
-    /// Renders a single row.
-    fn render_row(&self, output: &mut String, row: &TableRow) {
+    /// Renders a single row, collecting the full text of truncated cells.
+    fn render_row(&self, output: &mut String, row: &TableRow, footnotes: &mut Vec<CellFootnote>) {
         let _ = write!(output, "| ");
         for (i, cell) in row.cells.iter().enumerate() {
             if i > 0 && self.config.add_spacing {
                 let _ = write!(output, " ");
             }
-            let _ = write!(output, "{} |", self.render_cell(cell));
+            let mut rendered = self.render_cell(cell);
+            if let Some(full_text) = rendered.full_text.take() {
+                if self.config.cell_footnotes {
+                    let label = footnote_label(&row.page_id, i);
+                    let _ = write!(rendered.text, "[^{}]", label);
+                    footnotes.push(CellFootnote { label, full_text });
+                }
+            }
+            let _ = write!(output, "{} |", rendered.text);
+        }
+        let _ = writeln!(output);
+    }
+
+    /// Renders a row the integration may not read: the title column says
+    /// so, the other cells stay empty.
+    fn render_inaccessible_row(&self, output: &mut String, columns: &[Column]) {
+        let marker_column = columns
+            .iter()
+            .position(|col| col.property_type == PropertyType::Title)
+            .unwrap_or(0);
+        let _ = write!(output, "| ");
+        for i in 0..columns.len() {
+            if i > 0 && self.config.add_spacing {
+                let _ = write!(output, " ");
+            }
+            if i == marker_column {
+                let _ = write!(output, "*({})* |", self.config.messages.no_access);
+            } else {
+                let _ = write!(output, " |");
+            }
         }
         let _ = writeln!(output);
     }
 
-    /// Renders a single cell.
-    fn render_cell(&self, cell: &TableCell) -> String {
+    /// Renders a single cell, truncating long text to the configured limit.
+    fn render_cell(&self, cell: &TableCell) -> RenderedCell {
         match &cell.value {
-            CellValue::Text(text) => escape_for_table(text),
+            CellValue::Text(text) => self.render_truncated(text, escape_for_table),
             CellValue::Link { text, url } => {
                 if self.config.render_links {
-                    let link = format!("[{}]({})", text, url);
-                    escape_for_table(&link)
+                    self.render_truncated(text, |shown| {
+                        escape_for_table(&format!("[{}]({})", shown, url))
+                    })
                 } else {
-                    escape_for_table(text)
+                    self.render_truncated(text, escape_for_table)
                 }
             }
-            CellValue::Empty => {
-                if self.config.show_empty_cells {
+            CellValue::Empty => RenderedCell {
+                text: if self.config.show_empty_cells {
                     self.config.empty_cell_text.clone()
                 } else {
                     String::new()
-                }
-            }
+                },
+                full_text: None,
+            },
+        }
+    }
+
+    /// Applies the cell length limit to `text` before handing it to `render`.
+    ///
+    /// Truncation happens on the raw text so escape sequences are never cut
+    /// in half.
+    fn render_truncated(&self, text: &str, render: impl Fn(&str) -> String) -> RenderedCell {
+        match self
+            .config
+            .max_cell_chars
+            .and_then(|max| truncate_cell_text(text, max))
+        {
+            Some(shown) => RenderedCell {
+                text: render(&shown),
+                full_text: Some(text.to_string()),
+            },
+            None => RenderedCell {
+                text: render(text),
+                full_text: None,
+            },
         }
     }
+
+    /// Appends the full content of truncated cells below the table.
+    fn render_footnotes(&self, output: &mut String, footnotes: &[CellFootnote]) {
+        if footnotes.is_empty() {
+            return;
+        }
+        for footnote in footnotes {
+            // Continuation lines of a footnote are indented four spaces.
+            let body = footnote.full_text.trim().replace('\n', "\n    ");
+            let _ = writeln!(output, "[^{}]: {}", footnote.label, body);
+        }
+        let _ = writeln!(output);
+    }
+}
+
+/// A rendered cell, plus the original text when it was truncated.
+struct RenderedCell {
+    text: String,
+    full_text: Option<String>,
+}
+
+/// The full content of a truncated cell, listed below its table.
+struct CellFootnote {
+    label: String,
+    full_text: String,
+}
+
+/// Builds a footnote label that stays unique across every table in a prompt.
+fn footnote_label(row_id: &str, column: usize) -> String {
+    let short_id: String = row_id.chars().filter(|c| *c != '-').take(8).collect();
+    format!("{}-{}", short_id, column + 1)
+}
+
+/// Shortens `text` to at most `max_chars` characters, ending in an ellipsis.
+///
+/// Returns `None` when the text already fits or the limit is zero (disabled).
+fn truncate_cell_text(text: &str, max_chars: usize) -> Option<String> {
+    if max_chars == 0 || text.chars().count() <= max_chars {
+        return None;
+    }
+    let kept: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    Some(format!("{}…", kept.trim_end()))
 }
 
 /// Configuration for table rendering.
@@ -141,6 +271,23 @@ pub struct RenderConfig {
     pub empty_cell_text: String,
     /// Whether to add extra spacing between columns.
     pub add_spacing: bool,
+    /// Character limit for cell content; longer cells end in an ellipsis.
+    pub max_cell_chars: Option<usize>,
+    /// Whether to list the full content of truncated cells below the table.
+    pub cell_footnotes: bool,
+    /// Column limit; the least filled columns beyond it are left out.
+    pub max_columns: Option<usize>,
+    /// Whether to leave out columns without a value in any row.
+    pub exclude_empty_columns: bool,
+    /// Row order applied before rendering; empty keeps the fetched order.
+    pub row_sort: Vec<RowSort>,
+    /// Per-column alignments that replace the property type's default.
+    pub column_align: Vec<ColumnAlign>,
+    /// Select or status property whose options split the rows into
+    /// sections; `None` renders one table.
+    pub group_by: Option<PropertyName>,
+    /// Labels for empty tables, in the language from `--lang`.
+    pub messages: &'static Messages,
 }
 
 impl Default for RenderConfig {
@@ -150,6 +297,14 @@ impl Default for RenderConfig {
             show_empty_cells: false,
             empty_cell_text: "-".to_string(),
             add_spacing: false,
+            max_cell_chars: Some(TABLE_MAX_CELL_CHARS),
+            cell_footnotes: false,
+            max_columns: Some(PAGE_MAX_PROPERTIES),
+            exclude_empty_columns: false,
+            row_sort: Vec::new(),
+            column_align: Vec::new(),
+            group_by: None,
+            messages: &EN,
         }
     }
 }
@@ -172,10 +327,16 @@ impl Table {
     }
 
     /// Renders the table with custom indentation.
+    #[allow(dead_code)]
     pub fn render_indented(&self, indent: &str) -> String {
         let renderer = MarkdownRenderer::new();
         renderer.render_indented(self, indent)
     }
+
+    /// Renders the table with custom indentation and rendering settings.
+    pub fn render_indented_with(&self, indent: &str, config: RenderConfig) -> String {
+        MarkdownRenderer::with_config(config).render_indented(self, indent)
+    }
 }
 
 impl CellValue {
@@ -226,4 +387,87 @@ mod tests {
         let empty = CellValue::Empty;
         assert_eq!(empty.render_escaped(), "");
     }
+
+    fn notes_table(notes: &str) -> Table {
+        let mut table = Table::new();
+        table.columns.push(Column {
+            name: crate::types::PropertyName::new("Notes"),
+            property_type: PropertyType::Text,
+            alignment: ColumnAlignment::Left,
+            width_hint: None,
+        });
+        table.pages.push(
+            TableRow::new("1a2b3c4d-0000-0000-0000-000000000000".to_string())
+                .with_cell(TableCell::new(CellValue::Text(notes.to_string()))),
+        );
+        table
+    }
+
+    #[test]
+    fn test_truncate_cell_text() {
+        assert_eq!(truncate_cell_text("short", 10), None);
+        assert_eq!(truncate_cell_text("exactly10!", 10), None);
+        assert_eq!(
+            truncate_cell_text("a long cell value", 8),
+            Some("a long…".to_string())
+        );
+        assert_eq!(
+            truncate_cell_text("ünïcödé text", 4),
+            Some("ünï…".to_string())
+        );
+        assert_eq!(truncate_cell_text("anything", 0), None);
+    }
+
+    #[test]
+    fn test_long_cells_are_truncated_before_escaping() {
+        let config = RenderConfig {
+            max_cell_chars: Some(6),
+            ..RenderConfig::default()
+        };
+        let rendered = MarkdownRenderer::with_config(config).render(&notes_table("ab|cdefgh"));
+        assert!(rendered.contains("| ab\\|cd… |"));
+        assert!(!rendered.contains("[^"));
+    }
+
+    #[test]
+    fn test_truncated_cells_get_footnotes() {
+        let config = RenderConfig {
+            max_cell_chars: Some(6),
+            cell_footnotes: true,
+            ..RenderConfig::default()
+        };
+        let rendered =
+            MarkdownRenderer::with_config(config).render(&notes_table("first line\nsecond line"));
+        assert!(rendered.contains("| first…[^1a2b3c4d-1] |"));
+        assert!(rendered.ends_with("[^1a2b3c4d-1]: first line\n    second line\n\n"));
+    }
+
+    #[test]
+    fn test_empty_columns_are_noted_under_the_table() {
+        let mut table = notes_table("kept");
+        table.metadata.empty_columns = vec![
+            crate::types::PropertyName::new("Due"),
+            crate::types::PropertyName::new("Owner"),
+        ];
+        let rendered = table.render_markdown();
+        assert!(rendered.ends_with("*Empty columns not shown: Due, Owner.*\n\n"));
+    }
+
+    #[test]
+    fn test_inaccessible_rows_are_marked_and_counted() {
+        let mut table = notes_table("shared");
+        table.columns.push(Column {
+            name: crate::types::PropertyName::new("Due"),
+            property_type: PropertyType::Date,
+            alignment: ColumnAlignment::Left,
+            width_hint: None,
+        });
+        table.metadata.inaccessible_rows = 2;
+        let rendered = table.render_markdown();
+        assert_eq!(rendered.matches("| *(no access)* | |\n").count(), 2);
+        assert!(rendered.ends_with("*2 rows without access.*\n\n"));
+
+        table.pages.clear();
+        assert!(!table.is_empty());
+    }
 }
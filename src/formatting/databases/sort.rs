@@ -0,0 +1,311 @@
+// src/formatting/databases/sort.rs
+//! Compose-time row ordering for `--db-sort`.
+//!
+//! Rows are sorted after fetching, so the order also applies to cached
+//! content and to databases whose query had no API sort. Values compare by
+//! property type: numbers numerically, dates chronologically, selects and
+//! statuses by the option order of the schema, everything else as
+//! case-insensitive text. Empty values sort last in either direction.
+
+use crate::formatting::properties::render_property_value;
+use crate::model::{Database, DatabasePropertyType, Page, PropertyTypeValue};
+use crate::types::{DateValue, FormulaResult, PropertyName, RollupResult, SelectOption};
+use chrono::{NaiveDateTime, NaiveTime};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// One key of a row ordering, e.g. `Due Date:asc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowSort {
+    property: PropertyName,
+    direction: SortDirection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+impl FromStr for RowSort {
+    type Err = String;
+
+    /// Parses `PROPERTY`, `PROPERTY:asc` or `PROPERTY:desc`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (property, direction) = match s.rsplit_once(':') {
+            Some((name, direction)) => match direction.trim().to_lowercase().as_str() {
+                "asc" => (name, SortDirection::Ascending),
+                "desc" => (name, SortDirection::Descending),
+                other => {
+                    return Err(format!(
+                        "'{}' is not a sort direction in '{}'; expected asc or desc",
+                        other, s
+                    ))
+                }
+            },
+            None => (s, SortDirection::Ascending),
+        };
+
+        let property = property.trim();
+        if property.is_empty() {
+            return Err(format!("'{}' does not name a property", s));
+        }
+        Ok(Self {
+            property: PropertyName::new(property),
+            direction,
+        })
+    }
+}
+
+impl fmt::Display for RowSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self.direction {
+            SortDirection::Ascending => "asc",
+            SortDirection::Descending => "desc",
+        };
+        write!(f, "{}:{}", self.property.as_str(), direction)
+    }
+}
+
+/// A property value reduced to what it sorts by.
+#[derive(Debug, Clone, PartialEq)]
+enum SortValue {
+    Number(f64),
+    Date(NaiveDateTime),
+    /// Position in the schema's option list; unknown options follow known
+    /// ones and compare by name.
+    Option(usize, String),
+    Bool(bool),
+    Text(String),
+}
+
+impl SortValue {
+    fn compare(&self, other: &Self) -> Ordering {
+        use SortValue::*;
+        match (self, other) {
+            (Number(a), Number(b)) => a.total_cmp(b),
+            (Date(a), Date(b)) => a.cmp(b),
+            (Option(a, a_name), Option(b, b_name)) => a.cmp(b).then_with(|| a_name.cmp(b_name)),
+            (Bool(a), Bool(b)) => a.cmp(b),
+            (Text(a), Text(b)) => a.cmp(b),
+            // Mixed kinds only arise from formulas and rollups
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            SortValue::Number(_) => 0,
+            SortValue::Date(_) => 1,
+            SortValue::Option(..) => 2,
+            SortValue::Bool(_) => 3,
+            SortValue::Text(_) => 4,
+        }
+    }
+}
+
+/// Sorts `rows` by `sorts`, earlier keys first. The sort is stable, so rows
+/// that tie on every key keep their fetched order.
+pub fn sort_rows(rows: &mut [&Page], database: &Database, sorts: &[RowSort]) {
+    if sorts.is_empty() {
+        return;
+    }
+    let options: Vec<&[SelectOption]> = sorts
+        .iter()
+        .map(|sort| schema_options(database, &sort.property))
+        .collect();
+    let mut keyed: Vec<(Vec<Option<SortValue>>, &Page)> = rows
+        .iter()
+        .map(|row| {
+            let keys = sorts
+                .iter()
+                .zip(&options)
+                .map(|(sort, options)| sort_value(row, &sort.property, options))
+                .collect();
+            (keys, *row)
+        })
+        .collect();
+
+    keyed.sort_by(|(a, _), (b, _)| {
+        sorts
+            .iter()
+            .zip(a.iter().zip(b))
+            .map(|(sort, (a, b))| match (a, b) {
+                (Some(a), Some(b)) => match sort.direction {
+                    SortDirection::Ascending => a.compare(b),
+                    SortDirection::Descending => b.compare(a),
+                },
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    for (slot, (_, row)) in rows.iter_mut().zip(keyed) {
+        *slot = row;
+    }
+}
+
+/// The option list of a select, multi-select or status property.
+fn schema_options<'a>(database: &'a Database, property: &PropertyName) -> &'a [SelectOption] {
+    match database
+        .properties
+        .get(property)
+        .map(|schema| &schema.property_type)
+    {
+        Some(DatabasePropertyType::Select { options })
+        | Some(DatabasePropertyType::MultiSelect { options })
+        | Some(DatabasePropertyType::Status { options }) => options,
+        _ => &[],
+    }
+}
+
+/// What `row` sorts by for `property`, or `None` when the value is empty.
+fn sort_value(row: &Page, property: &PropertyName, options: &[SelectOption]) -> Option<SortValue> {
+    let value = row.properties.get(property)?;
+    let option = |selected: &SelectOption| {
+        let position = options
+            .iter()
+            .position(|o| (!o.id.is_empty() && o.id == selected.id) || o.name == selected.name)
+            .unwrap_or(options.len());
+        SortValue::Option(position, selected.name.to_lowercase())
+    };
+
+    match &value.type_specific_value {
+        PropertyTypeValue::Number { number } => number.map(SortValue::Number),
+        PropertyTypeValue::Date { date } => date.as_ref().map(date_value),
+        PropertyTypeValue::Select { select: selected }
+        | PropertyTypeValue::Status { status: selected } => selected.as_ref().map(option),
+        PropertyTypeValue::MultiSelect { multi_select } => {
+            multi_select.iter().map(option).min_by(|a, b| a.compare(b))
+        }
+        PropertyTypeValue::Checkbox { checkbox } => Some(SortValue::Bool(*checkbox)),
+        PropertyTypeValue::CreatedTime { created_time: time }
+        | PropertyTypeValue::LastEditedTime {
+            last_edited_time: time,
+        } => Some(SortValue::Date(time.naive_utc())),
+        PropertyTypeValue::UniqueID { unique_id } => {
+            Some(SortValue::Number(unique_id.number as f64))
+        }
+        PropertyTypeValue::Formula { formula } => match formula {
+            FormulaResult::Number(number) => Some(SortValue::Number(*number)),
+            FormulaResult::Date(date) => Some(date_value(date)),
+            FormulaResult::Boolean(flag) => Some(SortValue::Bool(*flag)),
+            FormulaResult::String(text) => text_value(text),
+        },
+        PropertyTypeValue::Rollup { rollup } => match rollup {
+            RollupResult::Number { number } => number.map(SortValue::Number),
+            RollupResult::Date { date } => date.as_ref().map(date_value),
+            RollupResult::Boolean { boolean } => boolean.map(SortValue::Bool),
+            RollupResult::String { string } => string.as_deref().and_then(text_value),
+            _ => render_property_value(Some(value))
+                .ok()
+                .as_deref()
+                .and_then(text_value),
+        },
+        _ => render_property_value(Some(value))
+            .ok()
+            .as_deref()
+            .and_then(text_value),
+    }
+}
+
+fn date_value(date: &DateValue) -> SortValue {
+    SortValue::Date(date.start.and_time(NaiveTime::MIN))
+}
+
+fn text_value(text: &str) -> Option<SortValue> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| SortValue::Text(text.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::testing::{test_database, test_page};
+    use crate::model::{DatabaseProperty, PropertyValue};
+    use crate::types::Color;
+
+    #[test]
+    fn test_parse_sort_keys() {
+        let sort: RowSort = "Due Date:desc".parse().unwrap();
+        assert_eq!(sort.to_string(), "Due Date:desc");
+        assert_eq!(
+            "Priority".parse::<RowSort>().unwrap().to_string(),
+            "Priority:asc"
+        );
+        assert_eq!(
+            " Priority : ASC ".parse::<RowSort>().unwrap().to_string(),
+            "Priority:asc"
+        );
+        assert!("Priority:up".parse::<RowSort>().is_err());
+        assert!(":desc".parse::<RowSort>().is_err());
+    }
+
+    #[test]
+    fn test_rows_sort_by_option_order_then_number_with_empty_values_last() {
+        let option = |name: &str| SelectOption {
+            id: String::new(),
+            name: name.to_string(),
+            color: Color::Default,
+        };
+        let row = |title: &str, priority: Option<&str>, estimate: Option<f64>| {
+            let property = |name: &str, value| {
+                (
+                    PropertyName::new(name),
+                    PropertyValue {
+                        id: PropertyName::new(name),
+                        type_specific_value: value,
+                    },
+                )
+            };
+            test_page(title).with_properties(
+                [
+                    property(
+                        "Priority",
+                        PropertyTypeValue::Select {
+                            select: priority.map(option),
+                        },
+                    ),
+                    property("Estimate", PropertyTypeValue::Number { number: estimate }),
+                ]
+                .into_iter()
+                .collect(),
+            )
+        };
+        let database = test_database("").with_properties(
+            [(
+                PropertyName::new("Priority"),
+                DatabaseProperty {
+                    id: PropertyName::new("Priority"),
+                    name: PropertyName::new("Priority"),
+                    property_type: DatabasePropertyType::Select {
+                        options: vec![option("High"), option("Medium"), option("Low")],
+                    },
+                },
+            )]
+            .into_iter()
+            .collect(),
+        );
+        let pages = [
+            row("a", Some("Low"), Some(1.0)),
+            row("b", None, Some(5.0)),
+            row("c", Some("High"), Some(2.0)),
+            row("d", Some("Low"), Some(10.0)),
+            row("e", Some("High"), Some(9.0)),
+        ];
+        let mut rows: Vec<&Page> = pages.iter().collect();
+        let sorts = [
+            "Priority:asc".parse().unwrap(),
+            "Estimate:desc".parse().unwrap(),
+        ];
+
+        sort_rows(&mut rows, &database, &sorts);
+
+        let titles: Vec<_> = rows.iter().map(|row| row.title().as_str()).collect();
+        assert_eq!(titles, ["e", "c", "d", "a", "b"]);
+    }
+}
@@ -6,6 +6,7 @@
 
 use crate::types::PropertyName;
 use std::fmt;
+use std::str::FromStr;
 
 // --- Table Structure ---
 
@@ -49,7 +50,7 @@ impl Table {
 
     /// Checks if the table is empty.
     pub fn is_empty(&self) -> bool {
-        self.columns.is_empty() || self.pages.is_empty()
+        self.columns.is_empty() || (self.pages.is_empty() && self.metadata.inaccessible_rows == 0)
     }
 }
 
@@ -62,6 +63,12 @@ pub struct TableMetadata {
     pub has_links: bool,
     #[allow(dead_code)]
     pub total_cells: usize,
+    /// Columns left out by `TableBuilder::max_columns`
+    pub omitted_columns: usize,
+    /// Columns left out by `TableBuilder::exclude_empty_columns`, in table order
+    pub empty_columns: Vec<PropertyName>,
+    /// Rows the integration may not read, rendered as `*(no access)*` rows
+    pub inaccessible_rows: usize,
 }
 
 /// Represents a table column with metadata.
@@ -152,9 +159,7 @@ impl PropertyType {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ColumnAlignment {
     Left,
-    #[allow(dead_code)]
     Center,
-    #[allow(dead_code)]
     Right,
 }
 
@@ -162,7 +167,7 @@ impl ColumnAlignment {
     /// Converts to Markdown alignment syntax.
     pub fn to_markdown(self) -> &'static str {
         match self {
-            ColumnAlignment::Left => "---",
+            ColumnAlignment::Left => ":---",
             ColumnAlignment::Center => ":---:",
             ColumnAlignment::Right => "---:",
         }
@@ -175,10 +180,64 @@ impl fmt::Display for ColumnAlignment {
     }
 }
 
+impl FromStr for ColumnAlignment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "left" => Ok(ColumnAlignment::Left),
+            "center" | "centre" => Ok(ColumnAlignment::Center),
+            "right" => Ok(ColumnAlignment::Right),
+            other => Err(format!(
+                "'{}' is not an alignment; expected left, center or right",
+                other
+            )),
+        }
+    }
+}
+
+/// A user-chosen alignment for one column, e.g. `Estimate=left`.
+///
+/// Overrides the alignment the column's property type would get.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnAlign {
+    pub property: PropertyName,
+    pub alignment: ColumnAlignment,
+}
+
+impl FromStr for ColumnAlign {
+    type Err = String;
+
+    /// Parses `PROPERTY=left`, `PROPERTY=center` or `PROPERTY=right`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (property, alignment) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format!("'{}' is not PROPERTY=ALIGNMENT", s))?;
+        let property = property.trim();
+        if property.is_empty() {
+            return Err(format!("'{}' does not name a property", s));
+        }
+        Ok(Self {
+            property: PropertyName::new(property),
+            alignment: alignment.parse()?,
+        })
+    }
+}
+
+impl fmt::Display for ColumnAlign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let alignment = match self.alignment {
+            ColumnAlignment::Left => "left",
+            ColumnAlignment::Center => "center",
+            ColumnAlignment::Right => "right",
+        };
+        write!(f, "{}={}", self.property.as_str(), alignment)
+    }
+}
+
 /// Represents a single row in the table.
 #[derive(Debug, Clone)]
 pub struct TableRow {
-    #[allow(dead_code)]
     pub page_id: String,
     pub cells: Vec<TableCell>,
 }
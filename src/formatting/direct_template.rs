@@ -4,14 +4,22 @@
 //! This module renders templates directly from NotionObject data,
 //! bypassing file intermediates to preserve child database content.
 
-use crate::config::PipelineConfig;
+use crate::config::{LinkStyle, PipelineConfig};
 use crate::error::AppError;
 use crate::formatting::block_renderer::RenderContext;
+use crate::formatting::link_index::style_links;
+use crate::formatting::markdown_check::check_markdown;
+use crate::formatting::messages::Messages;
+use crate::formatting::people::append_people_glossary;
+use crate::formatting::prepared_content;
+use crate::formatting::template_partials::register_template_tree;
+use crate::formatting::whitespace::normalize_whitespace;
 use crate::model::{Database, NotionObject, Page};
 use crate::output::create_clean_filename;
 use crate::types::RenderedPrompt;
 use handlebars::Handlebars;
 use serde_json::json;
+use std::io::Write;
 
 /// Template data structure for direct rendering.
 #[allow(dead_code)] // Used by bin target via compose_prompt
@@ -42,7 +50,8 @@ pub fn render_prompt(
 
     let template_engine = load_template(config)?;
     let content = gather_renderable_content(notion_object, config)?;
-    let prompt = render_with_template(&template_engine, &content, config)?;
+    let mut prompt = render_with_template(&template_engine, &content, config)?;
+    config.plugins.finalize(&mut prompt);
 
     log::info!(
         "Prompt composed: {} bytes from {} files",
@@ -53,25 +62,82 @@ pub fn render_prompt(
     Ok(RenderedPrompt::new(prompt))
 }
 
-// --- render_prompt helpers ---
+/// Renders a NotionObject through the template straight into `writer`.
+///
+/// The template output is never held as one `String`, which saves one copy
+/// of very large prompts written to a file. Memory use is not flat: the
+/// rendered pages and databases the template lays out are still built in
+/// memory first. Returns the number of bytes written. With compose plugins
+/// registered the prompt is buffered after all, since their `finalize` hook
+/// needs it whole.
+#[allow(dead_code)] // Used by bin target (main.rs)
+pub fn render_prompt_to(
+    notion_object: &NotionObject,
+    config: &PipelineConfig,
+    writer: &mut dyn Write,
+) -> Result<usize, AppError> {
+    if !config.plugins.is_empty() {
+        let prompt = render_prompt(notion_object, config)?.into_string();
+        writer.write_all(prompt.as_bytes())?;
+        return Ok(prompt.len());
+    }
+    log::info!("Composing prompt via streaming template rendering");
 
-/// Loads and registers a Handlebars template from the configured path.
-fn load_template(config: &PipelineConfig) -> Result<Handlebars<'static>, AppError> {
-    let template_path = config.get_template_path();
-    let template_content =
-        std::fs::read_to_string(&template_path).map_err(|e| AppError::TemplateNotFound {
-            path: template_path.display().to_string(),
-            source: e,
-        })?;
+    let template_engine = load_template(config)?;
+    let content = gather_renderable_content(notion_object, config)?;
 
-    let mut handlebars = Handlebars::new();
-    handlebars
-        .register_template_string(config.template.as_str(), &template_content)
+    let mut counting = CountingWriter {
+        inner: writer,
+        bytes: 0,
+    };
+    template_engine
+        .render_to_write(
+            config.template.as_str(),
+            &template_data(&content),
+            &mut counting,
+        )
         .map_err(|e| AppError::TemplateRenderError {
             name: config.template.to_string(),
             message: e.to_string(),
         })?;
 
+    log::info!(
+        "Prompt streamed: {} bytes from {} files",
+        counting.bytes,
+        content.files.len()
+    );
+
+    Ok(counting.bytes)
+}
+
+/// Forwards writes while counting the bytes that pass through.
+struct CountingWriter<'w> {
+    inner: &'w mut dyn Write,
+    bytes: usize,
+}
+
+impl Write for CountingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// --- render_prompt helpers ---
+
+/// Loads and registers a Handlebars template, plus any partials it references,
+/// from the configured path.
+fn load_template(config: &PipelineConfig) -> Result<Handlebars<'static>, AppError> {
+    let template_path = config.get_template_path();
+
+    let mut handlebars = Handlebars::new();
+    register_template_tree(&mut handlebars, config.template.as_str(), &template_path)?;
+
     Ok(handlebars)
 }
 
@@ -80,6 +146,8 @@ fn gather_renderable_content(
     notion_object: &NotionObject,
     config: &PipelineConfig,
 ) -> Result<PromptContent, AppError> {
+    let notion_object = prepared_content(notion_object, config);
+    let notion_object = notion_object.as_ref();
     let databases = crate::formatting::gather_embedded_databases(notion_object);
 
     log::debug!("Template data: {} databases available", databases.len());
@@ -90,7 +158,32 @@ fn gather_renderable_content(
     };
 
     let mut files = Vec::new();
-    let main_content = collect_rendered_files(notion_object, &mut files, &render_config)?;
+    let mut main_content = collect_rendered_files(notion_object, &mut files, &render_config)?;
+    if config.normalize_whitespace {
+        main_content = normalize_whitespace(&main_content);
+        for file in &mut files {
+            file.code = normalize_whitespace(&file.code);
+        }
+    }
+    if config.check_markdown {
+        for file in &mut files {
+            file.code = checked_markdown(&file.code, &file.path);
+        }
+        // The main content is the root's file, whose issues are reported above
+        main_content = check_markdown(&main_content).markdown;
+    }
+    if config.people_glossary {
+        append_people_glossary(
+            notion_object,
+            Messages::for_language(config.lang),
+            &mut main_content,
+            files.first_mut().map(|file| &mut file.code),
+        );
+    }
+    if config.link_style != LinkStyle::Inline {
+        let mut documents: Vec<&mut String> = files.iter_mut().map(|file| &mut file.code).collect();
+        style_links(config.link_style, &mut main_content, &mut documents);
+    }
 
     let source_tree = build_source_tree(&files);
 
@@ -103,6 +196,23 @@ fn gather_renderable_content(
     })
 }
 
+/// Runs the markdown check on one rendered file and logs what it found.
+fn checked_markdown(markdown: &str, path: &str) -> String {
+    let check = check_markdown(markdown);
+    for issue in &check.issues {
+        if issue.is_fixed() {
+            log::info!("Markdown check: {} {}", path, issue);
+        } else {
+            log::warn!("Markdown check: {} {}", path, issue);
+        }
+    }
+    let fixed = check.issues.iter().filter(|issue| issue.is_fixed()).count();
+    if fixed > 0 {
+        log::warn!("Markdown check: fixed {} problems in {}", fixed, path);
+    }
+    check.markdown
+}
+
 /// Renders the final prompt by applying the template engine to the prepared content.
 fn render_with_template(
     handlebars: &Handlebars,
@@ -111,7 +221,17 @@ fn render_with_template(
 ) -> Result<String, AppError> {
     let template_name = config.template.as_str();
 
-    let json_data = json!({
+    handlebars
+        .render(template_name, &template_data(data))
+        .map_err(|e| AppError::TemplateRenderError {
+            name: config.template.to_string(),
+            message: e.to_string(),
+        })
+}
+
+/// Builds the JSON context every prompt template is rendered against.
+fn template_data(data: &PromptContent) -> serde_json::Value {
+    json!({
         "absolute_content_path": data.absolute_content_path,
         "source_tree": data.source_tree,
         "files": data.files.iter().map(|f| json!({
@@ -120,14 +240,7 @@ fn render_with_template(
         })).collect::<Vec<_>>(),
         "main_content": data.main_content,
         "instructions": data.instructions
-    });
-
-    handlebars
-        .render(template_name, &json_data)
-        .map_err(|e| AppError::TemplateRenderError {
-            name: config.template.to_string(),
-            message: e.to_string(),
-        })
+    })
 }
 
 // --- Content rendering ---
@@ -151,7 +264,8 @@ fn collect_rendered_files(
             Ok(content)
         }
         NotionObject::Database(db) => {
-            let content = render_database_content(db, render_config)?;
+            let content = render_config.plugins().before_database(db)
+                + &render_database_content(db, render_config)?;
             let title = db.title().as_plain_text();
             let filename = clean_filename(&title, db.id.as_str());
 
@@ -160,7 +274,7 @@ fn collect_rendered_files(
                 code: content.clone(),
             });
 
-            for page in &db.pages {
+            for page in db.rows() {
                 let page_obj = NotionObject::Page(page.clone());
                 collect_rendered_files(&page_obj, files, render_config)?;
             }
@@ -187,9 +301,9 @@ fn render_page_content(page: &Page, render_config: &RenderContext) -> Result<Str
 
 fn render_database_content(
     db: &Database,
-    _render_config: &RenderContext,
+    render_config: &RenderContext,
 ) -> Result<String, AppError> {
-    crate::formatting::block_renderer::compose_database_summary(db)
+    crate::formatting::block_renderer::compose_database_summary(db, render_config)
 }
 
 fn render_block_content(
@@ -214,3 +328,168 @@ fn build_source_tree(files: &[RenderedDocument]) -> String {
     }
     tree
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatting::plugins::ComposePlugin;
+    use crate::model::{ParagraphBlock, TextBlockContent};
+    use crate::types::{BlockId, Color, PageId, RichTextItem, TemplateName};
+    use std::sync::Arc;
+
+    fn sample_page() -> NotionObject {
+        NotionObject::Page(
+            Page::new(
+                PageId::parse("12345678123456781234567812345678").unwrap(),
+                "Streaming",
+            )
+            .with_blocks(vec![crate::model::Block::Paragraph(ParagraphBlock {
+                common: crate::model::BlockCommon {
+                    id: BlockId::new_v4(),
+                    children: vec![],
+                    has_children: false,
+                    archived: false,
+                    last_edited_time: None,
+                    unfetched_children: None,
+                },
+                content: TextBlockContent {
+                    rich_text: vec![RichTextItem::plain_text("Body text")],
+                    color: Color::Default,
+                },
+            })]),
+        )
+    }
+
+    #[test]
+    fn test_streamed_prompt_matches_string_prompt() {
+        let config = PipelineConfig {
+            template: TemplateName::new("claude-xml").unwrap(),
+            ..Default::default()
+        };
+        let page = sample_page();
+
+        let rendered = render_prompt(&page, &config).unwrap().into_string();
+        let mut streamed = Vec::new();
+        let bytes = render_prompt_to(&page, &config, &mut streamed).unwrap();
+
+        assert_eq!(String::from_utf8(streamed).unwrap(), rendered);
+        assert_eq!(bytes, rendered.len());
+    }
+
+    /// Numbers top-level blocks and records the order the hooks ran in.
+    #[derive(Default)]
+    struct Numbering {
+        calls: std::sync::Mutex<Vec<&'static str>>,
+    }
+
+    impl ComposePlugin for Arc<Numbering> {
+        fn before_page(&self, page: &Page, out: &mut String) {
+            self.calls.lock().unwrap().push("before_page");
+            out.push_str(&format!("<!-- page {} -->\n", page.id.as_str()));
+        }
+
+        fn after_block(&self, _block: &crate::model::Block, out: &mut String) {
+            self.calls.lock().unwrap().push("after_block");
+            out.insert_str(0, "1. ");
+        }
+
+        fn finalize(&self, prompt: &mut String) {
+            self.calls.lock().unwrap().push("finalize");
+            prompt.push_str("\n-- end --\n");
+        }
+    }
+
+    #[test]
+    fn test_plugins_hook_into_composition_in_order() {
+        let numbering = Arc::new(Numbering::default());
+        let config = PipelineConfig {
+            template: TemplateName::new("claude-xml").unwrap(),
+            ..Default::default()
+        }
+        .with_plugin(Arc::clone(&numbering));
+
+        let prompt = render_prompt(&sample_page(), &config)
+            .unwrap()
+            .into_string();
+        let mut streamed = Vec::new();
+        render_prompt_to(&sample_page(), &config, &mut streamed).unwrap();
+
+        assert!(prompt.contains("<!-- page 12345678123456781234567812345678 -->\n# Streaming"));
+        assert!(prompt.contains("1. Body text"));
+        assert!(prompt.ends_with("\n-- end --\n"));
+        assert_eq!(String::from_utf8(streamed).unwrap(), prompt);
+        assert_eq!(
+            numbering.calls.lock().unwrap()[..3],
+            ["before_page", "after_block", "finalize"]
+        );
+    }
+
+    #[test]
+    fn test_german_prompt_has_no_english_labels() {
+        use crate::config::Language;
+        use crate::model::{PropertyTypeValue, PropertyValue};
+        use crate::types::{DatabaseId, PropertyName};
+
+        let text = |name: &str, value: &str| {
+            let value = PropertyValue {
+                id: PropertyName::new(name),
+                type_specific_value: PropertyTypeValue::RichText {
+                    rich_text: vec![RichTextItem::plain_text(value)],
+                },
+            };
+            (PropertyName::new(name), value)
+        };
+        let NotionObject::Page(page) = sample_page() else {
+            unreachable!()
+        };
+        let page =
+            page.with_properties([text("Notes", "A long note"), text("Owner", "Ada")].into());
+        let database = Database::new(
+            DatabaseId::parse("87654321876543218765432187654321").unwrap(),
+            "Roadmap",
+        );
+        let config = PipelineConfig {
+            lang: Language::De,
+            max_properties: 1,
+            max_property_chars: 4,
+            ..Default::default()
+        };
+
+        let english = [
+            "## Metadata",
+            "Page ID",
+            "## Schema",
+            "## Data",
+            "Database contains",
+            "Database has no rows",
+            "Database ID",
+            "more properties not shown",
+            "truncated",
+        ];
+        let prompts: String = [
+            NotionObject::Page(page),
+            NotionObject::Database(database.clone()),
+            NotionObject::Database(
+                database.with_pages(vec![Page::new(PageId::new_v4(), "Launch")]),
+            ),
+        ]
+        .iter()
+        .map(|content| render_prompt(content, &config).unwrap().into_string())
+        .collect();
+
+        for label in english {
+            assert!(!prompts.contains(label), "{label} in:\n{prompts}");
+        }
+        for label in [
+            "## Metadaten",
+            "Seiten-ID",
+            "Datenbank-ID",
+            "Die Datenbank hat keine Zeilen.",
+            "Die Datenbank enthält 1 Seiten.",
+            "weitere Eigenschaften ausgeblendet",
+            "gekürzt, 11 Zeichen insgesamt",
+        ] {
+            assert!(prompts.contains(label), "{label} missing from:\n{prompts}");
+        }
+    }
+}
@@ -0,0 +1,191 @@
+// src/formatting/edited_since.rs
+//! Compose-time filter for `--since`: a digest of what changed on a page.
+//!
+//! Only blocks edited on or after the given date are rendered. Their
+//! ancestors stay for context, as do the headings they sit under: in Notion
+//! a heading is a sibling of the blocks that follow it, so for each kept
+//! block the latest heading of each level before it is kept too. Blocks the
+//! API gave no edit time for are kept.
+
+use crate::config::PipelineConfig;
+use crate::model::blocks::ChildDatabaseContent;
+use crate::model::{Block, Database, NotionObject};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::borrow::Cow;
+
+/// `object` with only the blocks edited since `--since`, passed through
+/// unchanged when the option is not given.
+pub fn edited_since<'a>(
+    object: Cow<'a, NotionObject>,
+    config: &PipelineConfig,
+) -> Cow<'a, NotionObject> {
+    let Some(since) = config.since else {
+        return object;
+    };
+    let since = start_of(since);
+    let mut object = object.into_owned();
+    match &mut object {
+        NotionObject::Page(page) => {
+            keep_edited(&mut page.blocks, since);
+        }
+        NotionObject::Database(db) => {
+            keep_edited_in_database(db, since);
+        }
+        NotionObject::Block(block) => {
+            keep_edited(block.children_mut(), since);
+        }
+    }
+    Cow::Owned(object)
+}
+
+fn start_of(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc()
+}
+
+/// Drops the blocks in `blocks` that were not edited since `since`, keeping
+/// the ancestors and headings of those that were. Returns whether any
+/// block was kept.
+fn keep_edited(blocks: &mut Vec<Block>, since: DateTime<Utc>) -> bool {
+    let mut keep = vec![false; blocks.len()];
+    // The latest heading of each level, cleared when a higher one starts
+    let mut headings: [Option<usize>; 3] = [None; 3];
+
+    for (index, block) in blocks.iter_mut().enumerate() {
+        if let Some(level) = heading_level(block) {
+            headings[level - 1] = Some(index);
+            headings[level..].fill(None);
+        }
+        let edited = block
+            .common()
+            .last_edited_time
+            .is_none_or(|time| time >= since);
+        let mut has_edited_children = keep_edited(block.children_mut(), since);
+        if let Block::ChildDatabase(child) = block {
+            if let ChildDatabaseContent::Fetched(db) = &mut child.content {
+                has_edited_children |= keep_edited_in_database(db, since);
+            }
+        }
+        if edited || has_edited_children {
+            keep[index] = true;
+            for heading in headings.iter().flatten() {
+                keep[*heading] = true;
+            }
+        }
+    }
+
+    let kept = keep.iter().any(|k| *k);
+    let mut keep = keep.into_iter();
+    blocks.retain(|_| keep.next().unwrap_or(false));
+    kept
+}
+
+/// Filters the blocks of every row; returns whether any block was kept.
+/// Rows themselves are all kept, so the table stays whole.
+fn keep_edited_in_database(db: &mut Database, since: DateTime<Utc>) -> bool {
+    let mut kept = false;
+    for row in &mut db.pages {
+        kept |= keep_edited(&mut row.blocks, since);
+    }
+    for source in &mut db.data_sources {
+        for row in &mut source.pages {
+            kept |= keep_edited(&mut row.blocks, since);
+        }
+    }
+    kept
+}
+
+fn heading_level(block: &Block) -> Option<usize> {
+    match block {
+        Block::Heading1(_) => Some(1),
+        Block::Heading2(_) => Some(2),
+        Block::Heading3(_) => Some(3),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        BlockCommon, Heading1Block, Heading2Block, ParagraphBlock, TextBlockContent,
+    };
+    use crate::types::{BlockId, Color, RichTextItem};
+
+    fn common(edited: &str) -> BlockCommon {
+        BlockCommon {
+            last_edited_time: Some(edited.parse().unwrap()),
+            ..BlockCommon::new(BlockId::new_v4())
+        }
+    }
+
+    fn content(text: &str) -> TextBlockContent {
+        TextBlockContent {
+            rich_text: vec![RichTextItem::plain_text(text)],
+            color: Color::Default,
+        }
+    }
+
+    fn paragraph(text: &str, edited: &str) -> Block {
+        Block::Paragraph(ParagraphBlock {
+            common: common(edited),
+            content: content(text),
+        })
+    }
+
+    fn text_of(block: &Block) -> String {
+        let content = match block {
+            Block::Paragraph(b) => &b.content,
+            Block::Heading1(b) => &b.content,
+            Block::Heading2(b) => &b.content,
+            _ => panic!("unexpected block"),
+        };
+        content.rich_text[0].plain_text.clone()
+    }
+
+    #[test]
+    fn test_edited_blocks_keep_their_headings() {
+        const OLD: &str = "2024-01-10T09:00:00Z";
+        const NEW: &str = "2024-06-02T09:00:00Z";
+        let mut blocks = vec![
+            Block::Heading1(Heading1Block {
+                common: common(OLD),
+                content: content("Plan"),
+            }),
+            Block::Heading2(Heading2Block {
+                common: common(OLD),
+                content: content("Old section"),
+            }),
+            paragraph("unchanged", OLD),
+            Block::Heading2(Heading2Block {
+                common: common(OLD),
+                content: content("Budget"),
+            }),
+            paragraph("before the edit", OLD),
+            paragraph("edited", NEW),
+            paragraph("after the edit", OLD),
+        ];
+
+        let since = start_of(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap());
+        assert!(keep_edited(&mut blocks, since));
+        let texts: Vec<String> = blocks.iter().map(text_of).collect();
+        assert_eq!(texts, ["Plan", "Budget", "edited"]);
+    }
+
+    #[test]
+    fn test_parents_of_edited_blocks_are_kept() {
+        let mut parent = paragraph("parent", "2024-01-10T09:00:00Z");
+        parent.set_children(vec![
+            paragraph("old child", "2024-01-10T09:00:00Z"),
+            paragraph("new child", "2024-06-02T09:00:00Z"),
+        ]);
+        let mut blocks = vec![parent, paragraph("old", "2024-01-10T09:00:00Z")];
+
+        let since = start_of(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap());
+        keep_edited(&mut blocks, since);
+        assert_eq!(blocks.len(), 1);
+        let children: Vec<String> = blocks[0].children().iter().map(text_of).collect();
+        assert_eq!(children, ["new child"]);
+    }
+}
@@ -0,0 +1,187 @@
+// src/formatting/embeds.rs
+//! Provider-aware rendering for embed blocks.
+//!
+//! A bare `[Embed: url]` tells a reader nothing about what sits behind the
+//! link. Embeds from well-known providers render with the provider name,
+//! the block caption as a title, and a thumbnail link where the provider
+//! exposes one at a predictable address. Unknown hosts keep the plain form.
+
+use super::messages::Messages;
+use super::rich_text::rich_text_to_markdown;
+use crate::error::AppError;
+use crate::model::EmbedBlock;
+use url::Url;
+
+/// A known embed host and how to describe its content.
+struct EmbedProvider {
+    name: &'static str,
+    /// Hosts matched exactly or as a parent domain (`www.youtube.com`).
+    hosts: &'static [&'static str],
+    thumbnail: Option<fn(&Url) -> Option<String>>,
+}
+
+/// Providers recognised by [`render_embed`], checked in order.
+static EMBED_PROVIDERS: &[EmbedProvider] = &[
+    EmbedProvider {
+        name: "YouTube",
+        hosts: &["youtube.com", "youtu.be", "youtube-nocookie.com"],
+        thumbnail: Some(youtube_thumbnail),
+    },
+    EmbedProvider {
+        name: "Figma",
+        hosts: &["figma.com"],
+        thumbnail: None,
+    },
+    EmbedProvider {
+        name: "Miro",
+        hosts: &["miro.com"],
+        thumbnail: None,
+    },
+    EmbedProvider {
+        name: "Loom",
+        hosts: &["loom.com"],
+        thumbnail: None,
+    },
+    EmbedProvider {
+        name: "Twitter",
+        hosts: &["twitter.com", "x.com"],
+        thumbnail: None,
+    },
+];
+
+/// Renders an embed block, using the provider table when the host is known.
+pub fn render_embed(embed: &EmbedBlock, messages: &Messages) -> Result<String, AppError> {
+    let title = if embed.caption.is_empty() {
+        None
+    } else {
+        Some(rich_text_to_markdown(&embed.caption)?)
+    };
+
+    let parsed = Url::parse(&embed.url).ok();
+    let provider = parsed.as_ref().and_then(detect_provider);
+
+    let Some(provider) = provider else {
+        return Ok(match title {
+            Some(title) => format!("[{}: {} - {}]\n", messages.embed, embed.url, title),
+            None => format!("[{}: {}]\n", messages.embed, embed.url),
+        });
+    };
+
+    let mut out = match title {
+        Some(title) => format!(
+            "[{} {}: {}]({})",
+            provider.name, messages.provider_embed, title, embed.url
+        ),
+        None => format!(
+            "[{} {}]({})",
+            provider.name, messages.provider_embed, embed.url
+        ),
+    };
+    let thumbnail = provider
+        .thumbnail
+        .zip(parsed.as_ref())
+        .and_then(|(thumbnail, url)| thumbnail(url));
+    if let Some(thumbnail) = thumbnail {
+        out.push_str(&format!(" ([thumbnail]({}))", thumbnail));
+    }
+    out.push('\n');
+    Ok(out)
+}
+
+/// Finds the provider whose host list covers the URL's host.
+fn detect_provider(url: &Url) -> Option<&'static EmbedProvider> {
+    let host = url.host_str()?.to_ascii_lowercase();
+    EMBED_PROVIDERS.iter().find(|provider| {
+        provider
+            .hosts
+            .iter()
+            .any(|h| host == *h || host.ends_with(&format!(".{}", h)))
+    })
+}
+
+/// YouTube serves a still for every video at a fixed address.
+fn youtube_thumbnail(url: &Url) -> Option<String> {
+    let video_id = if url.host_str()?.ends_with("youtu.be") {
+        url.path_segments()?.next().map(str::to_string)
+    } else {
+        url.query_pairs()
+            .find(|(key, _)| key == "v")
+            .map(|(_, value)| value.into_owned())
+            .or_else(|| {
+                let mut segments = url.path_segments()?;
+                match segments.next()? {
+                    "embed" | "shorts" | "live" => segments.next().map(str::to_string),
+                    _ => None,
+                }
+            })
+    }?;
+
+    (!video_id.is_empty()).then(|| format!("https://img.youtube.com/vi/{}/hqdefault.jpg", video_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatting::messages::EN;
+    use crate::model::BlockCommon;
+    use crate::types::{BlockId, RichTextItem};
+
+    fn embed(url: &str, caption: &str) -> EmbedBlock {
+        EmbedBlock {
+            common: BlockCommon {
+                id: BlockId::new_v4(),
+                children: vec![],
+                has_children: false,
+                archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
+            },
+            url: url.to_string(),
+            caption: if caption.is_empty() {
+                vec![]
+            } else {
+                vec![RichTextItem::plain_text(caption)]
+            },
+        }
+    }
+
+    #[test]
+    fn test_youtube_embed_has_title_and_thumbnail() {
+        let out = render_embed(
+            &embed("https://www.youtube.com/watch?v=abc123", "Demo"),
+            &EN,
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "[YouTube embed: Demo](https://www.youtube.com/watch?v=abc123) \
+             ([thumbnail](https://img.youtube.com/vi/abc123/hqdefault.jpg))\n"
+        );
+
+        let short = render_embed(&embed("https://youtu.be/xyz", ""), &EN).unwrap();
+        assert!(short.contains("https://img.youtube.com/vi/xyz/hqdefault.jpg"));
+    }
+
+    #[test]
+    fn test_known_providers_without_thumbnails() {
+        assert_eq!(
+            render_embed(&embed("https://www.figma.com/file/k3y/Design", ""), &EN).unwrap(),
+            "[Figma embed](https://www.figma.com/file/k3y/Design)\n"
+        );
+        assert!(render_embed(&embed("https://x.com/user/status/1", ""), &EN)
+            .unwrap()
+            .starts_with("[Twitter embed]"));
+    }
+
+    #[test]
+    fn test_unknown_host_keeps_plain_placeholder() {
+        assert_eq!(
+            render_embed(&embed("https://example.com/widget", ""), &EN).unwrap(),
+            "[Embed: https://example.com/widget]\n"
+        );
+        assert_eq!(
+            render_embed(&embed("https://notyoutube.com/x", ""), &EN).unwrap(),
+            "[Embed: https://notyoutube.com/x]\n"
+        );
+    }
+}
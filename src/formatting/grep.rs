@@ -0,0 +1,337 @@
+// src/formatting/grep.rs
+//! Compose-time filter for `--grep`: a focused prompt from a large page.
+//!
+//! Only blocks whose text matches the pattern are rendered, with
+//! `--grep-context` sibling blocks on either side of each. Matching and
+//! context blocks are kept whole, children included. As with `--since`,
+//! the ancestors of kept blocks stay, as do the headings they sit under.
+//!
+//! A table is kept whole when one of its rows matches, and a child
+//! database when its title does; otherwise its rows stay (the table is
+//! whole) and only their matching content is rendered. What matched is
+//! set in bold.
+
+use super::rules::rich_text_mut;
+use crate::config::PipelineConfig;
+use crate::model::blocks::ChildDatabaseContent;
+use crate::model::{Block, Database, NotionObject, Page};
+use crate::types::{RichTextItem, RichTextType};
+use regex::Regex;
+use std::borrow::Cow;
+
+/// `object` with only the blocks matching `--grep` and their context,
+/// passed through unchanged when the option is not given.
+pub fn matching_blocks<'a>(
+    object: Cow<'a, NotionObject>,
+    config: &PipelineConfig,
+) -> Cow<'a, NotionObject> {
+    let Some(pattern) = &config.grep else {
+        return object;
+    };
+    let search = Search {
+        pattern,
+        context: config.grep_context,
+    };
+    let mut object = object.into_owned();
+    let matched = match &mut object {
+        NotionObject::Page(page) => search.keep_matching(&mut page.blocks),
+        NotionObject::Database(db) => search.keep_matching_in_database(db),
+        NotionObject::Block(block) => search.keep_matching(block.children_mut()),
+    };
+    if matched == 0 {
+        log::warn!("--grep '{}' matched no blocks", pattern);
+    } else {
+        log::info!("--grep '{}' matched {} blocks", pattern, matched);
+    }
+    match &mut object {
+        NotionObject::Page(page) => highlight(&mut page.blocks, pattern),
+        NotionObject::Database(db) => highlight_in_database(db, pattern),
+        NotionObject::Block(block) => highlight(std::slice::from_mut(block), pattern),
+    }
+    Cow::Owned(object)
+}
+
+struct Search<'p> {
+    pattern: &'p Regex,
+    /// Sibling blocks kept on either side of a match
+    context: usize,
+}
+
+impl Search<'_> {
+    /// Drops the blocks in `blocks` that neither match, surround a match nor
+    /// hold one, keeping the headings of those that stay. Returns how many
+    /// blocks matched.
+    fn keep_matching(&self, blocks: &mut Vec<Block>) -> usize {
+        let mut matched = 0;
+        // Kept with everything below them
+        let len = blocks.len();
+        let mut whole = vec![false; len];
+        for (index, block) in blocks.iter_mut().enumerate() {
+            if self.matches(block) {
+                matched += 1;
+                let end = (index + self.context + 1).min(len);
+                whole[index.saturating_sub(self.context)..end].fill(true);
+            }
+        }
+
+        let mut keep = whole.clone();
+        for (index, block) in blocks.iter_mut().enumerate() {
+            if whole[index] {
+                continue;
+            }
+            let mut within = self.keep_matching(block.children_mut());
+            if let Block::ChildDatabase(child) = block {
+                if let ChildDatabaseContent::Fetched(db) = &mut child.content {
+                    within += self.keep_matching_in_database(db);
+                }
+            }
+            matched += within;
+            keep[index] = within > 0;
+        }
+
+        // The latest heading of each level, cleared when a higher one starts
+        let mut headings: [Option<usize>; 3] = [None; 3];
+        for index in 0..blocks.len() {
+            if let Some(level) = heading_level(&blocks[index]) {
+                headings[level - 1] = Some(index);
+                headings[level..].fill(None);
+            }
+            if keep[index] {
+                for heading in headings.iter().flatten() {
+                    keep[*heading] = true;
+                }
+            }
+        }
+
+        let mut keep = keep.into_iter();
+        blocks.retain(|_| keep.next().unwrap_or(false));
+        matched
+    }
+
+    /// Filters the blocks of every row; returns how many rows and blocks
+    /// matched. Rows themselves are all kept, so the table stays whole, and
+    /// a row whose title matches keeps all its content.
+    fn keep_matching_in_database(&self, db: &mut Database) -> usize {
+        let rows = db
+            .pages
+            .iter_mut()
+            .chain(db.data_sources.iter_mut().flat_map(|s| &mut s.pages));
+        rows.map(|row| self.keep_matching_in_row(row)).sum()
+    }
+
+    fn keep_matching_in_row(&self, row: &mut Page) -> usize {
+        if self.pattern.is_match(row.title().as_str()) {
+            1
+        } else {
+            self.keep_matching(&mut row.blocks)
+        }
+    }
+
+    /// Whether the text of `block` itself matches; for a table, the text of
+    /// any of its rows.
+    fn matches(&self, block: &mut Block) -> bool {
+        match block {
+            Block::Table(_) => block.children_mut().iter_mut().any(|row| self.matches(row)),
+            Block::ChildPage(b) => self.pattern.is_match(&b.title),
+            Block::ChildDatabase(b) => self.pattern.is_match(&b.title),
+            Block::Equation(b) => self.pattern.is_match(&b.expression),
+            Block::Code(b) => {
+                self.pattern.is_match(&plain_text(&b.content.rich_text))
+                    || self.pattern.is_match(&plain_text(&b.caption))
+            }
+            _ => rich_text_mut(block)
+                .into_iter()
+                .any(|text| self.pattern.is_match(&plain_text(text))),
+        }
+    }
+}
+
+fn plain_text(text: &[RichTextItem]) -> String {
+    text.iter().map(|item| item.plain_text.as_str()).collect()
+}
+
+fn heading_level(block: &Block) -> Option<usize> {
+    match block {
+        Block::Heading1(_) => Some(1),
+        Block::Heading2(_) => Some(2),
+        Block::Heading3(_) => Some(3),
+        _ => None,
+    }
+}
+
+/// Sets what `pattern` matches in the text of `blocks` in bold.
+fn highlight(blocks: &mut [Block], pattern: &Regex) {
+    for block in blocks {
+        for text in rich_text_mut(block) {
+            *text = highlighted(std::mem::take(text), pattern);
+        }
+        if let Block::ChildDatabase(child) = block {
+            if let ChildDatabaseContent::Fetched(db) = &mut child.content {
+                highlight_in_database(db, pattern);
+            }
+        }
+        highlight(block.children_mut(), pattern);
+    }
+}
+
+fn highlight_in_database(db: &mut Database, pattern: &Regex) {
+    let rows = db
+        .pages
+        .iter_mut()
+        .chain(db.data_sources.iter_mut().flat_map(|s| &mut s.pages));
+    for row in rows {
+        highlight(&mut row.blocks, pattern);
+    }
+}
+
+/// `text` with each item split around the matches in it, which are bold.
+/// Mentions, equations, code and text already bold are left as they are.
+fn highlighted(text: Vec<RichTextItem>, pattern: &Regex) -> Vec<RichTextItem> {
+    let mut out = Vec::with_capacity(text.len());
+    for item in text {
+        let RichTextType::Text { content, .. } = &item.text_type else {
+            out.push(item);
+            continue;
+        };
+        if item.annotations.code || item.annotations.bold {
+            out.push(item);
+            continue;
+        }
+        let mut last = 0;
+        for found in pattern.find_iter(content).filter(|m| !m.is_empty()) {
+            if found.start() > last {
+                out.push(piece(&item, &content[last..found.start()], false));
+            }
+            out.push(piece(&item, found.as_str(), true));
+            last = found.end();
+        }
+        if last == 0 {
+            out.push(item);
+        } else if last < content.len() {
+            out.push(piece(&item, &content[last..], false));
+        }
+    }
+    out
+}
+
+/// Part of a text item, keeping its link and style.
+fn piece(item: &RichTextItem, text: &str, bold: bool) -> RichTextItem {
+    let mut piece = item.clone();
+    if let RichTextType::Text { content, .. } = &mut piece.text_type {
+        *content = text.to_string();
+    }
+    piece.plain_text = text.to_string();
+    piece.annotations.bold |= bold;
+    piece
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        BlockCommon, Heading1Block, Heading2Block, ParagraphBlock, TextBlockContent, ToggleBlock,
+    };
+    use crate::types::{BlockId, Color};
+
+    fn content(text: &str) -> TextBlockContent {
+        TextBlockContent {
+            rich_text: vec![RichTextItem::plain_text(text)],
+            color: Color::Default,
+        }
+    }
+
+    fn paragraph(text: &str) -> Block {
+        Block::Paragraph(ParagraphBlock {
+            common: BlockCommon::new(BlockId::new_v4()),
+            content: content(text),
+        })
+    }
+
+    fn text_of(block: &Block) -> String {
+        let content = match block {
+            Block::Paragraph(b) => &b.content,
+            Block::Heading1(b) => &b.content,
+            Block::Heading2(b) => &b.content,
+            Block::Toggle(b) => &b.content,
+            _ => panic!("unexpected block"),
+        };
+        plain_text(&content.rich_text)
+    }
+
+    fn search(pattern: &Regex, context: usize) -> Search<'_> {
+        Search { pattern, context }
+    }
+
+    #[test]
+    fn test_matches_keep_their_context_and_headings() {
+        let mut blocks = vec![
+            Block::Heading1(Heading1Block {
+                common: BlockCommon::new(BlockId::new_v4()),
+                content: content("Plan"),
+            }),
+            Block::Heading2(Heading2Block {
+                common: BlockCommon::new(BlockId::new_v4()),
+                content: content("Hiring"),
+            }),
+            paragraph("far away"),
+            Block::Heading2(Heading2Block {
+                common: BlockCommon::new(BlockId::new_v4()),
+                content: content("Budget"),
+            }),
+            paragraph("far before"),
+            paragraph("just before"),
+            paragraph("the invoice is late"),
+            paragraph("just after"),
+            paragraph("far after"),
+        ];
+
+        let pattern = Regex::new("(?i)INVOICE").unwrap();
+        assert_eq!(search(&pattern, 1).keep_matching(&mut blocks), 1);
+        let texts: Vec<String> = blocks.iter().map(text_of).collect();
+        assert_eq!(
+            texts,
+            [
+                "Plan",
+                "Budget",
+                "just before",
+                "the invoice is late",
+                "just after"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ancestors_keep_only_matching_children() {
+        let mut toggle = Block::Toggle(ToggleBlock {
+            common: BlockCommon::new(BlockId::new_v4()),
+            content: content("Notes"),
+        });
+        toggle.set_children(vec![paragraph("unrelated"), paragraph("invoice #12")]);
+        let mut blocks = vec![toggle, paragraph("other")];
+
+        let pattern = Regex::new("invoice").unwrap();
+        assert_eq!(search(&pattern, 0).keep_matching(&mut blocks), 1);
+        assert_eq!(blocks.len(), 1);
+        let children: Vec<String> = blocks[0].children().iter().map(text_of).collect();
+        assert_eq!(children, ["invoice #12"]);
+    }
+
+    #[test]
+    fn test_matches_are_bold() {
+        let pattern = Regex::new("late").unwrap();
+        let text = highlighted(vec![RichTextItem::plain_text("late, too late.")], &pattern);
+        let pieces: Vec<(&str, bool)> = text
+            .iter()
+            .map(|item| (item.plain_text.as_str(), item.annotations.bold))
+            .collect();
+        assert_eq!(
+            pieces,
+            [
+                ("late", true),
+                (", too ", false),
+                ("late", true),
+                (".", false)
+            ]
+        );
+    }
+}
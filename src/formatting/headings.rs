@@ -0,0 +1,99 @@
+// src/formatting/headings.rs
+//! Heading levels for Notion heading blocks.
+//!
+//! Every composed document opens with the page title as `#`, and Notion's
+//! own H1 blocks render as `#` too. `--heading-offset` pushes content
+//! headings down a fixed number of levels; `--normalize-headings` pushes
+//! them down only as far as needed to leave the title the single H1.
+
+use crate::config::PipelineConfig;
+use crate::constants::MARKDOWN_MAX_HEADING_LEVEL;
+use crate::model::Block;
+
+/// How many levels to push the headings in `document_blocks` down.
+///
+/// The configured offset always applies. With normalization on, a
+/// document whose content would still contain an H1 is shifted one
+/// level further.
+pub fn heading_shift(config: Option<&PipelineConfig>, document_blocks: &[Block]) -> u8 {
+    let Some(config) = config else {
+        return 0;
+    };
+    let offset = config.heading_offset;
+    if config.normalize_headings && offset == 0 && contains_heading1(document_blocks) {
+        1
+    } else {
+        offset
+    }
+}
+
+/// The markdown level for a Notion heading of `level` after `shift`,
+/// never deeper than H6.
+pub fn shifted_level(level: u8, shift: u8) -> u8 {
+    level.saturating_add(shift).min(MARKDOWN_MAX_HEADING_LEVEL)
+}
+
+fn contains_heading1(blocks: &[Block]) -> bool {
+    blocks
+        .iter()
+        .any(|block| matches!(block, Block::Heading1(_)) || contains_heading1(block.children()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BlockCommon, Heading1Block, Heading2Block, TextBlockContent};
+    use crate::types::{BlockId, Color};
+
+    fn common() -> BlockCommon {
+        BlockCommon {
+            id: BlockId::new_v4(),
+            children: vec![],
+            has_children: false,
+            archived: false,
+            last_edited_time: None,
+            unfetched_children: None,
+        }
+    }
+
+    fn content() -> TextBlockContent {
+        TextBlockContent {
+            rich_text: vec![],
+            color: Color::Default,
+        }
+    }
+
+    fn config(heading_offset: u8, normalize_headings: bool) -> PipelineConfig {
+        PipelineConfig {
+            heading_offset,
+            normalize_headings,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_normalization_shifts_only_documents_with_an_h1() {
+        let with_h1 = vec![Block::Heading1(Heading1Block {
+            common: common(),
+            content: content(),
+        })];
+        let without_h1 = vec![Block::Heading2(Heading2Block {
+            common: common(),
+            content: content(),
+        })];
+
+        assert_eq!(heading_shift(Some(&config(0, true)), &with_h1), 1);
+        assert_eq!(heading_shift(Some(&config(0, true)), &without_h1), 0);
+        assert_eq!(heading_shift(Some(&config(0, false)), &with_h1), 0);
+        assert_eq!(heading_shift(Some(&config(2, true)), &with_h1), 2);
+        assert_eq!(heading_shift(None, &with_h1), 0);
+    }
+
+    #[test]
+    fn test_shifted_levels_stop_at_h6() {
+        assert_eq!(shifted_level(1, 1), 2);
+        assert_eq!(shifted_level(3, 2), 5);
+        assert_eq!(shifted_level(3, 5), 6);
+        assert_eq!(shifted_level(1, u8::MAX), 6);
+    }
+}
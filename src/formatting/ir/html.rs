@@ -0,0 +1,226 @@
+// src/formatting/ir/html.rs
+//! HTML renderer for the IR.
+//!
+//! Produces a self-contained `<article>` fragment with no styling, so the
+//! host page or EPUB decides how it looks. All text is escaped, and void
+//! elements are self-closed so the fragment is also well-formed XHTML.
+
+use super::{plain_text, Document, Element, Heading, LinkKind, ListItem, Span};
+
+pub(super) fn render_document(doc: &Document) -> String {
+    let mut out = String::from("<article>\n");
+    out.push_str(&format!("<h1>{}</h1>\n", escape(&doc.title)));
+
+    if !doc.properties.is_empty() {
+        out.push_str("<dl>\n");
+        for property in &doc.properties {
+            out.push_str(&format!(
+                "<dt>{}</dt><dd>{}</dd>\n",
+                escape(&property.name),
+                escape(&property.value)
+            ));
+        }
+        out.push_str("</dl>\n");
+    }
+
+    for section in &doc.sections {
+        out.push_str("<section>\n");
+        if let Some(heading) = &section.heading {
+            out.push_str(&heading_tag(heading));
+        }
+        out.push_str(&render_elements(&section.elements));
+        out.push_str("</section>\n");
+    }
+
+    if let Some(url) = &doc.url {
+        out.push_str(&format!(
+            "<footer><a href=\"{0}\">{0}</a></footer>\n",
+            escape(url)
+        ));
+    }
+    out.push_str("</article>\n");
+    out
+}
+
+/// Section headings sit one level below the document's `<h1>`.
+fn heading_tag(heading: &Heading) -> String {
+    let level = heading.level.saturating_add(1).min(6);
+    format!("<h{0}>{1}</h{0}>\n", level, render_spans(&heading.spans))
+}
+
+fn render_elements(elements: &[Element]) -> String {
+    elements.iter().map(render_element).collect()
+}
+
+fn render_element(element: &Element) -> String {
+    match element {
+        Element::Paragraph { spans } => format!("<p>{}</p>\n", render_spans(spans)),
+        Element::Heading(heading) => heading_tag(heading),
+        Element::List { ordered, items } => {
+            let tag = if *ordered { "ol" } else { "ul" };
+            let items: String = items.iter().map(render_list_item).collect();
+            format!("<{tag}>\n{items}</{tag}>\n")
+        }
+        Element::Quote { elements } => {
+            format!("<blockquote>\n{}</blockquote>\n", render_elements(elements))
+        }
+        Element::Callout {
+            icon,
+            kind,
+            elements,
+        } => {
+            let icon = icon
+                .as_ref()
+                .map(|icon| format!("<span>{}</span>\n", escape(icon)))
+                .unwrap_or_default();
+            let class = kind
+                .map(|kind| format!(" class=\"callout callout-{}\"", kind))
+                .unwrap_or_default();
+            format!(
+                "<aside{}>\n{}{}</aside>\n",
+                class,
+                icon,
+                render_elements(elements)
+            )
+        }
+        Element::Toggle { summary, elements } => format!(
+            "<details>\n<summary>{}</summary>\n{}</details>\n",
+            render_spans(summary),
+            render_elements(elements)
+        ),
+        Element::Code { language, text } => format!(
+            "<pre><code class=\"language-{}\">{}</code></pre>\n",
+            escape(language),
+            escape(text)
+        ),
+        Element::Equation { expression } => {
+            format!("<div class=\"math\">{}</div>\n", escape(expression))
+        }
+        Element::Divider => "<hr />\n".to_string(),
+        Element::Table { header, rows } => {
+            let row = |cells: &Vec<Vec<Span>>, tag: &str| -> String {
+                let cells: String = cells
+                    .iter()
+                    .map(|cell| format!("<{tag}>{}</{tag}>", render_spans(cell)))
+                    .collect();
+                format!("<tr>{}</tr>\n", cells)
+            };
+            let head = header
+                .as_ref()
+                .map(|cells| format!("<thead>\n{}</thead>\n", row(cells, "th")))
+                .unwrap_or_default();
+            let body: String = rows.iter().map(|cells| row(cells, "td")).collect();
+            format!("<table>\n{}<tbody>\n{}</tbody>\n</table>\n", head, body)
+        }
+        Element::DataTable {
+            title,
+            columns,
+            rows,
+        } => {
+            let head: String = columns
+                .iter()
+                .map(|column| format!("<th>{}</th>", escape(column)))
+                .collect();
+            let body: String = rows
+                .iter()
+                .map(|cells| {
+                    let cells: String = cells
+                        .iter()
+                        .map(|cell| format!("<td>{}</td>", escape(cell)))
+                        .collect();
+                    format!("<tr>{}</tr>\n", cells)
+                })
+                .collect();
+            format!(
+                "<table>\n<caption>{}</caption>\n<thead>\n<tr>{}</tr>\n</thead>\n<tbody>\n{}</tbody>\n</table>\n",
+                escape(title),
+                head,
+                body
+            )
+        }
+        Element::Link {
+            kind,
+            label,
+            url,
+            title,
+        } => {
+            let text = if title.is_empty() {
+                label.clone()
+            } else {
+                plain_text(title)
+            };
+            match (kind, url) {
+                (LinkKind::Image, Some(url)) => format!(
+                    "<figure><img src=\"{}\" alt=\"{}\" /><figcaption>{}</figcaption></figure>\n",
+                    escape(url),
+                    escape(&text),
+                    render_spans(title)
+                ),
+                (_, Some(url)) => {
+                    format!("<p><a href=\"{}\">{}</a></p>\n", escape(url), escape(&text))
+                }
+                (_, None) => format!("<p>{}: {}</p>\n", label, escape(&text)),
+            }
+        }
+        Element::Note { text } => format!("<p class=\"note\">{}</p>\n", escape(text)),
+    }
+}
+
+fn render_list_item(item: &ListItem) -> String {
+    let checkbox = match item.checked {
+        Some(true) => "<input type=\"checkbox\" checked=\"checked\" disabled=\"disabled\" /> ",
+        Some(false) => "<input type=\"checkbox\" disabled=\"disabled\" /> ",
+        None => "",
+    };
+    let children = if item.children.is_empty() {
+        String::new()
+    } else {
+        format!("\n{}", render_elements(&item.children))
+    };
+    format!(
+        "<li>{}{}{}</li>\n",
+        checkbox,
+        render_spans(&item.spans),
+        children
+    )
+}
+
+fn render_spans(spans: &[Span]) -> String {
+    spans.iter().map(render_span).collect()
+}
+
+fn render_span(span: &Span) -> String {
+    if span.equation {
+        return format!("<span class=\"math\">{}</span>", escape(&span.text));
+    }
+    let mut html = escape(&span.text);
+    for (on, tag) in [
+        (span.code, "code"),
+        (span.underline, "u"),
+        (span.strikethrough, "s"),
+        (span.italic, "em"),
+        (span.bold, "strong"),
+    ] {
+        if on {
+            html = format!("<{tag}>{html}</{tag}>");
+        }
+    }
+    if let Some(url) = &span.link {
+        html = format!("<a href=\"{}\">{}</a>", escape(url), html);
+    }
+    html
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
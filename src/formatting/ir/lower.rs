@@ -0,0 +1,556 @@
+// src/formatting/ir/lower.rs
+//! Lowering from the Notion model into the IR.
+
+use super::{Document, Element, Heading, LinkKind, ListItem, PropertyEntry, Section, Span};
+use crate::config::PipelineConfig;
+use crate::error::AppError;
+use crate::formatting::callouts::classify;
+use crate::formatting::properties::render_property_value;
+use crate::formatting::Messages;
+use crate::model::{
+    Block, ChildDatabaseContent, Database, DatabasePropertyType, FileObject, Icon, NotionObject,
+    Page, PropertyTypeValue,
+};
+use crate::types::{RichTextItem, RichTextType};
+
+/// What lowering takes from the configuration: the labels of `--lang`.
+pub(super) struct Lowering {
+    messages: &'static Messages,
+}
+
+impl Lowering {
+    /// Lowering as `config` asks, or with the defaults without one.
+    pub(super) fn new(config: Option<&PipelineConfig>) -> Self {
+        Self {
+            messages: Messages::for_language(config.map(|c| c.lang).unwrap_or_default()),
+        }
+    }
+
+    pub(super) fn object(&self, object: &NotionObject) -> Result<Document, AppError> {
+        match object {
+            NotionObject::Page(page) => self.page(page),
+            NotionObject::Database(db) => Ok(Document {
+                title: object.display_title(),
+                url: Some(db.url.clone()).filter(|url| !url.is_empty()),
+                properties: Vec::new(),
+                sections: self.database_sections(db)?,
+            }),
+            NotionObject::Block(block) => Ok(Document {
+                title: object.display_title(),
+                url: None,
+                properties: Vec::new(),
+                sections: self.sections(std::slice::from_ref(block))?,
+            }),
+        }
+    }
+
+    pub(super) fn page(&self, page: &Page) -> Result<Document, AppError> {
+        let mut properties = Vec::new();
+        for (name, value) in &page.properties {
+            if matches!(value.type_specific_value, PropertyTypeValue::Title { .. }) {
+                continue;
+            }
+            let value = render_property_value(Some(value))?;
+            if !value.is_empty() {
+                properties.push(PropertyEntry {
+                    name: name.as_str().to_string(),
+                    value,
+                });
+            }
+        }
+        properties.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Document {
+            title: page.title().as_str().to_string(),
+            url: Some(page.url.clone()).filter(|url| !url.is_empty()),
+            properties,
+            sections: self.sections(&page.blocks)?,
+        })
+    }
+
+    /// One section per data source, or a single untitled one.
+    fn database_sections(&self, db: &Database) -> Result<Vec<Section>, AppError> {
+        if db.data_sources.is_empty() {
+            return Ok(vec![Section {
+                heading: None,
+                elements: vec![self.data_table(db)?],
+            }]);
+        }
+        db.data_sources
+            .iter()
+            .map(|source| {
+                Ok(Section {
+                    heading: Some(Heading {
+                        level: 2,
+                        spans: vec![Span::plain(&source.name)],
+                    }),
+                    elements: vec![self.data_table(&source.as_database(db))?],
+                })
+            })
+            .collect()
+    }
+
+    /// Splits top-level blocks into sections at each heading.
+    pub(super) fn sections(&self, blocks: &[Block]) -> Result<Vec<Section>, AppError> {
+        let mut sections = Vec::new();
+        let mut current = Section {
+            heading: None,
+            elements: Vec::new(),
+        };
+        let mut pending = Vec::new();
+
+        for block in blocks {
+            if let Some(heading) = heading_of(block) {
+                current.elements = self.elements(&pending)?;
+                pending.clear();
+                if current.heading.is_some() || !current.elements.is_empty() {
+                    sections.push(current);
+                }
+                current = Section {
+                    heading: Some(heading),
+                    elements: Vec::new(),
+                };
+                // Toggleable headings keep their content under the heading
+                pending.extend(block.children().iter().cloned());
+            } else {
+                pending.push(block.clone());
+            }
+        }
+        current.elements = self.elements(&pending)?;
+        if current.heading.is_some() || !current.elements.is_empty() {
+            sections.push(current);
+        }
+        Ok(sections)
+    }
+
+    /// Lowers sibling blocks, grouping consecutive list items into lists.
+    fn elements(&self, blocks: &[Block]) -> Result<Vec<Element>, AppError> {
+        let mut out: Vec<Element> = Vec::new();
+        for block in blocks {
+            if let Some((ordered, item)) = self.list_item(block)? {
+                match out.last_mut() {
+                    Some(Element::List {
+                        ordered: last_ordered,
+                        items,
+                    }) if *last_ordered == ordered && same_list_kind(items, &item) => {
+                        items.push(item);
+                    }
+                    _ => out.push(Element::List {
+                        ordered,
+                        items: vec![item],
+                    }),
+                }
+                continue;
+            }
+            out.extend(self.element(block)?);
+        }
+        Ok(out)
+    }
+
+    fn list_item(&self, block: &Block) -> Result<Option<(bool, ListItem)>, AppError> {
+        let (ordered, checked, content) = match block {
+            Block::BulletedListItem(b) => (false, None, &b.content),
+            Block::NumberedListItem(b) => (true, None, &b.content),
+            Block::ToDo(b) => (false, Some(b.checked), &b.content),
+            _ => return Ok(None),
+        };
+        Ok(Some((
+            ordered,
+            ListItem {
+                checked,
+                spans: spans(&content.rich_text),
+                children: self.elements(block.children())?,
+            },
+        )))
+    }
+
+    /// Lowers one block that is not a list item.
+    ///
+    /// Blocks whose only role is layout (columns, synced blocks) and the
+    /// children of paragraphs are flattened into the surrounding sequence.
+    fn element(&self, block: &Block) -> Result<Vec<Element>, AppError> {
+        let children = || self.elements(block.children());
+        let with_children = |first: Element| -> Result<Vec<Element>, AppError> {
+            let mut out = vec![first];
+            out.extend(self.elements(block.children())?);
+            Ok(out)
+        };
+
+        Ok(match block {
+            Block::Paragraph(b) => with_children(Element::Paragraph {
+                spans: spans(&b.content.rich_text),
+            })?,
+            Block::Template(b) => with_children(Element::Paragraph {
+                spans: spans(&b.content.rich_text),
+            })?,
+            Block::Heading1(_) | Block::Heading2(_) | Block::Heading3(_) => {
+                let heading = heading_of(block).expect("heading block");
+                with_children(Element::Heading(heading))?
+            }
+            Block::BulletedListItem(_) | Block::NumberedListItem(_) | Block::ToDo(_) => {
+                self.elements(std::slice::from_ref(block))?
+            }
+            Block::Quote(b) => vec![Element::Quote {
+                elements: self.text_then_children(&b.content.rich_text, block)?,
+            }],
+            Block::Callout(b) => vec![Element::Callout {
+                icon: b.icon.as_ref().and_then(|icon| match icon {
+                    Icon::Emoji { emoji } => Some(emoji.clone()),
+                    _ => None,
+                }),
+                kind: classify(b, &[]),
+                elements: self.text_then_children(&b.content.rich_text, block)?,
+            }],
+            Block::Toggle(b) => vec![Element::Toggle {
+                summary: spans(&b.content.rich_text),
+                elements: children()?,
+            }],
+            Block::Code(b) => vec![Element::Code {
+                language: b.language.clone(),
+                text: plain(&b.content.rich_text),
+            }],
+            Block::Equation(b) => vec![Element::Equation {
+                expression: b.expression.clone(),
+            }],
+            Block::Divider(_) => vec![Element::Divider],
+            Block::Table(b) => vec![table(block, b.has_column_header)],
+            Block::TableRow(_) => vec![table(block, false)],
+            Block::ColumnList(_) | Block::Column(_) | Block::Synced(_) => children()?,
+            Block::Image(b) => vec![self.file_link(LinkKind::Image, &b.image, &b.caption)],
+            Block::Video(b) => vec![self.file_link(LinkKind::Video, &b.video, &b.caption)],
+            Block::Audio(b) => vec![self.file_link(LinkKind::Audio, &b.audio, &b.caption)],
+            Block::File(b) => vec![self.file_link(LinkKind::File, &b.file, &b.caption)],
+            Block::Pdf(b) => vec![self.file_link(LinkKind::Pdf, &b.pdf, &b.caption)],
+            Block::Bookmark(b) => vec![self.url_link(LinkKind::Bookmark, &b.url, &b.caption)],
+            Block::Embed(b) => vec![self.url_link(LinkKind::Embed, &b.url, &b.caption)],
+            Block::LinkPreview(b) => vec![self.url_link(LinkKind::LinkPreview, &b.url, &[])],
+            Block::ChildPage(b) => vec![self.link(
+                LinkKind::Page,
+                b.included_elsewhere.clone(),
+                vec![Span::plain(&b.title)],
+            )],
+            Block::LinkToPage(b) => {
+                vec![self.link(LinkKind::Page, None, vec![Span::plain(b.page_id.as_str())])]
+            }
+            Block::ChildDatabase(b) => match &b.content {
+                ChildDatabaseContent::Fetched(db) if db.data_sources.is_empty() => {
+                    vec![self.data_table(db)?]
+                }
+                ChildDatabaseContent::Fetched(db) => db
+                    .data_sources
+                    .iter()
+                    .map(|source| self.data_table(&source.as_database(db)))
+                    .collect::<Result<_, _>>()?,
+                ChildDatabaseContent::LinkedDatabase => vec![Element::Note {
+                    text: format!(
+                        "{} '{}' ({})",
+                        self.messages.database, b.title, self.messages.linked_database
+                    ),
+                }],
+                ChildDatabaseContent::Inaccessible { reason } => vec![Element::Note {
+                    text: format!(
+                        "{} '{}' {}: {}",
+                        self.messages.database, b.title, self.messages.not_fetched, reason
+                    ),
+                }],
+                ChildDatabaseContent::IncludedElsewhere { location } => vec![self.link(
+                    LinkKind::Database,
+                    Some(location.clone()),
+                    vec![Span::plain(&b.title)],
+                )],
+                ChildDatabaseContent::ShownAbove { title } => vec![Element::Note {
+                    text: format!(
+                        "{} '{}' ({} '{}' {})",
+                        self.messages.database_view,
+                        b.title,
+                        self.messages.see,
+                        title,
+                        self.messages.above
+                    ),
+                }],
+                ChildDatabaseContent::NotFetched => {
+                    vec![self.link(LinkKind::Database, None, vec![Span::plain(&b.title)])]
+                }
+            },
+            Block::Breadcrumb(b) if !b.ancestors.is_empty() => vec![Element::Note {
+                text: b.ancestors.join(" / "),
+            }],
+            Block::Breadcrumb(_) => vec![Element::Note {
+                text: self.messages.breadcrumb.to_string(),
+            }],
+            Block::TableOfContents(_) => vec![Element::Note {
+                text: self.messages.table_of_contents.to_string(),
+            }],
+            Block::Unsupported(b) if b.skipped_bytes.is_some() => vec![Element::Note {
+                text: format!("{}: {}", self.messages.oversized_block, b.block_type),
+            }],
+            Block::Unsupported(b) => vec![Element::Note {
+                text: format!("{}: {}", self.messages.unsupported_block, b.block_type),
+            }],
+        })
+    }
+
+    fn text_then_children(
+        &self,
+        rich_text: &[RichTextItem],
+        block: &Block,
+    ) -> Result<Vec<Element>, AppError> {
+        let mut out = vec![Element::Paragraph {
+            spans: spans(rich_text),
+        }];
+        out.extend(self.elements(block.children())?);
+        Ok(out)
+    }
+
+    fn data_table(&self, db: &Database) -> Result<Element, AppError> {
+        let mut columns: Vec<_> = db.properties.iter().collect();
+        columns.sort_by(|(a_name, a), (b_name, b)| {
+            let a_title = matches!(a.property_type, DatabasePropertyType::Title);
+            let b_title = matches!(b.property_type, DatabasePropertyType::Title);
+            b_title.cmp(&a_title).then(a_name.cmp(b_name))
+        });
+
+        let mut rows = db
+            .rows()
+            .map(|row| {
+                columns
+                    .iter()
+                    .map(|(name, _)| render_property_value(row.properties.get(*name)))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for _ in 0..db.inaccessible_row_count() {
+            let mut row = vec![String::new(); columns.len()];
+            if let Some(first) = row.first_mut() {
+                *first = format!("({})", self.messages.no_access);
+            }
+            rows.push(row);
+        }
+
+        Ok(Element::DataTable {
+            title: db.title().as_plain_text(),
+            columns: columns
+                .iter()
+                .map(|(name, _)| name.as_str().to_string())
+                .collect(),
+            rows,
+        })
+    }
+
+    fn file_link(&self, kind: LinkKind, file: &FileObject, caption: &[RichTextItem]) -> Element {
+        let url = match file {
+            FileObject::External { external } => &external.url,
+            FileObject::File { file } => &file.url,
+        };
+        self.url_link(kind, url, caption)
+    }
+
+    fn url_link(&self, kind: LinkKind, url: &str, caption: &[RichTextItem]) -> Element {
+        self.link(kind, Some(url.to_string()), spans(caption))
+    }
+
+    /// A link labelled with its kind in the language of `--lang`.
+    fn link(&self, kind: LinkKind, url: Option<String>, title: Vec<Span>) -> Element {
+        Element::Link {
+            kind,
+            label: kind.label(self.messages).to_string(),
+            url,
+            title,
+        }
+    }
+}
+
+fn heading_of(block: &Block) -> Option<Heading> {
+    let (level, content) = match block {
+        Block::Heading1(b) => (1, &b.content),
+        Block::Heading2(b) => (2, &b.content),
+        Block::Heading3(b) => (3, &b.content),
+        _ => return None,
+    };
+    Some(Heading {
+        level,
+        spans: spans(&content.rich_text),
+    })
+}
+
+/// To-do items and bullets both lower to unordered lists but never share one.
+fn same_list_kind(items: &[ListItem], item: &ListItem) -> bool {
+    items
+        .last()
+        .is_none_or(|last| last.checked.is_some() == item.checked.is_some())
+}
+
+/// A simple table from its row children.
+fn table(block: &Block, has_column_header: bool) -> Element {
+    let rows_of = |block: &Block| -> Vec<Vec<Span>> {
+        match block {
+            Block::TableRow(row) => row.cells.iter().map(|cell| spans(cell)).collect(),
+            _ => Vec::new(),
+        }
+    };
+    let mut rows: Vec<_> = match block {
+        Block::TableRow(_) => vec![rows_of(block)],
+        _ => block.children().iter().map(rows_of).collect(),
+    };
+    let header = (has_column_header && !rows.is_empty()).then(|| rows.remove(0));
+    Element::Table { header, rows }
+}
+
+/// Database rows as plain text, title column first, then by property name.
+fn plain(rich_text: &[RichTextItem]) -> String {
+    rich_text
+        .iter()
+        .map(|item| item.plain_text.as_str())
+        .collect()
+}
+
+/// Rich text as spans; mentions keep their rendered text and link.
+pub(super) fn spans(rich_text: &[RichTextItem]) -> Vec<Span> {
+    rich_text
+        .iter()
+        .map(|item| {
+            let (text, link, equation) = match &item.text_type {
+                RichTextType::Text { content, link } => (
+                    content.clone(),
+                    link.as_ref()
+                        .map(|link| link.url.clone())
+                        .or_else(|| item.href.clone()),
+                    false,
+                ),
+                RichTextType::Equation(equation) => (equation.expression.clone(), None, true),
+                RichTextType::Mention(_) => (item.plain_text.clone(), item.href.clone(), false),
+            };
+            Span {
+                text,
+                bold: item.annotations.bold,
+                italic: item.annotations.italic,
+                strikethrough: item.annotations.strikethrough,
+                underline: item.annotations.underline,
+                code: item.annotations.code,
+                equation,
+                link,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Language;
+    use crate::model::{
+        BlockCommon, BulletedListItemBlock, ChildDatabaseBlock, ExternalFile, Heading2Block,
+        ImageBlock, ParagraphBlock, TextBlockContent, ToDoBlock,
+    };
+    use crate::types::{BlockId, Color, PageId};
+
+    fn common() -> BlockCommon {
+        BlockCommon {
+            id: BlockId::new_v4(),
+            children: vec![],
+            has_children: false,
+            archived: false,
+            last_edited_time: None,
+            unfetched_children: None,
+        }
+    }
+
+    fn content(text: &str) -> TextBlockContent {
+        TextBlockContent {
+            rich_text: vec![RichTextItem::plain_text(text)],
+            color: Color::Default,
+        }
+    }
+
+    fn bullet(text: &str) -> Block {
+        Block::BulletedListItem(BulletedListItemBlock {
+            common: common(),
+            content: content(text),
+        })
+    }
+
+    #[test]
+    fn test_sections_split_at_headings_and_group_lists() {
+        let blocks = vec![
+            Block::Paragraph(ParagraphBlock {
+                common: common(),
+                content: content("Intro"),
+            }),
+            Block::Heading2(Heading2Block {
+                common: common(),
+                content: content("Tasks"),
+            }),
+            bullet("one"),
+            bullet("two"),
+            Block::ToDo(ToDoBlock {
+                common: common(),
+                content: content("ship"),
+                checked: true,
+            }),
+        ];
+
+        let sections = Lowering::new(None).sections(&blocks).unwrap();
+
+        assert_eq!(sections.len(), 2);
+        assert!(sections[0].heading.is_none());
+        assert_eq!(sections[1].heading.as_ref().unwrap().level, 2);
+        match sections[1].elements.as_slice() {
+            [Element::List { items: bullets, .. }, Element::List { items: todos, .. }] => {
+                assert_eq!(bullets.len(), 2);
+                assert_eq!(todos[0].checked, Some(true));
+            }
+            other => panic!("unexpected elements: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_html_labels_follow_lang() {
+        let child_database = |title: &str, content| {
+            Block::ChildDatabase(ChildDatabaseBlock {
+                common: common(),
+                title: title.to_string(),
+                content,
+            })
+        };
+        let page = NotionObject::Page(
+            Page::new(
+                PageId::parse("12345678123456781234567812345678").unwrap(),
+                "Handbuch",
+            )
+            .with_blocks(vec![
+                Block::Image(ImageBlock {
+                    common: common(),
+                    image: FileObject::External {
+                        external: ExternalFile {
+                            url: "https://example.com/a.png".to_string(),
+                        },
+                    },
+                    caption: vec![],
+                }),
+                child_database("Aufgaben", ChildDatabaseContent::NotFetched),
+                child_database("Archiv", ChildDatabaseContent::LinkedDatabase),
+                child_database(
+                    "Offen",
+                    ChildDatabaseContent::ShownAbove {
+                        title: "Aufgaben".to_string(),
+                    },
+                ),
+            ]),
+        );
+        let config = PipelineConfig {
+            lang: Language::De,
+            ..Default::default()
+        };
+
+        let html = Document::from_object_with(&page, &config)
+            .unwrap()
+            .to_html();
+
+        assert!(html.contains("alt=\"Bild\""));
+        assert!(html.contains("<p>Datenbank: Aufgaben</p>"));
+        assert!(html
+            .contains("Datenbank 'Archiv' (verknüpfte Datenbank — über die API nicht abrufbar)"));
+        assert!(html.contains("Datenbankansicht 'Offen' (siehe 'Aufgaben' oben)"));
+        assert!(!html.contains("Database"));
+    }
+}
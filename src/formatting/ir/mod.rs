@@ -0,0 +1,319 @@
+// src/formatting/ir/mod.rs
+//! Renderer-agnostic intermediate representation of a Notion object.
+//!
+//! Lowering turns a `NotionObject` into a [`Document`]: a title, the page
+//! properties, and sections of elements built from inline [`Span`]s. All
+//! Notion-specific decisions (grouping list items, reading table headers,
+//! flattening columns and synced blocks, resolving child databases) happen
+//! once, in `lower`. The IR serves the output formats that are not
+//! Markdown, so it has no Markdown renderer of its own; Markdown always
+//! comes from `block_renderer`, the prompt's renderer:
+//!
+//! - [`Document::to_html`], also packaged as EPUB by `output::epub`
+//! - [`Document::to_json`]
+
+mod html;
+mod lower;
+
+use crate::config::PipelineConfig;
+use crate::error::AppError;
+use crate::formatting::callouts::CalloutKind;
+use crate::formatting::Messages;
+use crate::model::{Block, NotionObject, Page};
+
+pub(crate) use html::escape as escape_html;
+use lower::Lowering;
+use serde::Serialize;
+
+/// A Notion object lowered for rendering.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Document {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Page properties other than the title, sorted by name
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<PropertyEntry>,
+    pub sections: Vec<Section>,
+}
+
+/// One page property as plain text.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PropertyEntry {
+    pub name: String,
+    pub value: String,
+}
+
+/// A run of elements under one top-level heading.
+///
+/// Content before the first heading goes into a section without one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Section {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading: Option<Heading>,
+    pub elements: Vec<Element>,
+}
+
+/// A heading with its Notion level (1–3). Renderers choose the output level.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Heading {
+    pub level: u8,
+    pub spans: Vec<Span>,
+}
+
+/// A block-level element.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Element {
+    Paragraph {
+        spans: Vec<Span>,
+    },
+    /// A heading nested inside another element, e.g. in a toggle or column
+    Heading(Heading),
+    /// Consecutive list items of the same kind
+    List {
+        ordered: bool,
+        items: Vec<ListItem>,
+    },
+    Quote {
+        elements: Vec<Element>,
+    },
+    Callout {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        icon: Option<String>,
+        /// The type the built-in icon and color rules give the callout
+        #[serde(skip_serializing_if = "Option::is_none")]
+        kind: Option<CalloutKind>,
+        elements: Vec<Element>,
+    },
+    Toggle {
+        summary: Vec<Span>,
+        elements: Vec<Element>,
+    },
+    Code {
+        language: String,
+        text: String,
+    },
+    Equation {
+        expression: String,
+    },
+    Divider,
+    /// A Notion simple table; cells hold rich text
+    Table {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        header: Option<Vec<Vec<Span>>>,
+        rows: Vec<Vec<Vec<Span>>>,
+    },
+    /// Database rows, one plain-text cell per property
+    DataTable {
+        title: String,
+        columns: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    /// Media, embeds, bookmarks and references to other pages or databases
+    Link {
+        kind: LinkKind,
+        /// `kind` in the language of `--lang`, for renderers that show it
+        label: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        url: Option<String>,
+        title: Vec<Span>,
+    },
+    /// Content notion2prompt cannot represent, described in words
+    Note {
+        text: String,
+    },
+}
+
+/// One item of a bulleted, numbered or to-do list.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ListItem {
+    /// `Some` for to-do items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checked: Option<bool>,
+    pub spans: Vec<Span>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Element>,
+}
+
+/// What an [`Element::Link`] points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    Image,
+    Video,
+    Audio,
+    File,
+    Pdf,
+    Bookmark,
+    Embed,
+    LinkPreview,
+    Page,
+    Database,
+}
+
+impl LinkKind {
+    /// A short label in the language of `messages`, for renderers that
+    /// show the kind in text.
+    pub fn label(self, messages: &Messages) -> &'static str {
+        match self {
+            LinkKind::Image => messages.image,
+            LinkKind::Video => messages.video,
+            LinkKind::Audio => messages.audio,
+            LinkKind::File => messages.file,
+            LinkKind::Pdf => messages.pdf,
+            LinkKind::Bookmark => messages.bookmark,
+            LinkKind::Embed => messages.embed,
+            LinkKind::LinkPreview => messages.link_preview,
+            LinkKind::Page => messages.page,
+            LinkKind::Database => messages.database,
+        }
+    }
+}
+
+/// A run of inline text with one set of formatting.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct Span {
+    pub text: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub bold: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub italic: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub strikethrough: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub underline: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub code: bool,
+    /// `text` is a LaTeX expression
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub equation: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+}
+
+impl Span {
+    /// Unformatted text.
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Document {
+    /// Lowers a fetched Notion object into the IR.
+    pub fn from_object(object: &NotionObject) -> Result<Self, AppError> {
+        Lowering::new(None).object(object)
+    }
+
+    /// Lowers a fetched Notion object with the labels of `config.lang`.
+    pub fn from_object_with(
+        object: &NotionObject,
+        config: &PipelineConfig,
+    ) -> Result<Self, AppError> {
+        Lowering::new(Some(config)).object(object)
+    }
+
+    /// Lowers a single page, without wrapping it in a `NotionObject`.
+    pub fn from_page(page: &Page) -> Result<Self, AppError> {
+        Lowering::new(None).page(page)
+    }
+
+    /// Lowers a single page with the labels of `config.lang`.
+    pub fn from_page_with(page: &Page, config: &PipelineConfig) -> Result<Self, AppError> {
+        Lowering::new(Some(config)).page(page)
+    }
+
+    /// Renders the document as a standalone HTML `<article>`.
+    pub fn to_html(&self) -> String {
+        html::render_document(self)
+    }
+
+    /// Serializes the document as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, AppError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Lowers a run of sibling blocks into sections, split at top-level headings.
+pub(crate) fn lower_blocks(
+    blocks: &[Block],
+    config: Option<&PipelineConfig>,
+) -> Result<Vec<Section>, AppError> {
+    Lowering::new(config).sections(blocks)
+}
+
+/// The concatenated text of `spans`, without formatting.
+fn plain_text(spans: &[Span]) -> String {
+    spans.iter().map(|span| span.text.as_str()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(elements: Vec<Element>) -> Document {
+        Document {
+            title: "Notes".to_string(),
+            url: None,
+            properties: vec![],
+            sections: vec![Section {
+                heading: Some(Heading {
+                    level: 1,
+                    spans: vec![Span::plain("Overview")],
+                }),
+                elements,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_html_escapes_text_and_attributes() {
+        let doc = document(vec![Element::Link {
+            kind: LinkKind::Bookmark,
+            label: "Bookmark".to_string(),
+            url: Some("https://example.com/?a=1&b=\"2\"".to_string()),
+            title: vec![Span::plain("<script>")],
+        }]);
+
+        let html = doc.to_html();
+
+        assert!(html.contains("<h2>Overview</h2>"));
+        assert!(html.contains(
+            "<a href=\"https://example.com/?a=1&amp;b=&quot;2&quot;\">&lt;script&gt;</a>"
+        ));
+    }
+
+    #[test]
+    fn test_html_classes_typed_callouts() {
+        let doc = document(vec![Element::Callout {
+            icon: Some("⚠️".to_string()),
+            kind: Some(CalloutKind::Warning),
+            elements: vec![Element::Paragraph {
+                spans: vec![Span::plain("Back up first")],
+            }],
+        }]);
+
+        assert!(doc.to_html().contains(
+            "<aside class=\"callout callout-warning\">\n<span>⚠️</span>\n<p>Back up first</p>\n</aside>"
+        ));
+    }
+
+    #[test]
+    fn test_json_tags_elements_by_type() {
+        let doc = document(vec![Element::Divider]);
+
+        let json: serde_json::Value = serde_json::from_str(&doc.to_json().unwrap()).unwrap();
+
+        assert_eq!(json["sections"][0]["elements"][0]["type"], "divider");
+        assert_eq!(
+            json["sections"][0]["heading"]["spans"][0]["text"],
+            "Overview"
+        );
+        assert!(json["sections"][0]["heading"]["spans"][0]
+            .get("bold")
+            .is_none());
+    }
+}
@@ -0,0 +1,339 @@
+// src/formatting/link_index.rs
+//! Link styles for `--link-style`, and the `## Links` appendix of the
+//! reference and footnote styles.
+//!
+//! Links reach the prompt from rich text, mentions, bookmarks, embeds,
+//! link previews and property values, each rendered by its own code. The
+//! style is applied here, once the documents are rendered, so that it
+//! covers them all alike:
+//!
+//! - **reference** (`--link-index`): long URLs cost many tokens, and pages
+//!   often repeat them. Every external URL in the prompt is numbered once,
+//!   in order of first appearance. Inline links become reference links
+//!   (`[text][3]`), bare URLs from bookmarks, embeds and link previews
+//!   become `[3]`, and the root document ends with the definitions
+//!   (`[3]: https://…`).
+//! - **footnote**: numbered the same way, as footnotes (`text[^3]`,
+//!   `[^3]: https://…`). Images keep their inline URL, which a footnote
+//!   cannot stand in for.
+//! - **bare**: no link syntax at all; inline links become `text (https://…)`,
+//!   or just the URL when the text is the URL.
+//!
+//! Code is left as written, fenced blocks and inline spans alike.
+
+use super::whitespace::Fence;
+use crate::config::LinkStyle;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+
+/// An inline link or image to an external URL (group 1 the bracketed text,
+/// group 2 the URL, which may hold balanced parentheses), an autolink
+/// (group 3) or a bare URL (group 4).
+static LINK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(!?\[[^\]]*\])\((https?://[^\s()]*(?:\([^\s()]*\)[^\s()]*)*)\)|<(https?://[^\s<>]+)>|(https?://[^\s<>()\[\]"'`|]+)"#,
+    )
+    .expect("valid regex")
+});
+
+/// The external URLs of one prompt, numbered in order of first appearance.
+#[derive(Debug, Default)]
+pub struct LinkIndex {
+    urls: Vec<String>,
+    numbers: HashMap<String, usize>,
+    /// Number the links as footnotes rather than references
+    footnotes: bool,
+}
+
+impl LinkIndex {
+    /// An index numbering links as footnotes.
+    pub fn footnotes() -> Self {
+        Self {
+            footnotes: true,
+            ..Self::default()
+        }
+    }
+
+    /// The number of `url`, assigning the next one when it is new.
+    fn number(&mut self, url: &str) -> usize {
+        if let Some(number) = self.numbers.get(url) {
+            return *number;
+        }
+        self.urls.push(url.to_string());
+        let number = self.urls.len();
+        self.numbers.insert(url.to_string(), number);
+        number
+    }
+
+    /// How the link to `url` is marked: `[3]` or `[^3]`.
+    fn marker(&mut self, url: &str) -> String {
+        let number = self.number(url);
+        if self.footnotes {
+            format!("[^{}]", number)
+        } else {
+            format!("[{}]", number)
+        }
+    }
+
+    /// `markdown` with its external links pointing into the index.
+    pub fn reference_links(&mut self, markdown: &str) -> String {
+        outside_code(markdown, |text| self.reference_text(text))
+    }
+
+    fn reference_text(&mut self, text: &str) -> String {
+        LINK.replace_all(text, |captures: &Captures| {
+            if let (Some(label), Some(url)) = (captures.get(1), captures.get(2)) {
+                let label = label.as_str();
+                if !self.footnotes {
+                    return format!("{}[{}]", label, self.number(url.as_str()));
+                }
+                if label.starts_with('!') {
+                    return captures[0].to_string();
+                }
+                let text = &label[1..label.len() - 1];
+                return format!("{}{}", text, self.marker(url.as_str()));
+            }
+            if let Some(url) = captures.get(3) {
+                return self.marker(url.as_str());
+            }
+            let (url, punctuation) = split_bare_url(&captures[4]);
+            format!("{}{}", self.marker(url), punctuation)
+        })
+        .into_owned()
+    }
+
+    /// The `## Links` section defining every reference, or nothing when no
+    /// link was indexed.
+    pub fn appendix(&self) -> String {
+        if self.urls.is_empty() {
+            return String::new();
+        }
+        let mut out = String::from("## Links\n\n");
+        let caret = if self.footnotes { "^" } else { "" };
+        for (index, url) in self.urls.iter().enumerate() {
+            out.push_str(&format!("[{}{}]: {}\n", caret, index + 1, url));
+        }
+        out
+    }
+}
+
+/// `markdown` with its inline links and autolinks spelled out as text.
+pub fn bare_links(markdown: &str) -> String {
+    outside_code(markdown, |text| {
+        LINK.replace_all(text, |captures: &Captures| {
+            if let (Some(label), Some(url)) = (captures.get(1), captures.get(2)) {
+                let (label, url) = (label.as_str(), url.as_str());
+                if label.starts_with('!') {
+                    return captures[0].to_string();
+                }
+                let text = &label[1..label.len() - 1];
+                return if text.is_empty() || text == url {
+                    url.to_string()
+                } else {
+                    format!("{} ({})", text, url)
+                };
+            }
+            if let Some(url) = captures.get(3) {
+                return url.as_str().to_string();
+            }
+            captures[0].to_string()
+        })
+        .into_owned()
+    })
+}
+
+/// A bare URL, and the punctuation after it, which ends the sentence, not
+/// the URL.
+fn split_bare_url(bare: &str) -> (&str, &str) {
+    let url = bare.trim_end_matches(['.', ',', ';', ':', '!', '?', '*', '_', '~']);
+    (url, &bare[url.len()..])
+}
+
+/// `markdown` with `rewrite` applied to the text outside fenced blocks and
+/// code spans.
+fn outside_code(markdown: &str, mut rewrite: impl FnMut(&str) -> String) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut fence: Option<Fence> = None;
+    for line in markdown.split_inclusive('\n') {
+        if let Some(open) = &fence {
+            if open.is_closed_by(line) {
+                fence = None;
+            }
+            out.push_str(line);
+            continue;
+        }
+        if let Some(open) = Fence::opened_by(line) {
+            fence = Some(open);
+            out.push_str(line);
+            continue;
+        }
+        rewrite_line(line, &mut rewrite, &mut out);
+    }
+    out
+}
+
+/// Appends `line` to `out`, rewriting the text outside code spans.
+fn rewrite_line(line: &str, rewrite: &mut impl FnMut(&str) -> String, out: &mut String) {
+    let mut rest = line;
+    while !rest.is_empty() {
+        let Some(start) = rest.find('`') else {
+            out.push_str(&rewrite(rest));
+            return;
+        };
+        out.push_str(&rewrite(&rest[..start]));
+        let run = backtick_run(&rest[start..]);
+        // The span ends at the next run of the same length
+        let mut end = start + run;
+        loop {
+            match rest[end..].find('`') {
+                Some(next) => {
+                    let closing = backtick_run(&rest[end + next..]);
+                    end += next + closing;
+                    if closing == run {
+                        break;
+                    }
+                }
+                None => {
+                    end = rest.len();
+                    break;
+                }
+            }
+        }
+        out.push_str(&rest[start..end]);
+        rest = &rest[end..];
+    }
+}
+
+fn backtick_run(text: &str) -> usize {
+    text.bytes().take_while(|b| *b == b'`').count()
+}
+
+/// Renders the links of every rendered document in `style`. The reference
+/// and footnote styles number them across all documents, then append the
+/// appendix to the root document, which comes first.
+pub fn style_links(style: LinkStyle, main_content: &mut String, documents: &mut [&mut String]) {
+    let mut index = match style {
+        LinkStyle::Inline => return,
+        LinkStyle::Bare => {
+            *main_content = bare_links(main_content);
+            for document in documents.iter_mut() {
+                **document = bare_links(document);
+            }
+            return;
+        }
+        LinkStyle::Reference => LinkIndex::default(),
+        LinkStyle::Footnote => LinkIndex::footnotes(),
+    };
+    *main_content = index.reference_links(main_content);
+    for document in documents.iter_mut() {
+        **document = index.reference_links(document);
+    }
+
+    let appendix = index.appendix();
+    if appendix.is_empty() {
+        return;
+    }
+    log::info!("Indexed {} distinct links", index.urls.len());
+    let root = documents.first_mut().map(|d| &mut **d);
+    for document in std::iter::once(main_content).chain(root) {
+        if !document.is_empty() && !document.ends_with("\n\n") {
+            document.push_str(if document.ends_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+        document.push_str(&appendix);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_links_become_numbered_references() {
+        let mut index = LinkIndex::default();
+        let markdown =
+            "See [the spec](https://example.com/spec) and [again](https://example.com/spec).\n\
+            ![chart](https://example.com/a_(b).png)\n\
+            [🔖 https://example.com/blog - Blog]\n\
+            Mail <https://example.com/x>, or https://example.com/y.\n";
+        assert_eq!(
+            index.reference_links(markdown),
+            "See [the spec][1] and [again][1].\n\
+             ![chart][2]\n\
+             [🔖 [3] - Blog]\n\
+             Mail [4], or [5].\n"
+        );
+        assert_eq!(
+            index.appendix(),
+            "## Links\n\n\
+             [1]: https://example.com/spec\n\
+             [2]: https://example.com/a_(b).png\n\
+             [3]: https://example.com/blog\n\
+             [4]: https://example.com/x\n\
+             [5]: https://example.com/y\n"
+        );
+    }
+
+    #[test]
+    fn test_code_and_relative_links_are_left_alone() {
+        let mut index = LinkIndex::default();
+        let markdown = "```sh\ncurl https://example.com/api\n```\n\
+            Run `curl https://example.com/api` or read [Row](Row_1a2b.md).\n";
+        assert_eq!(index.reference_links(markdown), markdown);
+        assert_eq!(index.appendix(), "");
+    }
+
+    #[test]
+    fn test_numbers_are_shared_across_documents() {
+        let mut main = "[Home](https://example.com)\n".to_string();
+        let mut root = main.clone();
+        let mut row =
+            "Back to https://example.com and [docs](https://docs.example.com)".to_string();
+        style_links(LinkStyle::Reference, &mut main, &mut [&mut root, &mut row]);
+
+        assert_eq!(
+            root,
+            "[Home][1]\n\n## Links\n\n[1]: https://example.com\n[2]: https://docs.example.com\n"
+        );
+        assert_eq!(main, root);
+        assert_eq!(row, "Back to [1] and [docs][2]");
+    }
+
+    #[test]
+    fn test_footnote_style_numbers_links_as_footnotes() {
+        let mut index = LinkIndex::footnotes();
+        let markdown = "See [the spec](https://example.com/spec), https://example.com/spec.\n\
+            ![chart](https://example.com/chart.png)\n\
+            [🔖 https://example.com/blog - Blog]\n";
+        assert_eq!(
+            index.reference_links(markdown),
+            "See the spec[^1], [^1].\n\
+             ![chart](https://example.com/chart.png)\n\
+             [🔖 [^2] - Blog]\n"
+        );
+        assert_eq!(
+            index.appendix(),
+            "## Links\n\n[^1]: https://example.com/spec\n[^2]: https://example.com/blog\n"
+        );
+    }
+
+    #[test]
+    fn test_bare_style_spells_links_out() {
+        let mut main = "[the spec](https://example.com/spec) and [https://example.com](https://example.com)\n\
+            ![chart](https://example.com/chart.png) <https://example.com/x> `[a](https://example.com/a)`\n"
+            .to_string();
+        let mut row = "[Row](Row_1a2b.md)".to_string();
+        style_links(LinkStyle::Bare, &mut main, &mut [&mut row]);
+
+        assert_eq!(
+            main,
+            "the spec (https://example.com/spec) and https://example.com\n\
+             ![chart](https://example.com/chart.png) https://example.com/x `[a](https://example.com/a)`\n"
+        );
+        assert_eq!(row, "[Row](Row_1a2b.md)");
+    }
+}
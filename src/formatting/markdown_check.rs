@@ -0,0 +1,407 @@
+// src/formatting/markdown_check.rs
+//! Validity check for rendered markdown (`--check-markdown`).
+//!
+//! Rendering goes block by block, so nothing looks at the finished document
+//! as a whole. Content that Notion allows but markdown does not survives
+//! into the prompt: multi-line database cells split a table row over several
+//! lines, a pipe in a cell adds a column, a code block left open swallows
+//! the rest of the page. This pass finds those problems and repairs the ones
+//! with an unambiguous fix; the rest are only reported.
+//!
+//! Fenced code is copied verbatim and never checked.
+
+use super::whitespace::Fence;
+use std::fmt;
+
+/// The checked markdown and everything found in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownCheck {
+    pub markdown: String,
+    pub issues: Vec<MarkdownIssue>,
+}
+
+/// A problem found at a line of the input (1-based).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkdownIssue {
+    pub line: usize,
+    pub kind: MarkdownIssueKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkdownIssueKind {
+    /// A code fence is never closed; a closing fence was appended
+    UnclosedFence,
+    /// A table row continued on following lines; they were joined with `<br>`
+    SplitTableRow { lines: usize },
+    /// A table row had fewer cells than its header; empty cells were added
+    MissingCells { expected: usize, found: usize },
+    /// A table row had more cells than its header, from unescaped pipes;
+    /// the extra cells were merged into the last one with escaped pipes
+    StrayPipes { expected: usize, found: usize },
+    /// Spaces in a link target; they were percent-encoded
+    SpaceInLink,
+    /// A link target is never closed; left as is
+    UnclosedLink,
+}
+
+impl MarkdownIssue {
+    /// Whether the check repaired the problem.
+    pub fn is_fixed(&self) -> bool {
+        !matches!(self.kind, MarkdownIssueKind::UnclosedLink)
+    }
+}
+
+impl fmt::Display for MarkdownIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            MarkdownIssueKind::UnclosedFence => write!(f, "code fence never closed; closed it"),
+            MarkdownIssueKind::SplitTableRow { lines } => {
+                write!(f, "table row split over {} lines; joined them", lines)
+            }
+            MarkdownIssueKind::MissingCells { expected, found } => write!(
+                f,
+                "table row has {} of {} cells; added empty cells",
+                found, expected
+            ),
+            MarkdownIssueKind::StrayPipes { expected, found } => write!(
+                f,
+                "table row has {} cells for {} columns; escaped the extra pipes",
+                found, expected
+            ),
+            MarkdownIssueKind::SpaceInLink => write!(f, "spaces in link target; encoded them"),
+            MarkdownIssueKind::UnclosedLink => write!(f, "link target never closed"),
+        }
+    }
+}
+
+/// Checks `markdown` and returns it with every fixable problem repaired.
+pub fn check_markdown(markdown: &str) -> MarkdownCheck {
+    let lines: Vec<&str> = markdown.lines().collect();
+    let mut out = String::with_capacity(markdown.len());
+    let mut issues = Vec::new();
+    let mut fence: Option<(Fence, usize)> = None;
+    // Cell count of the table the previous line belongs to.
+    let mut columns: Option<usize> = None;
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let line_no = i + 1;
+        i += 1;
+
+        if let Some((open, _)) = &fence {
+            if open.is_closed_by(line) {
+                fence = None;
+            }
+            push_line(&mut out, line);
+            continue;
+        }
+        if let Some(open) = Fence::opened_by(line) {
+            fence = Some((open, line_no));
+            columns = None;
+            push_line(&mut out, line);
+            continue;
+        }
+        if !line.trim_start().starts_with('|') {
+            columns = None;
+            push_line(&mut out, &check_links(line, line_no, &mut issues));
+            continue;
+        }
+
+        // A newline inside a cell leaves the row without its closing pipe
+        // and carries the rest of the cell onto the next lines.
+        let mut row = line.trim_end().to_string();
+        let first = i;
+        while columns.is_some()
+            && !ends_with_unescaped_pipe(&row)
+            && i < lines.len()
+            && continues_row(lines[i])
+        {
+            row.push_str("<br>");
+            row.push_str(lines[i].trim());
+            i += 1;
+        }
+        if i > first {
+            issues.push(MarkdownIssue {
+                line: line_no,
+                kind: MarkdownIssueKind::SplitTableRow {
+                    lines: i - first + 1,
+                },
+            });
+        }
+
+        let cells = split_cells(&row);
+        let row = match columns {
+            None => {
+                if lines.get(i).is_some_and(|next| is_delimiter_row(next)) {
+                    columns = Some(cells.len());
+                }
+                row
+            }
+            Some(_) if is_delimiter_row(&row) => row,
+            Some(expected) if cells.len() < expected => {
+                issues.push(MarkdownIssue {
+                    line: line_no,
+                    kind: MarkdownIssueKind::MissingCells {
+                        expected,
+                        found: cells.len(),
+                    },
+                });
+                let mut cells = cells;
+                cells.resize(expected, String::new());
+                join_cells(&row, &cells)
+            }
+            Some(expected) if cells.len() > expected => {
+                issues.push(MarkdownIssue {
+                    line: line_no,
+                    kind: MarkdownIssueKind::StrayPipes {
+                        expected,
+                        found: cells.len(),
+                    },
+                });
+                let mut cells = cells;
+                let extra = cells.split_off(expected);
+                let last = cells.last_mut().expect("a table has at least one column");
+                *last = extra.iter().fold(last.trim().to_string(), |merged, cell| {
+                    format!("{} \\| {}", merged, cell.trim())
+                });
+                join_cells(&row, &cells)
+            }
+            Some(_) => row,
+        };
+        push_line(&mut out, &check_links(&row, line_no, &mut issues));
+    }
+
+    if let Some((open, line)) = fence {
+        issues.push(MarkdownIssue {
+            line,
+            kind: MarkdownIssueKind::UnclosedFence,
+        });
+        push_line(&mut out, &open.closing());
+    }
+
+    if !markdown.ends_with('\n') {
+        out.pop();
+    }
+    MarkdownCheck {
+        markdown: out,
+        issues,
+    }
+}
+
+fn push_line(out: &mut String, line: &str) {
+    out.push_str(line);
+    out.push('\n');
+}
+
+/// Whether `line` is the rest of a table cell rather than a new block.
+fn continues_row(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && !trimmed.starts_with('|') && Fence::opened_by(line).is_none()
+}
+
+fn ends_with_unescaped_pipe(row: &str) -> bool {
+    let body = row.trim_end();
+    body.len() > 1 && body.ends_with('|') && !body.ends_with("\\|")
+}
+
+/// The cells of a table row, split at unescaped pipes outside code spans.
+fn split_cells(row: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut in_code = false;
+    let mut chars = row.trim().chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                cell.push(c);
+                if let Some(escaped) = chars.next() {
+                    cell.push(escaped);
+                }
+            }
+            '`' => {
+                in_code = !in_code;
+                cell.push(c);
+            }
+            '|' if !in_code => cells.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell);
+    // Drop the empty edges outside the leading and trailing pipes.
+    if cells.first().is_some_and(|c| c.trim().is_empty()) {
+        cells.remove(0);
+    }
+    if cells.len() > 1 && cells.last().is_some_and(|c| c.trim().is_empty()) {
+        cells.pop();
+    }
+    cells
+}
+
+fn join_cells(row: &str, cells: &[String]) -> String {
+    let indent = &row[..row.len() - row.trim_start().len()];
+    let cells: Vec<&str> = cells.iter().map(|cell| cell.trim()).collect();
+    format!("{}| {} |", indent, cells.join(" | "))
+}
+
+/// `| --- | :-: |`: the row separating a table's header from its body.
+fn is_delimiter_row(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with('|')
+        && split_cells(trimmed).iter().all(|cell| {
+            let cell = cell.trim().trim_start_matches(':').trim_end_matches(':');
+            !cell.is_empty() && cell.chars().all(|c| c == '-')
+        })
+}
+
+/// Encodes spaces in link targets and reports targets that never close.
+/// Inline code spans are left alone.
+fn check_links(line: &str, line_no: usize, issues: &mut Vec<MarkdownIssue>) -> String {
+    if !line.contains("](") {
+        return line.to_string();
+    }
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    let mut in_code = false;
+    while let Some(pos) = rest.find(['`', ']']) {
+        let (before, from) = rest.split_at(pos);
+        out.push_str(before);
+        if let Some(after) = from.strip_prefix('`') {
+            in_code = !in_code;
+            out.push('`');
+            rest = after;
+            continue;
+        }
+        if in_code || !from.starts_with("](") {
+            out.push(']');
+            rest = &from[1..];
+            continue;
+        }
+        out.push_str("](");
+        let target_start = &from[2..];
+        let Some(end) = link_target_end(target_start) else {
+            issues.push(MarkdownIssue {
+                line: line_no,
+                kind: MarkdownIssueKind::UnclosedLink,
+            });
+            out.push_str(target_start);
+            return out;
+        };
+        let target = &target_start[..end];
+        if needs_encoding(target) {
+            issues.push(MarkdownIssue {
+                line: line_no,
+                kind: MarkdownIssueKind::SpaceInLink,
+            });
+            out.push_str(&target.trim().replace(' ', "%20"));
+        } else {
+            out.push_str(target);
+        }
+        out.push(')');
+        rest = &target_start[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The index of the `)` closing a link target, allowing balanced parentheses inside.
+fn link_target_end(target: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, c) in target.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(index),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether a link target has bare spaces, as opposed to `<url with spaces>`
+/// or `url "title"`, which are valid.
+fn needs_encoding(target: &str) -> bool {
+    let target = target.trim();
+    if target.starts_with('<') && target.ends_with('>') {
+        return false;
+    }
+    match target.split_once(' ') {
+        None => false,
+        Some((_, title)) => {
+            let title = title.trim();
+            !(title.len() >= 2
+                && ((title.starts_with('"') && title.ends_with('"'))
+                    || (title.starts_with('\'') && title.ends_with('\''))))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(check: &MarkdownCheck) -> Vec<&MarkdownIssueKind> {
+        check.issues.iter().map(|issue| &issue.kind).collect()
+    }
+
+    #[test]
+    fn test_valid_markdown_passes_unchanged() {
+        let input = "# Title\n\n| A | B |\n| --- | --- |\n| 1 | `a|b` |\n\n[link](https://x.y \"T\")\n\n```\n| not | a table\n```\n";
+        let check = check_markdown(input);
+        assert_eq!(check.markdown, input);
+        assert!(check.issues.is_empty());
+    }
+
+    #[test]
+    fn test_split_row_is_joined_and_cell_counts_repaired() {
+        let input =
+            "| Name | Notes |\n| --- | --- |\n| a | first\nsecond |\n| b |\n| c | x | y |\n";
+        let check = check_markdown(input);
+        assert_eq!(
+            check.markdown,
+            "| Name | Notes |\n| --- | --- |\n| a | first<br>second |\n| b |  |\n| c | x \\| y |\n"
+        );
+        assert_eq!(
+            kinds(&check),
+            vec![
+                &MarkdownIssueKind::SplitTableRow { lines: 2 },
+                &MarkdownIssueKind::MissingCells {
+                    expected: 2,
+                    found: 1
+                },
+                &MarkdownIssueKind::StrayPipes {
+                    expected: 2,
+                    found: 3
+                },
+            ]
+        );
+        assert_eq!(check.issues[0].line, 3);
+    }
+
+    #[test]
+    fn test_unclosed_fence_is_closed() {
+        let check = check_markdown("text\n````rust\nfn main() {}");
+        assert_eq!(check.markdown, "text\n````rust\nfn main() {}\n````");
+        assert_eq!(check.issues[0].line, 2);
+        assert_eq!(kinds(&check), vec![&MarkdownIssueKind::UnclosedFence]);
+    }
+
+    #[test]
+    fn test_link_targets_are_encoded_or_reported() {
+        let check =
+            check_markdown("See [doc](files/my doc.pdf) and `[x](a b)`.\n[open](https://x.y");
+        assert_eq!(
+            check.markdown,
+            "See [doc](files/my%20doc.pdf) and `[x](a b)`.\n[open](https://x.y"
+        );
+        assert_eq!(
+            kinds(&check),
+            vec![
+                &MarkdownIssueKind::SpaceInLink,
+                &MarkdownIssueKind::UnclosedLink
+            ]
+        );
+        assert!(!check.issues[1].is_fixed());
+    }
+}
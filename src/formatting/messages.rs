@@ -0,0 +1,326 @@
+// src/formatting/messages.rs
+//! Labels the renderer writes into prompts, per output language (`--lang`).
+//!
+//! Only text notion2prompt adds itself is translated: section headings,
+//! placeholders for blocks without text, table notes, mention labels and
+//! the `--expect-schema` drift report. Workspace content is never touched.
+
+use crate::config::Language;
+
+/// One language's renderer labels.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Messages {
+    pub table_of_contents: &'static str,
+    pub no_headings_found: &'static str,
+    pub breadcrumb: &'static str,
+    pub workspace: &'static str,
+    pub image: &'static str,
+    pub file: &'static str,
+    pub video: &'static str,
+    pub audio: &'static str,
+    pub pdf: &'static str,
+    pub embed: &'static str,
+    /// Follows a provider name, as in "YouTube embed"
+    pub provider_embed: &'static str,
+    pub link_preview: &'static str,
+    pub bookmark: &'static str,
+    pub page: &'static str,
+    pub database: &'static str,
+    pub synced_from: &'static str,
+    pub template: &'static str,
+    pub unsupported_block: &'static str,
+    pub oversized_block: &'static str,
+    pub child_database: &'static str,
+    pub database_view: &'static str,
+    /// Describes a linked database, which the API does not return
+    pub linked_database: &'static str,
+    /// Follows the title of a database that failed to load, before the reason
+    pub not_fetched: &'static str,
+    pub no_data: &'static str,
+    pub no_properties: &'static str,
+    pub rows: &'static str,
+    pub columns: &'static str,
+    pub open_in_notion: &'static str,
+    pub see: &'static str,
+    /// Follows the title of a database view shown earlier in the document
+    pub above: &'static str,
+    /// Names an untitled database told apart from another one
+    pub untitled_database: &'static str,
+    /// Stands in for a database row the integration may not read
+    pub no_access: &'static str,
+    pub rows_without_access: &'static str,
+    /// Precedes a property name, heading the `--db-group-by` rows without
+    /// a value
+    pub no_value: &'static str,
+    /// Marks where `--depth` or `--limit` cut blocks off
+    pub more_blocks_not_fetched: &'static str,
+    /// Heads the `--people-glossary` appendix
+    pub people: &'static str,
+    /// Heads the page properties section
+    pub properties: &'static str,
+    /// Precedes the count of properties `--max-properties` left out, as in
+    /// "…and 3 more properties not shown"
+    pub and: &'static str,
+    pub more_properties_not_shown: &'static str,
+    /// Marks a value `--max-property-chars` cut short, as in
+    /// "[truncated, 4000 characters in total]"
+    pub truncated: &'static str,
+    pub characters_in_total: &'static str,
+    /// Heads the page and database metadata section
+    pub metadata: &'static str,
+    pub page_id: &'static str,
+    pub database_id: &'static str,
+    /// Heads a database summary's schema table
+    pub schema: &'static str,
+    /// Column headings of the schema table
+    pub property_column: &'static str,
+    pub type_column: &'static str,
+    /// Heads a database summary's row count
+    pub data: &'static str,
+    pub database_has_no_rows: &'static str,
+    /// Precedes the row count, as in "Database contains 12 pages."
+    pub database_contains: &'static str,
+    pub pages: &'static str,
+    /// Follows the row count of a multi-source database, as in
+    /// "12 pages in 2 data sources"
+    pub pages_in: &'static str,
+    pub data_sources: &'static str,
+    /// Heads the `--auto-summary` section
+    pub summary: &'static str,
+    /// Labels the databases line of the `--auto-summary` section
+    pub databases: &'static str,
+    pub row: &'static str,
+    pub column: &'static str,
+    /// Marks a toggle or toggleable heading folded into one line
+    pub collapsed: &'static str,
+    pub blocks: &'static str,
+    pub characters: &'static str,
+    /// Precedes the columns `--exclude-empty-columns` left out
+    pub empty_columns_not_shown: &'static str,
+    /// `--expect-schema` drift, as in "missing property 'Due' (date)"
+    pub missing_property: &'static str,
+    /// `--expect-schema` drift, as in "unexpected property 'Notes' (rich_text)"
+    pub unexpected_property: &'static str,
+    /// `--expect-schema` drift, as in "property 'Due' is text, expected date"
+    pub property: &'static str,
+    pub is: &'static str,
+    pub expected: &'static str,
+}
+
+impl Messages {
+    /// The catalog for `language`.
+    pub fn for_language(language: Language) -> &'static Messages {
+        match language {
+            Language::En => &EN,
+            Language::De => &DE,
+            Language::Fi => &FI,
+        }
+    }
+}
+
+pub static EN: Messages = Messages {
+    table_of_contents: "Table of Contents",
+    no_headings_found: "No headings found",
+    breadcrumb: "Breadcrumb",
+    workspace: "Workspace",
+    image: "Image",
+    file: "File",
+    video: "Video",
+    audio: "Audio",
+    pdf: "PDF",
+    embed: "Embed",
+    provider_embed: "embed",
+    link_preview: "Link Preview",
+    bookmark: "Bookmark",
+    page: "Page",
+    database: "Database",
+    synced_from: "Synced from",
+    template: "Template",
+    unsupported_block: "Unsupported block type",
+    oversized_block: "Block too large to include",
+    child_database: "Child Database",
+    database_view: "Database view",
+    linked_database: "linked database — not retrievable via API",
+    not_fetched: "could not be fetched",
+    no_data: "No data available.",
+    no_properties: "Database has no properties defined.",
+    rows: "rows",
+    columns: "columns",
+    open_in_notion: "open in Notion",
+    see: "see",
+    above: "above",
+    untitled_database: "Untitled database",
+    no_access: "no access",
+    rows_without_access: "rows without access",
+    no_value: "No",
+    more_blocks_not_fetched: "more blocks not fetched",
+    people: "People",
+    properties: "Properties",
+    and: "and",
+    more_properties_not_shown: "more properties not shown",
+    truncated: "truncated",
+    characters_in_total: "characters in total",
+    metadata: "Metadata",
+    page_id: "Page ID",
+    database_id: "Database ID",
+    schema: "Schema",
+    property_column: "Property",
+    type_column: "Type",
+    data: "Data",
+    database_has_no_rows: "Database has no rows.",
+    database_contains: "Database contains",
+    pages: "pages",
+    pages_in: "pages in",
+    data_sources: "data sources",
+    summary: "Summary",
+    databases: "Databases",
+    row: "row",
+    column: "column",
+    collapsed: "collapsed",
+    blocks: "blocks",
+    characters: "characters",
+    empty_columns_not_shown: "Empty columns not shown",
+    missing_property: "missing property",
+    unexpected_property: "unexpected property",
+    property: "property",
+    is: "is",
+    expected: "expected",
+};
+
+pub static DE: Messages = Messages {
+    table_of_contents: "Inhaltsverzeichnis",
+    no_headings_found: "Keine Überschriften gefunden",
+    breadcrumb: "Navigationspfad",
+    workspace: "Arbeitsbereich",
+    image: "Bild",
+    file: "Datei",
+    video: "Video",
+    audio: "Audio",
+    pdf: "PDF",
+    embed: "Einbettung",
+    provider_embed: "Einbettung",
+    link_preview: "Linkvorschau",
+    bookmark: "Lesezeichen",
+    page: "Seite",
+    database: "Datenbank",
+    synced_from: "Synchronisiert von",
+    template: "Vorlage",
+    unsupported_block: "Nicht unterstützter Blocktyp",
+    oversized_block: "Block zu groß zum Einbinden",
+    child_database: "Untergeordnete Datenbank",
+    database_view: "Datenbankansicht",
+    linked_database: "verknüpfte Datenbank — über die API nicht abrufbar",
+    not_fetched: "konnte nicht abgerufen werden",
+    no_data: "Keine Daten vorhanden.",
+    no_properties: "Die Datenbank hat keine Eigenschaften.",
+    rows: "Zeilen",
+    columns: "Spalten",
+    open_in_notion: "in Notion öffnen",
+    see: "siehe",
+    above: "oben",
+    untitled_database: "Unbenannte Datenbank",
+    no_access: "kein Zugriff",
+    rows_without_access: "Zeilen ohne Zugriff",
+    no_value: "Ohne",
+    more_blocks_not_fetched: "weitere Blöcke nicht abgerufen",
+    people: "Personen",
+    properties: "Eigenschaften",
+    and: "und",
+    more_properties_not_shown: "weitere Eigenschaften ausgeblendet",
+    truncated: "gekürzt",
+    characters_in_total: "Zeichen insgesamt",
+    metadata: "Metadaten",
+    page_id: "Seiten-ID",
+    database_id: "Datenbank-ID",
+    schema: "Struktur",
+    property_column: "Eigenschaft",
+    type_column: "Typ",
+    data: "Daten",
+    database_has_no_rows: "Die Datenbank hat keine Zeilen.",
+    database_contains: "Die Datenbank enthält",
+    pages: "Seiten",
+    pages_in: "Seiten in",
+    data_sources: "Datenquellen",
+    summary: "Zusammenfassung",
+    databases: "Datenbanken",
+    row: "Zeile",
+    column: "Spalte",
+    collapsed: "eingeklappt",
+    blocks: "Blöcke",
+    characters: "Zeichen",
+    empty_columns_not_shown: "Leere Spalten ausgeblendet",
+    missing_property: "fehlende Eigenschaft",
+    unexpected_property: "unerwartete Eigenschaft",
+    property: "Eigenschaft",
+    is: "ist",
+    expected: "erwartet",
+};
+
+pub static FI: Messages = Messages {
+    table_of_contents: "Sisällysluettelo",
+    no_headings_found: "Otsikoita ei löytynyt",
+    breadcrumb: "Murupolku",
+    workspace: "Työtila",
+    image: "Kuva",
+    file: "Tiedosto",
+    video: "Video",
+    audio: "Ääni",
+    pdf: "PDF",
+    embed: "Upotus",
+    provider_embed: "upotus",
+    link_preview: "Linkin esikatselu",
+    bookmark: "Kirjanmerkki",
+    page: "Sivu",
+    database: "Tietokanta",
+    synced_from: "Synkronoitu lähteestä",
+    template: "Malli",
+    unsupported_block: "Tukematon lohkotyyppi",
+    oversized_block: "Lohko liian suuri sisällytettäväksi",
+    child_database: "Alitietokanta",
+    database_view: "Tietokantanäkymä",
+    linked_database: "linkitetty tietokanta — ei haettavissa API:n kautta",
+    not_fetched: "ei voitu hakea",
+    no_data: "Ei tietoja.",
+    no_properties: "Tietokannalla ei ole ominaisuuksia.",
+    rows: "riviä",
+    columns: "saraketta",
+    open_in_notion: "avaa Notionissa",
+    see: "katso",
+    above: "yllä",
+    untitled_database: "Nimetön tietokanta",
+    no_access: "ei käyttöoikeutta",
+    rows_without_access: "riviä ilman käyttöoikeutta",
+    no_value: "Ilman",
+    more_blocks_not_fetched: "lohkoa lisää hakematta",
+    people: "Henkilöt",
+    properties: "Ominaisuudet",
+    and: "ja",
+    more_properties_not_shown: "ominaisuutta lisää piilotettu",
+    truncated: "katkaistu",
+    characters_in_total: "merkkiä yhteensä",
+    metadata: "Metatiedot",
+    page_id: "Sivun tunnus",
+    database_id: "Tietokannan tunnus",
+    schema: "Rakenne",
+    property_column: "Ominaisuus",
+    type_column: "Tyyppi",
+    data: "Tiedot",
+    database_has_no_rows: "Tietokannassa ei ole rivejä.",
+    database_contains: "Tietokannassa on",
+    pages: "sivua",
+    pages_in: "sivua",
+    data_sources: "tietolähteessä",
+    summary: "Yhteenveto",
+    databases: "Tietokannat",
+    row: "rivi",
+    column: "sarake",
+    collapsed: "suljettu",
+    blocks: "lohkoa",
+    characters: "merkkiä",
+    empty_columns_not_shown: "Tyhjiä sarakkeita ei näytetä",
+    missing_property: "puuttuva ominaisuus",
+    unexpected_property: "odottamaton ominaisuus",
+    property: "ominaisuus",
+    is: "on",
+    expected: "odotettu",
+};
@@ -3,21 +3,96 @@
 
 // Sub-modules
 pub mod block_renderer;
+mod callouts;
+mod changes;
+mod code_language;
+mod database_titles;
 pub mod databases;
 pub mod direct_template;
+mod edited_since;
+mod embeds;
+mod grep;
+mod headings;
+#[allow(dead_code)] // Library API; the bin crate renders through block_renderer
+pub mod ir;
+mod link_index;
+pub mod markdown_check;
+mod messages;
+mod outline;
+mod paragraphs;
+mod people;
+pub mod plugins;
 mod properties;
+pub mod property_json;
+mod property_limits;
+mod provenance;
 mod pure_visitor;
+#[allow(dead_code)] // Library API
+pub mod records;
+pub mod redact;
 mod rich_text;
+mod row_filter;
+mod rules;
+mod sections;
 mod state;
+mod summary;
+mod template_partials;
+mod unfetched;
+mod whitespace;
 
 // --- Prompt Rendering (top-level entry point) ---
+#[allow(unused_imports)] // Library API
+pub use self::callouts::CalloutKind;
+pub use self::callouts::CalloutRule;
 #[allow(unused_imports)] // Used by bin crate
-pub use self::direct_template::render_prompt;
+pub use self::changes::annotate_changes;
+use self::database_titles::titled_databases;
+pub use self::database_titles::DatabaseTitleOverride;
+pub use self::databases::{ColumnAlign, RowSort};
+#[allow(unused_imports)] // Used by bin crate
+pub use self::direct_template::{render_prompt, render_prompt_to};
+use self::edited_since::edited_since;
+use self::grep::matching_blocks;
+pub(crate) use self::messages::Messages;
+#[allow(unused_imports)] // Used by bin crate
+pub use self::outline::{SectionOutline, SectionWeight};
+use self::paragraphs::merged_paragraphs;
+#[allow(unused_imports)] // Used by bin crate
+pub use self::property_limits::property_limit_warnings;
+#[allow(unused_imports)] // Used by bin crate
+pub use self::provenance::Provenance;
+use self::row_filter::rendered_rows;
+#[cfg(feature = "fetch")]
+pub use self::row_filter::row_selected;
+pub use self::row_filter::{RowPredicate, RowTitleGlob};
+use self::rules::rewritten_blocks;
+pub use self::rules::BlockRules;
+#[allow(unused_imports)] // Library API
+pub use self::sections::{compose_sections, PromptSection, SectionKind};
+#[allow(unused_imports)] // Used by bin crate
+pub(crate) use self::unfetched::has_unfetched;
+use self::unfetched::marked_unfetched;
 
+use crate::config::PipelineConfig;
 use crate::model::{Database, NotionObject};
 use crate::types::NotionId;
+use std::borrow::Cow;
 use std::collections::HashMap;
 
+/// `object` as every output renders it: rows chosen by `--row` and
+/// `--drop-rows`, `--rules` applied, `--since`, `--merge-paragraphs` and
+/// `--grep` applied, markers where the fetch cut blocks off, and `--db-title`
+/// names. Borrowed unchanged when none of them apply.
+pub(crate) fn prepared_content<'a>(
+    object: &'a NotionObject,
+    config: &PipelineConfig,
+) -> Cow<'a, NotionObject> {
+    let object = rewritten_blocks(rendered_rows(object, config), config);
+    let object = merged_paragraphs(edited_since(object, config), config);
+    let object = marked_unfetched(matching_blocks(object, config), config);
+    titled_databases(object, config)
+}
+
 /// Gathers all embedded databases from a NotionObject tree.
 pub fn gather_embedded_databases(obj: &NotionObject) -> HashMap<NotionId, Database> {
     let mut databases = HashMap::new();
@@ -30,7 +105,7 @@ fn walk_tree_for_databases(obj: &NotionObject, databases: &mut HashMap<NotionId,
     match obj {
         NotionObject::Database(db) => {
             databases.insert(NotionId::from(&db.id), db.clone());
-            for page in &db.pages {
+            for page in db.rows() {
                 let page_obj = NotionObject::Page(page.clone());
                 walk_tree_for_databases(&page_obj, databases);
             }
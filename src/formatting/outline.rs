@@ -0,0 +1,140 @@
+// src/formatting/outline.rs
+//! Size weights of the prompt's sections for `--outline-file`.
+//!
+//! Each rendered document is split at its top-level headings, and every
+//! section is measured in characters and estimated tokens. The outline
+//! ranks sections from heaviest to lightest so tooling that packs prompts
+//! into a context window can decide what to keep.
+
+use super::sections::{compose_sections, PromptSection};
+use crate::analytics::estimate_tokens;
+use crate::config::PipelineConfig;
+use crate::error::AppError;
+use crate::model::NotionObject;
+use serde::Serialize;
+use std::ops::ControlFlow;
+
+/// The prompt's sections ranked by estimated tokens, heaviest first.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectionOutline {
+    pub total_chars: usize,
+    pub total_tokens: usize,
+    pub sections: Vec<SectionWeight>,
+}
+
+/// The size of one section of a rendered document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectionWeight {
+    /// 1 for the heaviest section
+    pub rank: usize,
+    /// The file the section belongs to, as named in the prompt
+    pub document: String,
+    /// The heading text, or the document title for content before the first heading
+    pub heading: String,
+    /// Heading level 1–3, or 0 for content before the first heading
+    pub level: u8,
+    pub chars: usize,
+    pub tokens: usize,
+}
+
+impl SectionOutline {
+    /// Renders `object` as the prompt would and measures every section.
+    pub fn measure(object: &NotionObject, config: &PipelineConfig) -> Result<Self, AppError> {
+        let mut sections = Vec::new();
+        compose_sections(object, config, &mut |section| {
+            sections.push(weigh(section));
+            ControlFlow::Continue(())
+        })?;
+        sections.sort_by(|a, b| b.tokens.cmp(&a.tokens).then(b.chars.cmp(&a.chars)));
+        for (index, section) in sections.iter_mut().enumerate() {
+            section.rank = index + 1;
+        }
+
+        Ok(Self {
+            total_chars: sections.iter().map(|s| s.chars).sum(),
+            total_tokens: sections.iter().map(|s| s.tokens).sum(),
+            sections,
+        })
+    }
+}
+
+fn weigh(section: PromptSection) -> SectionWeight {
+    SectionWeight {
+        rank: 0,
+        document: section.document,
+        heading: section.heading,
+        level: section.level,
+        chars: section.markdown.chars().count(),
+        tokens: estimate_tokens(&section.markdown),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::testing::test_page;
+    use crate::model::{Block, BlockCommon, Heading2Block, ParagraphBlock, TextBlockContent};
+    use crate::types::{BlockId, Color, RichTextItem};
+
+    fn text(content: &str) -> TextBlockContent {
+        TextBlockContent {
+            rich_text: vec![RichTextItem::plain_text(content)],
+            color: Color::Default,
+        }
+    }
+
+    fn common() -> BlockCommon {
+        BlockCommon {
+            id: BlockId::new_v4(),
+            children: vec![],
+            has_children: false,
+            archived: false,
+            last_edited_time: None,
+            unfetched_children: None,
+        }
+    }
+
+    #[test]
+    fn test_sections_split_at_headings_and_rank_by_size() {
+        let page = test_page("Handbook").with_blocks(vec![
+            Block::Paragraph(ParagraphBlock {
+                common: common(),
+                content: text("Welcome."),
+            }),
+            Block::Heading2(Heading2Block {
+                common: common(),
+                content: text("Onboarding"),
+            }),
+            Block::Paragraph(ParagraphBlock {
+                common: common(),
+                content: text(&"Read the docs. ".repeat(40)),
+            }),
+            Block::Heading2(Heading2Block {
+                common: common(),
+                content: text("Contacts"),
+            }),
+        ]);
+        let config = PipelineConfig {
+            include_properties: false,
+            ..Default::default()
+        };
+
+        let outline = SectionOutline::measure(&NotionObject::Page(page), &config).unwrap();
+
+        let ranked: Vec<_> = outline
+            .sections
+            .iter()
+            .map(|s| (s.rank, s.heading.as_str(), s.level))
+            .collect();
+        assert_eq!(ranked[0], (1, "Onboarding", 2));
+        assert_eq!(outline.sections.len(), 3);
+        assert!(outline
+            .sections
+            .iter()
+            .any(|s| s.heading == "Handbook" && s.level == 0));
+        assert_eq!(
+            outline.total_chars,
+            outline.sections.iter().map(|s| s.chars).sum::<usize>()
+        );
+    }
+}
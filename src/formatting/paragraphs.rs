@@ -0,0 +1,184 @@
+// src/formatting/paragraphs.rs
+//! Compose-time merging of split paragraphs for `--merge-paragraphs`.
+//!
+//! Pasting text into Notion turns every line into its own paragraph block,
+//! which renders as a run of one-line paragraphs separated by blank lines.
+//! This pass joins consecutive plain paragraphs (unstyled text, no links,
+//! no children) into one. Empty paragraphs stay, so deliberate breaks
+//! between groups of lines survive.
+
+use crate::config::PipelineConfig;
+use crate::model::blocks::ChildDatabaseContent;
+use crate::model::{Block, Database, NotionObject, ParagraphBlock};
+use crate::types::{Annotations, Color, RichTextItem, RichTextType};
+use std::borrow::Cow;
+
+/// `object` with split paragraphs merged when `--merge-paragraphs` is on,
+/// passed through unchanged otherwise.
+pub fn merged_paragraphs<'a>(
+    object: Cow<'a, NotionObject>,
+    config: &PipelineConfig,
+) -> Cow<'a, NotionObject> {
+    if !config.merge_paragraphs {
+        return object;
+    }
+    let mut object = object.into_owned();
+    let merged = match &mut object {
+        NotionObject::Page(page) => merge_in_blocks(&mut page.blocks),
+        NotionObject::Database(db) => merge_in_database(db),
+        NotionObject::Block(block) => merge_in_blocks(block.children_mut()),
+    };
+    if merged > 0 {
+        log::info!("Merged {} split paragraphs into their predecessors", merged);
+    }
+    Cow::Owned(object)
+}
+
+/// Merges runs of plain paragraphs in `blocks` and everything below them;
+/// returns how many paragraphs were merged away.
+fn merge_in_blocks(blocks: &mut Vec<Block>) -> usize {
+    let mut merged = 0;
+    let mut kept: Vec<Block> = Vec::with_capacity(blocks.len());
+    for mut block in blocks.drain(..) {
+        if let Block::ChildDatabase(child) = &mut block {
+            if let ChildDatabaseContent::Fetched(db) = &mut child.content {
+                merged += merge_in_database(db);
+            }
+        }
+        merged += merge_in_blocks(block.children_mut());
+
+        match (kept.last_mut(), &block) {
+            (Some(Block::Paragraph(previous)), Block::Paragraph(next))
+                if is_plain(previous) && is_plain(next) =>
+            {
+                let text = &mut previous.content.rich_text;
+                if !ends_with_space(text) {
+                    text.push(RichTextItem::plain_text(" "));
+                }
+                text.extend(next.content.rich_text.iter().cloned());
+                merged += 1;
+            }
+            _ => kept.push(block),
+        }
+    }
+    *blocks = kept;
+    merged
+}
+
+fn merge_in_database(db: &mut Database) -> usize {
+    let mut merged = 0;
+    for row in &mut db.pages {
+        merged += merge_in_blocks(&mut row.blocks);
+    }
+    for source in &mut db.data_sources {
+        for row in &mut source.pages {
+            merged += merge_in_blocks(&mut row.blocks);
+        }
+    }
+    merged
+}
+
+/// Non-empty, default-colored, childless, and only unstyled text without links.
+fn is_plain(paragraph: &ParagraphBlock) -> bool {
+    let text = &paragraph.content.rich_text;
+    paragraph.content.color == Color::Default
+        && !paragraph.common.has_children
+        && paragraph.common.children.is_empty()
+        && text.iter().any(|item| !item.plain_text.trim().is_empty())
+        && text.iter().all(|item| {
+            item.annotations == Annotations::default()
+                && item.href.is_none()
+                && matches!(item.text_type, RichTextType::Text { link: None, .. })
+        })
+}
+
+fn ends_with_space(text: &[RichTextItem]) -> bool {
+    text.last()
+        .is_some_and(|item| item.plain_text.ends_with(char::is_whitespace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::testing::test_page;
+    use crate::model::{BlockCommon, TextBlockContent};
+    use crate::types::BlockId;
+
+    fn paragraph(text: &[RichTextItem]) -> Block {
+        Block::Paragraph(ParagraphBlock {
+            common: BlockCommon {
+                id: BlockId::new_v4(),
+                children: vec![],
+                has_children: false,
+                archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
+            },
+            content: TextBlockContent {
+                rich_text: text.to_vec(),
+                color: Color::Default,
+            },
+        })
+    }
+
+    fn plain(text: &str) -> Block {
+        paragraph(&[RichTextItem::plain_text(text)])
+    }
+
+    fn text_of(block: &Block) -> String {
+        match block {
+            Block::Paragraph(p) => p
+                .content
+                .rich_text
+                .iter()
+                .map(|i| i.plain_text.as_str())
+                .collect(),
+            _ => panic!("expected a paragraph"),
+        }
+    }
+
+    #[test]
+    fn test_consecutive_plain_paragraphs_merge() {
+        let mut bold = RichTextItem::plain_text("Important");
+        bold.annotations.bold = true;
+        let mut blocks = vec![
+            plain("Pasted line one"),
+            plain("line two "),
+            plain("line three"),
+            plain(""),
+            plain("Next group"),
+            paragraph(&[bold]),
+            plain("After styling"),
+        ];
+
+        assert_eq!(merge_in_blocks(&mut blocks), 2);
+        let texts: Vec<String> = blocks.iter().map(text_of).collect();
+        assert_eq!(
+            texts,
+            [
+                "Pasted line one line two line three",
+                "",
+                "Next group",
+                "Important",
+                "After styling"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merging_is_opt_in() {
+        let page = NotionObject::Page(test_page("Notes").with_blocks(vec![plain("a"), plain("b")]));
+
+        let off = merged_paragraphs(Cow::Borrowed(&page), &PipelineConfig::default());
+        assert!(matches!(off, Cow::Borrowed(_)));
+
+        let config = PipelineConfig {
+            merge_paragraphs: true,
+            ..Default::default()
+        };
+        match merged_paragraphs(Cow::Borrowed(&page), &config).as_ref() {
+            NotionObject::Page(page) => assert_eq!(page.blocks.len(), 1),
+            other => panic!("expected a page, got {:?}", other),
+        }
+    }
+}
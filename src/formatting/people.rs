@@ -0,0 +1,236 @@
+// src/formatting/people.rs
+//! A `## People` appendix for `--people-glossary`: who is who in the prompt.
+//!
+//! Meeting notes and task lists name people by mention (`@Ada Lovelace`),
+//! which gives a model a name and nothing else to go on. The glossary lists
+//! every user the rendered content mentions, in rich text or in a people,
+//! created-by or last-edited-by property, once each and by name, with the
+//! email address where Notion gives one. Notion does not expose workspace
+//! roles to integrations, so the email is all there is besides the name.
+
+use super::messages::Messages;
+use super::rich_text::{format_rich_text_items, MentionContent, TextContent};
+use super::rules::rich_text;
+use crate::model::blocks::ChildDatabaseContent;
+use crate::model::{Block, Database, NotionObject, Page, PropertyTypeValue};
+use crate::types::{RichTextItem, User};
+use std::collections::HashMap;
+
+/// A user the content mentions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Person {
+    /// Unknown when Notion gave only the user ID
+    name: Option<String>,
+    email: Option<String>,
+}
+
+/// The users mentioned in one prompt, by user ID.
+#[derive(Debug, Default)]
+pub struct PeopleGlossary {
+    people: HashMap<String, Person>,
+}
+
+impl PeopleGlossary {
+    /// Every user mentioned in `object`.
+    pub fn of(object: &NotionObject) -> Self {
+        let mut glossary = Self::default();
+        match object {
+            NotionObject::Page(page) => glossary.add_page(page),
+            NotionObject::Database(db) => glossary.add_database(db),
+            NotionObject::Block(block) => glossary.add_blocks(std::slice::from_ref(block)),
+        }
+        glossary
+    }
+
+    fn add_page(&mut self, page: &Page) {
+        for property in page.properties.values() {
+            match &property.type_specific_value {
+                PropertyTypeValue::Title { title: text }
+                | PropertyTypeValue::RichText { rich_text: text } => self.add_rich_text(text),
+                PropertyTypeValue::People { people } => {
+                    people.iter().for_each(|user| self.add_user(user))
+                }
+                PropertyTypeValue::CreatedBy { created_by: user }
+                | PropertyTypeValue::LastEditedBy {
+                    last_edited_by: user,
+                } => self.add_user(user),
+                _ => {}
+            }
+        }
+        self.add_blocks(&page.blocks);
+    }
+
+    fn add_database(&mut self, db: &Database) {
+        db.rows().for_each(|row| self.add_page(row));
+    }
+
+    fn add_blocks(&mut self, blocks: &[Block]) {
+        for block in blocks {
+            for text in rich_text(block) {
+                self.add_rich_text(text);
+            }
+            if let Block::ChildDatabase(child) = block {
+                if let ChildDatabaseContent::Fetched(db) = &child.content {
+                    self.add_database(db);
+                }
+            }
+            self.add_blocks(block.children());
+        }
+    }
+
+    /// Adds the user mentions of `text`, named as the renderer names them.
+    fn add_rich_text(&mut self, text: &[RichTextItem]) {
+        let Ok(formatted) = format_rich_text_items(text) else {
+            return;
+        };
+        for segment in formatted.segments {
+            if let TextContent::Mention(MentionContent::User { id, name }) = segment.content {
+                self.add(id, Some(name), None);
+            }
+        }
+    }
+
+    fn add_user(&mut self, user: &User) {
+        self.add(user.id.clone(), user.name.clone(), user.email.clone());
+    }
+
+    /// Records `id`, filling in its name and email if still unknown. The
+    /// renderer names an unnamed mention by its ID, which does not count.
+    fn add(&mut self, id: String, name: Option<String>, email: Option<String>) {
+        let name = name.filter(|name| *name != id);
+        let person = self.people.entry(id).or_default();
+        person.name = person.name.take().or(name);
+        person.email = person.email.take().or(email);
+    }
+
+    /// The `## People` section, sorted by name, or nothing when no one was
+    /// mentioned.
+    pub fn appendix(&self, messages: &Messages) -> String {
+        if self.people.is_empty() {
+            return String::new();
+        }
+        let mut people: Vec<(&str, Option<&str>)> = self
+            .people
+            .iter()
+            .map(|(id, person)| {
+                let name = person.name.as_deref().unwrap_or(id);
+                (name, person.email.as_deref())
+            })
+            .collect();
+        people.sort_by_key(|(name, email)| (name.to_lowercase(), *email));
+        let mut out = format!("## {}\n\n", messages.people);
+        for (name, email) in people {
+            match email {
+                Some(email) => out.push_str(&format!("- @{} — {}\n", name, email)),
+                None => out.push_str(&format!("- @{}\n", name)),
+            }
+        }
+        out
+    }
+}
+
+/// Appends the glossary of `object` to the root document, as
+/// `--link-style reference` appends its links.
+pub fn append_people_glossary(
+    object: &NotionObject,
+    messages: &Messages,
+    main_content: &mut String,
+    root: Option<&mut String>,
+) {
+    let glossary = PeopleGlossary::of(object);
+    let appendix = glossary.appendix(messages);
+    if appendix.is_empty() {
+        return;
+    }
+    log::info!("Listed {} mentioned people", glossary.people.len());
+    for document in std::iter::once(main_content).chain(root) {
+        if !document.is_empty() && !document.ends_with("\n\n") {
+            document.push_str(if document.ends_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+        document.push_str(&appendix);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::testing::test_page;
+    use crate::model::{BlockCommon, ParagraphBlock, PropertyValue, TextBlockContent};
+    use crate::types::{
+        Annotations, BlockId, Color, MentionData, MentionType, PartialUser, PropertyName,
+        RichTextType,
+    };
+
+    const ADA: &str = "8a1c2f4e-0000-4000-8000-000000000001";
+    const GRACE: &str = "8a1c2f4e-0000-4000-8000-000000000002";
+
+    fn mention(id: &str, name: Option<&str>) -> RichTextItem {
+        RichTextItem {
+            text_type: RichTextType::Mention(MentionData {
+                mention_type: MentionType::User {
+                    user: PartialUser {
+                        id: id.to_string(),
+                        name: name.map(str::to_string),
+                        avatar_url: None,
+                    },
+                },
+            }),
+            annotations: Annotations::default(),
+            plain_text: format!("@{}", name.unwrap_or("Anonymous")),
+            href: None,
+        }
+    }
+
+    #[test]
+    fn test_mentioned_users_are_listed_once_by_name() {
+        let paragraph = Block::Paragraph(ParagraphBlock {
+            common: BlockCommon::new(BlockId::new_v4()),
+            content: TextBlockContent {
+                rich_text: vec![
+                    mention(GRACE, Some("Grace Hopper")),
+                    RichTextItem::plain_text(" and "),
+                    mention(ADA, None),
+                    RichTextItem::plain_text(" to follow up with "),
+                    mention(GRACE, Some("Grace Hopper")),
+                ],
+                color: Color::Default,
+            },
+        });
+        let owner = PropertyValue {
+            id: PropertyName::new("Owner"),
+            type_specific_value: PropertyTypeValue::People {
+                people: vec![User {
+                    id: ADA.to_string(),
+                    name: Some("Ada Lovelace".to_string()),
+                    avatar_url: None,
+                    email: Some("ada@example.com".to_string()),
+                }],
+            },
+        };
+        let page = NotionObject::Page(
+            test_page("Standup")
+                .with_blocks(vec![paragraph])
+                .with_properties([(PropertyName::new("Owner"), owner)].into_iter().collect()),
+        );
+
+        assert_eq!(
+            PeopleGlossary::of(&page).appendix(&crate::formatting::messages::EN),
+            "## People\n\n- @Ada Lovelace — ada@example.com\n- @Grace Hopper\n"
+        );
+        let nobody = NotionObject::Block(Block::Paragraph(ParagraphBlock {
+            common: BlockCommon::new(BlockId::new_v4()),
+            content: TextBlockContent {
+                rich_text: vec![RichTextItem::plain_text("No one")],
+                color: Color::Default,
+            },
+        }));
+        assert_eq!(
+            PeopleGlossary::of(&nobody).appendix(&crate::formatting::messages::EN),
+            ""
+        );
+    }
+}
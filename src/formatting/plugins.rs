@@ -0,0 +1,90 @@
+// src/formatting/plugins.rs
+//! Compose-phase plugins: hooks the composer calls while rendering.
+//!
+//! A [`ComposePlugin`] sees each page, top-level block and database as it is
+//! rendered and the finished prompt at the end, so an embedder can add
+//! numbering, annotations or custom sections without forking the renderer.
+//! Plugins are registered on the configuration with
+//! [`PipelineConfig::with_plugin`](crate::config::PipelineConfig::with_plugin)
+//! and run in registration order.
+
+use crate::model::{Block, Database, Page};
+use std::fmt;
+use std::sync::Arc;
+
+/// Hooks into prompt composition. Every hook defaults to doing nothing, so
+/// hooks added in later releases leave existing plugins working.
+pub trait ComposePlugin: Send + Sync {
+    /// Called before a page is composed; whatever is written to `out` opens
+    /// the page, ahead of its title.
+    fn before_page(&self, _page: &Page, _out: &mut String) {}
+
+    /// Called after each top-level block of a page is rendered, with the
+    /// block's markdown in `out`.
+    fn after_block(&self, _block: &Block, _out: &mut String) {}
+
+    /// Called before a database is rendered, as a table or as a summary;
+    /// whatever is written to `out` precedes it.
+    fn before_database(&self, _database: &Database, _out: &mut String) {}
+
+    /// Called once with the finished prompt, after the template has run.
+    fn finalize(&self, _prompt: &mut String) {}
+}
+
+/// The plugins registered on a configuration, in registration order.
+#[derive(Clone, Default)]
+pub struct ComposePlugins {
+    plugins: Vec<Arc<dyn ComposePlugin>>,
+}
+
+/// What a configuration without plugins refers to.
+pub(crate) static NO_PLUGINS: ComposePlugins = ComposePlugins {
+    plugins: Vec::new(),
+};
+
+impl fmt::Debug for ComposePlugins {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ComposePlugins({})", self.plugins.len())
+    }
+}
+
+impl ComposePlugins {
+    /// Appends a plugin; it runs after those registered before it.
+    pub fn push(&mut self, plugin: Arc<dyn ComposePlugin>) {
+        self.plugins.push(plugin);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// The text the plugins open `page` with.
+    pub fn before_page(&self, page: &Page) -> String {
+        let mut out = String::new();
+        for plugin in &self.plugins {
+            plugin.before_page(page, &mut out);
+        }
+        out
+    }
+
+    pub fn after_block(&self, block: &Block, out: &mut String) {
+        for plugin in &self.plugins {
+            plugin.after_block(block, out);
+        }
+    }
+
+    /// The text the plugins put ahead of `database`.
+    pub fn before_database(&self, database: &Database) -> String {
+        let mut out = String::new();
+        for plugin in &self.plugins {
+            plugin.before_database(database, &mut out);
+        }
+        out
+    }
+
+    pub fn finalize(&self, prompt: &mut String) {
+        for plugin in &self.plugins {
+            plugin.finalize(prompt);
+        }
+    }
+}
@@ -29,6 +29,12 @@ pub fn render_property_value(value: Option<&PropertyValue>) -> Result<String, Ap
     }
 }
 
+/// Renders a property value as plain text, without markdown links or
+/// checkbox symbols.
+pub fn render_property_text(value: &PropertyValue) -> Result<String, AppError> {
+    Ok(format_property_value(value)?.render_text())
+}
+
 /// Formats a property for display in a table cell.
 #[allow(dead_code)]
 pub fn format_property_for_table(value: Option<&PropertyValue>) -> Result<String, AppError> {
@@ -0,0 +1,389 @@
+// src/formatting/property_json.rs
+//! Typed property values as JSON, for `export-workspace --properties-json`.
+//!
+//! The markdown export renders properties as text for people and models to
+//! read; automation wants the values themselves. [`PropertiesFile`] holds
+//! the properties of every page in an exported object, each value tagged
+//! with its Notion type. The schema is versioned by
+//! [`PROPERTIES_SCHEMA_VERSION`] and only grows: new fields and types may
+//! appear, existing ones keep their names and shapes.
+
+use crate::model::{
+    Block, ChildDatabaseContent, Database, NotionObject, Page, PropertyTypeValue, PropertyValue,
+};
+use crate::types::{DateValue, FormulaResult, RollupArrayItem, RollupResult, User};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// The version written to every properties file. Bumped only for changes
+/// that would break an existing reader.
+pub const PROPERTIES_SCHEMA_VERSION: u32 = 1;
+
+/// The properties of every page in one exported object.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PropertiesFile {
+    pub schema_version: u32,
+    /// The page itself, or the rows of a database, then the rows of every
+    /// embedded child database, depth first
+    pub pages: Vec<PageProperties>,
+}
+
+/// One page's properties, keyed by property name.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PageProperties {
+    pub id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// The database this page is a row of
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database_id: Option<String>,
+    pub properties: BTreeMap<String, TypedValue>,
+}
+
+/// A property value tagged with its Notion type, serialized as
+/// `{"type": "select", "value": "Done"}`. Empty values are `null` or `[]`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum TypedValue {
+    Title(String),
+    RichText(String),
+    Number(Option<f64>),
+    Select(Option<String>),
+    MultiSelect(Vec<String>),
+    Status(Option<String>),
+    Date(Option<DateRange>),
+    Formula(Option<Scalar>),
+    /// Page IDs
+    Relation(Vec<String>),
+    Rollup(Option<Scalar>),
+    People(Vec<Person>),
+    Files(Vec<FileLink>),
+    Checkbox(bool),
+    Url(Option<String>),
+    Email(Option<String>),
+    PhoneNumber(Option<String>),
+    CreatedTime(chrono::DateTime<chrono::Utc>),
+    CreatedBy(Person),
+    LastEditedTime(chrono::DateTime<chrono::Utc>),
+    LastEditedBy(Person),
+    UniqueId(UniqueId),
+    /// The verification state, e.g. `verified`
+    Verification(Option<String>),
+}
+
+/// A formula result or rollup value, serialized as the bare JSON value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Scalar {
+    Text(String),
+    Number(f64),
+    Boolean(bool),
+    Date(DateRange),
+    List(Vec<Scalar>),
+}
+
+/// A date or date range, as ISO 8601 dates.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DateRange {
+    pub start: chrono::NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<chrono::NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+}
+
+/// A Notion user.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Person {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+/// An attached or linked file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileLink {
+    pub name: String,
+    pub url: String,
+}
+
+/// A unique ID property, e.g. `TASK-42`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UniqueId {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    pub number: i64,
+}
+
+impl PropertiesFile {
+    /// Collects the properties of every page in `object`. A block has none.
+    pub fn from_object(object: &NotionObject) -> Self {
+        let mut pages = Vec::new();
+        match object {
+            NotionObject::Page(page) => collect_page(page, None, &mut pages),
+            NotionObject::Database(db) => collect_rows(db, &mut pages),
+            NotionObject::Block(block) => collect_nested(std::slice::from_ref(block), &mut pages),
+        }
+        Self {
+            schema_version: PROPERTIES_SCHEMA_VERSION,
+            pages,
+        }
+    }
+}
+
+impl From<&PropertyValue> for TypedValue {
+    fn from(value: &PropertyValue) -> Self {
+        use PropertyTypeValue as V;
+        match &value.type_specific_value {
+            V::Title { title } => {
+                Self::Title(title.iter().map(|t| t.plain_text.as_str()).collect())
+            }
+            V::RichText { rich_text } => {
+                Self::RichText(rich_text.iter().map(|t| t.plain_text.as_str()).collect())
+            }
+            V::Number { number } => Self::Number(*number),
+            V::Select { select } => Self::Select(select.as_ref().map(|s| s.name.clone())),
+            V::MultiSelect { multi_select } => {
+                Self::MultiSelect(multi_select.iter().map(|s| s.name.clone()).collect())
+            }
+            V::Status { status } => Self::Status(status.as_ref().map(|s| s.name.clone())),
+            V::Date { date } => Self::Date(date.as_ref().map(DateRange::from)),
+            V::Formula { formula } => Self::Formula(Some(match formula {
+                FormulaResult::String(s) => Scalar::Text(s.clone()),
+                FormulaResult::Number(n) => Scalar::Number(*n),
+                FormulaResult::Boolean(b) => Scalar::Boolean(*b),
+                FormulaResult::Date(d) => Scalar::Date(d.into()),
+            })),
+            V::Relation { relation } => {
+                Self::Relation(relation.iter().map(|id| id.as_str().to_string()).collect())
+            }
+            V::Rollup { rollup } => Self::Rollup(rollup_scalar(rollup)),
+            V::People { people } => Self::People(people.iter().map(Person::from).collect()),
+            V::Files { files } => Self::Files(
+                files
+                    .iter()
+                    .map(|f| FileLink {
+                        name: f.name.clone(),
+                        url: f.url.clone(),
+                    })
+                    .collect(),
+            ),
+            V::Checkbox { checkbox } => Self::Checkbox(*checkbox),
+            V::Url { url } => Self::Url(url.clone()),
+            V::Email { email } => Self::Email(email.clone()),
+            V::PhoneNumber { phone_number } => Self::PhoneNumber(phone_number.clone()),
+            V::CreatedTime { created_time } => Self::CreatedTime(*created_time),
+            V::CreatedBy { created_by } => Self::CreatedBy(created_by.into()),
+            V::LastEditedTime { last_edited_time } => Self::LastEditedTime(*last_edited_time),
+            V::LastEditedBy { last_edited_by } => Self::LastEditedBy(last_edited_by.into()),
+            V::UniqueID { unique_id } => Self::UniqueId(UniqueId {
+                prefix: unique_id.prefix.clone(),
+                number: unique_id.number,
+            }),
+            V::Verification { verification } => {
+                Self::Verification(verification.as_ref().map(|v| v.state.clone()))
+            }
+        }
+    }
+}
+
+impl From<&DateValue> for DateRange {
+    fn from(date: &DateValue) -> Self {
+        Self {
+            start: date.start,
+            end: date.end,
+            time_zone: date.time_zone.clone(),
+        }
+    }
+}
+
+impl From<&User> for Person {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id.clone(),
+            name: user.name.clone(),
+            email: user.email.clone(),
+        }
+    }
+}
+
+/// The rollup's value; `None` when it is empty or Notion could not compute it.
+fn rollup_scalar(rollup: &RollupResult) -> Option<Scalar> {
+    match rollup {
+        RollupResult::Number { number } => number.map(Scalar::Number),
+        RollupResult::Date { date } => date.as_ref().map(|d| Scalar::Date(d.into())),
+        RollupResult::String { string } => string.clone().map(Scalar::Text),
+        RollupResult::Boolean { boolean } => boolean.map(Scalar::Boolean),
+        RollupResult::Array { array } => Some(Scalar::List(
+            array
+                .iter()
+                .map(|item| match item {
+                    RollupArrayItem::Title(title) => {
+                        Scalar::Text(title.iter().map(|t| t.plain_text.as_str()).collect())
+                    }
+                    RollupArrayItem::Number(n) => Scalar::Number(*n),
+                    RollupArrayItem::Date(d) => Scalar::Date(d.into()),
+                    RollupArrayItem::Text(s) => Scalar::Text(s.clone()),
+                })
+                .collect(),
+        )),
+        RollupResult::Unsupported { .. } | RollupResult::Incomplete { .. } => None,
+    }
+}
+
+fn collect_page(page: &Page, database_id: Option<String>, pages: &mut Vec<PageProperties>) {
+    pages.push(PageProperties {
+        id: page.id.as_str().to_string(),
+        title: page.title().as_str().to_string(),
+        url: Some(page.url.clone()).filter(|url| !url.is_empty()),
+        database_id,
+        properties: page
+            .properties
+            .iter()
+            .map(|(name, value)| (name.as_str().to_string(), value.into()))
+            .collect(),
+    });
+    collect_nested(&page.blocks, pages);
+}
+
+fn collect_rows(db: &Database, pages: &mut Vec<PageProperties>) {
+    let database_id = Some(db.id.as_str().to_string());
+    for row in db.rows() {
+        collect_page(row, database_id.clone(), pages);
+    }
+}
+
+fn collect_nested(blocks: &[Block], pages: &mut Vec<PageProperties>) {
+    for block in blocks {
+        if let Block::ChildDatabase(b) = block {
+            if let ChildDatabaseContent::Fetched(db) = &b.content {
+                collect_rows(db, pages);
+            }
+        }
+        collect_nested(block.children(), pages);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::testing::{test_database, test_page};
+    use crate::model::BlockCommon;
+    use crate::model::ChildDatabaseBlock;
+    use crate::types::SelectOption;
+    use crate::types::{BlockId, Color, PropertyName, RichTextItem};
+    use serde_json::json;
+
+    fn property(value: PropertyTypeValue) -> PropertyValue {
+        PropertyValue {
+            id: PropertyName::new("id"),
+            type_specific_value: value,
+        }
+    }
+
+    fn row(title: &str, properties: Vec<(&str, PropertyTypeValue)>) -> Page {
+        test_page(title).with_properties(
+            properties
+                .into_iter()
+                .map(|(name, value)| (PropertyName::new(name), property(value)))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_values_are_tagged_with_their_type() {
+        let cases = [
+            (
+                PropertyTypeValue::Title {
+                    title: vec![
+                        RichTextItem::plain_text("Ship "),
+                        RichTextItem::plain_text("it"),
+                    ],
+                },
+                json!({"type": "title", "value": "Ship it"}),
+            ),
+            (
+                PropertyTypeValue::Number { number: Some(3.5) },
+                json!({"type": "number", "value": 3.5}),
+            ),
+            (
+                PropertyTypeValue::Status {
+                    status: Some(SelectOption {
+                        id: "s".to_string(),
+                        name: "Done".to_string(),
+                        color: Color::Green,
+                    }),
+                },
+                json!({"type": "status", "value": "Done"}),
+            ),
+            (
+                PropertyTypeValue::Select { select: None },
+                json!({"type": "select", "value": null}),
+            ),
+            (
+                PropertyTypeValue::Date {
+                    date: Some(DateValue {
+                        start: chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+                        end: None,
+                        time_zone: None,
+                    }),
+                },
+                json!({"type": "date", "value": {"start": "2024-05-01"}}),
+            ),
+            (
+                PropertyTypeValue::Formula {
+                    formula: FormulaResult::Boolean(true),
+                },
+                json!({"type": "formula", "value": true}),
+            ),
+            (
+                PropertyTypeValue::Rollup {
+                    rollup: RollupResult::Array {
+                        array: vec![
+                            RollupArrayItem::Number(1.0),
+                            RollupArrayItem::Text("a".into()),
+                        ],
+                    },
+                },
+                json!({"type": "rollup", "value": [1.0, "a"]}),
+            ),
+            (
+                PropertyTypeValue::Checkbox { checkbox: false },
+                json!({"type": "checkbox", "value": false}),
+            ),
+        ];
+        for (value, expected) in cases {
+            let typed = TypedValue::from(&property(value));
+            assert_eq!(serde_json::to_value(&typed).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_database_rows_and_nested_rows_are_collected() {
+        let nested = test_database("Subtasks").with_pages(vec![row("Sub", vec![])]);
+        let mut task = row(
+            "Task",
+            vec![("Done", PropertyTypeValue::Checkbox { checkbox: true })],
+        );
+        task.blocks.push(Block::ChildDatabase(ChildDatabaseBlock {
+            common: BlockCommon::new(BlockId::new_v4()),
+            title: "Subtasks".to_string(),
+            content: ChildDatabaseContent::Fetched(Box::new(nested.clone())),
+        }));
+        let db = test_database("Tasks").with_pages(vec![task]);
+
+        let file = PropertiesFile::from_object(&NotionObject::Database(db.clone()));
+        assert_eq!(file.schema_version, PROPERTIES_SCHEMA_VERSION);
+        let titles: Vec<&str> = file.pages.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["Task", "Sub"]);
+        assert_eq!(file.pages[0].database_id.as_deref(), Some(db.id.as_str()));
+        assert_eq!(
+            file.pages[1].database_id.as_deref(),
+            Some(nested.id.as_str())
+        );
+        assert_eq!(file.pages[0].properties["Done"], TypedValue::Checkbox(true));
+    }
+}
@@ -0,0 +1,207 @@
+// src/formatting/property_limits.rs
+//! Guardrails for pages with very many or very large properties.
+//!
+//! `--max-properties` caps how many properties a page lists (the rest are
+//! summarized as a count) and how many columns a database table keeps.
+//! `--max-property-chars` truncates single values with a marker that gives
+//! the full length. [`property_limit_warnings`] reports what was cut so the
+//! run summary can say so.
+
+use super::messages::EN;
+use super::properties::render_property_value;
+use super::Messages;
+use crate::config::PipelineConfig;
+use crate::constants::{PAGE_MAX_PROPERTIES, PROPERTY_MAX_VALUE_CHARS};
+use crate::error::AppError;
+use crate::model::{Block, ChildDatabaseContent, Database, NotionObject, Page, PropertyTypeValue};
+use std::borrow::Cow;
+
+/// Property count and value size limits; `None` means unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PropertyLimits {
+    pub max_properties: Option<usize>,
+    pub max_value_chars: Option<usize>,
+    /// Labels for the truncation marker
+    pub messages: &'static Messages,
+}
+
+impl Default for PropertyLimits {
+    fn default() -> Self {
+        Self {
+            max_properties: Some(PAGE_MAX_PROPERTIES),
+            max_value_chars: Some(PROPERTY_MAX_VALUE_CHARS),
+            messages: &EN,
+        }
+    }
+}
+
+impl PropertyLimits {
+    /// Reads the limits from the config, where 0 disables a limit.
+    pub fn from_config(config: Option<&PipelineConfig>) -> Self {
+        match config {
+            Some(config) => Self {
+                max_properties: Some(config.max_properties).filter(|max| *max > 0),
+                max_value_chars: Some(config.max_property_chars).filter(|max| *max > 0),
+                messages: Messages::for_language(config.lang),
+            },
+            None => Self::default(),
+        }
+    }
+
+    /// Cuts `value` to the character limit, marking where and by how much.
+    pub fn truncate<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        let Some(max) = self.max_value_chars else {
+            return Cow::Borrowed(value);
+        };
+        match value.char_indices().nth(max) {
+            None => Cow::Borrowed(value),
+            Some((cut, _)) => Cow::Owned(format!(
+                "{}… [{}, {} {}]",
+                &value[..cut],
+                self.messages.truncated,
+                value.chars().count(),
+                self.messages.characters_in_total
+            )),
+        }
+    }
+}
+
+/// A page's non-title properties, rendered, non-empty and sorted by name.
+pub fn rendered_properties(page: &Page) -> Result<Vec<(String, String)>, AppError> {
+    let mut rendered = Vec::new();
+    for (name, value) in &page.properties {
+        if matches!(value.type_specific_value, PropertyTypeValue::Title { .. }) {
+            continue;
+        }
+        let formatted = render_property_value(Some(value))?;
+        if !formatted.is_empty() {
+            rendered.push((name.as_str().to_string(), formatted));
+        }
+    }
+    rendered.sort();
+    Ok(rendered)
+}
+
+/// What the limits cut from a tree, for the run summary.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Trimmed {
+    /// Pages whose property list was cut short
+    pages: usize,
+    /// Database tables that lost columns
+    tables: usize,
+    /// Property values that were truncated
+    values: usize,
+}
+
+/// Describes, one line per kind, what the property limits left out of
+/// `object`'s rendering. Empty when nothing was cut.
+pub fn property_limit_warnings(object: &NotionObject, config: &PipelineConfig) -> Vec<String> {
+    let limits = PropertyLimits::from_config(Some(config));
+    let mut trimmed = Trimmed::default();
+    match object {
+        NotionObject::Page(page) => trim_page(page, &limits, &mut trimmed),
+        NotionObject::Database(db) => trim_database(db, &limits, &mut trimmed),
+        NotionObject::Block(block) => {
+            trim_blocks(std::slice::from_ref(block), &limits, &mut trimmed)
+        }
+    }
+
+    let mut warnings = Vec::new();
+    if let (Some(max), true) = (limits.max_properties, trimmed.pages > 0) {
+        warnings.push(format!(
+            "{} pages have more than {} properties; only the first {} are listed. Raise --max-properties to see all.",
+            trimmed.pages, max, max
+        ));
+    }
+    if let (Some(max), true) = (limits.max_properties, trimmed.tables > 0) {
+        warnings.push(format!(
+            "{} database tables have more than {} columns; only the {} most filled are shown. Raise --max-properties to see all.",
+            trimmed.tables, max, max
+        ));
+    }
+    if let (Some(max), true) = (limits.max_value_chars, trimmed.values > 0) {
+        warnings.push(format!(
+            "{} property values were longer than {} characters and were truncated. Raise --max-property-chars to keep them whole.",
+            trimmed.values, max
+        ));
+    }
+    warnings
+}
+
+fn trim_page(page: &Page, limits: &PropertyLimits, trimmed: &mut Trimmed) {
+    let Ok(properties) = rendered_properties(page) else {
+        return;
+    };
+    if limits
+        .max_properties
+        .is_some_and(|max| properties.len() > max)
+    {
+        trimmed.pages += 1;
+    }
+    trimmed.values += properties
+        .iter()
+        .filter(|(_, value)| matches!(limits.truncate(value), Cow::Owned(_)))
+        .count();
+    trim_blocks(&page.blocks, limits, trimmed);
+}
+
+fn trim_database(db: &Database, limits: &PropertyLimits, trimmed: &mut Trimmed) {
+    let column_counts = if db.data_sources.is_empty() {
+        vec![db.properties.len()]
+    } else {
+        db.data_sources
+            .iter()
+            .map(|source| source.properties.len())
+            .collect()
+    };
+    if let Some(max) = limits.max_properties {
+        trimmed.tables += column_counts.iter().filter(|count| **count > max).count();
+    }
+    for row in db.rows() {
+        trim_page(row, limits, trimmed);
+    }
+}
+
+fn trim_blocks(blocks: &[Block], limits: &PropertyLimits, trimmed: &mut Trimmed) {
+    for block in blocks {
+        if let Block::ChildDatabase(child) = block {
+            if let ChildDatabaseContent::Fetched(db) = &child.content {
+                trim_database(db, limits, trimmed);
+            }
+        }
+        trim_blocks(block.children(), limits, trimmed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncation_marks_full_length_and_respects_char_boundaries() {
+        let limits = PropertyLimits {
+            max_properties: None,
+            max_value_chars: Some(3),
+            messages: &EN,
+        };
+
+        assert_eq!(limits.truncate("abc"), "abc");
+        assert_eq!(
+            limits.truncate("äöüß"),
+            "äöü… [truncated, 4 characters in total]"
+        );
+    }
+
+    #[test]
+    fn test_zero_disables_limits() {
+        let config = PipelineConfig {
+            max_properties: 0,
+            max_property_chars: 0,
+            ..Default::default()
+        };
+        let limits = PropertyLimits::from_config(Some(&config));
+
+        assert_eq!(limits.max_properties, None);
+        assert_eq!(limits.truncate(&"x".repeat(10_000)).len(), 10_000);
+    }
+}
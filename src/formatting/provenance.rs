@@ -0,0 +1,189 @@
+// src/formatting/provenance.rs
+//! Fetch provenance header for generated prompts.
+//!
+//! Prompts get pasted into chats and documents far from the command that
+//! produced them. With `--provenance`, the prompt opens with a short record
+//! of where the content came from, when it was fetched, and with which
+//! limits, so a reader can tell whether it is complete and current.
+
+use crate::config::{PipelineConfig, ProvenanceFormat};
+use crate::model::NotionObject;
+use chrono::{DateTime, SecondsFormat, Utc};
+
+/// Where a prompt's content came from and how it was fetched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provenance {
+    pub source_url: String,
+    pub fetched_at: DateTime<Utc>,
+    pub tool_version: &'static str,
+    pub depth: u8,
+    pub limit: u32,
+    pub warning_count: usize,
+    /// API calls and rate limiting during the fetch, when they were counted
+    pub api_usage: Option<String>,
+}
+
+impl Provenance {
+    /// Describes a fetch of `content` made with `config`.
+    pub fn new(
+        content: &NotionObject,
+        config: &PipelineConfig,
+        fetched_at: DateTime<Utc>,
+        warning_count: usize,
+    ) -> Self {
+        Self {
+            source_url: source_url(content, &config.raw_input),
+            fetched_at,
+            tool_version: env!("CARGO_PKG_VERSION"),
+            depth: config.depth,
+            limit: config.limit,
+            warning_count,
+            api_usage: None,
+        }
+    }
+
+    /// Renders the header, including the blank line that separates it from
+    /// the prompt body.
+    pub fn render(&self, format: ProvenanceFormat) -> String {
+        let fetched_at = self.fetched_at.to_rfc3339_opts(SecondsFormat::Secs, true);
+        match format {
+            ProvenanceFormat::Comment => format!(
+                "<!-- notion2prompt provenance\n\
+                 source: {}\n\
+                 fetched_at: {}\n\
+                 generator: notion2prompt {}\n\
+                 depth: {}\n\
+                 limit: {}\n\
+                 warnings: {}\n\
+                 {}\
+                 -->\n\n",
+                // A literal `-->` would end the comment early.
+                self.source_url.replace("-->", "--&gt;"),
+                fetched_at,
+                self.tool_version,
+                self.depth,
+                self.limit,
+                self.warning_count,
+                self.api_usage
+                    .as_ref()
+                    .map(|usage| format!("api_usage: {}\n", usage))
+                    .unwrap_or_default()
+            ),
+            ProvenanceFormat::Yaml => format!(
+                "---\n\
+                 source: \"{}\"\n\
+                 fetched_at: {}\n\
+                 generator: notion2prompt {}\n\
+                 depth: {}\n\
+                 limit: {}\n\
+                 warnings: {}\n\
+                 {}\
+                 ---\n\n",
+                self.source_url.replace('\\', "\\\\").replace('"', "\\\""),
+                fetched_at,
+                self.tool_version,
+                self.depth,
+                self.limit,
+                self.warning_count,
+                self.api_usage
+                    .as_ref()
+                    .map(|usage| format!("api_usage: \"{}\"\n", usage))
+                    .unwrap_or_default()
+            ),
+        }
+    }
+}
+
+/// The object's own Notion URL, else the URL the user passed, else a URL
+/// built from the object ID.
+fn source_url(content: &NotionObject, raw_input: &str) -> String {
+    let object_url = match content {
+        NotionObject::Page(page) => page.url.as_str(),
+        NotionObject::Database(db) => db.url.as_str(),
+        NotionObject::Block(_) => "",
+    };
+    if !object_url.is_empty() {
+        object_url.to_string()
+    } else if raw_input.starts_with("http://") || raw_input.starts_with("https://") {
+        raw_input.to_string()
+    } else {
+        format!("https://www.notion.so/{}", content.id().as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Page;
+    use crate::types::PageId;
+    use chrono::TimeZone;
+
+    fn page(url: &str) -> NotionObject {
+        NotionObject::Page(
+            Page::new(
+                PageId::parse("12345678123456781234567812345678").unwrap(),
+                "Roadmap",
+            )
+            .with_url(url),
+        )
+    }
+
+    fn provenance(url: &str) -> Provenance {
+        Provenance {
+            warning_count: 2,
+            tool_version: "1.2.3",
+            ..Provenance::new(
+                &page(url),
+                &PipelineConfig::default(),
+                Utc.with_ymd_and_hms(2026, 3, 4, 5, 6, 7).unwrap(),
+                0,
+            )
+        }
+    }
+
+    #[test]
+    fn test_comment_header() {
+        let header =
+            provenance("https://www.notion.so/Roadmap-1234").render(ProvenanceFormat::Comment);
+        assert_eq!(
+            header,
+            "<!-- notion2prompt provenance\n\
+             source: https://www.notion.so/Roadmap-1234\n\
+             fetched_at: 2026-03-04T05:06:07Z\n\
+             generator: notion2prompt 1.2.3\n\
+             depth: 10\n\
+             limit: 1000\n\
+             warnings: 2\n\
+             -->\n\n"
+        );
+    }
+
+    #[test]
+    fn test_yaml_header_quotes_source() {
+        let header = provenance("https://example.com/a\"b").render(ProvenanceFormat::Yaml);
+        assert!(header.starts_with("---\nsource: \"https://example.com/a\\\"b\"\n"));
+        assert!(header.ends_with("warnings: 2\n---\n\n"));
+    }
+
+    #[test]
+    fn test_api_usage_line_when_counted() {
+        let provenance = Provenance {
+            api_usage: Some("4 API calls (blocks 4); 0 rate limited".to_string()),
+            ..provenance("https://www.notion.so/Roadmap-1234")
+        };
+        assert!(provenance
+            .render(ProvenanceFormat::Comment)
+            .ends_with("warnings: 2\napi_usage: 4 API calls (blocks 4); 0 rate limited\n-->\n\n"));
+        assert!(provenance
+            .render(ProvenanceFormat::Yaml)
+            .ends_with("api_usage: \"4 API calls (blocks 4); 0 rate limited\"\n---\n\n"));
+    }
+
+    #[test]
+    fn test_source_falls_back_to_object_id() {
+        assert_eq!(
+            provenance("").source_url,
+            "https://www.notion.so/12345678123456781234567812345678"
+        );
+    }
+}
@@ -4,11 +4,21 @@
 //! This module provides an immutable, functional approach to visiting
 //! and formatting Notion blocks, following functional programming principles.
 
-use super::block_renderer::RenderContext;
-use super::rich_text::rich_text_to_markdown;
-use super::state::FormatContext;
+use super::block_renderer::{render_blocks_from, RenderContext};
+use super::callouts::classify;
+use super::embeds::render_embed;
+use super::messages::Messages;
+use super::rich_text::{render_block_equation, rich_text_to_markdown_with};
+use super::state::{FormatContext, HeadingNumbers};
+use crate::analytics::count_blocks;
+use crate::config::{CalloutStyle, HeadingToggles, MathStyle, ToggleStyle};
+use crate::constants::{INLINE_DATABASE_MAX_DEPTH, TOGGLE_COLLAPSE_THRESHOLD_CHARS};
 use crate::error::AppError;
+use crate::formatting::code_language::fence_language;
+use crate::formatting::databases::{sort_rows, RenderConfig};
+use crate::formatting::headings::{heading_shift, shifted_level};
 use crate::model::*;
+use std::borrow::Cow;
 
 /// Table of contents entry
 #[derive(Debug, Clone)]
@@ -40,11 +50,11 @@ pub trait BlockRenderer {
         context: FormatContext,
     ) -> Result<Vec<BlockRenderResult>, AppError> {
         let mut results = Vec::with_capacity(blocks.len());
-        let mut current_context = context;
+        let mut current_context = context.enter_list_level();
 
         for block in blocks {
             let result = self.render_block(block, current_context)?;
-            current_context = result.context.clone();
+            current_context = result.context.after_block(block);
             results.push(result);
         }
 
@@ -52,11 +62,48 @@ pub trait BlockRenderer {
     }
 }
 
+fn math_style(config: &RenderContext) -> MathStyle {
+    config.app_config.map(|c| c.math_style).unwrap_or_default()
+}
+
+/// One line standing in for a database nested too deep to show as a table.
+fn summarize_nested_database(db: &Database, title: &str, messages: &Messages) -> String {
+    let columns = if db.data_sources.is_empty() {
+        db.properties.len()
+    } else {
+        db.data_sources
+            .iter()
+            .map(|source| source.properties.len())
+            .sum()
+    };
+    let summary = format!(
+        "{} {}, {} {}",
+        db.row_count(),
+        messages.rows,
+        columns,
+        messages.columns
+    );
+    if db.url.is_empty() {
+        format!("🗄️ **{}** _({})_\n", title, summary)
+    } else {
+        format!(
+            "🗄️ **{}** _({})_ — [{}]({})\n",
+            title, summary, messages.open_in_notion, db.url
+        )
+    }
+}
+
 /// Formats Notion blocks as markdown.
 pub struct MarkdownBlockRenderer<'a> {
     config: &'a RenderContext<'a>,
     /// All blocks in the document (for TOC generation)
     document_blocks: Option<&'a [Block]>,
+    /// Levels to push heading blocks down, from `--heading-offset`/`--normalize-headings`
+    heading_shift: u8,
+    /// Equation delimiters, from `--math-style`
+    math_style: MathStyle,
+    /// Labels in the language from `--lang`
+    messages: &'static Messages,
 }
 
 impl<'a> MarkdownBlockRenderer<'a> {
@@ -65,6 +112,9 @@ impl<'a> MarkdownBlockRenderer<'a> {
         Self {
             config,
             document_blocks: None,
+            heading_shift: heading_shift(config.app_config, &[]),
+            math_style: math_style(config),
+            messages: config.messages(),
         }
     }
 
@@ -75,6 +125,9 @@ impl<'a> MarkdownBlockRenderer<'a> {
         Self {
             config,
             document_blocks: Some(document_blocks),
+            heading_shift: heading_shift(config.app_config, document_blocks),
+            math_style: math_style(config),
+            messages: config.messages(),
         }
     }
 
@@ -94,13 +147,18 @@ impl<'a> MarkdownBlockRenderer<'a> {
         self.render_block(block, context)
     }
 
+    /// Renders rich text with the configured equation delimiters.
+    fn rich_text(&self, items: &[crate::types::RichTextItem]) -> Result<String, AppError> {
+        rich_text_to_markdown_with(items, self.math_style, self.messages)
+    }
+
     /// Format text content with prefix - pure function
     fn format_text_content(
         &self,
         content: &TextBlockContent,
         prefix: &str,
     ) -> Result<String, AppError> {
-        let text = rich_text_to_markdown(&content.rich_text)?;
+        let text = self.rich_text(&content.rich_text)?;
         Ok(if text.trim().is_empty() {
             format!("{}\n", prefix)
         } else {
@@ -108,26 +166,37 @@ impl<'a> MarkdownBlockRenderer<'a> {
         })
     }
 
-    /// Format heading with appropriate markdown level
-    fn format_heading(&self, level: u8, content: &TextBlockContent) -> Result<String, AppError> {
-        let prefix = "#".repeat(level as usize);
-        self.format_text_content(content, &format!("{} ", prefix))
+    /// Format heading with appropriate markdown level, after its section
+    /// number when there is one
+    fn format_heading(
+        &self,
+        level: u8,
+        content: &TextBlockContent,
+        number: Option<&str>,
+    ) -> Result<String, AppError> {
+        let prefix = "#".repeat(shifted_level(level, self.heading_shift) as usize);
+        match number {
+            Some(number) => self.format_text_content(content, &format!("{} {} ", prefix, number)),
+            None => self.format_text_content(content, &format!("{} ", prefix)),
+        }
     }
 
     /// Format code block with language
     fn format_code_block(&self, code: &CodeBlock) -> Result<String, AppError> {
-        let lang = &code.language;
+        let body: String = code
+            .content
+            .rich_text
+            .iter()
+            .map(|item| item.plain_text.as_str())
+            .collect();
+        let lang = fence_language(&code.language, &body);
         let caption = if !code.caption.is_empty() {
-            rich_text_to_markdown(&code.caption)?
+            self.rich_text(&code.caption)?
         } else {
             String::new()
         };
 
-        let mut result = format!("```{}\n", lang);
-        for item in &code.content.rich_text {
-            result.push_str(&item.plain_text);
-        }
-        result.push_str("\n```\n");
+        let mut result = format!("```{}\n{}\n```\n", lang, body);
 
         if !caption.is_empty() {
             result.push_str(&format!("*{}*\n", caption));
@@ -139,20 +208,28 @@ impl<'a> MarkdownBlockRenderer<'a> {
     /// Generate table of contents from document headings
     fn generate_table_of_contents(&self) -> Result<String, AppError> {
         let Some(blocks) = self.document_blocks else {
-            return Ok("[Table of Contents]\n".to_string());
+            return Ok(format!("[{}]\n", self.messages.table_of_contents));
         };
 
         let mut toc_entries = Vec::new();
         self.collect_headings_recursive(blocks, &mut toc_entries)?;
 
         if toc_entries.is_empty() {
-            return Ok("[Table of Contents - No headings found]\n".to_string());
+            return Ok(format!(
+                "[{} - {}]\n",
+                self.messages.table_of_contents, self.messages.no_headings_found
+            ));
         }
 
         let mut result = String::new();
-        result.push_str("## Table of Contents\n\n");
+        result.push_str(&format!("## {}\n\n", self.messages.table_of_contents));
 
-        for entry in toc_entries {
+        let mut numbers = HeadingNumbers::default();
+        for mut entry in toc_entries {
+            if self.numbered_headings() {
+                numbers.advance(entry.level);
+                entry.text = format!("{} {}", numbers.current(), entry.text);
+            }
             let indent = "  ".repeat((entry.level as usize).saturating_sub(1));
             let anchor = self.create_anchor_link(&entry.text);
             result.push_str(&format!("{}* [{}](#{})\n", indent, entry.text, anchor));
@@ -162,52 +239,68 @@ impl<'a> MarkdownBlockRenderer<'a> {
         Ok(result)
     }
 
+    /// Render the ancestor trail of a breadcrumb, e.g. `Workspace / Projects / Roadmap`.
+    ///
+    /// A trail that does not reach the workspace starts with `…`. When no
+    /// ancestor above the current page was fetched, the placeholder stays.
+    fn format_breadcrumb(&self, breadcrumb: &BreadcrumbBlock) -> String {
+        let lead = if breadcrumb.from_workspace {
+            self.messages.workspace
+        } else if breadcrumb.ancestors.len() > 1 {
+            "…"
+        } else {
+            return format!("[{}]\n", self.messages.breadcrumb);
+        };
+        let mut trail = vec![lead];
+        trail.extend(breadcrumb.ancestors.iter().map(String::as_str));
+        format!("{}\n", trail.join(" / "))
+    }
+
     /// Recursively collect headings from blocks
-    #[allow(clippy::only_used_in_recursion)]
     fn collect_headings_recursive(
         &self,
         blocks: &[Block],
         toc_entries: &mut Vec<TocEntry>,
+    ) -> Result<(), AppError> {
+        self.visit_headings(blocks, &mut |level, text| {
+            toc_entries.push(TocEntry { level, text })
+        })
+    }
+
+    /// Calls `visit` with the level and text of each heading in `blocks`
+    /// that the output shows, in document order. Empty headings are
+    /// skipped, as are those folded away by `--toggle-headings collapse`.
+    fn visit_headings(
+        &self,
+        blocks: &[Block],
+        visit: &mut dyn FnMut(u8, String),
     ) -> Result<(), AppError> {
         for block in blocks {
-            match block {
-                Block::Heading1(h) => {
-                    let text = rich_text_to_markdown(&h.content.rich_text)?;
-                    if !text.trim().is_empty() {
-                        toc_entries.push(TocEntry {
-                            level: 1,
-                            text: text.trim().to_string(),
-                        });
-                    }
+            let heading = match block {
+                Block::Heading1(h) => Some((1, &h.content)),
+                Block::Heading2(h) => Some((2, &h.content)),
+                Block::Heading3(h) => Some((3, &h.content)),
+                _ => None,
+            };
+            if let Some((level, content)) = heading {
+                let text = self.rich_text(&content.rich_text)?;
+                if !text.trim().is_empty() {
+                    visit(level, text.trim().to_string());
                 }
-                Block::Heading2(h) => {
-                    let text = rich_text_to_markdown(&h.content.rich_text)?;
-                    if !text.trim().is_empty() {
-                        toc_entries.push(TocEntry {
-                            level: 2,
-                            text: text.trim().to_string(),
-                        });
-                    }
-                }
-                Block::Heading3(h) => {
-                    let text = rich_text_to_markdown(&h.content.rich_text)?;
-                    if !text.trim().is_empty() {
-                        toc_entries.push(TocEntry {
-                            level: 3,
-                            text: text.trim().to_string(),
-                        });
-                    }
-                }
-                _ => {}
             }
 
-            if block.has_children() {
-                self.collect_headings_recursive(block.children(), toc_entries)?;
+            let collapsed = heading.is_some() && self.heading_toggles() == HeadingToggles::Collapse;
+            if block.has_children() && !collapsed {
+                self.visit_headings(block.children(), visit)?;
             }
         }
         Ok(())
     }
 
+    fn numbered_headings(&self) -> bool {
+        self.config.app_config.is_some_and(|c| c.numbered_headings)
+    }
+
     /// Create an anchor link from heading text
     fn create_anchor_link(&self, text: &str) -> String {
         text.to_lowercase()
@@ -281,19 +374,28 @@ impl BlockRenderer for MarkdownBlockRenderer<'_> {
             Block::Callout(b) => self.format_callout(b, &context)?,
             Block::Code(b) => self.format_code_block(b)?,
             Block::Divider(_) => "---\n".to_string(),
-            Block::Equation(b) => format!("$$\n{}\n$$\n", b.expression),
+            Block::Equation(b) => render_block_equation(&b.expression, self.math_style),
             Block::Image(b) => self.format_image(b)?,
-            Block::Video(b) => format!("[Video: {}]\n", extract_file_url(&b.video)),
+            Block::Video(b) => format!(
+                "[{}: {}]\n",
+                self.messages.video,
+                extract_file_url(&b.video)
+            ),
+            Block::Audio(b) => self.format_audio(b)?,
             Block::File(b) => self.format_file(b)?,
-            Block::Pdf(b) => format!("[PDF: {}]\n", extract_file_url(&b.pdf)),
+            Block::Pdf(b) => format!("[{}: {}]\n", self.messages.pdf, extract_file_url(&b.pdf)),
             Block::Bookmark(b) => self.format_bookmark(b)?,
-            Block::Embed(b) => format!("[Embed: {}]\n", b.url),
-            Block::ChildPage(b) => format!("📄 [[{}]]\n", b.title),
-            Block::ChildDatabase(b) => self.format_child_database(b)?,
+            Block::Embed(b) => render_embed(b, self.messages)?,
+            Block::ChildPage(b) => match &b.included_elsewhere {
+                Some(location) => format!(
+                    "📄 [[{}]] _({} {})_\n",
+                    b.title, self.messages.see, location
+                ),
+                None => format!("📄 [[{}]]\n", b.title),
+            },
+            Block::ChildDatabase(b) => self.format_child_database(b, &context)?,
             Block::LinkToPage(b) => format!("[[{}]]\n", b.page_id.as_str()),
-            Block::Table(b) => {
-                self.format_children(&b.common.children, context.enter_table(b.table_width))?
-            }
+            Block::Table(b) => self.format_children(&b.common.children, context.enter_table(b))?,
             Block::TableRow(b) => self.format_table_row(b, &context)?,
             Block::ColumnList(_) => {
                 self.format_children(block.children(), context.enter_columns())?
@@ -302,23 +404,35 @@ impl BlockRenderer for MarkdownBlockRenderer<'_> {
             Block::Synced(b) => self.format_synced(b, &context)?,
             Block::Template(b) => self.format_text_with_children(
                 &b.content,
-                "[Template] ",
+                &format!("[{}] ", self.messages.template),
                 &b.common.children,
                 &context,
             )?,
-            Block::LinkPreview(b) => format!("[Link Preview: {}]\n", b.url),
-            Block::Breadcrumb(_) => "[Breadcrumb]\n".to_string(),
+            Block::LinkPreview(b) => {
+                format!("[{}: {}]\n", self.messages.link_preview, b.url)
+            }
+            Block::Breadcrumb(b) => self.format_breadcrumb(b),
             Block::TableOfContents(_) => self.generate_table_of_contents()?,
-            Block::Unsupported(b) => format!("[Unsupported block type: {}]\n", b.block_type),
+            Block::Unsupported(b) if b.skipped_bytes.is_some() => {
+                format!("[{}: {}]\n", self.messages.oversized_block, b.block_type)
+            }
+            Block::Unsupported(b) => {
+                format!("[{}: {}]\n", self.messages.unsupported_block, b.block_type)
+            }
         };
 
         // Determine final context based on block type
-        let final_context = match block {
-            Block::NumberedListItem(_) => context.increment_list_number(),
+        let mut final_context = match block {
             Block::TableRow(_) => context.process_table_row(),
-            Block::BulletedListItem(_) | Block::ToDo(_) => context,
             _ => context,
         };
+        if self.numbered_headings() {
+            let mut levels = Vec::new();
+            self.visit_headings(std::slice::from_ref(block), &mut |level, _| {
+                levels.push(level)
+            })?;
+            final_context = final_context.past_headings(levels);
+        }
 
         Ok(BlockRenderResult {
             content,
@@ -344,6 +458,11 @@ impl MarkdownBlockRenderer<'_> {
     }
 
     /// Format a heading block (h1–h3) with its children.
+    ///
+    /// A heading with children is a toggleable heading. Its content belongs
+    /// to the heading's section rather than nesting inside it, so it renders
+    /// at the heading's own depth, starting a fresh list run; with
+    /// `--toggle-headings collapse` it is folded into a summary marker.
     fn format_heading_block(
         &self,
         level: u8,
@@ -351,9 +470,35 @@ impl MarkdownBlockRenderer<'_> {
         children: &[Block],
         context: &FormatContext,
     ) -> Result<String, AppError> {
-        let text = self.format_heading(level, content)?;
-        let child_md = self.format_children(children, context.clone())?;
-        Ok(format!("{}{}", text, child_md))
+        let numbered =
+            self.numbered_headings() && !self.rich_text(&content.rich_text)?.trim().is_empty();
+        let context = if numbered {
+            context.past_headings([level])
+        } else {
+            context.clone()
+        };
+        let number = numbered.then(|| context.heading_numbers().current());
+        let text = self.format_heading(level, content, number.as_deref())?;
+        let child_md = self.format_children(children, context.exit_list_level())?;
+        if child_md.is_empty() || self.heading_toggles() == HeadingToggles::Expand {
+            return Ok(format!("{}{}", text, child_md));
+        }
+        Ok(format!(
+            "{}*({}: {} {}, {} {})*\n",
+            text,
+            self.messages.collapsed,
+            count_blocks(children),
+            self.messages.blocks,
+            child_md.chars().count(),
+            self.messages.characters
+        ))
+    }
+
+    fn heading_toggles(&self) -> HeadingToggles {
+        self.config
+            .app_config
+            .map(|c| c.toggle_headings)
+            .unwrap_or_default()
     }
 
     /// Format a bulleted list item with indented children.
@@ -363,11 +508,8 @@ impl MarkdownBlockRenderer<'_> {
         context: &FormatContext,
     ) -> Result<String, AppError> {
         let text = self.format_text_content(&b.content, "- ")?;
-        let children = self.format_indented_children(
-            &b.common.children,
-            context.enter_bulleted_list().enter_children(),
-            "   ",
-        )?;
+        let children =
+            self.format_indented_children(&b.common.children, context.enter_children(), "   ")?;
         Ok(format!("{}{}", text, children))
     }
 
@@ -379,7 +521,7 @@ impl MarkdownBlockRenderer<'_> {
     ) -> Result<String, AppError> {
         let number = format!("{}. ", context.current_list_number());
         let text = self.format_text_content(&b.content, &number)?;
-        let child_context = context.enter_children().enter_numbered_list();
+        let child_context = context.enter_children();
         let children = self.format_indented_children(&b.common.children, child_context, "   ")?;
         Ok(format!("{}{}", text, children))
     }
@@ -392,36 +534,101 @@ impl MarkdownBlockRenderer<'_> {
         Ok(format!("{}{}", text, children))
     }
 
-    /// Format a toggle block with indented children.
+    /// Format a toggle block, folding large bodies.
+    ///
+    /// Toggles whose rendered body stays under the collapse threshold are
+    /// shown inline; larger ones are folded according to the configured
+    /// [`ToggleStyle`].
     fn format_toggle(&self, b: &ToggleBlock, context: &FormatContext) -> Result<String, AppError> {
         let text = self.format_text_content(&b.content, "▸ ")?;
-        let children =
-            self.format_indented_children(&b.common.children, context.enter_toggle(), "  ")?;
-        Ok(format!("{}{}", text, children))
+        let body = self.format_children(&b.common.children, context.enter_toggle())?;
+
+        let (style, threshold) = self
+            .config
+            .app_config
+            .map(|c| (c.toggle_style, c.toggle_collapse_chars))
+            .unwrap_or((ToggleStyle::Indent, TOGGLE_COLLAPSE_THRESHOLD_CHARS));
+
+        let body_chars = body.chars().count();
+        let collapse = body_chars > threshold;
+        match style {
+            ToggleStyle::Details if collapse => Ok(format!(
+                "<details>\n<summary>{}</summary>\n\n{}\n</details>\n",
+                self.rich_text(&b.content.rich_text)?.trim(),
+                body.trim_end()
+            )),
+            ToggleStyle::Summary if collapse => Ok(format!(
+                "▸ {} *({}: {} {}, {} {})*\n",
+                self.rich_text(&b.content.rich_text)?.trim(),
+                self.messages.collapsed,
+                count_blocks(&b.common.children),
+                self.messages.blocks,
+                body_chars,
+                self.messages.characters
+            )),
+            _ if body.is_empty() => Ok(text),
+            _ => Ok(format!("{}{}", text, indent_block_content(&body, "  "))),
+        }
     }
 
     /// Format a callout block with optional icon.
+    ///
+    /// With `--callout-style gfm` or `obsidian`, a callout whose icon or
+    /// color gives it a type becomes an admonition instead, its children
+    /// inside the quote.
     fn format_callout(
         &self,
         b: &CalloutBlock,
         context: &FormatContext,
     ) -> Result<String, AppError> {
-        let emoji = match &b.icon {
-            Some(Icon::Emoji { emoji }) => format!("{} ", emoji),
-            _ => String::new(),
+        let (style, rules) = self
+            .config
+            .app_config
+            .map(|c| (c.callout_style, c.callout_types.as_slice()))
+            .unwrap_or_default();
+        let marker = match (style, classify(b, rules)) {
+            (CalloutStyle::Gfm, Some(kind)) => format!("[!{}]", kind.gfm_alert()),
+            (CalloutStyle::Obsidian, Some(kind)) => format!("[!{}]", kind),
+            _ => {
+                let emoji = match &b.icon {
+                    Some(Icon::Emoji { emoji }) => format!("{} ", emoji),
+                    _ => String::new(),
+                };
+                let text = self.format_text_content(&b.content, &format!("> {} ", emoji))?;
+                let child_md = self.format_children(&b.common.children, context.enter_callout())?;
+                return Ok(format!("{}{}", text, child_md));
+            }
         };
-        let text = self.format_text_content(&b.content, &format!("> {} ", emoji))?;
-        let child_md = self.format_children(&b.common.children, context.enter_callout())?;
-        Ok(format!("{}{}", text, child_md))
+
+        let mut body = marker + "\n";
+        let text = self.rich_text(&b.content.rich_text)?;
+        if !text.trim().is_empty() {
+            body.push_str(&text);
+            body.push('\n');
+        }
+        body.push_str(&self.format_children(&b.common.children, context.enter_callout())?);
+        let mut quoted: String = body
+            .lines()
+            .map(|line| {
+                if line.is_empty() {
+                    ">\n".to_string()
+                } else {
+                    format!("> {}\n", line)
+                }
+            })
+            .collect();
+        // Ends the quote, so a following callout starts its own
+        quoted.push('\n');
+        Ok(quoted)
     }
 
     /// Format an image block with caption.
     fn format_image(&self, b: &ImageBlock) -> Result<String, AppError> {
         let url = extract_file_url(&b.image);
         let caption = if b.caption.is_empty() {
-            String::from("Image")
+            String::from(self.messages.image)
         } else {
-            rich_text_to_markdown(&b.caption)?
+            self.rich_text(&b.caption)?
         };
         Ok(format!("![{}]({})\n", caption, url))
     }
@@ -430,33 +637,57 @@ impl MarkdownBlockRenderer<'_> {
     fn format_file(&self, b: &FileBlock) -> Result<String, AppError> {
         let url = extract_file_url(&b.file);
         let caption = if b.caption.is_empty() {
-            String::from("File")
+            String::from(self.messages.file)
         } else {
-            rich_text_to_markdown(&b.caption)?
+            self.rich_text(&b.caption)?
         };
         Ok(format!("[{}: {}]\n", caption, url))
     }
 
+    /// Format an audio block with optional caption.
+    fn format_audio(&self, b: &AudioBlock) -> Result<String, AppError> {
+        let url = extract_file_url(&b.audio);
+        if b.caption.is_empty() {
+            Ok(format!("[{}: {}]\n", self.messages.audio, url))
+        } else {
+            Ok(format!(
+                "[{}: {} - {}]\n",
+                self.messages.audio,
+                url,
+                self.rich_text(&b.caption)?
+            ))
+        }
+    }
+
     /// Format a bookmark block with optional caption.
     fn format_bookmark(&self, b: &BookmarkBlock) -> Result<String, AppError> {
         let caption_text = if b.caption.is_empty() {
             String::new()
         } else {
-            format!(" - {}", rich_text_to_markdown(&b.caption)?)
+            format!(" - {}", self.rich_text(&b.caption)?)
         };
         Ok(format!("[🔖 {}{}]\n", b.url, caption_text))
     }
 
     /// Format a table row, adding a header separator after the first row.
+    ///
+    /// When the table has a row header column, the first cell of every row
+    /// below the header row is bolded; markdown has no header cells outside
+    /// the first row.
     fn format_table_row(
         &self,
         b: &TableRowBlock,
         context: &FormatContext,
     ) -> Result<String, AppError> {
+        let bold_first_cell = context.table_has_row_header() && !context.is_first_table_row();
         let mut row = String::from("|");
-        for cell in &b.cells {
-            let content = rich_text_to_markdown(cell)?;
-            row.push_str(&format!(" {} |", content));
+        for (index, cell) in b.cells.iter().enumerate() {
+            let content = self.rich_text(cell)?;
+            if index == 0 && bold_first_cell && !content.trim().is_empty() {
+                row.push_str(&format!(" **{}** |", content.trim()));
+            } else {
+                row.push_str(&format!(" {} |", content));
+            }
         }
         row.push('\n');
 
@@ -473,7 +704,8 @@ impl MarkdownBlockRenderer<'_> {
         let mut result = String::new();
         if let Some(ref synced_from) = b.synced_from {
             result.push_str(&format!(
-                "[Synced from: {}]\n",
+                "[{}: {}]\n",
+                self.messages.synced_from,
                 synced_from.block_id.as_str()
             ));
         }
@@ -499,7 +731,11 @@ impl MarkdownBlockRenderer<'_> {
     }
 
     /// Format a child database block based on its resolution state.
-    fn format_child_database(&self, b: &ChildDatabaseBlock) -> Result<String, AppError> {
+    fn format_child_database(
+        &self,
+        b: &ChildDatabaseBlock,
+        context: &FormatContext,
+    ) -> Result<String, AppError> {
         use crate::model::blocks::ChildDatabaseContent;
 
         match &b.content {
@@ -507,9 +743,9 @@ impl MarkdownBlockRenderer<'_> {
                 log::debug!(
                     "Formatting embedded child database '{}' ({} pages)",
                     b.title,
-                    db.pages.len()
+                    db.row_count()
                 );
-                self.format_database_or_fallback(db, &b.title)
+                self.format_database_or_fallback(db, &b.title, context)
             }
             ChildDatabaseContent::LinkedDatabase => {
                 log::debug!(
@@ -517,14 +753,22 @@ impl MarkdownBlockRenderer<'_> {
                     b.title
                 );
                 Ok(format!(
-                    "🗄️ **{}** _(linked database — not retrievable via API)_\n",
-                    b.title
+                    "🗄️ **{}** _({})_\n",
+                    b.title, self.messages.linked_database
                 ))
             }
             ChildDatabaseContent::Inaccessible { reason } => {
                 log::debug!("Database '{}' inaccessible: {}", b.title, reason);
                 Ok(format!("🗄️ [[{}]]\n", b.title))
             }
+            ChildDatabaseContent::IncludedElsewhere { location } => Ok(format!(
+                "🗄️ **{}** _({} {})_\n",
+                b.title, self.messages.see, location
+            )),
+            ChildDatabaseContent::ShownAbove { title } => Ok(format!(
+                "🗄️ **{}** _({} \"{}\" {})_\n",
+                b.title, self.messages.see, title, self.messages.above
+            )),
             ChildDatabaseContent::NotFetched => {
                 // Try external database lookup as fallback
                 if let Some(lookup) = self.config.databases {
@@ -533,9 +777,9 @@ impl MarkdownBlockRenderer<'_> {
                         log::debug!(
                             "Formatting external child database '{}' ({} pages)",
                             b.title,
-                            db.pages.len()
+                            db.row_count()
                         );
-                        return self.format_database_or_fallback(db, &b.title);
+                        return self.format_database_or_fallback(db, &b.title, context);
                     }
                 }
                 log::debug!("No database data found for '{}'", b.title);
@@ -545,13 +789,48 @@ impl MarkdownBlockRenderer<'_> {
     }
 
     /// Format a database inline, falling back to a placeholder on error.
+    ///
+    /// Databases in the rows of more than `--max-inline-db-depth` other
+    /// databases are summarized in one line.
     fn format_database_or_fallback(
         &self,
         db: &crate::model::Database,
         title: &str,
+        context: &FormatContext,
     ) -> Result<String, AppError> {
-        match crate::formatting::databases::format_database_inline(db, &db.pages, "") {
-            Ok(formatted) => Ok(formatted),
+        let max_depth = self
+            .config
+            .app_config
+            .map_or(INLINE_DATABASE_MAX_DEPTH, |c| c.max_inline_db_depth);
+        if context.database_depth() > max_depth {
+            return Ok(summarize_nested_database(db, title, self.messages));
+        }
+
+        let table_config = self
+            .config
+            .app_config
+            .map(|c| RenderConfig {
+                max_cell_chars: Some(c.max_cell_chars).filter(|max| *max > 0),
+                cell_footnotes: c.cell_footnotes,
+                max_columns: Some(c.max_properties).filter(|max| *max > 0),
+                exclude_empty_columns: c.exclude_empty_columns,
+                row_sort: c.db_sort.clone(),
+                column_align: c.db_align.clone(),
+                group_by: c.db_group_by.clone(),
+                messages: self.messages,
+                ..RenderConfig::default()
+            })
+            .unwrap_or_default();
+
+        let formatted = if db.data_sources.is_empty() {
+            crate::formatting::databases::format_database_inline(db, &db.pages, "", table_config)
+        } else {
+            crate::formatting::databases::format_data_sources_inline(db, "", table_config)
+        };
+        match formatted {
+            Ok(formatted) => Ok(self.config.plugins().before_database(db)
+                + &formatted
+                + &self.expanded_rows(db, context)?),
             Err(e) => {
                 log::warn!("Failed to format child database '{}': {}", title, e);
                 Ok(format!("🗄️ [[{}]]\n", title))
@@ -559,6 +838,40 @@ impl MarkdownBlockRenderer<'_> {
         }
     }
 
+    /// The content of each row under its title, in table order, for
+    /// databases small enough for `--expand-rows`. Rows without content are
+    /// left to the table.
+    fn expanded_rows(&self, db: &Database, context: &FormatContext) -> Result<String, AppError> {
+        let Some(config) = self.config.app_config else {
+            return Ok(String::new());
+        };
+        if config.expand_rows == 0 || db.row_count() > config.expand_rows {
+            return Ok(String::new());
+        }
+
+        let sources: Vec<Cow<Database>> = if db.data_sources.is_empty() {
+            vec![Cow::Borrowed(db)]
+        } else {
+            db.data_sources
+                .iter()
+                .map(|source| Cow::Owned(source.as_database(db)))
+                .collect()
+        };
+        let mut out = String::new();
+        for source in &sources {
+            let mut rows: Vec<&Page> = source.pages.iter().collect();
+            sort_rows(&mut rows, source, &config.db_sort);
+            for row in rows.into_iter().filter(|row| !row.blocks.is_empty()) {
+                out.push_str(&format!("#### {}\n\n", row.title().as_str()));
+                let (content, _) =
+                    render_blocks_from(&row.blocks, self.config, context.enter_database_row())?;
+                out.push_str(content.trim_end());
+                out.push_str("\n\n");
+            }
+        }
+        Ok(out)
+    }
+
     /// Format children blocks with proper context
     fn format_children(
         &self,
@@ -582,6 +895,7 @@ impl MarkdownBlockRenderer<'_> {
 mod tests {
     use super::*;
     use crate::model::blocks::*;
+    use crate::model::testing::{test_database, test_page};
     use crate::types::{BlockId, Color, RichTextItem};
 
     fn create_test_rich_text(text: &str) -> Vec<RichTextItem> {
@@ -595,6 +909,8 @@ mod tests {
                 has_children: !children.is_empty(),
                 children,
                 archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
             },
             content: TextBlockContent {
                 rich_text: create_test_rich_text(text),
@@ -610,6 +926,8 @@ mod tests {
                 has_children: !children.is_empty(),
                 children,
                 archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
             },
             content: TextBlockContent {
                 rich_text: create_test_rich_text(text),
@@ -716,6 +1034,91 @@ mod tests {
         );
     }
 
+    fn numbered(text: &str, children: Vec<Block>) -> Block {
+        create_numbered_list_item("", text, children)
+    }
+
+    fn bulleted(text: &str, children: Vec<Block>) -> Block {
+        create_bulleted_list_item("", text, children)
+    }
+
+    fn render(blocks: &[Block]) -> String {
+        crate::formatting::block_renderer::render_blocks(blocks, &RenderContext::default()).unwrap()
+    }
+
+    #[test]
+    fn test_numbering_continues_after_nested_list() {
+        let blocks = vec![
+            numbered(
+                "one",
+                vec![numbered("one.a", vec![]), numbered("one.b", vec![])],
+            ),
+            numbered("two", vec![numbered("two.a", vec![])]),
+            numbered("three", vec![]),
+        ];
+
+        assert_eq!(
+            render(&blocks),
+            "1. one\n   1. one.a\n   2. one.b\n2. two\n   1. two.a\n3. three\n"
+        );
+    }
+
+    #[test]
+    fn test_numbered_children_of_bullet_count_up() {
+        let blocks = vec![bulleted(
+            "steps",
+            vec![numbered("first", vec![]), numbered("second", vec![])],
+        )];
+
+        assert_eq!(render(&blocks), "- steps\n   1. first\n   2. second\n");
+    }
+
+    #[test]
+    fn test_mixed_list_restarts_numbering_after_bullet() {
+        let blocks = vec![
+            bulleted("intro", vec![]),
+            numbered("a", vec![]),
+            numbered("b", vec![]),
+            bulleted("aside", vec![]),
+            numbered("c", vec![]),
+        ];
+
+        assert_eq!(render(&blocks), "- intro\n1. a\n2. b\n- aside\n1. c\n");
+    }
+
+    #[test]
+    fn test_other_block_between_numbered_items_restarts_numbering() {
+        let blocks = vec![
+            numbered("a", vec![]),
+            numbered("b", vec![]),
+            create_heading1("", "Next"),
+            numbered("c", vec![]),
+        ];
+
+        let output = render(&blocks);
+        assert!(output.starts_with("1. a\n2. b\n"), "Got:\n{}", output);
+        assert!(output.ends_with("1. c\n"), "Got:\n{}", output);
+    }
+
+    #[test]
+    fn test_numbering_is_independent_per_branch_in_deep_documents() {
+        let blocks = vec![
+            create_toggle(
+                "Details",
+                vec![
+                    numbered("x", vec![bulleted("note", vec![numbered("deep", vec![])])]),
+                    numbered("y", vec![]),
+                ],
+            ),
+            numbered("top", vec![]),
+        ];
+
+        assert_eq!(
+            render(&blocks),
+            "▸ Details\n  1. x\n     - note\n        1. deep\n  2. y\n1. top\n"
+        );
+    }
+
     fn create_heading1(id: &str, text: &str) -> Block {
         Block::Heading1(Heading1Block {
             common: crate::model::BlockCommon {
@@ -723,6 +1126,8 @@ mod tests {
                 has_children: false,
                 children: vec![],
                 archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
             },
             content: TextBlockContent {
                 rich_text: create_test_rich_text(text),
@@ -738,6 +1143,8 @@ mod tests {
                 has_children: false,
                 children: vec![],
                 archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
             },
             content: TextBlockContent {
                 rich_text: create_test_rich_text(text),
@@ -753,6 +1160,8 @@ mod tests {
                 has_children: false,
                 children: vec![],
                 archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
             },
         })
     }
@@ -784,4 +1193,221 @@ mod tests {
 
         println!("Generated TOC output:\n{}", output);
     }
+
+    #[test]
+    fn test_numbered_headings_continue_through_nested_blocks() {
+        let app_config = crate::config::PipelineConfig {
+            numbered_headings: true,
+            ..Default::default()
+        };
+        let config = RenderContext {
+            app_config: Some(&app_config),
+            databases: None,
+        };
+        let blocks = vec![
+            create_table_of_contents(""),
+            create_heading1("", "Introduction"),
+            create_heading2("", "Overview"),
+            create_toggle("More", vec![create_heading2("", "Details")]),
+            create_heading1("", "Main Content"),
+            create_heading2("", "Section A"),
+        ];
+
+        let output = crate::formatting::block_renderer::render_blocks(&blocks, &config).unwrap();
+
+        assert!(output.contains("\n# 1. Introduction\n"));
+        assert!(output.contains("\n## 1.1 Overview\n"));
+        assert!(output.contains("## 1.2 Details\n"));
+        assert!(output.contains("\n# 2. Main Content\n"));
+        assert!(output.contains("\n## 2.1 Section A\n"));
+        assert!(output.contains("* [1. Introduction](#1-introduction)\n"));
+        assert!(output.contains("  * [1.2 Details](#12-details)\n"));
+        assert!(output.contains("  * [2.1 Section A](#21-section-a)\n"));
+    }
+
+    fn create_toggle(text: &str, children: Vec<Block>) -> Block {
+        Block::Toggle(ToggleBlock {
+            common: crate::model::BlockCommon {
+                id: BlockId::new_v4(),
+                has_children: !children.is_empty(),
+                children,
+                archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
+            },
+            content: TextBlockContent {
+                rich_text: create_test_rich_text(text),
+                color: Color::Default,
+            },
+        })
+    }
+
+    fn render_toggle_with(style: ToggleStyle, threshold: usize) -> String {
+        let app_config = crate::config::PipelineConfig {
+            toggle_style: style,
+            toggle_collapse_chars: threshold,
+            ..Default::default()
+        };
+        let config = RenderContext {
+            app_config: Some(&app_config),
+            databases: None,
+        };
+        let toggle = create_toggle(
+            "Details",
+            vec![
+                create_bulleted_list_item("", "first", vec![]),
+                create_bulleted_list_item("", "second", vec![]),
+            ],
+        );
+        crate::formatting::block_renderer::render_blocks(&[toggle], &config).unwrap()
+    }
+
+    #[test]
+    fn test_small_toggle_renders_inline() {
+        let output = render_toggle_with(ToggleStyle::Details, 1000);
+        assert_eq!(output, "▸ Details\n  - first\n  - second\n");
+    }
+
+    #[test]
+    fn test_large_toggle_renders_details() {
+        let output = render_toggle_with(ToggleStyle::Details, 5);
+        assert_eq!(
+            output,
+            "<details>\n<summary>Details</summary>\n\n- first\n- second\n</details>\n"
+        );
+    }
+
+    #[test]
+    fn test_large_toggle_renders_summary_marker() {
+        let output = render_toggle_with(ToggleStyle::Summary, 5);
+        assert_eq!(output, "▸ Details *(collapsed: 2 blocks, 17 characters)*\n");
+    }
+
+    fn child_database(title: &str, url: &str, pages: Vec<Page>) -> Block {
+        let database = test_database(title).with_url(url).with_pages(pages);
+        Block::ChildDatabase(ChildDatabaseBlock {
+            common: crate::model::BlockCommon::new(BlockId::new_v4()),
+            title: title.to_string(),
+            content: ChildDatabaseContent::Fetched(Box::new(database)),
+        })
+    }
+
+    /// A "Tasks" database in the only row of `levels` enclosing databases.
+    fn nested_database(levels: usize) -> Block {
+        let tasks = child_database("Tasks", "https://www.notion.so/tasks", vec![]);
+        (0..levels).fold(tasks, |inner, _| {
+            let row = test_page("Row").with_blocks(vec![inner]);
+            child_database("Projects", "", vec![row])
+        })
+    }
+
+    fn render_with_max_db_depth(block: Block, max_inline_db_depth: usize) -> String {
+        let app_config = crate::config::PipelineConfig {
+            max_inline_db_depth,
+            expand_rows: 10,
+            ..Default::default()
+        };
+        let config = RenderContext {
+            app_config: Some(&app_config),
+            databases: None,
+        };
+        crate::formatting::block_renderer::render_blocks(&[block], &config).unwrap()
+    }
+
+    #[test]
+    fn test_databases_beyond_max_depth_render_as_summary() {
+        let summary =
+            "🗄️ **Tasks** _(0 rows, 0 columns)_ — [open in Notion](https://www.notion.so/tasks)";
+
+        let shallow = render_with_max_db_depth(nested_database(2), 2);
+        assert!(!shallow.contains(summary), "{}", shallow);
+        assert!(shallow.contains("*No data available.*"), "{}", shallow);
+
+        let deep = render_with_max_db_depth(nested_database(2), 1);
+        assert!(deep.contains(summary), "{}", deep);
+
+        // Indentation alone does not count as database nesting
+        let listed = (0..3).fold(nested_database(0), |inner, _| bulleted("item", vec![inner]));
+        let listed = render_with_max_db_depth(listed, 0);
+        assert!(!listed.contains(summary), "{}", listed);
+    }
+
+    #[test]
+    fn test_small_databases_expand_their_rows() {
+        let row = |title: &str, blocks: Vec<Block>| test_page(title).with_blocks(blocks);
+        let database = test_database("Tasks").with_pages(vec![
+            row(
+                "Ship",
+                vec![Block::Paragraph(ParagraphBlock {
+                    common: crate::model::BlockCommon::new(BlockId::new_v4()),
+                    content: TextBlockContent {
+                        rich_text: create_test_rich_text("Soon."),
+                        color: Color::Default,
+                    },
+                })],
+            ),
+            row("Empty", vec![]),
+        ]);
+        let block = Block::ChildDatabase(ChildDatabaseBlock {
+            common: crate::model::BlockCommon::new(BlockId::new_v4()),
+            title: "Tasks".to_string(),
+            content: ChildDatabaseContent::Fetched(Box::new(database)),
+        });
+        let render = |expand_rows| {
+            let app_config = crate::config::PipelineConfig {
+                expand_rows,
+                ..Default::default()
+            };
+            let config = RenderContext {
+                app_config: Some(&app_config),
+                databases: None,
+            };
+            crate::formatting::block_renderer::render_blocks(std::slice::from_ref(&block), &config)
+                .unwrap()
+        };
+
+        let expanded = render(2);
+        assert!(expanded.ends_with("#### Ship\n\nSoon.\n\n"), "{}", expanded);
+        assert!(!expanded.contains("#### Empty"), "{}", expanded);
+        assert!(!render(1).contains("####"));
+        assert!(!render(0).contains("####"));
+    }
+
+    #[test]
+    fn test_labels_follow_the_configured_language() {
+        let app_config = crate::config::PipelineConfig {
+            lang: crate::config::Language::De,
+            ..Default::default()
+        };
+        let config = RenderContext {
+            app_config: Some(&app_config),
+            databases: None,
+        };
+        let unsupported = Block::Unsupported(UnsupportedBlock {
+            common: crate::model::BlockCommon {
+                id: BlockId::new_v4(),
+                children: vec![],
+                has_children: false,
+                archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
+            },
+            block_type: "ai_block".to_string(),
+            raw: None,
+            skipped_bytes: None,
+        });
+
+        let output = crate::formatting::block_renderer::render_blocks(
+            &[unsupported, nested_database(0)],
+            &config,
+        )
+        .unwrap();
+
+        assert!(
+            output.contains("[Nicht unterstützter Blocktyp: ai_block]"),
+            "{}",
+            output
+        );
+        assert!(output.contains("*Keine Daten vorhanden.*"), "{}", output);
+    }
 }
@@ -0,0 +1,263 @@
+// src/formatting/records.rs
+//! Flat records for embedding and indexing pipelines.
+//!
+//! [`extract_records`] skips markdown entirely: every page in a fetched
+//! object becomes one [`Record`] with its properties as plain strings and
+//! its content as plain text, ready to be chunked, embedded or indexed.
+//! Database rows, rows of embedded child databases and fetched child pages
+//! each get their own record, so their text never ends up inside the
+//! parent's.
+
+use super::ir::{self, Element, Section, Span};
+use super::properties::render_property_text;
+use crate::error::AppError;
+use crate::model::{Block, ChildDatabaseContent, Database, NotionObject, Page, PropertyTypeValue};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// One page, row or block as flat, plain-text fields.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Record {
+    pub id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// The database this record is a row of
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database_id: Option<String>,
+    /// Properties other than the title; empty values are left out
+    pub properties: BTreeMap<String, String>,
+    /// The content as plain text, one block per line
+    pub text: String,
+}
+
+/// Extracts one record per page in `object`: the page itself, every
+/// database row and every fetched child page, depth first.
+///
+/// A database yields only its rows; a block yields itself.
+pub fn extract_records(object: &NotionObject) -> Result<Vec<Record>, AppError> {
+    let mut records = Vec::new();
+    match object {
+        NotionObject::Page(page) => collect_page(page, None, &mut records)?,
+        NotionObject::Database(db) => collect_rows(db, &mut records)?,
+        NotionObject::Block(block) => {
+            let blocks = std::slice::from_ref(block);
+            records.push(Record {
+                id: block.id().as_str().to_string(),
+                title: object.display_title(),
+                url: None,
+                database_id: None,
+                properties: BTreeMap::new(),
+                text: sections_text(&ir::lower_blocks(blocks, None)?),
+            });
+            collect_nested(blocks, &mut records)?;
+        }
+    }
+    Ok(records)
+}
+
+/// Extracts one record per database row in `object`, from the database
+/// itself and from every fetched child database below it.
+pub fn extract_rows(object: &NotionObject) -> Result<Vec<Record>, AppError> {
+    let mut records = extract_records(object)?;
+    records.retain(|record| record.database_id.is_some());
+    Ok(records)
+}
+
+fn collect_page(
+    page: &Page,
+    database_id: Option<String>,
+    records: &mut Vec<Record>,
+) -> Result<(), AppError> {
+    let mut properties = BTreeMap::new();
+    for (name, value) in &page.properties {
+        if matches!(value.type_specific_value, PropertyTypeValue::Title { .. }) {
+            continue;
+        }
+        let text = render_property_text(value)?;
+        if !text.is_empty() {
+            properties.insert(name.as_str().to_string(), text);
+        }
+    }
+
+    records.push(Record {
+        id: page.id.as_str().to_string(),
+        title: page.title().as_str().to_string(),
+        url: Some(page.url.clone()).filter(|url| !url.is_empty()),
+        database_id,
+        properties,
+        text: sections_text(&ir::lower_blocks(&page.blocks, None)?),
+    });
+    collect_nested(&page.blocks, records)
+}
+
+fn collect_rows(db: &Database, records: &mut Vec<Record>) -> Result<(), AppError> {
+    let database_id = Some(db.id.as_str().to_string());
+    for row in db.rows() {
+        collect_page(row, database_id.clone(), records)?;
+    }
+    Ok(())
+}
+
+/// Collects records for child databases and fetched child pages below `blocks`.
+fn collect_nested(blocks: &[Block], records: &mut Vec<Record>) -> Result<(), AppError> {
+    for block in blocks {
+        match block {
+            Block::ChildDatabase(b) => {
+                if let ChildDatabaseContent::Fetched(db) = &b.content {
+                    collect_rows(db, records)?;
+                }
+            }
+            Block::ChildPage(b) if !b.common.children.is_empty() => {
+                records.push(Record {
+                    id: b.common.id.as_str().to_string(),
+                    title: b.title.clone(),
+                    url: None,
+                    database_id: None,
+                    properties: BTreeMap::new(),
+                    text: sections_text(&ir::lower_blocks(&b.common.children, None)?),
+                });
+            }
+            _ => {}
+        }
+        collect_nested(block.children(), records)?;
+    }
+    Ok(())
+}
+
+fn sections_text(sections: &[Section]) -> String {
+    let mut lines = Vec::new();
+    for section in sections {
+        if let Some(heading) = &section.heading {
+            lines.push(spans_text(&heading.spans));
+        }
+        elements_text(&section.elements, &mut lines);
+    }
+    lines.retain(|line| !line.trim().is_empty());
+    lines.join("\n")
+}
+
+fn elements_text(elements: &[Element], lines: &mut Vec<String>) {
+    for element in elements {
+        match element {
+            Element::Paragraph { spans } => lines.push(spans_text(spans)),
+            Element::Heading(heading) => lines.push(spans_text(&heading.spans)),
+            Element::List { items, .. } => {
+                for item in items {
+                    lines.push(spans_text(&item.spans));
+                    elements_text(&item.children, lines);
+                }
+            }
+            Element::Quote { elements } | Element::Callout { elements, .. } => {
+                elements_text(elements, lines)
+            }
+            Element::Toggle { summary, elements } => {
+                lines.push(spans_text(summary));
+                elements_text(elements, lines);
+            }
+            Element::Code { text, .. } => lines.push(text.clone()),
+            Element::Equation { expression } => lines.push(expression.clone()),
+            Element::Table { header, rows } => {
+                for row in header.iter().chain(rows) {
+                    let cells: Vec<String> = row.iter().map(|cell| spans_text(cell)).collect();
+                    lines.push(cells.join(" | "));
+                }
+            }
+            Element::Link { title, .. } => lines.push(spans_text(title)),
+            // Rows become records of their own; notes describe content
+            // that could not be shown rather than content itself.
+            Element::DataTable { .. } | Element::Note { .. } | Element::Divider => {}
+        }
+    }
+}
+
+fn spans_text(spans: &[Span]) -> String {
+    spans.iter().map(|span| span.text.as_str()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::testing::{test_database, test_page};
+    use crate::model::{BlockCommon, ChildDatabaseBlock, ParagraphBlock, TextBlockContent};
+    use crate::types::{BlockId, RichTextItem};
+
+    fn common(children: Vec<Block>) -> BlockCommon {
+        BlockCommon {
+            id: BlockId::new_v4(),
+            has_children: !children.is_empty(),
+            children,
+            archived: false,
+            last_edited_time: None,
+            unfetched_children: None,
+        }
+    }
+
+    fn paragraph(text: &str) -> Block {
+        Block::Paragraph(ParagraphBlock {
+            common: common(vec![]),
+            content: TextBlockContent {
+                rich_text: vec![RichTextItem::plain_text(text)],
+                ..Default::default()
+            },
+        })
+    }
+
+    fn page(title: &str, properties: Vec<(&str, PropertyTypeValue)>, blocks: Vec<Block>) -> Page {
+        let page = test_page(title)
+            .with_url(format!("https://www.notion.so/{}", title))
+            .with_blocks(blocks);
+        properties
+            .into_iter()
+            .fold(page, |page, (name, value)| page.with_property(name, value))
+    }
+
+    #[test]
+    fn test_pages_and_embedded_rows_become_flat_records() {
+        let row = page(
+            "Ship v2",
+            vec![
+                ("Done", PropertyTypeValue::Checkbox { checkbox: true }),
+                (
+                    "Spec",
+                    PropertyTypeValue::Url {
+                        url: Some("https://example.com/spec".to_string()),
+                    },
+                ),
+                ("Estimate", PropertyTypeValue::Number { number: None }),
+            ],
+            vec![paragraph("Row notes")],
+        );
+        let db = test_database("Tasks").with_pages(vec![row]);
+        let root = page(
+            "Roadmap",
+            vec![],
+            vec![
+                paragraph("Intro"),
+                Block::ChildDatabase(ChildDatabaseBlock {
+                    common: common(vec![]),
+                    title: "Tasks".to_string(),
+                    content: ChildDatabaseContent::Fetched(Box::new(db.clone())),
+                }),
+                paragraph("Outro"),
+            ],
+        );
+
+        let object = NotionObject::Page(root);
+        let records = extract_records(&object).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].title, "Roadmap");
+        assert_eq!(records[0].text, "Intro\nOutro");
+        assert_eq!(records[0].database_id, None);
+        let row = &records[1];
+        assert_eq!(row.title, "Ship v2");
+        assert_eq!(row.database_id.as_deref(), Some(db.id.as_str()));
+        assert_eq!(row.properties["Done"], "Yes");
+        assert_eq!(row.properties["Spec"], "https://example.com/spec");
+        assert!(!row.properties.contains_key("Estimate"));
+        assert_eq!(row.text, "Row notes");
+
+        assert_eq!(extract_rows(&object).unwrap(), records[1..]);
+    }
+}
@@ -0,0 +1,258 @@
+// src/formatting/redact.rs
+//! Replaces Notion IDs in a prompt with stable pseudonyms (`--redact-ids`).
+//!
+//! Page, database and block IDs appear in links, anchors, file names and
+//! synced-block references, and identify the workspace they come from. Each
+//! ID is replaced by its kind and a number (`page-1`, `db-2`, `block-3`;
+//! `id-4` for IDs that point outside the fetched content), numbered in the
+//! order they first appear. The mapping is kept in a local JSON file so the
+//! pseudonyms stay the same across runs and can be reversed.
+
+use crate::error::AppError;
+use crate::model::blocks::ChildDatabaseContent;
+use crate::model::{Block, Database, NotionObject};
+use crate::types::NotionId;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A UUID with or without hyphens.
+static NOTION_ID: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"[0-9a-fA-F]{8}-?[0-9a-fA-F]{4}-?[0-9a-fA-F]{4}-?[0-9a-fA-F]{4}-?[0-9a-fA-F]{12}")
+        .expect("valid Notion ID regex")
+});
+
+/// What a redacted ID refers to, which picks its pseudonym's prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdKind {
+    Page,
+    Database,
+    Block,
+    /// Not part of the fetched content, e.g. a mentioned page
+    Other,
+}
+
+impl IdKind {
+    fn prefix(self) -> &'static str {
+        match self {
+            IdKind::Page => "page",
+            IdKind::Database => "db",
+            IdKind::Block => "block",
+            IdKind::Other => "id",
+        }
+    }
+}
+
+/// One entry of the mapping file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pseudonym {
+    pub pseudonym: String,
+    pub id: String,
+    pub kind: IdKind,
+}
+
+/// The ID ↔ pseudonym mapping, in the order the IDs were first seen.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdPseudonyms {
+    entries: Vec<Pseudonym>,
+    by_id: HashMap<String, usize>,
+}
+
+impl IdPseudonyms {
+    /// Reads the mapping file at `path`, or starts an empty mapping if there is none.
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let entries: Vec<Pseudonym> =
+            serde_json::from_str(&text).map_err(|source| AppError::JsonParseError {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        Ok(Self::from_entries(entries))
+    }
+
+    fn from_entries(entries: Vec<Pseudonym>) -> Self {
+        let by_id = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (entry.id.clone(), index))
+            .collect();
+        Self { entries, by_id }
+    }
+
+    /// Writes the mapping to `path` as a JSON array.
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        std::fs::write(path, serde_json::to_string_pretty(&self.entries)? + "\n")?;
+        Ok(())
+    }
+
+    /// Every pseudonym handed out so far.
+    #[allow(dead_code)] // Library API
+    pub fn entries(&self) -> &[Pseudonym] {
+        &self.entries
+    }
+
+    /// The ID a pseudonym stands for.
+    #[allow(dead_code)] // Library API
+    pub fn original(&self, pseudonym: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.pseudonym == pseudonym)
+            .map(|entry| entry.id.as_str())
+    }
+
+    /// Replaces every Notion ID in `text` by its pseudonym, handing out new
+    /// ones as needed. `content` tells pages, databases and blocks apart.
+    pub fn redact(&mut self, text: &str, content: &NotionObject) -> String {
+        let kinds = id_kinds(content);
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for found in NOTION_ID.find_iter(text) {
+            if !stands_alone(text, found.start(), found.end()) {
+                continue;
+            }
+            let Ok(id) = NotionId::parse(found.as_str()) else {
+                continue;
+            };
+            let kind = kinds.get(id.as_str()).copied().unwrap_or(IdKind::Other);
+            out.push_str(&text[last..found.start()]);
+            out.push_str(self.pseudonym_for(id, kind));
+            last = found.end();
+        }
+        out.push_str(&text[last..]);
+        out
+    }
+
+    fn pseudonym_for(&mut self, id: NotionId, kind: IdKind) -> &str {
+        let index = match self.by_id.get(id.as_str()) {
+            Some(&index) => index,
+            None => {
+                let number = self.entries.iter().filter(|e| e.kind == kind).count() + 1;
+                self.entries.push(Pseudonym {
+                    pseudonym: format!("{}-{}", kind.prefix(), number),
+                    id: id.as_str().to_string(),
+                    kind,
+                });
+                self.by_id
+                    .insert(id.as_str().to_string(), self.entries.len() - 1);
+                self.entries.len() - 1
+            }
+        };
+        &self.entries[index].pseudonym
+    }
+}
+
+/// Whether the match is not part of a longer run of hex digits.
+fn stands_alone(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    !before.is_some_and(|c| c.is_ascii_hexdigit()) && !after.is_some_and(|c| c.is_ascii_hexdigit())
+}
+
+/// The kind of every object in the content tree, keyed by normalized ID.
+fn id_kinds(content: &NotionObject) -> HashMap<String, IdKind> {
+    let mut kinds = HashMap::new();
+    match content {
+        NotionObject::Page(page) => {
+            kinds.insert(page.id.as_str().to_string(), IdKind::Page);
+            collect_block_kinds(&page.blocks, &mut kinds);
+        }
+        NotionObject::Database(db) => collect_database_kinds(db, &mut kinds),
+        NotionObject::Block(block) => collect_block_kinds(std::slice::from_ref(block), &mut kinds),
+    }
+    kinds
+}
+
+fn collect_database_kinds(db: &Database, kinds: &mut HashMap<String, IdKind>) {
+    kinds.insert(db.id.as_str().to_string(), IdKind::Database);
+    for source in &db.data_sources {
+        kinds.insert(source.id.as_str().to_string(), IdKind::Database);
+    }
+    for page in db.rows() {
+        kinds.insert(page.id.as_str().to_string(), IdKind::Page);
+        collect_block_kinds(&page.blocks, kinds);
+    }
+}
+
+fn collect_block_kinds(blocks: &[Block], kinds: &mut HashMap<String, IdKind>) {
+    for block in blocks {
+        let kind = match block {
+            Block::ChildPage(_) => IdKind::Page,
+            Block::ChildDatabase(child) => {
+                if let ChildDatabaseContent::Fetched(db) = &child.content {
+                    collect_database_kinds(db, kinds);
+                }
+                IdKind::Database
+            }
+            _ => IdKind::Block,
+        };
+        kinds.entry(block.id().as_str().to_string()).or_insert(kind);
+        collect_block_kinds(block.children(), kinds);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Page;
+    use crate::types::PageId;
+
+    const PAGE: &str = "12345678123456781234567812345678";
+
+    fn page() -> NotionObject {
+        NotionObject::Page(Page::new(PageId::parse(PAGE).unwrap(), "Notes"))
+    }
+
+    #[test]
+    fn test_ids_become_stable_pseudonyms_by_kind() {
+        let text = "[Notes](https://www.notion.so/Notes-12345678123456781234567812345678) \
+                    mentions 550e8400-e29b-41d4-a716-446655440000, \
+                    again 12345678-1234-5678-1234-567812345678 and \
+                    550E8400E29B41D4A716446655440000.";
+        let mut pseudonyms = IdPseudonyms::default();
+
+        let redacted = pseudonyms.redact(text, &page());
+
+        assert_eq!(
+            redacted,
+            "[Notes](https://www.notion.so/Notes-page-1) mentions id-1, again page-1 and id-1."
+        );
+        assert_eq!(
+            pseudonyms.original("id-1"),
+            Some("550e8400e29b41d4a716446655440000")
+        );
+        assert_eq!(pseudonyms.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_longer_hex_runs_are_left_alone() {
+        let hash = format!("{}abcd", PAGE);
+        let mut pseudonyms = IdPseudonyms::default();
+        assert_eq!(pseudonyms.redact(&hash, &page()), hash);
+    }
+
+    #[test]
+    fn test_mapping_survives_a_round_trip_and_keeps_numbering() {
+        let dir = std::env::temp_dir().join(format!("n2p_redact_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ids.json");
+
+        let mut first = IdPseudonyms::load(&path).unwrap();
+        first.redact("550e8400e29b41d4a716446655440000", &page());
+        first.save(&path).unwrap();
+
+        let mut second = IdPseudonyms::load(&path).unwrap();
+        let redacted = second.redact(
+            "550e8400e29b41d4a716446655440000 650e8400e29b41d4a716446655440000",
+            &page(),
+        );
+        assert_eq!(redacted, "id-1 id-2");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
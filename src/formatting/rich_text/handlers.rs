@@ -7,8 +7,8 @@
 use super::types::*;
 use crate::types::DateValue;
 use crate::types::{
-    DatabaseReference, LinkPreviewReference, MentionData, MentionType, NotionId, PageReference,
-    PartialUser,
+    DatabaseReference, LinkMentionReference, LinkPreviewReference, MentionData, MentionType,
+    NotionId, PageReference, PartialUser, TemplateMentionValue,
 };
 
 /// Trait for handling mention types.
@@ -51,8 +51,11 @@ impl MentionHandlerRegistry {
             MentionType::LinkPreview { link_preview } => self
                 .link_handler
                 .handle_link_preview(link_preview, plain_text),
-            MentionType::LinkMention { url } => {
-                self.link_handler.handle_link_mention(url, plain_text)
+            MentionType::LinkMention { link_mention } => self
+                .link_handler
+                .handle_link_mention(link_mention, plain_text),
+            MentionType::TemplateMention { template_mention } => {
+                handle_template(*template_mention, plain_text)
             }
         }
     }
@@ -97,9 +100,16 @@ impl DatabaseMentionHandler {
         } else {
             plain_text
         };
-        MentionContent::Database {
-            id: database.id.clone(),
-            title: title.to_string(),
+        match &database.view_id {
+            Some(view_id) => MentionContent::DatabaseView {
+                id: database.id.clone(),
+                view_id: view_id.clone(),
+                title: title.to_string(),
+            },
+            None => MentionContent::Database {
+                id: database.id.clone(),
+                title: title.to_string(),
+            },
         }
     }
 }
@@ -128,8 +138,15 @@ impl LinkMentionHandler {
         self.create_link_mention(&link_preview.url, plain_text)
     }
 
-    fn handle_link_mention(&self, url: &str, plain_text: &str) -> MentionContent {
-        self.create_link_mention(url, plain_text)
+    /// Unfurled links show the page title Notion fetched, falling back to
+    /// the item's plain text.
+    fn handle_link_mention(
+        &self,
+        link_mention: &LinkMentionReference,
+        plain_text: &str,
+    ) -> MentionContent {
+        let text = link_mention.title.as_deref().unwrap_or(plain_text);
+        self.create_link_mention(&link_mention.href, text)
     }
 
     fn create_link_mention(&self, url: &str, plain_text: &str) -> MentionContent {
@@ -151,6 +168,23 @@ impl LinkMentionHandler {
     }
 }
 
+/// Renders a template placeholder as Notion displays it before the template
+/// is applied.
+fn handle_template(value: TemplateMentionValue, plain_text: &str) -> MentionContent {
+    let label = if plain_text.is_empty() {
+        match value {
+            TemplateMentionValue::Today => "@Today",
+            TemplateMentionValue::Now => "@Now",
+            TemplateMentionValue::Me => "@Me",
+        }
+    } else {
+        plain_text
+    };
+    MentionContent::Template {
+        label: label.to_string(),
+    }
+}
+
 /// Checks if text indicates a database reference.
 pub fn is_database_reference(text: &str, url: &ValidatedUrl) -> bool {
     if !url.is_notion_url() {
@@ -14,6 +14,8 @@ pub use types::{
     ValidatedUrl,
 };
 
+use super::messages::{Messages, EN};
+use crate::config::MathStyle;
 use crate::error::AppError;
 use crate::types::RichTextItem;
 use crate::types::{EquationData, Link};
@@ -25,8 +27,18 @@ use handlers::{extract_notion_id, is_database_reference, MentionHandlerRegistry}
 /// Formats an array of RichTextItems into Markdown.
 /// This is the main entry point maintaining backward compatibility.
 pub fn rich_text_to_markdown(items: &[RichTextItem]) -> Result<String, AppError> {
+    rich_text_to_markdown_with(items, MathStyle::default(), &EN)
+}
+
+/// Formats rich text into Markdown, delimiting equations with `math` and
+/// labelling database mentions in the language of `messages`.
+pub fn rich_text_to_markdown_with(
+    items: &[RichTextItem],
+    math: MathStyle,
+    messages: &Messages,
+) -> Result<String, AppError> {
     let formatted = format_rich_text_items(items)?;
-    Ok(render_to_markdown(&formatted))
+    Ok(render_to_markdown_with(&formatted, math, messages))
 }
 
 /// Formats rich text items into a structured representation.
@@ -120,12 +132,16 @@ fn format_single_item(
     Ok(TextSegment { content, style })
 }
 
-/// Renders formatted text to Markdown.
-pub fn render_to_markdown(formatted: &FormattedText) -> String {
+/// Renders formatted text to Markdown, delimiting equations with `math`.
+pub fn render_to_markdown_with(
+    formatted: &FormattedText,
+    math: MathStyle,
+    messages: &Messages,
+) -> String {
     let mut output = String::new();
 
     for segment in &formatted.segments {
-        let rendered = render_segment_markdown(segment);
+        let rendered = render_segment_markdown(segment, math, messages);
         output.push_str(&rendered);
     }
 
@@ -133,23 +149,61 @@ pub fn render_to_markdown(formatted: &FormattedText) -> String {
 }
 
 /// Renders a single text segment to Markdown.
-fn render_segment_markdown(segment: &TextSegment) -> String {
+fn render_segment_markdown(segment: &TextSegment, math: MathStyle, messages: &Messages) -> String {
     match &segment.content {
         TextContent::Plain(text) => MarkdownStyleRenderer::apply_styles(text, &segment.style),
         TextContent::Equation(eq) => {
             // Equations typically ignore styling
             if eq.inline {
-                format!("${}$", eq.expression)
+                render_inline_equation(&eq.expression, math)
             } else {
-                format!("$$\n{}\n$$", eq.expression)
+                render_block_equation(&eq.expression, math)
+                    .trim_end()
+                    .to_string()
             }
         }
-        TextContent::Mention(mention) => render_mention_markdown(mention, &segment.style),
+        TextContent::Mention(mention) => render_mention_markdown(mention, &segment.style, messages),
+    }
+}
+
+/// Renders an inline equation with the delimiters of `math`.
+pub fn render_inline_equation(expression: &str, math: MathStyle) -> String {
+    match math {
+        MathStyle::Dollar => format!("${}$", expression),
+        MathStyle::Latex => format!("\\({}\\)", expression),
+        MathStyle::Code => {
+            // The fence must be longer than any backtick run in the expression
+            let longest_run = expression
+                .split(|c| c != '`')
+                .map(str::len)
+                .max()
+                .unwrap_or(0);
+            let fence = "`".repeat(longest_run + 1);
+            let padding = if expression.starts_with('`') || expression.ends_with('`') {
+                " "
+            } else {
+                ""
+            };
+            format!("{0}{1}{2}{1}{0}", fence, padding, expression)
+        }
+    }
+}
+
+/// Renders an equation block, ending in a newline, with the delimiters of `math`.
+pub fn render_block_equation(expression: &str, math: MathStyle) -> String {
+    match math {
+        MathStyle::Dollar => format!("$$\n{}\n$$\n", expression),
+        MathStyle::Latex => format!("\\[\n{}\n\\]\n", expression),
+        MathStyle::Code => format!("```math\n{}\n```\n", expression),
     }
 }
 
 /// Renders a mention to Markdown.
-fn render_mention_markdown(mention: &MentionContent, style: &TextStyle) -> String {
+fn render_mention_markdown(
+    mention: &MentionContent,
+    style: &TextStyle,
+    messages: &Messages,
+) -> String {
     let base = match mention {
         MentionContent::User { name, .. } => format!("@{}", name),
         MentionContent::Page { id, title } => {
@@ -158,7 +212,15 @@ fn render_mention_markdown(mention: &MentionContent, style: &TextStyle) -> Strin
         }
         MentionContent::Database { id, title } => {
             let url = format!("https://www.notion.so/{}", id.value_hyphenated());
-            format!("📊 **Child Database:** [{}]({})", title, url)
+            format!("📊 **{}:** [{}]({})", messages.child_database, title, url)
+        }
+        MentionContent::DatabaseView { id, view_id, title } => {
+            let url = format!(
+                "https://www.notion.so/{}?v={}",
+                id.as_str(),
+                view_id.as_str()
+            );
+            format!("📊 **{}:** [{}]({})", messages.database_view, title, url)
         }
         MentionContent::Date { start, end } => {
             if let Some(end) = end {
@@ -174,6 +236,7 @@ fn render_mention_markdown(mention: &MentionContent, style: &TextStyle) -> Strin
                 format!("[{}]({})", text, url.as_str())
             }
         }
+        MentionContent::Template { label } => label.clone(),
     };
 
     // Apply additional styling if needed (mentions usually have limited styling)
@@ -235,4 +298,47 @@ mod tests {
         let result = rich_text_to_markdown(&items).unwrap();
         assert_eq!(result, "$E = mc^2$");
     }
+
+    #[test]
+    fn test_math_styles_protect_equations() {
+        let expression = "a_1 * b_2";
+
+        assert_eq!(
+            render_inline_equation(expression, MathStyle::Latex),
+            "\\(a_1 * b_2\\)"
+        );
+        assert_eq!(
+            render_inline_equation(expression, MathStyle::Code),
+            "`a_1 * b_2`"
+        );
+        assert_eq!(render_inline_equation("`x`", MathStyle::Code), "`` `x` ``");
+        assert_eq!(
+            render_block_equation(expression, MathStyle::Code),
+            "```math\na_1 * b_2\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_newer_mention_types_from_fixture() {
+        let body: serde_json::Value = serde_json::from_str(include_str!(
+            "../../../tests/fixtures/api_responses/blocks_children_mentions.json"
+        ))
+        .unwrap();
+        let block =
+            crate::api::notion_client_adapter::convert_block_value(body["results"][0].clone())
+                .unwrap();
+        let crate::model::Block::Paragraph(paragraph) = block else {
+            panic!("expected a paragraph block");
+        };
+
+        let result = rich_text_to_markdown(&paragraph.content.rich_text).unwrap();
+        assert_eq!(
+            result,
+            "See [sariola/notion2prompt](https://github.com/sariola/notion2prompt), the \
+             📊 **Database view:** [Roadmap](https://www.notion.so/\
+             1bacd412853380c29d41e3a3f5b2c7d1?v=1bacd412853380a6b7e2c0d9f4a1b3c5) board, \
+             and notes from @Today by @Me."
+        );
+        assert!(!result.contains("Unsupported"));
+    }
 }
@@ -65,11 +65,34 @@ pub struct EquationContent {
 /// Mention content types.
 #[derive(Debug, Clone, PartialEq)]
 pub enum MentionContent {
-    User { id: String, name: String },
-    Page { id: NotionId, title: String },
-    Database { id: NotionId, title: String },
-    Date { start: String, end: Option<String> },
-    Link { url: ValidatedUrl, text: String },
+    User {
+        id: String,
+        name: String,
+    },
+    Page {
+        id: NotionId,
+        title: String,
+    },
+    Database {
+        id: NotionId,
+        title: String,
+    },
+    DatabaseView {
+        id: NotionId,
+        view_id: NotionId,
+        title: String,
+    },
+    Date {
+        start: String,
+        end: Option<String>,
+    },
+    Link {
+        url: ValidatedUrl,
+        text: String,
+    },
+    Template {
+        label: String,
+    },
 }
 
 impl MentionContent {
@@ -79,8 +102,10 @@ impl MentionContent {
             MentionContent::User { name, .. } => name.is_empty(),
             MentionContent::Page { title, .. } => title.is_empty(),
             MentionContent::Database { title, .. } => title.is_empty(),
+            MentionContent::DatabaseView { title, .. } => title.is_empty(),
             MentionContent::Date { start, .. } => start.is_empty(),
             MentionContent::Link { text, .. } => text.is_empty(),
+            MentionContent::Template { label } => label.is_empty(),
         }
     }
 }
@@ -0,0 +1,369 @@
+// src/formatting/row_filter.rs
+//! Compose-time row selection for `--drop-rows` and `--row`.
+//!
+//! API filters decide which rows are fetched; these predicates decide which
+//! fetched rows are rendered. That lets cached content be reused with
+//! different filters without another round-trip to Notion.
+
+use super::properties::render_property_value;
+use crate::config::PipelineConfig;
+use crate::model::{
+    Block, ChildDatabaseContent, Database, NotionObject, Page, PropertyTypeValue, PropertyValue,
+};
+use crate::types::PropertyName;
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+/// A condition on one property of a database row, e.g. `Status=Archived`.
+///
+/// Values compare case-insensitively against the property's plain text.
+/// Properties with several values (multi-select, people, relations) match
+/// when any one value does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowPredicate {
+    property: PropertyName,
+    comparison: Comparison,
+    value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    /// `Name=Value`: some value equals
+    Equals,
+    /// `Name!=Value`: no value equals (rows without the property match)
+    NotEquals,
+    /// `Name~Value`: some value contains the text
+    Contains,
+}
+
+impl Comparison {
+    fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Equals => "=",
+            Comparison::NotEquals => "!=",
+            Comparison::Contains => "~",
+        }
+    }
+}
+
+impl FromStr for RowPredicate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The first operator ends the property name; the value may contain
+        // any of them
+        let operator = s.char_indices().find_map(|(at, _)| {
+            [
+                Comparison::NotEquals,
+                Comparison::Equals,
+                Comparison::Contains,
+            ]
+            .into_iter()
+            .find(|comparison| s[at..].starts_with(comparison.symbol()))
+            .map(|comparison| (at, comparison))
+        });
+        let Some((at, comparison)) = operator else {
+            return Err(format!(
+                "'{}' is not a row predicate; expected PROPERTY=VALUE, PROPERTY!=VALUE or PROPERTY~TEXT",
+                s
+            ));
+        };
+        let (property, value) = (&s[..at], &s[at + comparison.symbol().len()..]);
+
+        let property = property.trim();
+        if property.is_empty() {
+            return Err(format!("'{}' does not name a property", s));
+        }
+        Ok(Self {
+            property: PropertyName::new(property),
+            comparison,
+            value: value.trim().to_lowercase(),
+        })
+    }
+}
+
+impl fmt::Display for RowPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.property.as_str(),
+            self.comparison.symbol(),
+            self.value
+        )
+    }
+}
+
+impl RowPredicate {
+    /// Whether `row` satisfies the predicate.
+    pub fn matches(&self, row: &Page) -> bool {
+        let values = row
+            .properties
+            .get(&self.property)
+            .map(property_texts)
+            .unwrap_or_default();
+
+        match self.comparison {
+            Comparison::Equals => values.contains(&self.value),
+            Comparison::NotEquals => values.iter().all(|v| *v != self.value),
+            Comparison::Contains => values.iter().any(|v| v.contains(&self.value)),
+        }
+    }
+}
+
+/// A glob on a database row's title, e.g. `Q3 Launch*`.
+///
+/// `*` matches any run of characters and `?` any single one; everything
+/// else matches itself, case-insensitively.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowTitleGlob {
+    pattern: String,
+}
+
+impl FromStr for RowTitleGlob {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pattern = s.trim();
+        if pattern.is_empty() {
+            return Err("a row title pattern cannot be empty".to_string());
+        }
+        Ok(Self {
+            pattern: pattern.to_lowercase(),
+        })
+    }
+}
+
+impl fmt::Display for RowTitleGlob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+impl RowTitleGlob {
+    /// Whether `row`'s title matches the glob.
+    pub fn matches(&self, row: &Page) -> bool {
+        let title: Vec<char> = row.title().as_str().trim().to_lowercase().chars().collect();
+        let pattern: Vec<char> = self.pattern.chars().collect();
+        glob_matches(&pattern, &title)
+    }
+}
+
+/// Whether `row` is selected by `--row`: it matches one of `globs`, or no
+/// globs were given.
+pub fn row_selected(globs: &[RowTitleGlob], row: &Page) -> bool {
+    globs.is_empty() || globs.iter().any(|glob| glob.matches(row))
+}
+
+/// Wildcard matching that backtracks to the most recent `*` on a mismatch.
+fn glob_matches(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// The lowercase plain-text values a property holds.
+fn property_texts(value: &PropertyValue) -> Vec<String> {
+    let texts = match &value.type_specific_value {
+        PropertyTypeValue::Title { title: text }
+        | PropertyTypeValue::RichText { rich_text: text } => {
+            vec![text.iter().map(|item| item.plain_text.as_str()).collect()]
+        }
+        PropertyTypeValue::Select { select: option }
+        | PropertyTypeValue::Status { status: option } => {
+            option.iter().map(|o| o.name.clone()).collect()
+        }
+        PropertyTypeValue::MultiSelect { multi_select } => {
+            multi_select.iter().map(|o| o.name.clone()).collect()
+        }
+        PropertyTypeValue::People { people } => {
+            people.iter().filter_map(|p| p.name.clone()).collect()
+        }
+        PropertyTypeValue::Checkbox { checkbox } => vec![checkbox.to_string()],
+        _ => render_property_value(Some(value)).into_iter().collect(),
+    };
+    texts.into_iter().map(|t| t.trim().to_lowercase()).collect()
+}
+
+/// `object` with only the rows `--row` selects and `--drop-rows` keeps,
+/// borrowed unchanged when neither option is given.
+pub fn rendered_rows<'a>(
+    object: &'a NotionObject,
+    config: &PipelineConfig,
+) -> Cow<'a, NotionObject> {
+    let mut object = Cow::Borrowed(object);
+    if !config.rows.is_empty() {
+        object = Cow::Owned(only_selected_rows(&object, &config.rows));
+    }
+    if !config.drop_rows.is_empty() {
+        object = Cow::Owned(without_matching_rows(&object, &config.drop_rows));
+    }
+    object
+}
+
+/// Returns a copy of `object` without the database rows any predicate matches.
+///
+/// Rows are dropped from every database in the tree: the root, data
+/// sources, and child databases embedded in pages and blocks.
+pub fn without_matching_rows(object: &NotionObject, predicates: &[RowPredicate]) -> NotionObject {
+    let (object, dropped) = retain_rows(object, &|row| !predicates.iter().any(|p| p.matches(row)));
+    if dropped > 0 {
+        log::info!("Dropped {} database rows matching --drop-rows", dropped);
+    }
+    object
+}
+
+/// Returns a copy of `object` keeping only the database rows whose title
+/// matches one of `globs`, in every database in the tree.
+pub fn only_selected_rows(object: &NotionObject, globs: &[RowTitleGlob]) -> NotionObject {
+    let (object, dropped) = retain_rows(object, &|row| row_selected(globs, row));
+    if dropped > 0 {
+        log::info!("Left out {} database rows not matching --row", dropped);
+    }
+    object
+}
+
+/// Copies `object`, removing every database row `keep` rejects; returns
+/// the copy and the number of rows removed.
+fn retain_rows(object: &NotionObject, keep: &dyn Fn(&Page) -> bool) -> (NotionObject, usize) {
+    let mut object = object.clone();
+    let dropped = match &mut object {
+        NotionObject::Page(page) => retain_in_blocks(&mut page.blocks, keep),
+        NotionObject::Database(db) => retain_in_database(db, keep),
+        NotionObject::Block(block) => retain_in_blocks(std::slice::from_mut(block), keep),
+    };
+    (object, dropped)
+}
+
+fn retain_in_database(db: &mut Database, keep: &dyn Fn(&Page) -> bool) -> usize {
+    let mut dropped = retain_in_rows(&mut db.pages, keep);
+    for source in &mut db.data_sources {
+        dropped += retain_in_rows(&mut source.pages, keep);
+    }
+    dropped
+}
+
+fn retain_in_rows(rows: &mut Vec<Page>, keep: &dyn Fn(&Page) -> bool) -> usize {
+    let before = rows.len();
+    rows.retain(|row| keep(row));
+    let mut dropped = before - rows.len();
+    for row in rows {
+        dropped += retain_in_blocks(&mut row.blocks, keep);
+    }
+    dropped
+}
+
+fn retain_in_blocks(blocks: &mut [Block], keep: &dyn Fn(&Page) -> bool) -> usize {
+    let mut dropped = 0;
+    for block in blocks {
+        if let Block::ChildDatabase(child) = block {
+            if let ChildDatabaseContent::Fetched(db) = &mut child.content {
+                dropped += retain_in_database(db, keep);
+            }
+        }
+        dropped += retain_in_blocks(block.children_mut(), keep);
+    }
+    dropped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::testing::test_page;
+    use crate::model::PageTitle;
+    use crate::types::{Color, SelectOption};
+
+    fn row(status: &str, tags: &[&str]) -> Page {
+        let option = |name: &str| SelectOption {
+            id: String::new(),
+            name: name.to_string(),
+            color: Color::Default,
+        };
+        test_page("Row")
+            .with_property(
+                "Status",
+                PropertyTypeValue::Status {
+                    status: Some(option(status)),
+                },
+            )
+            .with_property(
+                "Tags",
+                PropertyTypeValue::MultiSelect {
+                    multi_select: tags.iter().copied().map(option).collect(),
+                },
+            )
+    }
+
+    fn predicate(s: &str) -> RowPredicate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_predicates_compare_plain_text_case_insensitively() {
+        let archived = row("Archived", &["Q3", "Backend"]);
+
+        assert!(predicate("Status=archived").matches(&archived));
+        assert!(!predicate("Status!=Archived").matches(&archived));
+        assert!(predicate("Tags=backend").matches(&archived));
+        assert!(predicate("Tags~end").matches(&archived));
+        assert!(!predicate("Owner=Ada").matches(&archived));
+        assert!(predicate("Owner!=Ada").matches(&archived));
+    }
+
+    fn titled(title: &str) -> Page {
+        Page {
+            title: PageTitle::new(title),
+            ..row("Open", &[])
+        }
+    }
+
+    #[test]
+    fn test_row_title_globs() {
+        let glob = |s: &str| s.parse::<RowTitleGlob>().unwrap();
+        let launch = titled("Q3 Launch: Website");
+
+        assert!(glob("Q3 Launch*").matches(&launch));
+        assert!(glob("q3 launch*").matches(&launch));
+        assert!(glob("*website").matches(&launch));
+        assert!(glob("Q? Launch*Web*").matches(&launch));
+        assert!(!glob("Q3 Launch").matches(&launch));
+        assert!(!glob("Q4*").matches(&launch));
+        assert!(glob("*").matches(&titled("")));
+        assert!("  ".parse::<RowTitleGlob>().is_err());
+
+        assert!(row_selected(&[], &launch));
+        assert!(row_selected(&[glob("Q4*"), glob("Q3*")], &launch));
+    }
+
+    #[test]
+    fn test_predicate_parsing() {
+        assert_eq!(predicate(" Status != Done ").to_string(), "Status!=done");
+        assert_eq!(predicate("Notes=a=b").to_string(), "Notes=a=b");
+        assert_eq!(predicate("Title~a=b").to_string(), "Title~a=b");
+        assert_eq!(predicate("Title~a!=b").to_string(), "Title~a!=b");
+        assert_eq!(predicate("Notes=x~y").to_string(), "Notes=x~y");
+        assert!("Status".parse::<RowPredicate>().is_err());
+        assert!("=Done".parse::<RowPredicate>().is_err());
+    }
+}
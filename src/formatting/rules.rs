@@ -0,0 +1,582 @@
+// src/formatting/rules.rs
+//! Compose-time block rules from a `--rules` YAML file.
+//!
+//! A rules file lists transforms applied to every block before rendering,
+//! in the order they are written:
+//!
+//! ```yaml
+//! rules:
+//!   - drop:
+//!       type: image
+//!   - replace_prefix:
+//!       callout: "⚠️"
+//!       with: "WARNING:"
+//!   - rewrite_links:
+//!       match: '^https://old\.example\.com/(.*)'
+//!       replace: 'https://docs.example.com/$1'
+//! ```
+//!
+//! `drop` leaves out blocks of a type, children included. `replace_prefix`
+//! takes the icon off callouts that show the given emoji and starts their
+//! text with `with` instead. `rewrite_links` rewrites the URLs of links in
+//! block text, bookmarks, embeds and link previews that match a regular
+//! expression; `$1` or `${name}` in `replace` insert capture groups.
+//!
+//! Rules are checked when the file is loaded: unknown rules, fields and
+//! block types, invalid expressions and references to missing groups are
+//! errors rather than rules that silently never match.
+
+use crate::config::PipelineConfig;
+use crate::error::AppError;
+use crate::model::blocks::ChildDatabaseContent;
+use crate::model::{Block, Database, Icon, NotionObject};
+use crate::types::{RichTextItem, RichTextType};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::path::Path;
+
+/// The block types `drop` accepts, as `Block::block_type` names them.
+const BLOCK_TYPES: &[&str] = &[
+    "paragraph",
+    "heading_1",
+    "heading_2",
+    "heading_3",
+    "bulleted_list_item",
+    "numbered_list_item",
+    "to_do",
+    "toggle",
+    "quote",
+    "callout",
+    "code",
+    "equation",
+    "divider",
+    "breadcrumb",
+    "table_of_contents",
+    "image",
+    "video",
+    "audio",
+    "file",
+    "pdf",
+    "bookmark",
+    "embed",
+    "child_page",
+    "child_database",
+    "link_to_page",
+    "table",
+    "table_row",
+    "column_list",
+    "column",
+    "synced_block",
+    "template",
+    "link_preview",
+    "unsupported",
+];
+
+/// A capture group reference in a replacement: `$$` (a literal dollar),
+/// `${name}` or `$name`.
+static GROUP_REFERENCE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\$\$|\$\{([^}]*)\}|\$([A-Za-z0-9_]+)").expect("valid regex"));
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    rules: Vec<RuleSpec>,
+}
+
+/// One rule as written in the file: a map with exactly one of the fields.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSpec {
+    drop: Option<DropSpec>,
+    replace_prefix: Option<ReplacePrefixSpec>,
+    rewrite_links: Option<RewriteLinksSpec>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DropSpec {
+    #[serde(rename = "type")]
+    block_type: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReplacePrefixSpec {
+    callout: String,
+    with: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RewriteLinksSpec {
+    #[serde(rename = "match")]
+    pattern: String,
+    replace: String,
+}
+
+/// A checked rule, ready to apply.
+#[derive(Debug, Clone)]
+enum BlockRule {
+    Drop(String),
+    CalloutPrefix { icon: String, prefix: String },
+    RewriteLinks { pattern: Regex, replacement: String },
+}
+
+/// The rules of a `--rules` file, in file order.
+#[derive(Debug, Clone, Default)]
+pub struct BlockRules {
+    rules: Vec<BlockRule>,
+}
+
+impl BlockRules {
+    /// Reads and checks the rules file at `path`.
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|message| {
+            AppError::Validation(format!("rules file {}: {}", path.display(), message))
+        })
+    }
+
+    /// Parses and checks rules written as YAML.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let file: RulesFile = serde_norway::from_str(text).map_err(|e| e.to_string())?;
+        let rules = file
+            .rules
+            .into_iter()
+            .enumerate()
+            .map(|(index, spec)| {
+                BlockRule::checked(spec).map_err(|e| format!("rule {}: {}", index + 1, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rules })
+    }
+
+    /// True when there are no rules to apply.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Applies the rules to `blocks` and everything below them; returns how
+    /// many blocks were dropped or changed.
+    fn apply_in_blocks(&self, blocks: &mut Vec<Block>) -> usize {
+        let before = blocks.len();
+        blocks.retain(|block| !self.drops(block));
+        let mut changed = before - blocks.len();
+        for block in blocks.iter_mut() {
+            changed += self.apply_to_block(block);
+        }
+        changed
+    }
+
+    /// Applies the rules to `block` itself, which is kept, and to what it holds.
+    fn apply_to_block(&self, block: &mut Block) -> usize {
+        let mut changed = 0;
+        let mut block_changed = false;
+        for rule in &self.rules {
+            block_changed |= rule.rewrite(block);
+        }
+        changed += usize::from(block_changed);
+        if let Block::ChildDatabase(child) = block {
+            if let ChildDatabaseContent::Fetched(db) = &mut child.content {
+                changed += self.apply_in_database(db);
+            }
+        }
+        changed + self.apply_in_blocks(block.children_mut())
+    }
+
+    fn apply_in_database(&self, db: &mut Database) -> usize {
+        let sources = db.data_sources.iter_mut().flat_map(|s| s.pages.iter_mut());
+        db.pages
+            .iter_mut()
+            .chain(sources)
+            .map(|row| self.apply_in_blocks(&mut row.blocks))
+            .sum()
+    }
+
+    fn drops(&self, block: &Block) -> bool {
+        self.rules.iter().any(
+            |rule| matches!(rule, BlockRule::Drop(block_type) if block_type == block.block_type()),
+        )
+    }
+}
+
+impl BlockRule {
+    fn checked(spec: RuleSpec) -> Result<Self, String> {
+        match spec {
+            RuleSpec {
+                drop: Some(DropSpec { block_type }),
+                replace_prefix: None,
+                rewrite_links: None,
+            } => {
+                let block_type = block_type.trim().to_lowercase();
+                if !BLOCK_TYPES.contains(&block_type.as_str()) {
+                    return Err(format!(
+                        "unknown block type '{}'; expected one of {}",
+                        block_type,
+                        BLOCK_TYPES.join(", ")
+                    ));
+                }
+                Ok(Self::Drop(block_type))
+            }
+            RuleSpec {
+                drop: None,
+                replace_prefix: Some(ReplacePrefixSpec { callout, with }),
+                rewrite_links: None,
+            } => {
+                let icon = emoji_key(&callout);
+                if icon.is_empty() {
+                    return Err("replace_prefix needs the callout's emoji".to_string());
+                }
+                Ok(Self::CalloutPrefix {
+                    icon,
+                    prefix: with.trim().to_string(),
+                })
+            }
+            RuleSpec {
+                drop: None,
+                replace_prefix: None,
+                rewrite_links: Some(RewriteLinksSpec { pattern, replace }),
+            } => {
+                let pattern = Regex::new(&pattern)
+                    .map_err(|e| format!("invalid expression '{}': {}", pattern, e))?;
+                check_group_references(&pattern, &replace)?;
+                Ok(Self::RewriteLinks {
+                    pattern,
+                    replacement: replace,
+                })
+            }
+            _ => Err("expected exactly one of drop, replace_prefix or rewrite_links".to_string()),
+        }
+    }
+
+    /// Applies a non-dropping rule to `block` alone; true if it changed.
+    fn rewrite(&self, block: &mut Block) -> bool {
+        match self {
+            BlockRule::Drop(_) => false,
+            BlockRule::CalloutPrefix { icon, prefix } => {
+                let Block::Callout(callout) = block else {
+                    return false;
+                };
+                let shows_icon = matches!(
+                    &callout.icon,
+                    Some(Icon::Emoji { emoji }) if emoji_key(emoji) == *icon
+                );
+                if !shows_icon {
+                    return false;
+                }
+                callout.icon = None;
+                if !prefix.is_empty() {
+                    let text = &mut callout.content.rich_text;
+                    let separator = if text.is_empty() { "" } else { " " };
+                    text.insert(
+                        0,
+                        RichTextItem::plain_text(&format!("{}{}", prefix, separator)),
+                    );
+                }
+                true
+            }
+            BlockRule::RewriteLinks {
+                pattern,
+                replacement,
+            } => {
+                let mut changed = false;
+                let mut rewrite_url = |url: &mut String| {
+                    if let Cow::Owned(new) = pattern.replace_all(url, replacement.as_str()) {
+                        changed |= new != *url;
+                        *url = new;
+                    }
+                };
+                match block {
+                    Block::Bookmark(b) => rewrite_url(&mut b.url),
+                    Block::Embed(b) => rewrite_url(&mut b.url),
+                    Block::LinkPreview(b) => rewrite_url(&mut b.url),
+                    _ => {}
+                }
+                for text in rich_text_mut(block) {
+                    for item in text.iter_mut() {
+                        if let Some(href) = &mut item.href {
+                            rewrite_url(href);
+                        }
+                        if let RichTextType::Text {
+                            link: Some(link), ..
+                        } = &mut item.text_type
+                        {
+                            rewrite_url(&mut link.url);
+                        }
+                    }
+                }
+                changed
+            }
+        }
+    }
+}
+
+/// Every rich text of `block`: its text, caption or table cells.
+pub(super) fn rich_text(block: &Block) -> Vec<&Vec<RichTextItem>> {
+    match block {
+        Block::Paragraph(b) => vec![&b.content.rich_text],
+        Block::Heading1(b) => vec![&b.content.rich_text],
+        Block::Heading2(b) => vec![&b.content.rich_text],
+        Block::Heading3(b) => vec![&b.content.rich_text],
+        Block::BulletedListItem(b) => vec![&b.content.rich_text],
+        Block::NumberedListItem(b) => vec![&b.content.rich_text],
+        Block::ToDo(b) => vec![&b.content.rich_text],
+        Block::Toggle(b) => vec![&b.content.rich_text],
+        Block::Quote(b) => vec![&b.content.rich_text],
+        Block::Callout(b) => vec![&b.content.rich_text],
+        Block::Template(b) => vec![&b.content.rich_text],
+        Block::Code(b) => vec![&b.caption],
+        Block::Image(b) => vec![&b.caption],
+        Block::Video(b) => vec![&b.caption],
+        Block::Audio(b) => vec![&b.caption],
+        Block::File(b) => vec![&b.caption],
+        Block::Pdf(b) => vec![&b.caption],
+        Block::Bookmark(b) => vec![&b.caption],
+        Block::Embed(b) => vec![&b.caption],
+        Block::TableRow(b) => b.cells.iter().collect(),
+        _ => vec![],
+    }
+}
+
+/// [`rich_text`], mutably.
+pub(super) fn rich_text_mut(block: &mut Block) -> Vec<&mut Vec<RichTextItem>> {
+    match block {
+        Block::Paragraph(b) => vec![&mut b.content.rich_text],
+        Block::Heading1(b) => vec![&mut b.content.rich_text],
+        Block::Heading2(b) => vec![&mut b.content.rich_text],
+        Block::Heading3(b) => vec![&mut b.content.rich_text],
+        Block::BulletedListItem(b) => vec![&mut b.content.rich_text],
+        Block::NumberedListItem(b) => vec![&mut b.content.rich_text],
+        Block::ToDo(b) => vec![&mut b.content.rich_text],
+        Block::Toggle(b) => vec![&mut b.content.rich_text],
+        Block::Quote(b) => vec![&mut b.content.rich_text],
+        Block::Callout(b) => vec![&mut b.content.rich_text],
+        Block::Template(b) => vec![&mut b.content.rich_text],
+        Block::Code(b) => vec![&mut b.caption],
+        Block::Image(b) => vec![&mut b.caption],
+        Block::Video(b) => vec![&mut b.caption],
+        Block::Audio(b) => vec![&mut b.caption],
+        Block::File(b) => vec![&mut b.caption],
+        Block::Pdf(b) => vec![&mut b.caption],
+        Block::Bookmark(b) => vec![&mut b.caption],
+        Block::Embed(b) => vec![&mut b.caption],
+        Block::TableRow(b) => b.cells.iter_mut().collect(),
+        _ => vec![],
+    }
+}
+
+/// An emoji compared without the variation selector, which Notion keeps on
+/// some icons (`⚠️`) and a rules file may leave off (`⚠`).
+fn emoji_key(emoji: &str) -> String {
+    emoji.trim().replace('\u{fe0f}', "")
+}
+
+/// Rejects a replacement that refers to a group `pattern` does not have,
+/// which would otherwise expand to nothing.
+fn check_group_references(pattern: &Regex, replacement: &str) -> Result<(), String> {
+    for captures in GROUP_REFERENCE.captures_iter(replacement) {
+        let Some(name) = captures.get(1).or_else(|| captures.get(2)) else {
+            continue;
+        };
+        let name = name.as_str();
+        let exists = match name.parse::<usize>() {
+            Ok(index) => index < pattern.captures_len(),
+            Err(_) => pattern.capture_names().flatten().any(|n| n == name),
+        };
+        if !exists {
+            return Err(format!(
+                "replacement refers to group '{}', which '{}' does not have",
+                name,
+                pattern.as_str()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// `object` with the `--rules` transforms applied, passed through unchanged
+/// when there are none. A block given as the root is kept even if a rule
+/// drops its type.
+pub fn rewritten_blocks<'a>(
+    object: Cow<'a, NotionObject>,
+    config: &PipelineConfig,
+) -> Cow<'a, NotionObject> {
+    let rules = &config.rules;
+    if rules.is_empty() {
+        return object;
+    }
+    let mut object = object.into_owned();
+    let changed = match &mut object {
+        NotionObject::Page(page) => rules.apply_in_blocks(&mut page.blocks),
+        NotionObject::Database(db) => rules.apply_in_database(db),
+        NotionObject::Block(block) => rules.apply_to_block(block),
+    };
+    if changed > 0 {
+        log::info!("Rules dropped or changed {} blocks", changed);
+    }
+    Cow::Owned(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::testing::test_page;
+    use crate::model::{
+        BlockCommon, BookmarkBlock, CalloutBlock, ExternalFile, FileObject, ImageBlock,
+        ParagraphBlock, TextBlockContent,
+    };
+    use crate::types::{BlockId, Color, Link};
+
+    const RULES: &str = r#"
+rules:
+  - drop:
+      type: image
+  - replace_prefix:
+      callout: "⚠"
+      with: "WARNING:"
+  - rewrite_links:
+      match: '^https://old\.example\.com/(?P<path>.*)'
+      replace: 'https://docs.example.com/${path}'
+"#;
+
+    fn text(text: &str) -> TextBlockContent {
+        TextBlockContent {
+            rich_text: vec![RichTextItem::plain_text(text)],
+            color: Color::Default,
+        }
+    }
+
+    fn image() -> Block {
+        Block::Image(ImageBlock {
+            common: BlockCommon::new(BlockId::new_v4()),
+            image: FileObject::External {
+                external: ExternalFile {
+                    url: "https://example.com/chart.png".to_string(),
+                },
+            },
+            caption: vec![],
+        })
+    }
+
+    fn page(blocks: Vec<Block>) -> NotionObject {
+        NotionObject::Page(test_page("Plan").with_blocks(blocks))
+    }
+
+    fn apply(object: &NotionObject) -> Vec<Block> {
+        let config = PipelineConfig {
+            rules: BlockRules::parse(RULES).unwrap(),
+            ..PipelineConfig::default()
+        };
+        match rewritten_blocks(Cow::Borrowed(object), &config).into_owned() {
+            NotionObject::Page(page) => page.blocks,
+            _ => panic!("expected a page"),
+        }
+    }
+
+    #[test]
+    fn test_rules_drop_prefix_and_rewrite() {
+        let mut linked = RichTextItem::plain_text("guide");
+        linked.href = Some("https://old.example.com/guide".to_string());
+        linked.text_type = RichTextType::Text {
+            content: "guide".to_string(),
+            link: Some(Link {
+                url: "https://old.example.com/guide".to_string(),
+            }),
+        };
+        let mut paragraph_common = BlockCommon::new(BlockId::new_v4());
+        paragraph_common.has_children = true;
+        paragraph_common.children = vec![image()];
+        let object = page(vec![
+            image(),
+            Block::Callout(CalloutBlock {
+                common: BlockCommon::new(BlockId::new_v4()),
+                icon: Some(Icon::Emoji {
+                    emoji: "⚠️".to_string(),
+                }),
+                content: text("Rotate keys"),
+            }),
+            Block::Paragraph(ParagraphBlock {
+                common: paragraph_common,
+                content: TextBlockContent {
+                    rich_text: vec![linked],
+                    color: Color::Default,
+                },
+            }),
+            Block::Bookmark(BookmarkBlock {
+                common: BlockCommon::new(BlockId::new_v4()),
+                url: "https://old.example.com/api".to_string(),
+                caption: vec![],
+            }),
+        ]);
+
+        let blocks = apply(&object);
+        assert_eq!(blocks.len(), 3);
+        let Block::Callout(callout) = &blocks[0] else {
+            panic!("expected the callout first");
+        };
+        assert_eq!(callout.icon, None);
+        assert_eq!(
+            callout.content.rich_text[0].plain_text,
+            "WARNING: ".to_string()
+        );
+        let Block::Paragraph(paragraph) = &blocks[1] else {
+            panic!("expected the paragraph");
+        };
+        assert!(paragraph.common.children.is_empty());
+        let item = &paragraph.content.rich_text[0];
+        assert_eq!(item.href.as_deref(), Some("https://docs.example.com/guide"));
+        assert!(matches!(
+            &item.text_type,
+            RichTextType::Text { link: Some(link), .. } if link.url == "https://docs.example.com/guide"
+        ));
+        let Block::Bookmark(bookmark) = &blocks[2] else {
+            panic!("expected the bookmark");
+        };
+        assert_eq!(bookmark.url, "https://docs.example.com/api");
+    }
+
+    #[test]
+    fn test_other_callouts_are_left_alone() {
+        let object = page(vec![Block::Callout(CalloutBlock {
+            common: BlockCommon::new(BlockId::new_v4()),
+            icon: Some(Icon::Emoji {
+                emoji: "💡".to_string(),
+            }),
+            content: text("Tip"),
+        })]);
+        let NotionObject::Page(original) = &object else {
+            unreachable!();
+        };
+        assert_eq!(apply(&object), original.blocks);
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        let error = |yaml: &str| BlockRules::parse(yaml).unwrap_err();
+        assert!(error("rules:\n  - drop:\n      type: picture\n").contains("'picture'"));
+        let both = "rules:\n  - drop:\n      type: image\n    \
+                    rewrite_links:\n      match: a\n      replace: b\n";
+        assert!(error(both).contains("exactly one"));
+        assert!(error("rules:\n  - dorp:\n      type: image\n").contains("dorp"));
+        assert!(error("rules:\n  - drop:\n      type: image\n      kind: x\n").contains("kind"));
+        assert!(
+            error("rules:\n  - rewrite_links:\n      match: '('\n      replace: x\n")
+                .starts_with("rule 1: invalid expression")
+        );
+        assert!(
+            error("rules:\n  - rewrite_links:\n      match: 'a(b)'\n      replace: '$2'\n")
+                .contains("group '2'")
+        );
+        assert!(
+            error("rules:\n  - replace_prefix:\n      callout: ' '\n      with: x\n")
+                .contains("emoji")
+        );
+        assert!(BlockRules::parse("rules: []\n").unwrap().is_empty());
+        assert!(BlockRules::parse(
+            "rules:\n  - rewrite_links:\n      match: 'a(b)'\n      replace: '$$1 ${1}'\n"
+        )
+        .is_ok());
+    }
+}
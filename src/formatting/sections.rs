@@ -0,0 +1,280 @@
+// src/formatting/sections.rs
+//! The prompt's documents composed one section at a time.
+//!
+//! Pages are split at their top-level headings: the header (title,
+//! summary and properties, up to the first heading) comes first, then one
+//! section per heading. A database is one section holding its summary,
+//! followed by its rows as pages. Sections are handed to a callback as each
+//! document is composed, so a caller can pass them on (to a client, a
+//! size outline) without waiting for the whole prompt.
+
+use super::block_renderer::{
+    compose_block_markdown, compose_database_summary, compose_page_sections, RenderContext,
+    SectionBoundary,
+};
+use super::markdown_check::check_markdown;
+use super::prepared_content;
+use super::whitespace::normalize_whitespace;
+use crate::config::PipelineConfig;
+use crate::error::AppError;
+use crate::model::{NotionObject, Page};
+use crate::output::create_clean_filename;
+use serde::Serialize;
+use std::ops::ControlFlow;
+
+/// What part of a document a section is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SectionKind {
+    /// A page's title, summary and properties, up to its first heading
+    Header,
+    /// A top-level heading of a page and what follows it
+    Section,
+    /// A database's summary table
+    Database,
+    /// A block rendered on its own, when the root is a block
+    Block,
+}
+
+impl SectionKind {
+    /// The lowercase name, as serialized.
+    #[allow(dead_code)] // Library API
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Header => "header",
+            Self::Section => "section",
+            Self::Database => "database",
+            Self::Block => "block",
+        }
+    }
+}
+
+/// One composed section of a document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PromptSection {
+    pub kind: SectionKind,
+    /// The file the section belongs to, as named in the prompt
+    pub document: String,
+    /// The heading text, or the document title for a header or database
+    pub heading: String,
+    /// Heading level 1–3, or 0 for anything that is not a heading's section
+    pub level: u8,
+    pub markdown: String,
+}
+
+/// Composes `object` as the prompt would, handing each non-empty section to
+/// `emit` in document order. Stops early when `emit` breaks.
+///
+/// `--normalize-whitespace` and `--check-markdown` apply to each section.
+/// `--link-style` and `--people-glossary` do not: they collect links and
+/// people across the whole prompt, so the sections keep inline links and
+/// no glossary is emitted.
+pub fn compose_sections(
+    object: &NotionObject,
+    config: &PipelineConfig,
+    emit: &mut dyn FnMut(PromptSection) -> ControlFlow<()>,
+) -> Result<(), AppError> {
+    let object = prepared_content(object, config);
+    let object = object.as_ref();
+    let databases = super::gather_embedded_databases(object);
+    let context = RenderContext {
+        app_config: Some(config),
+        databases: Some(&databases),
+    };
+    let mut emit_cleaned = |mut section: PromptSection| {
+        if config.normalize_whitespace {
+            section.markdown = normalize_whitespace(&section.markdown);
+        }
+        if config.check_markdown {
+            section.markdown = check_markdown(&section.markdown).markdown;
+        }
+        emit(section)
+    };
+    // A break only means the caller has heard enough
+    let _ = object_sections(object, &context, &mut emit_cleaned)?;
+    Ok(())
+}
+
+fn object_sections(
+    object: &NotionObject,
+    context: &RenderContext,
+    emit: &mut dyn FnMut(PromptSection) -> ControlFlow<()>,
+) -> Result<ControlFlow<()>, AppError> {
+    match object {
+        NotionObject::Page(page) => page_sections(page, context, emit),
+        NotionObject::Database(db) => {
+            let title = db.title().as_plain_text();
+            let section = PromptSection {
+                kind: SectionKind::Database,
+                document: create_clean_filename(&title, db.id.as_str(), false),
+                heading: title,
+                level: 0,
+                markdown: compose_database_summary(db, context)?,
+            };
+            if emit(section).is_break() {
+                return Ok(ControlFlow::Break(()));
+            }
+            for row in db.rows() {
+                if page_sections(row, context, emit)?.is_break() {
+                    return Ok(ControlFlow::Break(()));
+                }
+            }
+            Ok(ControlFlow::Continue(()))
+        }
+        NotionObject::Block(block) => {
+            let document = format!("block_{}.md", block.id().as_str());
+            Ok(emit(PromptSection {
+                kind: SectionKind::Block,
+                heading: document.clone(),
+                document,
+                level: 0,
+                markdown: compose_block_markdown(block, context)?,
+            }))
+        }
+    }
+}
+
+/// Splits a page at its top-level headings.
+fn page_sections(
+    page: &Page,
+    context: &RenderContext,
+    emit: &mut dyn FnMut(PromptSection) -> ControlFlow<()>,
+) -> Result<ControlFlow<()>, AppError> {
+    let (markdown, boundaries) = compose_page_sections(page, context)?;
+    let document = create_clean_filename(page.title().as_str(), page.id.as_str(), false);
+
+    let header = SectionBoundary {
+        offset: 0,
+        heading: page.title().as_str().to_string(),
+        level: 0,
+    };
+    let starts: Vec<SectionBoundary> = std::iter::once(header).chain(boundaries).collect();
+    for (index, start) in starts.iter().enumerate() {
+        let end = starts
+            .get(index + 1)
+            .map_or(markdown.len(), |next| next.offset);
+        let text = &markdown[start.offset..end];
+        if text.is_empty() {
+            continue;
+        }
+        let section = PromptSection {
+            kind: if start.level == 0 {
+                SectionKind::Header
+            } else {
+                SectionKind::Section
+            },
+            document: document.clone(),
+            heading: start.heading.clone(),
+            level: start.level,
+            markdown: text.to_string(),
+        };
+        if emit(section).is_break() {
+            return Ok(ControlFlow::Break(()));
+        }
+    }
+    Ok(ControlFlow::Continue(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::testing::{test_database, test_page};
+    use crate::model::{Block, BlockCommon, Heading2Block, ParagraphBlock, TextBlockContent};
+    use crate::types::{BlockId, Color, RichTextItem};
+
+    fn page(title: &str, blocks: Vec<Block>) -> Page {
+        test_page(title).with_blocks(blocks)
+    }
+
+    fn text(content: &str) -> TextBlockContent {
+        TextBlockContent {
+            rich_text: vec![RichTextItem::plain_text(content)],
+            color: Color::Default,
+        }
+    }
+
+    fn sections(object: &NotionObject) -> Vec<PromptSection> {
+        let mut sections = Vec::new();
+        compose_sections(object, &PipelineConfig::default(), &mut |section| {
+            sections.push(section);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        sections
+    }
+
+    #[test]
+    fn test_database_summary_comes_before_its_rows() {
+        let db = test_database("Tasks").with_pages(vec![
+            page(
+                "Ship",
+                vec![
+                    Block::Paragraph(ParagraphBlock {
+                        common: BlockCommon::new(BlockId::new_v4()),
+                        content: text("Soon."),
+                    }),
+                    Block::Heading2(Heading2Block {
+                        common: BlockCommon::new(BlockId::new_v4()),
+                        content: text("Steps"),
+                    }),
+                ],
+            ),
+            page("Test", vec![]),
+        ]);
+
+        let found: Vec<_> = sections(&NotionObject::Database(db))
+            .into_iter()
+            .map(|s| (s.kind, s.heading))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (SectionKind::Database, "Tasks".to_string()),
+                (SectionKind::Header, "Ship".to_string()),
+                (SectionKind::Section, "Steps".to_string()),
+                (SectionKind::Header, "Test".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_a_break_stops_composition() {
+        let object = NotionObject::Page(page(
+            "Plan",
+            vec![Block::Heading2(Heading2Block {
+                common: BlockCommon::new(BlockId::new_v4()),
+                content: text("Later"),
+            })],
+        ));
+        let mut heard = Vec::new();
+        compose_sections(&object, &PipelineConfig::default(), &mut |section| {
+            heard.push(section.kind);
+            ControlFlow::Break(())
+        })
+        .unwrap();
+        assert_eq!(heard, [SectionKind::Header]);
+    }
+
+    #[test]
+    fn test_sections_normalize_whitespace_like_the_prompt() {
+        let object = NotionObject::Page(page(
+            "Plan",
+            vec![Block::Paragraph(ParagraphBlock {
+                common: BlockCommon::new(BlockId::new_v4()),
+                content: text("ten\u{a0}days"),
+            })],
+        ));
+        let config = PipelineConfig {
+            normalize_whitespace: true,
+            ..Default::default()
+        };
+        let mut markdown = String::new();
+        compose_sections(&object, &config, &mut |section| {
+            markdown.push_str(&section.markdown);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert!(markdown.contains("ten days"));
+        assert!(!markdown.contains('\u{a0}'));
+    }
+}
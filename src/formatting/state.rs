@@ -5,13 +5,14 @@
 //! during the transformation of Notion blocks to formatted content.
 
 use crate::constants::BLOCK_MAX_RENDER_DEPTH;
+use crate::model::{Block, TableBlock};
 use im_rc::HashSet;
 
 // --- Core Types ---
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ListKind {
-    Numbered(usize), // Current number for this level
+    Numbered(usize), // Number the next item in this run gets
     Bulleted,
 }
 
@@ -34,6 +35,46 @@ pub enum ColumnAlignment {
     Right,
 }
 
+/// Section numbers for `--numbered-headings`, as a stack of the headings
+/// open at the current block, outermost first.
+///
+/// A heading closes every open heading of its level or deeper. Levels
+/// nobody used are not counted, so a page whose first heading is an H2
+/// numbers it `1.` rather than `0.1`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HeadingNumbers {
+    /// Notion level and number of each open heading
+    open: Vec<(u8, usize)>,
+}
+
+impl HeadingNumbers {
+    /// Moves past a heading of `level`.
+    pub fn advance(&mut self, level: u8) {
+        let mut closed = None;
+        while let Some(&(open_level, number)) = self.open.last() {
+            if open_level < level {
+                break;
+            }
+            self.open.pop();
+            if open_level == level {
+                closed = Some(number);
+            }
+        }
+        self.open.push((level, closed.map_or(1, |n| n + 1)));
+    }
+
+    /// The number of the innermost open heading: `1.` at the top, then
+    /// `1.1`, `1.1.1`.
+    pub fn current(&self) -> String {
+        let numbers: Vec<String> = self.open.iter().map(|(_, n)| n.to_string()).collect();
+        if numbers.len() == 1 {
+            format!("{}.", numbers[0])
+        } else {
+            numbers.join(".")
+        }
+    }
+}
+
 /// Immutable formatting context that tracks state during block transformation.
 /// All methods return new instances, preserving immutability.
 ///
@@ -47,7 +88,7 @@ pub struct FormatContext {
     processed_ids: HashSet<String>,
     /// Current recursion depth
     recursion_depth: usize,
-    /// Stack of list contexts for nested lists
+    /// One entry per sibling sequence being rendered, innermost last
     list_stack: Vec<ListContext>,
     /// Current table context if within a table
     table_context: Option<TableContext>,
@@ -59,12 +100,21 @@ pub struct FormatContext {
     in_columns: bool,
     /// Current column index if in columns
     column_index: Option<usize>,
+    /// Headings open at this block, for `--numbered-headings`
+    heading_numbers: HeadingNumbers,
+    /// Databases whose row pages enclose this block, for `--max-inline-db-depth`
+    database_depth: usize,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// List state of one sibling sequence.
+///
+/// Notion numbers a list by position among its siblings: a numbered item
+/// continues the run of numbered items directly before it, and anything else
+/// (a bullet, a paragraph) ends the run. Nested lists start their own run.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 struct ListContext {
-    kind: ListKind,
-    depth: usize,
+    /// The list run the previous sibling belonged to, if it was a list item
+    run: Option<ListKind>,
 }
 
 // --- Public API ---
@@ -76,12 +126,14 @@ impl FormatContext {
         Self {
             processed_ids: HashSet::new(),
             recursion_depth: 0,
-            list_stack: Vec::new(),
+            list_stack: vec![ListContext::default()],
             table_context: None,
             indent_level: 0,
             block_depth: 0,
             in_columns: false,
             column_index: None,
+            heading_numbers: HeadingNumbers::default(),
+            database_depth: 0,
         }
     }
 
@@ -130,75 +182,69 @@ impl FormatContext {
         new_context
     }
 
-    /// Checks if currently in a list.
+    /// Checks if the previous sibling at this level was a list item.
     pub fn is_in_list(&self) -> bool {
-        !self.list_stack.is_empty()
+        self.list_stack
+            .last()
+            .is_some_and(|level| level.run.is_some())
     }
 
-    /// Returns the current list depth.
+    /// Returns how many sibling sequences are being rendered.
     pub fn list_depth(&self) -> usize {
         self.list_stack.len()
     }
 
-    /// Enters a numbered list context.
-    pub fn enter_numbered_list(&self) -> Self {
+    /// Starts a new sibling sequence, such as a block's children.
+    ///
+    /// Numbering in the new sequence starts at 1 and is independent of
+    /// every enclosing sequence.
+    pub fn enter_list_level(&self) -> Self {
         let mut new_context = self.clone();
-        let depth = self.list_stack.len();
-        new_context.list_stack.push(ListContext {
-            kind: ListKind::Numbered(1),
-            depth,
-        });
+        new_context.list_stack.push(ListContext::default());
         new_context
     }
 
-    /// Enters a bulleted list context.
-    pub fn enter_bulleted_list(&self) -> Self {
+    /// Leaves the innermost sibling sequence.
+    pub fn exit_list_level(&self) -> Self {
         let mut new_context = self.clone();
-        let depth = self.list_stack.len();
-        new_context.list_stack.push(ListContext {
-            kind: ListKind::Bulleted,
-            depth,
-        });
+        new_context.list_stack.pop();
         new_context
     }
 
-    /// Gets the current list number (for numbered lists).
+    /// Gets the number a numbered list item at this level would get.
     pub fn current_list_number(&self) -> usize {
-        self.list_stack
-            .last()
-            .and_then(|ctx| match &ctx.kind {
-                ListKind::Numbered(n) => Some(*n),
-                _ => None,
-            })
-            .unwrap_or(1)
-    }
-
-    /// Increments the current list number.
-    pub fn increment_list_number(&self) -> Self {
-        let mut new_context = self.clone();
-        if let Some(last) = new_context.list_stack.last_mut() {
-            if let ListKind::Numbered(n) = &mut last.kind {
-                *n += 1;
-            }
+        match self.list_stack.last().and_then(|level| level.run.as_ref()) {
+            Some(ListKind::Numbered(n)) => *n,
+            _ => 1,
         }
-        new_context
     }
 
-    /// Exits the current list context.
-    pub fn exit_list(&self) -> Self {
+    /// Advances the current sibling sequence past `block`.
+    ///
+    /// A numbered item continues (or starts) the numbered run, a bulleted item
+    /// starts a bulleted run, and any other block ends the run.
+    pub fn after_block(&self, block: &Block) -> Self {
+        let run = match block {
+            Block::NumberedListItem(_) => Some(ListKind::Numbered(self.current_list_number() + 1)),
+            Block::BulletedListItem(_) => Some(ListKind::Bulleted),
+            _ => None,
+        };
         let mut new_context = self.clone();
-        new_context.list_stack.pop();
+        match new_context.list_stack.last_mut() {
+            Some(level) => level.run = run,
+            None => new_context.list_stack.push(ListContext { run }),
+        }
         new_context
     }
 
-    /// Enters a table context.
-    pub fn enter_table(&self, table_width: usize) -> Self {
+    /// Enters the context of `table`, taking its width and header flags.
+    pub fn enter_table(&self, table: &TableBlock) -> Self {
         let mut new_context = self.clone();
         new_context.table_context = Some(TableContext {
-            column_count: table_width,
-            has_column_header: true, // Default to true
-            has_row_header: false,
-            alignments: vec![ColumnAlignment::Left; table_width],
+            column_count: table.table_width,
+            has_column_header: table.has_column_header,
+            has_row_header: table.has_row_header,
+            alignments: vec![ColumnAlignment::Left; table.table_width],
             row_count: 0,
             header_rendered: false,
         });
@@ -232,6 +278,13 @@ impl FormatContext {
             .unwrap_or(false)
     }
 
+    /// Checks if the current table marks its first column as row headers.
+    pub fn table_has_row_header(&self) -> bool {
+        self.table_context
+            .as_ref()
+            .is_some_and(|t| t.has_row_header)
+    }
+
     /// Enters a columns layout.
     pub fn enter_columns(&self) -> Self {
         let mut new_context = self.clone();
@@ -248,6 +301,20 @@ impl FormatContext {
         new_context
     }
 
+    /// A fresh context for the content of a database row page, one
+    /// database deeper than this block.
+    pub fn enter_database_row(&self) -> Self {
+        Self {
+            database_depth: self.database_depth + 1,
+            ..Self::new()
+        }
+    }
+
+    /// How many databases' row pages enclose this block.
+    pub fn database_depth(&self) -> usize {
+        self.database_depth
+    }
+
     /// Gets the current indent level.
     pub fn indent_level(&self) -> usize {
         self.indent_level
@@ -259,6 +326,20 @@ impl FormatContext {
         self.block_depth == 0 && !self.is_in_list()
     }
 
+    /// The section numbers as of this block.
+    pub fn heading_numbers(&self) -> &HeadingNumbers {
+        &self.heading_numbers
+    }
+
+    /// Moves the section numbers past headings of `levels`, in order.
+    pub fn past_headings(&self, levels: impl IntoIterator<Item = u8>) -> Self {
+        let mut new_context = self.clone();
+        for level in levels {
+            new_context.heading_numbers.advance(level);
+        }
+        new_context
+    }
+
     /// Enters a toggle context — semantic marker for toggle nesting.
     pub fn enter_toggle(&self) -> Self {
         // Toggles just increase indentation
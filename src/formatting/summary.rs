@@ -0,0 +1,263 @@
+// src/formatting/summary.rs
+//! The generated Summary section for `--auto-summary`.
+//!
+//! A consumer model reads a prompt top to bottom, so an overview before the
+//! body helps it place what follows. The summary is built from the page
+//! itself, with no model involved: either its heading outline or its first
+//! paragraphs, followed by the size of every database embedded in it.
+
+use super::block_renderer::heading_text;
+use super::messages::Messages;
+use super::rich_text::rich_text_to_markdown;
+use crate::config::AutoSummary;
+use crate::constants::SUMMARY_LEAD_PARAGRAPHS;
+use crate::error::AppError;
+use crate::model::blocks::ChildDatabaseContent;
+use crate::model::{Block, Database, Page};
+use std::fmt::Write;
+
+/// The `## Summary` section for `page`, or an empty string when the mode is
+/// off or the page has nothing to summarize.
+pub fn compose_summary_section(
+    page: &Page,
+    mode: AutoSummary,
+    messages: &Messages,
+) -> Result<String, AppError> {
+    let overview = match mode {
+        AutoSummary::Off => return Ok(String::new()),
+        AutoSummary::Outline => heading_outline(&page.blocks),
+        AutoSummary::Lead => lead_paragraphs(&page.blocks)?,
+    };
+    let stats = database_stats(&page.blocks, messages)?;
+    if overview.is_empty() && stats.is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut out = format!("## {}\n\n", messages.summary);
+    for part in [overview, stats] {
+        if !part.is_empty() {
+            out.push_str(&part);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+/// The page's headings as a nested list, the shallowest level unindented.
+fn heading_outline(blocks: &[Block]) -> String {
+    let mut headings = Vec::new();
+    collect_headings(blocks, &mut headings);
+    let Some(top) = headings.iter().map(|(level, _)| *level).min() else {
+        return String::new();
+    };
+
+    headings
+        .iter()
+        .filter(|(_, text)| !text.trim().is_empty())
+        .map(|(level, text)| {
+            let indent = "  ".repeat(usize::from(level - top));
+            format!("{}- {}\n", indent, text.trim())
+        })
+        .collect()
+}
+
+fn collect_headings(blocks: &[Block], headings: &mut Vec<(u8, String)>) {
+    for block in blocks {
+        if let Some(heading) = heading_text(block) {
+            headings.push(heading);
+        }
+        collect_headings(block.children(), headings);
+    }
+}
+
+/// The first non-empty top-level paragraphs, one per line pair.
+fn lead_paragraphs(blocks: &[Block]) -> Result<String, AppError> {
+    let mut out = String::new();
+    let paragraphs = blocks.iter().filter_map(|block| match block {
+        Block::Paragraph(p) => Some(&p.content.rich_text),
+        _ => None,
+    });
+    let mut taken = 0;
+    for rich_text in paragraphs {
+        if taken == SUMMARY_LEAD_PARAGRAPHS {
+            break;
+        }
+        let text = rich_text_to_markdown(rich_text)?;
+        if text.trim().is_empty() {
+            continue;
+        }
+        if taken > 0 {
+            out.push('\n');
+        }
+        writeln!(out, "{}", text.trim())?;
+        taken += 1;
+    }
+    Ok(out)
+}
+
+/// One line naming every fetched database in the page with its size.
+fn database_stats(blocks: &[Block], messages: &Messages) -> Result<String, AppError> {
+    let mut databases = Vec::new();
+    collect_databases(blocks, &mut databases);
+    if databases.is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut out = format!("**{}**: ", messages.databases);
+    for (index, db) in databases.iter().enumerate() {
+        if index > 0 {
+            out.push_str(", ");
+        }
+        let rows = db.row_count();
+        let columns = db.properties.len()
+            + db.data_sources
+                .iter()
+                .map(|source| source.properties.len())
+                .sum::<usize>();
+        write!(
+            out,
+            "{} ({} {}, {} {})",
+            db.title().as_plain_text(),
+            rows,
+            if rows == 1 {
+                messages.row
+            } else {
+                messages.rows
+            },
+            columns,
+            if columns == 1 {
+                messages.column
+            } else {
+                messages.columns
+            },
+        )?;
+    }
+    out.push('\n');
+    Ok(out)
+}
+
+fn collect_databases<'a>(blocks: &'a [Block], databases: &mut Vec<&'a Database>) {
+    for block in blocks {
+        if let Block::ChildDatabase(child) = block {
+            if let ChildDatabaseContent::Fetched(db) = &child.content {
+                databases.push(db);
+            }
+        }
+        collect_databases(block.children(), databases);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatting::messages::{DE, EN};
+    use crate::model::testing::{test_database, test_page};
+    use crate::model::{
+        BlockCommon, ChildDatabaseBlock, Heading1Block, Heading2Block, Heading3Block,
+        ParagraphBlock, TextBlockContent,
+    };
+    use crate::types::{BlockId, Color, RichTextItem};
+
+    fn content(text: &str) -> TextBlockContent {
+        TextBlockContent {
+            rich_text: vec![RichTextItem::plain_text(text)],
+            color: Color::Default,
+        }
+    }
+
+    fn common() -> BlockCommon {
+        BlockCommon::new(BlockId::new_v4())
+    }
+
+    fn paragraph(text: &str) -> Block {
+        Block::Paragraph(ParagraphBlock {
+            common: common(),
+            content: content(text),
+        })
+    }
+
+    fn page(blocks: Vec<Block>) -> Page {
+        test_page("Plan").with_blocks(blocks)
+    }
+
+    fn database(title: &str, rows: usize) -> Block {
+        let db = test_database(title).with_pages((0..rows).map(|_| page(vec![])).collect());
+        Block::ChildDatabase(ChildDatabaseBlock {
+            common: common(),
+            title: title.to_string(),
+            content: ChildDatabaseContent::Fetched(Box::new(db)),
+        })
+    }
+
+    fn sample() -> Page {
+        page(vec![
+            paragraph("First."),
+            paragraph(""),
+            Block::Heading2(Heading2Block {
+                common: common(),
+                content: content("Goals"),
+            }),
+            paragraph("Second."),
+            Block::Heading3(Heading3Block {
+                common: common(),
+                content: content("Stretch"),
+            }),
+            paragraph("Third."),
+            paragraph("Fourth."),
+            database("Tasks", 2),
+        ])
+    }
+
+    #[test]
+    fn test_off_adds_nothing() {
+        assert_eq!(
+            compose_summary_section(&sample(), AutoSummary::Off, &EN).unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_outline_nests_headings_below_the_shallowest() {
+        let summary = compose_summary_section(&sample(), AutoSummary::Outline, &EN).unwrap();
+        assert_eq!(
+            summary,
+            "## Summary\n\n- Goals\n  - Stretch\n\n**Databases**: Tasks (2 rows, 0 columns)\n\n"
+        );
+    }
+
+    #[test]
+    fn test_summary_labels_follow_the_language() {
+        let summary = compose_summary_section(&sample(), AutoSummary::Outline, &DE).unwrap();
+        assert!(summary.starts_with("## Zusammenfassung\n\n"), "{}", summary);
+        assert!(
+            summary.contains("**Datenbanken**: Tasks (2 Zeilen, 0 Spalten)"),
+            "{}",
+            summary
+        );
+    }
+
+    #[test]
+    fn test_lead_takes_the_first_non_empty_paragraphs() {
+        let summary = compose_summary_section(&sample(), AutoSummary::Lead, &EN).unwrap();
+        assert_eq!(
+            summary,
+            "## Summary\n\nFirst.\n\nSecond.\n\nThird.\n\n**Databases**: Tasks (2 rows, 0 columns)\n\n"
+        );
+    }
+
+    #[test]
+    fn test_page_with_nothing_to_summarize_gets_no_section() {
+        let bare = page(vec![Block::Heading1(Heading1Block {
+            common: common(),
+            content: content("  "),
+        })]);
+        assert_eq!(
+            compose_summary_section(&bare, AutoSummary::Lead, &EN).unwrap(),
+            ""
+        );
+        assert_eq!(
+            compose_summary_section(&page(vec![]), AutoSummary::Outline, &EN).unwrap(),
+            ""
+        );
+    }
+}
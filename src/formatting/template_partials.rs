@@ -0,0 +1,299 @@
+// src/formatting/template_partials.rs
+//! Resolves Handlebars partials from disk, relative to the including template.
+//!
+//! A template can pull in shared fragments with `{{> header}}` and inherit
+//! from a skeleton with a partial block:
+//!
+//! ```handlebars
+//! {{#> base}}
+//!   {{#*inline "body"}}project-specific content{{/inline}}
+//! {{/base}}
+//! ```
+//!
+//! where `base.hbs` declares overridable slots as `{{#> body}}default{{/body}}`.
+//! Partial names map to `<name>.hbs` next to the template that references
+//! them, so `{{> shared/header}}` loads `shared/header.hbs`. Cycles between
+//! template files are rejected at load time instead of overflowing at render.
+//! Handlebars knows partials by name only, so one name resolving to two
+//! different files (`{{> header}}` from templates in two directories) is
+//! rejected too, rather than one file silently replacing the other.
+
+use crate::error::AppError;
+use handlebars::Handlebars;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Matches `{{> name}}` and `{{#> name}}`, capturing whether it is a block.
+static PARTIAL_REFERENCE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\{\{~?\s*(#?)>\s*"?([A-Za-z0-9_./-]+)"?"#).expect("valid partial regex")
+});
+
+/// Matches `{{#*inline "name"}}` definitions.
+static INLINE_DEFINITION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\{\{~?\s*#\*inline\s+"([^"]+)""#).expect("valid inline regex"));
+
+/// A partial referenced by a template source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PartialReference {
+    name: String,
+    /// Partial blocks carry a fallback body, so a missing file is not an error.
+    is_block: bool,
+}
+
+/// Registers a template and, recursively, every partial it references.
+pub fn register_template_tree(
+    handlebars: &mut Handlebars<'static>,
+    name: &str,
+    path: &Path,
+) -> Result<(), AppError> {
+    let mut loader = PartialLoader {
+        handlebars,
+        stack: Vec::new(),
+        registered: HashMap::new(),
+        inline_names: HashSet::new(),
+        missing: Vec::new(),
+    };
+    loader.register(name, path)?;
+
+    let unresolved: Vec<String> = loader
+        .missing
+        .into_iter()
+        .filter(|(partial, _)| !loader.inline_names.contains(partial))
+        .map(|(partial, path)| format!("{} (expected at {})", partial, path.display()))
+        .collect();
+
+    if unresolved.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::TemplateRenderError {
+            name: name.to_string(),
+            message: format!("unresolved partials: {}", unresolved.join(", ")),
+        })
+    }
+}
+
+/// Depth-first loader that tracks the include chain for cycle detection.
+struct PartialLoader<'h> {
+    handlebars: &'h mut Handlebars<'static>,
+    /// Canonical paths of templates currently being loaded.
+    stack: Vec<PathBuf>,
+    /// Partial names already registered with the engine, and the canonical
+    /// path of the file each was loaded from.
+    registered: HashMap<String, PathBuf>,
+    /// Names supplied by `{{#*inline}}` anywhere in the tree.
+    inline_names: HashSet<String>,
+    /// Required partials with no file on disk, checked after loading.
+    missing: Vec<(String, PathBuf)>,
+}
+
+impl PartialLoader<'_> {
+    fn register(&mut self, name: &str, path: &Path) -> Result<(), AppError> {
+        let source = std::fs::read_to_string(path).map_err(|e| AppError::TemplateNotFound {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+        let canonical = canonical_path(path);
+
+        if let Some(start) = self.stack.iter().position(|p| p == &canonical) {
+            let chain: Vec<String> = self.stack[start..]
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(AppError::TemplateRenderError {
+                name: name.to_string(),
+                message: format!("partial cycle detected: {}", chain.join(" -> ")),
+            });
+        }
+
+        log::debug!("Registering template '{}' from {}", name, path.display());
+        self.handlebars
+            .register_template_string(name, &source)
+            .map_err(|e| AppError::TemplateRenderError {
+                name: name.to_string(),
+                message: e.to_string(),
+            })?;
+        self.registered.insert(name.to_string(), canonical.clone());
+        self.inline_names.extend(find_inline_definitions(&source));
+
+        self.stack.push(canonical);
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        for reference in find_partial_references(&source) {
+            let partial_path = resolve_partial_path(base_dir, &reference.name);
+            if partial_path.is_file() {
+                if let Some(loaded) = self.registered.get(&reference.name) {
+                    let canonical = canonical_path(&partial_path);
+                    if loaded != &canonical {
+                        return Err(AppError::TemplateRenderError {
+                            name: name.to_string(),
+                            message: format!(
+                                "partial '{}' resolves to both {} and {}; give one of them another name",
+                                reference.name,
+                                loaded.display(),
+                                canonical.display()
+                            ),
+                        });
+                    }
+                    if !self.stack.contains(&canonical) {
+                        continue;
+                    }
+                }
+                self.register(&reference.name, &partial_path)?;
+            } else if !reference.is_block {
+                self.missing.push((reference.name, partial_path));
+            }
+        }
+        self.stack.pop();
+
+        Ok(())
+    }
+}
+
+fn canonical_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Maps a partial name to its file, relative to the including template.
+fn resolve_partial_path(base_dir: &Path, name: &str) -> PathBuf {
+    let has_extension = Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext == "hbs" || ext == "handlebars");
+    if has_extension {
+        base_dir.join(name)
+    } else {
+        base_dir.join(format!("{}.hbs", name))
+    }
+}
+
+/// Extracts the statically named partials a template references.
+fn find_partial_references(source: &str) -> Vec<PartialReference> {
+    let mut seen = HashSet::new();
+    PARTIAL_REFERENCE
+        .captures_iter(source)
+        .filter_map(|caps| {
+            let name = caps.get(2)?.as_str().to_string();
+            seen.insert(name.clone()).then(|| PartialReference {
+                name,
+                is_block: !caps[1].is_empty(),
+            })
+        })
+        .collect()
+}
+
+/// Extracts the names of inline partials a template defines.
+fn find_inline_definitions(source: &str) -> Vec<String> {
+    INLINE_DEFINITION
+        .captures_iter(source)
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn template_dir(files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("n2p_partials_{}", uuid::Uuid::new_v4()));
+        for (name, content) in files {
+            let path = dir.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_finds_partial_references() {
+        let refs = find_partial_references(
+            r#"{{> header}} {{#> base}}x{{/base}} {{> "shared/footer"}} {{> @partial-block}}"#,
+        );
+        assert_eq!(
+            refs,
+            vec![
+                PartialReference {
+                    name: "header".into(),
+                    is_block: false
+                },
+                PartialReference {
+                    name: "base".into(),
+                    is_block: true
+                },
+                PartialReference {
+                    name: "shared/footer".into(),
+                    is_block: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_renders_nested_partials_relative_to_template() {
+        let dir = template_dir(&[
+            ("main.hbs", "{{> shared/header}}|{{main_content}}"),
+            ("shared/header.hbs", "[{{> title}}]"),
+            ("shared/title.hbs", "T"),
+        ]);
+        let mut hb = Handlebars::new();
+        register_template_tree(&mut hb, "main", &dir.join("main.hbs")).unwrap();
+
+        let out = hb.render("main", &json!({"main_content": "body"})).unwrap();
+        assert_eq!(out, "[T]|body");
+    }
+
+    #[test]
+    fn test_inheritance_via_partial_blocks() {
+        let dir = template_dir(&[
+            (
+                "main.hbs",
+                r#"{{#> base}}{{#*inline "body"}}custom{{/inline}}{{/base}}"#,
+            ),
+            ("base.hbs", "<{{#> body}}default{{/body}}>"),
+        ]);
+        let mut hb = Handlebars::new();
+        register_template_tree(&mut hb, "main", &dir.join("main.hbs")).unwrap();
+
+        assert_eq!(hb.render("main", &json!({})).unwrap(), "<custom>");
+    }
+
+    #[test]
+    fn test_detects_partial_cycles() {
+        let dir = template_dir(&[
+            ("main.hbs", "{{> a}}"),
+            ("a.hbs", "{{> b}}"),
+            ("b.hbs", "{{> a}}"),
+        ]);
+        let mut hb = Handlebars::new();
+        let err = register_template_tree(&mut hb, "main", &dir.join("main.hbs")).unwrap_err();
+
+        assert!(err.to_string().contains("partial cycle detected"));
+    }
+
+    #[test]
+    fn test_rejects_one_partial_name_for_two_files() {
+        let dir = template_dir(&[
+            ("main.hbs", "{{> a/card}}{{> b/card}}"),
+            ("a/card.hbs", "{{> header}}"),
+            ("a/header.hbs", "A"),
+            ("b/card.hbs", "{{> header}}"),
+            ("b/header.hbs", "B"),
+        ]);
+        let mut hb = Handlebars::new();
+        let err = register_template_tree(&mut hb, "main", &dir.join("main.hbs")).unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("partial 'header' resolves to both"));
+    }
+
+    #[test]
+    fn test_reports_missing_partials() {
+        let dir = template_dir(&[("main.hbs", "{{> nowhere}}")]);
+        let mut hb = Handlebars::new();
+        let err = register_template_tree(&mut hb, "main", &dir.join("main.hbs")).unwrap_err();
+
+        assert!(err.to_string().contains("unresolved partials: nowhere"));
+    }
+}
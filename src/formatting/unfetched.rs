@@ -0,0 +1,206 @@
+// src/formatting/unfetched.rs
+//! Compose-time markers for blocks the fetch left out.
+//!
+//! When `--limit` cuts a list of blocks off, or `--depth` stops above a
+//! block's children, the fetch records the cut on the parent. This pass
+//! puts a `[... N more blocks not fetched]` paragraph at each cut, so a
+//! reader of the prompt knows content is missing right there instead of
+//! taking the list for complete. The count is left out where the blocks
+//! were never listed.
+//!
+//! The marker becomes the parent's last child; after a table or column
+//! list, whose children are rows and columns, it follows the parent.
+
+use super::messages::Messages;
+use crate::config::PipelineConfig;
+use crate::model::blocks::ChildDatabaseContent;
+use crate::model::{
+    Block, BlockCommon, Database, NotionObject, Page, ParagraphBlock, TextBlockContent,
+    UnfetchedBlocks,
+};
+use crate::types::{BlockId, Color, RichTextItem};
+use std::borrow::Cow;
+
+/// `object` with a marker where the fetch cut blocks off, borrowed
+/// unchanged when nothing was.
+pub fn marked_unfetched<'a>(
+    object: Cow<'a, NotionObject>,
+    config: &PipelineConfig,
+) -> Cow<'a, NotionObject> {
+    if !has_unfetched(&object) {
+        return object;
+    }
+    let messages = Messages::for_language(config.lang);
+    let mut object = object.into_owned();
+    let marked = match &mut object {
+        NotionObject::Page(page) => mark_in_page(page, messages),
+        NotionObject::Database(db) => mark_in_database(db, messages),
+        // A table or column list on its own has nowhere to put its marker
+        NotionObject::Block(block) => mark_below(block, messages),
+    };
+    log::info!("Marked {} places where blocks were not fetched", marked);
+    Cow::Owned(object)
+}
+
+/// Whether the fetch cut blocks off anywhere in `object`, because of
+/// `--depth` or `--limit`.
+pub fn has_unfetched(object: &NotionObject) -> bool {
+    match object {
+        NotionObject::Page(page) => page_has_unfetched(page),
+        NotionObject::Database(db) => db.rows().any(page_has_unfetched),
+        NotionObject::Block(block) => blocks_have_unfetched(std::slice::from_ref(block)),
+    }
+}
+
+fn page_has_unfetched(page: &Page) -> bool {
+    page.unfetched_blocks.is_some() || blocks_have_unfetched(&page.blocks)
+}
+
+fn blocks_have_unfetched(blocks: &[Block]) -> bool {
+    blocks.iter().any(|block| {
+        block.common().unfetched_children.is_some()
+            || blocks_have_unfetched(block.children())
+            || matches!(block, Block::ChildDatabase(child)
+                if matches!(&child.content, ChildDatabaseContent::Fetched(db)
+                    if db.rows().any(page_has_unfetched)))
+    })
+}
+
+fn mark_in_page(page: &mut Page, messages: &Messages) -> usize {
+    let mut marked = mark_in_sequence(&mut page.blocks, messages);
+    if let Some(unfetched) = page.unfetched_blocks.take() {
+        page.blocks.push(marker(unfetched, messages));
+        marked += 1;
+    }
+    marked
+}
+
+fn mark_in_database(db: &mut Database, messages: &Messages) -> usize {
+    let rows = db
+        .pages
+        .iter_mut()
+        .chain(db.data_sources.iter_mut().flat_map(|s| &mut s.pages));
+    rows.map(|row| mark_in_page(row, messages)).sum()
+}
+
+/// Marks the cuts below a sibling sequence. The marker of a table or
+/// column list follows it in the sequence; any other block gets its
+/// marker as its last child.
+fn mark_in_sequence(blocks: &mut Vec<Block>, messages: &Messages) -> usize {
+    let mut marked = 0;
+    let mut index = 0;
+    while index < blocks.len() {
+        marked += mark_below(&mut blocks[index], messages);
+        if let Some(unfetched) = blocks[index].common_mut().unfetched_children.take() {
+            blocks.insert(index + 1, marker(unfetched, messages));
+            marked += 1;
+            index += 1;
+        }
+        index += 1;
+    }
+    marked
+}
+
+/// Marks the cuts within `block`, leaving its own for the enclosing
+/// sequence when it is a table or column list.
+fn mark_below(block: &mut Block, messages: &Messages) -> usize {
+    let mut marked = 0;
+    if let Block::ChildDatabase(child) = block {
+        if let ChildDatabaseContent::Fetched(db) = &mut child.content {
+            marked += mark_in_database(db, messages);
+        }
+    }
+    marked += mark_in_sequence(block.children_mut(), messages);
+    if matches!(block, Block::Table(_) | Block::ColumnList(_)) {
+        return marked;
+    }
+    if let Some(unfetched) = block.common_mut().unfetched_children.take() {
+        let common = block.common_mut();
+        common.children.push(marker(unfetched, messages));
+        common.has_children = true;
+        marked += 1;
+    }
+    marked
+}
+
+/// The paragraph standing in for `unfetched`.
+fn marker(unfetched: UnfetchedBlocks, messages: &Messages) -> Block {
+    let text = match unfetched.count {
+        Some(count) => format!("[... {} {}]", count, messages.more_blocks_not_fetched),
+        None => format!("[... {}]", messages.more_blocks_not_fetched),
+    };
+    Block::Paragraph(ParagraphBlock {
+        common: BlockCommon::new(BlockId::new_v4()),
+        content: TextBlockContent {
+            rich_text: vec![RichTextItem::plain_text(&text)],
+            color: Color::Default,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::testing::test_page;
+    use crate::model::{TableBlock, ToggleBlock};
+
+    fn text_of(block: &Block) -> &str {
+        match block {
+            Block::Paragraph(b) => &b.content.rich_text[0].plain_text,
+            Block::Toggle(b) => &b.content.rich_text[0].plain_text,
+            _ => "",
+        }
+    }
+
+    #[test]
+    fn test_markers_stand_where_blocks_were_cut_off() {
+        let mut toggle = Block::Toggle(ToggleBlock {
+            common: BlockCommon::new(BlockId::new_v4()),
+            content: TextBlockContent {
+                rich_text: vec![RichTextItem::plain_text("Details")],
+                color: Color::Default,
+            },
+        });
+        toggle.common_mut().has_children = true;
+        toggle.common_mut().unfetched_children = Some(UnfetchedBlocks::default());
+        let mut table = Block::Table(TableBlock {
+            common: BlockCommon::new(BlockId::new_v4()),
+            table_width: 1,
+            has_column_header: false,
+            has_row_header: false,
+        });
+        table.common_mut().unfetched_children = Some(UnfetchedBlocks::default());
+        let mut page = test_page("Plan").with_blocks(vec![toggle, table]);
+        page.unfetched_blocks = Some(UnfetchedBlocks { count: Some(3) });
+
+        let object = NotionObject::Page(page);
+        let marked = marked_unfetched(Cow::Borrowed(&object), &PipelineConfig::default());
+        let NotionObject::Page(page) = marked.as_ref() else {
+            panic!("expected a page");
+        };
+        let texts: Vec<&str> = page.blocks.iter().map(text_of).collect();
+        assert_eq!(
+            texts,
+            [
+                "Details",
+                "",
+                "[... more blocks not fetched]",
+                "[... 3 more blocks not fetched]"
+            ]
+        );
+        let children: Vec<&str> = page.blocks[0].children().iter().map(text_of).collect();
+        assert_eq!(children, ["[... more blocks not fetched]"]);
+        assert!(page.blocks[1].children().is_empty());
+
+        let NotionObject::Page(original) = &object else {
+            unreachable!()
+        };
+        let unmarked = NotionObject::Page(Page {
+            unfetched_blocks: None,
+            blocks: vec![],
+            ..original.clone()
+        });
+        let unchanged = marked_unfetched(Cow::Borrowed(&unmarked), &PipelineConfig::default());
+        assert!(matches!(unchanged, Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,217 @@
+// src/formatting/whitespace.rs
+//! Whitespace cleanup for rendered markdown (`--normalize-whitespace`).
+//!
+//! Notion text carries no-break and other Unicode spaces, zero-width
+//! characters and hyphen look-alikes, and composed documents can end up
+//! with runs of blank lines or padded table cells. None of it changes how
+//! the markdown reads, but all of it shows up in diffs and token counts.
+//!
+//! Code is left exactly as written: fenced blocks are copied verbatim, since
+//! whitespace is significant in languages such as Python, YAML or Make, and
+//! so are inline code spans.
+
+/// Normalizes whitespace in `markdown` outside code.
+///
+/// - Unicode spaces become plain spaces and zero-width spaces are dropped
+/// - Non-breaking and other hyphen variants become `-`
+/// - Runs of spaces after a line's indentation collapse to one, and
+///   trailing whitespace is removed
+/// - Table cells are trimmed to `| cell | cell |`
+/// - Consecutive blank lines collapse to one
+pub fn normalize_whitespace(markdown: &str) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut fence: Option<Fence> = None;
+    let mut previous_blank = false;
+
+    for line in markdown.lines() {
+        if let Some(open) = &fence {
+            if open.is_closed_by(line) {
+                fence = None;
+            }
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        }
+        if let Some(open) = Fence::opened_by(line) {
+            fence = Some(open);
+            previous_blank = false;
+            out.push_str(line.trim_end());
+            out.push('\n');
+            continue;
+        }
+
+        let line = normalize_line(line);
+        let blank = line.is_empty();
+        if blank && previous_blank {
+            continue;
+        }
+        previous_blank = blank;
+        out.push_str(&line);
+        out.push('\n');
+    }
+
+    let trimmed = out.trim_end_matches('\n').len();
+    out.truncate(trimmed);
+    if markdown.ends_with('\n') && !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+/// An open code fence: its marker character and length.
+pub(super) struct Fence {
+    marker: char,
+    len: usize,
+}
+
+impl Fence {
+    pub(super) fn opened_by(line: &str) -> Option<Self> {
+        let trimmed = line.trim_start();
+        let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+        let len = trimmed.chars().take_while(|c| *c == marker).count();
+        (len >= 3).then_some(Self { marker, len })
+    }
+
+    pub(super) fn is_closed_by(&self, line: &str) -> bool {
+        let trimmed = line.trim();
+        trimmed.chars().take_while(|c| *c == self.marker).count() >= self.len
+            && trimmed.chars().all(|c| c == self.marker)
+    }
+
+    /// The shortest line that closes this fence.
+    pub(super) fn closing(&self) -> String {
+        std::iter::repeat_n(self.marker, self.len).collect()
+    }
+}
+
+fn normalize_line(line: &str) -> String {
+    let indent_len = line.len() - line.trim_start_matches(' ').len();
+    let (indent, rest) = line.split_at(indent_len);
+
+    let mut out = String::with_capacity(line.len());
+    out.push_str(indent);
+    // Length of the backtick run that opened the current code span.
+    let mut code_span: Option<usize> = None;
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '`' {
+            let mut run = 1;
+            while chars.next_if_eq(&'`').is_some() {
+                run += 1;
+            }
+            out.extend(std::iter::repeat_n('`', run));
+            code_span = match code_span {
+                None => Some(run),
+                Some(open) if open == run => None,
+                open => open,
+            };
+            continue;
+        }
+        if code_span.is_some() {
+            out.push(c);
+            continue;
+        }
+        match normalized_char(c) {
+            Some(' ') if out.len() > indent_len && out.ends_with(' ') => {}
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+
+    let trimmed = out.trim_end().len();
+    out.truncate(trimmed);
+    if is_table_row(&out) {
+        trim_table_cells(&out)
+    } else {
+        out
+    }
+}
+
+/// The plain replacement for `c`, or `None` to drop it.
+fn normalized_char(c: char) -> Option<char> {
+    match c {
+        ' ' | '\u{00A0}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}' => {
+            Some(' ')
+        }
+        '\u{200B}' | '\u{FEFF}' => None,
+        '\u{2010}' | '\u{2011}' | '\u{2012}' => Some('-'),
+        c => Some(c),
+    }
+}
+
+fn is_table_row(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.len() > 1 && trimmed.starts_with('|') && trimmed.ends_with('|')
+}
+
+/// Rewrites a table row with every cell trimmed, keeping escaped pipes.
+fn trim_table_cells(line: &str) -> String {
+    let indent_len = line.len() - line.trim_start().len();
+    let (indent, row) = line.split_at(indent_len);
+
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = row.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                cell.push(c);
+                if let Some(escaped) = chars.next() {
+                    cell.push(escaped);
+                }
+            }
+            '|' => cells.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    // The row starts and ends with a pipe: drop the empty edges.
+    let inner = &cells[1..];
+    let cells: Vec<&str> = inner.iter().map(|cell| cell.trim()).collect();
+    format!("{}| {} |", indent, cells.join(" | "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unicode_spaces_hyphens_and_runs_are_normalized() {
+        let input = "Non\u{00A0}breaking\u{2009}thin  double\u{200B} and self\u{2011}hosted   \n";
+
+        assert_eq!(
+            normalize_whitespace(input),
+            "Non breaking thin double and self-hosted\n"
+        );
+    }
+
+    #[test]
+    fn test_blank_line_runs_collapse_and_indentation_stays() {
+        let input = "# Title\n\n\n\n- item\n    - nested  item\n\n\n";
+
+        assert_eq!(
+            normalize_whitespace(input),
+            "# Title\n\n- item\n    - nested item\n"
+        );
+    }
+
+    #[test]
+    fn test_table_cells_are_trimmed_and_escaped_pipes_kept() {
+        let input = "| Name   | Note |\n| :--- | :--- |\n| a\\|b  |  x   y |\n";
+
+        assert_eq!(
+            normalize_whitespace(input),
+            "| Name | Note |\n| :--- | :--- |\n| a\\|b | x y |\n"
+        );
+    }
+
+    #[test]
+    fn test_code_is_left_untouched() {
+        let input =
+            "```python\ndef f():\n\n\n    return\u{00A0}1  \n```\nuse `a  b` here\u{00A0}\n";
+
+        assert_eq!(
+            normalize_whitespace(input),
+            "```python\ndef f():\n\n\n    return\u{00A0}1  \n```\nuse `a  b` here\n"
+        );
+    }
+}
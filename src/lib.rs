@@ -3,26 +3,59 @@
 //!
 //! # Public API
 //!
-//! The library exposes types organized by concern:
-//! - **Error handling** — `AppError`, `ValidationError`
-//! - **Configuration** — `PipelineConfig`
-//! - **Domain model** — `NotionObject`, `Page`, `Database`, `Block`, etc.
-//! - **Domain types** — `NotionId`, `ApiKey`, `BlockId`, `PageId`, etc.
+//! The stable API covers four concerns and follows semantic versioning:
+//! - **Error handling** — `AppError`, `ExitCode`, `ValidationError`, and the
+//!   `DeliveryFailure`/`DeliveryKind` records of `AppError::DeliveryFailed`
+//! - **Configuration** — `PipelineConfig` and its option enums
+//! - **Pipeline** — `ContentSource`, `PromptComposer`, `PromptDelivery` and their async forms
+//! - **Rendering** — `render_prompt`, `render_prompt_to`, `render_blocks`, `RenderContext`,
+//!   and `ComposePlugin` hooks registered with `PipelineConfig::with_plugin`
+//!
+//! together with the core model they take and return (`NotionObject`, `Page`,
+//! `Database`, `Block`, `NotionId`, `RenderedPrompt`). [`prelude`] brings the
+//! everyday subset into scope with one `use`. `PipelineConfig`, `AppError`,
+//! `Page`, `Database` and `Block` are `#[non_exhaustive]`: start from
+//! `PipelineConfig::default()`, and build pages and databases with
+//! `Page::new` and `Database::new`.
+//!
+//! Everything else is exported only with the `unstable` feature and may change
+//! in any release:
+//! - **Domain model** — block types, properties, rich text and typed IDs
 //! - **API client** — `NotionFetcher`, `NotionHttpClient`, parsers
-//! - **Formatting** — `render_blocks`, `RenderContext`, `TableBuilder`
+//! - **Formatting** — `TableBuilder`, section outlines, provenance
+//! - **Intermediate representation** — `Document` with HTML and JSON renderers,
+//!   and `write_epub`, which packages its HTML as an EPUB book
+//! - **Records** — `extract_records` flattens pages and rows for embedding pipelines;
+//!   `PropertiesFile` holds their typed property values
+//! - **Analytics** — `measure_content_detailed`, `ContentMeasurement`, `StatsReport`,
+//!   and `ExpectedSchema`, which checks a database for `--expect-schema`
+//! - **Algebras** — `NotionContent`, `NotionContentExt` and friends
+//!
+//! The `testkit` feature adds [`testkit`], property-based checks of the
+//! algebras' laws for downstream implementations.
+
+// Without `unstable`, only the stable facade is exported and most of the
+// crate is reachable from the binary alone.
+#![cfg_attr(not(feature = "unstable"), allow(dead_code, unused_imports))]
 
 // Internal modules — must match what's in main.rs
 mod analytics;
-#[cfg(feature = "bench")]
+#[cfg(all(feature = "fetch", feature = "bench"))]
 pub mod api;
-#[cfg(not(feature = "bench"))]
+#[cfg(all(feature = "fetch", not(feature = "bench")))]
 mod api;
 
 mod algebras;
+#[cfg(feature = "fetch")]
+mod clock;
 mod config;
+mod console;
 mod constants;
 mod error;
+#[cfg(feature = "fetch")]
 mod error_recovery;
+#[cfg(feature = "fetch")]
+mod file_lock;
 
 #[cfg(feature = "bench")]
 pub mod formatting;
@@ -42,63 +75,150 @@ pub mod types;
 #[cfg(not(feature = "bench"))]
 mod types;
 
+pub mod prelude;
+
 // --- Error Handling ---
-pub use crate::error::{AppError, DatabaseFetchFailure};
+pub use crate::error::{AppError, DeliveryFailure, DeliveryKind, ExitCode};
 pub use crate::types::ValidationError;
 
 // --- Configuration ---
-pub use crate::config::PipelineConfig;
+pub use crate::config::{
+    AutoSummary, CacheScope, CalloutStyle, HeadingToggles, HttpVersion, Language, LinkStyle,
+    MathStyle, Newline, OutputFormat, PipelineConfig, PropertyPlacement, ProvenanceFormat,
+    SchemaDriftAction, ToggleStyle,
+};
+
+// --- Core Model ---
+pub use crate::model::{Block, Database, NotionObject, Page};
+pub use crate::types::{NotionId, RenderedPrompt};
+
+// --- Pipeline Traits ---
+pub use crate::pipeline::{
+    AsyncPromptComposer, AsyncPromptDelivery, ContentSource, PromptComposer, PromptDelivery,
+};
+
+// --- Rendering ---
+pub use crate::formatting::block_renderer::{
+    compose_block_markdown, compose_database_summary, compose_notion_markdown,
+    compose_page_markdown, render_blocks, RenderContext,
+};
+pub use crate::formatting::direct_template::{render_prompt, render_prompt_to};
+pub use crate::formatting::plugins::{ComposePlugin, ComposePlugins};
+
+// ---------------------------------------------------------------------------
+// Unstable API: exported only with the `unstable` feature, no semver promise.
+// ---------------------------------------------------------------------------
+
+// --- Error Details ---
+#[cfg(feature = "unstable")]
+pub use crate::error::{AccessFailure, DatabaseFetchFailure, ResolveAttempt};
+
+// --- Console Output ---
+#[cfg(feature = "unstable")]
+pub use crate::config::PoolConfig;
+#[cfg(feature = "unstable")]
+pub use crate::console::{ColorChoice, Console, Verbosity};
+#[cfg(all(feature = "unstable", feature = "fetch"))]
+pub use crate::file_lock::FileLock;
 
 // --- Domain Model ---
+#[cfg(feature = "unstable")]
 pub use crate::model::{
-    Block, BlockCommon, BlockVisitor, Database, DatabaseProperty, DatabasePropertyType,
-    DatabaseTitle, NotionObject, NumberFormat, Page, PageTitle, Parent, PropertyTypeValue,
-    PropertyValue,
+    BlockCommon, BlockVisitor, DataSource, DatabaseProperty, DatabasePropertyType, DatabaseTitle,
+    NumberFormat, PageTitle, Parent, PropertyTypeValue, PropertyValue, UnfetchedBlocks,
 };
 
 // --- Block Types ---
+#[cfg(feature = "unstable")]
 pub use crate::model::blocks::{
-    BookmarkBlock, BreadcrumbBlock, BulletedListItemBlock, CalloutBlock, ChildDatabaseBlock,
-    ChildDatabaseContent, ChildPageBlock, CodeBlock, ColumnBlock, ColumnListBlock, DividerBlock,
-    EmbedBlock, EquationBlock, ExternalFile, FileBlock, FileObject, Heading1Block, Heading2Block,
-    Heading3Block, Icon, ImageBlock, LinkPreviewBlock, LinkToPageBlock, NotionFile,
-    NumberedListItemBlock, ParagraphBlock, PdfBlock, QuoteBlock, SyncedBlock, SyncedFrom,
-    TableBlock, TableOfContentsBlock, TableRowBlock, TemplateBlock, TextBlockContent, ToDoBlock,
-    ToggleBlock, UnsupportedBlock, VideoBlock,
+    AudioBlock, BookmarkBlock, BreadcrumbBlock, BulletedListItemBlock, CalloutBlock,
+    ChildDatabaseBlock, ChildDatabaseContent, ChildPageBlock, CodeBlock, ColumnBlock,
+    ColumnListBlock, DividerBlock, EmbedBlock, EquationBlock, ExternalFile, FileBlock, FileObject,
+    Heading1Block, Heading2Block, Heading3Block, Icon, ImageBlock, LinkPreviewBlock,
+    LinkToPageBlock, NotionFile, NumberedListItemBlock, ParagraphBlock, PdfBlock, QuoteBlock,
+    SyncedBlock, SyncedFrom, TableBlock, TableOfContentsBlock, TableRowBlock, TemplateBlock,
+    TextBlockContent, ToDoBlock, ToggleBlock, UnsupportedBlock, VideoBlock,
 };
 
 // --- Domain Types ---
+#[cfg(feature = "unstable")]
 pub use crate::types::{
-    Annotations, ApiKey, BlockId, Color, DatabaseId, DateValue, EquationData, FormulaResult, Link,
-    MentionData, MentionType, NotionId, PageId, PartialUser, PropertyName, RenderedPrompt,
-    RichTextItem, RichTextType, RollupResult, SelectOption, TemplateName, TextContent, User,
-    UserId, ValidatedUrl, Warning, WarningLevel,
+    Annotations, AnyId, ApiKey, BlockId, Color, DatabaseId, DateValue, EquationData, FormulaResult,
+    Link, MentionData, MentionType, PageId, PartialUser, PropertyName, RichTextItem, RichTextType,
+    RollupResult, SelectOption, TemplateName, TextContent, User, UserId, ValidatedUrl, Warning,
+    WarningLevel,
 };
 
 // --- API Client ---
+#[cfg(all(feature = "unstable", feature = "fetch"))]
 pub use crate::api::{
-    cache::CachedNotionClient,
-    client::ApiResponse,
+    assemble_from_json,
+    cache::{CacheMode, CachedNotionClient},
+    client::{relative_endpoint, ApiResponse},
     object_graph::ObjectGraph,
     parser::{
-        parse_block_response, parse_blocks_pagination, parse_database_response,
-        parse_page_response, parse_pages_pagination,
+        parse_block_response, parse_blocks_pagination, parse_blocks_pagination_within,
+        parse_database_response, parse_error_response, parse_page_response, parse_pages_pagination,
     },
-    NotionFetcher, NotionHttpClient, NotionRepository,
+    BudgetUsage, CategoryUsage, CheckpointedRepository, FetchCategory, FetchMetadata, FetchResult,
+    HarRecorder, NotionFetcher, NotionHttpClient, NotionRepository, RecordedExchange, SizeUsage,
+    TransferUsage,
 };
+#[cfg(feature = "unstable")]
+pub use crate::pipeline::WarningSink;
 
 // --- Formatting ---
-pub use crate::formatting::block_renderer::{
-    compose_block_markdown, compose_database_summary, compose_notion_markdown,
-    compose_page_markdown, render_blocks, RenderContext,
-};
+#[cfg(feature = "unstable")]
 pub use crate::formatting::databases::builder::TableBuilder;
-pub use crate::formatting::direct_template::render_prompt;
+#[cfg(feature = "unstable")]
+pub use crate::formatting::databases::{ColumnAlign, ColumnAlignment};
+#[cfg(feature = "unstable")]
+pub use crate::formatting::markdown_check::{
+    check_markdown, MarkdownCheck, MarkdownIssue, MarkdownIssueKind,
+};
+#[cfg(feature = "unstable")]
+pub use crate::formatting::redact::{IdKind, IdPseudonyms, Pseudonym};
+#[cfg(feature = "unstable")]
+pub use crate::formatting::{
+    annotate_changes, property_limit_warnings, Provenance, SectionOutline, SectionWeight,
+};
+#[cfg(feature = "unstable")]
+pub use crate::formatting::{compose_sections, PromptSection, SectionKind};
+#[cfg(feature = "unstable")]
+pub use crate::formatting::{BlockRules, CalloutKind, CalloutRule, DatabaseTitleOverride};
 
-// --- Pipeline Traits ---
-pub use crate::pipeline::{ContentSource, PromptComposer, PromptDelivery};
+// --- Intermediate Representation ---
+#[cfg(feature = "unstable")]
+pub use crate::formatting::ir::{
+    Document, Element, Heading, LinkKind, ListItem, PropertyEntry, Section, Span,
+};
+
+// --- Output ---
+#[cfg(feature = "unstable")]
+pub use crate::output::{write_epub, EncodingWriter, OutputEncoding};
+
+// --- Records ---
+#[cfg(feature = "unstable")]
+pub use crate::formatting::property_json::{
+    DateRange, FileLink, PageProperties, Person, PropertiesFile, Scalar, TypedValue, UniqueId,
+    PROPERTIES_SCHEMA_VERSION,
+};
+#[cfg(feature = "unstable")]
+pub use crate::formatting::records::{extract_records, extract_rows, Record};
+
+// --- Analytics ---
+#[cfg(feature = "unstable")]
+pub use crate::analytics::{
+    estimate_tokens, measure_content_detailed, ContentMeasurement, DatabaseSize, ExpectedSchema,
+    SchemaDrift, StatsReport,
+};
 
 // --- Algebras (Capability Traits) ---
+#[cfg(feature = "unstable")]
 pub use crate::algebras::{
     DepthLimiter, FetchError, NotionContent, NotionContentExt, TrackError, VisitTracker,
 };
+
+// --- Law Checks ---
+#[cfg(feature = "testkit")]
+pub use crate::algebras::testkit;
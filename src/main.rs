@@ -3,25 +3,42 @@
 // Modules defined in the crate
 mod analytics;
 mod api;
+mod clock;
 mod config;
+mod console;
 mod constants;
 mod error;
 mod error_recovery;
+mod file_lock;
+mod fixture;
 mod formatting;
 mod model;
+mod onboarding;
 mod output;
 mod pipeline;
 mod types;
 
 // Specific imports
-use crate::analytics::{embedded_database_count, measure_content};
-use crate::config::{CommandLineInput, PipelineConfig};
-use crate::error::AppError;
-use crate::pipeline::{ContentSource, PromptComposer, PromptDelivery};
+use crate::analytics::{
+    contained_object_ids, embedded_database_count, estimate_tokens, measure_content,
+    measure_content_detailed, unsupported_blocks, StatsReport,
+};
+use crate::config::{
+    ApiMethod, CacheCommand, Command, CommandLineInput, OutputFormat, PipelineConfig,
+    SchemaDriftAction,
+};
+use crate::console::{Console, Verbosity};
+use crate::error::{AppError, ExitCode};
+use crate::error_recovery::retry_with_backoff;
+use crate::file_lock::FileLock;
+use crate::pipeline::{
+    AsyncPromptComposer, AsyncPromptDelivery, ContentSource, PromptComposer, PromptDelivery,
+    WarningSink,
+};
 use clap::Parser;
 use log::LevelFilter;
 use log4rs::{
-    append::console::ConsoleAppender,
+    append::console::{ConsoleAppender, Target},
     append::file::FileAppender,
     config::{Appender, Root},
     encode::pattern::PatternEncoder,
@@ -31,14 +48,19 @@ use log4rs::{
 use model::NotionObject;
 use output::{deliver, DeliveryTarget, OutputReport};
 use std::fs;
-use types::RenderedPrompt;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use types::{NotionId, RenderedPrompt, Warning};
 
 /// Sets up logging configuration.
-fn setup_logging(verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
-    let log_level = if verbose {
-        LevelFilter::Debug
-    } else {
-        LevelFilter::Warn
+fn setup_logging(verbosity: Verbosity) -> Result<(), Box<dyn std::error::Error>> {
+    let verbose = verbosity == Verbosity::Verbose;
+    let log_level = match verbosity {
+        Verbosity::Quiet => LevelFilter::Error,
+        Verbosity::Normal => LevelFilter::Warn,
+        Verbosity::Verbose => LevelFilter::Debug,
     };
 
     let log_file_path = std::env::temp_dir().join("notion_fetcher.log");
@@ -52,7 +74,10 @@ fn setup_logging(verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
         "{m}{n}"
     };
 
+    // Log lines are diagnostics, so they share stderr with console warnings
+    // and never interleave with a piped prompt on stdout.
     let stdout_appender = ConsoleAppender::builder()
+        .target(Target::Stderr)
         .encoder(Box::new(PatternEncoder::new(pattern)))
         .build();
 
@@ -82,31 +107,628 @@ fn setup_logging(verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
 }
 
 /// Executes the three-stage Notion-to-prompt pipeline: fetch → render → deliver.
-async fn execute_pipeline(config: &PipelineConfig) -> Result<(), AppError> {
-    let pipeline = NotionToPrompt::new(config);
+///
+/// Returns `PartialSuccess` when the prompt was delivered but the fetch
+/// stage reported warnings or hit a depth/item limit.
+async fn execute_pipeline(
+    config: &PipelineConfig,
+    console: &Console,
+) -> Result<ExitCode, AppError> {
+    let pipeline = NotionToPrompt::new(config, console);
 
+    let _locks = pipeline.lock_outputs().await?;
+    let started = Instant::now();
     let content = pipeline.fetch(&config.notion_id).await?;
-    let prompt = pipeline.compose(&content)?;
-    let report = pipeline.deliver(prompt)?;
+    let fetching = started.elapsed();
+    pipeline.check_schema(&content)?;
+    let mut report = pipeline.compose_and_deliver(&content).await?;
+    report.timings.fetch = fetching;
+    pipeline.write_outline(&content)?;
+    pipeline.write_rows_jsonl(&content)?;
     pipeline.report_completion(&content, &report)?;
 
+    Ok(pipeline.completion_status(&content))
+}
+
+/// Exports every page and database shared with the integration into `output_dir`.
+///
+/// Objects already contained in an earlier export (rows of an exported
+/// database, child pages and databases) are not exported again; the index
+/// points at the file that includes them instead. With
+/// `dedupe_across_roots`, the same goes for databases and linked pages
+/// inside later roots: they are not refetched, and the file references
+/// the earlier one. With `properties_json`, every exported file gets a
+/// `.properties.json` sibling holding the typed property values of its pages.
+async fn export_workspace(
+    cli: &CommandLineInput,
+    output_dir: &Path,
+    dedupe_across_roots: bool,
+    properties_json: bool,
+    console: &Console,
+) -> Result<ExitCode, AppError> {
+    let api_key = PipelineConfig::resolve_api_key()?;
+    let client = api::NotionHttpClient::with_pool_config(&api_key, &cli.pool_config())?;
+    let mut roots = api::NotionRepository::search_shared(&client).await?;
+    roots.sort_by_key(export_order);
+    console.info(format!("Found {} shared pages and databases", roots.len()));
+
+    fs::create_dir_all(output_dir)?;
+    let covered_by = Arc::new(api::RootVisits::new());
+    let mut entries = Vec::with_capacity(roots.len());
+    let mut outcome = ExitCode::Success;
+
+    for (index, root) in roots.iter().enumerate() {
+        let id = root.id();
+        let title = root.display_title();
+        console.progress(index + 1, roots.len(), &title);
+
+        if let Some(file) = covered_by.first_occurrence(&id) {
+            console.detail(format!("'{}' is already included in {}", title, file));
+            entries.push(ExportEntry::new(root, ExportStatus::IncludedIn(file)));
+            continue;
+        }
+
+        let mut filename = output::create_clean_filename(&title, id.as_str(), true);
+        let mut config = PipelineConfig::resolve_for(cli, id.as_str())?;
+        match config.format {
+            OutputFormat::Epub => filename = format!("{}.epub", filename.trim_end_matches(".md")),
+            OutputFormat::Json => filename = format!("{}.json", filename.trim_end_matches(".md")),
+            OutputFormat::Prompt => {}
+        }
+        config.output_file = Some(output_dir.join(&filename));
+        config.clipboard = false;
+        config.pipe = false;
+        // Checkpoints and HAR files belong to a single root
+        config.checkpoint = None;
+        config.resume = None;
+        config.har = None;
+
+        let mut pipeline = NotionToPrompt::new(&config, console);
+        if dedupe_across_roots {
+            pipeline = pipeline.with_earlier_roots(Arc::clone(&covered_by));
+        }
+        match export_root(&pipeline, &id).await {
+            Ok(content) => {
+                if pipeline.completion_status(&content) != ExitCode::Success {
+                    outcome = ExitCode::PartialSuccess;
+                }
+                if properties_json {
+                    write_properties_json(output_dir, &filename, &content)?;
+                }
+                covered_by.record(contained_object_ids(&content), &filename);
+                entries.push(ExportEntry::new(root, ExportStatus::Exported(filename)));
+            }
+            Err(err) => {
+                log::warn!("Failed to export '{}' ({}): {}", title, id.as_str(), err);
+                outcome = ExitCode::PartialSuccess;
+                entries.push(ExportEntry::new(
+                    root,
+                    ExportStatus::Failed(err.to_string()),
+                ));
+            }
+        }
+    }
+    console.finish_progress();
+
+    let index_path = output_dir.join("index.md");
+    fs::write(&index_path, render_export_index(&entries))?;
+    console.success(format!(
+        "Exported {} objects to {} (index: {})",
+        entries
+            .iter()
+            .filter(|e| matches!(e.status, ExportStatus::Exported(_)))
+            .count(),
+        output_dir.display(),
+        index_path.display()
+    ));
+
+    Ok(outcome)
+}
+
+/// Renders API responses read from `input`, or from stdin without one, and
+/// delivers the prompt like a fetched one.
+async fn render_offline(
+    cli: &CommandLineInput,
+    input: Option<&Path>,
+    console: &Console,
+) -> Result<ExitCode, AppError> {
+    let text = match input {
+        Some(path) => fs::read_to_string(path)?,
+        None => std::io::read_to_string(std::io::stdin())?,
+    };
+    let content = api::assemble_from_json(&text)?;
+    let config = PipelineConfig::resolve_offline(cli, content.id().as_str())?;
+    let pipeline = NotionToPrompt::new(&config, console);
+
+    let _locks = pipeline.lock_outputs().await?;
+    pipeline.check_schema(&content)?;
+    let report = pipeline.compose_and_deliver(&content).await?;
+    pipeline.write_outline(&content)?;
+    pipeline.write_rows_jsonl(&content)?;
+    console.info(format!(
+        "Rendered {} objects from the input.",
+        measure_content(&content).total_objects
+    ));
+    pipeline.report_delivery(&report);
+
+    Ok(ExitCode::Success)
+}
+
+/// Fetches `notion_input` and prints a structural report to stdout.
+///
+/// The prompt is rendered in memory to estimate its size but never
+/// delivered, so this is safe to run before deciding on `--depth`,
+/// `--limit` or `--drop-rows` for the real run.
+async fn print_stats(
+    cli: &CommandLineInput,
+    notion_input: &str,
+    console: &Console,
+) -> Result<ExitCode, AppError> {
+    let config = PipelineConfig::resolve_for(cli, notion_input)?;
+    let pipeline = NotionToPrompt::new(&config, console);
+
+    let content = pipeline.fetch(&config.notion_id).await?;
+    let prompt = pipeline.compose(&content)?;
+    let report = StatsReport {
+        title: content.display_title(),
+        measurement: measure_content_detailed(&content),
+        estimated_tokens: estimate_tokens(prompt.as_str()),
+    };
+    print!("{}", report);
+
+    Ok(pipeline.completion_status(&content))
+}
+
+/// Fetches `notion_input` into the response cache without composing or
+/// delivering anything, so a later run within `--cache-ttl` is answered
+/// from disk. Every response is fetched fresh, so entries already cached
+/// get a new timestamp too.
+async fn warm_cache(
+    cli: &CommandLineInput,
+    notion_input: &str,
+    console: &Console,
+) -> Result<ExitCode, AppError> {
+    let config = PipelineConfig::resolve_for(cli, notion_input)?;
+    if config.no_cache || config.cache_scope == config::CacheScope::Run {
+        return Err(AppError::Validation(
+            "cache warm needs the shared cache; drop --no-cache and --cache-scope run".to_string(),
+        ));
+    }
+    let pipeline = NotionToPrompt::new(&config, console).refreshing_cache();
+
+    let content = pipeline.fetch(&config.notion_id).await?;
+    let stats = measure_content(&content);
+    let transfer = pipeline.transfer_usage.lock().unwrap_or_default();
+    console.success(format!(
+        "Cached '{}' ({} objects): {} responses fetched from Notion, {} new cache entries, {} refreshed",
+        content.display_title(),
+        stats.total_objects,
+        transfer.requests,
+        transfer.cache_added,
+        transfer.cache_refreshed
+    ));
+    console.info(format!("Transferred: {}.", transfer));
+    console.info(format!(
+        "Cached responses stay fresh for {}s; pass the same --cache-ttl to the next run.",
+        config.cache_ttl
+    ));
+
+    Ok(pipeline.completion_status(&content))
+}
+
+/// Sends one request through the same HTTP client the fetch uses (API key,
+/// proxy, headers, client certificate, retries) and prints the response
+/// body to stdout, pretty-printed when it is JSON.
+///
+/// Rate limits and server errors are retried. Other error responses are
+/// printed as well, so the exact body Notion returned is visible; the exit
+/// code then reflects the error.
+async fn call_api(
+    cli: &CommandLineInput,
+    method: ApiMethod,
+    path: &str,
+    data: Option<&str>,
+) -> Result<ExitCode, AppError> {
+    if method == ApiMethod::Get && data.is_some() {
+        return Err(AppError::Validation(
+            "--data is only sent with POST".to_string(),
+        ));
+    }
+    let body: serde_json::Value = match data {
+        Some(data) => serde_json::from_str(data)
+            .map_err(|e| AppError::Validation(format!("--data is not valid JSON: {}", e)))?,
+        None => serde_json::json!({}),
+    };
+
+    let api_key = PipelineConfig::resolve_api_key()?;
+    let client = api::NotionHttpClient::with_pool_config(&api_key, &cli.pool_config())?;
+    let endpoint = api::client::relative_endpoint(path);
+    let response = retry_with_backoff(
+        || async {
+            let response = match method {
+                ApiMethod::Get => client.get_text(endpoint).await?,
+                ApiMethod::Post => client.post_text(endpoint, &body).await?,
+            };
+            if response.status.as_u16() == 429 || response.status.is_server_error() {
+                return Err(api::parser::parse_error_response(&response));
+            }
+            Ok(response)
+        },
+        3,
+        Duration::from_millis(100),
+        Duration::from_secs(5),
+    )
+    .await?;
+
+    match serde_json::from_str::<serde_json::Value>(&response.data) {
+        Ok(json) => println!(
+            "{}",
+            serde_json::to_string_pretty(&json).unwrap_or(response.data.clone())
+        ),
+        Err(_) => println!("{}", response.data),
+    }
+    if !response.status.is_success() {
+        return Err(api::parser::parse_error_response(&response));
+    }
+    Ok(ExitCode::Success)
+}
+
+/// Writes the typed property values of every page in `content` next to
+/// the exported `filename`, as `<stem>.properties.json`.
+fn write_properties_json(
+    output_dir: &Path,
+    filename: &str,
+    content: &NotionObject,
+) -> Result<(), AppError> {
+    let stem = Path::new(filename)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(filename);
+    let file = formatting::property_json::PropertiesFile::from_object(content);
+    let json = serde_json::to_string_pretty(&file)?;
+    fs::write(output_dir.join(format!("{}.properties.json", stem)), json)?;
     Ok(())
 }
 
+/// Runs fetch → compose → deliver for one workspace export root.
+async fn export_root(
+    pipeline: &NotionToPrompt<'_>,
+    id: &NotionId,
+) -> Result<NotionObject, AppError> {
+    let _locks = pipeline.lock_outputs().await?;
+    let content = pipeline.fetch(id).await?;
+    pipeline.compose_and_deliver(&content).await?;
+    Ok(content)
+}
+
+/// Orders export roots so containers are exported before what they contain:
+/// workspace-level objects first, database rows last.
+fn export_order(object: &NotionObject) -> u8 {
+    let parent = match object {
+        NotionObject::Page(page) => page.parent.as_ref(),
+        NotionObject::Database(db) => db.parent.as_ref(),
+        NotionObject::Block(_) => None,
+    };
+    match parent {
+        Some(model::Parent::Workspace) | None => 0,
+        Some(model::Parent::Page { .. }) | Some(model::Parent::Block { .. }) => 1,
+        Some(model::Parent::Database { .. }) => 2,
+    }
+}
+
+/// How one search result ended up in a workspace export.
+enum ExportStatus {
+    /// Written to its own file
+    Exported(String),
+    /// Already contained in another exported file
+    IncludedIn(String),
+    /// The pipeline failed for this object
+    Failed(String),
+}
+
+/// One line of the workspace export index.
+struct ExportEntry {
+    title: String,
+    kind: String,
+    status: ExportStatus,
+}
+
+impl ExportEntry {
+    fn new(object: &NotionObject, status: ExportStatus) -> Self {
+        Self {
+            title: object.display_title(),
+            kind: object.object_type_name().to_string(),
+            status,
+        }
+    }
+}
+
+/// Renders the top-level `index.md` for a workspace export.
+fn render_export_index(entries: &[ExportEntry]) -> String {
+    let mut index = String::from("# Workspace Export\n\n");
+    for entry in entries {
+        let line = match &entry.status {
+            ExportStatus::Exported(file) => {
+                format!("- [{}]({}) ({})", entry.title, file, entry.kind)
+            }
+            ExportStatus::IncludedIn(file) => format!(
+                "- {} ({}) — included in [{}]({})",
+                entry.title, entry.kind, file, file
+            ),
+            ExportStatus::Failed(reason) => {
+                format!(
+                    "- {} ({}) — ⚠️ export failed: {}",
+                    entry.title, entry.kind, reason
+                )
+            }
+        };
+        index.push_str(&line);
+        index.push('\n');
+    }
+    index
+}
+
 /// Orchestrates the retrieval, rendering, and delivery of Notion content as prompts.
 struct NotionToPrompt<'a> {
     config: &'a PipelineConfig,
+    console: &'a Console,
+    /// Number of warnings reported by the fetch stage.
+    fetch_warnings: AtomicUsize,
+    /// When the fetch stage finished, for the provenance header.
+    fetched_at: parking_lot::Mutex<Option<chrono::DateTime<chrono::Utc>>>,
+    /// How much of each category's share of `--limit` the fetch spent.
+    budget_usage: parking_lot::Mutex<Option<api::BudgetUsage>>,
+    /// The estimated prompt size against `--token-budget`, when set.
+    size_usage: parking_lot::Mutex<Option<api::SizeUsage>>,
+    /// Response payloads of the fetch, downloaded and answered from cache.
+    transfer_usage: parking_lot::Mutex<Option<api::TransferUsage>>,
+    /// Objects earlier roots of a multi-root run already include.
+    earlier_roots: Option<Arc<api::RootVisits>>,
+    /// Fetch every response, storing it over the cached one (`cache warm`).
+    refresh_cache: bool,
 }
 
 impl<'a> NotionToPrompt<'a> {
-    fn new(config: &'a PipelineConfig) -> Self {
-        Self { config }
+    fn new(config: &'a PipelineConfig, console: &'a Console) -> Self {
+        Self {
+            config,
+            console,
+            fetch_warnings: AtomicUsize::new(0),
+            fetched_at: parking_lot::Mutex::new(None),
+            budget_usage: parking_lot::Mutex::new(None),
+            size_usage: parking_lot::Mutex::new(None),
+            transfer_usage: parking_lot::Mutex::new(None),
+            earlier_roots: None,
+            refresh_cache: false,
+        }
+    }
+
+    /// Leaves out what earlier roots already include, referencing it instead.
+    fn with_earlier_roots(self, earlier_roots: Arc<api::RootVisits>) -> Self {
+        Self {
+            earlier_roots: Some(earlier_roots),
+            ..self
+        }
+    }
+
+    /// Fetches every response fresh, replacing what the cache holds.
+    fn refreshing_cache(self) -> Self {
+        Self {
+            refresh_cache: true,
+            ..self
+        }
+    }
+
+    /// Locks the output file and checkpoint this run writes, so a concurrent
+    /// run cannot interleave its writes with ours.
+    async fn lock_outputs(&self) -> Result<Vec<FileLock>, AppError> {
+        let mut locks = Vec::new();
+        if let Some(path) = &self.config.output_file {
+            locks.push(FileLock::for_file(path, "output file", self.config.lock_wait).await?);
+        }
+        if let Some(path) = &self.config.checkpoint {
+            locks.push(FileLock::for_file(path, "checkpoint", self.config.lock_wait).await?);
+        }
+        Ok(locks)
+    }
+
+    /// Classifies a delivered run as complete or partial. Content that only
+    /// reaches `--depth` or `--limit` is complete; it is partial when the
+    /// fetch actually left blocks or budgeted content out.
+    fn completion_status(&self, content: &NotionObject) -> ExitCode {
+        let cut_off = !self.cut_off_warnings(content).is_empty();
+
+        if self.fetch_warnings.load(Ordering::Relaxed) > 0 || cut_off {
+            ExitCode::PartialSuccess
+        } else {
+            ExitCode::Success
+        }
+    }
+
+    /// One warning per way the fetch left content out: blocks cut off at
+    /// `--depth` or `--limit`, a share of the item limit that ran out, or
+    /// the token budget. Empty when nothing was left out, however close the
+    /// content came to the limits.
+    fn cut_off_warnings(&self, content: &NotionObject) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if formatting::has_unfetched(content) {
+            warnings.push(format!(
+                "Some blocks were not fetched (--depth {}, --limit {}); the prompt marks where. Raise them to fetch more.",
+                self.config.depth, self.config.limit
+            ));
+        }
+        if let Some(usage) = *self.budget_usage.lock() {
+            for category in usage.exhausted() {
+                warnings.push(format!(
+                    "The {} share of the item limit ran out. Raise --limit to fetch more.",
+                    category
+                ));
+            }
+        }
+        if self.size_usage.lock().is_some_and(|usage| usage.reached) {
+            warnings.push(
+                "The token budget was reached, so blocks and linked pages were left out. Raise --token-budget to fetch more."
+                    .to_string(),
+            );
+        }
+        warnings
+    }
+
+    /// Composes the prompt and delivers it, streaming straight to disk when
+    /// a file is the only destination. With `--format epub` the book goes to
+    /// the output file instead, and nowhere else; with `--format json` the
+    /// content itself is delivered in place of the prompt. The report holds
+    /// how long composing and delivering took; for a streamed file, writing
+    /// counts as composing and delivering is the clipboard copy.
+    async fn compose_and_deliver(&self, content: &NotionObject) -> Result<OutputReport, AppError> {
+        let started = Instant::now();
+        let mut copying = Duration::ZERO;
+        let encoding = output::OutputEncoding::for_config(self.config);
+        let mut report = if self.config.format == OutputFormat::Epub {
+            let path = self.config.output_file.as_deref().ok_or_else(|| {
+                AppError::Validation(
+                    "--format epub writes a file; give it with --output-file".into(),
+                )
+            })?;
+            if !encoding.is_identity() {
+                return Err(AppError::Validation(
+                    "--newline and --ascii apply to text output, not to --format epub".into(),
+                ));
+            }
+            output::stream_to_file(path, self.config.backups, |writer| {
+                output::write_epub(content, self.config, writer)
+            })?
+        } else if self.config.format == OutputFormat::Json {
+            let mut json = serde_json::to_string_pretty(content)?;
+            if encoding.ascii {
+                // Transliterated quotes would break the JSON strings
+                json = output::escape_non_ascii_json(&json);
+            }
+            return self
+                .deliver_timed(RenderedPrompt::new(json), started.elapsed())
+                .await;
+        } else {
+            let Some(path) = self.streaming_target() else {
+                let prompt = self.compose_async(content).await?;
+                return self.deliver_timed(prompt, started.elapsed()).await;
+            };
+            let report = if encoding.is_identity() {
+                output::stream_to_file(path, self.config.backups, |writer| {
+                    self.compose_to(content, writer)
+                })?
+            } else {
+                output::stream_to_file(path, self.config.backups, |writer| {
+                    let mut encoded = output::EncodingWriter::new(writer, encoding);
+                    self.compose_to(content, &mut encoded)?;
+                    Ok(encoded.finish()?)
+                })?
+            };
+            if !self.config.clipboard {
+                report
+            } else {
+                let written = Instant::now();
+                let report = self.copy_streamed_to_clipboard(path, report)?;
+                copying = written.elapsed();
+                report
+            }
+        };
+        report.timings.compose = started.elapsed() - copying;
+        report.timings.deliver = copying;
+        report.timings.streamed = true;
+        Ok(report)
+    }
+
+    /// Delivers a prompt that took `composing` to compose, timing delivery.
+    async fn deliver_timed(
+        &self,
+        prompt: RenderedPrompt,
+        composing: Duration,
+    ) -> Result<OutputReport, AppError> {
+        let started = Instant::now();
+        let mut report = self.deliver_async(prompt).await?;
+        report.timings.compose = composing;
+        report.timings.deliver = started.elapsed();
+        Ok(report)
+    }
+
+    /// The output file, unless stdout needs the prompt as a string. With
+    /// `--clipboard` the file is streamed first and read back for the copy.
+    fn streaming_target(&self) -> Option<&Path> {
+        // Redaction rewrites the finished prompt, so it cannot be streamed
+        if self.config.pipe || self.config.redact_ids.is_some() {
+            return None;
+        }
+        self.config.output_file.as_deref()
+    }
+
+    /// Copies the file just streamed to `path` to the clipboard, adding the
+    /// copy to `report`. The file already has the output encoding applied,
+    /// so it is copied as written.
+    fn copy_streamed_to_clipboard(
+        &self,
+        path: &Path,
+        mut report: OutputReport,
+    ) -> Result<OutputReport, AppError> {
+        let content = fs::read_to_string(path)?;
+        let copied = deliver(
+            output::OutputPlan::new().with_operation(DeliveryTarget::CopyToClipboard { content }),
+        )?;
+        if !copied.is_success() {
+            return Err(AppError::DeliveryFailed {
+                failures: copied.failed.iter().map(|f| f.failure.clone()).collect(),
+            });
+        }
+        for completed in copied.completed {
+            report = report.with_completed(completed);
+        }
+        Ok(report)
+    }
+
+    /// The provenance header to prepend, if `--provenance` was given.
+    fn provenance_header(&self, content: &NotionObject) -> Option<String> {
+        let format = self.config.provenance?;
+        let fetched_at = self.fetched_at.lock().unwrap_or_else(chrono::Utc::now);
+        let provenance = formatting::Provenance {
+            api_usage: self
+                .transfer_usage
+                .lock()
+                .map(|usage| usage.rate_limit_summary()),
+            ..formatting::Provenance::new(
+                content,
+                self.config,
+                fetched_at,
+                self.fetch_warnings.load(Ordering::Relaxed),
+            )
+        };
+        Some(provenance.render(format))
+    }
+
+    /// Replaces the Notion IDs in `prompt` with pseudonyms if `--redact-ids`
+    /// was given, extending the mapping file with any new ones.
+    fn redact_ids(
+        &self,
+        content: &NotionObject,
+        prompt: RenderedPrompt,
+    ) -> Result<RenderedPrompt, AppError> {
+        let Some(map_path) = &self.config.redact_ids else {
+            return Ok(prompt);
+        };
+        let mut pseudonyms = formatting::redact::IdPseudonyms::load(map_path)?;
+        let redacted = pseudonyms.redact(prompt.as_str(), content);
+        pseudonyms.save(map_path)?;
+        self.console.detail(format!(
+            "Redacted Notion IDs; the mapping is in {}",
+            map_path.display()
+        ));
+        Ok(RenderedPrompt::new(redacted))
     }
 
     /// Delivers the rendered prompt to configured outputs (file, clipboard, stdout).
     fn deliver_prompt(&self, prompt: RenderedPrompt) -> Result<OutputReport, AppError> {
         let prompt_str = prompt.into_string();
-        let mut plan = output::OutputPlan::new();
+        let mut plan = output::OutputPlan::new()
+            .with_encoding(output::OutputEncoding::for_config(self.config))
+            .with_backups(self.config.backups);
 
         if self.config.pipe {
             plan = plan.with_operation(DeliveryTarget::PrintToStdout {
@@ -131,7 +753,7 @@ impl<'a> NotionToPrompt<'a> {
 
         if !report.is_success() {
             return Err(AppError::DeliveryFailed {
-                failures: report.failed.iter().map(|f| f.error.clone()).collect(),
+                failures: report.failed.iter().map(|f| f.failure.clone()).collect(),
             });
         }
 
@@ -144,44 +766,222 @@ impl<'a> NotionToPrompt<'a> {
         content: &NotionObject,
         report: &OutputReport,
     ) -> Result<(), AppError> {
-        if self.config.pipe {
-            return Ok(());
-        }
-
         let stats = measure_content(content);
+        self.console.info(format!(
+            "Fetched {} objects from Notion.",
+            stats.total_objects
+        ));
 
-        if self.config.depth > 0 && stats.deepest_nesting >= self.config.depth as usize {
-            eprintln!(
-                "⚠️  Maximum recursion depth ({}) reached. Some deeply nested content may be missing.",
-                self.config.depth
-            );
+        if let Some(usage) = *self.budget_usage.lock() {
+            self.console.info(format!("Fetch budget: {}.", usage));
+        }
+        if let Some(usage) = *self.size_usage.lock() {
+            self.console.info(format!("Estimated size: {}.", usage));
+        }
+        for warning in self.cut_off_warnings(content) {
+            self.console.warn(warning);
         }
 
-        if stats.total_objects >= self.config.limit as usize {
-            eprintln!(
-                "⚠️  Item limit reached ({}/{}). Some content may be missing.",
-                stats.total_objects, self.config.limit
-            );
-        } else {
-            println!("📄 Fetched {} objects from Notion.", stats.total_objects);
+        if let Some(usage) = *self.transfer_usage.lock() {
+            self.console.info(format!("Transferred: {}.", usage));
+            self.console
+                .info(format!("API usage: {}.", usage.rate_limit_summary()));
+            if usage.rate_limited > 0 {
+                self.console.warn(format!(
+                    "Notion rate limited {} requests. A lower --concurrency may finish sooner.",
+                    usage.rate_limited
+                ));
+            }
+        }
+
+        for warning in formatting::property_limit_warnings(content, self.config) {
+            self.console.warn(warning);
         }
 
+        self.report_delivery(report);
+        self.console.detail(format!("{}.", report.timings));
+        Ok(())
+    }
+
+    /// Tells where the prompt went.
+    fn report_delivery(&self, report: &OutputReport) {
         for completed in &report.completed {
             match &completed.operation {
-                DeliveryTarget::WriteFile { path, .. } => {
-                    println!("✓ Prompt saved to {}", path.display());
+                DeliveryTarget::WriteFile { path, .. } | DeliveryTarget::StreamToFile { path } => {
+                    self.console
+                        .success(format!("Prompt saved to {}", path.display()));
                 }
                 DeliveryTarget::CopyToClipboard { .. } => {
-                    println!("✓ Prompt copied to clipboard");
+                    self.console.success("Prompt copied to clipboard");
                 }
                 _ => {}
             }
         }
 
-        if report.completed.is_empty() && !self.config.pipe {
-            println!("✓ Prompt generated (no output file or clipboard requested).");
+        if report.completed.is_empty() {
+            self.console
+                .success("Prompt generated (no output file or clipboard requested).");
         }
+    }
 
+    /// The content as an earlier run left it in the response cache, for
+    /// `--annotate-changes`. None when the option is off or nothing to
+    /// compare with is cached.
+    async fn cached_version(&self, id: &types::NotionId) -> Option<NotionObject> {
+        if !self.config.annotate_changes {
+            return None;
+        }
+        if self.config.cache_scope == config::CacheScope::Run {
+            self.console.warn(
+                "--annotate-changes compares with the shared cache; with --cache-scope run nothing is marked",
+            );
+            return None;
+        }
+        let stored = async {
+            let http_client = api::NotionHttpClient::with_pool_config(
+                &self.config.api_key,
+                &self.config.connection_pool,
+            )?
+            .with_max_block_bytes(self.config.max_block_bytes);
+            let client = api::CachedNotionClient::stored(http_client).await?;
+            api::NotionFetcher::new(Arc::new(client), self.config)
+                .fetch_recursive(id)
+                .await
+        };
+        match stored.await {
+            Ok(result) => Some(result.data),
+            Err(e) => {
+                log::debug!("No cached version of {}: {}", id.as_str(), e);
+                self.console.warn(format!(
+                    "No cached version of {} to compare with; nothing is marked",
+                    id.as_str()
+                ));
+                None
+            }
+        }
+    }
+
+    /// Wraps `client` in a checkpoint when `--checkpoint` or `--resume` is given.
+    fn checkpoint(
+        &self,
+        client: Arc<dyn api::NotionRepository>,
+        id: &types::NotionId,
+    ) -> Result<Option<Arc<api::CheckpointedRepository>>, AppError> {
+        let Some(path) = &self.config.checkpoint else {
+            return Ok(None);
+        };
+        let checkpoint = match &self.config.resume {
+            Some(from) => {
+                let checkpoint =
+                    api::CheckpointedRepository::resume(client, id, from, path.clone())?;
+                self.console.info(format!(
+                    "Resuming from {} ({} responses already fetched)",
+                    from.display(),
+                    checkpoint.restored()
+                ));
+                checkpoint
+            }
+            None => api::CheckpointedRepository::start(client, id, path.clone()),
+        };
+        Ok(Some(Arc::new(checkpoint)))
+    }
+
+    /// Writes the ranked section sizes to `--outline-file`, if given.
+    fn write_outline(&self, content: &NotionObject) -> Result<(), AppError> {
+        let Some(path) = &self.config.outline_file else {
+            return Ok(());
+        };
+        let outline = formatting::SectionOutline::measure(content, self.config)?;
+        fs::write(path, serde_json::to_string_pretty(&outline)? + "\n")?;
+        self.console.info(format!(
+            "Wrote an outline of {} sections (~{} tokens) to {}",
+            outline.sections.len(),
+            outline.total_tokens,
+            path.display()
+        ));
+        Ok(())
+    }
+
+    /// Writes every database row to `--export-jsonl`, if given, one JSON
+    /// object per line.
+    fn write_rows_jsonl(&self, content: &NotionObject) -> Result<(), AppError> {
+        let Some(path) = &self.config.export_jsonl else {
+            return Ok(());
+        };
+        let rows = formatting::records::extract_rows(content)?;
+        let mut jsonl = String::new();
+        for row in &rows {
+            jsonl.push_str(&serde_json::to_string(row)?);
+            jsonl.push('\n');
+        }
+        fs::write(path, jsonl)?;
+        self.console.info(format!(
+            "Wrote {} database rows to {}",
+            rows.len(),
+            path.display()
+        ));
+        Ok(())
+    }
+
+    /// Checks a fetched database against `--expect-schema`, if given,
+    /// failing on drift unless `--schema-drift warn` asks to render anyway.
+    fn check_schema(&self, content: &NotionObject) -> Result<(), AppError> {
+        let Some(schema) = &self.config.expect_schema else {
+            return Ok(());
+        };
+        let NotionObject::Database(db) = content else {
+            return Err(AppError::Validation(format!(
+                "--expect-schema needs a database, but '{}' is a {}",
+                content.display_title(),
+                content.object_type_name()
+            )));
+        };
+        let drift = schema.check(db);
+        if drift.is_empty() {
+            self.console.detail(format!(
+                "'{}' matches the expected schema",
+                content.display_title()
+            ));
+            return Ok(());
+        }
+        let drift: Vec<String> = drift
+            .iter()
+            .map(|difference| difference.describe(self.config.lang))
+            .collect();
+        for difference in &drift {
+            self.console.warn(format!("Schema drift: {}", difference));
+        }
+        match self.config.schema_drift {
+            SchemaDriftAction::Warn => Ok(()),
+            SchemaDriftAction::Fail => Err(AppError::SchemaDrift {
+                database: content.display_title(),
+                drift,
+            }),
+        }
+    }
+
+    /// Writes the raw JSON of unsupported blocks to `--diagnostics-file`, if given.
+    fn write_diagnostics(&self, content: &NotionObject) -> Result<(), AppError> {
+        let Some(path) = &self.config.diagnostics_file else {
+            return Ok(());
+        };
+        let blocks = unsupported_blocks(content);
+        let report: Vec<_> = blocks
+            .iter()
+            .map(|block| {
+                serde_json::json!({
+                    "id": block.common.id.as_str(),
+                    "type": block.block_type,
+                    "raw": block.raw,
+                })
+            })
+            .collect();
+        fs::write(path, serde_json::to_string_pretty(&report)? + "\n")?;
+        self.console.info(format!(
+            "Wrote {} unsupported block(s) to {}",
+            blocks.len(),
+            path.display()
+        ));
         Ok(())
     }
 
@@ -191,7 +991,7 @@ impl<'a> NotionToPrompt<'a> {
             NotionObject::Database(db) => {
                 log::info!(
                     "  {} rows, {} properties",
-                    db.pages.len(),
+                    db.row_count(),
                     db.properties.len()
                 );
             }
@@ -208,23 +1008,118 @@ impl<'a> NotionToPrompt<'a> {
     }
 }
 
+/// Logs fetch warnings as the workers report them.
+struct LogWarnings;
+
+impl WarningSink for LogWarnings {
+    fn warn(&self, warning: &Warning) {
+        log::warn!("Fetch warning: {}", warning);
+    }
+}
+
 #[async_trait::async_trait]
 impl ContentSource for NotionToPrompt<'_> {
     async fn fetch(&self, id: &types::NotionId) -> Result<NotionObject, AppError> {
         log::info!("Retrieving content for {}", id.as_str());
 
-        let http_client = api::NotionHttpClient::new(&self.config.api_key)?;
+        let mut http_client = api::NotionHttpClient::with_pool_config(
+            &self.config.api_key,
+            &self.config.connection_pool,
+        )?
+        .with_max_block_bytes(self.config.max_block_bytes);
+        let har = self
+            .config
+            .har
+            .as_ref()
+            .map(|path| (path, Arc::new(api::HarRecorder::new())));
+        if let Some((_, recorder)) = &har {
+            http_client = http_client.with_har(Arc::clone(recorder));
+        }
+        let previous = self.cached_version(id).await;
         let client: std::sync::Arc<dyn api::NotionRepository> = if self.config.no_cache {
             log::info!("Cache disabled — all requests go to Notion API");
             std::sync::Arc::new(http_client)
         } else {
-            log::info!("Cache enabled (TTL: {}s)", self.config.cache_ttl);
+            log::info!(
+                "Cache enabled (TTL: {}s, scope: {:?})",
+                self.config.cache_ttl,
+                self.config.cache_scope
+            );
+            let mode = if self.config.annotate_changes || self.refresh_cache {
+                api::CacheMode::Refresh
+            } else {
+                api::CacheMode::ReadWrite
+            };
             std::sync::Arc::new(
-                api::CachedNotionClient::new(http_client, self.config.cache_ttl).await?,
+                api::CachedNotionClient::new(
+                    http_client,
+                    self.config.cache_ttl,
+                    self.config.cache_scope,
+                )
+                .await?
+                .with_mode(mode),
             )
         };
-        let fetcher = api::NotionFetcher::new(client, self.config);
-        let result = fetcher.fetch_recursive(id).await?;
+        let checkpoint = self.checkpoint(client.clone(), id)?;
+        let client = match &checkpoint {
+            Some(checkpoint) => checkpoint.clone(),
+            None => client,
+        };
+        let mut fetcher =
+            api::NotionFetcher::new(client, self.config).with_warning_sink(Arc::new(LogWarnings));
+        if let Some(earlier_roots) = &self.earlier_roots {
+            fetcher = fetcher.with_earlier_roots(Arc::clone(earlier_roots));
+        }
+        let fetched = fetcher.fetch_recursive(id).await;
+        // Keep the progress first; a failed fetch's error outranks ours
+        if let Some(checkpoint) = &checkpoint {
+            match &fetched {
+                Ok(_) => checkpoint.remove(),
+                Err(_) => match checkpoint.save() {
+                    Ok(()) => self.console.warn(format!(
+                        "Progress saved; continue with --resume {}",
+                        checkpoint.path().display()
+                    )),
+                    Err(err) => log::warn!(
+                        "Could not save progress to {}: {}",
+                        checkpoint.path().display(),
+                        err
+                    ),
+                },
+            }
+        }
+        if let Some((path, recorder)) = &har {
+            match recorder.write(path) {
+                Ok(()) => self.console.info(format!(
+                    "Recorded {} API requests to {}",
+                    recorder.len(),
+                    path.display()
+                )),
+                Err(err) if fetched.is_err() => log::warn!(
+                    "Could not write the API recording to {}: {}",
+                    path.display(),
+                    err
+                ),
+                Err(err) => return Err(err),
+            }
+        }
+        let mut result = fetched?;
+        if let Some(earlier_roots) = &self.earlier_roots {
+            let linked = earlier_roots.link_duplicates(&mut result.data);
+            if linked > 0 {
+                log::info!(
+                    "Referenced {} databases and pages included by earlier roots",
+                    linked
+                );
+            }
+        }
+        if let Some(previous) = &previous {
+            let marked = formatting::annotate_changes(&mut result.data, previous);
+            self.console.info(format!(
+                "Marked {} changes since the cached version",
+                marked
+            ));
+        }
 
         log::info!(
             "Retrieved {} '{}' ({} items, depth {})",
@@ -233,10 +1128,14 @@ impl ContentSource for NotionToPrompt<'_> {
             result.metadata.items_fetched,
             result.metadata.max_depth_reached,
         );
-        for warning in &result.metadata.warnings {
-            log::warn!("Fetch warning: {}", warning);
-        }
+        self.fetch_warnings
+            .store(result.metadata.warnings.len(), Ordering::Relaxed);
+        *self.fetched_at.lock() = Some(chrono::Utc::now());
+        *self.budget_usage.lock() = result.metadata.budget;
+        *self.size_usage.lock() = result.metadata.size;
+        *self.transfer_usage.lock() = result.metadata.transfer;
         self.log_retrieved_content(&result.data);
+        self.write_diagnostics(&result.data)?;
 
         Ok(result.data)
     }
@@ -244,7 +1143,22 @@ impl ContentSource for NotionToPrompt<'_> {
 
 impl PromptComposer for NotionToPrompt<'_> {
     fn compose(&self, content: &NotionObject) -> Result<RenderedPrompt, AppError> {
-        formatting::render_prompt(content, self.config)
+        let prompt = formatting::render_prompt(content, self.config)?;
+        let prompt = match self.provenance_header(content) {
+            Some(header) => RenderedPrompt::new(header + prompt.as_str()),
+            None => prompt,
+        };
+        self.redact_ids(content, prompt)
+    }
+
+    fn compose_to(
+        &self,
+        content: &NotionObject,
+        writer: &mut dyn std::io::Write,
+    ) -> Result<usize, AppError> {
+        let header = self.provenance_header(content).unwrap_or_default();
+        writer.write_all(header.as_bytes())?;
+        Ok(header.len() + formatting::render_prompt_to(content, self.config, writer)?)
     }
 }
 
@@ -254,15 +1168,166 @@ impl PromptDelivery for NotionToPrompt<'_> {
     }
 }
 
+/// Parses arguments and runs the pipeline, returning the process exit code.
+///
+/// See [`ExitCode`] for the taxonomy scripts can branch on.
+async fn run() -> ExitCode {
+    let cli = match CommandLineInput::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+            return if err.use_stderr() {
+                ExitCode::InvalidInput
+            } else {
+                ExitCode::Success // --help and --version
+            };
+        }
+    };
+
+    let console = Console::new(cli.verbosity(), cli.color_choice(), cli.pipe);
+
+    if let Err(err) = setup_logging(cli.verbosity()) {
+        console.error(format!("failed to initialize logging: {}", err));
+        return ExitCode::Failure;
+    }
+
+    let result = match &cli.command {
+        Some(Command::ExportWorkspace {
+            output_dir,
+            dedupe_across_roots,
+            properties_json,
+        }) => {
+            export_workspace(
+                &cli,
+                output_dir,
+                *dedupe_across_roots,
+                *properties_json,
+                &console,
+            )
+            .await
+        }
+        Some(Command::Init) => onboarding::run_init(&console).await,
+        Some(Command::Stats { notion_input }) => print_stats(&cli, notion_input, &console).await,
+        Some(Command::Render {
+            input,
+            stdin_content: _,
+        }) => render_offline(&cli, input.as_deref(), &console).await,
+        Some(Command::Fixture {
+            notion_input,
+            out,
+            scramble_text,
+        }) => fixture::write_fixtures(&cli, notion_input, out, *scramble_text, &console).await,
+        Some(Command::Cache {
+            action: CacheCommand::Warm { notion_input },
+        }) => warm_cache(&cli, notion_input, &console).await,
+        Some(Command::Api { method, path, data }) => {
+            call_api(&cli, *method, path, data.as_deref()).await
+        }
+        None => match PipelineConfig::resolve(cli) {
+            Ok(config) => execute_pipeline(&config, &console).await,
+            Err(err) => Err(err),
+        },
+    };
+
+    result.unwrap_or_else(|err| {
+        console.error(err.to_string());
+        err.exit_code()
+    })
+}
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = CommandLineInput::parse();
+async fn main() -> std::process::ExitCode {
+    run().await.into()
+}
 
-    setup_logging(cli.verbose)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::ColorChoice;
 
-    let config = PipelineConfig::resolve(cli)?;
+    const PAGE_ID: &str = "12345678123456781234567812345678";
 
-    execute_pipeline(&config).await?;
+    fn config_for(args: &[&str]) -> PipelineConfig {
+        let cli = CommandLineInput::try_parse_from(["notion2prompt", PAGE_ID].iter().chain(args))
+            .unwrap();
+        PipelineConfig::resolve_offline(&cli, PAGE_ID).unwrap()
+    }
 
-    Ok(())
+    fn plan_page() -> model::Page {
+        model::Page::new(types::PageId::parse(PAGE_ID).unwrap(), "Plan")
+    }
+
+    #[test]
+    fn test_output_file_is_streamed_even_with_the_clipboard_on() {
+        let console = Console::new(Verbosity::Quiet, ColorChoice::Never, false);
+
+        let config = config_for(&["-o", "big.md"]);
+        assert!(config.clipboard);
+        let app = NotionToPrompt::new(&config, &console);
+        assert_eq!(app.streaming_target(), Some(Path::new("big.md")));
+
+        let config = config_for(&["-o", "big.md", "--no-clipboard"]);
+        assert!(!config.clipboard);
+        let app = NotionToPrompt::new(&config, &console);
+        assert_eq!(app.streaming_target(), Some(Path::new("big.md")));
+
+        let config = config_for(&["-o", "big.md", "--pipe"]);
+        let app = NotionToPrompt::new(&config, &console);
+        assert_eq!(app.streaming_target(), None);
+    }
+
+    #[test]
+    fn test_content_reaching_the_limit_is_complete_unless_cut_off() {
+        let console = Console::new(Verbosity::Quiet, ColorChoice::Never, false);
+        let config = config_for(&["--limit", "1"]);
+        let app = NotionToPrompt::new(&config, &console);
+        let mut page = plan_page();
+        assert_eq!(
+            app.completion_status(&NotionObject::Page(page.clone())),
+            ExitCode::Success
+        );
+        assert!(app
+            .cut_off_warnings(&NotionObject::Page(page.clone()))
+            .is_empty());
+
+        page.unfetched_blocks = Some(model::UnfetchedBlocks { count: Some(3) });
+        assert_eq!(
+            app.completion_status(&NotionObject::Page(page.clone())),
+            ExitCode::PartialSuccess
+        );
+        assert_eq!(app.cut_off_warnings(&NotionObject::Page(page)).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_compose_and_deliver_times_each_stage() {
+        let console = Console::new(Verbosity::Quiet, ColorChoice::Never, false);
+        let dir = std::env::temp_dir().join(format!("n2p_timings_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let content = NotionObject::Page(plan_page());
+
+        let path = dir.join("plan.md");
+        let config = config_for(&["-o", path.to_str().unwrap(), "--no-clipboard"]);
+        let app = NotionToPrompt::new(&config, &console);
+        let report = app.compose_and_deliver(&content).await.unwrap();
+        assert!(report.timings.streamed);
+        assert!(report.timings.compose > Duration::ZERO);
+        assert_eq!(report.timings.deliver, Duration::ZERO);
+        assert!(path.exists());
+
+        let path = dir.join("plan.json");
+        let config = config_for(&[
+            "-o",
+            path.to_str().unwrap(),
+            "--format",
+            "json",
+            "--no-clipboard",
+        ]);
+        let app = NotionToPrompt::new(&config, &console);
+        let report = app.compose_and_deliver(&content).await.unwrap();
+        assert!(!report.timings.streamed);
+        assert!(report.timings.deliver > Duration::ZERO);
+        assert!(path.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
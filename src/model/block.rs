@@ -24,6 +24,7 @@ macro_rules! match_all_blocks {
             Block::TableOfContents($pattern) => $result,
             Block::Image($pattern) => $result,
             Block::Video($pattern) => $result,
+            Block::Audio($pattern) => $result,
             Block::File($pattern) => $result,
             Block::Pdf($pattern) => $result,
             Block::Bookmark($pattern) => $result,
@@ -44,7 +45,10 @@ macro_rules! match_all_blocks {
 }
 
 /// Block represents all possible Notion block types
+///
+/// Block types may be added in any release.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum Block {
     Paragraph(ParagraphBlock),
     Heading1(Heading1Block),
@@ -63,6 +67,7 @@ pub enum Block {
     TableOfContents(TableOfContentsBlock),
     Image(ImageBlock),
     Video(VideoBlock),
+    Audio(AudioBlock),
     File(FileBlock),
     Pdf(PdfBlock),
     Bookmark(BookmarkBlock),
@@ -136,6 +141,7 @@ impl Block {
             Block::TableOfContents(_) => "table_of_contents",
             Block::Image(_) => "image",
             Block::Video(_) => "video",
+            Block::Audio(_) => "audio",
             Block::File(_) => "file",
             Block::Pdf(_) => "pdf",
             Block::Bookmark(_) => "bookmark",
@@ -178,6 +184,7 @@ impl Block {
             Block::TableOfContents(b) => visitor.visit_table_of_contents(&b.common.id),
             Block::Image(b) => visitor.visit_image(&b.common.id, b),
             Block::Video(b) => visitor.visit_video(&b.common.id, b),
+            Block::Audio(b) => visitor.visit_audio(&b.common.id, b),
             Block::File(b) => visitor.visit_file(&b.common.id, b),
             Block::Pdf(b) => visitor.visit_pdf(&b.common.id, b),
             Block::Bookmark(b) => visitor.visit_bookmark(&b.common.id, b),
@@ -263,6 +270,9 @@ pub trait BlockVisitor {
     fn visit_video(&mut self, _id: &BlockId, _video: &VideoBlock) -> Self::Output {
         Default::default()
     }
+    fn visit_audio(&mut self, _id: &BlockId, _audio: &AudioBlock) -> Self::Output {
+        Default::default()
+    }
     fn visit_file(&mut self, _id: &BlockId, _file: &FileBlock) -> Self::Output {
         Default::default()
     }
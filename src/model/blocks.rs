@@ -128,6 +128,14 @@ pub struct DividerBlock {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BreadcrumbBlock {
     pub common: BlockCommon,
+    /// Titles of the fetched pages and databases above the block, outermost
+    /// first and ending with the page that holds it. Filled in when the
+    /// object tree is assembled; empty if the block was never placed in one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ancestors: Vec<String>,
+    /// Whether `ancestors` starts at the top level of the workspace
+    #[serde(default)]
+    pub from_workspace: bool,
 }
 
 /// Table of contents block
@@ -152,6 +160,14 @@ pub struct VideoBlock {
     pub caption: Vec<RichTextItem>,
 }
 
+/// Audio block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioBlock {
+    pub common: BlockCommon,
+    pub audio: FileObject,
+    pub caption: Vec<RichTextItem>,
+}
+
 /// File block
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FileBlock {
@@ -181,6 +197,8 @@ pub struct BookmarkBlock {
 pub struct EmbedBlock {
     pub common: BlockCommon,
     pub url: String,
+    #[serde(default)]
+    pub caption: Vec<RichTextItem>,
 }
 
 /// Child page block
@@ -188,6 +206,10 @@ pub struct EmbedBlock {
 pub struct ChildPageBlock {
     pub common: BlockCommon,
     pub title: String,
+    /// The output of an earlier root that already includes this page
+    /// (`--dedupe-across-roots`); its content is then left out here
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub included_elsewhere: Option<String>,
 }
 
 /// The resolution state of a child database's content.
@@ -206,6 +228,12 @@ pub enum ChildDatabaseContent {
     LinkedDatabase,
     /// The database could not be accessed (permissions, not found, etc.)
     Inaccessible { reason: String },
+    /// Another output of the same run already includes this database
+    /// (`--dedupe-across-roots`); `location` names that output.
+    IncludedElsewhere { location: String },
+    /// Another view of a database this document already shows in full;
+    /// `title` names that first view.
+    ShownAbove { title: String },
     /// Fetch has not been attempted (initial state from block parsing).
     #[default]
     NotFetched,
@@ -297,6 +325,13 @@ pub struct LinkPreviewBlock {
 pub struct UnsupportedBlock {
     pub common: BlockCommon,
     pub block_type: String,
+    /// The block exactly as Notion returned it, kept with `--keep-raw-unsupported`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<serde_json::Value>,
+    /// The payload size, when the block was left out for exceeding
+    /// `--max-block-bytes` rather than for its type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped_bytes: Option<usize>,
 }
 
 /// File object types
@@ -9,6 +9,21 @@ pub struct BlockCommon {
     pub children: Vec<Block>,
     pub has_children: bool,
     pub archived: bool,
+    /// When the block was last edited, if the API said
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_edited_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// Children `--depth` or `--limit` left unfetched, after those fetched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unfetched_children: Option<UnfetchedBlocks>,
+}
+
+/// Blocks that `--depth` or `--limit` left unfetched, marked where they
+/// would have followed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct UnfetchedBlocks {
+    /// How many, when the fetch listed them before the limit cut them off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
 }
 
 impl BlockCommon {
@@ -19,6 +34,8 @@ impl BlockCommon {
             children: Vec::new(),
             has_children: false,
             archived: false,
+            last_edited_time: None,
+            unfetched_children: None,
         }
     }
 
@@ -37,6 +54,8 @@ impl Default for BlockCommon {
             children: Vec::new(),
             has_children: false,
             archived: false,
+            last_edited_time: None,
+            unfetched_children: None,
         }
     }
 }
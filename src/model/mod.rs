@@ -3,6 +3,8 @@ pub mod blocks;
 pub mod common;
 pub mod properties;
 mod property_value;
+#[cfg(test)]
+pub(crate) mod testing;
 
 pub use block::{Block, BlockVisitor};
 pub use blocks::*;
@@ -55,7 +57,11 @@ impl NotionObject {
 }
 
 /// A Notion page
+///
+/// Fields may be added in any release; build pages with [`Page::new`] and
+/// the `with_*` methods.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Page {
     pub id: PageId,
     pub title: PageTitle,
@@ -64,6 +70,51 @@ pub struct Page {
     pub properties: std::collections::HashMap<PropertyName, PropertyValue>,
     pub parent: Option<Parent>,
     pub archived: bool,
+    /// Blocks `--limit` left unfetched, after those fetched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unfetched_blocks: Option<UnfetchedBlocks>,
+}
+
+#[allow(dead_code)] // Library API
+impl Page {
+    /// An empty page: no URL, blocks, properties or parent.
+    pub fn new(id: impl Into<PageId>, title: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            title: PageTitle::new(title),
+            url: String::new(),
+            blocks: Vec::new(),
+            properties: std::collections::HashMap::new(),
+            parent: None,
+            archived: false,
+            unfetched_blocks: None,
+        }
+    }
+
+    pub fn with_url(self, url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            ..self
+        }
+    }
+
+    pub fn with_blocks(self, blocks: Vec<Block>) -> Self {
+        Self { blocks, ..self }
+    }
+
+    pub fn with_properties(
+        self,
+        properties: std::collections::HashMap<PropertyName, PropertyValue>,
+    ) -> Self {
+        Self { properties, ..self }
+    }
+
+    pub fn with_parent(self, parent: Parent) -> Self {
+        Self {
+            parent: Some(parent),
+            ..self
+        }
+    }
 }
 
 impl Page {
@@ -74,7 +125,11 @@ impl Page {
 }
 
 /// A Notion database
+///
+/// Fields may be added in any release; build databases with
+/// [`Database::new`] and the `with_*` methods.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct Database {
     pub id: DatabaseId,
     pub title: DatabaseTitle,
@@ -83,6 +138,64 @@ pub struct Database {
     pub properties: std::collections::HashMap<PropertyName, DatabaseProperty>,
     pub parent: Option<Parent>,
     pub archived: bool,
+    /// The database's data sources when it has more than one, each with
+    /// its own schema and rows. Empty for single-source databases, whose
+    /// rows and schema live in `pages` and `properties`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub data_sources: Vec<DataSource>,
+    /// Rows the query listed but the integration may not read, as with a
+    /// partially shared database; rendered as `*(no access)*` rows.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inaccessible_rows: Vec<PageId>,
+}
+
+#[allow(dead_code)] // Library API
+impl Database {
+    /// An empty database: no URL, rows, schema or parent. An empty `title`
+    /// leaves the database untitled.
+    pub fn new(id: impl Into<DatabaseId>, title: &str) -> Self {
+        let title = if title.is_empty() {
+            Vec::new()
+        } else {
+            vec![crate::types::RichTextItem::plain_text(title)]
+        };
+        Self {
+            id: id.into(),
+            title: DatabaseTitle::new(title),
+            url: String::new(),
+            pages: Vec::new(),
+            properties: std::collections::HashMap::new(),
+            parent: None,
+            archived: false,
+            data_sources: Vec::new(),
+            inaccessible_rows: Vec::new(),
+        }
+    }
+
+    pub fn with_url(self, url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            ..self
+        }
+    }
+
+    pub fn with_pages(self, pages: Vec<Page>) -> Self {
+        Self { pages, ..self }
+    }
+
+    pub fn with_properties(
+        self,
+        properties: std::collections::HashMap<PropertyName, DatabaseProperty>,
+    ) -> Self {
+        Self { properties, ..self }
+    }
+
+    pub fn with_parent(self, parent: Parent) -> Self {
+        Self {
+            parent: Some(parent),
+            ..self
+        }
+    }
 }
 
 impl Database {
@@ -90,6 +203,62 @@ impl Database {
     pub fn title(&self) -> &DatabaseTitle {
         &self.title
     }
+
+    /// Iterates over every row, across all data sources.
+    pub fn rows(&self) -> impl Iterator<Item = &Page> {
+        self.pages
+            .iter()
+            .chain(self.data_sources.iter().flat_map(|source| &source.pages))
+    }
+
+    /// Counts every row, across all data sources.
+    pub fn row_count(&self) -> usize {
+        self.pages.len()
+            + self
+                .data_sources
+                .iter()
+                .map(|source| source.pages.len())
+                .sum::<usize>()
+    }
+
+    /// Counts the rows the integration may not read, across all data sources.
+    pub fn inaccessible_row_count(&self) -> usize {
+        self.inaccessible_rows.len()
+            + self
+                .data_sources
+                .iter()
+                .map(|source| source.inaccessible_rows.len())
+                .sum::<usize>()
+    }
+}
+
+/// One data source of a multi-source database.
+///
+/// Since API version 2025-09-03 a Notion database is a container for one or
+/// more data sources; each has its own properties and is queried separately.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataSource {
+    pub id: NotionId,
+    pub name: String,
+    pub properties: std::collections::HashMap<PropertyName, DatabaseProperty>,
+    pub pages: Vec<Page>,
+    /// Rows of this source the integration may not read
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inaccessible_rows: Vec<PageId>,
+}
+
+impl DataSource {
+    /// Views this source as a single-source database, for the table renderer.
+    pub fn as_database(&self, database: &Database) -> Database {
+        Database {
+            title: DatabaseTitle::new(vec![crate::types::RichTextItem::plain_text(&self.name)]),
+            pages: self.pages.clone(),
+            properties: self.properties.clone(),
+            data_sources: Vec::new(),
+            inaccessible_rows: self.inaccessible_rows.clone(),
+            ..database.clone()
+        }
+    }
 }
 
 /// Parent reference with typed IDs
@@ -0,0 +1,29 @@
+//! Pages and databases for unit tests: everything left at its default
+//! except what a test sets with the `with_*` methods.
+
+use super::{Database, Page, PropertyTypeValue, PropertyValue};
+use crate::types::{DatabaseId, PageId, PropertyName};
+
+/// An empty page with a fresh id.
+pub(crate) fn test_page(title: &str) -> Page {
+    Page::new(PageId::new_v4(), title)
+}
+
+/// An empty database with a fresh id.
+pub(crate) fn test_database(title: &str) -> Database {
+    Database::new(DatabaseId::new_v4(), title)
+}
+
+impl Page {
+    /// Sets one property, keyed and identified by `name`.
+    pub(crate) fn with_property(mut self, name: &str, value: PropertyTypeValue) -> Self {
+        self.properties.insert(
+            PropertyName::new(name),
+            PropertyValue {
+                id: PropertyName::new(name),
+                type_specific_value: value,
+            },
+        );
+        self
+    }
+}
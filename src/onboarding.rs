@@ -0,0 +1,219 @@
+// src/onboarding.rs
+//! The `notion2prompt init` first-run wizard.
+//!
+//! Most first runs fail the same two ways: no API key, or a key whose
+//! integration has not been given access to the page (Notion answers 403 or
+//! 404). The wizard asks for the key, checks it against the API, explains
+//! how to share pages with the integration, saves the key to the config
+//! file, and tries a fetch on a URL of the user's choosing.
+
+use crate::api::{NotionHttpClient, NotionRepository};
+use crate::config::StoredConfig;
+use crate::console::Console;
+use crate::error::{AppError, ExitCode};
+use crate::types::{ApiKey, NotionId};
+use std::io::{BufRead, Write};
+
+/// Where Notion lists a user's integrations and their secrets.
+const INTEGRATIONS_URL: &str = "https://www.notion.so/my-integrations";
+
+/// Runs the wizard against the terminal.
+pub async fn run_init(console: &Console) -> Result<ExitCode, AppError> {
+    let stdin = std::io::stdin();
+    let mut prompts = Prompts {
+        input: &mut stdin.lock(),
+        output: &mut std::io::stdout(),
+    };
+
+    writeln!(prompts.output, "notion2prompt setup\n")?;
+    writeln!(
+        prompts.output,
+        "Create an internal integration at {} and copy its secret.",
+        INTEGRATIONS_URL
+    )?;
+
+    let Some((api_key, client, integration)) = ask_for_working_key(&mut prompts, console).await?
+    else {
+        console.warn("Setup cancelled; nothing was saved.");
+        return Ok(ExitCode::InvalidInput);
+    };
+
+    writeln!(prompts.output, "\n{}", sharing_instructions(&integration))?;
+
+    let path = StoredConfig::default_path().ok_or_else(|| {
+        AppError::MissingConfiguration(
+            "no user config directory found; set HOME or XDG_CONFIG_HOME".to_string(),
+        )
+    })?;
+    if path.exists() && !prompts.confirm(&format!("Overwrite {}?", path.display()))? {
+        console.warn("Kept the existing config file.");
+    } else {
+        StoredConfig {
+            api_key: api_key.as_str().to_string(),
+        }
+        .save(&path)?;
+        console.success(format!("API key saved to {}", path.display()));
+    }
+
+    test_fetch(&mut prompts, console, &client, &integration).await
+}
+
+/// Asks until Notion accepts a key, returning `None` on end of input.
+async fn ask_for_working_key(
+    prompts: &mut Prompts<'_>,
+    console: &Console,
+) -> Result<Option<(ApiKey, NotionHttpClient, String)>, AppError> {
+    let from_env = std::env::var("NOTION_API_KEY").ok();
+    let question = if from_env.is_some() {
+        "Integration secret (Enter to use NOTION_API_KEY): "
+    } else {
+        "Integration secret: "
+    };
+
+    loop {
+        let Some(answer) = prompts.ask(question)? else {
+            return Ok(None);
+        };
+        let secret = match (answer.is_empty(), &from_env) {
+            (true, Some(env_key)) => env_key.clone(),
+            (true, None) => continue,
+            (false, _) => answer,
+        };
+
+        let api_key = match ApiKey::new(secret) {
+            Ok(api_key) => api_key,
+            Err(err) => {
+                console.warn(err.to_string());
+                continue;
+            }
+        };
+        let client = NotionHttpClient::new(&api_key)?;
+        match client.retrieve_bot_user().await {
+            Ok(identity) => {
+                let integration = identity
+                    .name
+                    .unwrap_or_else(|| "your integration".to_string());
+                match identity.workspace_name {
+                    Some(workspace) => console.success(format!(
+                        "Connected as '{}' in workspace '{}'",
+                        integration, workspace
+                    )),
+                    None => console.success(format!("Connected as '{}'", integration)),
+                }
+                return Ok(Some((api_key, client, integration)));
+            }
+            Err(err) => console.warn(format!("Notion rejected this key: {}", err)),
+        }
+    }
+}
+
+/// Fetches one object with the new key, explaining sharing again if
+/// Notion cannot see it.
+async fn test_fetch(
+    prompts: &mut Prompts<'_>,
+    console: &Console,
+    client: &NotionHttpClient,
+    integration: &str,
+) -> Result<ExitCode, AppError> {
+    let id = loop {
+        let Some(answer) = prompts.ask("\nPage or database URL to test (Enter to skip): ")? else {
+            return Ok(ExitCode::Success);
+        };
+        if answer.is_empty() {
+            return Ok(ExitCode::Success);
+        }
+        match NotionId::parse(&answer) {
+            Ok(id) => break id,
+            Err(err) => console.warn(err.to_string()),
+        }
+    };
+
+    match client.resolve_root(&id).await {
+        Ok(object) => {
+            console.success(format!(
+                "Fetched {} '{}'. You're ready: notion2prompt {}",
+                object.object_type_name(),
+                object.display_title(),
+                id.as_str()
+            ));
+            Ok(ExitCode::Success)
+        }
+        Err(err) => {
+            if matches!(
+                err.exit_code(),
+                ExitCode::NotFound | ExitCode::AuthenticationFailed
+            ) {
+                console.warn(format!(
+                    "'{}' cannot see this page yet. {}",
+                    integration,
+                    sharing_instructions(integration)
+                ));
+            }
+            Err(err)
+        }
+    }
+}
+
+/// How to give the integration access to pages.
+fn sharing_instructions(integration: &str) -> String {
+    format!(
+        "Notion integrations only see pages that are shared with them. For each page or \
+         database you want to export:\n  \
+         1. Open it in Notion and click ••• in the top-right corner.\n  \
+         2. Choose Connections and add '{}'.\n\
+         Child pages and databases inherit access from the page they are in.",
+        integration
+    )
+}
+
+/// Line-based questions over any reader and writer.
+struct Prompts<'a> {
+    input: &'a mut dyn BufRead,
+    output: &'a mut dyn Write,
+}
+
+impl Prompts<'_> {
+    /// Asks a question and returns the trimmed answer, or `None` at end of input.
+    fn ask(&mut self, question: &str) -> std::io::Result<Option<String>> {
+        write!(self.output, "{}", question)?;
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            writeln!(self.output)?;
+            return Ok(None);
+        }
+        Ok(Some(line.trim().to_string()))
+    }
+
+    /// Asks a yes/no question that defaults to no.
+    fn confirm(&mut self, question: &str) -> std::io::Result<bool> {
+        let answer = self.ask(&format!("{} [y/N] ", question))?;
+        Ok(matches!(
+            answer.as_deref().map(str::to_lowercase).as_deref(),
+            Some("y" | "yes")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompts_trim_answers_and_default_to_no() {
+        let mut input: &[u8] = b"  secret_abc \nYES\n\n";
+        let mut output = Vec::new();
+        let mut prompts = Prompts {
+            input: &mut input,
+            output: &mut output,
+        };
+
+        assert_eq!(prompts.ask("Key: ").unwrap().as_deref(), Some("secret_abc"));
+        assert!(prompts.confirm("Overwrite?").unwrap());
+        assert!(!prompts.confirm("Overwrite?").unwrap());
+        assert_eq!(prompts.ask("Key: ").unwrap(), None);
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .starts_with("Key: Overwrite? [y/N] "));
+    }
+}
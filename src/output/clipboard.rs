@@ -29,6 +29,7 @@ pub fn copy_to_clipboard(content: &str) -> Result<(), AppError> {
     }
 
     // Try arboard first (cross-platform)
+    #[cfg(feature = "clipboard")]
     match try_arboard_clipboard(content) {
         Ok(()) => {
             log::info!("Content copied to clipboard using arboard");
@@ -51,6 +52,7 @@ pub fn copy_to_clipboard(content: &str) -> Result<(), AppError> {
 }
 
 /// Tries to copy using the arboard crate.
+#[cfg(feature = "clipboard")]
 fn try_arboard_clipboard(content: &str) -> Result<(), AppError> {
     use arboard::Clipboard;
 
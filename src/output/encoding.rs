@@ -0,0 +1,356 @@
+// src/output/encoding.rs
+//! Line endings and ASCII-only output, applied when a prompt is delivered.
+//!
+//! `--newline crlf` ends every line with `\r\n` for Windows consumers, and
+//! `--newline lf` turns any `\r\n` in the content into `\n`. Without the
+//! option, line endings are left as rendered.
+//!
+//! `--ascii` keeps the output to ASCII for pipelines that cannot take
+//! anything else: accented Latin letters lose their accents, typographic
+//! quotes, dashes and spaces become their plain forms, and what has no
+//! ASCII form (emoji, CJK, most symbols) is dropped, along with the space
+//! that followed it when it began a line or a word.
+
+use crate::config::{Newline, PipelineConfig};
+use std::borrow::Cow;
+use std::io::Write;
+
+/// ASCII forms of U+00C0 to U+017F, one letter each. `_` marks the letters
+/// [`transliterate`] spells with more than one.
+const LATIN: &str = "AAAAAA_CEEEEIIIIDNOOOOOxOUUUUY__\
+                     aaaaaa_ceeeeiiiidnooooo/ouuuuy_y\
+                     AaAaAaCcCcCcCcDdDdEeEeEeEeEeGgGg\
+                     GgGgHhHhIiIiIiIiIi__JjKkkLlLlLlL\
+                     lLlNnNnNn___OoOoOo__RrRrRrSsSsSs\
+                     SsTtTtTtUuUuUuUuUuUuWwYyYZzZzZzs";
+
+/// How delivered text is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OutputEncoding {
+    /// Line endings to write, or None to leave them as rendered
+    pub newline: Option<Newline>,
+    /// Transliterate or drop everything outside ASCII
+    pub ascii: bool,
+}
+
+impl OutputEncoding {
+    /// The encoding asked for with `--newline` and `--ascii`.
+    #[allow(dead_code)] // Used by bin crate
+    pub fn for_config(config: &PipelineConfig) -> Self {
+        Self {
+            newline: config.newline,
+            ascii: config.ascii,
+        }
+    }
+
+    /// Whether text passes through unchanged.
+    pub fn is_identity(&self) -> bool {
+        self.newline.is_none() && !self.ascii
+    }
+
+    /// `text` as it is delivered.
+    pub fn encode<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.is_identity() {
+            return Cow::Borrowed(text);
+        }
+        let mut encoder = Encoder::new(*self);
+        let mut out = String::with_capacity(text.len() + text.len() / 32);
+        encoder.push(text, &mut out);
+        encoder.finish(&mut out);
+        Cow::Owned(out)
+    }
+}
+
+/// Encodes text handed over in pieces, carrying what a piece boundary may
+/// split: a `\r` whose `\n` is still to come, and the space after a
+/// dropped character.
+struct Encoder {
+    encoding: OutputEncoding,
+    pending_cr: bool,
+    /// Nothing but whitespace written since the line began
+    after_space: bool,
+    /// A dropped character began a word, so the space after it goes too
+    drop_space: bool,
+}
+
+impl Encoder {
+    fn new(encoding: OutputEncoding) -> Self {
+        Self {
+            encoding,
+            pending_cr: false,
+            after_space: true,
+            drop_space: false,
+        }
+    }
+
+    fn push(&mut self, text: &str, out: &mut String) {
+        for c in text.chars() {
+            if self.pending_cr {
+                self.pending_cr = false;
+                if c == '\n' {
+                    self.push_newline(out);
+                    continue;
+                }
+                self.push_char('\r', out);
+            }
+            match (c, self.encoding.newline) {
+                ('\r', Some(_)) => self.pending_cr = true,
+                ('\n', Some(_)) => self.push_newline(out),
+                _ => self.push_char(c, out),
+            }
+        }
+    }
+
+    /// Writes out a `\r` left at the very end.
+    fn finish(&mut self, out: &mut String) {
+        if std::mem::take(&mut self.pending_cr) {
+            self.push_char('\r', out);
+        }
+    }
+
+    fn push_newline(&mut self, out: &mut String) {
+        out.push_str(match self.encoding.newline {
+            Some(Newline::Crlf) => "\r\n",
+            _ => "\n",
+        });
+        self.after_space = true;
+        self.drop_space = false;
+    }
+
+    fn push_char(&mut self, c: char, out: &mut String) {
+        if self.encoding.ascii && !c.is_ascii() {
+            match transliterate(c) {
+                Some("") => {}
+                Some(ascii) => {
+                    out.push_str(ascii);
+                    self.after_space = ascii.ends_with(' ');
+                    self.drop_space = false;
+                }
+                None => self.drop_space |= self.after_space,
+            }
+            return;
+        }
+        if c == ' ' && std::mem::take(&mut self.drop_space) {
+            return;
+        }
+        self.drop_space = false;
+        self.after_space = c.is_whitespace();
+        out.push(c);
+    }
+}
+
+/// The ASCII form of `c`, or None when it has none and is dropped.
+fn transliterate(c: char) -> Option<&'static str> {
+    let ascii = match c {
+        'Æ' => "AE",
+        'æ' => "ae",
+        'Þ' => "Th",
+        'þ' => "th",
+        'ß' => "ss",
+        'Ĳ' => "IJ",
+        'ĳ' => "ij",
+        'ŉ' => "'n",
+        'Ŋ' => "NG",
+        'ŋ' => "ng",
+        'Œ' => "OE",
+        'œ' => "oe",
+        '\u{00C0}'..='\u{017F}' => {
+            let index = c as usize - 0xC0;
+            return LATIN.get(index..index + 1);
+        }
+        '\u{00A0}' | '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}' => " ",
+        '\u{00AD}' | '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}' => "",
+        '‘' | '’' | '‚' | '‛' | '′' | '´' => "'",
+        '“' | '”' | '„' | '‟' | '″' => "\"",
+        '‐' | '‑' | '‒' | '–' | '—' | '―' | '−' => "-",
+        '…' => "...",
+        '•' | '·' | '‧' => "*",
+        '‹' => "<",
+        '›' => ">",
+        '«' => "<<",
+        '»' => ">>",
+        '¡' => "!",
+        '¿' => "?",
+        '©' => "(c)",
+        '®' => "(R)",
+        '™' => "(TM)",
+        '°' => " deg",
+        '±' => "+/-",
+        '¢' => "c",
+        '£' => "GBP",
+        '¥' => "JPY",
+        '€' => "EUR",
+        '¼' => "1/4",
+        '½' => "1/2",
+        '¾' => "3/4",
+        '¹' => "1",
+        '²' => "2",
+        '³' => "3",
+        '→' => "->",
+        '←' => "<-",
+        '↔' => "<->",
+        '⇒' => "=>",
+        '≤' => "<=",
+        '≥' => ">=",
+        '≠' => "!=",
+        '≈' => "~",
+        '⁄' => "/",
+        _ => return None,
+    };
+    Some(ascii)
+}
+
+/// A writer encoding everything written through it before passing it on.
+///
+/// [`finish`](Self::finish) must be called once writing is done, for a
+/// trailing `\r` or an incomplete character.
+#[allow(dead_code)] // Used by bin crate
+pub struct EncodingWriter<'w> {
+    inner: &'w mut dyn Write,
+    encoder: Encoder,
+    /// The start of a character whose other bytes are still to come
+    partial: Vec<u8>,
+    bytes: usize,
+}
+
+#[allow(dead_code)] // Used by bin crate
+impl<'w> EncodingWriter<'w> {
+    pub fn new(inner: &'w mut dyn Write, encoding: OutputEncoding) -> Self {
+        Self {
+            inner,
+            encoder: Encoder::new(encoding),
+            partial: Vec::new(),
+            bytes: 0,
+        }
+    }
+
+    /// Writes out what is still held back, returning the bytes written in
+    /// all.
+    pub fn finish(mut self) -> std::io::Result<usize> {
+        let tail = String::from_utf8_lossy(&self.partial).into_owned();
+        let mut out = String::new();
+        self.encoder.push(&tail, &mut out);
+        self.encoder.finish(&mut out);
+        self.write_encoded(&out)?;
+        self.inner.flush()?;
+        Ok(self.bytes)
+    }
+
+    fn write_encoded(&mut self, text: &str) -> std::io::Result<()> {
+        self.inner.write_all(text.as_bytes())?;
+        self.bytes += text.len();
+        Ok(())
+    }
+}
+
+impl Write for EncodingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.partial.extend_from_slice(buf);
+        let mut out = String::new();
+        loop {
+            match std::str::from_utf8(&self.partial) {
+                Ok(text) => {
+                    self.encoder.push(text, &mut out);
+                    self.partial.clear();
+                    break;
+                }
+                Err(e) => {
+                    let valid = e.valid_up_to();
+                    let text = std::str::from_utf8(&self.partial[..valid]).unwrap_or_default();
+                    self.encoder.push(text, &mut out);
+                    match e.error_len() {
+                        Some(invalid) => {
+                            self.encoder.push("\u{FFFD}", &mut out);
+                            self.partial.drain(..valid + invalid);
+                        }
+                        None => {
+                            self.partial.drain(..valid);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        self.write_encoded(&out)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// `json` with every non-ASCII character written as a `\u` escape, which
+/// keeps the document valid where transliteration would not.
+#[allow(dead_code)] // Used by bin crate
+pub fn escape_non_ascii_json(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    for c in json.chars() {
+        if c.is_ascii() {
+            out.push(c);
+            continue;
+        }
+        let mut units = [0u16; 2];
+        for unit in c.encode_utf16(&mut units) {
+            out.push_str(&format!("\\u{:04x}", unit));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoding(newline: Option<Newline>, ascii: bool) -> OutputEncoding {
+        OutputEncoding { newline, ascii }
+    }
+
+    #[test]
+    fn test_mixed_content_is_transliterated_and_stripped() {
+        let text = "# Café — “naïve” plan\n\n⚠️ Straße costs 5 € … 🚀\r\n- 日本 ½ ✅\n";
+        assert_eq!(
+            encoding(None, true).encode(text),
+            "# Cafe - \"naive\" plan\n\nStrasse costs 5 EUR ... \r\n- 1/2 \n"
+        );
+        assert_eq!(
+            encoding(Some(Newline::Crlf), true).encode(text),
+            "# Cafe - \"naive\" plan\r\n\r\nStrasse costs 5 EUR ... \r\n- 1/2 \r\n"
+        );
+        assert_eq!(
+            encoding(Some(Newline::Lf), false).encode(text),
+            "# Café — “naïve” plan\n\n⚠️ Straße costs 5 € … 🚀\n- 日本 ½ ✅\n"
+        );
+        assert!(matches!(
+            encoding(None, false).encode(text),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_writer_encodes_across_split_writes() {
+        let text = "Résumé\r\nnext\r";
+        let mut written = Vec::new();
+        let mut writer = EncodingWriter::new(&mut written, encoding(Some(Newline::Crlf), true));
+        // Split inside "é" and between "\r" and "\n"
+        for chunk in [
+            &text.as_bytes()[..2],
+            &text.as_bytes()[2..9],
+            &text.as_bytes()[9..],
+        ] {
+            writer.write_all(chunk).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        assert_eq!(String::from_utf8(written).unwrap(), "Resume\r\nnext\r");
+        assert_eq!(bytes, 13);
+    }
+
+    #[test]
+    fn test_json_keeps_its_text_as_escapes() {
+        assert_eq!(
+            escape_non_ascii_json(r#"{"title":"Café 🚀"}"#),
+            r#"{"title":"Caf\u00e9 \ud83d\ude80"}"#
+        );
+    }
+}
@@ -0,0 +1,369 @@
+// src/output/epub.rs
+//! Packages the rendered page tree as an EPUB 3 book (`--format epub`).
+//!
+//! Each page becomes a chapter rendered through the IR's HTML renderer, much
+//! as multi-file mode gives each page its own file. Child pages and database
+//! rows with content are nested under the page or database holding them.
+//! That hierarchy becomes the table of contents, and the spine reads the
+//! chapters depth-first.
+
+use crate::config::PipelineConfig;
+use crate::error::AppError;
+use crate::formatting::ir::{escape_html, lower_blocks, Document};
+use crate::formatting::prepared_content;
+use crate::model::{Block, ChildDatabaseContent, Database, NotionObject, Page};
+use std::io::{Cursor, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+/// One chapter of the book and the chapters nested under it.
+#[derive(Debug)]
+struct Chapter {
+    title: String,
+    /// The chapter's `<article>`, from the HTML renderer
+    article: String,
+    children: Vec<Chapter>,
+}
+
+/// Writes `content` to `writer` as an EPUB, returning the bytes written.
+///
+/// Row selection, `--rules`, `--since`, `--merge-paragraphs`, `--grep` and
+/// `--db-title` apply as they do to the prompt; templates and prompt-only
+/// options do not.
+pub fn write_epub(
+    content: &NotionObject,
+    config: &PipelineConfig,
+    writer: &mut dyn Write,
+) -> Result<usize, AppError> {
+    let content = prepared_content(content, config);
+    let book = object_chapter(&content, config)?;
+    let bytes = package(&book, content.id().as_str(), config.lang.code())?;
+    writer.write_all(&bytes)?;
+    Ok(bytes.len())
+}
+
+// --- Chapter tree ---
+
+fn object_chapter(object: &NotionObject, config: &PipelineConfig) -> Result<Chapter, AppError> {
+    match object {
+        NotionObject::Page(page) => page_chapter(page, config),
+        NotionObject::Database(db) => {
+            let mut chapter = Chapter {
+                title: object.display_title(),
+                article: Document::from_object_with(object, config)?.to_html(),
+                children: Vec::new(),
+            };
+            row_chapters(db, config, &mut chapter.children)?;
+            Ok(chapter)
+        }
+        NotionObject::Block(block) => {
+            let mut chapter = Chapter {
+                title: object.display_title(),
+                article: Document::from_object_with(object, config)?.to_html(),
+                children: Vec::new(),
+            };
+            nested_chapters(block.children(), config, &mut chapter.children)?;
+            Ok(chapter)
+        }
+    }
+}
+
+fn page_chapter(page: &Page, config: &PipelineConfig) -> Result<Chapter, AppError> {
+    let mut chapter = Chapter {
+        title: page.title().as_str().to_string(),
+        article: Document::from_page_with(page, config)?.to_html(),
+        children: Vec::new(),
+    };
+    nested_chapters(&page.blocks, config, &mut chapter.children)?;
+    Ok(chapter)
+}
+
+/// Rows with content get a chapter; the database's table lists the rest.
+fn row_chapters(
+    db: &Database,
+    config: &PipelineConfig,
+    chapters: &mut Vec<Chapter>,
+) -> Result<(), AppError> {
+    for row in db.rows().filter(|row| !row.blocks.is_empty()) {
+        chapters.push(page_chapter(row, config)?);
+    }
+    Ok(())
+}
+
+/// Finds the child pages and the rows of child databases among `blocks`.
+///
+/// A page's own chapter shows them as a link or a table, so their content
+/// only appears in the nested chapter.
+fn nested_chapters(
+    blocks: &[Block],
+    config: &PipelineConfig,
+    chapters: &mut Vec<Chapter>,
+) -> Result<(), AppError> {
+    for block in blocks {
+        match block {
+            Block::ChildPage(child) if !child.common.children.is_empty() => {
+                let document = Document {
+                    title: child.title.clone(),
+                    url: None,
+                    properties: Vec::new(),
+                    sections: lower_blocks(&child.common.children, Some(config))?,
+                };
+                let mut chapter = Chapter {
+                    title: child.title.clone(),
+                    article: document.to_html(),
+                    children: Vec::new(),
+                };
+                nested_chapters(&child.common.children, config, &mut chapter.children)?;
+                chapters.push(chapter);
+            }
+            Block::ChildDatabase(child) => {
+                if let ChildDatabaseContent::Fetched(db) = &child.content {
+                    row_chapters(db, config, chapters)?;
+                }
+            }
+            _ => nested_chapters(block.children(), config, chapters)?,
+        }
+    }
+    Ok(())
+}
+
+/// The chapters in reading order: each one before those nested under it.
+fn flatten(book: &Chapter) -> Vec<&Chapter> {
+    fn walk<'a>(chapter: &'a Chapter, out: &mut Vec<&'a Chapter>) {
+        out.push(chapter);
+        for child in &chapter.children {
+            walk(child, out);
+        }
+    }
+    let mut out = Vec::new();
+    walk(book, &mut out);
+    out
+}
+
+fn chapter_file(index: usize) -> String {
+    format!("chapter-{}.xhtml", index + 1)
+}
+
+// --- Packaging ---
+
+fn package(book: &Chapter, id: &str, lang: &str) -> Result<Vec<u8>, AppError> {
+    let chapters = flatten(book);
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    // The mimetype must come first and uncompressed, so readers can sniff it
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut add = |name: &str, options: SimpleFileOptions, text: &str| -> Result<(), AppError> {
+        zip.start_file(name, options).map_err(zip_error)?;
+        zip.write_all(text.as_bytes())?;
+        Ok(())
+    };
+    add("mimetype", stored, "application/epub+zip")?;
+    add("META-INF/container.xml", deflated, CONTAINER_XML)?;
+    add(
+        "OEBPS/content.opf",
+        deflated,
+        &package_document(book, chapters.len(), id, lang),
+    )?;
+    add("OEBPS/nav.xhtml", deflated, &navigation(book, lang))?;
+    for (index, chapter) in chapters.iter().enumerate() {
+        add(
+            &format!("OEBPS/{}", chapter_file(index)),
+            deflated,
+            &xhtml(&chapter.title, &chapter.article, lang),
+        )?;
+    }
+
+    Ok(zip.finish().map_err(zip_error)?.into_inner())
+}
+
+fn zip_error(e: zip::result::ZipError) -> AppError {
+    std::io::Error::from(e).into()
+}
+
+/// The OPF package document: metadata, manifest and spine.
+fn package_document(book: &Chapter, chapter_count: usize, id: &str, lang: &str) -> String {
+    let identifier = uuid::Uuid::parse_str(id)
+        .map(|uuid| format!("urn:uuid:{}", uuid))
+        .unwrap_or_else(|_| format!("urn:notion:{}", id));
+    let modified = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+
+    let mut manifest = String::from(
+        "    <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n",
+    );
+    let mut spine = String::new();
+    for index in 0..chapter_count {
+        manifest.push_str(&format!(
+            "    <item id=\"chapter-{0}\" href=\"{1}\" media-type=\"application/xhtml+xml\"/>\n",
+            index + 1,
+            chapter_file(index)
+        ));
+        spine.push_str(&format!("    <itemref idref=\"chapter-{}\"/>\n", index + 1));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" xml:lang="{lang}">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="book-id">{identifier}</dc:identifier>
+    <dc:title>{title}</dc:title>
+    <dc:language>{lang}</dc:language>
+    <meta property="dcterms:modified">{modified}</meta>
+  </metadata>
+  <manifest>
+{manifest}  </manifest>
+  <spine>
+{spine}  </spine>
+</package>
+"#,
+        lang = lang,
+        identifier = escape_html(&identifier),
+        title = escape_html(&book.title),
+        modified = modified,
+        manifest = manifest,
+        spine = spine,
+    )
+}
+
+/// The navigation document: the chapter hierarchy as nested lists.
+fn navigation(book: &Chapter, lang: &str) -> String {
+    fn entries(chapter: &Chapter, next: &mut usize, out: &mut String) {
+        out.push_str(&format!(
+            "<li><a href=\"{}\">{}</a>",
+            chapter_file(*next),
+            escape_html(&chapter.title)
+        ));
+        *next += 1;
+        if !chapter.children.is_empty() {
+            out.push_str("\n<ol>\n");
+            for child in &chapter.children {
+                entries(child, next, out);
+            }
+            out.push_str("</ol>\n");
+        }
+        out.push_str("</li>\n");
+    }
+
+    let mut list = String::new();
+    entries(book, &mut 0, &mut list);
+    xhtml(
+        &book.title,
+        &format!(
+            "<nav epub:type=\"toc\" id=\"toc\">\n<ol>\n{}</ol>\n</nav>\n",
+            list
+        ),
+        lang,
+    )
+}
+
+fn xhtml(title: &str, body: &str, lang: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="{lang}" xml:lang="{lang}">
+<head>
+<meta charset="UTF-8" />
+<title>{title}</title>
+</head>
+<body>
+{body}</body>
+</html>
+"#,
+        lang = lang,
+        title = escape_html(title),
+        body = body,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BlockCommon, ChildPageBlock, ParagraphBlock, TextBlockContent};
+    use crate::types::{BlockId, Color, PageId, RichTextItem};
+    use std::io::Read;
+
+    fn paragraph(text: &str) -> Block {
+        Block::Paragraph(ParagraphBlock {
+            common: BlockCommon {
+                id: BlockId::new_v4(),
+                children: vec![],
+                has_children: false,
+                archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
+            },
+            content: TextBlockContent {
+                rich_text: vec![RichTextItem::plain_text(text)],
+                color: Color::Default,
+            },
+        })
+    }
+
+    fn handbook() -> NotionObject {
+        let onboarding = Block::ChildPage(ChildPageBlock {
+            common: BlockCommon {
+                id: BlockId::new_v4(),
+                children: vec![paragraph("Day one <checklist>")],
+                has_children: true,
+                archived: false,
+                last_edited_time: None,
+                unfetched_children: None,
+            },
+            title: "Onboarding".to_string(),
+            included_elsewhere: None,
+        });
+        NotionObject::Page(
+            Page::new(
+                PageId::parse("12345678123456781234567812345678").unwrap(),
+                "Handbook & Guide",
+            )
+            .with_blocks(vec![paragraph("Welcome"), onboarding]),
+        )
+    }
+
+    fn entry(archive: &mut zip::ZipArchive<Cursor<Vec<u8>>>, name: &str) -> String {
+        let mut text = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    #[test]
+    fn test_pages_become_nested_chapters() {
+        let mut bytes = Vec::new();
+        let written = write_epub(&handbook(), &PipelineConfig::default(), &mut bytes).unwrap();
+        assert_eq!(written, bytes.len());
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(archive.by_index(0).unwrap().name(), "mimetype");
+        assert_eq!(entry(&mut archive, "mimetype"), "application/epub+zip");
+
+        let opf = entry(&mut archive, "OEBPS/content.opf");
+        assert!(opf.contains("urn:uuid:12345678-1234-5678-1234-567812345678"));
+        assert!(opf.contains("<dc:title>Handbook &amp; Guide</dc:title>"));
+        assert!(opf.contains("<itemref idref=\"chapter-2\"/>"));
+
+        let nav = entry(&mut archive, "OEBPS/nav.xhtml");
+        assert!(nav.contains(
+            "<li><a href=\"chapter-1.xhtml\">Handbook &amp; Guide</a>\n<ol>\n\
+             <li><a href=\"chapter-2.xhtml\">Onboarding</a></li>\n</ol>\n</li>"
+        ));
+
+        let root = entry(&mut archive, "OEBPS/chapter-1.xhtml");
+        assert!(root.contains("<p>Welcome</p>"));
+        assert!(!root.contains("Day one"));
+        let child = entry(&mut archive, "OEBPS/chapter-2.xhtml");
+        assert!(child.contains("<p>Day one &lt;checklist&gt;</p>"));
+    }
+}
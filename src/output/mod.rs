@@ -6,6 +6,8 @@
 //! phase (I/O operations).
 
 mod clipboard;
+mod encoding;
+mod epub;
 mod paths;
 mod types;
 mod writer;
@@ -13,8 +15,12 @@ mod writer;
 // Re-export the public interface
 #[allow(unused_imports)] // Used by bin crate
 pub use clipboard::copy_to_clipboard;
+#[allow(unused_imports)] // Used by bin crate
+pub use encoding::{escape_non_ascii_json, EncodingWriter, OutputEncoding};
+#[allow(unused_imports)] // Used by bin crate
+pub use epub::write_epub;
 pub use paths::{create_clean_filename, get_relative_path};
 #[allow(unused_imports)] // Used by bin crate
-pub use types::{DeliveryTarget, OutputPlan, OutputReport};
+pub use types::{DeliveryTarget, OutputPlan, OutputReport, StageTimings};
 #[allow(unused_imports)] // Used by bin crate
-pub use writer::deliver;
+pub use writer::{deliver, stream_to_file};
@@ -4,7 +4,11 @@
 //! This module defines immutable types for planning and executing
 //! output operations following data-oriented design principles.
 
-use std::path::PathBuf;
+use super::encoding::OutputEncoding;
+use crate::error::{DeliveryFailure, DeliveryKind};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Represents a complete output plan.
 #[derive(Debug, Clone, Default)]
@@ -12,6 +16,10 @@ use std::path::PathBuf;
 pub struct OutputPlan {
     /// List of operations to perform
     pub operations: Vec<DeliveryTarget>,
+    /// Line endings and character set of the delivered text
+    pub encoding: OutputEncoding,
+    /// Earlier versions of each written file to keep as backups
+    pub backups: usize,
 }
 
 #[allow(dead_code)] // Used by bin crate
@@ -26,6 +34,18 @@ impl OutputPlan {
         self.operations.push(operation);
         self
     }
+
+    /// Sets how the delivered text is encoded.
+    pub fn with_encoding(mut self, encoding: OutputEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Sets how many earlier versions of each written file to keep.
+    pub fn with_backups(mut self, backups: usize) -> Self {
+        self.backups = backups;
+        self
+    }
 }
 
 /// Represents a single output operation.
@@ -33,6 +53,10 @@ impl OutputPlan {
 pub enum DeliveryTarget {
     /// Write content to a file
     WriteFile { path: PathBuf, content: String },
+    /// A file rendered incrementally by [`stream_to_file`](super::stream_to_file);
+    /// recorded in reports only, since there is no content to hand to `deliver`
+    #[allow(dead_code)] // Used by bin crate
+    StreamToFile { path: PathBuf },
     /// Create a directory
     #[allow(dead_code)] // Used in advanced output scenarios
     CreateDirectory { path: PathBuf },
@@ -44,6 +68,28 @@ pub enum DeliveryTarget {
     PrintToStdout { content: String },
 }
 
+impl DeliveryTarget {
+    /// The kind of destination this target writes to.
+    pub fn kind(&self) -> DeliveryKind {
+        match self {
+            Self::WriteFile { .. } | Self::StreamToFile { .. } => DeliveryKind::File,
+            Self::CreateDirectory { .. } => DeliveryKind::Directory,
+            Self::CopyToClipboard { .. } => DeliveryKind::Clipboard,
+            Self::PrintToStdout { .. } => DeliveryKind::Stdout,
+        }
+    }
+
+    /// The file or directory written, for filesystem targets.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::WriteFile { path, .. }
+            | Self::StreamToFile { path }
+            | Self::CreateDirectory { path } => Some(path),
+            Self::CopyToClipboard { .. } | Self::PrintToStdout { .. } => None,
+        }
+    }
+}
+
 /// Result of executing an output plan.
 #[derive(Debug, Clone)]
 pub struct OutputReport {
@@ -53,6 +99,8 @@ pub struct OutputReport {
     pub failed: Vec<FailedOperation>,
     /// Execution statistics
     pub stats: ExecutionStats,
+    /// How long each stage of the run took
+    pub timings: StageTimings,
 }
 
 impl Default for OutputReport {
@@ -68,6 +116,7 @@ impl OutputReport {
             completed: Vec::new(),
             failed: Vec::new(),
             stats: ExecutionStats::default(),
+            timings: StageTimings::default(),
         }
     }
 
@@ -106,7 +155,7 @@ pub struct CompletedOperation {
 pub struct FailedOperation {
     #[allow(dead_code)] // Used in error reporting
     pub operation: DeliveryTarget,
-    pub error: String,
+    pub failure: DeliveryFailure,
 }
 
 /// Execution statistics.
@@ -117,3 +166,79 @@ pub struct ExecutionStats {
     pub bytes_written: usize,
     pub total_duration_ms: u64,
 }
+
+/// Wall-clock time spent in each stage of the pipeline.
+///
+/// When the prompt is streamed to its output file, it is written while it
+/// is composed; that time counts as composing, and delivering is only the
+/// clipboard copy made afterwards.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StageTimings {
+    pub fetch: Duration,
+    pub compose: Duration,
+    pub deliver: Duration,
+    /// Whether the output file was written while composing
+    pub streamed: bool,
+}
+
+impl fmt::Display for StageTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Fetched in {:.1}s, ", self.fetch.as_secs_f64())?;
+        if !self.streamed {
+            return write!(
+                f,
+                "composed in {:.1}s, delivered in {:.1}s",
+                self.compose.as_secs_f64(),
+                self.deliver.as_secs_f64()
+            );
+        }
+        write!(
+            f,
+            "composed and written in {:.1}s",
+            self.compose.as_secs_f64()
+        )?;
+        if !self.deliver.is_zero() {
+            write!(f, ", delivered in {:.1}s", self.deliver.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timings_name_each_stage() {
+        let timings = StageTimings {
+            fetch: Duration::from_millis(1200),
+            compose: Duration::from_millis(300),
+            deliver: Duration::from_millis(40),
+            streamed: false,
+        };
+        assert_eq!(
+            timings.to_string(),
+            "Fetched in 1.2s, composed in 0.3s, delivered in 0.0s"
+        );
+    }
+
+    #[test]
+    fn test_streamed_timings_count_writing_as_composing() {
+        let mut timings = StageTimings {
+            fetch: Duration::from_secs(2),
+            compose: Duration::from_millis(500),
+            deliver: Duration::ZERO,
+            streamed: true,
+        };
+        assert_eq!(
+            timings.to_string(),
+            "Fetched in 2.0s, composed and written in 0.5s"
+        );
+
+        timings.deliver = Duration::from_millis(100);
+        assert_eq!(
+            timings.to_string(),
+            "Fetched in 2.0s, composed and written in 0.5s, delivered in 0.1s"
+        );
+    }
+}
@@ -3,13 +3,20 @@
 //!
 //! This module is the only place where file I/O operations occur,
 //! keeping the rest of the codebase pure and testable.
+//!
+//! Files are written to a temporary file beside the destination and renamed
+//! over it once complete, so a crash mid-write or a reader opening the file
+//! at the same time never sees a truncated prompt. With `--backup N` the
+//! file being replaced is kept as `NAME.YYYYMMDD-HHMMSS.mmm.bak`, the N
+//! newest of those kept.
 
 use super::clipboard::copy_to_clipboard;
+use super::encoding::OutputEncoding;
 use super::types::*;
-use crate::error::AppError;
+use crate::error::{AppError, DeliveryFailure};
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 /// Delivers the output plan, performing all I/O operations.
@@ -25,7 +32,7 @@ pub fn deliver(plan: OutputPlan) -> Result<OutputReport, AppError> {
 
     for operation in plan.operations {
         let op_start = Instant::now();
-        match execute_operation(&operation) {
+        match execute_operation(&operation, &plan.encoding, plan.backups) {
             Ok(bytes_written) => {
                 let duration_ms = op_start.elapsed().as_millis() as u64;
                 report = report.with_completed(CompletedOperation {
@@ -36,10 +43,12 @@ pub fn deliver(plan: OutputPlan) -> Result<OutputReport, AppError> {
             }
             Err(e) => {
                 log::error!("Operation failed: {}", e);
-                report = report.with_failed(FailedOperation {
-                    operation,
-                    error: e.to_string(),
-                });
+                let failure = DeliveryFailure::new(
+                    operation.kind(),
+                    operation.path().map(Path::to_path_buf),
+                    &e,
+                );
+                report = report.with_failed(FailedOperation { operation, failure });
             }
         }
     }
@@ -56,39 +65,172 @@ pub fn deliver(plan: OutputPlan) -> Result<OutputReport, AppError> {
     Ok(report)
 }
 
-/// Executes a single output operation.
-fn execute_operation(operation: &DeliveryTarget) -> Result<usize, AppError> {
+/// Executes a single output operation, encoding the text it delivers.
+fn execute_operation(
+    operation: &DeliveryTarget,
+    encoding: &OutputEncoding,
+    backups: usize,
+) -> Result<usize, AppError> {
     match operation {
-        DeliveryTarget::WriteFile { path, content } => write_file(path, content),
+        DeliveryTarget::WriteFile { path, content } => {
+            write_file(path, &encoding.encode(content), backups)
+        }
+        DeliveryTarget::StreamToFile { path } => Err(AppError::InternalError {
+            message: format!(
+                "{} must be written with stream_to_file, not deliver",
+                path.display()
+            ),
+            source: None,
+        }),
         DeliveryTarget::CreateDirectory { path } => {
             create_directory(path)?;
             Ok(0)
         }
         DeliveryTarget::CopyToClipboard { content } => {
-            copy_to_clipboard(content)?;
+            let content = encoding.encode(content);
+            copy_to_clipboard(&content)?;
             Ok(content.len())
         }
         DeliveryTarget::PrintToStdout { content } => {
-            print_to_stdout(content)?;
+            let content = encoding.encode(content);
+            print_to_stdout(&content)?;
             Ok(content.len())
         }
     }
 }
 
+/// Writes a file by handing a buffered writer to `render`, so the content
+/// never has to exist in memory as a whole.
+///
+/// `render` returns the number of bytes it wrote. The file only replaces
+/// `path` once `render` succeeds, keeping `backups` earlier versions; if it
+/// fails, `path` is left as it was.
+#[allow(dead_code)] // Used by bin crate
+pub fn stream_to_file<F>(path: &Path, backups: usize, render: F) -> Result<OutputReport, AppError>
+where
+    F: FnOnce(&mut dyn Write) -> Result<usize, AppError>,
+{
+    let start_time = Instant::now();
+    log::debug!("Streaming output to {}", path.display());
+
+    let bytes_written = write_atomically(path, backups, |file| {
+        let mut writer = std::io::BufWriter::new(file);
+        let bytes = render(&mut writer)?;
+        writer.flush()?;
+        Ok(bytes)
+    })?;
+
+    log::info!("Streamed {} bytes to {}", bytes_written, path.display());
+    let duration_ms = start_time.elapsed().as_millis() as u64;
+    let mut report = OutputReport::new().with_completed(CompletedOperation {
+        operation: DeliveryTarget::StreamToFile {
+            path: path.to_path_buf(),
+        },
+        bytes_written,
+        duration_ms,
+    });
+    report.stats.total_duration_ms = duration_ms;
+    Ok(report)
+}
+
 /// Writes content to a file.
-fn write_file(path: &Path, content: &str) -> Result<usize, AppError> {
+fn write_file(path: &Path, content: &str, backups: usize) -> Result<usize, AppError> {
     log::debug!("Writing {} bytes to {}", content.len(), path.display());
+    write_atomically(path, backups, |file| {
+        file.write_all(content.as_bytes())?;
+        Ok(content.len())
+    })?;
+    log::info!("Wrote file: {}", path.display());
+    Ok(content.len())
+}
 
-    // Create parent directories if needed
-    if let Some(parent) = path.parent() {
+/// Has `write` fill a temporary file beside `path`, then renames it over
+/// `path`, keeping `backups` earlier versions. The temporary file is removed
+/// if anything fails.
+///
+/// When `path` is a symlink, the file it points at is replaced and the
+/// link stays. A replaced file keeps its permissions, so a prompt the user
+/// made private stays private.
+fn write_atomically<F>(path: &Path, backups: usize, write: F) -> Result<usize, AppError>
+where
+    F: FnOnce(&mut fs::File) -> Result<usize, AppError>,
+{
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if let Some(parent) = target.parent() {
         fs::create_dir_all(parent)?;
     }
+    let permissions = fs::metadata(&target)
+        .ok()
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.permissions());
 
-    // Write the file
-    fs::write(path, content)?;
+    let temp = temp_file_beside(&target);
+    let result = fs::File::create(&temp)
+        .map_err(AppError::from)
+        .and_then(|mut file| {
+            let bytes = write(&mut file)?;
+            if let Some(permissions) = permissions {
+                file.set_permissions(permissions)?;
+            }
+            file.sync_all()?;
+            Ok(bytes)
+        })
+        .and_then(|bytes| {
+            if backups > 0 && target.is_file() {
+                back_up(&target, backups)?;
+            }
+            fs::rename(&temp, &target)?;
+            Ok(bytes)
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
 
-    log::info!("Wrote file: {}", path.display());
-    Ok(content.len())
+/// A hidden, unique file name beside `path`, on the same filesystem so the
+/// rename is atomic.
+fn temp_file_beside(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4().simple()))
+}
+
+/// Keeps the current `path` as a timestamped backup, then removes all but
+/// the `keep` newest backups.
+fn back_up(path: &Path, keep: usize) -> Result<(), AppError> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S%.3f");
+    let backup = path.with_file_name(format!("{}.{}.bak", name, stamp));
+    // A hard link keeps the old version without copying it; `path` itself
+    // stays in place until the rename replaces it
+    if fs::hard_link(path, &backup).is_err() {
+        fs::copy(path, &backup)?;
+    }
+    log::debug!("Backed up {} to {}", path.display(), backup.display());
+
+    let prefix = format!("{}.", name);
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut existing: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|candidate| {
+            candidate
+                .file_name()
+                .and_then(|file| file.to_str())
+                .is_some_and(|file| file.starts_with(&prefix) && file.ends_with(".bak"))
+        })
+        .collect();
+    // Timestamps sort as text, oldest first
+    existing.sort();
+    let excess = existing.len().saturating_sub(keep);
+    for old in &existing[..excess] {
+        if let Err(e) = fs::remove_file(old) {
+            log::warn!("Could not remove old backup {}: {}", old.display(), e);
+        }
+    }
+    Ok(())
 }
 
 /// Creates a directory.
@@ -118,3 +260,154 @@ fn print_to_stdout(content: &str) -> Result<(), AppError> {
     std::io::stdout().flush()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir()
+            .join(format!("n2p_stream_{}", uuid::Uuid::new_v4()))
+            .join(name)
+    }
+
+    #[test]
+    fn test_stream_to_file_writes_and_reports() {
+        let path = temp_path("prompt.md");
+        let report = stream_to_file(&path, 0, |w| {
+            w.write_all(b"hello ")?;
+            w.write_all(b"world")?;
+            Ok(11)
+        })
+        .unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "hello world");
+        assert_eq!(report.stats.bytes_written, 11);
+        assert!(matches!(
+            report.completed[0].operation,
+            DeliveryTarget::StreamToFile { .. }
+        ));
+    }
+
+    #[test]
+    fn test_stream_to_file_removes_partial_output_on_failure() {
+        let path = temp_path("prompt.md");
+        let result = stream_to_file(&path, 0, |w| {
+            w.write_all(b"partial")?;
+            Err(AppError::Validation("render failed".to_string()))
+        });
+
+        assert!(result.is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_failed_stream_leaves_the_previous_file_intact() {
+        let path = temp_path("prompt.md");
+        stream_to_file(&path, 0, |w| {
+            w.write_all(b"first")?;
+            Ok(5)
+        })
+        .unwrap();
+
+        let result = stream_to_file(&path, 0, |w| {
+            w.write_all(b"par")?;
+            Err(AppError::Validation("render failed".to_string()))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+        let leftovers = fs::read_dir(path.parent().unwrap()).unwrap().count();
+        assert_eq!(leftovers, 1, "the temporary file should be removed");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_replacing_a_file_keeps_its_permissions_and_symlink() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let path = temp_path("prompt.md");
+        let link = path.with_file_name("link.md");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        symlink(&path, &link).unwrap();
+
+        stream_to_file(&link, 0, |w| {
+            w.write_all(b"new")?;
+            Ok(3)
+        })
+        .unwrap();
+
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_backups_keep_the_newest_earlier_versions() {
+        let path = temp_path("prompt.md");
+        for version in ["v1", "v2", "v3", "v4"] {
+            let plan =
+                OutputPlan::new()
+                    .with_backups(2)
+                    .with_operation(DeliveryTarget::WriteFile {
+                        path: path.clone(),
+                        content: version.to_string(),
+                    });
+            assert!(deliver(plan).unwrap().is_success());
+            // Backup names are stamped to the millisecond
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "v4");
+        let mut backups: Vec<PathBuf> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|file| file.extension().is_some_and(|ext| ext == "bak"))
+            .collect();
+        backups.sort();
+        let contents: Vec<String> = backups
+            .iter()
+            .map(|file| fs::read_to_string(file).unwrap())
+            .collect();
+        assert_eq!(contents, ["v2", "v3"]);
+    }
+
+    #[test]
+    fn test_failed_targets_are_reported_with_kind_path_and_io_kind() {
+        // A file standing where the parent directory should be.
+        let blocker = temp_path("blocker");
+        fs::create_dir_all(blocker.parent().unwrap()).unwrap();
+        fs::write(&blocker, "").unwrap();
+        let path = blocker.join("prompt.md");
+
+        let report = deliver(OutputPlan::new().with_operation(DeliveryTarget::WriteFile {
+            path: path.clone(),
+            content: "prompt".to_string(),
+        }))
+        .unwrap();
+
+        let failure = &report.failed[0].failure;
+        assert_eq!(failure.kind, crate::error::DeliveryKind::File);
+        assert_eq!(failure.path.as_deref(), Some(path.as_path()));
+        assert!(failure.io_kind.is_some());
+        assert!(!failure.is_retryable());
+        assert!(failure.to_string().starts_with("file "));
+
+        let clipboard = DeliveryFailure::new(
+            crate::error::DeliveryKind::Clipboard,
+            None,
+            &AppError::Clipboard("no display".to_string()),
+        );
+        assert!(clipboard.is_retryable());
+        assert_eq!(
+            clipboard.to_string(),
+            "clipboard: Error interacting with clipboard: no display"
+        );
+    }
+}
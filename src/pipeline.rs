@@ -2,11 +2,23 @@
 //! Pipeline capability traits — abstract the three stages of the Notion-to-prompt pipeline.
 //!
 //! Each trait describes a single capability, enabling testing each stage in isolation.
+//!
+//! Composition and delivery come in two flavours. [`PromptComposer`] and
+//! [`PromptDelivery`] are synchronous, which suits template rendering and
+//! local file output. Stages that call out to other services — a tokenizer,
+//! a summarizing model, a remote store — implement [`AsyncPromptComposer`] or
+//! [`AsyncPromptDelivery`] instead. Every synchronous implementation is also
+//! an asynchronous one, so pipeline drivers only need the async traits.
+//!
+//! [`WarningSink`] is the side channel: it receives each [`Warning`] as the
+//! fetch produces it, so an embedder can show warnings while a long run is
+//! still going instead of reading them from the metadata afterwards.
 
 use crate::error::AppError;
 use crate::model::NotionObject;
 use crate::output::OutputReport;
-use crate::types::{NotionId, RenderedPrompt};
+use crate::types::{NotionId, RenderedPrompt, Warning};
+use std::io::Write;
 
 /// Retrieves Notion content by ID.
 #[async_trait::async_trait]
@@ -17,9 +29,110 @@ pub trait ContentSource {
 /// Transforms a NotionObject into a RenderedPrompt.
 pub trait PromptComposer {
     fn compose(&self, content: &NotionObject) -> Result<RenderedPrompt, AppError>;
+
+    /// Composes the prompt directly into `writer`, returning the bytes written.
+    ///
+    /// The default renders to a string first; implementors that can stream
+    /// should override it to avoid holding the whole prompt in memory.
+    fn compose_to(
+        &self,
+        content: &NotionObject,
+        writer: &mut dyn Write,
+    ) -> Result<usize, AppError> {
+        let prompt = self.compose(content)?.into_string();
+        writer.write_all(prompt.as_bytes())?;
+        Ok(prompt.len())
+    }
 }
 
 /// Delivers a rendered prompt to its destinations.
 pub trait PromptDelivery {
     fn deliver(&self, prompt: RenderedPrompt) -> Result<OutputReport, AppError>;
 }
+
+/// Transforms a NotionObject into a RenderedPrompt, possibly awaiting I/O.
+#[async_trait::async_trait]
+pub trait AsyncPromptComposer: Send + Sync {
+    async fn compose_async(&self, content: &NotionObject) -> Result<RenderedPrompt, AppError>;
+}
+
+/// Delivers a rendered prompt to its destinations, possibly awaiting I/O.
+#[async_trait::async_trait]
+pub trait AsyncPromptDelivery: Send + Sync {
+    async fn deliver_async(&self, prompt: RenderedPrompt) -> Result<OutputReport, AppError>;
+}
+
+/// Receives warnings as they are produced. Called from fetch workers, so
+/// implementations must be cheap and thread-safe.
+pub trait WarningSink: Send + Sync {
+    fn warn(&self, warning: &Warning);
+}
+
+/// Any thread-safe closure taking a warning is a sink.
+impl<F: Fn(&Warning) + Send + Sync> WarningSink for F {
+    fn warn(&self, warning: &Warning) {
+        self(warning)
+    }
+}
+
+/// Sync adapter: a synchronous composer runs inline on the calling task.
+#[async_trait::async_trait]
+impl<T: PromptComposer + Send + Sync> AsyncPromptComposer for T {
+    async fn compose_async(&self, content: &NotionObject) -> Result<RenderedPrompt, AppError> {
+        self.compose(content)
+    }
+}
+
+/// Sync adapter: a synchronous delivery runs inline on the calling task.
+#[async_trait::async_trait]
+impl<T: PromptDelivery + Send + Sync> AsyncPromptDelivery for T {
+    async fn deliver_async(&self, prompt: RenderedPrompt) -> Result<OutputReport, AppError> {
+        self.deliver(prompt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Page;
+    use crate::types::PageId;
+
+    fn page() -> NotionObject {
+        NotionObject::Page(Page::new(
+            PageId::parse("12345678123456781234567812345678").unwrap(),
+            "Notes",
+        ))
+    }
+
+    /// Composes by awaiting, standing in for a call to an external service.
+    struct RemoteComposer;
+
+    #[async_trait::async_trait]
+    impl AsyncPromptComposer for RemoteComposer {
+        async fn compose_async(&self, content: &NotionObject) -> Result<RenderedPrompt, AppError> {
+            tokio::task::yield_now().await;
+            Ok(RenderedPrompt::new(format!(
+                "remote: {}",
+                content.display_title()
+            )))
+        }
+    }
+
+    struct TitleComposer;
+
+    impl PromptComposer for TitleComposer {
+        fn compose(&self, content: &NotionObject) -> Result<RenderedPrompt, AppError> {
+            Ok(RenderedPrompt::new(content.display_title()))
+        }
+    }
+
+    async fn compose_with(composer: &dyn AsyncPromptComposer) -> String {
+        composer.compose_async(&page()).await.unwrap().into_string()
+    }
+
+    #[tokio::test]
+    async fn test_sync_and_async_composers_share_one_interface() {
+        assert_eq!(compose_with(&TitleComposer).await, "Notes");
+        assert_eq!(compose_with(&RemoteComposer).await, "remote: Notes");
+    }
+}
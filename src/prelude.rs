@@ -0,0 +1,22 @@
+// src/prelude.rs
+//! The everyday stable API in one import.
+//!
+//! ```no_run
+//! use notion2prompt::prelude::*;
+//!
+//! fn prompt_for(content: &NotionObject) -> Result<RenderedPrompt, AppError> {
+//!     render_prompt(content, &PipelineConfig::default())
+//! }
+//! ```
+//!
+//! Only items covered by semantic versioning are listed here; nothing behind
+//! the `unstable` feature is ever added to the prelude.
+
+pub use crate::error::{AppError, ExitCode};
+pub use crate::formatting::direct_template::{render_prompt, render_prompt_to};
+pub use crate::model::NotionObject;
+pub use crate::pipeline::{
+    AsyncPromptComposer, AsyncPromptDelivery, ContentSource, PromptComposer, PromptDelivery,
+};
+pub use crate::types::{NotionId, RenderedPrompt};
+pub use crate::PipelineConfig;
@@ -2,11 +2,16 @@ use super::ValidationError;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 use uuid::Uuid;
 
 /// Strong typing for IDs with phantom types
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// Equality and hashing compare the canonical (unhyphenated, lowercase)
+/// form, and work across kinds: a `PageId` equals the `BlockId` of the
+/// child page block that holds it.
+#[derive(Debug, Clone)]
 pub struct Id<T> {
     value: String,
     _phantom: PhantomData<T>,
@@ -47,10 +52,11 @@ impl<T> Id<T> {
         })
     }
 
-    /// Create an ID from an already normalized string (internal use)
-    pub(crate) fn from_normalized(value: String) -> Self {
+    /// Create an ID from a trusted string, canonicalizing it when it is a
+    /// Notion UUID and keeping it verbatim otherwise (internal use)
+    pub(crate) fn from_raw(value: String) -> Self {
         Self {
-            value,
+            value: canonical_uuid(&value).unwrap_or(value),
             _phantom: PhantomData,
         }
     }
@@ -85,6 +91,32 @@ impl<T> Id<T> {
             self.value.clone()
         }
     }
+
+    /// Reinterprets the ID as another object kind.
+    ///
+    /// Notion uses one ID space for all objects, so the ID of a
+    /// `child_database` block is also the database's ID.
+    #[allow(dead_code)]
+    pub fn into_kind<U>(self) -> Id<U> {
+        Id {
+            value: self.value,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<A, B> PartialEq<Id<B>> for Id<A> {
+    fn eq(&self, other: &Id<B>) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> Hash for Id<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
 }
 
 impl<T> fmt::Display for Id<T> {
@@ -108,10 +140,18 @@ impl<'de, T> Deserialize<'de> for Id<T> {
         D: serde::Deserializer<'de>,
     {
         let value = String::deserialize(deserializer)?;
-        Ok(Self::from_normalized(value))
+        Ok(Self::from_raw(value))
     }
 }
 
+/// Returns the canonical form of a Notion UUID: 32 lowercase hex digits
+/// without hyphens, or `None` if `value` is not a UUID.
+fn canonical_uuid(value: &str) -> Option<String> {
+    let stripped: String = value.chars().filter(|c| *c != '-').collect();
+    (stripped.len() == 32 && stripped.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| stripped.to_ascii_lowercase())
+}
+
 /// Normalize various Notion ID formats into a consistent format
 fn normalize_notion_id(input: &str) -> Result<String, ValidationError> {
     let input = input.trim();
@@ -204,6 +244,63 @@ mod tests {
         let id = PageId::parse("550e8400e29b41d4a716446655440000").unwrap();
         assert_eq!(id.to_dashed(), "550e8400-e29b-41d4-a716-446655440000");
     }
+
+    const DASHED: &str = "550E8400-e29b-41d4-a716-446655440000";
+    const SIMPLE: &str = "550e8400e29b41d4a716446655440000";
+
+    fn hash_of<T: Hash>(value: &T) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_deserialized_ids_are_canonical() {
+        let dashed: PageId = serde_json::from_value(serde_json::json!(DASHED)).unwrap();
+        let simple = PageId::parse(SIMPLE).unwrap();
+        assert_eq!(dashed.as_str(), SIMPLE);
+        assert_eq!(dashed, simple);
+        assert_eq!(hash_of(&dashed), hash_of(&simple));
+
+        // Non-UUID identifiers are kept as they are.
+        let raw: BlockId = serde_json::from_value(serde_json::json!("not-a-uuid")).unwrap();
+        assert_eq!(raw.as_str(), "not-a-uuid");
+    }
+
+    #[test]
+    fn test_conversions_round_trip() {
+        let page = PageId::parse(DASHED).unwrap();
+        let notion: NotionId = page.clone().into();
+        assert_eq!(notion.as_str(), SIMPLE);
+
+        let back: PageId = notion.clone().into();
+        assert_eq!(back, page);
+        assert_eq!(page, notion);
+        assert_eq!(notion, page);
+
+        let block: BlockId = page.clone().into_kind();
+        assert_eq!(block, page);
+        assert_eq!(block.as_str(), page.as_str());
+    }
+
+    #[test]
+    fn test_any_id_ignores_kind() {
+        let page = AnyId::from(PageId::parse(SIMPLE).unwrap());
+        let block = AnyId::from(BlockId::parse(DASHED).unwrap());
+        let unknown = AnyId::from(NotionId::parse(DASHED).unwrap());
+
+        assert_eq!(page, block);
+        assert_eq!(page, unknown);
+        assert_eq!(page.kind(), Some("page"));
+        assert_eq!(unknown.kind(), None);
+
+        let set: std::collections::HashSet<AnyId> = [page, block, unknown].into_iter().collect();
+        assert_eq!(set.len(), 1);
+        assert_eq!(
+            NotionId::from(set.into_iter().next().unwrap()).as_str(),
+            SIMPLE
+        );
+    }
 }
 
 /// NotionId - A general-purpose Notion ID that can represent any type of object
@@ -333,39 +430,140 @@ impl<'de> Deserialize<'de> for NotionId {
     }
 }
 
-// Conversions from specific ID types to NotionId
-impl From<PageId> for NotionId {
-    fn from(id: PageId) -> Self {
-        NotionId(id.as_str().to_string())
+// Conversions between typed IDs and NotionId. Typed IDs are canonical
+// whenever they hold a UUID, so these never lose information.
+impl<T> From<Id<T>> for NotionId {
+    fn from(id: Id<T>) -> Self {
+        NotionId(id.value)
     }
 }
 
-impl From<BlockId> for NotionId {
-    fn from(id: BlockId) -> Self {
-        NotionId(id.as_str().to_string())
+impl<T> From<&Id<T>> for NotionId {
+    fn from(id: &Id<T>) -> Self {
+        NotionId(id.value.clone())
+    }
+}
+
+impl<T> From<NotionId> for Id<T> {
+    fn from(id: NotionId) -> Self {
+        Id::from_raw(id.0)
     }
 }
 
-impl From<DatabaseId> for NotionId {
+impl<T> From<&NotionId> for Id<T> {
+    fn from(id: &NotionId) -> Self {
+        Id::from_raw(id.0.clone())
+    }
+}
+
+impl<T> PartialEq<NotionId> for Id<T> {
+    fn eq(&self, other: &NotionId) -> bool {
+        self.value == other.0
+    }
+}
+
+impl<T> PartialEq<Id<T>> for NotionId {
+    fn eq(&self, other: &Id<T>) -> bool {
+        self.0 == other.value
+    }
+}
+
+/// The ID of a Notion object whose kind is known, or [`AnyId::Unknown`] when
+/// it is not.
+///
+/// Equality and hashing ignore the kind, so a set of `AnyId`s holds each
+/// object once regardless of how it was reached.
+#[derive(Debug, Clone)]
+pub enum AnyId {
+    Page(PageId),
+    Database(DatabaseId),
+    Block(BlockId),
+    Unknown(NotionId),
+}
+
+impl AnyId {
+    /// Returns the canonical ID string.
+    pub fn as_str(&self) -> &str {
+        match self {
+            AnyId::Page(id) => id.as_str(),
+            AnyId::Database(id) => id.as_str(),
+            AnyId::Block(id) => id.as_str(),
+            AnyId::Unknown(id) => id.as_str(),
+        }
+    }
+
+    /// Returns the object kind as Notion names it, if known.
+    #[allow(dead_code)]
+    pub fn kind(&self) -> Option<&'static str> {
+        match self {
+            AnyId::Page(_) => Some("page"),
+            AnyId::Database(_) => Some("database"),
+            AnyId::Block(_) => Some("block"),
+            AnyId::Unknown(_) => None,
+        }
+    }
+
+    /// Returns the untyped ID.
+    pub fn to_notion_id(&self) -> NotionId {
+        NotionId(self.as_str().to_string())
+    }
+}
+
+impl PartialEq for AnyId {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for AnyId {}
+
+impl Hash for AnyId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl PartialEq<NotionId> for AnyId {
+    fn eq(&self, other: &NotionId) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl fmt::Display for AnyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<PageId> for AnyId {
+    fn from(id: PageId) -> Self {
+        AnyId::Page(id)
+    }
+}
+
+impl From<DatabaseId> for AnyId {
     fn from(id: DatabaseId) -> Self {
-        NotionId(id.as_str().to_string())
+        AnyId::Database(id)
     }
 }
 
-impl From<&PageId> for NotionId {
-    fn from(id: &PageId) -> Self {
-        NotionId(id.as_str().to_string())
+impl From<BlockId> for AnyId {
+    fn from(id: BlockId) -> Self {
+        AnyId::Block(id)
     }
 }
 
-impl From<&BlockId> for NotionId {
-    fn from(id: &BlockId) -> Self {
-        NotionId(id.as_str().to_string())
+impl From<NotionId> for AnyId {
+    fn from(id: NotionId) -> Self {
+        AnyId::Unknown(id)
     }
 }
 
-impl From<&DatabaseId> for NotionId {
-    fn from(id: &DatabaseId) -> Self {
-        NotionId(id.as_str().to_string())
+impl From<AnyId> for NotionId {
+    fn from(id: AnyId) -> Self {
+        match id {
+            AnyId::Unknown(id) => id,
+            other => other.to_notion_id(),
+        }
     }
 }
@@ -45,4 +45,11 @@ pub enum ValidationError {
 
     #[error("Invalid file path: {path} - {reason}")]
     InvalidFilePath { path: String, reason: String },
+
+    #[error("Invalid {kind} '{value}', expected one of: {expected}")]
+    InvalidChoice {
+        kind: &'static str,
+        value: String,
+        expected: String,
+    },
 }
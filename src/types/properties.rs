@@ -300,9 +300,11 @@ pub enum MentionType {
     LinkPreview {
         link_preview: LinkPreviewReference,
     },
-    #[serde(rename = "link_mention")]
     LinkMention {
-        url: String,
+        link_mention: LinkMentionReference,
+    },
+    TemplateMention {
+        template_mention: TemplateMentionValue,
     },
 }
 
@@ -314,6 +316,9 @@ pub struct PageReference {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct DatabaseReference {
     pub id: super::NotionId,
+    /// Set when the mention points at a specific view of the database.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub view_id: Option<super::NotionId>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -321,6 +326,25 @@ pub struct LinkPreviewReference {
     pub url: String,
 }
 
+/// A pasted link that Notion unfurled into a titled mention.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LinkMentionReference {
+    pub href: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A template placeholder that Notion fills in when the template is used.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateMentionValue {
+    Today,
+    Now,
+    Me,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct EquationData {
     pub expression: String,
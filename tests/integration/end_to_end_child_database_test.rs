@@ -302,8 +302,8 @@ mod tests {
             "Final output should contain database table headers"
         );
         assert!(
-            final_output.contains("| --- | --- | --- |"),
-            "Final output should contain markdown table separator"
+            final_output.contains("| :--- | :--- | :--- |"),
+            "Final output should contain a left-aligned markdown table separator"
         );
 
         // The database content is being rendered as a table, which is the main goal
@@ -157,9 +157,12 @@ mod tests {
                 .join("\n")
         );
 
-        fs::write("test_expected_child_database_output.md", &expected_output)
-            .expect("Failed to write expected output");
+        let output_path = std::env::temp_dir().join(format!(
+            "n2p_expected_child_database_{}.md",
+            uuid::Uuid::new_v4().simple()
+        ));
+        fs::write(&output_path, &expected_output).expect("Failed to write expected output");
 
-        println!("✅ Expected output saved to test_expected_child_database_output.md");
+        println!("✅ Expected output saved to {}", output_path.display());
     }
 }
@@ -15,6 +15,8 @@ use notion2prompt::{
     // Domain types
     Annotations,
     ApiResponse,
+    AudioBlock,
+    AutoSummary,
     // Domain model
     Block,
     BlockCommon,
@@ -24,6 +26,7 @@ use notion2prompt::{
     BreadcrumbBlock,
     BulletedListItemBlock,
     CalloutBlock,
+    CalloutStyle,
     ChildDatabaseBlock,
     ChildDatabaseContent,
     ChildPageBlock,
@@ -31,11 +34,11 @@ use notion2prompt::{
     Color,
     ColumnBlock,
     ColumnListBlock,
+    DataSource,
     Database,
     DatabaseId,
     DatabaseProperty,
     DatabasePropertyType,
-    DatabaseTitle,
     DateValue,
     DividerBlock,
     EmbedBlock,
@@ -47,19 +50,22 @@ use notion2prompt::{
     Heading1Block,
     Heading2Block,
     Heading3Block,
+    HeadingToggles,
     Icon,
     ImageBlock,
     LinkPreviewBlock,
     LinkToPageBlock,
+    NotionId,
     NumberFormat,
     NumberedListItemBlock,
     Page,
     PageId,
-    PageTitle,
     ParagraphBlock,
     Parent,
     PdfBlock,
+    PipelineConfig,
     PropertyName,
+    PropertyPlacement,
     PropertyTypeValue,
     PropertyValue,
     QuoteBlock,
@@ -185,6 +191,14 @@ fn heading3(text: &str) -> Block {
     })
 }
 
+/// A toggleable heading: a heading block with children.
+fn heading2_with_children(text: &str, children: Vec<Block>) -> Block {
+    Block::Heading2(Heading2Block {
+        common: common_with_children(children),
+        content: text_content(text),
+    })
+}
+
 fn bulleted(text: &str) -> Block {
     Block::BulletedListItem(BulletedListItemBlock {
         common: common(),
@@ -280,7 +294,11 @@ fn table_of_contents() -> Block {
 }
 
 fn breadcrumb() -> Block {
-    Block::Breadcrumb(BreadcrumbBlock { common: common() })
+    Block::Breadcrumb(BreadcrumbBlock {
+        common: common(),
+        ancestors: vec![],
+        from_workspace: false,
+    })
 }
 
 fn bookmark(url: &str) -> Block {
@@ -335,6 +353,18 @@ fn video(url: &str) -> Block {
     })
 }
 
+fn audio(url: &str) -> Block {
+    Block::Audio(AudioBlock {
+        common: common(),
+        audio: FileObject::External {
+            external: ExternalFile {
+                url: url.to_string(),
+            },
+        },
+        caption: vec![],
+    })
+}
+
 fn file_block(url: &str) -> Block {
     Block::File(FileBlock {
         common: common(),
@@ -363,6 +393,7 @@ fn embed(url: &str) -> Block {
     Block::Embed(EmbedBlock {
         common: common(),
         url: url.to_string(),
+        caption: vec![],
     })
 }
 
@@ -377,6 +408,7 @@ fn child_page(title: &str) -> Block {
     Block::ChildPage(ChildPageBlock {
         common: common(),
         title: title.to_string(),
+        included_elsewhere: None,
     })
 }
 
@@ -411,6 +443,15 @@ fn table_with_rows(has_header: bool, width: usize, rows: Vec<Block>) -> Block {
     })
 }
 
+fn table_with_row_header(width: usize, rows: Vec<Block>) -> Block {
+    Block::Table(TableBlock {
+        common: common_with_children(rows),
+        table_width: width,
+        has_column_header: true,
+        has_row_header: true,
+    })
+}
+
 fn table_row(cells: Vec<&str>) -> Block {
     Block::TableRow(TableRowBlock {
         common: common(),
@@ -452,21 +493,18 @@ fn unsupported(block_type: &str) -> Block {
     Block::Unsupported(UnsupportedBlock {
         common: common(),
         block_type: block_type.to_string(),
+        raw: None,
+        skipped_bytes: None,
     })
 }
 
 // --- Page/Database constructors ---
 
 fn simple_page(title: &str, blocks: Vec<Block>) -> Page {
-    Page {
-        id: PageId::parse(TEST_PAGE_ID).unwrap(),
-        title: PageTitle::new(title),
-        url: format!("https://www.notion.so/{}", TEST_PAGE_ID),
-        blocks,
-        properties: HashMap::new(),
-        parent: Some(Parent::Workspace),
-        archived: false,
-    }
+    Page::new(PageId::parse(TEST_PAGE_ID).unwrap(), title)
+        .with_url(format!("https://www.notion.so/{}", TEST_PAGE_ID))
+        .with_blocks(blocks)
+        .with_parent(Parent::Workspace)
 }
 
 fn page_with_properties(
@@ -484,27 +522,14 @@ fn page_with_properties(
             },
         );
     }
-    Page {
-        id: PageId::parse(TEST_PAGE_ID).unwrap(),
-        title: PageTitle::new(title),
-        url: format!("https://www.notion.so/{}", TEST_PAGE_ID),
-        blocks,
-        properties,
-        parent: Some(Parent::Workspace),
-        archived: false,
-    }
+    simple_page(title, blocks).with_properties(properties)
 }
 
 fn simple_database(title: &str, pages: Vec<Page>) -> Database {
-    Database {
-        id: DatabaseId::parse(TEST_DB_ID).unwrap(),
-        title: DatabaseTitle::new(rich(title)),
-        url: format!("https://www.notion.so/{}", TEST_DB_ID),
-        pages,
-        properties: HashMap::new(),
-        parent: Some(Parent::Workspace),
-        archived: false,
-    }
+    Database::new(DatabaseId::parse(TEST_DB_ID).unwrap(), title)
+        .with_url(format!("https://www.notion.so/{}", TEST_DB_ID))
+        .with_pages(pages)
+        .with_parent(Parent::Workspace)
 }
 
 fn database_with_schema(
@@ -523,15 +548,7 @@ fn database_with_schema(
             },
         );
     }
-    Database {
-        id: DatabaseId::parse(TEST_DB_ID).unwrap(),
-        title: DatabaseTitle::new(rich(title)),
-        url: format!("https://www.notion.so/{}", TEST_DB_ID),
-        pages,
-        properties,
-        parent: Some(Parent::Workspace),
-        archived: false,
-    }
+    simple_database(title, pages).with_properties(properties)
 }
 
 /// Normalize a markdown table section by sorting data rows (preserving header + separator).
@@ -721,6 +738,21 @@ mod blocks {
         insta::assert_snapshot!(render_one(&breadcrumb()));
     }
 
+    #[test]
+    fn breadcrumb_with_ancestors() {
+        let from_workspace = Block::Breadcrumb(BreadcrumbBlock {
+            common: common(),
+            ancestors: vec!["Projects".to_string(), "Roadmap".to_string()],
+            from_workspace: true,
+        });
+        let partial = Block::Breadcrumb(BreadcrumbBlock {
+            common: common(),
+            ancestors: vec!["Roadmap".to_string(), "Q3".to_string()],
+            from_workspace: false,
+        });
+        insta::assert_snapshot!(render(&[from_workspace, partial]));
+    }
+
     #[test]
     fn bookmark_block() {
         insta::assert_snapshot!(render_one(&bookmark("https://example.com")));
@@ -752,6 +784,11 @@ mod blocks {
         insta::assert_snapshot!(render_one(&video("https://example.com/video.mp4")));
     }
 
+    #[test]
+    fn audio_block() {
+        insta::assert_snapshot!(render_one(&audio("https://example.com/episode.mp3")));
+    }
+
     #[test]
     fn file_block_test() {
         insta::assert_snapshot!(render_one(&file_block("https://example.com/doc.pdf")));
@@ -1079,6 +1116,37 @@ mod nesting {
         )));
     }
 
+    fn typed_callouts(callout_style: CalloutStyle) -> String {
+        let mut config = PipelineConfig::default();
+        config.callout_style = callout_style;
+        config.callout_types = vec!["🦀=note".parse().unwrap()];
+        let context = RenderContext {
+            app_config: Some(&config),
+            databases: None,
+        };
+        let blocks = [
+            callout_with_children(
+                "Back up first",
+                "⚠️",
+                vec![paragraph("Restores take an hour."), bulleted("Step 1")],
+            ),
+            callout("Use the cache", "💡"),
+            callout("Written in Rust", "🦀"),
+            callout("Unclassified", "🎉"),
+        ];
+        render_blocks(&blocks, &context).unwrap()
+    }
+
+    #[test]
+    fn callouts_as_gfm_alerts() {
+        insta::assert_snapshot!(typed_callouts(CalloutStyle::Gfm));
+    }
+
+    #[test]
+    fn callouts_as_obsidian_callouts() {
+        insta::assert_snapshot!(typed_callouts(CalloutStyle::Obsidian));
+    }
+
     #[test]
     fn bulleted_with_nested_bullets_3_levels() {
         insta::assert_snapshot!(render(&[bulleted_with_children(
@@ -1109,6 +1177,40 @@ mod nesting {
         ]));
     }
 
+    fn toggleable_headings(toggle_headings: HeadingToggles) -> String {
+        let mut config = PipelineConfig::default();
+        config.toggle_headings = toggle_headings;
+        let context = RenderContext {
+            app_config: Some(&config),
+            databases: None,
+        };
+        let blocks = [
+            table_of_contents(),
+            numbered("Before the heading"),
+            heading2_with_children(
+                "Setup",
+                vec![
+                    paragraph("Install the tools."),
+                    numbered("Clone the repository"),
+                    numbered_with_children("Build it", vec![bulleted("Debug or release")]),
+                    heading3("Troubleshooting"),
+                ],
+            ),
+            numbered("After the heading"),
+        ];
+        render_blocks(&blocks, &context).unwrap()
+    }
+
+    #[test]
+    fn toggleable_heading_expanded() {
+        insta::assert_snapshot!(toggleable_headings(HeadingToggles::Expand));
+    }
+
+    #[test]
+    fn toggleable_heading_collapsed() {
+        insta::assert_snapshot!(toggleable_headings(HeadingToggles::Collapse));
+    }
+
     #[test]
     fn deep_nesting_5_levels() {
         insta::assert_snapshot!(render_one(&toggle(
@@ -1194,6 +1296,20 @@ mod tables {
         );
         insta::assert_snapshot!(render_one(&t));
     }
+
+    #[test]
+    fn table_with_row_header_column() {
+        let t = table_with_row_header(
+            3,
+            vec![
+                table_row(vec!["", "Q1", "Q2"]),
+                table_row(vec!["Revenue", "10", "12"]),
+                table_row(vec!["", "3", "4"]),
+                table_row(vec!["Costs", "7", "8"]),
+            ],
+        );
+        insta::assert_snapshot!(render_one(&t));
+    }
 }
 
 mod databases {
@@ -1211,7 +1327,7 @@ mod databases {
             vec![simple_page("Task 1", vec![]), simple_page("Task 2", vec![])],
         );
         insta::assert_snapshot!(normalize_table_rows(
-            &compose_database_summary(&db).unwrap()
+            &compose_database_summary(&db, &RenderContext::default()).unwrap()
         ));
     }
 
@@ -1223,7 +1339,7 @@ mod databases {
             vec![],
         );
         insta::assert_snapshot!(normalize_table_rows(
-            &compose_database_summary(&db).unwrap()
+            &compose_database_summary(&db, &RenderContext::default()).unwrap()
         ));
     }
 
@@ -1249,10 +1365,184 @@ mod databases {
             vec![simple_page("Row 1", vec![])],
         );
         insta::assert_snapshot!(normalize_table_rows(
-            &compose_database_summary(&db).unwrap()
+            &compose_database_summary(&db, &RenderContext::default()).unwrap()
         ));
     }
 
+    fn data_source(id: &str, name: &str, pages: Vec<Page>) -> DataSource {
+        let schema =
+            database_with_schema(name, vec![("Name", DatabasePropertyType::Title)], vec![]);
+        DataSource {
+            id: NotionId::parse(id).unwrap(),
+            name: name.to_string(),
+            properties: schema.properties,
+            pages,
+            inaccessible_rows: Vec::new(),
+        }
+    }
+
+    /// A data source row with its own id, titled through its Name property.
+    fn source_row(id: &str, title: &str) -> Page {
+        let mut row = page_with_properties(
+            title,
+            vec![],
+            vec![("Name", PropertyTypeValue::Title { title: rich(title) })],
+        );
+        row.id = PageId::parse(id).unwrap();
+        row
+    }
+
+    fn multi_source_database() -> Database {
+        let mut db = simple_database("Tracker", vec![]);
+        db.data_sources = vec![
+            data_source(
+                "22222222222222222222222222222222",
+                "Tasks",
+                vec![
+                    source_row("22222222222222222222222222222201", "Task 1"),
+                    source_row("22222222222222222222222222222202", "Task 2"),
+                ],
+            ),
+            data_source(
+                "33333333333333333333333333333333",
+                "Bugs",
+                vec![source_row(
+                    "33333333333333333333333333333301",
+                    "Crash on save",
+                )],
+            ),
+        ];
+        db
+    }
+
+    #[test]
+    fn database_summary_multiple_data_sources() {
+        insta::assert_snapshot!(compose_database_summary(
+            &multi_source_database(),
+            &RenderContext::default()
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn child_database_with_multiple_data_sources() {
+        let block = Block::ChildDatabase(ChildDatabaseBlock {
+            common: common(),
+            title: "Tracker".to_string(),
+            content: ChildDatabaseContent::Fetched(Box::new(multi_source_database())),
+        });
+        insta::assert_snapshot!(render_one(&block));
+    }
+
+    fn render_aligned_database(db_align: &str) -> String {
+        let db = database_with_schema(
+            "Sprint",
+            vec![
+                ("Name", DatabasePropertyType::Title),
+                (
+                    "Estimate",
+                    DatabasePropertyType::Number {
+                        format: NumberFormat::Number,
+                    },
+                ),
+                ("Due", DatabasePropertyType::Date),
+                ("Done", DatabasePropertyType::Checkbox),
+                ("Status", DatabasePropertyType::Select { options: vec![] }),
+            ],
+            vec![simple_page("Task 1", vec![])],
+        );
+        let block = Block::ChildDatabase(ChildDatabaseBlock {
+            common: common(),
+            title: "Sprint".to_string(),
+            content: ChildDatabaseContent::Fetched(Box::new(db)),
+        });
+        let mut config = PipelineConfig::default();
+        config.db_align = db_align
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let context = RenderContext {
+            app_config: Some(&config),
+            databases: None,
+        };
+        render_blocks(std::slice::from_ref(&block), &context).unwrap()
+    }
+
+    #[test]
+    fn child_database_columns_aligned_by_property_type() {
+        insta::assert_snapshot!(render_aligned_database(""));
+    }
+
+    #[test]
+    fn child_database_column_alignment_overrides() {
+        insta::assert_snapshot!(render_aligned_database("Estimate=left,Status=center"));
+    }
+
+    #[test]
+    fn child_database_grouped_by_status() {
+        let task = |title: &str| PropertyTypeValue::Title { title: rich(title) };
+        let status = |name: &str| {
+            (
+                "Status",
+                PropertyTypeValue::Status {
+                    status: Some(SelectOption {
+                        id: String::new(),
+                        name: name.to_string(),
+                        color: Color::Default,
+                    }),
+                },
+            )
+        };
+        let option = |name: &str| SelectOption {
+            id: String::new(),
+            name: name.to_string(),
+            color: Color::Default,
+        };
+        let db = database_with_schema(
+            "Sprint",
+            vec![
+                ("Name", DatabasePropertyType::Title),
+                (
+                    "Status",
+                    DatabasePropertyType::Status {
+                        options: vec![option("Not started"), option("In progress"), option("Done")],
+                    },
+                ),
+            ],
+            vec![
+                page_with_properties(
+                    "Ship it",
+                    vec![],
+                    vec![("Name", task("Ship it")), status("Done")],
+                ),
+                page_with_properties("Triage", vec![], vec![("Name", task("Triage"))]),
+                page_with_properties(
+                    "Write docs",
+                    vec![],
+                    vec![("Name", task("Write docs")), status("Not started")],
+                ),
+                page_with_properties(
+                    "Review",
+                    vec![],
+                    vec![("Name", task("Review")), status("Done")],
+                ),
+            ],
+        );
+        let block = Block::ChildDatabase(ChildDatabaseBlock {
+            common: common(),
+            title: "Sprint".to_string(),
+            content: ChildDatabaseContent::Fetched(Box::new(db)),
+        });
+        let mut config = PipelineConfig::default();
+        config.db_group_by = Some(PropertyName::new("Status"));
+        let context = RenderContext {
+            app_config: Some(&config),
+            databases: None,
+        };
+        insta::assert_snapshot!(render_blocks(std::slice::from_ref(&block), &context).unwrap());
+    }
+
     #[test]
     fn child_database_not_fetched() {
         insta::assert_snapshot!(render_one(&child_database("Key Highlights")));
@@ -1329,6 +1619,44 @@ mod pages {
         ));
     }
 
+    #[test]
+    fn page_with_props_at_bottom() {
+        let page = page_with_properties(
+            "Feature Spec",
+            vec![heading1("Scope"), paragraph("Description of the feature.")],
+            vec![("Done", PropertyTypeValue::Checkbox { checkbox: false })],
+        );
+        let mut config = PipelineConfig::default();
+        config.property_placement = PropertyPlacement::Bottom;
+        let context = RenderContext {
+            app_config: Some(&config),
+            databases: None,
+        };
+        insta::assert_snapshot!(compose_page_markdown(&page, &context).unwrap());
+    }
+
+    #[test]
+    fn page_with_outline_summary() {
+        let page = page_with_properties(
+            "Feature Spec",
+            vec![
+                paragraph("Why we are building this."),
+                heading1("Scope"),
+                paragraph("Description of the feature."),
+                heading2("Out of scope"),
+                paragraph("Everything else."),
+            ],
+            vec![("Done", PropertyTypeValue::Checkbox { checkbox: false })],
+        );
+        let mut config = PipelineConfig::default();
+        config.auto_summary = AutoSummary::Outline;
+        let context = RenderContext {
+            app_config: Some(&config),
+            databases: None,
+        };
+        insta::assert_snapshot!(compose_page_markdown(&page, &context).unwrap());
+    }
+
     #[test]
     fn full_page() {
         let page = page_with_properties(
@@ -1384,6 +1712,37 @@ mod pages {
         );
         insta::assert_snapshot!(compose_page_markdown(&page, &RenderContext::default()).unwrap());
     }
+
+    fn compose_with_headings(heading_offset: u8, normalize_headings: bool) -> String {
+        let page = simple_page(
+            "Design Notes",
+            vec![
+                heading1("Goals"),
+                paragraph("Keep it small."),
+                heading2("Non-goals"),
+                heading3("Details"),
+            ],
+        );
+        let mut config = PipelineConfig::default();
+        config.include_properties = false;
+        config.heading_offset = heading_offset;
+        config.normalize_headings = normalize_headings;
+        let context = RenderContext {
+            app_config: Some(&config),
+            databases: None,
+        };
+        compose_page_markdown(&page, &context).unwrap()
+    }
+
+    #[test]
+    fn page_with_normalized_headings() {
+        insta::assert_snapshot!(compose_with_headings(0, true));
+    }
+
+    #[test]
+    fn page_with_heading_offset_capped_at_h6() {
+        insta::assert_snapshot!(compose_with_headings(4, false));
+    }
 }
 
 mod integration {
@@ -1431,6 +1790,30 @@ mod integration {
         insta::assert_snapshot!("amundi_blocks", output);
     }
 
+    /// `--format json` output renders the same once read back, as it is
+    /// when rendered elsewhere, e.g. by the WASM bindings.
+    #[test]
+    fn fixture_blocks_survive_json_round_trip() {
+        for blocks_json in [
+            include_str!("fixtures/api_responses/blocks_flow_ai_jetbrains.json"),
+            include_str!("fixtures/api_responses/blocks_aie_agents_nyc.json"),
+            include_str!("fixtures/api_responses/blocks_flow_ai_amundi.json"),
+            include_str!("fixtures/api_responses/blocks_children_mentions.json"),
+        ] {
+            let blocks = parse_blocks_pagination(ApiResponse {
+                data: blocks_json.to_string(),
+                status: StatusCode::OK,
+                url: "https://api.notion.com/v1/blocks/test/children".to_string(),
+            })
+            .unwrap()
+            .results;
+
+            let json = serde_json::to_string(&blocks).unwrap();
+            let read_back: Vec<Block> = serde_json::from_str(&json).unwrap();
+            assert_eq!(read_back, blocks);
+        }
+    }
+
     #[test]
     fn fixture_key_highlights_database() {
         let db_json = include_str!("fixtures/api_responses/database_key_highlights.json");
@@ -1441,7 +1824,7 @@ mod integration {
         })
         .unwrap();
 
-        let output = compose_database_summary(&db).unwrap();
+        let output = compose_database_summary(&db, &RenderContext::default()).unwrap();
         insta::assert_snapshot!("key_highlights_db_summary", normalize_table_rows(&output));
     }
 }
@@ -0,0 +1,51 @@
+// tests/stable_api.rs
+//! Guards the stable API: every item here is covered by semver, so a change
+//! that breaks this file needs a major version bump.
+
+use notion2prompt::prelude::*;
+use notion2prompt::{Block, Database, Page, RenderContext, ToggleStyle, ValidationError};
+use std::io::Write;
+
+#[test]
+fn test_stable_signatures_are_unchanged() {
+    let _: fn(&NotionObject, &PipelineConfig) -> Result<RenderedPrompt, AppError> = render_prompt;
+    let _: fn(&NotionObject, &PipelineConfig, &mut dyn Write) -> Result<usize, AppError> =
+        render_prompt_to;
+    let _: fn(&[Block], &RenderContext) -> Result<String, AppError> = notion2prompt::render_blocks;
+    let _: fn(&Page) -> NotionObject = |page| NotionObject::Page(page.clone());
+    let _: fn(&Database) -> NotionObject = |db| NotionObject::Database(db.clone());
+}
+
+#[test]
+fn test_prelude_covers_the_everyday_pipeline() {
+    struct Echo;
+    impl PromptComposer for Echo {
+        fn compose(&self, content: &NotionObject) -> Result<RenderedPrompt, AppError> {
+            Ok(RenderedPrompt::new(content.display_title()))
+        }
+    }
+
+    let id: Result<NotionId, ValidationError> = NotionId::parse("550e8400e29b41d4a716446655440000");
+    assert!(id.is_ok());
+    assert!(NotionId::parse("not an id").is_err());
+    assert_eq!(
+        PipelineConfig::default().toggle_style,
+        ToggleStyle::default()
+    );
+    let _: &dyn PromptComposer = &Echo;
+    assert_ne!(ExitCode::Success as i32, ExitCode::Failure as i32);
+}
+
+#[test]
+fn test_model_is_built_from_stable_types() {
+    let id = NotionId::parse("550e8400e29b41d4a716446655440000").unwrap();
+    let row = Page::new(id.clone(), "Launch").with_url("https://www.notion.so/launch");
+    let database = Database::new(id, "Roadmap").with_pages(vec![row.clone()]);
+
+    assert_eq!(NotionObject::Page(row).display_title(), "Launch");
+    assert_eq!(NotionObject::Database(database).display_title(), "Roadmap");
+
+    let mut config = PipelineConfig::default();
+    config.include_properties = false;
+    assert!(!config.include_properties);
+}
@@ -14,7 +14,9 @@ fn create_bookmark_block(url: &str, caption: &str) -> Block {
             id: BlockId::parse("12345678-1234-1234-1234-123456789abc").unwrap(),
             has_children: false,
             archived: false,
+            last_edited_time: None,
             children: vec![],
+            unfetched_children: None,
         },
         url: url.to_string(),
         caption: caption_items,
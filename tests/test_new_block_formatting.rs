@@ -113,7 +113,10 @@ fn test_new_block_types_formatting() {
     let blocks = vec![embed_block];
     let output = render_blocks(&blocks, &config).expect("Formatting should succeed");
 
-    assert!(output.contains("[Embed: https://www.youtube.com/watch?v=example]"));
+    assert!(output.contains(
+        "[YouTube embed](https://www.youtube.com/watch?v=example) \
+         ([thumbnail](https://img.youtube.com/vi/example/hqdefault.jpg))"
+    ));
     println!("✅ Embed block formatting: {}", output.trim());
 
     // Test child page block formatting
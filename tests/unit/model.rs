@@ -31,6 +31,7 @@ mod block_macro_tests {
                     children: vec![],
                     has_children: false,
                     archived: false,
+                    last_edited_time: None,
                 },
                 content: ParagraphContent {
                     rich_text: test_rich_text("test"),
@@ -43,6 +44,7 @@ mod block_macro_tests {
                     children: vec![],
                     has_children: false,
                     archived: false,
+                    last_edited_time: None,
                 },
                 content: HeadingContent {
                     rich_text: test_rich_text("test"),
@@ -56,6 +58,7 @@ mod block_macro_tests {
                     children: vec![],
                     has_children: false,
                     archived: false,
+                    last_edited_time: None,
                 },
             }),
         ];
@@ -74,6 +77,7 @@ mod block_macro_tests {
                 children: vec![],
                 has_children: false,
                 archived: false,
+                last_edited_time: None,
             },
         }));
         
@@ -83,6 +87,7 @@ mod block_macro_tests {
                 children: vec![child.clone()],
                 has_children: true,
                 archived: false,
+                last_edited_time: None,
             },
             content: ParagraphContent {
                 rich_text: test_rich_text("parent"),
@@ -111,6 +116,7 @@ mod block_macro_tests {
                 children: vec![],
                 has_children: false,
                 archived: true,
+                last_edited_time: None,
             },
             content: TextBlockContent {
                 rich_text: test_rich_text("quoted text"),